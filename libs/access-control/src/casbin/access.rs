@@ -87,6 +87,20 @@ impl AccessControl {
     Ok(())
   }
 
+  /// Replaces the policies of the subject on the object, see [AFEnforcerV2::replace_policy].
+  pub async fn replace_policy<T>(
+    &self,
+    sub: SubjectType,
+    obj: ObjectType,
+    act: T,
+  ) -> Result<(), AppError>
+  where
+    T: Acts,
+  {
+    self.enforcer.replace_policy(sub, obj, act).await?;
+    Ok(())
+  }
+
   pub async fn remove_policy(&self, sub: SubjectType, obj: ObjectType) -> Result<(), AppError> {
     self.enforcer.remove_policy(sub, obj).await?;
     Ok(())
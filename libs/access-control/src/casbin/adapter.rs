@@ -7,8 +7,10 @@ use casbin::Filter;
 use casbin::Model;
 use casbin::Result;
 
-use database::pg_row::AFWorkspaceMemberPermRow;
+use database::guest::select_guest_view_access_perm_stream;
+use database::pg_row::{AFGuestViewAccessLevelRow, AFWorkspaceMemberPermRow};
 use database::workspace::select_workspace_member_perm_stream;
+use database_entity::dto::AFAccessLevel;
 
 use crate::act::Acts;
 use futures_util::stream::BoxStream;
@@ -94,6 +96,31 @@ pub async fn load_workspace_policies(
   Ok(policies)
 }
 
+/// Loads the view level policies of the guests from a given stream of guest view access levels.
+///
+/// Each view that was shared with a guest, either explicitly or by inheriting the share from one
+/// of its ancestors, results in a policy of the form `[uid, collab::<view_id>, access_level]`.
+pub async fn load_guest_view_policies(
+  mut stream: BoxStream<'_, sqlx::Result<AFGuestViewAccessLevelRow>>,
+) -> Result<Vec<Vec<String>>> {
+  let mut policies: Vec<Vec<String>> = Vec::new();
+
+  while let Some(Ok(guest_access)) = stream.next().await {
+    let object_type = ObjectType::Collab(guest_access.view_id.to_string());
+    let access_level = AFAccessLevel::from(guest_access.access_level);
+    for act in access_level.policy_acts() {
+      let policy = vec![
+        guest_access.uid.to_string(),
+        object_type.policy_object(),
+        act,
+      ];
+      policies.push(policy);
+    }
+  }
+
+  Ok(policies)
+}
+
 #[async_trait]
 impl Adapter for PgAdapter {
   async fn load_policy(&mut self, model: &mut dyn Model) -> Result<()> {
@@ -101,8 +128,12 @@ impl Adapter for PgAdapter {
     let workspace_member_perm_stream = select_workspace_member_perm_stream(&self.pg_pool);
     let workspace_policies = load_workspace_policies(workspace_member_perm_stream).await?;
 
+    let guest_view_access_stream = select_guest_view_access_perm_stream(&self.pg_pool);
+    let guest_view_policies = load_guest_view_policies(guest_view_access_stream).await?;
+
     // Policy definition `p` of type `p`. See `model.conf`
     model.add_policies("p", "p", workspace_policies);
+    model.add_policies("p", "p", guest_view_policies);
 
     self
       .access_control_metrics
@@ -1,16 +1,92 @@
 use crate::{
   act::Action,
   collab::{CollabAccessControl, RealtimeAccessControl},
-  entity::ObjectType,
+  entity::{ObjectType, SubjectType},
+  scope::enforce_access_scope,
 };
 use app_error::AppError;
 use async_trait::async_trait;
-use database_entity::dto::AFAccessLevel;
+use database_entity::dto::{AFAccessLevel, AFRole};
 use tracing::instrument;
 use uuid::Uuid;
 
 use super::access::AccessControl;
 
+/// Workspace members can access every collab in the workspace, according to their role.
+/// Guests can only access the collabs that have been shared with them, according to the
+/// access level of the share.
+async fn enforce_collab_action(
+  access_control: &AccessControl,
+  workspace_id: &Uuid,
+  uid: &i64,
+  oid: &Uuid,
+  action: Action,
+) -> Result<bool, AppError> {
+  if is_workspace_member(access_control, workspace_id, uid).await? {
+    // Anyone who can write to a workspace, can also delete a collab.
+    let workspace_action = match action {
+      Action::Read => Action::Read,
+      Action::Write => Action::Write,
+      Action::Delete => Action::Write,
+    };
+    return access_control
+      .enforce_immediately(
+        uid,
+        ObjectType::Workspace(workspace_id.to_string()),
+        workspace_action,
+      )
+      .await;
+  }
+
+  access_control
+    .enforce_immediately(uid, ObjectType::Collab(oid.to_string()), action)
+    .await
+}
+
+async fn enforce_collab_access_level(
+  access_control: &AccessControl,
+  workspace_id: &Uuid,
+  uid: &i64,
+  oid: &Uuid,
+  access_level: AFAccessLevel,
+) -> Result<bool, AppError> {
+  if is_workspace_member(access_control, workspace_id, uid).await? {
+    // Anyone who can write to a workspace, also have full access to a collab.
+    let workspace_action = match access_level {
+      AFAccessLevel::ReadOnly => Action::Read,
+      AFAccessLevel::ReadAndComment => Action::Read,
+      AFAccessLevel::ReadAndWrite => Action::Write,
+      AFAccessLevel::FullAccess => Action::Write,
+    };
+    return access_control
+      .enforce_immediately(
+        uid,
+        ObjectType::Workspace(workspace_id.to_string()),
+        workspace_action,
+      )
+      .await;
+  }
+
+  access_control
+    .enforce_immediately(uid, ObjectType::Collab(oid.to_string()), access_level)
+    .await
+}
+
+#[inline]
+async fn is_workspace_member(
+  access_control: &AccessControl,
+  workspace_id: &Uuid,
+  uid: &i64,
+) -> Result<bool, AppError> {
+  access_control
+    .enforce_immediately(
+      uid,
+      ObjectType::Workspace(workspace_id.to_string()),
+      AFRole::Member,
+    )
+    .await
+}
+
 #[derive(Clone)]
 pub struct CollabAccessControlImpl {
   access_control: AccessControl,
@@ -28,26 +104,11 @@ impl CollabAccessControl for CollabAccessControlImpl {
     &self,
     workspace_id: &Uuid,
     uid: &i64,
-    _oid: &Uuid,
+    oid: &Uuid,
     action: Action,
   ) -> Result<(), AppError> {
-    // TODO: allow non workspace member to read a collab.
-
-    // Anyone who can write to a workspace, can also delete a collab.
-    let workspace_action = match action {
-      Action::Read => Action::Read,
-      Action::Write => Action::Write,
-      Action::Delete => Action::Write,
-    };
-
-    let result = self
-      .access_control
-      .enforce_immediately(
-        uid,
-        ObjectType::Workspace(workspace_id.to_string()),
-        workspace_action,
-      )
-      .await;
+    enforce_access_scope(workspace_id, &action)?;
+    let result = enforce_collab_action(&self.access_control, workspace_id, uid, oid, action).await;
     match result {
       Ok(true) => Ok(()),
       Ok(false) => Err(AppError::NotEnoughPermissions),
@@ -59,27 +120,12 @@ impl CollabAccessControl for CollabAccessControlImpl {
     &self,
     workspace_id: &Uuid,
     uid: &i64,
-    _oid: &Uuid,
+    oid: &Uuid,
     access_level: AFAccessLevel,
   ) -> Result<(), AppError> {
-    // TODO: allow non workspace member to read a collab.
-
-    // Anyone who can write to a workspace, also have full access to a collab.
-    let workspace_action = match access_level {
-      AFAccessLevel::ReadOnly => Action::Read,
-      AFAccessLevel::ReadAndComment => Action::Read,
-      AFAccessLevel::ReadAndWrite => Action::Write,
-      AFAccessLevel::FullAccess => Action::Write,
-    };
-
-    let result = self
-      .access_control
-      .enforce_immediately(
-        uid,
-        ObjectType::Workspace(workspace_id.to_string()),
-        workspace_action,
-      )
-      .await;
+    enforce_access_scope(workspace_id, &access_level)?;
+    let result =
+      enforce_collab_access_level(&self.access_control, workspace_id, uid, oid, access_level).await;
     match result {
       Ok(true) => Ok(()),
       Ok(false) => Err(AppError::NotEnoughPermissions),
@@ -90,17 +136,29 @@ impl CollabAccessControl for CollabAccessControlImpl {
   #[instrument(level = "info", skip_all)]
   async fn update_access_level_policy(
     &self,
-    _uid: &i64,
-    _oid: &Uuid,
-    _level: AFAccessLevel,
+    uid: &i64,
+    oid: &Uuid,
+    level: AFAccessLevel,
   ) -> Result<(), AppError> {
-    // TODO: allow non workspace member to read a collab.
+    // Policies are additive, so the previous access level is replaced rather than added to.
+    // Otherwise a downgrade would not take effect.
+    self
+      .access_control
+      .replace_policy(
+        SubjectType::User(*uid),
+        ObjectType::Collab(oid.to_string()),
+        level,
+      )
+      .await?;
     Ok(())
   }
 
   #[instrument(level = "info", skip_all)]
-  async fn remove_access_level(&self, _uid: &i64, _oid: &Uuid) -> Result<(), AppError> {
-    // TODO: allow non workspace member to read a collab.
+  async fn remove_access_level(&self, uid: &i64, oid: &Uuid) -> Result<(), AppError> {
+    self
+      .access_control
+      .remove_policy(SubjectType::User(*uid), ObjectType::Collab(oid.to_string()))
+      .await?;
     Ok(())
   }
 }
@@ -114,32 +172,6 @@ impl RealtimeCollabAccessControlImpl {
   pub fn new(access_control: AccessControl) -> Self {
     Self { access_control }
   }
-
-  async fn can_perform_action(
-    &self,
-    workspace_id: &Uuid,
-    uid: &i64,
-    _oid: &Uuid,
-    required_action: Action,
-  ) -> Result<bool, AppError> {
-    // TODO: allow non workspace member to read a collab.
-
-    // Anyone who can write to a workspace, can also delete a collab.
-    let workspace_action = match required_action {
-      Action::Read => Action::Read,
-      Action::Write => Action::Write,
-      Action::Delete => Action::Write,
-    };
-
-    self
-      .access_control
-      .enforce_immediately(
-        uid,
-        ObjectType::Workspace(workspace_id.to_string()),
-        workspace_action,
-      )
-      .await
-  }
 }
 
 #[async_trait]
@@ -150,9 +182,7 @@ impl RealtimeAccessControl for RealtimeCollabAccessControlImpl {
     uid: &i64,
     oid: &Uuid,
   ) -> Result<bool, AppError> {
-    self
-      .can_perform_action(workspace_id, uid, oid, Action::Write)
-      .await
+    enforce_collab_action(&self.access_control, workspace_id, uid, oid, Action::Write).await
   }
 
   async fn can_read_collab(
@@ -161,15 +191,13 @@ impl RealtimeAccessControl for RealtimeCollabAccessControlImpl {
     uid: &i64,
     oid: &Uuid,
   ) -> Result<bool, AppError> {
-    self
-      .can_perform_action(workspace_id, uid, oid, Action::Read)
-      .await
+    enforce_collab_action(&self.access_control, workspace_id, uid, oid, Action::Read).await
   }
 }
 
 #[cfg(test)]
 mod tests {
-  use database_entity::dto::AFRole;
+  use database_entity::dto::{AFAccessLevel, AFRole};
   use uuid::Uuid;
 
   use crate::casbin::util::tests::test_enforcer_v2;
@@ -203,4 +231,69 @@ mod tests {
         .unwrap_or_else(|_| panic!("Failed to enforce action: {:?}", action));
     }
   }
+
+  #[tokio::test]
+  pub async fn test_guest_collab_access_control() {
+    let enforcer = test_enforcer_v2().await;
+    let uid = 1;
+    let workspace_id = Uuid::new_v4();
+    let shared_oid = Uuid::new_v4();
+    let other_oid = Uuid::new_v4();
+    enforcer
+      .update_policy(
+        SubjectType::User(uid),
+        ObjectType::Workspace(workspace_id.to_string()),
+        AFRole::Guest,
+      )
+      .await
+      .unwrap();
+    let access_control = AccessControl::with_enforcer(enforcer);
+    let collab_access_control = super::CollabAccessControlImpl::new(access_control);
+    collab_access_control
+      .update_access_level_policy(&uid, &shared_oid, AFAccessLevel::ReadAndComment)
+      .await
+      .unwrap();
+
+    collab_access_control
+      .enforce_action(&workspace_id, &uid, &shared_oid, Action::Read)
+      .await
+      .unwrap();
+    collab_access_control
+      .enforce_access_level(
+        &workspace_id,
+        &uid,
+        &shared_oid,
+        AFAccessLevel::ReadAndComment,
+      )
+      .await
+      .unwrap();
+    assert!(collab_access_control
+      .enforce_action(&workspace_id, &uid, &shared_oid, Action::Write)
+      .await
+      .is_err());
+    assert!(collab_access_control
+      .enforce_action(&workspace_id, &uid, &other_oid, Action::Read)
+      .await
+      .is_err());
+
+    // upgrade the access level
+    collab_access_control
+      .update_access_level_policy(&uid, &shared_oid, AFAccessLevel::ReadAndWrite)
+      .await
+      .unwrap();
+    collab_access_control
+      .enforce_action(&workspace_id, &uid, &shared_oid, Action::Write)
+      .await
+      .unwrap();
+
+    // revoke the access
+    collab_access_control
+      .remove_access_level(&uid, &shared_oid)
+      .await
+      .unwrap();
+    assert!(collab_access_control
+      .enforce_action(&workspace_id, &uid, &shared_oid, Action::Read)
+      .await
+      .is_err());
+  }
 }
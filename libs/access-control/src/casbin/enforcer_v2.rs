@@ -42,6 +42,15 @@ enum PolicyCommand {
     subject_object_keys: Vec<(String, String)>, // (subject, object) pairs
     response: tokio::sync::oneshot::Sender<Result<(), AppError>>,
   },
+  /// Removes and adds policies under the same write lock, so that no enforcement sees the
+  /// subject without any of them.
+  ReplacePolicies {
+    removed: Vec<Vec<String>>,
+    added: Vec<Vec<String>>,
+    generation: u64,
+    subject_object_keys: Vec<(String, String)>, // (subject, object) pairs
+    response: tokio::sync::oneshot::Sender<Result<(), AppError>>,
+  },
   Shutdown,
 }
 
@@ -192,6 +201,32 @@ impl AFEnforcerV2 {
             trace!("[access control v2]: RemovePolicies result: {:?}", result);
             let _ = response.send(result);
           },
+          PolicyCommand::ReplacePolicies {
+            removed,
+            added,
+            generation,
+            subject_object_keys,
+            response,
+          } => {
+            max_generation = max_generation.max(generation);
+            processed_keys.extend(subject_object_keys);
+            let result = async {
+              if !removed.is_empty() {
+                enforcer
+                  .remove_policies(removed)
+                  .await
+                  .map_err(|e| AppError::Internal(anyhow!("fail to remove policy: {e:?}")))?;
+              }
+              enforcer
+                .add_policies(added)
+                .await
+                .map_err(|e| AppError::Internal(anyhow!("fail to add policy: {e:?}")))?;
+              Ok(())
+            }
+            .await;
+            trace!("[access control v2]: ReplacePolicies result: {:?}", result);
+            let _ = response.send(result);
+          },
           PolicyCommand::Shutdown => {
             trace!("[access control v2]: Policy update processor shutting down");
             return;
@@ -336,6 +371,61 @@ impl AFEnforcerV2 {
     result
   }
 
+  /// Replaces the policies of the subject on the object with the given ones, in a single update.
+  #[instrument(level = "debug", skip_all, err)]
+  pub async fn replace_policy<T>(
+    &self,
+    sub: SubjectType,
+    obj: ObjectType,
+    act: T,
+  ) -> Result<(), AppError>
+  where
+    T: Acts,
+  {
+    let removed = {
+      let enforcer = self.enforcer.read().await;
+      policies_for_subject_with_given_object(sub.clone(), obj.clone(), &enforcer).await
+    };
+    let added = act
+      .policy_acts()
+      .into_iter()
+      .map(|act| vec![sub.policy_subject(), obj.policy_object(), act])
+      .collect::<Vec<Vec<_>>>();
+
+    info!(
+      "[access control v2]: queuing replace policy:{:?} with {:?}",
+      removed, added
+    );
+    let generation = self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+    let subject_object_keys = vec![(sub.policy_subject(), obj.policy_object())];
+    {
+      let mut pending = self.pending_operations.write().await;
+      for key in &subject_object_keys {
+        pending.insert(key.clone());
+      }
+    }
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    self
+      .send_command_with_metrics(PolicyCommand::ReplacePolicies {
+        removed,
+        added,
+        generation,
+        subject_object_keys,
+        response: tx,
+      })
+      .await?;
+
+    let result = rx
+      .await
+      .map_err(|_| AppError::Internal(anyhow!("Policy update response dropped")))?;
+    trace!(
+      "[access control v2]: Received policy replace response: {:?}",
+      result
+    );
+    result
+  }
+
   /// Remove policies for a subject and object type.
   pub async fn remove_policy(
     &self,
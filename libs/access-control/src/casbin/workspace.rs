@@ -5,6 +5,7 @@ use uuid::Uuid;
 use super::access::AccessControl;
 use crate::act::Action;
 use crate::entity::{ObjectType, SubjectType};
+use crate::scope::enforce_access_scope;
 use crate::workspace::WorkspaceAccessControl;
 use app_error::AppError;
 use database_entity::dto::AFRole;
@@ -28,6 +29,7 @@ impl WorkspaceAccessControl for WorkspaceAccessControlImpl {
     workspace_id: &Uuid,
     role: AFRole,
   ) -> Result<(), AppError> {
+    enforce_access_scope(workspace_id, &role)?;
     let result = self
       .access_control
       .enforce_strong(uid, ObjectType::Workspace(workspace_id.to_string()), role)
@@ -46,6 +48,7 @@ impl WorkspaceAccessControl for WorkspaceAccessControlImpl {
     workspace_id: &Uuid,
     role: AFRole,
   ) -> Result<(), AppError> {
+    enforce_access_scope(workspace_id, &role)?;
     let result = self
       .access_control
       .enforce_weak(uid, ObjectType::Workspace(workspace_id.to_string()), role)
@@ -64,6 +67,7 @@ impl WorkspaceAccessControl for WorkspaceAccessControlImpl {
     workspace_id: &Uuid,
     action: Action,
   ) -> Result<(), AppError> {
+    enforce_access_scope(workspace_id, &action)?;
     let result = self
       .access_control
       .enforce_immediately(uid, ObjectType::Workspace(workspace_id.to_string()), action)
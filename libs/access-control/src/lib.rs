@@ -6,4 +6,5 @@ pub mod entity;
 pub mod metrics;
 pub mod noops;
 mod request;
+pub mod scope;
 pub mod workspace;
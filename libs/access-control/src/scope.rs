@@ -0,0 +1,112 @@
+use std::future::Future;
+
+use app_error::AppError;
+use database_entity::dto::{AFAccessLevel, AFApiTokenScope, AFRole};
+use uuid::Uuid;
+
+use crate::act::Action;
+
+tokio::task_local! {
+  static ACCESS_SCOPE: AccessScope;
+}
+
+/// Narrows the permissions of a request that was authenticated with a personal API token. The
+/// scope is only applied on top of the policies of the user: a check that fails for the user
+/// still fails within the scope.
+#[derive(Debug, Clone)]
+pub struct AccessScope {
+  pub workspace_ids: Vec<Uuid>,
+  pub scope: AFApiTokenScope,
+}
+
+impl AccessScope {
+  /// Runs the future with the scope applied to every access control check made within it.
+  pub async fn run<F: Future>(self, fut: F) -> F::Output {
+    ACCESS_SCOPE.scope(self, fut).await
+  }
+
+  fn enforce(&self, workspace_id: &Uuid, required: AFApiTokenScope) -> Result<(), AppError> {
+    if self.scope >= required && self.workspace_ids.contains(workspace_id) {
+      Ok(())
+    } else {
+      Err(AppError::NotEnoughPermissions)
+    }
+  }
+}
+
+/// Returns the scope applied to the current request, if any.
+pub fn current_access_scope() -> Option<AccessScope> {
+  ACCESS_SCOPE.try_with(|scope| scope.clone()).ok()
+}
+
+/// Returns AppError::NotEnoughPermissions if the current request is restricted by an
+/// [AccessScope] that does not cover the workspace or the required permission.
+pub fn enforce_access_scope<T: ScopeRequirement>(
+  workspace_id: &Uuid,
+  requirement: &T,
+) -> Result<(), AppError> {
+  ACCESS_SCOPE
+    .try_with(|scope| scope.enforce(workspace_id, requirement.required_scope()))
+    .unwrap_or(Ok(()))
+}
+
+pub trait ScopeRequirement {
+  fn required_scope(&self) -> AFApiTokenScope;
+}
+
+impl ScopeRequirement for Action {
+  fn required_scope(&self) -> AFApiTokenScope {
+    match self {
+      Action::Read => AFApiTokenScope::Read,
+      Action::Write | Action::Delete => AFApiTokenScope::Write,
+    }
+  }
+}
+
+impl ScopeRequirement for AFAccessLevel {
+  fn required_scope(&self) -> AFApiTokenScope {
+    match self {
+      AFAccessLevel::ReadOnly | AFAccessLevel::ReadAndComment => AFApiTokenScope::Read,
+      AFAccessLevel::ReadAndWrite | AFAccessLevel::FullAccess => AFApiTokenScope::Write,
+    }
+  }
+}
+
+/// Role checks establish whether the user belongs to the workspace. Only the checks that require
+/// the owner role need more than a read scope, the kind of operation is enforced by the action
+/// checks. Handlers that write with a role check only, such as publishing or quick notes, are not
+/// covered by this mapping: the server rejects tokens on every route that is not explicitly
+/// allowed for them, with the scope that route requires.
+impl ScopeRequirement for AFRole {
+  fn required_scope(&self) -> AFApiTokenScope {
+    match self {
+      AFRole::Owner => AFApiTokenScope::Admin,
+      AFRole::Member | AFRole::Guest => AFApiTokenScope::Read,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn access_scope_test() {
+    let workspace_id = Uuid::new_v4();
+    let other_workspace_id = Uuid::new_v4();
+    assert!(enforce_access_scope(&other_workspace_id, &AFRole::Owner).is_ok());
+
+    let scope = AccessScope {
+      workspace_ids: vec![workspace_id],
+      scope: AFApiTokenScope::Write,
+    };
+    scope
+      .run(async {
+        assert!(enforce_access_scope(&workspace_id, &Action::Read).is_ok());
+        assert!(enforce_access_scope(&workspace_id, &AFAccessLevel::FullAccess).is_ok());
+        assert!(enforce_access_scope(&workspace_id, &AFRole::Owner).is_err());
+        assert!(enforce_access_scope(&other_workspace_id, &Action::Read).is_err());
+      })
+      .await;
+  }
+}
@@ -209,6 +209,18 @@ pub enum AppError {
   #[error("paid plan workspace guest limit exceeded")]
   PaidPlanGuestLimitExceeded,
 
+  #[error("published page is protected by a password")]
+  PublishedViewLocked,
+
+  #[error("published page has expired")]
+  PublishedViewExpired,
+
+  #[error("{0}")]
+  PublishDomainNotVerified(String),
+
+  #[error("{0}")]
+  PublishDomainAlreadyTaken(String),
+
   #[error("{0}")]
   RetryLater(anyhow::Error),
 }
@@ -298,6 +310,10 @@ impl AppError {
       AppError::InvalidGuest(_) => ErrorCode::InvalidGuest,
       AppError::FreePlanGuestLimitExceeded => ErrorCode::FreePlanGuestLimitExceeded,
       AppError::PaidPlanGuestLimitExceeded => ErrorCode::PaidPlanGuestLimitExceeded,
+      AppError::PublishedViewLocked => ErrorCode::PublishedViewLocked,
+      AppError::PublishedViewExpired => ErrorCode::PublishedViewExpired,
+      AppError::PublishDomainNotVerified(_) => ErrorCode::PublishDomainNotVerified,
+      AppError::PublishDomainAlreadyTaken(_) => ErrorCode::PublishDomainAlreadyTaken,
       AppError::RecordDeleted(_) => ErrorCode::RecordDeleted,
       AppError::RetryLater(_) => ErrorCode::RetryLater,
     }
@@ -483,6 +499,10 @@ pub enum ErrorCode {
   InvalidGuest = 1069,
   FreePlanGuestLimitExceeded = 1070,
   PaidPlanGuestLimitExceeded = 1071,
+  PublishedViewLocked = 1072,
+  PublishedViewExpired = 1073,
+  PublishDomainNotVerified = 1074,
+  PublishDomainAlreadyTaken = 1075,
 }
 
 impl ErrorCode {
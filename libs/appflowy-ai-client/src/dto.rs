@@ -226,7 +226,7 @@ pub struct TranslateRowResponse {
   pub items: Vec<HashMap<String, String>>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum EmbeddingModel {
   #[serde(rename = "text-embedding-3-small")]
   TextEmbedding3Small,
@@ -234,14 +234,23 @@ pub enum EmbeddingModel {
   TextEmbedding3Large,
   #[serde(rename = "text-embedding-ada-002")]
   TextEmbeddingAda002,
+  /// A model served by a self-hosted endpoint, eg. an OpenAI compatible server or Ollama.
+  #[serde(untagged)]
+  Custom(CustomEmbeddingModel),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CustomEmbeddingModel {
+  pub name: String,
+  pub dimensions: u32,
+  pub max_tokens: usize,
 }
 
 impl EmbeddingModel {
   /// Returns the default embedding model used in this system.
   ///
-  /// This model is hardcoded and used to generate embeddings whose dimensions are
-  /// reflected in the PostgreSQL database schema. Changing the default model may
-  /// require a migration to create a new table with the appropriate dimensions.
+  /// Embeddings of other models are stored alongside the ones of the default model, each
+  /// fragment records the model it was embedded with.
   pub fn default_model() -> Self {
     EmbeddingModel::TextEmbedding3Small
   }
@@ -259,6 +268,7 @@ impl EmbeddingModel {
       EmbeddingModel::TextEmbeddingAda002 => 8191,
       EmbeddingModel::TextEmbedding3Large => 8191,
       EmbeddingModel::TextEmbedding3Small => 8191,
+      EmbeddingModel::Custom(model) => model.max_tokens,
     }
   }
 
@@ -267,14 +277,16 @@ impl EmbeddingModel {
       EmbeddingModel::TextEmbeddingAda002 => 1536,
       EmbeddingModel::TextEmbedding3Large => 3072,
       EmbeddingModel::TextEmbedding3Small => 1536,
+      EmbeddingModel::Custom(model) => model.dimensions,
     }
   }
 
-  pub fn name(&self) -> &'static str {
+  pub fn name(&self) -> &str {
     match self {
       EmbeddingModel::TextEmbeddingAda002 => "text-embedding-ada-002",
       EmbeddingModel::TextEmbedding3Large => "text-embedding-3-large",
       EmbeddingModel::TextEmbedding3Small => "text-embedding-3-small",
+      EmbeddingModel::Custom(model) => &model.name,
     }
   }
 
@@ -290,11 +302,7 @@ impl EmbeddingModel {
 
 impl Display for EmbeddingModel {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-    match self {
-      EmbeddingModel::TextEmbedding3Small => write!(f, "text-embedding-3-small"),
-      EmbeddingModel::TextEmbedding3Large => write!(f, "text-embedding-3-large"),
-      EmbeddingModel::TextEmbeddingAda002 => write!(f, "text-embedding-ada-002"),
-    }
+    write!(f, "{}", self.name())
   }
 }
 
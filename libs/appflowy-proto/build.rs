@@ -14,6 +14,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     "proto/messages.proto",
     "proto/collab.proto",
     "proto/notification.proto",
+    "proto/observer.proto",
+    "proto/presence.proto",
   ];
   for proto_file in &proto_files {
     println!("cargo:rerun-if-changed={}", proto_file);
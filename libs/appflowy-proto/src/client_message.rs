@@ -56,6 +56,30 @@ pub enum ClientMessage {
     collab_type: CollabType,
     awareness: Vec<u8>,
   },
+
+  /// Tells which object of the workspace the user is currently looking at, so that it can be
+  /// shared with other members through workspace presence.
+  ///
+  /// # Fields
+  /// * `object_id` - The focused object, or `None` when no object is focused
+  FocusChanged { object_id: Option<ObjectId> },
+
+  /// Starts observing a collab. Only sent by observer sessions, which receive the updates of
+  /// the collabs they subscribed to and nothing else.
+  ///
+  /// # Fields
+  /// * `object_id` - The unique identifier of the collaborative object
+  /// * `collab_type` - The type of collaborative object
+  Subscribe {
+    object_id: ObjectId,
+    collab_type: CollabType,
+  },
+
+  /// Stops observing a collab. Only sent by observer sessions.
+  ///
+  /// # Fields
+  /// * `object_id` - The unique identifier of the collaborative object
+  Unsubscribe { object_id: ObjectId },
 }
 
 impl Debug for ClientMessage {
@@ -106,17 +130,36 @@ impl Debug for ClientMessage {
           .field("awareness", &awareness)
           .finish()
       },
+      ClientMessage::FocusChanged { object_id } => f
+        .debug_struct("FocusChanged")
+        .field("object_id", &object_id)
+        .finish(),
+      ClientMessage::Subscribe {
+        object_id,
+        collab_type,
+      } => f
+        .debug_struct("Subscribe")
+        .field("object_id", &object_id)
+        .field("collab_type", &collab_type)
+        .finish(),
+      ClientMessage::Unsubscribe { object_id } => f
+        .debug_struct("Unsubscribe")
+        .field("object_id", &object_id)
+        .finish(),
     }
   }
 }
 
 impl ClientMessage {
-  /// Returns a reference to the object ID contained in this message.
-  pub fn object_id(&self) -> &ObjectId {
+  /// Returns a reference to the object ID of the collab this message is related to, if any.
+  pub fn object_id(&self) -> Option<&ObjectId> {
     match self {
-      ClientMessage::Manifest { object_id, .. } => object_id,
-      ClientMessage::Update { object_id, .. } => object_id,
-      ClientMessage::AwarenessUpdate { object_id, .. } => object_id,
+      ClientMessage::Manifest { object_id, .. } => Some(object_id),
+      ClientMessage::Update { object_id, .. } => Some(object_id),
+      ClientMessage::AwarenessUpdate { object_id, .. } => Some(object_id),
+      ClientMessage::Subscribe { object_id, .. } => Some(object_id),
+      ClientMessage::Unsubscribe { object_id } => Some(object_id),
+      ClientMessage::FocusChanged { .. } => None,
     }
   }
 
@@ -191,6 +234,25 @@ impl From<ClientMessage> for pb::Message {
           })),
         }
       },
+      ClientMessage::FocusChanged { object_id } => pb::Message {
+        payload: Some(message::Payload::FocusChanged(pb::presence::FocusChanged {
+          object_id: object_id.map(|object_id| object_id.to_string()),
+        })),
+      },
+      ClientMessage::Subscribe {
+        object_id,
+        collab_type,
+      } => pb::Message {
+        payload: Some(message::Payload::Subscribe(pb::observer::Subscribe {
+          object_id: object_id.to_string(),
+          collab_type: collab_type as i32,
+        })),
+      },
+      ClientMessage::Unsubscribe { object_id } => pb::Message {
+        payload: Some(message::Payload::Unsubscribe(pb::observer::Unsubscribe {
+          object_id: object_id.to_string(),
+        })),
+      },
     }
   }
 }
@@ -240,7 +302,22 @@ impl TryFrom<pb::Message> for ClientMessage {
             _ => Err(Error::MissingFields),
           }
         },
-        Payload::Notification(_) => Err(Error::UnsupportedClientMessage),
+        Payload::FocusChanged(proto) => Ok(ClientMessage::FocusChanged {
+          object_id: proto
+            .object_id
+            .map(|object_id| Uuid::parse_str(&object_id))
+            .transpose()?,
+        }),
+        Payload::Subscribe(proto) => Ok(ClientMessage::Subscribe {
+          object_id: Uuid::parse_str(&proto.object_id)?,
+          collab_type: CollabType::from(proto.collab_type),
+        }),
+        Payload::Unsubscribe(proto) => Ok(ClientMessage::Unsubscribe {
+          object_id: Uuid::parse_str(&proto.object_id)?,
+        }),
+        Payload::Notification(_) | Payload::WorkspacePresence(_) => {
+          Err(Error::UnsupportedClientMessage)
+        },
       },
     }
   }
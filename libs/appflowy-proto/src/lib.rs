@@ -1,8 +1,10 @@
 mod client_message;
 mod pb;
+mod presence;
 mod server_message;
 mod shared;
 
 pub use client_message::*;
+pub use presence::*;
 pub use server_message::*;
 pub use shared::*;
@@ -3,7 +3,7 @@
 /// All messages send between client/server are wrapped into a `Message`.
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Message {
-  #[prost(oneof = "message::Payload", tags = "1, 2")]
+  #[prost(oneof = "message::Payload", tags = "1, 2, 3, 4, 5, 6")]
   pub payload: ::core::option::Option<message::Payload>,
 }
 /// Nested message and enum types in `Message`.
@@ -14,5 +14,13 @@ pub mod message {
     CollabMessage(super::super::collab::CollabMessage),
     #[prost(message, tag = "2")]
     Notification(super::super::notification::WorkspaceNotification),
+    #[prost(message, tag = "3")]
+    FocusChanged(super::super::presence::FocusChanged),
+    #[prost(message, tag = "4")]
+    WorkspacePresence(super::super::presence::WorkspacePresence),
+    #[prost(message, tag = "5")]
+    Subscribe(super::super::observer::Subscribe),
+    #[prost(message, tag = "6")]
+    Unsubscribe(super::super::observer::Unsubscribe),
   }
 }
@@ -1,6 +1,8 @@
 mod collab;
 mod messages;
 pub mod notification;
+pub mod observer;
+pub mod presence;
 
 pub use collab::*;
 pub use messages::*;
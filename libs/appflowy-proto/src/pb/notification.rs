@@ -1,7 +1,10 @@
 // This file is @generated by prost-build.
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WorkspaceNotification {
-  #[prost(oneof = "workspace_notification::Payload", tags = "1, 2")]
+  #[prost(
+    oneof = "workspace_notification::Payload",
+    tags = "1, 2, 3, 4, 5, 6, 7, 8"
+  )]
   pub payload: ::core::option::Option<workspace_notification::Payload>,
 }
 /// Nested message and enum types in `WorkspaceNotification`.
@@ -12,6 +15,18 @@ pub mod workspace_notification {
     ProfileChange(super::UserProfileChange),
     #[prost(message, tag = "2")]
     PermissionChanged(super::PermissionChanged),
+    #[prost(message, tag = "3")]
+    MemberChanged(super::MemberChanged),
+    #[prost(message, tag = "4")]
+    WorkspaceChanged(super::WorkspaceChanged),
+    #[prost(message, tag = "5")]
+    WorkspaceSettingsChanged(super::WorkspaceSettingsChanged),
+    #[prost(message, tag = "6")]
+    PublishChanged(super::PublishChanged),
+    #[prost(message, tag = "7")]
+    QuickNoteChanged(super::QuickNoteChanged),
+    #[prost(message, tag = "8")]
+    ImportTaskFinished(super::ImportTaskFinished),
   }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -30,3 +45,57 @@ pub struct PermissionChanged {
   #[prost(uint32, tag = "2")]
   pub reason: u32,
 }
+/// A member joined the workspace, left it or got another role.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MemberChanged {
+  #[prost(int64, tag = "1")]
+  pub uid: i64,
+  /// 0: added, 1: removed, 2: role changed.
+  #[prost(uint32, tag = "2")]
+  pub change: u32,
+  /// Role of the member, absent when the member was removed.
+  #[prost(int32, optional, tag = "3")]
+  pub role: ::core::option::Option<i32>,
+}
+/// Fields are only present when they changed.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkspaceChanged {
+  #[prost(string, optional, tag = "1")]
+  pub name: ::core::option::Option<::prost::alloc::string::String>,
+  #[prost(string, optional, tag = "2")]
+  pub icon: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkspaceSettingsChanged {
+  #[prost(bool, tag = "1")]
+  pub disable_search_indexing: bool,
+  #[prost(string, tag = "2")]
+  pub ai_model: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PublishChanged {
+  #[prost(string, repeated, tag = "1")]
+  pub view_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+  #[prost(bool, tag = "2")]
+  pub published: bool,
+}
+/// Quick notes are private, so only the sessions of their owner receive this notification.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuickNoteChanged {
+  #[prost(string, tag = "1")]
+  pub quick_note_id: ::prost::alloc::string::String,
+  /// 0: created, 1: updated, 2: deleted.
+  #[prost(uint32, tag = "2")]
+  pub change: u32,
+}
+/// Sent to the sessions of the user who started the import, in any workspace.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportTaskFinished {
+  #[prost(string, tag = "1")]
+  pub task_id: ::prost::alloc::string::String,
+  /// Workspace the data was imported into.
+  #[prost(string, tag = "2")]
+  pub workspace_id: ::prost::alloc::string::String,
+  #[prost(bool, tag = "3")]
+  pub success: bool,
+}
@@ -0,0 +1,21 @@
+// This file is @generated by prost-build.
+/// *
+/// Subscribe message is send by observer sessions to start receiving the updates of a collab.
+/// The server replies with the current state of the collab, followed by its updates.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Subscribe {
+  /// Unique identifier (UUID) of the collab to observe.
+  #[prost(string, tag = "1")]
+  pub object_id: ::prost::alloc::string::String,
+  /// Collab type - required to read the current state of the collab.
+  #[prost(int32, tag = "2")]
+  pub collab_type: i32,
+}
+/// *
+/// Unsubscribe message is send by observer sessions to stop receiving the updates of a collab.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Unsubscribe {
+  /// Unique identifier (UUID) of the observed collab.
+  #[prost(string, tag = "1")]
+  pub object_id: ::prost::alloc::string::String,
+}
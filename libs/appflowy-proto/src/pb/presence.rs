@@ -0,0 +1,32 @@
+// This file is @generated by prost-build.
+/// *
+/// FocusChanged message is send by the client whenever the user opens another object of
+/// the workspace, or closes the current one.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FocusChanged {
+  /// Unique identifier (UUID) of the object the user is looking at, if any.
+  #[prost(string, optional, tag = "1")]
+  pub object_id: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// *
+/// SessionPresence describes a single client session connected to the workspace.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SessionPresence {
+  #[prost(int64, tag = "1")]
+  pub uid: i64,
+  #[prost(string, tag = "2")]
+  pub device_id: ::prost::alloc::string::String,
+  /// Object focused in this session. It's left out when the receiving user
+  /// has no access to that object.
+  #[prost(string, optional, tag = "3")]
+  pub object_id: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// *
+/// WorkspacePresence message is send only by the server. It contains all sessions
+/// currently connected to the workspace, across all server nodes, and replaces any
+/// presence received before.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkspacePresence {
+  #[prost(message, repeated, tag = "1")]
+  pub sessions: ::prost::alloc::vec::Vec<SessionPresence>,
+}
@@ -0,0 +1,93 @@
+use crate::pb;
+use crate::shared::{Error, ObjectId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// A client session connected to a workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionPresence {
+  pub uid: i64,
+  pub device_id: String,
+  /// Object currently focused in this session. It's `None` when nothing is focused, or when
+  /// the receiving user has no access to the focused object.
+  pub object_id: Option<ObjectId>,
+}
+
+/// All sessions connected to a workspace at a given time.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspacePresence {
+  pub sessions: Vec<SessionPresence>,
+}
+
+impl WorkspacePresence {
+  /// Returns the number of distinct users online, as the same user can be connected from many
+  /// devices.
+  pub fn user_count(&self) -> usize {
+    self
+      .sessions
+      .iter()
+      .map(|session| session.uid)
+      .collect::<HashSet<_>>()
+      .len()
+  }
+
+  /// Returns the distinct users currently focused on the given object.
+  pub fn users_on(&self, object_id: &ObjectId) -> Vec<i64> {
+    let mut uids: Vec<i64> = self
+      .sessions
+      .iter()
+      .filter(|session| session.object_id.as_ref() == Some(object_id))
+      .map(|session| session.uid)
+      .collect();
+    uids.sort_unstable();
+    uids.dedup();
+    uids
+  }
+}
+
+impl From<SessionPresence> for pb::presence::SessionPresence {
+  fn from(value: SessionPresence) -> Self {
+    pb::presence::SessionPresence {
+      uid: value.uid,
+      device_id: value.device_id,
+      object_id: value.object_id.map(|object_id| object_id.to_string()),
+    }
+  }
+}
+
+impl TryFrom<pb::presence::SessionPresence> for SessionPresence {
+  type Error = Error;
+
+  fn try_from(value: pb::presence::SessionPresence) -> Result<Self, Self::Error> {
+    Ok(SessionPresence {
+      uid: value.uid,
+      device_id: value.device_id,
+      object_id: value
+        .object_id
+        .map(|object_id| Uuid::parse_str(&object_id))
+        .transpose()?,
+    })
+  }
+}
+
+impl From<WorkspacePresence> for pb::presence::WorkspacePresence {
+  fn from(value: WorkspacePresence) -> Self {
+    pb::presence::WorkspacePresence {
+      sessions: value.sessions.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+impl TryFrom<pb::presence::WorkspacePresence> for WorkspacePresence {
+  type Error = Error;
+
+  fn try_from(value: pb::presence::WorkspacePresence) -> Result<Self, Self::Error> {
+    let sessions = value
+      .sessions
+      .into_iter()
+      .map(SessionPresence::try_from)
+      .collect::<Result<_, _>>()?;
+    Ok(WorkspacePresence { sessions })
+  }
+}
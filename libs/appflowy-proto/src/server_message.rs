@@ -1,7 +1,11 @@
 use crate::pb;
 use crate::pb::collab_message::Data;
 use crate::pb::message::Payload;
-use crate::pb::notification::{PermissionChanged, UserProfileChange};
+use crate::pb::notification::{
+  ImportTaskFinished, MemberChanged, PermissionChanged, PublishChanged, QuickNoteChanged,
+  UserProfileChange, WorkspaceChanged, WorkspaceSettingsChanged,
+};
+use crate::presence::WorkspacePresence;
 #[rustfmt::skip]
 use crate::pb::{SyncRequest, message};
 use crate::shared::{Error, ObjectId, Rid, UpdateFlags};
@@ -62,6 +66,10 @@ pub enum ServerMessage {
   Notification {
     notification: WorkspaceNotification,
   },
+  /// Sessions currently connected to the workspace. Replaces any presence received before.
+  Presence {
+    presence: WorkspacePresence,
+  },
 }
 
 impl ServerMessage {
@@ -143,6 +151,10 @@ impl Debug for ServerMessage {
         .debug_struct("WorkspaceNotification")
         .field("notification", &notification)
         .finish(),
+      ServerMessage::Presence { presence } => f
+        .debug_struct("WorkspacePresence")
+        .field("presence", &presence)
+        .finish(),
     }
   }
 }
@@ -218,28 +230,11 @@ impl From<ServerMessage> for pb::Message {
           })),
         })),
       },
-      ServerMessage::Notification { notification } => match notification {
-        WorkspaceNotification::UserProfileChange { uid, email, name } => pb::Message {
-          payload: Some(message::Payload::Notification(
-            pb::notification::WorkspaceNotification {
-              payload: Some(NotificationPayload::ProfileChange(UserProfileChange {
-                uid,
-                name,
-                email,
-              })),
-            },
-          )),
-        },
-        WorkspaceNotification::ObjectAccessChanged { object_id, reason } => pb::Message {
-          payload: Some(message::Payload::Notification(
-            pb::notification::WorkspaceNotification {
-              payload: Some(NotificationPayload::PermissionChanged(PermissionChanged {
-                object_id: object_id.to_string(),
-                reason: reason as u32,
-              })),
-            },
-          )),
-        },
+      ServerMessage::Notification { notification } => pb::Message {
+        payload: Some(message::Payload::Notification(notification.into())),
+      },
+      ServerMessage::Presence { presence } => pb::Message {
+        payload: Some(message::Payload::WorkspacePresence(presence.into())),
       },
     }
   }
@@ -297,26 +292,14 @@ impl TryFrom<pb::Message> for ServerMessage {
             _ => Err(Error::MissingFields),
           }
         },
-        Payload::Notification(notification) => match notification.payload {
-          None => Err(Error::MissingFields),
-          Some(payload) => match payload {
-            NotificationPayload::ProfileChange(value) => Ok(ServerMessage::Notification {
-              notification: WorkspaceNotification::UserProfileChange {
-                uid: value.uid,
-                email: value.email,
-                name: value.name,
-              },
-            }),
-            NotificationPayload::PermissionChanged(value) => {
-              let object_id = Uuid::parse_str(&value.object_id)?;
-              Ok(ServerMessage::Notification {
-                notification: WorkspaceNotification::ObjectAccessChanged {
-                  object_id,
-                  reason: value.reason.into(),
-                },
-              })
-            },
-          },
+        Payload::Notification(notification) => Ok(ServerMessage::Notification {
+          notification: WorkspaceNotification::try_from(notification)?,
+        }),
+        Payload::WorkspacePresence(presence) => Ok(ServerMessage::Presence {
+          presence: WorkspacePresence::try_from(presence)?,
+        }),
+        Payload::FocusChanged(_) | Payload::Subscribe(_) | Payload::Unsubscribe(_) => {
+          Err(Error::UnsupportedServerMessage)
         },
       },
     }
@@ -334,6 +317,197 @@ pub enum WorkspaceNotification {
     object_id: Uuid,
     reason: AccessChangedReason,
   },
+  /// A member joined the workspace, left it, or got another role. `role` is the id of the
+  /// member's role, and is not set for removed members.
+  MemberChanged {
+    uid: i64,
+    change: MemberChange,
+    role: Option<i32>,
+  },
+  /// The name or icon of the workspace changed. Only the fields that changed are set.
+  WorkspaceChanged {
+    name: Option<String>,
+    icon: Option<String>,
+  },
+  WorkspaceSettingsChanged {
+    disable_search_indexing: bool,
+    ai_model: String,
+  },
+  PublishChanged {
+    view_ids: Vec<Uuid>,
+    published: bool,
+  },
+  /// Only sent to the sessions of the owner of the quick note.
+  QuickNoteChanged {
+    quick_note_id: Uuid,
+    change: QuickNoteChange,
+  },
+  /// Sent to the sessions of the user who started the import, whichever workspace they are
+  /// connected to, as the data is imported into a new workspace.
+  ImportTaskFinished {
+    task_id: Uuid,
+    workspace_id: Uuid,
+    success: bool,
+  },
+}
+
+#[derive(
+  Clone, Copy, Debug, PartialEq, Eq, serde_repr::Serialize_repr, serde_repr::Deserialize_repr,
+)]
+#[repr(u8)]
+pub enum MemberChange {
+  Added = 0,
+  Removed = 1,
+  RoleChanged = 2,
+}
+
+impl TryFrom<u32> for MemberChange {
+  type Error = Error;
+
+  fn try_from(value: u32) -> Result<Self, Self::Error> {
+    match value {
+      0 => Ok(MemberChange::Added),
+      1 => Ok(MemberChange::Removed),
+      2 => Ok(MemberChange::RoleChanged),
+      _ => Err(Error::UnknownChange(value)),
+    }
+  }
+}
+
+#[derive(
+  Clone, Copy, Debug, PartialEq, Eq, serde_repr::Serialize_repr, serde_repr::Deserialize_repr,
+)]
+#[repr(u8)]
+pub enum QuickNoteChange {
+  Created = 0,
+  Updated = 1,
+  Deleted = 2,
+}
+
+impl TryFrom<u32> for QuickNoteChange {
+  type Error = Error;
+
+  fn try_from(value: u32) -> Result<Self, Self::Error> {
+    match value {
+      0 => Ok(QuickNoteChange::Created),
+      1 => Ok(QuickNoteChange::Updated),
+      2 => Ok(QuickNoteChange::Deleted),
+      _ => Err(Error::UnknownChange(value)),
+    }
+  }
+}
+
+impl From<WorkspaceNotification> for pb::notification::WorkspaceNotification {
+  fn from(value: WorkspaceNotification) -> Self {
+    let payload = match value {
+      WorkspaceNotification::UserProfileChange { uid, email, name } => {
+        NotificationPayload::ProfileChange(UserProfileChange { uid, name, email })
+      },
+      WorkspaceNotification::ObjectAccessChanged { object_id, reason } => {
+        NotificationPayload::PermissionChanged(PermissionChanged {
+          object_id: object_id.to_string(),
+          reason: reason as u32,
+        })
+      },
+      WorkspaceNotification::MemberChanged { uid, change, role } => {
+        NotificationPayload::MemberChanged(MemberChanged {
+          uid,
+          change: change as u32,
+          role,
+        })
+      },
+      WorkspaceNotification::WorkspaceChanged { name, icon } => {
+        NotificationPayload::WorkspaceChanged(WorkspaceChanged { name, icon })
+      },
+      WorkspaceNotification::WorkspaceSettingsChanged {
+        disable_search_indexing,
+        ai_model,
+      } => NotificationPayload::WorkspaceSettingsChanged(WorkspaceSettingsChanged {
+        disable_search_indexing,
+        ai_model,
+      }),
+      WorkspaceNotification::PublishChanged {
+        view_ids,
+        published,
+      } => NotificationPayload::PublishChanged(PublishChanged {
+        view_ids: view_ids.iter().map(|id| id.to_string()).collect(),
+        published,
+      }),
+      WorkspaceNotification::QuickNoteChanged {
+        quick_note_id,
+        change,
+      } => NotificationPayload::QuickNoteChanged(QuickNoteChanged {
+        quick_note_id: quick_note_id.to_string(),
+        change: change as u32,
+      }),
+      WorkspaceNotification::ImportTaskFinished {
+        task_id,
+        workspace_id,
+        success,
+      } => NotificationPayload::ImportTaskFinished(ImportTaskFinished {
+        task_id: task_id.to_string(),
+        workspace_id: workspace_id.to_string(),
+        success,
+      }),
+    };
+    pb::notification::WorkspaceNotification {
+      payload: Some(payload),
+    }
+  }
+}
+
+impl TryFrom<pb::notification::WorkspaceNotification> for WorkspaceNotification {
+  type Error = Error;
+
+  fn try_from(value: pb::notification::WorkspaceNotification) -> Result<Self, Self::Error> {
+    match value.payload.ok_or(Error::MissingFields)? {
+      NotificationPayload::ProfileChange(value) => Ok(WorkspaceNotification::UserProfileChange {
+        uid: value.uid,
+        email: value.email,
+        name: value.name,
+      }),
+      NotificationPayload::PermissionChanged(value) => {
+        Ok(WorkspaceNotification::ObjectAccessChanged {
+          object_id: Uuid::parse_str(&value.object_id)?,
+          reason: value.reason.into(),
+        })
+      },
+      NotificationPayload::MemberChanged(value) => Ok(WorkspaceNotification::MemberChanged {
+        uid: value.uid,
+        change: MemberChange::try_from(value.change)?,
+        role: value.role,
+      }),
+      NotificationPayload::WorkspaceChanged(value) => Ok(WorkspaceNotification::WorkspaceChanged {
+        name: value.name,
+        icon: value.icon,
+      }),
+      NotificationPayload::WorkspaceSettingsChanged(value) => {
+        Ok(WorkspaceNotification::WorkspaceSettingsChanged {
+          disable_search_indexing: value.disable_search_indexing,
+          ai_model: value.ai_model,
+        })
+      },
+      NotificationPayload::PublishChanged(value) => Ok(WorkspaceNotification::PublishChanged {
+        view_ids: value
+          .view_ids
+          .iter()
+          .map(|id| Uuid::parse_str(id))
+          .collect::<Result<_, _>>()?,
+        published: value.published,
+      }),
+      NotificationPayload::QuickNoteChanged(value) => Ok(WorkspaceNotification::QuickNoteChanged {
+        quick_note_id: Uuid::parse_str(&value.quick_note_id)?,
+        change: QuickNoteChange::try_from(value.change)?,
+      }),
+      NotificationPayload::ImportTaskFinished(value) => {
+        Ok(WorkspaceNotification::ImportTaskFinished {
+          task_id: Uuid::parse_str(&value.task_id)?,
+          workspace_id: Uuid::parse_str(&value.workspace_id)?,
+          success: value.success,
+        })
+      },
+    }
+  }
 }
 
 impl From<AccessChangedReason> for i32 {
@@ -98,8 +98,12 @@ pub enum Error {
   UnsupportedFlag(u8),
   #[error("failed to decode message: unknown collab type: {0}")]
   UnknownCollabType(u8),
+  #[error("failed to decode message: unknown change: {0}")]
+  UnknownChange(u32),
   #[error("Message does not match expected client message")]
   UnsupportedClientMessage,
+  #[error("Message does not match expected server message")]
+  UnsupportedServerMessage,
 }
 
 pub struct TimestampedEncodedCollab {
@@ -991,6 +991,8 @@ impl TestClient {
           data: blob,
           comments_enabled,
           duplicate_enabled,
+          password: None,
+          expires_at: None,
         }
       })
       .collect();
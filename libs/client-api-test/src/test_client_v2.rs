@@ -19,7 +19,7 @@ use client_api::entity::id::user_awareness_object_id;
 use client_api::entity::{
   CompletionStream, CompletionStreamValue, PublishCollabItem, PublishCollabMetadata,
   QueryWorkspaceMember, QuestionStream, QuestionStreamValue, UpdateCollabWebParams,
-  WorkspaceNotification,
+  WorkspaceNotification, WorkspacePresence,
 };
 use client_api::v2::WorkspaceController;
 
@@ -194,6 +194,23 @@ impl TestClient {
       .subscribe_notification()
   }
 
+  pub async fn subscribe_workspace_presence(
+    &self,
+    workspace_id: &Uuid,
+  ) -> tokio::sync::watch::Receiver<WorkspacePresence> {
+    self
+      .workspace_controller_for(*workspace_id)
+      .await
+      .subscribe_presence()
+  }
+
+  pub async fn set_focused_object(&self, workspace_id: &Uuid, object_id: Option<Uuid>) {
+    self
+      .workspace_controller_for(*workspace_id)
+      .await
+      .set_focused_object(object_id);
+  }
+
   /// Enables/disables message receiving for debugging (debug builds only)
   #[cfg(debug_assertions)]
   pub fn disable_receive_message(&mut self) {
@@ -1039,6 +1056,8 @@ impl TestClient {
           data: blob,
           comments_enabled,
           duplicate_enabled,
+          password: None,
+          expires_at: None,
         }
       })
       .collect();
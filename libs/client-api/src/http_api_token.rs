@@ -0,0 +1,43 @@
+use client_api_entity::api_token_dto::{AFApiToken, CreateApiTokenParams};
+use reqwest::Method;
+use shared_entity::response::AppResponseError;
+use uuid::Uuid;
+
+use crate::{process_response_data, process_response_error, Client};
+
+impl Client {
+  /// The returned token contains the token string, which is not returned again afterwards.
+  pub async fn create_api_token(
+    &self,
+    params: &CreateApiTokenParams,
+  ) -> Result<AFApiToken, AppResponseError> {
+    let url = format!("{}/api/token", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<AFApiToken>(resp).await
+  }
+
+  pub async fn list_api_tokens(&self) -> Result<Vec<AFApiToken>, AppResponseError> {
+    let url = format!("{}/api/token", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<Vec<AFApiToken>>(resp).await
+  }
+
+  pub async fn revoke_api_token(&self, token_id: &Uuid) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/token/{}", self.base_url, token_id);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+}
@@ -0,0 +1,45 @@
+use client_api_entity::audit_dto::{AFAuditLogEntry, QueryAuditLogParams};
+use reqwest::Method;
+use shared_entity::response::AppResponseError;
+use uuid::Uuid;
+
+use crate::{process_response_data, Client};
+
+impl Client {
+  pub async fn list_workspace_audit_logs(
+    &self,
+    workspace_id: &Uuid,
+    params: &QueryAuditLogParams,
+  ) -> Result<Vec<AFAuditLogEntry>, AppResponseError> {
+    let url = format!("{}/api/audit/{}", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(params)
+      .send()
+      .await?;
+    process_response_data::<Vec<AFAuditLogEntry>>(resp).await
+  }
+
+  /// Returns the matching audit log entries as CSV.
+  pub async fn export_workspace_audit_logs_csv(
+    &self,
+    workspace_id: &Uuid,
+    params: &QueryAuditLogParams,
+  ) -> Result<String, AppResponseError> {
+    let url = format!("{}/api/audit/{}/export", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(params)
+      .send()
+      .await?;
+    let csv = resp.error_for_status()?.text().await?;
+
+    if let Ok(app_err) = serde_json::from_str::<AppResponseError>(&csv) {
+      return Err(app_err);
+    }
+
+    Ok(csv)
+  }
+}
@@ -8,14 +8,15 @@ use bytes::Bytes;
 use chrono::{DateTime, Utc};
 use client_api_entity::workspace_dto::{
   AFDatabase, AFDatabaseField, AFDatabaseRow, AFDatabaseRowDetail, AFInsertDatabaseField,
-  AddDatatabaseRow, DatabaseRowUpdatedItem, ListDatabaseRowDetailParam,
-  ListDatabaseRowUpdatedParam, UpsertDatatabaseRow,
+  AFReorderDatabaseFields, AFUpdateDatabaseField, AddDatatabaseRow, DatabaseRowUpdatedItem,
+  ListDatabaseRowDetailParam, ListDatabaseRowUpdatedParam, QueryDatabaseRowParams,
+  QueryDatabaseRowResponse, UpsertDatatabaseRow,
 };
 use client_api_entity::{
-  AFCollabEmbedInfo, AFDatabaseRowDocumentCollabExistenceInfo, BatchQueryCollabParams,
-  BatchQueryCollabResult, CollabParams, CreateCollabData, CreateCollabParams, DeleteCollabParams,
-  PublishCollabItem, QueryCollab, QueryCollabParams, RepeatedAFCollabEmbedInfo,
-  UpdateCollabWebParams,
+  AFCollabEmbedInfo, AFDatabaseRowDocumentCollabExistenceInfo, AFSnapshotMeta, AFSnapshotMetas,
+  BatchQueryCollabParams, BatchQueryCollabResult, CollabParams, CreateCollabData,
+  CreateCollabParams, DeleteCollabParams, PatchPublishedCollab, PublishCollabItem, QueryCollab,
+  QueryCollabParams, RepeatedAFCollabEmbedInfo, SnapshotData, UpdateCollabWebParams,
 };
 use collab_rt_entity::collab_proto::{CollabDocStateParams, PayloadCompressionType};
 use collab_rt_entity::HttpRealtimeMessage;
@@ -25,7 +26,10 @@ use prost::Message;
 use rayon::prelude::*;
 use reqwest::{Body, Method};
 use serde::Serialize;
-use shared_entity::dto::workspace_dto::{CollabResponse, CollabTypeParam, EmbeddedCollabQuery};
+use shared_entity::dto::workspace_dto::{
+  CollabJsonResponse, CollabResponse, CollabTypeParam, DocumentBlame, DocumentChanges,
+  DocumentChangesQuery, EmbeddedCollabQuery,
+};
 use shared_entity::response::AppResponseError;
 use std::collections::HashMap;
 use std::future::Future;
@@ -231,6 +235,82 @@ impl Client {
     process_response_data::<String>(resp).await
   }
 
+  pub async fn update_database_field(
+    &self,
+    workspace_id: &Uuid,
+    database_id: &str,
+    field_id: &str,
+    update_field: &AFUpdateDatabaseField,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/fields/{}",
+      self.base_url, workspace_id, database_id, field_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PATCH, &url)
+      .await?
+      .json(update_field)
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  pub async fn delete_database_field(
+    &self,
+    workspace_id: &Uuid,
+    database_id: &str,
+    field_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/fields/{}",
+      self.base_url, workspace_id, database_id, field_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  /// Fields that are not listed in `field_ids` are placed after the listed ones.
+  pub async fn reorder_database_fields(
+    &self,
+    workspace_id: &Uuid,
+    database_id: &str,
+    field_ids: Vec<String>,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/fields/order",
+      self.base_url, workspace_id, database_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&AFReorderDatabaseFields { field_ids })
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  pub async fn delete_database_row(
+    &self,
+    workspace_id: &Uuid,
+    database_id: &str,
+    row_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/row/{}",
+      self.base_url, workspace_id, database_id, row_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
   pub async fn list_database_row_ids_updated(
     &self,
     workspace_id: &Uuid,
@@ -270,6 +350,27 @@ impl Client {
     process_response_data::<Vec<AFDatabaseRowDetail>>(resp).await
   }
 
+  /// Returns the rows matching [QueryDatabaseRowParams::filter], ordered by
+  /// [QueryDatabaseRowParams::sorts]. Pass the returned `next_cursor` to fetch the next page.
+  pub async fn query_database_rows(
+    &self,
+    workspace_id: &Uuid,
+    database_id: &str,
+    params: &QueryDatabaseRowParams,
+  ) -> Result<QueryDatabaseRowResponse, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/row/query",
+      self.base_url, workspace_id, database_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<QueryDatabaseRowResponse>(resp).await
+  }
+
   /// Example payload:
   /// {
   ///   "Name": "some_data",        # using column name
@@ -411,6 +512,8 @@ impl Client {
     RetryIf::spawn(retry_strategy, action, RetryGetCollabCondition).await
   }
 
+  /// The publish stream only carries the metadata and data of each item, so the password and
+  /// expiry are applied with a follow-up patch.
   pub async fn publish_collabs<Metadata, Data>(
     &self,
     workspace_id: &Uuid,
@@ -420,6 +523,19 @@ impl Client {
     Metadata: serde::Serialize + Send + 'static + Unpin,
     Data: AsRef<[u8]> + Send + 'static + Unpin,
   {
+    let access_patches: Vec<PatchPublishedCollab> = items
+      .iter()
+      .filter(|item| item.password.is_some() || item.expires_at.is_some())
+      .map(|item| PatchPublishedCollab {
+        view_id: item.meta.view_id,
+        publish_name: None,
+        comments_enabled: None,
+        duplicate_enabled: None,
+        password: item.password.clone(),
+        expires_at: item.expires_at,
+        clear_expires_at: false,
+      })
+      .collect();
     let publish_collab_stream = PublishCollabItemStream::new(items);
     let url = format!("{}/api/workspace/{}/publish", self.base_url, workspace_id,);
     let resp = self
@@ -428,7 +544,13 @@ impl Client {
       .body(Body::wrap_stream(publish_collab_stream))
       .send()
       .await?;
-    process_response_error(resp).await
+    process_response_error(resp).await?;
+    if !access_patches.is_empty() {
+      self
+        .patch_published_collabs(workspace_id, &access_patches)
+        .await?;
+    }
+    Ok(())
   }
 
   pub async fn check_if_row_document_collab_exists(
@@ -504,6 +626,120 @@ impl Client {
     process_response_error(resp).await
   }
 
+  /// Returns the last editor of each block of the document, in document order.
+  pub async fn get_document_blame(
+    &self,
+    workspace_id: &Uuid,
+    object_id: &Uuid,
+  ) -> Result<DocumentBlame, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/v1/{workspace_id}/collab/{object_id}/blame",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<DocumentBlame>(resp).await
+  }
+
+  /// Returns the blocks of the document that changed in the given period.
+  pub async fn get_document_changes(
+    &self,
+    workspace_id: &Uuid,
+    object_id: &Uuid,
+    query: &DocumentChangesQuery,
+  ) -> Result<DocumentChanges, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/v1/{workspace_id}/collab/{object_id}/changes",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send()
+      .await?;
+    process_response_data::<DocumentChanges>(resp).await
+  }
+
+  /// Returns the snapshots of the collab, newest first.
+  pub async fn list_collab_snapshots(
+    &self,
+    workspace_id: &Uuid,
+    object_id: &Uuid,
+  ) -> Result<AFSnapshotMetas, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/v1/{workspace_id}/collab/{object_id}/snapshot",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<AFSnapshotMetas>(resp).await
+  }
+
+  pub async fn get_collab_snapshot(
+    &self,
+    workspace_id: &Uuid,
+    object_id: &Uuid,
+    snapshot_id: i64,
+  ) -> Result<SnapshotData, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/v1/{workspace_id}/collab/{object_id}/snapshot/{snapshot_id}",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<SnapshotData>(resp).await
+  }
+
+  pub async fn get_collab_snapshot_json(
+    &self,
+    workspace_id: &Uuid,
+    object_id: &Uuid,
+    snapshot_id: i64,
+  ) -> Result<CollabJsonResponse, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/v1/{workspace_id}/collab/{object_id}/snapshot/{snapshot_id}/json",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<CollabJsonResponse>(resp).await
+  }
+
+  /// Restores the collab to the given snapshot. The state before the restoration is kept as a
+  /// new snapshot, whose metadata is returned.
+  pub async fn restore_collab_snapshot(
+    &self,
+    workspace_id: &Uuid,
+    object_id: &Uuid,
+    snapshot_id: i64,
+    collab_type: CollabType,
+  ) -> Result<AFSnapshotMeta, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/v1/{workspace_id}/collab/{object_id}/snapshot/{snapshot_id}/restore",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&CollabTypeParam { collab_type })
+      .send()
+      .await?;
+    process_response_data::<AFSnapshotMeta>(resp).await
+  }
+
   pub async fn collab_full_sync(
     &self,
     workspace_id: &Uuid,
@@ -8,7 +8,7 @@ use std::fs::metadata;
 use client_api_entity::{
   CompleteUploadRequest, CreateUploadRequest, CreateUploadResponse, UploadPartResponse,
 };
-use client_api_entity::{CreateImportTask, CreateImportTaskResponse};
+use client_api_entity::{CreateImportTask, CreateImportTaskResponse, ImportSource};
 
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use reqwest::{multipart, Body, Method};
@@ -17,6 +17,7 @@ use std::path::Path;
 
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
+use shared_entity::dto::export_dto::{CreateExportTaskResponse, ExportDownloadUrl, UserExportTask};
 use shared_entity::dto::import_dto::UserImportTask;
 use tokio::fs::File;
 use tokio::io::{AsyncBufReadExt, BufReader};
@@ -124,6 +125,18 @@ impl Client {
   /// - The MIME type is automatically determined based on the file extension using `mime_guess`.
   ///
   pub async fn import_file(&self, file_path: &Path) -> Result<(), AppResponseError> {
+    self
+      .import_file_with_source(file_path, ImportSource::Notion)
+      .await
+  }
+
+  /// Same as [Self::import_file], but the file is imported with the importer of the given
+  /// [ImportSource]. The source is sent in the `X-Import-Source` header.
+  pub async fn import_file_with_source(
+    &self,
+    file_path: &Path,
+    source: ImportSource,
+  ) -> Result<(), AppResponseError> {
     let md5_base64 = calculate_md5(file_path).await?;
     let file = File::open(&file_path).await?;
     let metadata = file.metadata().await?;
@@ -152,7 +165,8 @@ impl Client {
     builder = builder
       .header("X-Host", self.base_url.clone())
       .header("X-Content-MD5", md5_base64)
-      .header("X-Content-Length", metadata.len());
+      .header("X-Content-Length", metadata.len())
+      .header("X-Import-Source", source.as_str());
     let resp = builder.send().await?;
 
     process_response_error(resp).await
@@ -170,6 +184,18 @@ impl Client {
   pub async fn create_import(
     &self,
     file_path: &Path,
+  ) -> Result<CreateImportTaskResponse, AppResponseError> {
+    self
+      .create_import_with_source(file_path, ImportSource::Notion)
+      .await
+  }
+
+  /// Same as [Self::create_import], but the uploaded file is imported with the importer of the
+  /// given [ImportSource].
+  pub async fn create_import_with_source(
+    &self,
+    file_path: &Path,
+    source: ImportSource,
   ) -> Result<CreateImportTaskResponse, AppResponseError> {
     let url = format!("{}/api/import/create", self.base_url);
     let file_name = file_path
@@ -181,6 +207,7 @@ impl Client {
     let params = CreateImportTask {
       workspace_name: file_name.clone(),
       content_length,
+      source,
     };
     let resp = self
       .http_client_with_auth(Method::POST, &url)
@@ -211,13 +238,17 @@ impl Client {
     let file = File::open(file_path).await?;
     let file_stream = FramedRead::new(file, BytesCodec::new());
     let stream_body = Body::wrap_stream(file_stream);
+    let content_type = match file_path.extension().and_then(|ext| ext.to_str()) {
+      Some("csv") => ImportSource::Csv.content_type(),
+      _ => ImportSource::Notion.content_type(),
+    };
     trace!("start upload file to s3: {}", url);
 
     let client = reqwest::Client::new();
     let upload_resp = client
       .put(url)
       .header("Content-Length", file_size)
-      .header("Content-Type", "application/zip")
+      .header("Content-Type", content_type)
       .body(stream_body)
       .send()
       .await?;
@@ -240,6 +271,57 @@ impl Client {
 
     process_response_data::<UserImportTask>(resp).await
   }
+
+  /// Creates a task that exports the workspace into a zip archive. The archive is built in the
+  /// background, and the user receives an email with the download link once it is ready. The
+  /// progress can be followed with [Self::get_export_list].
+  pub async fn create_export(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<CreateExportTaskResponse, AppResponseError> {
+    let url = format!("{}/api/export/{}", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .header("X-Host", self.base_url.clone())
+      .send()
+      .await?;
+
+    process_response_data::<CreateExportTaskResponse>(resp).await
+  }
+
+  pub async fn get_export_list(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<UserExportTask, AppResponseError> {
+    let url = format!("{}/api/export/{}", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+
+    process_response_data::<UserExportTask>(resp).await
+  }
+
+  /// Returns a short-lived url to download the archive of a completed export task.
+  pub async fn get_export_download_url(
+    &self,
+    workspace_id: &Uuid,
+    task_id: &Uuid,
+  ) -> Result<ExportDownloadUrl, AppResponseError> {
+    let url = format!(
+      "{}/api/export/{}/{}/download",
+      self.base_url, workspace_id, task_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+
+    process_response_data::<ExportDownloadUrl>(resp).await
+  }
 }
 
 #[async_trait]
@@ -1,6 +1,10 @@
 use crate::{process_response_data, process_response_error, Client};
 use bytes::Bytes;
-use client_api_entity::publish_dto::DuplicatePublishedPageResponse;
+use client_api_entity::publish_dto::{
+  CreatePublishCustomDomainParams, DuplicatePublishedPageResponse, PublishCustomDomain,
+  PublishedAnalytics, PublishedAnalyticsQuery, PublishedViewAccessToken, UnlockPublishedViewParams,
+  PUBLISH_ACCESS_TOKEN_HEADER,
+};
 use client_api_entity::workspace_dto::{PublishInfoView, PublishedView};
 use client_api_entity::{workspace_dto::PublishedDuplicate, PublishInfo, UpdatePublishNamespace};
 use client_api_entity::{
@@ -33,6 +37,28 @@ impl Client {
     process_response_data::<Vec<PublishInfoView>>(resp).await
   }
 
+  /// Daily views, unique visitors and referrers of the pages published by the workspace.
+  /// Only available to the workspace owner.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_analytics(
+    &self,
+    workspace_id: &Uuid,
+    query: &PublishedAnalyticsQuery,
+  ) -> Result<PublishedAnalytics, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/published-analytics",
+      self.base_url, workspace_id,
+    );
+
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send()
+      .await?;
+    process_response_data::<PublishedAnalytics>(resp).await
+  }
+
   /// Changes the namespace for the first non-original publish namespace
   /// or the original publish namespace if not exists.
   pub async fn set_workspace_publish_namespace(
@@ -75,6 +101,78 @@ impl Client {
     process_response_data::<String>(resp).await
   }
 
+  pub async fn list_publish_custom_domains(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<Vec<PublishCustomDomain>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-domain",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<Vec<PublishCustomDomain>>(resp).await
+  }
+
+  /// Claims a custom domain for the published pages of the workspace. The returned TXT record
+  /// must be added to the DNS zone of the domain before calling [Self::verify_publish_custom_domain].
+  pub async fn create_publish_custom_domain(
+    &self,
+    workspace_id: &Uuid,
+    domain: &str,
+  ) -> Result<PublishCustomDomain, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-domain",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&CreatePublishCustomDomainParams {
+        domain: domain.to_string(),
+      })
+      .send()
+      .await?;
+    process_response_data::<PublishCustomDomain>(resp).await
+  }
+
+  pub async fn verify_publish_custom_domain(
+    &self,
+    workspace_id: &Uuid,
+    domain: &str,
+  ) -> Result<PublishCustomDomain, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-domain/{}/verify",
+      self.base_url, workspace_id, domain
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<PublishCustomDomain>(resp).await
+  }
+
+  pub async fn delete_publish_custom_domain(
+    &self,
+    workspace_id: &Uuid,
+    domain: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-domain/{}",
+      self.base_url, workspace_id, domain
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
   pub async fn patch_published_collabs(
     &self,
     workspace_id: &Uuid,
@@ -328,6 +426,23 @@ impl Client {
     publish_namespace: &str,
     publish_name: &str,
   ) -> Result<T, AppResponseError>
+  where
+    T: serde::de::DeserializeOwned + 'static,
+  {
+    self
+      .get_published_collab_with_access_token(publish_namespace, publish_name, None)
+      .await
+  }
+
+  /// Same as [Client::get_published_collab], for pages unlocked with
+  /// [Client::unlock_published_collab].
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_collab_with_access_token<T>(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    access_token: Option<&str>,
+  ) -> Result<T, AppResponseError>
   where
     T: serde::de::DeserializeOwned + 'static,
   {
@@ -341,12 +456,11 @@ impl Client {
       self.base_url, publish_namespace, publish_name
     );
 
-    let resp = self
-      .cloud_client
-      .get(&url)
-      .send()
-      .await?
-      .error_for_status()?;
+    let mut builder = self.cloud_client.get(&url);
+    if let Some(access_token) = access_token {
+      builder = builder.header(PUBLISH_ACCESS_TOKEN_HEADER, access_token);
+    }
+    let resp = builder.send().await?.error_for_status()?;
 
     process_response_data::<T>(resp).await
   }
@@ -356,6 +470,20 @@ impl Client {
     &self,
     publish_namespace: &str,
     publish_name: &str,
+  ) -> Result<Bytes, AppResponseError> {
+    self
+      .get_published_collab_blob_with_access_token(publish_namespace, publish_name, None)
+      .await
+  }
+
+  /// Same as [Client::get_published_collab_blob], for pages unlocked with
+  /// [Client::unlock_published_collab].
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_collab_blob_with_access_token(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    access_token: Option<&str>,
   ) -> Result<Bytes, AppResponseError> {
     tracing::debug!(
       "get_published_collab_blob: {} {}",
@@ -366,7 +494,11 @@ impl Client {
       "{}/api/workspace/published/{}/{}/blob",
       self.base_url, publish_namespace, publish_name
     );
-    let resp = self.cloud_client.get(&url).send().await?;
+    let mut builder = self.cloud_client.get(&url);
+    if let Some(access_token) = access_token {
+      builder = builder.header(PUBLISH_ACCESS_TOKEN_HEADER, access_token);
+    }
+    let resp = builder.send().await?;
     let bytes = resp.error_for_status()?.bytes().await?;
 
     if let Ok(app_err) = serde_json::from_slice::<AppResponseError>(&bytes) {
@@ -376,21 +508,80 @@ impl Client {
     Ok(bytes)
   }
 
+  /// Returns the published page rendered to static HTML.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_collab_html(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+  ) -> Result<String, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/{}/{}/html",
+      self.base_url, publish_namespace, publish_name
+    );
+    let resp = self.cloud_client.get(&url).send().await?;
+    let html = resp.error_for_status()?.text().await?;
+
+    if let Ok(app_err) = serde_json::from_str::<AppResponseError>(&html) {
+      return Err(app_err);
+    }
+
+    Ok(html)
+  }
+
+  /// Exchanges the password of a protected published page for an access token.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn unlock_published_collab(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    password: &str,
+  ) -> Result<PublishedViewAccessToken, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/{}/{}/unlock",
+      self.base_url, publish_namespace, publish_name
+    );
+    let resp = self
+      .cloud_client
+      .post(&url)
+      .json(&UnlockPublishedViewParams {
+        password: password.to_string(),
+      })
+      .send()
+      .await?;
+    process_response_data::<PublishedViewAccessToken>(resp).await
+  }
+
   pub async fn duplicate_published_to_workspace(
     &self,
     workspace_id: Uuid,
     publish_duplicate: &PublishedDuplicate,
+  ) -> Result<DuplicatePublishedPageResponse, AppResponseError> {
+    self
+      .duplicate_published_to_workspace_with_access_token(workspace_id, publish_duplicate, None)
+      .await
+  }
+
+  /// Same as [Client::duplicate_published_to_workspace], for pages unlocked with
+  /// [Client::unlock_published_collab].
+  pub async fn duplicate_published_to_workspace_with_access_token(
+    &self,
+    workspace_id: Uuid,
+    publish_duplicate: &PublishedDuplicate,
+    access_token: Option<&str>,
   ) -> Result<DuplicatePublishedPageResponse, AppResponseError> {
     let url = format!(
       "{}/api/workspace/{}/published-duplicate",
       self.base_url, workspace_id
     );
-    let resp = self
+    let mut builder = self
       .http_client_with_auth(Method::POST, &url)
       .await?
-      .json(publish_duplicate)
-      .send()
-      .await?;
+      .json(publish_duplicate);
+    if let Some(access_token) = access_token {
+      builder = builder.header(PUBLISH_ACCESS_TOKEN_HEADER, access_token);
+    }
+    let resp = builder.send().await?;
     process_response_data::<DuplicatePublishedPageResponse>(resp).await
   }
 
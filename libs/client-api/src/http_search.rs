@@ -1,7 +1,8 @@
 use app_error::ErrorCode;
 use reqwest::Method;
 use shared_entity::dto::search_dto::{
-  SearchDocumentResponseItem, SearchResult, SearchSummaryResult, SummarySearchResultRequest,
+  EmbeddingMigrationResult, EmbeddingStatus, SearchDocumentResponseItem, SearchMode, SearchResult,
+  SearchSummaryResult, SummarySearchResultRequest,
 };
 use shared_entity::response::AppResponseError;
 use uuid::Uuid;
@@ -19,7 +20,30 @@ impl Client {
     preview_size: u32,
     score: T,
   ) -> Result<Vec<SearchDocumentResponseItem>, AppResponseError> {
-    let mut raw_query = Vec::with_capacity(4);
+    self
+      .search_documents_with_mode(
+        workspace_id,
+        query,
+        limit,
+        preview_size,
+        score,
+        SearchMode::Vector,
+      )
+      .await
+  }
+
+  /// Same as [Client::search_documents], but allows to choose how the documents are ranked.
+  /// `score` only applies to the vector search results.
+  pub async fn search_documents_with_mode<T: Into<Option<f32>>>(
+    &self,
+    workspace_id: &Uuid,
+    query: &str,
+    limit: u32,
+    preview_size: u32,
+    score: T,
+    mode: SearchMode,
+  ) -> Result<Vec<SearchDocumentResponseItem>, AppResponseError> {
+    let mut raw_query = Vec::with_capacity(5);
     raw_query.push(("query", query.to_string()));
     raw_query.push(("limit", limit.to_string()));
     raw_query.push(("preview_size", preview_size.to_string()));
@@ -28,6 +52,13 @@ impl Client {
       raw_query.push(("score", score_limit.to_string()));
     }
 
+    let mode = match mode {
+      SearchMode::Vector => "vector",
+      SearchMode::Keyword => "keyword",
+      SearchMode::Hybrid => "hybrid",
+    };
+    raw_query.push(("mode", mode.to_string()));
+
     let query = serde_urlencoded::to_string(raw_query)
       .map_err(|err| AppResponseError::new(ErrorCode::InvalidRequest, err.to_string()))?;
 
@@ -62,4 +93,37 @@ impl Client {
       .await?;
     process_response_data::<SearchSummaryResult>(resp).await
   }
+
+  /// Returns how many documents of the workspace are embedded with the model configured on the
+  /// server, and how many still use another model. Only the workspace owner can call it.
+  pub async fn get_embedding_status(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<EmbeddingStatus, AppResponseError> {
+    let url = format!("{}/api/search/{workspace_id}/embedding", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<EmbeddingStatus>(resp).await
+  }
+
+  /// Queues the documents embedded with another model to be embedded again with the configured
+  /// one. Only the workspace owner can call it.
+  pub async fn migrate_embeddings(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<EmbeddingMigrationResult, AppResponseError> {
+    let url = format!(
+      "{}/api/search/{workspace_id}/embedding/migrate",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<EmbeddingMigrationResult>(resp).await
+  }
 }
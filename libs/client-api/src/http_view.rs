@@ -1,3 +1,7 @@
+use client_api_entity::comment_dto::{
+  CreateViewCommentParams, CreateViewCommentThreadParams, QueryViewCommentThreadParams,
+  UpdateViewCommentParams, ViewCommentEdit, ViewCommentThread,
+};
 use client_api_entity::workspace_dto::{
   AddRecentPagesParams, AppendBlockToPageParams, CreateFolderViewParams,
   CreatePageDatabaseViewParams, CreatePageParams, CreateSpaceParams, DuplicatePageParams,
@@ -427,4 +431,154 @@ impl Client {
       .await?;
     process_response_error(resp).await
   }
+
+  pub async fn list_view_comment_threads(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+    params: &QueryViewCommentThreadParams,
+  ) -> Result<Vec<ViewCommentThread>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/comment-thread",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(params)
+      .send()
+      .await?;
+    process_response_data::<Vec<ViewCommentThread>>(resp).await
+  }
+
+  pub async fn create_view_comment_thread(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+    params: &CreateViewCommentThreadParams,
+  ) -> Result<ViewCommentThread, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/comment-thread",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<ViewCommentThread>(resp).await
+  }
+
+  pub async fn reply_to_view_comment_thread(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+    thread_id: &Uuid,
+    params: &CreateViewCommentParams,
+  ) -> Result<ViewCommentThread, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/comment-thread/{}/comment",
+      self.base_url, workspace_id, view_id, thread_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<ViewCommentThread>(resp).await
+  }
+
+  pub async fn resolve_view_comment_thread(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+    thread_id: &Uuid,
+  ) -> Result<ViewCommentThread, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/comment-thread/{}/resolve",
+      self.base_url, workspace_id, view_id, thread_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<ViewCommentThread>(resp).await
+  }
+
+  pub async fn reopen_view_comment_thread(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+    thread_id: &Uuid,
+  ) -> Result<ViewCommentThread, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/comment-thread/{}/reopen",
+      self.base_url, workspace_id, view_id, thread_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<ViewCommentThread>(resp).await
+  }
+
+  pub async fn update_view_comment(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+    comment_id: &Uuid,
+    params: &UpdateViewCommentParams,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/comment/{}",
+      self.base_url, workspace_id, view_id, comment_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PATCH, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  pub async fn delete_view_comment(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+    comment_id: &Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/comment/{}",
+      self.base_url, workspace_id, view_id, comment_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  pub async fn list_view_comment_edits(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+    comment_id: &Uuid,
+  ) -> Result<Vec<ViewCommentEdit>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/comment/{}/history",
+      self.base_url, workspace_id, view_id, comment_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<Vec<ViewCommentEdit>>(resp).await
+  }
 }
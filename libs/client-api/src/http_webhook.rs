@@ -0,0 +1,97 @@
+use client_api_entity::webhook_dto::{
+  AFWebhook, AFWebhookDelivery, CreateWebhookParams, QueryWebhookDeliveryParams,
+  UpdateWebhookParams,
+};
+use reqwest::Method;
+use shared_entity::response::AppResponseError;
+use uuid::Uuid;
+
+use crate::{process_response_data, process_response_error, Client};
+
+impl Client {
+  /// The returned webhook contains the secret used to sign the deliveries. It is not returned
+  /// again afterwards.
+  pub async fn create_webhook(
+    &self,
+    workspace_id: &Uuid,
+    params: &CreateWebhookParams,
+  ) -> Result<AFWebhook, AppResponseError> {
+    let url = format!("{}/api/webhook/{}", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<AFWebhook>(resp).await
+  }
+
+  pub async fn list_webhooks(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<Vec<AFWebhook>, AppResponseError> {
+    let url = format!("{}/api/webhook/{}", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<Vec<AFWebhook>>(resp).await
+  }
+
+  pub async fn update_webhook(
+    &self,
+    workspace_id: &Uuid,
+    webhook_id: &Uuid,
+    params: &UpdateWebhookParams,
+  ) -> Result<AFWebhook, AppResponseError> {
+    let url = format!(
+      "{}/api/webhook/{}/{}",
+      self.base_url, workspace_id, webhook_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PATCH, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<AFWebhook>(resp).await
+  }
+
+  pub async fn delete_webhook(
+    &self,
+    workspace_id: &Uuid,
+    webhook_id: &Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/webhook/{}/{}",
+      self.base_url, workspace_id, webhook_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  /// Returns the deliveries of the webhook, newest first.
+  pub async fn list_webhook_deliveries(
+    &self,
+    workspace_id: &Uuid,
+    webhook_id: &Uuid,
+    params: &QueryWebhookDeliveryParams,
+  ) -> Result<Vec<AFWebhookDelivery>, AppResponseError> {
+    let url = format!(
+      "{}/api/webhook/{}/{}/delivery",
+      self.base_url, workspace_id, webhook_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(params)
+      .send()
+      .await?;
+    process_response_data::<Vec<AFWebhookDelivery>>(resp).await
+  }
+}
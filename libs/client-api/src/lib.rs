@@ -3,6 +3,8 @@ mod http_ai;
 mod http_billing;
 
 mod http_access_request;
+mod http_api_token;
+mod http_audit;
 mod http_blob;
 mod http_collab;
 mod http_guest;
@@ -13,6 +15,7 @@ mod http_quick_note;
 mod http_search;
 mod http_template;
 mod http_view;
+mod http_webhook;
 pub use http::*;
 
 pub mod collab_sync;
@@ -37,7 +40,7 @@ pub mod error {
 pub mod entity {
   #[cfg(not(target_arch = "wasm32"))]
   pub use crate::http_chat::*;
-  pub use appflowy_proto::WorkspaceNotification;
+  pub use appflowy_proto::{SessionPresence, WorkspaceNotification, WorkspacePresence};
   pub use client_api_entity::*;
 }
 
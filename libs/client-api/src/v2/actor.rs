@@ -6,6 +6,7 @@ use crate::{sync_debug, sync_error, sync_info, sync_trace, sync_warn};
 use app_error::AppError;
 use appflowy_proto::{
   AccessChangedReason, ClientMessage, Rid, ServerMessage, UpdateFlags, WorkspaceNotification,
+  WorkspacePresence,
 };
 use arc_swap::ArcSwap;
 use bytes::BytesMut;
@@ -51,6 +52,9 @@ pub(super) struct WorkspaceControllerActor {
   /// Persistent database handle.
   db: Db,
   notification_tx: tokio::sync::broadcast::Sender<WorkspaceNotification>,
+  presence_tx: tokio::sync::watch::Sender<WorkspacePresence>,
+  /// Object focused by the user, shared with the server as part of the workspace presence.
+  focused_object: ArcSwap<Option<ObjectId>>,
   /// Used to record recently changed collabs
   changed_collab_sender: tokio::sync::broadcast::Sender<ChangedCollab>,
   #[cfg(debug_assertions)]
@@ -67,6 +71,7 @@ impl WorkspaceControllerActor {
     let (status_tx, status_rx) = tokio::sync::watch::channel(ConnectionStatus::default());
     let (message_tx, message_rx) = tokio::sync::mpsc::unbounded_channel();
     let (notification_tx, _) = tokio::sync::broadcast::channel(100);
+    let (presence_tx, _) = tokio::sync::watch::channel(WorkspacePresence::default());
     let actor = Arc::new(WorkspaceControllerActor {
       options,
       status_rx,
@@ -78,6 +83,8 @@ impl WorkspaceControllerActor {
       #[cfg(debug_assertions)]
       skip_realtime_message: AtomicBool::new(false),
       notification_tx,
+      presence_tx,
+      focused_object: ArcSwap::new(Arc::new(None)),
       changed_collab_sender,
     });
     tokio::spawn(Self::actor_loop(
@@ -95,6 +102,20 @@ impl WorkspaceControllerActor {
     self.notification_tx.subscribe()
   }
 
+  pub fn subscribe_presence(&self) -> tokio::sync::watch::Receiver<WorkspacePresence> {
+    self.presence_tx.subscribe()
+  }
+
+  pub fn set_focused_object(&self, object_id: Option<ObjectId>) {
+    let previous = self.focused_object.swap(Arc::new(object_id));
+    if *previous != object_id {
+      self.trigger(WorkspaceAction::Send(
+        ClientMessage::FocusChanged { object_id },
+        ActionSource::Local,
+      ));
+    }
+  }
+
   pub fn client_id(&self) -> ClientID {
     self.db.client_id()
   }
@@ -251,6 +272,10 @@ impl WorkspaceControllerActor {
   #[instrument(level = "trace", skip_all)]
   pub(crate) fn set_connection_status(&self, status: ConnectionStatus) {
     sync_info!("set connection status: {:?}", status);
+    if matches!(status, ConnectionStatus::Disconnected { .. }) {
+      // presence is only known while connected
+      self.presence_tx.send_replace(WorkspacePresence::default());
+    }
     self.status_tx.send_replace(status);
   }
 
@@ -413,6 +438,9 @@ impl WorkspaceControllerActor {
       },
       ClientMessage::Update { object_id, .. } => Some((*object_id, SyncState::SyncFinished)),
       ClientMessage::AwarenessUpdate { .. } => None,
+      ClientMessage::FocusChanged { .. }
+      | ClientMessage::Subscribe { .. }
+      | ClientMessage::Unsubscribe { .. } => None,
     };
     if let Some(sink) = self.ws_sink() {
       sync_debug!("[{}] sending message: {:?}", self.db.client_id(), msg);
@@ -514,6 +542,15 @@ impl WorkspaceControllerActor {
         if let Err(err) = actor.publish_pending_collabs().await {
           sync_error!("failed to publish pending collabs: {}", err);
         }
+        // the server doesn't know the focus of a new session
+        if let Some(object_id) = **actor.focused_object.load() {
+          actor.trigger(WorkspaceAction::Send(
+            ClientMessage::FocusChanged {
+              object_id: Some(object_id),
+            },
+            ActionSource::Local,
+          ));
+        }
         tokio::spawn(Self::remote_receiver_task(
           Arc::downgrade(actor),
           stream,
@@ -710,19 +747,20 @@ impl WorkspaceControllerActor {
         sync_info!("received notification: {:?}", notification);
         self.send_notification(notification).await;
       },
+      ServerMessage::Presence { presence } => {
+        sync_trace!("received presence: {:?}", presence);
+        self.presence_tx.send_replace(presence);
+      },
     }
     Ok(())
   }
 
   async fn send_notification(&self, notification: WorkspaceNotification) {
     sync_trace!("Receive server notification: {:?}", notification);
-    match &notification {
-      WorkspaceNotification::UserProfileChange { .. } => {},
-      WorkspaceNotification::ObjectAccessChanged { object_id, reason } => {
-        if matches!(reason, AccessChangedReason::ObjectDeleted) {
-          self.unbind(object_id).await;
-        }
-      },
+    if let WorkspaceNotification::ObjectAccessChanged { object_id, reason } = &notification {
+      if matches!(reason, AccessChangedReason::ObjectDeleted) {
+        self.unbind(object_id).await;
+      }
     }
 
     if let Err(err) = self.notification_tx.send(notification) {
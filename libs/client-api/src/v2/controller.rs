@@ -5,7 +5,7 @@ use crate::sync_trace;
 use crate::v2::actor::{WorkspaceAction, WorkspaceControllerActor, WsConn};
 use crate::v2::conn_retry::{ReconnectTarget, ReconnectionManager};
 use app_error::ErrorCode;
-use appflowy_proto::{Rid, WorkspaceNotification};
+use appflowy_proto::{Rid, WorkspaceNotification, WorkspacePresence};
 use async_trait::async_trait;
 use collab::preclude::Collab;
 use collab_rt_protocol::CollabRef;
@@ -86,6 +86,18 @@ impl WorkspaceController {
     self.actor.subscribe_notification()
   }
 
+  /// Returns the sessions connected to the workspace, with the object each of them is looking at.
+  /// The presence is empty while disconnected.
+  pub fn subscribe_presence(&self) -> tokio::sync::watch::Receiver<WorkspacePresence> {
+    self.actor.subscribe_presence()
+  }
+
+  /// Shares the object the user is currently looking at with the other members of the workspace.
+  /// Pass `None` when no object is open anymore.
+  pub fn set_focused_object(&self, object_id: Option<ObjectId>) {
+    self.actor.set_focused_object(object_id)
+  }
+
   pub async fn connect(&self, access_token: String) -> anyhow::Result<()> {
     if access_token.is_empty() {
       return Err(anyhow::anyhow!("access token is empty"));
@@ -484,6 +484,12 @@ pub struct PublishInfo {
   pub comments_enabled: bool,
   #[serde(default = "default_duplicate_enabled")]
   pub duplicate_enabled: bool,
+  /// Whether a password is required to view the published page.
+  #[serde(default)]
+  pub password_protected: bool,
+  /// After this time the published page is no longer accessible.
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
 }
 
 fn default_comments_enabled() -> bool {
@@ -638,6 +644,31 @@ impl From<&str> for AFAccessLevel {
   }
 }
 
+/// The permissions granted by a personal API token in the workspaces it is scoped to. A token
+/// never grants more than the role of the user that created it.
+#[derive(Deserialize_repr, Serialize_repr, Eq, PartialEq, PartialOrd, Ord, Debug, Clone, Copy)]
+#[repr(i32)]
+pub enum AFApiTokenScope {
+  // Can't modify the value of the enum
+  Read = 10,
+  Write = 20,
+  Admin = 30,
+}
+
+impl From<i32> for AFApiTokenScope {
+  fn from(value: i32) -> Self {
+    match value {
+      10 => AFApiTokenScope::Read,
+      20 => AFApiTokenScope::Write,
+      30 => AFApiTokenScope::Admin,
+      _ => {
+        error!("Invalid api token scope: {}", value);
+        AFApiTokenScope::Read
+      },
+    }
+  }
+}
+
 impl From<AFAccessLevel> for i32 {
   fn from(level: AFAccessLevel) -> Self {
     level as i32
@@ -875,6 +906,9 @@ pub struct PublishCollabItem<Meta, Data> {
   pub data: Data,
   pub comments_enabled: bool,
   pub duplicate_enabled: bool,
+  /// Plain text password required to view the published page. Stored hashed.
+  pub password: Option<String>,
+  pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -883,6 +917,14 @@ pub struct PatchPublishedCollab {
   pub publish_name: Option<String>,
   pub comments_enabled: Option<bool>,
   pub duplicate_enabled: Option<bool>,
+  /// Sets the password of the published page. An empty string removes the password.
+  #[serde(default)]
+  pub password: Option<String>,
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
+  /// Removes the expiry of the published page. Takes precedence over `expires_at`.
+  #[serde(default)]
+  pub clear_expires_at: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -1251,6 +1293,51 @@ pub struct CreateImportTask {
   #[validate(custom(function = "validate_not_empty_str"))]
   pub workspace_name: String,
   pub content_length: u64,
+  #[serde(default)]
+  pub source: ImportSource,
+}
+
+/// The kind of file that is imported into a new workspace.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+  /// A zip file exported from Notion.
+  #[default]
+  Notion,
+  /// A zip file of Markdown files. The folder structure becomes the view hierarchy.
+  Markdown,
+  /// A single CSV file that becomes a grid database.
+  Csv,
+}
+
+impl ImportSource {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ImportSource::Notion => "notion",
+      ImportSource::Markdown => "markdown",
+      ImportSource::Csv => "csv",
+    }
+  }
+
+  pub fn content_type(&self) -> &'static str {
+    match self {
+      ImportSource::Notion | ImportSource::Markdown => "application/zip",
+      ImportSource::Csv => "text/csv",
+    }
+  }
+}
+
+impl FromStr for ImportSource {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "notion" => Ok(ImportSource::Notion),
+      "markdown" => Ok(ImportSource::Markdown),
+      "csv" => Ok(ImportSource::Csv),
+      _ => Err(format!("Unknown import source: {}", s)),
+    }
+  }
 }
 
 /// Create a import task
@@ -0,0 +1,95 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::pg_row::{AFApiTokenOwnerRow, AFApiTokenRow};
+
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_api_token(
+  pg_pool: &PgPool,
+  uid: i64,
+  name: &str,
+  token_hash: &str,
+  token_prefix: &str,
+  workspace_ids: &[Uuid],
+  scope: i32,
+  expires_at: Option<DateTime<Utc>>,
+) -> Result<AFApiTokenRow, AppError> {
+  let token = sqlx::query_as::<_, AFApiTokenRow>(
+    r#"
+      INSERT INTO af_api_token (uid, name, token_hash, token_prefix, workspace_ids, scope, expires_at)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+      RETURNING token_id, uid, name, token_prefix, workspace_ids, scope, created_at, expires_at, last_used_at
+    "#,
+  )
+  .bind(uid)
+  .bind(name)
+  .bind(token_hash)
+  .bind(token_prefix)
+  .bind(workspace_ids)
+  .bind(scope)
+  .bind(expires_at)
+  .fetch_one(pg_pool)
+  .await?;
+  Ok(token)
+}
+
+pub async fn select_api_tokens(pg_pool: &PgPool, uid: i64) -> Result<Vec<AFApiTokenRow>, AppError> {
+  let tokens = sqlx::query_as::<_, AFApiTokenRow>(
+    r#"
+      SELECT token_id, uid, name, token_prefix, workspace_ids, scope, created_at, expires_at, last_used_at
+      FROM af_api_token
+      WHERE uid = $1
+      ORDER BY created_at DESC
+    "#,
+  )
+  .bind(uid)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(tokens)
+}
+
+pub async fn delete_api_token(pg_pool: &PgPool, uid: i64, token_id: &Uuid) -> Result<(), AppError> {
+  let res = sqlx::query("DELETE FROM af_api_token WHERE uid = $1 AND token_id = $2")
+    .bind(uid)
+    .bind(token_id)
+    .execute(pg_pool)
+    .await?;
+  if res.rows_affected() == 0 {
+    return Err(AppError::RecordNotFound(format!(
+      "api token {} not found",
+      token_id
+    )));
+  }
+  Ok(())
+}
+
+/// Returns the owner of the token with the given hash, if the token exists and has not expired.
+/// The last used timestamp of the token is updated at most once per minute.
+pub async fn select_api_token_owner_and_touch(
+  pg_pool: &PgPool,
+  token_hash: &str,
+) -> Result<Option<AFApiTokenOwnerRow>, AppError> {
+  let owner = sqlx::query_as::<_, AFApiTokenOwnerRow>(
+    r#"
+      WITH token AS (
+        SELECT t.token_id, t.uid, u.uuid, u.email, t.workspace_ids, t.scope, t.expires_at
+        FROM af_api_token AS t
+        JOIN af_user AS u ON t.uid = u.uid
+        WHERE t.token_hash = $1
+          AND (t.expires_at IS NULL OR t.expires_at > NOW())
+      ), touched AS (
+        UPDATE af_api_token
+        SET last_used_at = NOW()
+        WHERE token_id IN (SELECT token_id FROM token)
+          AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')
+      )
+      SELECT * FROM token
+    "#,
+  )
+  .bind(token_hash)
+  .fetch_optional(pg_pool)
+  .await?;
+  Ok(owner)
+}
@@ -0,0 +1,88 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFAuditLogRow;
+
+pub struct AuditLogInsert<'a> {
+  pub workspace_id: &'a Uuid,
+  pub actor_uid: Option<i64>,
+  pub action: &'a str,
+  pub target: Option<&'a str>,
+  pub ip_address: Option<&'a str>,
+  pub user_agent: Option<&'a str>,
+  pub before_value: Option<&'a serde_json::Value>,
+  pub after_value: Option<&'a serde_json::Value>,
+}
+
+pub async fn insert_audit_log<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  entry: AuditLogInsert<'_>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_workspace_audit_log
+        (workspace_id, actor_uid, action, target, ip_address, user_agent, before_value, after_value)
+      VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+    "#,
+  )
+  .bind(entry.workspace_id)
+  .bind(entry.actor_uid)
+  .bind(entry.action)
+  .bind(entry.target)
+  .bind(entry.ip_address)
+  .bind(entry.user_agent)
+  .bind(entry.before_value)
+  .bind(entry.after_value)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub struct AuditLogFilter<'a> {
+  pub action: Option<&'a str>,
+  pub actor_uid: Option<i64>,
+  pub target: Option<&'a str>,
+  pub from: Option<DateTime<Utc>>,
+  pub to: Option<DateTime<Utc>>,
+}
+
+/// Returns the matching entries of the workspace audit log, most recent first.
+pub async fn select_audit_logs(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  filter: &AuditLogFilter<'_>,
+  limit: i64,
+  offset: i64,
+) -> Result<Vec<AFAuditLogRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFAuditLogRow>(
+    r#"
+      SELECT
+        log.event_id, log.workspace_id, log.actor_uid, u.email AS actor_email, log.action,
+        log.target, log.ip_address, log.user_agent, log.before_value, log.after_value,
+        log.created_at
+      FROM af_workspace_audit_log log
+      LEFT JOIN af_user u ON u.uid = log.actor_uid
+      WHERE log.workspace_id = $1
+        AND ($2::TEXT IS NULL OR log.action = $2)
+        AND ($3::BIGINT IS NULL OR log.actor_uid = $3)
+        AND ($4::TEXT IS NULL OR log.target = $4)
+        AND ($5::TIMESTAMPTZ IS NULL OR log.created_at >= $5)
+        AND ($6::TIMESTAMPTZ IS NULL OR log.created_at < $6)
+      ORDER BY log.created_at DESC, log.event_id DESC
+      LIMIT $7 OFFSET $8
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(filter.action)
+  .bind(filter.actor_uid)
+  .bind(filter.target)
+  .bind(filter.from)
+  .bind(filter.to)
+  .bind(limit)
+  .bind(offset)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
@@ -0,0 +1,102 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFCollabUpdateAuthorRow;
+
+/// Clocks [clock_start, clock_end) of a Yjs client written by a user, last updated at `updated_at`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollabUpdateAuthorRange {
+  pub oid: Uuid,
+  pub client_id: i64,
+  pub clock_start: i64,
+  pub clock_end: i64,
+  pub uid: i64,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// Attributes the ranges to their users. A Yjs client belongs to the first user it was attributed
+/// to: ranges of the same client sent by another user are ignored, as the client id is chosen by
+/// the client itself.
+pub async fn insert_collab_update_authors<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  ranges: &[CollabUpdateAuthorRange],
+) -> Result<(), AppError> {
+  if ranges.is_empty() {
+    return Ok(());
+  }
+  let mut oids = Vec::with_capacity(ranges.len());
+  let mut client_ids = Vec::with_capacity(ranges.len());
+  let mut clock_starts = Vec::with_capacity(ranges.len());
+  let mut clock_ends = Vec::with_capacity(ranges.len());
+  let mut uids = Vec::with_capacity(ranges.len());
+  let mut updated_ats = Vec::with_capacity(ranges.len());
+  for range in ranges {
+    oids.push(range.oid);
+    client_ids.push(range.client_id);
+    clock_starts.push(range.clock_start);
+    clock_ends.push(range.clock_end);
+    uids.push(range.uid);
+    updated_ats.push(range.updated_at);
+  }
+  sqlx::query(
+    r#"
+      INSERT INTO af_collab_update_author
+        (workspace_id, oid, client_id, clock_start, clock_end, uid, updated_at)
+      SELECT $1, r.oid, r.client_id, r.clock_start, r.clock_end, r.uid, r.updated_at
+      FROM UNNEST(
+        $2::uuid[], $3::bigint[], $4::bigint[], $5::bigint[], $6::bigint[], $7::timestamptz[]
+      ) AS r(oid, client_id, clock_start, clock_end, uid, updated_at)
+      WHERE NOT EXISTS (
+        SELECT 1
+        FROM af_collab_update_author AS a
+        WHERE a.oid = r.oid
+          AND a.client_id = r.client_id
+          AND a.uid <> r.uid
+      )
+      ON CONFLICT (oid, client_id, clock_start) DO NOTHING
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(&oids)
+  .bind(&client_ids)
+  .bind(&clock_starts)
+  .bind(&clock_ends)
+  .bind(&uids)
+  .bind(&updated_ats)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn delete_collab_update_authors<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query("DELETE FROM af_collab_update_author WHERE oid = $1")
+    .bind(oid)
+    .execute(executor)
+    .await?;
+  Ok(())
+}
+
+/// Returns all attributed ranges of the collab, ordered by client and clock.
+pub async fn select_collab_update_authors(
+  pg_pool: &PgPool,
+  oid: &Uuid,
+) -> Result<Vec<AFCollabUpdateAuthorRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFCollabUpdateAuthorRow>(
+    r#"
+      SELECT client_id, clock_start, clock_end, uid, updated_at
+      FROM af_collab_update_author
+      WHERE oid = $1
+      ORDER BY client_id, clock_start
+    "#,
+  )
+  .bind(oid)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
@@ -0,0 +1,266 @@
+use app_error::AppError;
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{AFViewCommentEditRow, AFViewCommentRow, AFViewCommentThreadRow};
+
+const SELECT_VIEW_COMMENT_THREAD: &str = r#"
+  SELECT
+    t.thread_id,
+    t.view_id,
+    t.block_id,
+    t.created_at,
+    resolver.uuid AS resolved_by,
+    t.resolved_at
+  FROM af_view_comment_thread AS t
+  LEFT JOIN af_user AS resolver ON t.resolved_by = resolver.uid
+"#;
+
+const SELECT_VIEW_COMMENT: &str = r#"
+  SELECT
+    c.comment_id,
+    c.thread_id,
+    c.content,
+    c.mentions,
+    c.created_by,
+    author.uuid AS author_uuid,
+    author.name AS author_name,
+    author.metadata ->> 'icon_url' AS author_avatar_url,
+    c.created_at,
+    c.updated_at,
+    c.is_deleted,
+    (SELECT COUNT(*) FROM af_view_comment_edit AS e WHERE e.comment_id = c.comment_id) AS edit_count
+  FROM af_view_comment AS c
+  JOIN af_view_comment_thread AS t ON c.thread_id = t.thread_id
+  LEFT JOIN af_user AS author ON c.created_by = author.uid
+"#;
+
+pub async fn insert_view_comment_thread<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  block_id: Option<&str>,
+  uid: i64,
+) -> Result<Uuid, AppError> {
+  let thread_id = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      INSERT INTO af_view_comment_thread (workspace_id, view_id, block_id, created_by)
+      VALUES ($1, $2, $3, $4)
+      RETURNING thread_id
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(block_id)
+  .bind(uid)
+  .fetch_one(executor)
+  .await?;
+  Ok(thread_id)
+}
+
+pub async fn insert_view_comment<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  thread_id: &Uuid,
+  uid: i64,
+  content: &str,
+  mentions: &[Uuid],
+) -> Result<Uuid, AppError> {
+  let comment_id = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      INSERT INTO af_view_comment (thread_id, created_by, content, mentions)
+      VALUES ($1, $2, $3, $4)
+      RETURNING comment_id
+    "#,
+  )
+  .bind(thread_id)
+  .bind(uid)
+  .bind(content)
+  .bind(mentions)
+  .fetch_one(executor)
+  .await?;
+  Ok(comment_id)
+}
+
+/// Returns the comment threads of a view, oldest first. If `block_id` is given, only the threads
+/// anchored to that block are returned.
+pub async fn select_view_comment_threads(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  block_id: Option<&str>,
+  include_resolved: bool,
+) -> Result<Vec<AFViewCommentThreadRow>, AppError> {
+  let query = format!(
+    r#"
+      {}
+      WHERE t.workspace_id = $1
+        AND t.view_id = $2
+        AND ($3::TEXT IS NULL OR t.block_id = $3)
+        AND ($4 OR t.resolved_at IS NULL)
+      ORDER BY t.created_at
+    "#,
+    SELECT_VIEW_COMMENT_THREAD
+  );
+  let threads = sqlx::query_as::<_, AFViewCommentThreadRow>(&query)
+    .bind(workspace_id)
+    .bind(view_id)
+    .bind(block_id)
+    .bind(include_resolved)
+    .fetch_all(pg_pool)
+    .await?;
+  Ok(threads)
+}
+
+pub async fn select_view_comment_thread(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  thread_id: &Uuid,
+) -> Result<AFViewCommentThreadRow, AppError> {
+  let query = format!(
+    "{} WHERE t.workspace_id = $1 AND t.view_id = $2 AND t.thread_id = $3",
+    SELECT_VIEW_COMMENT_THREAD
+  );
+  sqlx::query_as::<_, AFViewCommentThreadRow>(&query)
+    .bind(workspace_id)
+    .bind(view_id)
+    .bind(thread_id)
+    .fetch_optional(pg_pool)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("comment thread {} not found", thread_id)))
+}
+
+/// Returns the comments of the given threads, oldest first.
+pub async fn select_view_comments_for_threads(
+  pg_pool: &PgPool,
+  thread_ids: &[Uuid],
+) -> Result<Vec<AFViewCommentRow>, AppError> {
+  let query = format!(
+    "{} WHERE c.thread_id = ANY($1) ORDER BY c.created_at",
+    SELECT_VIEW_COMMENT
+  );
+  let comments = sqlx::query_as::<_, AFViewCommentRow>(&query)
+    .bind(thread_ids)
+    .fetch_all(pg_pool)
+    .await?;
+  Ok(comments)
+}
+
+pub async fn select_view_comment(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  comment_id: &Uuid,
+) -> Result<AFViewCommentRow, AppError> {
+  let query = format!(
+    "{} WHERE t.workspace_id = $1 AND t.view_id = $2 AND c.comment_id = $3",
+    SELECT_VIEW_COMMENT
+  );
+  sqlx::query_as::<_, AFViewCommentRow>(&query)
+    .bind(workspace_id)
+    .bind(view_id)
+    .bind(comment_id)
+    .fetch_optional(pg_pool)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("comment {} not found", comment_id)))
+}
+
+/// Marks the thread as resolved by the given user, or reopens it when `resolved_by` is `None`.
+pub async fn update_view_comment_thread_resolution<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  thread_id: &Uuid,
+  resolved_by: Option<i64>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_view_comment_thread
+      SET resolved_by = $2,
+          resolved_at = CASE WHEN $2::BIGINT IS NULL THEN NULL ELSE NOW() END
+      WHERE thread_id = $1
+    "#,
+  )
+  .bind(thread_id)
+  .bind(resolved_by)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Replaces the content of a comment. The previous content is kept in `af_view_comment_edit`.
+pub async fn update_view_comment_content<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  comment_id: &Uuid,
+  uid: i64,
+  content: &str,
+  mentions: &[Uuid],
+) -> Result<(), AppError> {
+  let res = sqlx::query(
+    r#"
+      WITH previous AS (
+        INSERT INTO af_view_comment_edit (comment_id, content, edited_by)
+        SELECT comment_id, content, $2
+        FROM af_view_comment
+        WHERE comment_id = $1 AND NOT is_deleted
+        RETURNING comment_id
+      )
+      UPDATE af_view_comment
+      SET content = $3,
+          mentions = $4,
+          updated_at = NOW()
+      WHERE comment_id IN (SELECT comment_id FROM previous)
+    "#,
+  )
+  .bind(comment_id)
+  .bind(uid)
+  .bind(content)
+  .bind(mentions)
+  .execute(executor)
+  .await?;
+  if res.rows_affected() != 1 {
+    return Err(AppError::RecordNotFound(format!(
+      "comment {} not found",
+      comment_id
+    )));
+  }
+  Ok(())
+}
+
+pub async fn update_view_comment_deletion_status<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  comment_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_view_comment
+      SET is_deleted = TRUE, updated_at = NOW()
+      WHERE comment_id = $1
+    "#,
+  )
+  .bind(comment_id)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the previous versions of a comment, oldest first.
+pub async fn select_view_comment_edits(
+  pg_pool: &PgPool,
+  comment_id: &Uuid,
+) -> Result<Vec<AFViewCommentEditRow>, AppError> {
+  let edits = sqlx::query_as::<_, AFViewCommentEditRow>(
+    r#"
+      SELECT
+        e.content,
+        editor.uuid AS edited_by,
+        e.edited_at
+      FROM af_view_comment_edit AS e
+      LEFT JOIN af_user AS editor ON e.edited_by = editor.uid
+      WHERE e.comment_id = $1
+      ORDER BY e.edited_at
+    "#,
+  )
+  .bind(comment_id)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(edits)
+}
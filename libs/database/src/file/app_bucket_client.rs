@@ -0,0 +1,184 @@
+use crate::file::fs_client_impl::FsBucketClientImpl;
+use crate::file::s3_client_impl::{AwsS3BucketClientImpl, S3ResponseData};
+use crate::file::{BucketClient, BucketStorage};
+use app_error::AppError;
+use async_trait::async_trait;
+use aws_sdk_s3::primitives::ByteStream;
+use database_entity::file_dto::{
+  CompleteUploadRequest, CreateUploadRequest, CreateUploadResponse, UploadPartData,
+  UploadPartResponse,
+};
+
+pub type AppBucketStorage = BucketStorage<AppBucketClient>;
+
+/// The bucket client selected by the server configuration.
+#[derive(Clone)]
+pub enum AppBucketClient {
+  S3(AwsS3BucketClientImpl),
+  Fs(FsBucketClientImpl),
+}
+
+impl From<AwsS3BucketClientImpl> for AppBucketClient {
+  fn from(client: AwsS3BucketClientImpl) -> Self {
+    Self::S3(client)
+  }
+}
+
+impl From<FsBucketClientImpl> for AppBucketClient {
+  fn from(client: FsBucketClientImpl) -> Self {
+    Self::Fs(client)
+  }
+}
+
+impl AppBucketClient {
+  pub fn as_fs(&self) -> Option<&FsBucketClientImpl> {
+    match self {
+      Self::S3(_) => None,
+      Self::Fs(client) => Some(client),
+    }
+  }
+
+  pub async fn gen_presigned_url(
+    &self,
+    object_key: &str,
+    content_length: u64,
+    expires_in_secs: u64,
+  ) -> Result<String, AppError> {
+    match self {
+      Self::S3(client) => {
+        client
+          .gen_presigned_url(object_key, content_length, expires_in_secs)
+          .await
+      },
+      Self::Fs(client) => {
+        client
+          .gen_presigned_url(object_key, content_length, expires_in_secs)
+          .await
+      },
+    }
+  }
+
+  pub async fn gen_presigned_download_url(
+    &self,
+    object_key: &str,
+    expires_in_secs: u64,
+  ) -> Result<String, AppError> {
+    match self {
+      Self::S3(client) => {
+        client
+          .gen_presigned_download_url(object_key, expires_in_secs)
+          .await
+      },
+      Self::Fs(client) => {
+        client
+          .gen_presigned_download_url(object_key, expires_in_secs)
+          .await
+      },
+    }
+  }
+}
+
+#[async_trait]
+impl BucketClient for AppBucketClient {
+  type ResponseData = S3ResponseData;
+
+  async fn put_blob(
+    &self,
+    object_key: &str,
+    content: ByteStream,
+    content_type: Option<&str>,
+  ) -> Result<(), AppError> {
+    match self {
+      Self::S3(client) => client.put_blob(object_key, content, content_type).await,
+      Self::Fs(client) => client.put_blob(object_key, content, content_type).await,
+    }
+  }
+
+  async fn put_blob_with_content_type(
+    &self,
+    object_key: &str,
+    stream: ByteStream,
+    content_type: &str,
+  ) -> Result<(), AppError> {
+    match self {
+      Self::S3(client) => {
+        client
+          .put_blob_with_content_type(object_key, stream, content_type)
+          .await
+      },
+      Self::Fs(client) => {
+        client
+          .put_blob_with_content_type(object_key, stream, content_type)
+          .await
+      },
+    }
+  }
+
+  async fn delete_blob(&self, object_key: &str) -> Result<Self::ResponseData, AppError> {
+    match self {
+      Self::S3(client) => client.delete_blob(object_key).await,
+      Self::Fs(client) => client.delete_blob(object_key).await,
+    }
+  }
+
+  async fn delete_blobs(&self, object_keys: Vec<String>) -> Result<(), AppError> {
+    match self {
+      Self::S3(client) => client.delete_blobs(object_keys).await,
+      Self::Fs(client) => client.delete_blobs(object_keys).await,
+    }
+  }
+
+  async fn get_blob(&self, object_key: &str) -> Result<Self::ResponseData, AppError> {
+    match self {
+      Self::S3(client) => client.get_blob(object_key).await,
+      Self::Fs(client) => client.get_blob(object_key).await,
+    }
+  }
+
+  async fn create_upload(
+    &self,
+    object_key: &str,
+    req: CreateUploadRequest,
+  ) -> Result<CreateUploadResponse, AppError> {
+    match self {
+      Self::S3(client) => client.create_upload(object_key, req).await,
+      Self::Fs(client) => client.create_upload(object_key, req).await,
+    }
+  }
+
+  async fn upload_part(
+    &self,
+    object_key: &str,
+    req: UploadPartData,
+  ) -> Result<UploadPartResponse, AppError> {
+    match self {
+      Self::S3(client) => client.upload_part(object_key, req).await,
+      Self::Fs(client) => client.upload_part(object_key, req).await,
+    }
+  }
+
+  async fn complete_upload(
+    &self,
+    object_key: &str,
+    req: CompleteUploadRequest,
+  ) -> Result<(usize, String), AppError> {
+    match self {
+      Self::S3(client) => client.complete_upload(object_key, req).await,
+      Self::Fs(client) => client.complete_upload(object_key, req).await,
+    }
+  }
+
+  async fn remove_dir(&self, dir: &str) -> Result<(), AppError> {
+    match self {
+      Self::S3(client) => client.remove_dir(dir).await,
+      Self::Fs(client) => client.remove_dir(dir).await,
+    }
+  }
+
+  async fn list_dir(&self, dir: &str, limit: usize) -> Result<Vec<String>, AppError> {
+    match self {
+      Self::S3(client) => client.list_dir(dir, limit).await,
+      Self::Fs(client) => client.list_dir(dir, limit).await,
+    }
+  }
+}
@@ -0,0 +1,910 @@
+use crate::file::s3_client_impl::S3ResponseData;
+use crate::file::BucketClient;
+use anyhow::anyhow;
+use app_error::AppError;
+use async_trait::async_trait;
+use aws_sdk_s3::primitives::ByteStream;
+use database_entity::file_dto::{
+  CompleteUploadRequest, CompletedPartRequest, CreateUploadRequest, CreateUploadResponse,
+  UploadPartData, UploadPartResponse,
+};
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{error, info, trace, warn};
+use uuid::Uuid;
+
+const OBJECTS_DIR: &str = "objects";
+const METADATA_DIR: &str = "metadata";
+const UPLOADS_DIR: &str = "uploads";
+const TMP_DIR: &str = "tmp";
+const UPLOAD_MANIFEST: &str = "manifest.json";
+const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+const UPLOAD_CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Characters of an object key segment that are escaped in presigned urls: everything but the
+/// unreserved characters of RFC 3986.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+  .remove(b'-')
+  .remove(b'.')
+  .remove(b'_')
+  .remove(b'~');
+
+/// Stores blobs on the local filesystem under `root`, for deployments that don't run S3 or MinIO.
+///
+/// The layout of `root` is:
+/// - `objects/<object key>`: the blob content
+/// - `metadata/<object key>`: the content type of the blob
+/// - `uploads/<upload id>/`: the manifest and the parts of an unfinished multipart upload
+/// - `tmp/`: files being written, which are renamed into place once complete
+///
+/// Uploads that are not completed, and temporary files left by a crash, are removed by
+/// [run_expired_upload_cleanup].
+///
+/// Presigned urls point to `presigned_url_endpoint`, which is expected to be served by
+/// the AppFlowy Cloud server, and are signed with `presign_secret`.
+#[derive(Clone)]
+pub struct FsBucketClientImpl {
+  root: PathBuf,
+  presigned_url_endpoint: String,
+  presign_secret: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UploadManifest {
+  object_key: String,
+  content_type: String,
+}
+
+impl FsBucketClientImpl {
+  pub async fn new(
+    root: impl Into<PathBuf>,
+    presigned_url_endpoint: String,
+    presign_secret: String,
+  ) -> Result<Self, AppError> {
+    if presign_secret.is_empty() {
+      return Err(AppError::Internal(anyhow!(
+        "A presign secret is required by the filesystem storage backend"
+      )));
+    }
+    let root = root.into();
+    for dir in [OBJECTS_DIR, METADATA_DIR, UPLOADS_DIR, TMP_DIR] {
+      fs::create_dir_all(root.join(dir))
+        .await
+        .map_err(|err| anyhow!("Failed to create storage directory {:?}: {}", root, err))?;
+    }
+    Ok(Self {
+      root,
+      presigned_url_endpoint: presigned_url_endpoint.trim_end_matches('/').to_string(),
+      presign_secret,
+    })
+  }
+
+  pub fn root(&self) -> &Path {
+    &self.root
+  }
+
+  /// Returns the path of the blob stored under `object_key`.
+  pub fn object_path(&self, object_key: &str) -> Result<PathBuf, AppError> {
+    Ok(
+      self
+        .root
+        .join(OBJECTS_DIR)
+        .join(validate_object_key(object_key)?),
+    )
+  }
+
+  fn metadata_path(&self, object_key: &str) -> Result<PathBuf, AppError> {
+    Ok(
+      self
+        .root
+        .join(METADATA_DIR)
+        .join(validate_object_key(object_key)?),
+    )
+  }
+
+  fn upload_dir(&self, upload_id: &str) -> Result<PathBuf, AppError> {
+    let upload_id = Uuid::parse_str(upload_id)
+      .map_err(|_| AppError::InvalidRequest(format!("invalid upload id: {}", upload_id)))?;
+    Ok(self.root.join(UPLOADS_DIR).join(upload_id.to_string()))
+  }
+
+  /// Returns the content type stored alongside the blob, if any.
+  pub async fn content_type(&self, object_key: &str) -> Result<Option<String>, AppError> {
+    match fs::read_to_string(self.metadata_path(object_key)?).await {
+      Ok(content_type) => Ok(Some(content_type)),
+      Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+      Err(err) => Err(anyhow!("Failed to read metadata of {}: {}", object_key, err).into()),
+    }
+  }
+
+  /// Writes `data` to a temporary file and renames it to `path`, so that readers never observe
+  /// a partially written file.
+  async fn write_atomic(&self, path: &Path, data: &[u8]) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)
+        .await
+        .map_err(|err| anyhow!("Failed to create directory {:?}: {}", parent, err))?;
+    }
+    let tmp_path = self.root.join(TMP_DIR).join(Uuid::new_v4().to_string());
+    let result = async {
+      let mut file = fs::File::create(&tmp_path).await?;
+      file.write_all(data).await?;
+      file.sync_all().await?;
+      fs::rename(&tmp_path, path).await
+    }
+    .await;
+    if let Err(err) = result {
+      let _ = fs::remove_file(&tmp_path).await;
+      return Err(AppError::Internal(anyhow!(
+        "Failed to write file {:?}: {}",
+        path,
+        err
+      )));
+    }
+    Ok(())
+  }
+
+  async fn write_blob(
+    &self,
+    object_key: &str,
+    data: &[u8],
+    content_type: &str,
+  ) -> Result<(), AppError> {
+    self
+      .write_atomic(&self.metadata_path(object_key)?, content_type.as_bytes())
+      .await?;
+    self
+      .write_atomic(&self.object_path(object_key)?, data)
+      .await?;
+    trace!(
+      "put object to filesystem: {} ({})",
+      object_key,
+      content_type
+    );
+    Ok(())
+  }
+
+  /// Concatenates the parts of the upload in `upload_dir` into a temporary file, checking the
+  /// e_tag of every part, and renames it to the blob path. Parts are copied in chunks so that
+  /// the blob is never held in memory. Returns the size of the blob.
+  async fn write_parts(
+    &self,
+    object_key: &str,
+    upload_dir: &Path,
+    parts: &[CompletedPartRequest],
+  ) -> Result<usize, AppError> {
+    let path = self.object_path(object_key)?;
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)
+        .await
+        .map_err(|err| anyhow!("Failed to create directory {:?}: {}", parent, err))?;
+    }
+    let tmp_path = self.root.join(TMP_DIR).join(Uuid::new_v4().to_string());
+    let result = async {
+      let mut file = fs::File::create(&tmp_path)
+        .await
+        .map_err(|err| anyhow!("Failed to create {:?}: {}", tmp_path, err))?;
+      let mut buf = vec![0; COPY_BUFFER_SIZE];
+      let mut len = 0;
+      for part in parts {
+        let mut part_file = fs::File::open(upload_dir.join(part.part_number.to_string()))
+          .await
+          .map_err(|_| AppError::InvalidRequest(format!("part {} not found", part.part_number)))?;
+        let mut hasher = Sha256::new();
+        loop {
+          let n = part_file
+            .read(&mut buf)
+            .await
+            .map_err(|err| anyhow!("Failed to read part {}: {}", part.part_number, err))?;
+          if n == 0 {
+            break;
+          }
+          hasher.update(&buf[..n]);
+          file
+            .write_all(&buf[..n])
+            .await
+            .map_err(|err| anyhow!("Failed to write {:?}: {}", tmp_path, err))?;
+          len += n;
+        }
+        if hex::encode(hasher.finalize()) != part.e_tag {
+          return Err(AppError::InvalidRequest(format!(
+            "e_tag of part {} does not match",
+            part.part_number
+          )));
+        }
+      }
+      file
+        .sync_all()
+        .await
+        .map_err(|err| anyhow!("Failed to write {:?}: {}", tmp_path, err))?;
+      fs::rename(&tmp_path, &path)
+        .await
+        .map_err(|err| anyhow!("Failed to write file {:?}: {}", path, err))?;
+      Ok(len)
+    }
+    .await;
+    if result.is_err() {
+      let _ = fs::remove_file(&tmp_path).await;
+    }
+    result
+  }
+
+  /// Removes the uploads that were not modified for `max_age`, and the temporary files older
+  /// than `max_age`. Returns the number of removed entries.
+  pub async fn remove_expired_uploads(&self, max_age: Duration) -> Result<usize, AppError> {
+    let mut removed = 0;
+    for dir in [UPLOADS_DIR, TMP_DIR] {
+      let dir = self.root.join(dir);
+      let mut entries = fs::read_dir(&dir)
+        .await
+        .map_err(|err| anyhow!("Failed to read directory {:?}: {}", dir, err))?;
+      while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|err| anyhow!("Failed to read directory {:?}: {}", dir, err))?
+      {
+        let path = entry.path();
+        // A directory's modification time changes when a part is added to it.
+        let is_expired = entry
+          .metadata()
+          .await
+          .and_then(|metadata| metadata.modified())
+          .map(|modified| modified.elapsed().unwrap_or_default() >= max_age)
+          .unwrap_or(false);
+        if !is_expired {
+          continue;
+        }
+        let result = if path.is_dir() {
+          fs::remove_dir_all(&path).await
+        } else {
+          fs::remove_file(&path).await
+        };
+        match result {
+          Ok(_) => removed += 1,
+          Err(err) if err.kind() == ErrorKind::NotFound => {},
+          Err(err) => warn!("failed to remove expired upload {:?}: {}", path, err),
+        }
+      }
+    }
+    Ok(removed)
+  }
+
+  /// Lists the keys starting with `prefix` in ascending order. Like S3, `prefix` is matched
+  /// against the whole key rather than directory names.
+  async fn list_keys(&self, prefix: &str, limit: usize) -> Result<Vec<String>, AppError> {
+    let objects_dir = self.root.join(OBJECTS_DIR);
+    let start_dir = match prefix.rfind('/') {
+      Some(pos) => objects_dir.join(validate_object_key(&prefix[..pos])?),
+      None => objects_dir.clone(),
+    };
+
+    let mut keys = vec![];
+    let mut pending = vec![start_dir];
+    while let Some(dir) = pending.pop() {
+      let mut entries = match fs::read_dir(&dir).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => continue,
+        Err(err) => return Err(anyhow!("Failed to read directory {:?}: {}", dir, err).into()),
+      };
+      while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|err| anyhow!("Failed to read directory {:?}: {}", dir, err))?
+      {
+        let path = entry.path();
+        let file_type = entry
+          .file_type()
+          .await
+          .map_err(|err| anyhow!("Failed to read file type of {:?}: {}", path, err))?;
+        if file_type.is_dir() {
+          pending.push(path);
+          continue;
+        }
+        let key = path
+          .strip_prefix(&objects_dir)
+          .ok()
+          .and_then(|relative| relative.to_str())
+          .map(|relative| relative.replace(std::path::MAIN_SEPARATOR, "/"));
+        if let Some(key) = key.filter(|key| key.starts_with(prefix)) {
+          keys.push(key);
+        }
+      }
+    }
+    keys.sort();
+    keys.truncate(limit);
+    Ok(keys)
+  }
+
+  /// Removes the empty directories between the blob at `object_key` and the `objects` directory.
+  async fn prune_empty_dirs(&self, object_key: &str) -> Result<(), AppError> {
+    for root in [OBJECTS_DIR, METADATA_DIR] {
+      let stop = self.root.join(root);
+      let mut dir = stop.join(validate_object_key(object_key)?);
+      while dir.pop() && dir != stop {
+        // Fails if the directory is not empty, in which case its parents aren't either.
+        if fs::remove_dir(&dir).await.is_err() {
+          break;
+        }
+      }
+    }
+    Ok(())
+  }
+
+  fn sign(
+    &self,
+    method: &str,
+    object_key: &str,
+    expires_at: u64,
+    content_length: Option<u64>,
+  ) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(self.presign_secret.as_bytes())
+      .expect("HMAC can take key of any size");
+    let content_length = content_length
+      .map(|len| len.to_string())
+      .unwrap_or_default();
+    mac.update(format!("{method}\n{object_key}\n{expires_at}\n{content_length}").as_bytes());
+    mac
+  }
+
+  fn gen_presigned(
+    &self,
+    method: &str,
+    object_key: &str,
+    content_length: Option<u64>,
+    expires_in_secs: u64,
+  ) -> String {
+    let expires_at = now_secs() + expires_in_secs;
+    let signature = hex::encode(
+      self
+        .sign(method, object_key, expires_at, content_length)
+        .finalize()
+        .into_bytes(),
+    );
+    let encoded_key = object_key
+      .split('/')
+      .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+      .collect::<Vec<_>>()
+      .join("/");
+    let mut url = format!(
+      "{}/{}?expires={}&signature={}",
+      self.presigned_url_endpoint, encoded_key, expires_at, signature
+    );
+    if let Some(content_length) = content_length {
+      url.push_str(&format!("&content_length={}", content_length));
+    }
+    url
+  }
+
+  /// Generates a url that allows uploading the object with a `PUT` request until it expires.
+  /// The body of the request must not exceed `content_length`.
+  pub async fn gen_presigned_url(
+    &self,
+    object_key: &str,
+    content_length: u64,
+    expires_in_secs: u64,
+  ) -> Result<String, AppError> {
+    validate_object_key(object_key)?;
+    Ok(self.gen_presigned("PUT", object_key, Some(content_length), expires_in_secs))
+  }
+
+  /// Generates a url that allows downloading the object with a `GET` request until it expires.
+  pub async fn gen_presigned_download_url(
+    &self,
+    object_key: &str,
+    expires_in_secs: u64,
+  ) -> Result<String, AppError> {
+    validate_object_key(object_key)?;
+    Ok(self.gen_presigned("GET", object_key, None, expires_in_secs))
+  }
+
+  /// Checks the query parameters of a presigned url.
+  pub fn verify_presigned(
+    &self,
+    method: &str,
+    object_key: &str,
+    query: &PresignedQuery,
+  ) -> Result<(), AppError> {
+    if query.expires < now_secs() {
+      return Err(AppError::NotEnoughPermissions);
+    }
+    let signature = hex::decode(&query.signature).map_err(|_| AppError::NotEnoughPermissions)?;
+    self
+      .sign(method, object_key, query.expires, query.content_length)
+      .verify_slice(&signature)
+      .map_err(|_| AppError::NotEnoughPermissions)
+  }
+}
+
+/// Query parameters of a presigned url generated by [FsBucketClientImpl].
+#[derive(Debug, Deserialize)]
+pub struct PresignedQuery {
+  pub expires: u64,
+  pub signature: String,
+  pub content_length: Option<u64>,
+}
+
+#[async_trait]
+impl BucketClient for FsBucketClientImpl {
+  type ResponseData = S3ResponseData;
+
+  async fn put_blob(
+    &self,
+    object_key: &str,
+    content: ByteStream,
+    content_type: Option<&str>,
+  ) -> Result<(), AppError> {
+    self
+      .put_blob_with_content_type(
+        object_key,
+        content,
+        content_type.unwrap_or(DEFAULT_CONTENT_TYPE),
+      )
+      .await
+  }
+
+  async fn put_blob_with_content_type(
+    &self,
+    object_key: &str,
+    stream: ByteStream,
+    content_type: &str,
+  ) -> Result<(), AppError> {
+    let data = stream
+      .collect()
+      .await
+      .map_err(|err| anyhow!("Failed to read blob content: {}", err))?
+      .into_bytes();
+    self.write_blob(object_key, &data, content_type).await
+  }
+
+  async fn delete_blob(&self, object_key: &str) -> Result<Self::ResponseData, AppError> {
+    for path in [
+      self.object_path(object_key)?,
+      self.metadata_path(object_key)?,
+    ] {
+      match fs::remove_file(&path).await {
+        Ok(_) => {},
+        // Deleting a missing object is not an error in S3 either.
+        Err(err) if err.kind() == ErrorKind::NotFound => {},
+        Err(err) => return Err(anyhow!("Failed to delete {:?}: {}", path, err).into()),
+      }
+    }
+    self.prune_empty_dirs(object_key).await?;
+    trace!("deleted object from filesystem: {}", object_key);
+    Ok(S3ResponseData::new_with_data(vec![], None))
+  }
+
+  async fn delete_blobs(&self, object_keys: Vec<String>) -> Result<(), AppError> {
+    for object_key in object_keys {
+      if let Err(err) = self.delete_blob(&object_key).await {
+        warn!("failed to delete object {}: {}", object_key, err);
+      }
+    }
+    Ok(())
+  }
+
+  async fn get_blob(&self, object_key: &str) -> Result<Self::ResponseData, AppError> {
+    let data = match fs::read(self.object_path(object_key)?).await {
+      Ok(data) => data,
+      Err(err) if err.kind() == ErrorKind::NotFound => {
+        return Err(AppError::RecordNotFound(format!(
+          "blob not found for key:{object_key}"
+        )))
+      },
+      Err(err) => return Err(anyhow!("Failed to read object {}: {}", object_key, err).into()),
+    };
+    let content_type = self.content_type(object_key).await?;
+    trace!(
+      "get object from filesystem: {} ({} bytes)",
+      object_key,
+      data.len()
+    );
+    Ok(S3ResponseData::new_with_data(data, content_type))
+  }
+
+  async fn create_upload(
+    &self,
+    object_key: &str,
+    req: CreateUploadRequest,
+  ) -> Result<CreateUploadResponse, AppError> {
+    validate_object_key(object_key)?;
+    let upload_id = Uuid::new_v4().to_string();
+    let manifest = UploadManifest {
+      object_key: object_key.to_string(),
+      content_type: req.content_type,
+    };
+    let manifest = serde_json::to_vec(&manifest)?;
+    self
+      .write_atomic(
+        &self.upload_dir(&upload_id)?.join(UPLOAD_MANIFEST),
+        &manifest,
+      )
+      .await?;
+    trace!("created multi-part upload {} for {}", upload_id, object_key);
+    Ok(CreateUploadResponse {
+      file_id: req.file_id,
+      upload_id,
+    })
+  }
+
+  async fn upload_part(
+    &self,
+    object_key: &str,
+    req: UploadPartData,
+  ) -> Result<UploadPartResponse, AppError> {
+    if req.body.is_empty() {
+      return Err(AppError::InvalidRequest("body is empty".to_string()));
+    }
+    if req.part_number < 1 {
+      return Err(AppError::InvalidRequest(format!(
+        "invalid part number: {}",
+        req.part_number
+      )));
+    }
+    let upload_dir = self.upload_dir(&req.upload_id)?;
+    read_upload_manifest(&upload_dir, object_key).await?;
+
+    let e_tag = hex::encode(Sha256::digest(&req.body));
+    self
+      .write_atomic(&upload_dir.join(req.part_number.to_string()), &req.body)
+      .await?;
+    trace!("multi-part upload to filesystem: {} - {}", object_key, req);
+    Ok(UploadPartResponse {
+      part_num: req.part_number,
+      e_tag,
+    })
+  }
+
+  async fn complete_upload(
+    &self,
+    object_key: &str,
+    mut req: CompleteUploadRequest,
+  ) -> Result<(usize, String), AppError> {
+    let upload_dir = self.upload_dir(&req.upload_id)?;
+    let manifest = read_upload_manifest(&upload_dir, object_key).await?;
+
+    req.parts.sort_by_key(|part| part.part_number);
+    let len = self
+      .write_parts(object_key, &upload_dir, &req.parts)
+      .await?;
+    self
+      .write_atomic(
+        &self.metadata_path(object_key)?,
+        manifest.content_type.as_bytes(),
+      )
+      .await?;
+    if let Err(err) = fs::remove_dir_all(&upload_dir).await {
+      warn!(
+        "failed to remove upload directory {:?}: {}",
+        upload_dir, err
+      );
+    }
+    trace!(
+      "completed upload to filesystem: {} ({} bytes)",
+      object_key,
+      len
+    );
+    Ok((len, manifest.content_type))
+  }
+
+  async fn remove_dir(&self, dir: &str) -> Result<(), AppError> {
+    loop {
+      let keys = self.list_keys(dir, 1000).await?;
+      if keys.is_empty() {
+        break;
+      }
+      for key in keys {
+        self.delete_blob(&key).await?;
+      }
+    }
+    Ok(())
+  }
+
+  async fn list_dir(&self, dir: &str, limit: usize) -> Result<Vec<String>, AppError> {
+    self.list_keys(dir, limit).await
+  }
+}
+
+/// Removes the uploads of `client` that were not modified for `max_age`.
+pub async fn run_expired_upload_cleanup(client: FsBucketClientImpl, max_age: Duration) {
+  info!(
+    "Starting filesystem upload cleanup, expiry: {}s",
+    max_age.as_secs()
+  );
+  let mut interval = tokio::time::interval(UPLOAD_CLEANUP_INTERVAL);
+  loop {
+    interval.tick().await;
+    match client.remove_expired_uploads(max_age).await {
+      Ok(0) => {},
+      Ok(count) => info!("Removed {} expired filesystem uploads", count),
+      Err(err) => error!("Failed to remove expired filesystem uploads: {}", err),
+    }
+  }
+}
+
+async fn read_upload_manifest(
+  upload_dir: &Path,
+  object_key: &str,
+) -> Result<UploadManifest, AppError> {
+  let manifest = fs::read(upload_dir.join(UPLOAD_MANIFEST))
+    .await
+    .map_err(|_| AppError::RecordNotFound("upload not found".to_string()))?;
+  let manifest: UploadManifest = serde_json::from_slice(&manifest)?;
+  if manifest.object_key != object_key {
+    return Err(AppError::InvalidRequest(
+      "upload does not belong to the object".to_string(),
+    ));
+  }
+  Ok(manifest)
+}
+
+/// Rejects keys that would resolve outside of the storage root.
+fn validate_object_key(object_key: &str) -> Result<&str, AppError> {
+  let is_valid = !object_key.is_empty()
+    && !object_key.contains('\\')
+    && !object_key.contains('\0')
+    && object_key
+      .split('/')
+      .all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+  if is_valid {
+    Ok(object_key)
+  } else {
+    Err(AppError::InvalidRequest(format!(
+      "invalid object key: {}",
+      object_key
+    )))
+  }
+}
+
+fn now_secs() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file::ResponseBlob;
+
+  async fn client(root: &Path) -> FsBucketClientImpl {
+    FsBucketClientImpl::new(
+      root,
+      "http://localhost:8000/api/bucket".to_string(),
+      "secret".to_string(),
+    )
+    .await
+    .unwrap()
+  }
+
+  #[tokio::test]
+  async fn put_get_delete_blob() {
+    let root = tempfile::tempdir().unwrap();
+    let client = client(root.path()).await;
+    client
+      .put_blob(
+        "ws/a/blob",
+        ByteStream::from(b"hello".to_vec()),
+        Some("text/plain"),
+      )
+      .await
+      .unwrap();
+
+    let blob = client.get_blob("ws/a/blob").await.unwrap();
+    assert_eq!(blob.content_type().as_deref(), Some("text/plain"));
+    assert_eq!(blob.to_blob(), b"hello");
+
+    client.delete_blob("ws/a/blob").await.unwrap();
+    assert!(matches!(
+      client.get_blob("ws/a/blob").await,
+      Err(AppError::RecordNotFound(_))
+    ));
+    assert!(!root.path().join(OBJECTS_DIR).join("ws").exists());
+  }
+
+  #[tokio::test]
+  async fn reject_keys_outside_of_root() {
+    let root = tempfile::tempdir().unwrap();
+    let client = client(root.path()).await;
+    for key in ["../escape", "/absolute", "a//b", "a/./b", ""] {
+      assert!(client.get_blob(key).await.is_err(), "{key}");
+    }
+  }
+
+  #[tokio::test]
+  async fn multipart_upload() {
+    let root = tempfile::tempdir().unwrap();
+    let client = client(root.path()).await;
+    let upload = client
+      .create_upload(
+        "ws/file",
+        CreateUploadRequest {
+          file_id: "file".to_string(),
+          parent_dir: "ws".to_string(),
+          content_type: "image/png".to_string(),
+          file_size: None,
+        },
+      )
+      .await
+      .unwrap();
+
+    let mut parts = vec![];
+    for (part_number, body) in [(2, b"world".to_vec()), (1, b"hello ".to_vec())] {
+      let resp = client
+        .upload_part(
+          "ws/file",
+          UploadPartData {
+            file_id: "file".to_string(),
+            upload_id: upload.upload_id.clone(),
+            part_number,
+            body,
+          },
+        )
+        .await
+        .unwrap();
+      parts.push(CompletedPartRequest {
+        e_tag: resp.e_tag,
+        part_number: resp.part_num,
+      });
+    }
+
+    let (len, content_type) = client
+      .complete_upload(
+        "ws/file",
+        CompleteUploadRequest {
+          file_id: "file".to_string(),
+          parent_dir: "ws".to_string(),
+          upload_id: upload.upload_id.clone(),
+          parts,
+        },
+      )
+      .await
+      .unwrap();
+    assert_eq!(len, 11);
+    assert_eq!(content_type, "image/png");
+    assert_eq!(
+      client.get_blob("ws/file").await.unwrap().to_blob(),
+      b"hello world"
+    );
+    assert!(!client.upload_dir(&upload.upload_id).unwrap().exists());
+  }
+
+  #[tokio::test]
+  async fn remove_expired_uploads() {
+    let root = tempfile::tempdir().unwrap();
+    let client = client(root.path()).await;
+    let upload = client
+      .create_upload(
+        "ws/file",
+        CreateUploadRequest {
+          file_id: "file".to_string(),
+          parent_dir: "ws".to_string(),
+          content_type: "image/png".to_string(),
+          file_size: None,
+        },
+      )
+      .await
+      .unwrap();
+    let upload_dir = client.upload_dir(&upload.upload_id).unwrap();
+
+    assert_eq!(
+      client
+        .remove_expired_uploads(Duration::from_secs(60))
+        .await
+        .unwrap(),
+      0
+    );
+    assert!(upload_dir.exists());
+    assert_eq!(
+      client.remove_expired_uploads(Duration::ZERO).await.unwrap(),
+      1
+    );
+    assert!(!upload_dir.exists());
+  }
+
+  #[tokio::test]
+  async fn list_and_remove_dir() {
+    let root = tempfile::tempdir().unwrap();
+    let client = client(root.path()).await;
+    for key in ["ws1/a", "ws1/b/c", "ws10/d", "ws2/e"] {
+      client
+        .put_blob(key, ByteStream::from(b"x".to_vec()), None)
+        .await
+        .unwrap();
+    }
+
+    assert_eq!(
+      client.list_dir("ws1/", 10).await.unwrap(),
+      vec!["ws1/a", "ws1/b/c"]
+    );
+    assert_eq!(
+      client.list_dir("ws1", 10).await.unwrap(),
+      vec!["ws1/a", "ws1/b/c", "ws10/d"]
+    );
+    assert_eq!(client.list_dir("ws1", 1).await.unwrap(), vec!["ws1/a"]);
+
+    client.remove_dir("ws1/").await.unwrap();
+    assert_eq!(
+      client.list_dir("ws", 10).await.unwrap(),
+      vec!["ws10/d", "ws2/e"]
+    );
+  }
+
+  #[tokio::test]
+  async fn verify_presigned_url() {
+    let root = tempfile::tempdir().unwrap();
+    let client = client(root.path()).await;
+    let url = client
+      .gen_presigned_url("ws/import.zip", 1024, 60)
+      .await
+      .unwrap();
+    let (path, query_string) = url.split_once('?').unwrap();
+    assert_eq!(path, "http://localhost:8000/api/bucket/ws/import.zip");
+    let mut query = PresignedQuery {
+      expires: 0,
+      signature: String::new(),
+      content_length: None,
+    };
+    for pair in query_string.split('&') {
+      match pair.split_once('=').unwrap() {
+        ("expires", value) => query.expires = value.parse().unwrap(),
+        ("signature", value) => query.signature = value.to_string(),
+        ("content_length", value) => query.content_length = Some(value.parse().unwrap()),
+        _ => {},
+      }
+    }
+    assert_eq!(query.content_length, Some(1024));
+
+    assert!(client
+      .verify_presigned("PUT", "ws/import.zip", &query)
+      .is_ok());
+    assert!(client
+      .verify_presigned("GET", "ws/import.zip", &query)
+      .is_err());
+    assert!(client
+      .verify_presigned("PUT", "ws/other.zip", &query)
+      .is_err());
+
+    let tampered = PresignedQuery {
+      content_length: Some(4096),
+      ..query
+    };
+    assert!(client
+      .verify_presigned("PUT", "ws/import.zip", &tampered)
+      .is_err());
+  }
+
+  #[tokio::test]
+  async fn encode_object_key_in_presigned_url() {
+    let root = tempfile::tempdir().unwrap();
+    let client = client(root.path()).await;
+    let url = client
+      .gen_presigned_download_url("ws/my file?v=1#a.zip", 60)
+      .await
+      .unwrap();
+    let (path, _) = url.split_once('?').unwrap();
+    assert_eq!(
+      path,
+      "http://localhost:8000/api/bucket/ws/my%20file%3Fv%3D1%23a.zip"
+    );
+  }
+
+  #[tokio::test]
+  async fn require_presign_secret() {
+    let root = tempfile::tempdir().unwrap();
+    let result = FsBucketClientImpl::new(
+      root.path(),
+      "http://localhost:8000/api/bucket".to_string(),
+      String::new(),
+    )
+    .await;
+    assert!(result.is_err());
+  }
+}
@@ -83,7 +83,33 @@ impl AwsS3BucketClientImpl {
       .await
       .map_err(|err| AppError::Internal(anyhow!("Generate presigned url failed: {:?}", err)))?;
     let url = put_object_req.uri().to_string();
+    Ok(self.public_presigned_url(url))
+  }
+
+  /// Generates a url that allows downloading the object without authentication until it expires.
+  pub async fn gen_presigned_download_url(
+    &self,
+    s3_key: &str,
+    expires_in_secs: u64,
+  ) -> Result<String, AppError> {
+    let config = PresigningConfig::builder()
+      .start_time(SystemTime::now())
+      .expires_in(Duration::from_secs(expires_in_secs))
+      .build()
+      .map_err(|e| AppError::S3ResponseError(e.to_string()))?;
+
+    let get_object_req = self
+      .client
+      .get_object()
+      .bucket(&self.bucket)
+      .key(s3_key)
+      .presigned(config)
+      .await
+      .map_err(|err| AppError::Internal(anyhow!("Generate presigned url failed: {:?}", err)))?;
+    Ok(self.public_presigned_url(get_object_req.uri().to_string()))
+  }
 
+  fn public_presigned_url(&self, url: String) -> String {
     let public_url = self
       .presigned_url_endpoint
       .as_ref()
@@ -97,7 +123,7 @@ impl AwsS3BucketClientImpl {
       self.endpoint,
       self.presigned_url_endpoint
     );
-    Ok(public_url)
+    public_url
   }
 
   async fn complete_upload_and_get_metadata(
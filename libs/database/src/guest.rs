@@ -0,0 +1,341 @@
+use app_error::AppError;
+use database_entity::dto::{AFAccessLevel, AFRole};
+use futures_util::stream::BoxStream;
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{AFGuestViewAccessLevelRow, AFSharedViewGuestRow, AFSharedViewRow};
+
+/// Grants the guest with the given email access to `view_ids`. `shared_view_id` is the view that
+/// was explicitly shared, and must be part of `view_ids`. The remaining ids are the child views
+/// that inherit the grant.
+pub async fn upsert_guest_view_access<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  shared_view_id: &Uuid,
+  view_ids: &[Uuid],
+  email: &str,
+  access_level: AFAccessLevel,
+  invited_by: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_guest_view_access
+        (workspace_id, view_id, shared_view_id, email, access_level, invited_by)
+      SELECT $1, view_id, $2, LOWER($4), $5, $6
+      FROM UNNEST($3::UUID[]) AS view_id
+      ON CONFLICT (view_id, shared_view_id, email)
+      DO UPDATE SET access_level = EXCLUDED.access_level
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(shared_view_id)
+  .bind(view_ids)
+  .bind(email)
+  .bind(i32::from(access_level))
+  .bind(invited_by)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Copies the grants of `parent_view_id` to `child_view_ids`, so that views created or moved under
+/// a shared view are accessible to the same guests. Returns the uids of the guests whose access
+/// changed.
+pub async fn insert_inherited_guest_view_access<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  parent_view_id: &Uuid,
+  child_view_ids: &[Uuid],
+) -> Result<Vec<i64>, AppError> {
+  let uids = sqlx::query_scalar::<_, i64>(
+    r#"
+      WITH inserted AS (
+        INSERT INTO af_guest_view_access
+          (workspace_id, view_id, shared_view_id, email, access_level, invited_by)
+        SELECT gva.workspace_id, child.view_id, gva.shared_view_id, gva.email, gva.access_level,
+          gva.invited_by
+        FROM af_guest_view_access AS gva
+        CROSS JOIN UNNEST($3::UUID[]) AS child(view_id)
+        WHERE gva.workspace_id = $1 AND gva.view_id = $2
+        ON CONFLICT (view_id, shared_view_id, email) DO NOTHING
+        RETURNING email
+      )
+      SELECT DISTINCT af_user.uid
+      FROM inserted
+      JOIN af_user ON LOWER(af_user.email) = inserted.email
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(parent_view_id)
+  .bind(child_view_ids)
+  .fetch_all(executor)
+  .await?;
+  Ok(uids)
+}
+
+/// Removes the grants that `view_ids` inherited from views outside of them, keeping the grants
+/// made on the views themselves. Used before a subtree of views is moved to another parent.
+/// Returns the uids of the guests whose access changed.
+pub async fn delete_inherited_guest_view_access<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+) -> Result<Vec<i64>, AppError> {
+  let uids = sqlx::query_scalar::<_, i64>(
+    r#"
+      WITH deleted AS (
+        DELETE FROM af_guest_view_access
+        WHERE workspace_id = $1
+          AND view_id = ANY($2)
+          AND NOT (shared_view_id = ANY($2))
+        RETURNING email
+      )
+      SELECT DISTINCT af_user.uid
+      FROM deleted
+      JOIN af_user ON LOWER(af_user.email) = deleted.email
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_ids)
+  .fetch_all(executor)
+  .await?;
+  Ok(uids)
+}
+
+/// Removes the grants made on `shared_view_id` for the given email, including the grants
+/// inherited by the child views. Returns the ids of the views that were affected.
+pub async fn delete_guest_view_access<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  shared_view_id: &Uuid,
+  email: &str,
+) -> Result<Vec<Uuid>, AppError> {
+  let view_ids = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      DELETE FROM af_guest_view_access
+      WHERE workspace_id = $1
+        AND shared_view_id = $2
+        AND email = LOWER($3)
+      RETURNING view_id
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(shared_view_id)
+  .bind(email)
+  .fetch_all(executor)
+  .await?;
+  Ok(view_ids)
+}
+
+/// Removes all the grants of a guest in the workspace. Used when the guest is removed from the
+/// workspace. Returns the ids of the views that were affected.
+pub async fn delete_all_guest_view_access_for_email<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  email: &str,
+) -> Result<Vec<Uuid>, AppError> {
+  let view_ids = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      DELETE FROM af_guest_view_access
+      WHERE workspace_id = $1 AND email = LOWER($2)
+      RETURNING view_id
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(email)
+  .fetch_all(executor)
+  .await?;
+  Ok(view_ids)
+}
+
+/// Returns the effective access level of a guest on each of the given views. If the guest
+/// was granted access to a view multiple times (e.g. explicitly and through a parent), the
+/// highest access level wins. Views without any grant are omitted.
+pub async fn select_guest_access_levels_for_views<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  view_ids: &[Uuid],
+) -> Result<Vec<AFGuestViewAccessLevelRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFGuestViewAccessLevelRow>(
+    r#"
+      SELECT
+        af_user.uid,
+        gva.view_id,
+        MAX(gva.access_level) AS access_level
+      FROM af_guest_view_access AS gva
+      JOIN af_user ON LOWER(af_user.email) = gva.email
+      WHERE gva.workspace_id = $1
+        AND af_user.uid = $2
+        AND gva.view_id = ANY($3)
+      GROUP BY af_user.uid, gva.view_id
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .bind(view_ids)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the effective access level of a guest on every view that the guest can access in the
+/// workspace.
+pub async fn select_guest_access_levels<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<Vec<AFGuestViewAccessLevelRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFGuestViewAccessLevelRow>(
+    r#"
+      SELECT
+        af_user.uid,
+        gva.view_id,
+        MAX(gva.access_level) AS access_level
+      FROM af_guest_view_access AS gva
+      JOIN af_user ON LOWER(af_user.email) = gva.email
+      WHERE gva.workspace_id = $1
+        AND af_user.uid = $2
+      GROUP BY af_user.uid, gva.view_id
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the views that were explicitly shared with the given guest.
+pub async fn select_views_shared_with_guest<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<Vec<AFSharedViewRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFSharedViewRow>(
+    r#"
+      SELECT
+        gva.view_id,
+        MAX(gva.access_level) AS access_level
+      FROM af_guest_view_access AS gva
+      JOIN af_user ON LOWER(af_user.email) = gva.email
+      WHERE gva.workspace_id = $1
+        AND af_user.uid = $2
+        AND gva.view_id = gva.shared_view_id
+      GROUP BY gva.view_id
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the views of the workspace that have been explicitly shared with at least one guest.
+pub async fn select_views_shared_with_any_guest<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<Uuid>, AppError> {
+  let view_ids = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      SELECT DISTINCT view_id
+      FROM af_guest_view_access
+      WHERE workspace_id = $1 AND view_id = shared_view_id
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(view_ids)
+}
+
+/// Returns the guests that have access to any of the given views. `view_ids` is expected to
+/// contain a view and its ancestors, so that the access inherited from the ancestors is taken
+/// into account. A guest without a matching workspace member row has not accepted the
+/// invitation yet.
+pub async fn select_guests_with_access_to_views<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  view_ids: &[Uuid],
+) -> Result<Vec<AFSharedViewGuestRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFSharedViewGuestRow>(
+    r#"
+      SELECT
+        $2::UUID AS view_id,
+        gva.email,
+        af_user.name,
+        af_user.metadata ->> 'icon_url' AS avatar_url,
+        MAX(gva.access_level) AS access_level,
+        af_workspace_member.role_id
+      FROM af_guest_view_access AS gva
+      LEFT JOIN af_user ON LOWER(af_user.email) = gva.email
+      LEFT JOIN af_workspace_member
+        ON af_workspace_member.uid = af_user.uid
+        AND af_workspace_member.workspace_id = gva.workspace_id
+      WHERE gva.workspace_id = $1
+        AND gva.view_id = ANY($3)
+      GROUP BY gva.email, af_user.name, af_user.metadata, af_workspace_member.role_id
+      ORDER BY gva.email
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(view_ids)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the uuids of the guests that have accepted the invitation and have access to the view.
+pub async fn select_guest_uuids_with_access_to_view<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Vec<Uuid>, AppError> {
+  let uuids = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      SELECT DISTINCT af_user.uuid
+      FROM af_guest_view_access AS gva
+      JOIN af_user ON LOWER(af_user.email) = gva.email
+      JOIN af_workspace_member
+        ON af_workspace_member.uid = af_user.uid
+        AND af_workspace_member.workspace_id = gva.workspace_id
+      WHERE gva.workspace_id = $1
+        AND gva.view_id = $2
+        AND af_workspace_member.role_id = $3
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(AFRole::Guest as i32)
+  .fetch_all(executor)
+  .await?;
+  Ok(uuids)
+}
+
+/// Streams the effective access level of every guest on every view shared with them. Used by
+/// the access control to load the view level policies.
+pub fn select_guest_view_access_perm_stream(
+  pg_pool: &PgPool,
+) -> BoxStream<'_, sqlx::Result<AFGuestViewAccessLevelRow>> {
+  sqlx::query_as::<_, AFGuestViewAccessLevelRow>(
+    r#"
+      SELECT
+        af_user.uid,
+        gva.view_id,
+        MAX(gva.access_level) AS access_level
+      FROM af_guest_view_access AS gva
+      JOIN af_user ON LOWER(af_user.email) = gva.email
+      JOIN af_workspace_member
+        ON af_workspace_member.uid = af_user.uid
+        AND af_workspace_member.workspace_id = gva.workspace_id
+      WHERE af_workspace_member.role_id = $1
+      GROUP BY af_user.uid, gva.view_id
+    "#,
+  )
+  .bind(AFRole::Guest as i32)
+  .fetch(pg_pool)
+}
@@ -0,0 +1,152 @@
+use database_entity::dto::AFCollabEmbeddedChunk;
+use pgvector::Vector;
+use sqlx::{Executor, Postgres, Transaction};
+use std::ops::DerefMut;
+use uuid::Uuid;
+
+/// Text extracted from a file uploaded to the workspace.
+#[derive(Debug, Clone)]
+pub struct AttachmentIndexParams {
+  pub workspace_id: Uuid,
+  pub parent_dir: String,
+  pub file_id: String,
+  /// Page the file was uploaded to, if the `parent_dir` of the file is the id of a view.
+  pub view_id: Option<Uuid>,
+  /// Mime type of the file.
+  pub content_type: String,
+  /// User who uploaded the file.
+  pub uid: i64,
+  pub content: String,
+}
+
+/// Stores the text extracted from an attachment, so it can be searched by keyword. Returns the id
+/// the embeddings of the attachment are stored under.
+pub async fn upsert_attachment_index<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  params: &AttachmentIndexParams,
+) -> Result<Uuid, sqlx::Error> {
+  let attachment_id: Uuid = sqlx::query_scalar(
+    r#"
+      INSERT INTO af_attachment_index
+        (workspace_id, parent_dir, file_id, view_id, content_type, uid, content, indexed_at)
+      VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+      ON CONFLICT (workspace_id, parent_dir, file_id) DO UPDATE
+      SET view_id = EXCLUDED.view_id,
+          content_type = EXCLUDED.content_type,
+          content = EXCLUDED.content,
+          indexed_at = NOW()
+      RETURNING attachment_id
+    "#,
+  )
+  .bind(params.workspace_id)
+  .bind(&params.parent_dir)
+  .bind(&params.file_id)
+  .bind(params.view_id)
+  .bind(&params.content_type)
+  .bind(params.uid)
+  .bind(&params.content)
+  .fetch_one(executor)
+  .await?;
+  Ok(attachment_id)
+}
+
+/// Replaces the embeddings of an attachment and records the tokens used to create them.
+pub async fn upsert_attachment_embeddings(
+  transaction: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  attachment_id: &Uuid,
+  tokens_used: u32,
+  embedding_model: &str,
+  chunks: Vec<AFCollabEmbeddedChunk>,
+) -> Result<(), sqlx::Error> {
+  sqlx::query("DELETE FROM af_attachment_embeddings WHERE attachment_id = $1")
+    .bind(attachment_id)
+    .execute(transaction.deref_mut())
+    .await?;
+
+  for chunk in chunks {
+    let (content, embedding) = match (chunk.content, chunk.embedding) {
+      (Some(content), Some(embedding)) => (content, embedding),
+      _ => continue,
+    };
+    sqlx::query(
+      r#"
+        INSERT INTO af_attachment_embeddings
+          (attachment_id, fragment_index, content, embedding, embedding_model, metadata)
+        VALUES ($1, $2, $3, $4, $5, $6)
+      "#,
+    )
+    .bind(attachment_id)
+    .bind(chunk.fragment_index)
+    .bind(content)
+    .bind(Vector::from(embedding))
+    .bind(embedding_model)
+    .bind(chunk.metadata)
+    .execute(transaction.deref_mut())
+    .await?;
+  }
+
+  sqlx::query(
+    r#"
+      INSERT INTO af_workspace_ai_usage
+        (created_at, workspace_id, search_requests, search_tokens_consumed, index_tokens_consumed)
+      VALUES (NOW()::date, $1, 0, 0, $2)
+      ON CONFLICT (created_at, workspace_id) DO UPDATE
+      SET index_tokens_consumed = af_workspace_ai_usage.index_tokens_consumed + $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(tokens_used as i64)
+  .execute(transaction.deref_mut())
+  .await?;
+  Ok(())
+}
+
+/// Removes the attachment, and its embeddings, from the index.
+pub async fn delete_attachment_index<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  parent_dir: &str,
+  file_id: &str,
+) -> Result<(), sqlx::Error> {
+  sqlx::query(
+    r#"
+      DELETE FROM af_attachment_index
+      WHERE workspace_id = $1 AND parent_dir = $2 AND file_id = $3
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(parent_dir)
+  .bind(file_id)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the attachments of a workspace which have at least one fragment embedded with a model
+/// other than `embedding_model`, as `(uid, parent_dir, file_id)`.
+pub async fn select_attachments_with_stale_embeddings<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  embedding_model: &str,
+  limit: i64,
+) -> Result<Vec<(i64, String, String)>, sqlx::Error> {
+  sqlx::query_as::<_, (i64, String, String)>(
+    r#"
+      SELECT ai.uid, ai.parent_dir, ai.file_id
+      FROM af_attachment_index ai
+      WHERE ai.workspace_id = $1
+        AND EXISTS (
+          SELECT 1 FROM af_attachment_embeddings em
+          WHERE em.attachment_id = ai.attachment_id AND em.embedding_model <> $2
+        )
+      ORDER BY ai.indexed_at DESC
+      LIMIT $3
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(embedding_model)
+  .bind(limit)
+  .fetch_all(executor)
+  .await
+}
@@ -8,7 +8,7 @@ use pgvector::Vector;
 use serde_json::json;
 use sqlx::pool::PoolConnection;
 use sqlx::postgres::{PgHasArrayType, PgTypeInfo};
-use sqlx::{Error, Executor, Postgres, Transaction};
+use sqlx::{Error, Executor, PgPool, Postgres, Transaction};
 use std::collections::HashMap;
 use std::ops::DerefMut;
 use uuid::Uuid;
@@ -97,6 +97,7 @@ pub async fn upsert_collab_embeddings(
   workspace_id: &Uuid,
   object_id: &Uuid,
   tokens_used: u32,
+  embedding_model: &str,
   chunks: Vec<AFCollabEmbeddedChunk>,
 ) -> Result<(), sqlx::Error> {
   let fragments = chunks.into_iter().map(Fragment::from).collect::<Vec<_>>();
@@ -109,10 +110,11 @@ pub async fn upsert_collab_embeddings(
       .map(|v| v.fragment_id.clone())
       .collect::<Vec<_>>()
   );
-  sqlx::query(r#"CALL af_collab_embeddings_upsert($1, $2, $3, $4::af_fragment_v3[])"#)
+  sqlx::query(r#"CALL af_collab_embeddings_upsert_v2($1, $2, $3, $4, $5::af_fragment_v3[])"#)
     .bind(*workspace_id)
     .bind(object_id)
     .bind(tokens_used as i32)
+    .bind(embedding_model)
     .bind(fragments)
     .execute(transaction.deref_mut())
     .await?;
@@ -161,35 +163,176 @@ where
   Ok(fragments)
 }
 
+/// Returns the ids of the fragments of the given collabs which were embedded with `embedding_model`.
+/// Fragments embedded with another model are not returned, so they get embedded again.
 pub async fn get_collab_embedding_fragment_ids<'a, E>(
   tx: E,
   collab_ids: Vec<Uuid>,
+  embedding_model: &str,
 ) -> Result<HashMap<Uuid, Vec<String>>, sqlx::Error>
 where
   E: Executor<'a, Database = Postgres>,
 {
-  let records = sqlx::query!(
+  let records = sqlx::query_as::<_, (Uuid, String)>(
     r#"
         SELECT oid, fragment_id
         FROM af_collab_embeddings
-        WHERE oid = ANY($1::uuid[])
+        WHERE oid = ANY($1::uuid[]) AND embedding_model = $2
         "#,
-    &collab_ids,
   )
+  .bind(&collab_ids)
+  .bind(embedding_model)
   .fetch_all(tx)
   .await?;
 
   let mut fragment_ids_by_oid = HashMap::new();
-  for record in records {
-    // If your record.oid is not a String, convert it as needed.
+  for (oid, fragment_id) in records {
     fragment_ids_by_oid
-      .entry(record.oid)
+      .entry(oid)
       .or_insert_with(Vec::new)
-      .push(record.fragment_id);
+      .push(fragment_id);
   }
   Ok(fragment_ids_by_oid)
 }
 
+/// Number of collabs of a workspace with embeddings, by whether they were embedded with
+/// `embedding_model`.
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct EmbeddingModelStats {
+  pub indexed: i64,
+  pub stale: i64,
+}
+
+pub async fn select_embedding_model_stats<'a, E>(
+  executor: E,
+  workspace_id: &Uuid,
+  embedding_model: &str,
+) -> Result<EmbeddingModelStats, sqlx::Error>
+where
+  E: Executor<'a, Database = Postgres>,
+{
+  sqlx::query_as::<_, EmbeddingModelStats>(
+    r#"
+      WITH models AS (
+        SELECT em.oid, BOOL_AND(em.embedding_model = $2) AS up_to_date
+        FROM af_collab_embeddings em
+        JOIN af_collab c ON c.oid = em.oid
+        WHERE c.workspace_id = $1 AND c.deleted_at IS NULL
+        GROUP BY em.oid
+      )
+      SELECT
+        COUNT(*) FILTER (WHERE up_to_date) AS indexed,
+        COUNT(*) FILTER (WHERE NOT up_to_date) AS stale
+      FROM models
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(embedding_model)
+  .fetch_one(executor)
+  .await
+}
+
+/// Returns the collabs of a workspace which have at least one fragment embedded with a model other
+/// than `embedding_model`. Only the collabs of `collab_types` are returned, so that collabs which
+/// can't be reindexed don't fill every batch.
+pub async fn select_collabs_with_stale_embeddings<'a, E>(
+  executor: E,
+  workspace_id: &Uuid,
+  embedding_model: &str,
+  collab_types: &[CollabType],
+  limit: i64,
+) -> Result<Vec<CollabId>, sqlx::Error>
+where
+  E: Executor<'a, Database = Postgres>,
+{
+  let partition_keys = collab_types
+    .iter()
+    .map(partition_key_from_collab_type)
+    .collect::<Vec<_>>();
+  let rows = sqlx::query_as::<_, (Uuid, Uuid, i32)>(
+    r#"
+      SELECT c.workspace_id, c.oid, c.partition_key
+      FROM af_collab c
+      WHERE c.workspace_id = $1
+        AND c.deleted_at IS NULL
+        AND c.partition_key = ANY($3)
+        AND EXISTS (
+          SELECT 1 FROM af_collab_embeddings em
+          WHERE em.oid = c.oid AND em.embedding_model <> $2
+        )
+      ORDER BY c.updated_at DESC
+      LIMIT $4
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(embedding_model)
+  .bind(partition_keys)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|(workspace_id, object_id, partition_key)| CollabId {
+        collab_type: CollabType::from(partition_key),
+        workspace_id,
+        object_id,
+      })
+      .collect(),
+  )
+}
+
+/// HNSW indexes can only be built on vectors with a fixed dimension, so the embeddings are indexed
+/// separately for each dimension in use. pgvector doesn't index vectors with more than 2000
+/// dimensions, these embeddings are searched sequentially.
+///
+/// The index is built concurrently so that embeddings can still be written meanwhile, which can't
+/// be done in a transaction. A concurrent build that failed leaves an invalid index behind, which
+/// is dropped and built again.
+pub async fn create_embedding_similarity_index(
+  pg_pool: &PgPool,
+  dimensions: u32,
+) -> Result<bool, sqlx::Error> {
+  if dimensions == 0 || dimensions > 2000 {
+    return Ok(false);
+  }
+
+  let index_name = format!("af_collab_embeddings_similarity_{}_idx", dimensions);
+  let is_valid = sqlx::query_scalar::<_, bool>(
+    r#"
+      SELECT i.indisvalid
+      FROM pg_index i
+      JOIN pg_class c ON c.oid = i.indexrelid
+      WHERE c.relname = $1
+    "#,
+  )
+  .bind(&index_name)
+  .fetch_optional(pg_pool)
+  .await?;
+  match is_valid {
+    Some(true) => return Ok(true),
+    Some(false) => {
+      sqlx::raw_sql(&format!("DROP INDEX CONCURRENTLY IF EXISTS {}", index_name))
+        .execute(pg_pool)
+        .await?;
+    },
+    None => {},
+  }
+
+  // the dimension is a number, it can't be used to inject sql
+  let sql = format!(
+    r#"
+      CREATE INDEX CONCURRENTLY IF NOT EXISTS {index_name}
+      ON af_collab_embeddings USING hnsw ((embedding::VECTOR({dims})) vector_cosine_ops)
+      WHERE embedding_dimensions = {dims}
+    "#,
+    dims = dimensions
+  );
+  sqlx::raw_sql(&sql).execute(pg_pool).await?;
+  Ok(true)
+}
+
 pub async fn stream_collabs_without_embeddings(
   conn: &mut PoolConnection<Postgres>,
   workspace_id: Uuid,
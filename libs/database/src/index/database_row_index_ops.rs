@@ -0,0 +1,50 @@
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+/// Links the collabs of a database row, ie. the row itself and the document of the row, to the
+/// database the row belongs to.
+pub async fn upsert_database_row_index<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  database_id: &Uuid,
+  row_id: &Uuid,
+  object_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_database_row_index (oid, workspace_id, database_id, row_id, updated_at)
+      SELECT oid, $2, $3, $4, NOW()
+      FROM UNNEST($1::uuid[]) AS oid
+      ON CONFLICT (oid) DO UPDATE
+      SET database_id = EXCLUDED.database_id,
+          row_id = EXCLUDED.row_id,
+          updated_at = NOW()
+    "#,
+  )
+  .bind(object_ids)
+  .bind(workspace_id)
+  .bind(database_id)
+  .bind(row_id)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Removes the rows from the index, so they are not returned by the search anymore.
+pub async fn delete_database_row_index<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  row_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+  sqlx::query(
+    r#"
+      DELETE FROM af_database_row_index
+      WHERE workspace_id = $1 AND row_id = ANY($2::uuid[])
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(row_ids)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
@@ -0,0 +1,43 @@
+use crate::collab::partition_key_from_collab_type;
+use collab_entity::CollabType;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+/// Stores the plain text content of a collab in the keyword index. The `tsvector` used for the
+/// full-text search is generated by the database from the content.
+pub async fn upsert_collab_keyword_index<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  object_id: &Uuid,
+  collab_type: CollabType,
+  content: &str,
+) -> Result<(), sqlx::Error> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_collab_keyword_index (oid, workspace_id, partition_key, content, updated_at)
+      VALUES ($1, $2, $3, $4, NOW())
+      ON CONFLICT (oid) DO UPDATE
+      SET content = EXCLUDED.content,
+          updated_at = NOW()
+    "#,
+  )
+  .bind(object_id)
+  .bind(workspace_id)
+  .bind(partition_key_from_collab_type(&collab_type))
+  .bind(content)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Removes a deleted collab from the keyword index.
+pub async fn delete_collab_keyword_index<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  object_id: &Uuid,
+) -> Result<(), sqlx::Error> {
+  sqlx::query("DELETE FROM af_collab_keyword_index WHERE oid = $1")
+    .bind(object_id)
+    .execute(executor)
+    .await?;
+  Ok(())
+}
@@ -1,5 +1,11 @@
+mod attachment_index_ops;
 mod collab_embeddings_ops;
+mod database_row_index_ops;
+mod keyword_index_ops;
 mod search_ops;
 
+pub use attachment_index_ops::*;
 pub use collab_embeddings_ops::*;
+pub use database_row_index_ops::*;
+pub use keyword_index_ops::*;
 pub use search_ops::*;
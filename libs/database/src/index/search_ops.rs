@@ -1,4 +1,6 @@
+use crate::collab::partition_key_from_collab_type;
 use chrono::{DateTime, Utc};
+use collab_entity::CollabType;
 use pgvector::Vector;
 use sqlx::{Executor, Postgres};
 use tracing::trace;
@@ -16,7 +18,10 @@ pub async fn search_documents<'a, E: Executor<'a, Database = Postgres>>(
   params: SearchDocumentParams,
   tokens_used: u32,
 ) -> Result<Vec<SearchDocumentResult>, sqlx::Error> {
-  let query = sqlx::query_as::<_, SearchDocumentRow>(
+  // The vectors are compared with the expression and the predicate of the similarity index of
+  // their dimension, see `create_embedding_similarity_index`. The dimension is a number, it can't
+  // be used to inject sql.
+  let sql = format!(
     r#"
     WITH workspace AS (
       INSERT INTO af_workspace_ai_usage(created_at, workspace_id, search_requests, search_tokens_consumed, index_tokens_consumed)
@@ -35,31 +40,38 @@ pub async fn search_documents<'a, E: Executor<'a, Database = Postgres>>(
     LEFT(em.content, $4) AS content_preview,
     u.name AS created_by,
     collab.created_at AS created_at,
-    em.embedding <=> $3 AS distance
+    em.distance,
+    dr.database_id,
+    dr.row_id
     FROM af_collab collab
     JOIN LATERAL (
       -- Fetch the most relevant embedding per collab.oid
-      SELECT *
+      SELECT content_type, content, embedding::VECTOR({dims}) <=> $3::VECTOR({dims}) AS distance
       FROM af_collab_embeddings
-      WHERE oid = collab.oid
-      ORDER BY embedding <=> $3  -- Use vector index for sorting
+      WHERE oid = collab.oid AND embedding_model = $8 AND embedding_dimensions = {dims}
+      ORDER BY embedding::VECTOR({dims}) <=> $3::VECTOR({dims})  -- Use vector index for sorting
       LIMIT 1  -- Only keep the top result
     ) em ON true
     JOIN af_user u ON collab.owner_uid = u.uid
+    LEFT JOIN af_database_row_index dr ON dr.oid = collab.oid AND dr.workspace_id = $2
     WHERE
       collab.workspace_id = $2
-      AND collab.oid = ANY($7::uuid[])
+      AND (collab.oid = ANY($7::uuid[]) OR dr.database_id = ANY($9::uuid[]))
     ORDER BY distance
     LIMIT $5;
   "#,
-  )
-  .bind(params.user_id)
-  .bind(params.workspace_id)
-  .bind(Vector::from(params.embedding))
-  .bind(params.preview)
-  .bind(params.limit)
-  .bind(tokens_used as i64)
-  .bind(params.searchable_view_ids);
+    dims = params.embedding.len()
+  );
+  let query = sqlx::query_as::<_, SearchDocumentRow>(&sql)
+    .bind(params.user_id)
+    .bind(params.workspace_id)
+    .bind(Vector::from(params.embedding))
+    .bind(params.preview)
+    .bind(params.limit)
+    .bind(tokens_used as i64)
+    .bind(params.searchable_view_ids)
+    .bind(params.embedding_model)
+    .bind(params.searchable_database_ids);
   let rows = query.fetch_all(executor).await?;
   let has_rows = !rows.is_empty();
   trace!(
@@ -85,6 +97,9 @@ pub async fn search_documents<'a, E: Executor<'a, Database = Postgres>>(
       created_by: result.created_by,
       created_at: result.created_at,
       score,
+      database_id: result.database_id,
+      row_id: result.row_id,
+      attachment: None,
     });
   }
 
@@ -99,6 +114,198 @@ pub async fn search_documents<'a, E: Executor<'a, Database = Postgres>>(
   Ok(results)
 }
 
+/// Searches documents using the full-text keyword index. Unlike [search_documents], it doesn't
+/// require an embedding of the query, so it works on servers without an AI embedder configured.
+/// Results are ordered by their `ts_rank_cd` score, which is not bounded to `[0.0..1.0]`.
+pub async fn search_documents_by_keyword<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  params: KeywordSearchDocumentParams,
+) -> Result<Vec<SearchDocumentResult>, sqlx::Error> {
+  let rows = sqlx::query_as::<_, KeywordSearchDocumentRow>(
+    r#"
+    WITH query AS (
+      SELECT websearch_to_tsquery('simple', $2) AS tsq
+    )
+    SELECT
+      collab.oid AS object_id,
+      collab.workspace_id,
+      collab.partition_key AS collab_type,
+      0 AS content_type,
+      -- only the fragments around the matches, like for attachments, the index holds the text of
+      -- the whole document
+      ts_headline(
+        'simple',
+        ki.content,
+        query.tsq,
+        'StartSel="", StopSel="", MaxFragments=3, MaxWords=35, MinWords=15, FragmentDelimiter=" ... "'
+      ) AS content,
+      u.name AS created_by,
+      collab.created_at AS created_at,
+      ts_rank_cd(ki.content_tsv, query.tsq)::float8 AS rank,
+      dr.database_id,
+      dr.row_id
+    FROM af_collab_keyword_index ki
+    CROSS JOIN query
+    JOIN af_collab collab ON collab.oid = ki.oid
+    JOIN af_user u ON collab.owner_uid = u.uid
+    LEFT JOIN af_database_row_index dr ON dr.oid = collab.oid AND dr.workspace_id = $1
+    WHERE
+      ki.workspace_id = $1
+      AND collab.deleted_at IS NULL
+      AND ki.content_tsv @@ query.tsq
+      AND (collab.oid = ANY($4::uuid[]) OR dr.database_id = ANY($5::uuid[]))
+    ORDER BY rank DESC
+    LIMIT $3;
+  "#,
+  )
+  .bind(params.workspace_id)
+  .bind(&params.query)
+  .bind(params.limit)
+  .bind(params.searchable_view_ids)
+  .bind(params.searchable_database_ids)
+  .fetch_all(executor)
+  .await?;
+  trace!(
+    "[Search] found {} keyword results, ranks: {:?}",
+    rows.len(),
+    rows.iter().map(|r| r.rank).collect::<Vec<_>>()
+  );
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| SearchDocumentResult {
+        object_id: row.object_id,
+        workspace_id: row.workspace_id,
+        collab_type: row.collab_type,
+        content_type: row.content_type,
+        content: row.content,
+        created_by: row.created_by,
+        created_at: row.created_at,
+        score: row.rank,
+        database_id: row.database_id,
+        row_id: row.row_id,
+        attachment: None,
+      })
+      .collect(),
+  )
+}
+
+/// Searches the text extracted from the files uploaded to the pages the user can search. Works
+/// like [search_documents], except that the search request is not logged again.
+pub async fn search_attachments<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  params: SearchDocumentParams,
+) -> Result<Vec<SearchDocumentResult>, sqlx::Error> {
+  let sql = format!(
+    r#"
+    SELECT
+      a.attachment_id,
+      a.workspace_id,
+      a.view_id,
+      a.parent_dir,
+      a.file_id,
+      a.content_type AS mime_type,
+      em.content,
+      u.name AS created_by,
+      a.created_at,
+      em.distance::float8 AS score
+    FROM af_attachment_index a
+    JOIN LATERAL (
+      SELECT content, embedding::VECTOR({dims}) <=> $2::VECTOR({dims}) AS distance
+      FROM af_attachment_embeddings
+      WHERE attachment_id = a.attachment_id AND embedding_model = $5 AND embedding_dimensions = {dims}
+      ORDER BY embedding::VECTOR({dims}) <=> $2::VECTOR({dims})
+      LIMIT 1
+    ) em ON true
+    JOIN af_user u ON a.uid = u.uid
+    WHERE
+      a.workspace_id = $1
+      AND a.view_id = ANY($4::uuid[])
+    ORDER BY score
+    LIMIT $3;
+  "#,
+    dims = params.embedding.len()
+  );
+  let rows = sqlx::query_as::<_, AttachmentSearchRow>(&sql)
+    .bind(params.workspace_id)
+    .bind(Vector::from(params.embedding))
+    .bind(params.limit)
+    .bind(params.searchable_view_ids)
+    .bind(params.embedding_model)
+    .fetch_all(executor)
+    .await?;
+  trace!("[Search] found {} attachment results", rows.len());
+
+  Ok(
+    rows
+      .into_iter()
+      .filter_map(|row| {
+        // the score column holds the cosine distance
+        let score = _cosine_relevance_score_fn(row.score);
+        (score > params.score).then(|| row.into_result(score))
+      })
+      .collect(),
+  )
+}
+
+/// Keyword search counterpart of [search_attachments].
+pub async fn search_attachments_by_keyword<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  params: KeywordSearchDocumentParams,
+) -> Result<Vec<SearchDocumentResult>, sqlx::Error> {
+  let rows = sqlx::query_as::<_, AttachmentSearchRow>(
+    r#"
+    WITH query AS (
+      SELECT websearch_to_tsquery('simple', $2) AS tsq
+    )
+    SELECT
+      a.attachment_id,
+      a.workspace_id,
+      a.view_id,
+      a.parent_dir,
+      a.file_id,
+      a.content_type AS mime_type,
+      -- only the fragments around the matches, the text of a whole file can be large
+      ts_headline(
+        'simple',
+        a.content,
+        query.tsq,
+        'StartSel="", StopSel="", MaxFragments=3, MaxWords=35, MinWords=15, FragmentDelimiter=" ... "'
+      ) AS content,
+      u.name AS created_by,
+      a.created_at,
+      ts_rank_cd(a.content_tsv, query.tsq)::float8 AS score
+    FROM af_attachment_index a
+    CROSS JOIN query
+    JOIN af_user u ON a.uid = u.uid
+    WHERE
+      a.workspace_id = $1
+      AND a.content_tsv @@ query.tsq
+      AND a.view_id = ANY($4::uuid[])
+    ORDER BY score DESC
+    LIMIT $3;
+  "#,
+  )
+  .bind(params.workspace_id)
+  .bind(&params.query)
+  .bind(params.limit)
+  .bind(params.searchable_view_ids)
+  .fetch_all(executor)
+  .await?;
+  trace!("[Search] found {} attachment keyword results", rows.len());
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| {
+        let score = row.score;
+        row.into_result(score)
+      })
+      .collect(),
+  )
+}
+
 /// Converts cosine distance to a relevance score.
 /// Distance:
 ///   Represents the raw vector distance between the query embedding and the document embedding
@@ -129,14 +336,50 @@ pub struct SearchDocumentParams {
   pub limit: i32,
   /// How many characters of the content (starting from the beginning) should be returned.
   pub preview: i32,
-  /// Embedding of the query - generated by OpenAI embedder.
+  /// Embedding of the query - generated by the configured embedder.
   pub embedding: Vec<f32>,
+  /// Name of the model which generated `embedding`. Only the documents embedded with the same
+  /// model are compared with the query.
+  pub embedding_model: String,
   /// List of view ids which is not supposed to be returned in the search results.
   pub searchable_view_ids: Vec<Uuid>,
+  /// List of database ids whose rows, and documents of the rows, can be returned in the search
+  /// results.
+  pub searchable_database_ids: Vec<Uuid>,
   /// similarity score limit for the search results. The higher, the better.
   pub score: f64,
 }
 
+#[derive(Debug, Clone)]
+pub struct KeywordSearchDocumentParams {
+  /// Workspace ID to search for documents in.
+  pub workspace_id: Uuid,
+  /// Query statement, using the web search syntax ie. `"exact phrase" -excluded OR other`.
+  pub query: String,
+  /// How many results should be returned.
+  pub limit: i32,
+  /// List of view ids which are allowed to be returned in the search results.
+  pub searchable_view_ids: Vec<Uuid>,
+  /// List of database ids whose rows, and documents of the rows, can be returned in the search
+  /// results.
+  pub searchable_database_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct KeywordSearchDocumentRow {
+  pub object_id: Uuid,
+  pub workspace_id: Uuid,
+  pub collab_type: i32,
+  pub content_type: i32,
+  pub content: String,
+  pub created_by: String,
+  pub created_at: DateTime<Utc>,
+  /// Full-text search rank of the document. Higher is better.
+  pub rank: f64,
+  pub database_id: Option<Uuid>,
+  pub row_id: Option<Uuid>,
+}
+
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct SearchDocumentRow {
   /// Document identifier.
@@ -155,6 +398,10 @@ pub struct SearchDocumentRow {
   pub created_at: DateTime<Utc>,
   /// Similarity distance to an original query. Lower is better.
   pub distance: f64,
+  /// Database of the row, if the document is a database row or the document of a row.
+  pub database_id: Option<Uuid>,
+  /// Row the document belongs to, if it is a database row or the document of a row.
+  pub row_id: Option<Uuid>,
 }
 
 #[derive(Debug, Clone)]
@@ -167,4 +414,60 @@ pub struct SearchDocumentResult {
   pub created_by: String,
   pub created_at: DateTime<Utc>,
   pub score: f64,
+  pub database_id: Option<Uuid>,
+  pub row_id: Option<Uuid>,
+  /// Set when the result was found in the text extracted from an uploaded file.
+  pub attachment: Option<SearchAttachment>,
+}
+
+/// File uploaded to a page, found by [search_attachments] or [search_attachments_by_keyword].
+#[derive(Debug, Clone)]
+pub struct SearchAttachment {
+  /// Page the file was uploaded to.
+  pub view_id: Uuid,
+  pub parent_dir: String,
+  pub file_id: String,
+  /// Mime type of the file.
+  pub content_type: String,
 }
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct AttachmentSearchRow {
+  attachment_id: Uuid,
+  workspace_id: Uuid,
+  view_id: Uuid,
+  parent_dir: String,
+  file_id: String,
+  mime_type: String,
+  content: String,
+  created_by: String,
+  created_at: DateTime<Utc>,
+  score: f64,
+}
+
+impl AttachmentSearchRow {
+  fn into_result(self, score: f64) -> SearchDocumentResult {
+    SearchDocumentResult {
+      object_id: self.attachment_id,
+      workspace_id: self.workspace_id,
+      // attachments are searched as a part of the page they were uploaded to
+      collab_type: partition_key_from_collab_type(&CollabType::Document),
+      content_type: ATTACHMENT_CONTENT_TYPE,
+      content: self.content,
+      created_by: self.created_by,
+      created_at: self.created_at,
+      score,
+      database_id: None,
+      row_id: None,
+      attachment: Some(SearchAttachment {
+        view_id: self.view_id,
+        parent_dir: self.parent_dir,
+        file_id: self.file_id,
+        content_type: self.mime_type,
+      }),
+    }
+  }
+}
+
+/// Content type of the results found in the text extracted from attachments.
+const ATTACHMENT_CONTENT_TYPE: i32 = 1;
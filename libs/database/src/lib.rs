@@ -1,15 +1,23 @@
 pub mod access_request;
+pub mod api_token;
+pub mod audit;
 pub mod chat;
 pub mod collab;
+pub mod collab_author;
+pub mod comment;
 pub mod file;
+pub mod guest;
 pub mod history;
 pub mod index;
 pub mod listener;
 pub mod notification;
 pub mod pg_row;
 pub mod publish;
+pub mod publish_analytics;
+pub mod publish_domain;
 pub mod quick_note;
 pub mod resource_usage;
 pub mod template;
 pub mod user;
+pub mod webhook;
 pub mod workspace;
@@ -4,6 +4,10 @@ use app_error::AppError;
 use database_entity::dto::{PageMentionNotification, ProcessedPageMentionNotification};
 use sqlx::{postgres::types::PgInterval, Executor, Postgres, QueryBuilder};
 
+use crate::pg_row::AFWorkspaceNotification;
+
+pub const WORKSPACE_NOTIFICATION_CHANNEL: &str = "af_workspace_notification_channel";
+
 pub async fn select_recent_page_mentions<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
   recency_seconds: u64,
@@ -66,3 +70,19 @@ pub async fn update_page_mention_notification_status<'a, E: Executor<'a, Databas
   builder.build().execute(executor).await?;
   Ok(())
 }
+
+/// Sends the notification to the server nodes listening on [WORKSPACE_NOTIFICATION_CHANNEL].
+/// When sent within a transaction, the notification is only delivered once the transaction is
+/// committed.
+pub async fn notify_workspace_sessions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  notification: &AFWorkspaceNotification,
+) -> Result<(), AppError> {
+  let payload = serde_json::to_string(notification)?;
+  sqlx::query("SELECT pg_notify($1, $2)")
+    .bind(WORKSPACE_NOTIFICATION_CHANNEL)
+    .bind(payload)
+    .execute(executor)
+    .await?;
+  Ok(())
+}
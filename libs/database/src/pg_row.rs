@@ -1,13 +1,14 @@
 use anyhow::anyhow;
 use app_error::AppError;
-use chrono::{DateTime, Utc};
+use appflowy_proto::WorkspaceNotification;
+use chrono::{DateTime, NaiveDate, Utc};
 
 use database_entity::dto::{
   AFAccessLevel, AFRole, AFUserProfile, AFWebUser, AFWebUserWithObfuscatedName, AFWorkspace,
   AFWorkspaceInvitationStatus, AFWorkspaceMember, AccessRequestMinimal, AccessRequestStatus,
-  AccessRequestWithViewId, AccessRequesterInfo, AccountLink, GlobalComment, QuickNote, Reaction,
-  Template, TemplateCategory, TemplateCategoryMinimal, TemplateCategoryType, TemplateCreator,
-  TemplateCreatorMinimal, TemplateGroup, TemplateMinimal,
+  AccessRequestWithViewId, AccessRequesterInfo, AccountLink, GlobalComment, PublishInfo, QuickNote,
+  Reaction, Template, TemplateCategory, TemplateCategoryMinimal, TemplateCategoryType,
+  TemplateCreator, TemplateCreatorMinimal, TemplateGroup, TemplateMinimal,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -317,6 +318,18 @@ pub struct AFUserNotification {
   pub payload: Option<AFUserRow>,
 }
 
+/// A notification for the websocket sessions connected to a workspace, sent to every server node
+/// through [crate::notification::WORKSPACE_NOTIFICATION_CHANNEL].
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AFWorkspaceNotification {
+  /// When not set, the sessions of `uid` receive the notification whichever workspace they are
+  /// connected to.
+  pub workspace_id: Option<Uuid>,
+  /// When set, only the sessions of this user receive the notification.
+  pub uid: Option<i64>,
+  pub notification: WorkspaceNotification,
+}
+
 #[derive(FromRow, Debug, Clone)]
 pub struct AFPermissionRow {
   pub id: i32,
@@ -712,6 +725,118 @@ pub struct AFImportTask {
   #[serde(default)]
   pub file_url: Option<String>,
 }
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct AFExportTask {
+  pub task_id: Uuid,
+  pub workspace_id: Uuid,
+  pub created_by: i64,
+  pub status: i16,
+  pub s3_key: Option<String>,
+  pub file_size: Option<i64>,
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct AFWebhookRow {
+  pub webhook_id: Uuid,
+  pub workspace_id: Uuid,
+  pub url: String,
+  pub secret: String,
+  pub events: Vec<String>,
+  pub enabled: bool,
+  pub created_by: i64,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct AFWebhookDeliveryRow {
+  pub delivery_id: Uuid,
+  pub webhook_id: Uuid,
+  pub workspace_id: Uuid,
+  pub event: String,
+  pub payload: serde_json::Value,
+  pub status: i16,
+  pub attempts: i32,
+  pub next_attempt_at: DateTime<Utc>,
+  pub response_status: Option<i32>,
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// A pending delivery joined with the endpoint it must be sent to.
+#[derive(Debug, FromRow)]
+pub struct AFPendingWebhookDeliveryRow {
+  pub delivery_id: Uuid,
+  pub webhook_id: Uuid,
+  pub event: String,
+  pub payload: serde_json::Value,
+  pub attempts: i32,
+  pub url: String,
+  pub secret: String,
+}
+
+#[derive(Debug, FromRow)]
+pub struct AFViewCommentThreadRow {
+  pub thread_id: Uuid,
+  pub view_id: Uuid,
+  pub block_id: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub resolved_by: Option<Uuid>,
+  pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct AFViewCommentRow {
+  pub comment_id: Uuid,
+  pub thread_id: Uuid,
+  pub content: String,
+  pub mentions: Vec<Uuid>,
+  pub created_by: Option<i64>,
+  pub author_uuid: Option<Uuid>,
+  pub author_name: Option<String>,
+  pub author_avatar_url: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+  pub is_deleted: bool,
+  pub edit_count: i64,
+}
+
+#[derive(Debug, FromRow)]
+pub struct AFViewCommentEditRow {
+  pub content: String,
+  pub edited_by: Option<Uuid>,
+  pub edited_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct AFApiTokenRow {
+  pub token_id: Uuid,
+  pub uid: i64,
+  pub name: String,
+  pub token_prefix: String,
+  pub workspace_ids: Vec<Uuid>,
+  pub scope: i32,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: Option<DateTime<Utc>>,
+  pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A valid api token joined with the user that owns it.
+#[derive(Debug, FromRow)]
+pub struct AFApiTokenOwnerRow {
+  pub token_id: Uuid,
+  pub uid: i64,
+  pub uuid: Uuid,
+  pub email: String,
+  pub workspace_ids: Vec<Uuid>,
+  pub scope: i32,
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
 #[derive(sqlx::Type, Serialize, Deserialize, Debug)]
 #[repr(i32)]
 pub enum AFAccessRequestStatusColumn {
@@ -814,6 +939,63 @@ impl From<AFQuickNoteRow> for QuickNote {
   }
 }
 
+/// Represent the effective access level of a guest on a view, derived from the
+/// af_guest_view_access table.
+#[derive(FromRow, Debug, Clone)]
+pub struct AFGuestViewAccessLevelRow {
+  pub uid: i64,
+  pub view_id: Uuid,
+  pub access_level: i32,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AFSharedViewRow {
+  pub view_id: Uuid,
+  pub access_level: i32,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AFSharedViewGuestRow {
+  pub view_id: Uuid,
+  pub email: String,
+  pub name: Option<String>,
+  pub avatar_url: Option<String>,
+  pub access_level: i32,
+  pub role_id: Option<i32>,
+}
+
+#[derive(FromRow)]
+pub struct AFPublishInfoRow {
+  pub namespace: String,
+  pub publish_name: String,
+  pub view_id: Uuid,
+  pub publisher_email: String,
+  pub publish_timestamp: DateTime<Utc>,
+  pub unpublished_timestamp: Option<DateTime<Utc>>,
+  pub comments_enabled: bool,
+  pub duplicate_enabled: bool,
+  pub password_protected: bool,
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<AFPublishInfoRow> for PublishInfo {
+  fn from(value: AFPublishInfoRow) -> Self {
+    Self {
+      namespace: value.namespace,
+      publish_name: value.publish_name,
+      view_id: value.view_id,
+      publisher_email: value.publisher_email,
+      publish_timestamp: value.publish_timestamp,
+      unpublished_timestamp: value.unpublished_timestamp,
+      comments_enabled: value.comments_enabled,
+      duplicate_enabled: value.duplicate_enabled,
+      password_protected: value.password_protected,
+      expires_at: value.expires_at,
+    }
+  }
+}
+
+#[derive(FromRow)]
 pub struct AFPublishViewWithPublishInfo {
   pub view_id: Uuid,
   pub publish_name: String,
@@ -821,6 +1003,60 @@ pub struct AFPublishViewWithPublishInfo {
   pub publish_timestamp: DateTime<Utc>,
   pub comments_enabled: bool,
   pub duplicate_enabled: bool,
+  pub password_protected: bool,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AFPublishedCollabAccessRow {
+  pub workspace_id: Uuid,
+  pub view_id: Uuid,
+  pub password_hash: Option<String>,
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AFAuditLogRow {
+  pub event_id: i64,
+  pub workspace_id: Uuid,
+  pub actor_uid: Option<i64>,
+  pub actor_email: Option<String>,
+  pub action: String,
+  pub target: Option<String>,
+  pub ip_address: Option<String>,
+  pub user_agent: Option<String>,
+  pub before_value: Option<serde_json::Value>,
+  pub after_value: Option<serde_json::Value>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AFPublishCustomDomainRow {
+  pub workspace_id: Uuid,
+  pub domain: String,
+  pub verification_token: String,
+  pub verified_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AFPublishedViewDailyCountRow {
+  pub view_id: Uuid,
+  pub date: NaiveDate,
+  pub views: i64,
+  pub unique_visitors: i64,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AFPublishedDailyCountRow {
+  pub date: NaiveDate,
+  pub views: i64,
+  pub unique_visitors: i64,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AFPublishedReferrerCountRow {
+  pub referrer_domain: String,
+  pub views: i64,
 }
 
 #[cfg(test)]
@@ -842,3 +1078,12 @@ mod tests {
     assert_eq!(masked, "jonath");
   }
 }
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AFCollabUpdateAuthorRow {
+  pub client_id: i64,
+  pub clock_start: i64,
+  pub clock_end: i64,
+  pub uid: i64,
+  pub updated_at: DateTime<Utc>,
+}
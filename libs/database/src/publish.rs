@@ -1,11 +1,40 @@
 use app_error::AppError;
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, SaltString};
+use argon2::{Argon2, PasswordVerifier};
+use chrono::{DateTime, Utc};
 use database_entity::dto::{
   PatchPublishedCollab, PublishCollabItem, PublishCollabKey, PublishInfo, WorkspaceNamespace,
 };
-use sqlx::{Executor, PgPool, Postgres, QueryBuilder};
+use sqlx::{Executor, PgPool, Postgres, QueryBuilder, Transaction};
 use uuid::Uuid;
 
-use crate::pg_row::AFPublishViewWithPublishInfo;
+use crate::pg_row::{AFPublishInfoRow, AFPublishViewWithPublishInfo, AFPublishedCollabAccessRow};
+
+/// Hashes the password of a published page. Empty passwords are treated as no password.
+pub fn hash_publish_password(password: Option<&str>) -> Result<Option<String>, AppError> {
+  match password.filter(|password| !password.is_empty()) {
+    None => Ok(None),
+    Some(password) => {
+      let salt = SaltString::generate(&mut OsRng);
+      let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|err| AppError::Internal(anyhow::anyhow!("Failed to hash password: {}", err)))?;
+      Ok(Some(hash.to_string()))
+    },
+  }
+}
+
+pub fn verify_publish_password(password: &str, password_hash: &str) -> bool {
+  match PasswordHash::new(password_hash) {
+    Ok(hash) => Argon2::default()
+      .verify_password(password.as_bytes(), &hash)
+      .is_ok(),
+    Err(err) => {
+      tracing::error!("Invalid published collab password hash: {}", err);
+      false
+    },
+  }
+}
 
 pub async fn select_user_is_collab_publisher_for_all_views(
   pg_pool: &PgPool,
@@ -243,7 +272,7 @@ async fn delete_published_collabs(
 
 #[inline]
 pub async fn insert_or_replace_publish_collabs(
-  pg_pool: &PgPool,
+  txn: &mut Transaction<'_, Postgres>,
   workspace_id: &Uuid,
   publisher_uuid: &Uuid,
   publish_items: Vec<PublishCollabItem<serde_json::Value, Vec<u8>>>,
@@ -255,47 +284,58 @@ pub async fn insert_or_replace_publish_collabs(
   let mut blobs: Vec<Vec<u8>> = Vec::with_capacity(item_count);
   let mut comments_enabled_list: Vec<bool> = Vec::with_capacity(item_count);
   let mut duplicate_enabled_list: Vec<bool> = Vec::with_capacity(item_count);
-  publish_items.into_iter().for_each(|item| {
+  let mut password_hashes: Vec<Option<String>> = Vec::with_capacity(item_count);
+  let mut expires_at_list: Vec<Option<DateTime<Utc>>> = Vec::with_capacity(item_count);
+  for item in publish_items {
+    password_hashes.push(hash_publish_password(item.password.as_deref())?);
+    expires_at_list.push(item.expires_at);
     view_ids.push(item.meta.view_id);
     publish_names.push(item.meta.publish_name);
     metadatas.push(item.meta.metadata);
     blobs.push(item.data);
     comments_enabled_list.push(item.comments_enabled);
     duplicate_enabled_list.push(item.duplicate_enabled);
-  });
+  }
 
-  let mut txn = pg_pool.begin().await?;
-  delete_published_collabs(&mut txn, workspace_id, &publish_names).await?;
+  delete_published_collabs(txn, workspace_id, &publish_names).await?;
 
-  let res = sqlx::query!(
+  let res = sqlx::query(
     r#"
-      INSERT INTO af_published_collab (workspace_id, view_id, publish_name, published_by, metadata, blob, comments_enabled, duplicate_enabled)
+      INSERT INTO af_published_collab (workspace_id, view_id, publish_name, published_by, metadata, blob, comments_enabled, duplicate_enabled, password_hash, expires_at)
       SELECT * FROM UNNEST(
-        (SELECT array_agg((SELECT $1::uuid)) FROM generate_series(1, $9))::uuid[],
+        (SELECT array_agg((SELECT $1::uuid)) FROM generate_series(1, $11))::uuid[],
         $2::uuid[],
         $3::text[],
-        (SELECT array_agg((SELECT uid FROM af_user WHERE uuid = $4)) FROM generate_series(1, $9))::bigint[],
+        (SELECT array_agg((SELECT uid FROM af_user WHERE uuid = $4)) FROM generate_series(1, $11))::bigint[],
         $5::jsonb[],
         $6::bytea[],
         $7::boolean[],
-        $8::boolean[]
+        $8::boolean[],
+        $9::text[],
+        $10::timestamptz[]
       )
       ON CONFLICT (workspace_id, view_id) DO UPDATE
       SET metadata = EXCLUDED.metadata,
           blob = EXCLUDED.blob,
           published_by = EXCLUDED.published_by,
-          publish_name = EXCLUDED.publish_name
+          publish_name = EXCLUDED.publish_name,
+          password_hash = EXCLUDED.password_hash,
+          expires_at = EXCLUDED.expires_at,
+          rendered_html = NULL,
+          rendered_summary = NULL
     "#,
-    workspace_id,
-    &view_ids,
-    &publish_names,
-    publisher_uuid,
-    &metadatas,
-    &blobs,
-    &comments_enabled_list,
-    &duplicate_enabled_list,
-    item_count as i32,
   )
+  .bind(workspace_id)
+  .bind(&view_ids)
+  .bind(&publish_names)
+  .bind(publisher_uuid)
+  .bind(&metadatas)
+  .bind(&blobs)
+  .bind(&comments_enabled_list)
+  .bind(&duplicate_enabled_list)
+  .bind(&password_hashes)
+  .bind(&expires_at_list)
+  .bind(item_count as i32)
   .execute(txn.as_mut())
   .await?;
 
@@ -306,7 +346,6 @@ pub async fn insert_or_replace_publish_collabs(
     );
   }
 
-  txn.commit().await?;
   Ok(())
 }
 
@@ -404,9 +443,25 @@ pub async fn update_published_collabs(
       if !first_set {
         query_builder.push(",");
       }
+      first_set = false;
       query_builder.push(" publish_name = ");
       query_builder.push_bind(publish_name);
     }
+    if let Some(password) = &patch.password {
+      if !first_set {
+        query_builder.push(",");
+      }
+      first_set = false;
+      query_builder.push(" password_hash = ");
+      query_builder.push_bind(hash_publish_password(Some(password))?);
+    }
+    if patch.clear_expires_at || patch.expires_at.is_some() {
+      if !first_set {
+        query_builder.push(",");
+      }
+      query_builder.push(" expires_at = ");
+      query_builder.push_bind(patch.expires_at.filter(|_| !patch.clear_expires_at));
+    }
     query_builder.push(" WHERE workspace_id = ");
     query_builder.push_bind(workspace_id);
     query_builder.push(" AND view_id = ");
@@ -572,8 +627,7 @@ pub async fn select_publish_info_for_view_ids(
   pg_pool: &PgPool,
   view_ids: &[Uuid],
 ) -> Result<Vec<PublishInfo>, AppError> {
-  let mut res = sqlx::query_as!(
-    PublishInfo,
+  let mut res: Vec<PublishInfo> = sqlx::query_as::<_, AFPublishInfoRow>(
     r#"
       SELECT
         awn.namespace,
@@ -583,17 +637,22 @@ pub async fn select_publish_info_for_view_ids(
         apc.created_at AS publish_timestamp,
         apc.unpublished_at AS unpublished_timestamp,
         apc.comments_enabled,
-        apc.duplicate_enabled
+        apc.duplicate_enabled,
+        apc.password_hash IS NOT NULL AS password_protected,
+        apc.expires_at
       FROM af_published_collab apc
       JOIN af_user au ON apc.published_by = au.uid
       JOIN af_workspace aw ON apc.workspace_id = aw.workspace_id
       JOIN af_workspace_namespace awn ON aw.workspace_id = awn.workspace_id AND awn.is_original = TRUE
       WHERE apc.view_id = ANY($1);
     "#,
-    view_ids,
   )
+  .bind(view_ids)
   .fetch_all(pg_pool)
-  .await?;
+  .await?
+  .into_iter()
+  .map(PublishInfo::from)
+  .collect();
 
   if res.is_empty() {
     return Ok(res);
@@ -623,8 +682,7 @@ pub async fn select_all_published_collab_info(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
 ) -> Result<Vec<PublishInfo>, AppError> {
-  let mut res = sqlx::query_as!(
-    PublishInfo,
+  let mut res: Vec<PublishInfo> = sqlx::query_as::<_, AFPublishInfoRow>(
     r#"
       SELECT
         awn.namespace,
@@ -634,17 +692,22 @@ pub async fn select_all_published_collab_info(
         apc.created_at AS publish_timestamp,
         apc.unpublished_at AS unpublished_timestamp,
         apc.comments_enabled,
-        apc.duplicate_enabled
+        apc.duplicate_enabled,
+        apc.password_hash IS NOT NULL AS password_protected,
+        apc.expires_at
       FROM af_published_collab apc
       JOIN af_user au ON apc.published_by = au.uid
       JOIN af_workspace aw ON apc.workspace_id = aw.workspace_id
       JOIN af_workspace_namespace awn ON aw.workspace_id = awn.workspace_id AND awn.is_original = TRUE
       WHERE apc.workspace_id = $1 AND apc.unpublished_at IS NULL;
     "#,
-    workspace_id,
   )
+  .bind(workspace_id)
   .fetch_all(pg_pool)
-  .await?;
+  .await?
+  .into_iter()
+  .map(PublishInfo::from)
+  .collect();
 
   use_non_orginal_namespace_if_possible(pg_pool, &mut res).await?;
   Ok(res)
@@ -686,6 +749,20 @@ pub async fn select_workspace_id_for_publish_namespace<'a, E: Executor<'a, Datab
   Ok(res.workspace_id)
 }
 
+/// Returns the workspace of the published view, or `None` if the view is not published.
+pub async fn select_workspace_id_for_published_view<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+) -> Result<Option<Uuid>, AppError> {
+  let workspace_id = sqlx::query_scalar::<_, Uuid>(
+    "SELECT workspace_id FROM af_published_collab WHERE view_id = $1 AND unpublished_at IS NULL",
+  )
+  .bind(view_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(workspace_id)
+}
+
 pub async fn select_published_view_ids_for_workspace<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
   workspace_id: Uuid,
@@ -712,8 +789,7 @@ pub async fn select_published_view_ids_with_publish_info_for_workspace<
   executor: E,
   workspace_id: Uuid,
 ) -> Result<Vec<AFPublishViewWithPublishInfo>, AppError> {
-  let res = sqlx::query_as!(
-    AFPublishViewWithPublishInfo,
+  let res = sqlx::query_as::<_, AFPublishViewWithPublishInfo>(
     r#"
       SELECT
         apc.view_id,
@@ -721,16 +797,115 @@ pub async fn select_published_view_ids_with_publish_info_for_workspace<
         au.email AS publisher_email,
         apc.created_at AS publish_timestamp,
         apc.comments_enabled,
-        apc.duplicate_enabled
+        apc.duplicate_enabled,
+        apc.password_hash IS NOT NULL AS password_protected
       FROM af_published_collab apc
       JOIN af_user au ON apc.published_by = au.uid
       WHERE workspace_id = $1
       AND unpublished_at IS NULL
+      AND (expires_at IS NULL OR expires_at > NOW())
     "#,
-    workspace_id,
   )
+  .bind(workspace_id)
   .fetch_all(executor)
   .await?;
 
   Ok(res)
 }
+
+pub async fn select_published_collab_access<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<AFPublishedCollabAccessRow, AppError> {
+  let res = sqlx::query_as::<_, AFPublishedCollabAccessRow>(
+    r#"
+      SELECT workspace_id, view_id, password_hash, expires_at
+      FROM af_published_collab
+      WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
+        AND unpublished_at IS NULL
+        AND publish_name = $2
+    "#,
+  )
+  .bind(publish_namespace)
+  .bind(publish_name)
+  .fetch_one(executor)
+  .await?;
+
+  Ok(res)
+}
+
+pub async fn select_published_collab_access_for_view_id<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  view_id: &Uuid,
+) -> Result<Option<AFPublishedCollabAccessRow>, AppError> {
+  let res = sqlx::query_as::<_, AFPublishedCollabAccessRow>(
+    r#"
+      SELECT workspace_id, view_id, password_hash, expires_at
+      FROM af_published_collab
+      WHERE view_id = $1
+        AND unpublished_at IS NULL
+    "#,
+  )
+  .bind(view_id)
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(res)
+}
+
+/// Returns the cached HTML body and text summary of the published page, if it was rendered since
+/// it was last published.
+pub async fn select_published_collab_rendered_html<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<Option<(String, String)>, AppError> {
+  let res = sqlx::query_as::<_, (Option<String>, Option<String>)>(
+    r#"
+      SELECT rendered_html, rendered_summary
+      FROM af_published_collab
+      WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
+        AND unpublished_at IS NULL
+        AND publish_name = $2
+    "#,
+  )
+  .bind(publish_namespace)
+  .bind(publish_name)
+  .fetch_one(executor)
+  .await?;
+
+  Ok(match res {
+    (Some(html), Some(summary)) => Some((html, summary)),
+    _ => None,
+  })
+}
+
+pub async fn update_published_collab_rendered_html<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  publish_namespace: &str,
+  publish_name: &str,
+  rendered_html: &str,
+  rendered_summary: &str,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_published_collab
+      SET rendered_html = $3, rendered_summary = $4
+      WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
+        AND unpublished_at IS NULL
+        AND publish_name = $2
+    "#,
+  )
+  .bind(publish_namespace)
+  .bind(publish_name)
+  .bind(rendered_html)
+  .bind(rendered_summary)
+  .execute(executor)
+  .await?;
+
+  Ok(())
+}
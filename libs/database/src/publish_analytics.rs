@@ -0,0 +1,131 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{
+  AFPublishedDailyCountRow, AFPublishedReferrerCountRow, AFPublishedViewDailyCountRow,
+};
+
+pub async fn insert_published_view_event<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  visitor_hash: &str,
+  referrer_domain: Option<&str>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_published_view_event (workspace_id, view_id, visitor_hash, referrer_domain)
+      VALUES ($1, $2, $3, $4)
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(visitor_hash)
+  .bind(referrer_domain)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Daily views and unique visitors of every published page of the workspace, within `[from, to)`.
+pub async fn select_published_view_daily_counts(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+) -> Result<Vec<AFPublishedViewDailyCountRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFPublishedViewDailyCountRow>(
+    r#"
+      SELECT
+        view_id,
+        (created_at AT TIME ZONE 'UTC')::date AS date,
+        COUNT(*) AS views,
+        COUNT(DISTINCT visitor_hash) AS unique_visitors
+      FROM af_published_view_event
+      WHERE workspace_id = $1
+        AND created_at >= $2
+        AND created_at < $3
+      GROUP BY view_id, date
+      ORDER BY view_id, date
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(from)
+  .bind(to)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Daily views and unique visitors across all published pages of the workspace, within
+/// `[from, to)`. A visitor reading several pages on the same day is counted once.
+pub async fn select_published_namespace_daily_counts(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+) -> Result<Vec<AFPublishedDailyCountRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFPublishedDailyCountRow>(
+    r#"
+      SELECT
+        (created_at AT TIME ZONE 'UTC')::date AS date,
+        COUNT(*) AS views,
+        COUNT(DISTINCT visitor_hash) AS unique_visitors
+      FROM af_published_view_event
+      WHERE workspace_id = $1
+        AND created_at >= $2
+        AND created_at < $3
+      GROUP BY date
+      ORDER BY date
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(from)
+  .bind(to)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_published_top_referrers(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+  limit: i64,
+) -> Result<Vec<AFPublishedReferrerCountRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFPublishedReferrerCountRow>(
+    r#"
+      SELECT referrer_domain, COUNT(*) AS views
+      FROM af_published_view_event
+      WHERE workspace_id = $1
+        AND created_at >= $2
+        AND created_at < $3
+        AND referrer_domain IS NOT NULL
+      GROUP BY referrer_domain
+      ORDER BY views DESC, referrer_domain
+      LIMIT $4
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(from)
+  .bind(to)
+  .bind(limit)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Removes the view events recorded before `before`, returns the number of removed events.
+pub async fn delete_published_view_events_before(
+  pg_pool: &PgPool,
+  before: DateTime<Utc>,
+) -> Result<u64, AppError> {
+  let res = sqlx::query("DELETE FROM af_published_view_event WHERE created_at < $1")
+    .bind(before)
+    .execute(pg_pool)
+    .await?;
+  Ok(res.rows_affected())
+}
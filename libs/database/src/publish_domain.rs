@@ -0,0 +1,197 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::pg_row::AFPublishCustomDomainRow;
+
+/// Claims the domain for the workspace. Claiming a domain again returns the existing record, so
+/// that the verification token stays stable.
+pub async fn insert_publish_custom_domain(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  domain: &str,
+  verification_token: &str,
+) -> Result<AFPublishCustomDomainRow, AppError> {
+  let row = sqlx::query_as::<_, AFPublishCustomDomainRow>(
+    r#"
+      INSERT INTO af_publish_custom_domain (workspace_id, domain, verification_token)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id, domain) DO UPDATE SET domain = EXCLUDED.domain
+      RETURNING workspace_id, domain, verification_token, verified_at, created_at
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(domain)
+  .bind(verification_token)
+  .fetch_one(pg_pool)
+  .await?;
+  Ok(row)
+}
+
+pub async fn select_publish_custom_domains(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFPublishCustomDomainRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFPublishCustomDomainRow>(
+    r#"
+      SELECT workspace_id, domain, verification_token, verified_at, created_at
+      FROM af_publish_custom_domain
+      WHERE workspace_id = $1
+      ORDER BY created_at
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_publish_custom_domain(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<AFPublishCustomDomainRow, AppError> {
+  let row = sqlx::query_as::<_, AFPublishCustomDomainRow>(
+    r#"
+      SELECT workspace_id, domain, verification_token, verified_at, created_at
+      FROM af_publish_custom_domain
+      WHERE workspace_id = $1 AND domain = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(domain)
+  .fetch_optional(pg_pool)
+  .await?;
+  row.ok_or_else(|| AppError::RecordNotFound(format!("Custom domain {} not found", domain)))
+}
+
+pub async fn update_publish_custom_domain_verified(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<AFPublishCustomDomainRow, AppError> {
+  let row = sqlx::query_as::<_, AFPublishCustomDomainRow>(
+    r#"
+      UPDATE af_publish_custom_domain
+      SET verified_at = COALESCE(verified_at, NOW())
+      WHERE workspace_id = $1 AND domain = $2
+      RETURNING workspace_id, domain, verification_token, verified_at, created_at
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(domain)
+  .fetch_one(pg_pool)
+  .await
+  .map_err(|err| match &err {
+    // Another workspace verified the domain since it was checked
+    sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+      AppError::PublishDomainAlreadyTaken(format!(
+        "{} is already used by another workspace",
+        domain
+      ))
+    },
+    _ => AppError::from(err),
+  })?;
+  Ok(row)
+}
+
+/// Returns the verified domains last checked before the given time, least recently checked first.
+pub async fn select_publish_custom_domains_verified_before(
+  pg_pool: &PgPool,
+  before: DateTime<Utc>,
+  limit: i64,
+) -> Result<Vec<AFPublishCustomDomainRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFPublishCustomDomainRow>(
+    r#"
+      SELECT workspace_id, domain, verification_token, verified_at, created_at
+      FROM af_publish_custom_domain
+      WHERE verified_at < $1
+      ORDER BY verified_at
+      LIMIT $2
+    "#,
+  )
+  .bind(before)
+  .bind(limit)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Records the result of checking the TXT record of a verified domain again: the verification
+/// time is renewed when the record was found, and cleared otherwise.
+pub async fn update_publish_custom_domain_reverified(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  domain: &str,
+  found: bool,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_publish_custom_domain
+      SET verified_at = CASE WHEN $3 THEN NOW() ELSE NULL END
+      WHERE workspace_id = $1 AND domain = $2 AND verified_at IS NOT NULL
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(domain)
+  .bind(found)
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_verified_publish_domains(pg_pool: &PgPool) -> Result<Vec<String>, AppError> {
+  let domains = sqlx::query_scalar::<_, String>(
+    r#"
+      SELECT domain
+      FROM af_publish_custom_domain
+      WHERE verified_at IS NOT NULL
+    "#,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(domains)
+}
+
+pub async fn delete_publish_custom_domain(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<(), AppError> {
+  let res = sqlx::query(
+    r#"
+      DELETE FROM af_publish_custom_domain
+      WHERE workspace_id = $1 AND domain = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(domain)
+  .execute(pg_pool)
+  .await?;
+  if res.rows_affected() == 0 {
+    return Err(AppError::RecordNotFound(format!(
+      "Custom domain {} not found",
+      domain
+    )));
+  }
+  Ok(())
+}
+
+/// Returns the workspace that verified the domain, if any.
+pub async fn select_workspace_id_for_verified_publish_domain(
+  pg_pool: &PgPool,
+  domain: &str,
+) -> Result<Option<Uuid>, AppError> {
+  let workspace_id = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      SELECT workspace_id
+      FROM af_publish_custom_domain
+      WHERE domain = $1 AND verified_at IS NOT NULL
+    "#,
+  )
+  .bind(domain)
+  .fetch_optional(pg_pool)
+  .await?;
+  Ok(workspace_id)
+}
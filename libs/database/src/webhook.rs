@@ -0,0 +1,270 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{AFPendingWebhookDeliveryRow, AFWebhookDeliveryRow, AFWebhookRow};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WebhookDeliveryState {
+  Pending = 0,
+  Delivered = 1,
+  Failed = 2,
+}
+
+impl From<i16> for WebhookDeliveryState {
+  fn from(val: i16) -> Self {
+    match val {
+      1 => WebhookDeliveryState::Delivered,
+      2 => WebhookDeliveryState::Failed,
+      _ => WebhookDeliveryState::Pending,
+    }
+  }
+}
+
+pub async fn insert_webhook(
+  pg_pool: &PgPool,
+  webhook_id: &Uuid,
+  workspace_id: &Uuid,
+  url: &str,
+  secret: &str,
+  events: &[String],
+  created_by: i64,
+) -> Result<AFWebhookRow, AppError> {
+  let webhook = sqlx::query_as::<_, AFWebhookRow>(
+    r#"
+      INSERT INTO af_workspace_webhook (webhook_id, workspace_id, url, secret, events, created_by)
+      VALUES ($1, $2, $3, $4, $5, $6)
+      RETURNING *
+    "#,
+  )
+  .bind(webhook_id)
+  .bind(workspace_id)
+  .bind(url)
+  .bind(secret)
+  .bind(events)
+  .bind(created_by)
+  .fetch_one(pg_pool)
+  .await?;
+  Ok(webhook)
+}
+
+pub async fn select_webhooks(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFWebhookRow>, AppError> {
+  let webhooks = sqlx::query_as::<_, AFWebhookRow>(
+    r#"
+      SELECT * FROM af_workspace_webhook
+      WHERE workspace_id = $1
+      ORDER BY created_at
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(webhooks)
+}
+
+pub async fn select_webhook(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  webhook_id: &Uuid,
+) -> Result<AFWebhookRow, AppError> {
+  sqlx::query_as::<_, AFWebhookRow>(
+    "SELECT * FROM af_workspace_webhook WHERE workspace_id = $1 AND webhook_id = $2",
+  )
+  .bind(workspace_id)
+  .bind(webhook_id)
+  .fetch_optional(pg_pool)
+  .await?
+  .ok_or_else(|| AppError::RecordNotFound(format!("webhook {} not found", webhook_id)))
+}
+
+/// Updates the given columns of the webhook. `None` leaves the column unchanged.
+#[allow(clippy::too_many_arguments)]
+pub async fn update_webhook(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  webhook_id: &Uuid,
+  url: Option<&str>,
+  secret: Option<&str>,
+  events: Option<&[String]>,
+  enabled: Option<bool>,
+) -> Result<AFWebhookRow, AppError> {
+  sqlx::query_as::<_, AFWebhookRow>(
+    r#"
+      UPDATE af_workspace_webhook
+      SET url = COALESCE($3, url),
+          secret = COALESCE($4, secret),
+          events = COALESCE($5, events),
+          enabled = COALESCE($6, enabled),
+          updated_at = NOW()
+      WHERE workspace_id = $1 AND webhook_id = $2
+      RETURNING *
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(webhook_id)
+  .bind(url)
+  .bind(secret)
+  .bind(events)
+  .bind(enabled)
+  .fetch_optional(pg_pool)
+  .await?
+  .ok_or_else(|| AppError::RecordNotFound(format!("webhook {} not found", webhook_id)))
+}
+
+pub async fn delete_webhook(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  webhook_id: &Uuid,
+) -> Result<(), AppError> {
+  let res =
+    sqlx::query("DELETE FROM af_workspace_webhook WHERE workspace_id = $1 AND webhook_id = $2")
+      .bind(workspace_id)
+      .bind(webhook_id)
+      .execute(pg_pool)
+      .await?;
+  if res.rows_affected() == 0 {
+    return Err(AppError::RecordNotFound(format!(
+      "webhook {} not found",
+      webhook_id
+    )));
+  }
+  Ok(())
+}
+
+/// Queues a delivery of the event for every enabled webhook of the workspace that subscribes to
+/// it. Returns the number of queued deliveries.
+pub async fn insert_webhook_deliveries<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  event: &str,
+  payload: &serde_json::Value,
+) -> Result<u64, AppError> {
+  let res = sqlx::query(
+    r#"
+      INSERT INTO af_webhook_delivery (webhook_id, workspace_id, event, payload)
+      SELECT webhook_id, workspace_id, $2, $3
+      FROM af_workspace_webhook
+      WHERE workspace_id = $1
+        AND enabled
+        AND (CARDINALITY(events) = 0 OR $2 = ANY(events))
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(event)
+  .bind(payload)
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected())
+}
+
+/// Claims up to `limit` deliveries that are due. A claimed delivery is not returned again for
+/// `lease_secs`, so that several server instances can share the queue, and so that a delivery
+/// is retried if the instance that claimed it stops before recording the result.
+pub async fn claim_pending_webhook_deliveries(
+  pg_pool: &PgPool,
+  limit: i64,
+  lease_secs: i64,
+) -> Result<Vec<AFPendingWebhookDeliveryRow>, AppError> {
+  let deliveries = sqlx::query_as::<_, AFPendingWebhookDeliveryRow>(
+    r#"
+      WITH due AS (
+        SELECT d.delivery_id
+        FROM af_webhook_delivery d
+        JOIN af_workspace_webhook w ON w.webhook_id = d.webhook_id
+        WHERE d.status = $1 AND w.enabled AND d.next_attempt_at <= NOW()
+        ORDER BY d.next_attempt_at
+        LIMIT $2
+        FOR UPDATE OF d SKIP LOCKED
+      )
+      UPDATE af_webhook_delivery d
+      SET next_attempt_at = NOW() + $3 * INTERVAL '1 second'
+      FROM due, af_workspace_webhook w
+      WHERE d.delivery_id = due.delivery_id AND w.webhook_id = d.webhook_id
+      RETURNING d.delivery_id, d.webhook_id, d.event, d.payload, d.attempts, w.url, w.secret
+    "#,
+  )
+  .bind(WebhookDeliveryState::Pending as i16)
+  .bind(limit)
+  .bind(lease_secs as f64)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(deliveries)
+}
+
+/// Records the result of a delivery attempt. When `retry_in_secs` is set, the delivery stays
+/// pending and is attempted again after the delay. Otherwise the delivery ends in `state`.
+pub async fn update_webhook_delivery_attempt(
+  pg_pool: &PgPool,
+  delivery_id: &Uuid,
+  state: WebhookDeliveryState,
+  response_status: Option<i32>,
+  error: Option<&str>,
+  retry_in_secs: Option<i64>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_webhook_delivery
+      SET status = $2,
+          attempts = attempts + 1,
+          response_status = $3,
+          error = $4,
+          next_attempt_at = NOW() + COALESCE($5, 0) * INTERVAL '1 second',
+          delivered_at = CASE WHEN $2 = 1 THEN NOW() ELSE delivered_at END
+      WHERE delivery_id = $1
+    "#,
+  )
+  .bind(delivery_id)
+  .bind(state as i16)
+  .bind(response_status)
+  .bind(error)
+  .bind(retry_in_secs.map(|secs| secs as f64))
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
+
+/// Deletes the delivered and failed deliveries created before `before`. Returns the number of
+/// deleted deliveries.
+pub async fn delete_webhook_deliveries_before(
+  pg_pool: &PgPool,
+  before: DateTime<Utc>,
+) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    r#"
+      DELETE FROM af_webhook_delivery
+      WHERE status <> $1 AND created_at < $2
+    "#,
+  )
+  .bind(WebhookDeliveryState::Pending as i16)
+  .bind(before)
+  .execute(pg_pool)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+/// Returns the deliveries of the webhook, newest first.
+pub async fn select_webhook_deliveries(
+  pg_pool: &PgPool,
+  webhook_id: &Uuid,
+  limit: i64,
+  offset: i64,
+) -> Result<Vec<AFWebhookDeliveryRow>, AppError> {
+  let deliveries = sqlx::query_as::<_, AFWebhookDeliveryRow>(
+    r#"
+      SELECT * FROM af_webhook_delivery
+      WHERE webhook_id = $1
+      ORDER BY created_at DESC
+      LIMIT $2 OFFSET $3
+    "#,
+  )
+  .bind(webhook_id)
+  .bind(limit)
+  .bind(offset)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(deliveries)
+}
@@ -12,7 +12,7 @@ use tracing::{event, instrument};
 use uuid::Uuid;
 
 use crate::pg_row::{
-  AFGlobalCommentRow, AFImportTask, AFPermissionRow, AFReactionRow, AFUserProfileRow,
+  AFExportTask, AFGlobalCommentRow, AFImportTask, AFPermissionRow, AFReactionRow, AFUserProfileRow,
   AFWebUserWithEmailColumn, AFWorkspaceInvitationMinimal, AFWorkspaceMemberPermRow,
   AFWorkspaceMemberRow, AFWorkspaceRow, AFWorkspaceRowWithMemberCountAndRole,
 };
@@ -417,7 +417,7 @@ pub async fn select_workspace_invitation_for_user(
 #[inline]
 #[instrument(level = "trace", skip(pool, email, role), err)]
 pub async fn upsert_workspace_member(
-  pool: &PgPool,
+  txn: &mut Transaction<'_, sqlx::Postgres>,
   workspace_id: &Uuid,
   email: &str,
   role: AFRole,
@@ -426,7 +426,7 @@ pub async fn upsert_workspace_member(
     tracing::Level::TRACE,
     "update workspace member: workspace_id:{}, uid {:?}, role:{:?}",
     workspace_id,
-    select_uid_from_email(pool, email).await,
+    select_uid_from_email(txn.deref_mut(), email).await,
     role
   );
 
@@ -444,7 +444,7 @@ pub async fn upsert_workspace_member(
     workspace_id,
     email
   )
-  .execute(pool)
+  .execute(txn.deref_mut())
   .await?;
 
   Ok(())
@@ -1279,6 +1279,11 @@ pub async fn upsert_workspace_settings(
     )
     .execute(tx.deref_mut())
     .await?;
+
+    sqlx::query(r#"DELETE FROM af_collab_keyword_index WHERE workspace_id = $1"#)
+      .bind(workspace_id)
+      .execute(tx.deref_mut())
+      .await?;
   }
 
   Ok(())
@@ -1658,6 +1663,162 @@ pub async fn insert_import_task(
   Ok(())
 }
 
+#[derive(Clone, Debug)]
+pub enum ExportTaskState {
+  Pending = 0,
+  Completed = 1,
+  Failed = 2,
+}
+
+impl From<i16> for ExportTaskState {
+  fn from(val: i16) -> Self {
+    match val {
+      1 => ExportTaskState::Completed,
+      2 => ExportTaskState::Failed,
+      _ => ExportTaskState::Pending,
+    }
+  }
+}
+
+pub async fn insert_export_task(
+  task_id: Uuid,
+  workspace_id: &Uuid,
+  created_by: i64,
+  pg_pool: &PgPool,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_export_task (task_id, workspace_id, created_by, status)
+      VALUES ($1, $2, $3, $4)
+    "#,
+  )
+  .bind(task_id)
+  .bind(workspace_id)
+  .bind(created_by)
+  .bind(ExportTaskState::Pending as i16)
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_export_task(
+  pg_pool: &PgPool,
+  task_id: &Uuid,
+) -> Result<AFExportTask, AppError> {
+  let export_task =
+    sqlx::query_as::<_, AFExportTask>("SELECT * FROM af_export_task WHERE task_id = $1")
+      .bind(task_id)
+      .fetch_optional(pg_pool)
+      .await?
+      .ok_or_else(|| AppError::RecordNotFound(format!("export task {} not found", task_id)))?;
+  Ok(export_task)
+}
+
+/// Returns the export tasks created by the user in the workspace, newest first.
+pub async fn select_export_tasks(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  created_by: i64,
+) -> Result<Vec<AFExportTask>, AppError> {
+  let export_tasks = sqlx::query_as::<_, AFExportTask>(
+    r#"
+      SELECT * FROM af_export_task
+      WHERE workspace_id = $1 AND created_by = $2
+      ORDER BY created_at DESC
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(created_by)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(export_tasks)
+}
+
+pub async fn num_pending_export_task(uid: i64, pg_pool: &PgPool) -> Result<i64, AppError> {
+  let count = sqlx::query_scalar::<_, i64>(
+    "SELECT COUNT(*) FROM af_export_task WHERE created_by = $1 AND status = $2",
+  )
+  .bind(uid)
+  .bind(ExportTaskState::Pending as i16)
+  .fetch_one(pg_pool)
+  .await?;
+  Ok(count)
+}
+
+/// Marks the export task as completed, recording where the archive was stored.
+pub async fn update_export_task_completed<'a, E: Executor<'a, Database = Postgres>>(
+  task_id: &Uuid,
+  s3_key: &str,
+  file_size: i64,
+  executor: E,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_export_task
+      SET status = $1, s3_key = $2, file_size = $3, updated_at = NOW()
+      WHERE task_id = $4
+    "#,
+  )
+  .bind(ExportTaskState::Completed as i16)
+  .bind(s3_key)
+  .bind(file_size)
+  .bind(task_id)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_export_task_failed<'a, E: Executor<'a, Database = Postgres>>(
+  task_id: &Uuid,
+  error: &str,
+  executor: E,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_export_task
+      SET status = $1, error = $2, updated_at = NOW()
+      WHERE task_id = $3
+    "#,
+  )
+  .bind(ExportTaskState::Failed as i16)
+  .bind(error)
+  .bind(task_id)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the completed and failed export tasks last updated before `updated_before`, oldest
+/// first.
+pub async fn select_expired_export_tasks(
+  pg_pool: &PgPool,
+  updated_before: DateTime<Utc>,
+  limit: i64,
+) -> Result<Vec<AFExportTask>, AppError> {
+  let export_tasks = sqlx::query_as::<_, AFExportTask>(
+    r#"
+      SELECT * FROM af_export_task
+      WHERE status <> $1 AND updated_at < $2
+      ORDER BY updated_at
+      LIMIT $3
+    "#,
+  )
+  .bind(ExportTaskState::Pending as i16)
+  .bind(updated_before)
+  .bind(limit)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(export_tasks)
+}
+
+pub async fn delete_export_task(pg_pool: &PgPool, task_id: &Uuid) -> Result<(), AppError> {
+  sqlx::query("DELETE FROM af_export_task WHERE task_id = $1")
+    .bind(task_id)
+    .execute(pg_pool)
+    .await?;
+  Ok(())
+}
+
 pub async fn update_import_task_metadata(
   task_id: Uuid,
   new_metadata: serde_json::Value,
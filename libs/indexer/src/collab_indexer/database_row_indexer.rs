@@ -0,0 +1,203 @@
+use crate::collab_indexer::{embed_chunks, split_text_into_chunks, Indexer};
+use crate::vector::embedder::AFEmbedder;
+use app_error::AppError;
+use appflowy_ai_client::dto::EmbeddingModel;
+use async_trait::async_trait;
+use collab::core::collab::default_client_id;
+use collab::preclude::Collab;
+use collab_database::database::DatabaseBody;
+use collab_database::database_trait::NoPersistenceDatabaseCollabService;
+use collab_database::entity::FieldType;
+use collab_database::fields::{type_option_cell_reader, Field, TypeOptionData};
+use collab_database::rows::{meta_id_from_row_id, Cell, Row, RowDetail, RowMetaKey};
+use collab_database::template::timestamp_parse::TimestampCellData;
+use database_entity::dto::{AFCollabEmbeddedChunk, AFCollabEmbeddings};
+use infra::env_util::get_env_var;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Indexes the cells of database rows. The cells only reference their fields by id, so a row
+/// can't be indexed from its collab alone: the row is first serialized into `Field: value` lines
+/// with the fields of its database (see [database_row_paragraphs]), then indexed as text.
+pub struct DatabaseRowIndexer;
+
+#[async_trait]
+impl Indexer for DatabaseRowIndexer {
+  fn create_embedded_chunks_from_collab(
+    &self,
+    collab: &Collab,
+    _model: &EmbeddingModel,
+  ) -> Result<Vec<AFCollabEmbeddedChunk>, AppError> {
+    Err(AppError::InvalidRequest(format!(
+      "database row `{}` must be indexed with the fields of its database",
+      collab.object_id()
+    )))
+  }
+
+  fn create_embedded_chunks_from_text(
+    &self,
+    object_id: Uuid,
+    paragraphs: Vec<String>,
+    model: &EmbeddingModel,
+  ) -> Result<Vec<AFCollabEmbeddedChunk>, AppError> {
+    if paragraphs.is_empty() {
+      warn!(
+        "[Embedding] No paragraphs found in database row `{}`. Skipping embedding.",
+        object_id
+      );
+      return Ok(vec![]);
+    }
+
+    let mut chunks = split_text_into_chunks(
+      object_id,
+      paragraphs,
+      model.clone(),
+      get_env_var("APPFLOWY_EMBEDDING_CHUNK_SIZE", "2000")
+        .parse::<usize>()
+        .unwrap_or(1000),
+      get_env_var("APPFLOWY_EMBEDDING_CHUNK_OVERLAP", "200")
+        .parse::<usize>()
+        .unwrap_or(200),
+    )?;
+    for chunk in chunks.iter_mut() {
+      chunk.metadata = json!({
+          "id": object_id,
+          "source": "appflowy",
+          "name": "database_row",
+      });
+    }
+    Ok(chunks)
+  }
+
+  async fn embed(
+    &self,
+    embedder: &AFEmbedder,
+    chunks: Vec<AFCollabEmbeddedChunk>,
+  ) -> Result<Option<AFCollabEmbeddings>, AppError> {
+    embed_chunks(embedder, chunks).await
+  }
+}
+
+/// A database row waiting for the fields of its database to be indexed.
+#[derive(Debug, Clone)]
+pub struct UnindexedDatabaseRow {
+  pub workspace_id: Uuid,
+  pub row: Row,
+}
+
+impl UnindexedDatabaseRow {
+  pub fn from_collab(workspace_id: Uuid, collab: &Collab) -> Option<Self> {
+    let row = RowDetail::from_collab(collab)?.row;
+    Some(Self { workspace_id, row })
+  }
+
+  pub fn row_id(&self) -> Option<Uuid> {
+    Uuid::parse_str(&self.row.id.to_string()).ok()
+  }
+
+  pub fn database_id(&self) -> Option<Uuid> {
+    Uuid::parse_str(&self.row.database_id).ok()
+  }
+
+  /// Id of the document which can be attached to the row.
+  pub fn document_id(&self) -> Option<Uuid> {
+    let row_id = self.row_id()?;
+    Uuid::parse_str(&meta_id_from_row_id(&row_id, RowMetaKey::DocumentId)).ok()
+  }
+}
+
+/// The parts of a database its row index depends on: the rows, which are removed from the index
+/// once they leave the database, and the fields, whose names are part of the indexed text.
+/// Realtime edits of a database are detected by comparing its state before and after the edits.
+#[derive(Debug, Clone, Default)]
+pub struct DatabaseIndexState {
+  row_ids: HashSet<Uuid>,
+  /// id, name and type of the fields, sorted by id
+  fields: Vec<(String, String, i64)>,
+}
+
+impl DatabaseIndexState {
+  pub fn from_collab(collab: &Collab) -> Option<Self> {
+    let body = DatabaseBody::from_collab(
+      collab,
+      Arc::new(NoPersistenceDatabaseCollabService::new(default_client_id())),
+      None,
+    )?;
+    let txn = collab.transact();
+    let row_ids = body
+      .views
+      .get_all_views(&txn)
+      .into_iter()
+      .flat_map(|view| view.row_orders)
+      .filter_map(|row_order| Uuid::parse_str(&row_order.id.to_string()).ok())
+      .collect();
+    let mut fields = body
+      .fields
+      .get_all_fields(&txn)
+      .into_iter()
+      .map(|field| (field.id, field.name, field.field_type))
+      .collect::<Vec<_>>();
+    fields.sort();
+    Some(Self { row_ids, fields })
+  }
+
+  pub fn row_ids(&self) -> impl Iterator<Item = &Uuid> {
+    self.row_ids.iter()
+  }
+
+  /// Returns the rows of `self` which are not in `after` anymore.
+  pub fn removed_rows(&self, after: &Self) -> Vec<Uuid> {
+    self.row_ids.difference(&after.row_ids).copied().collect()
+  }
+
+  /// Returns true if a field was added, removed, renamed or changed its type.
+  pub fn fields_changed(&self, after: &Self) -> bool {
+    self.fields != after.fields
+  }
+}
+
+/// Serializes the non-empty cells of a row into `Field: value` lines. The primary field comes
+/// first, so the chunks start with the title of the row.
+pub fn database_row_paragraphs(fields: &[Field], row: &Row) -> Vec<String> {
+  let mut fields = fields.iter().collect::<Vec<_>>();
+  fields.sort_by_key(|field| !field.is_primary);
+
+  let mut paragraphs = Vec::with_capacity(fields.len());
+  for field in fields {
+    let field_type = FieldType::from(field.field_type);
+    let cell = match row.cells.get(&field.id) {
+      Some(cell) => cell.clone(),
+      None => match field_type {
+        FieldType::CreatedTime => TimestampCellData::new(Some(row.created_at)).to_cell(field_type),
+        FieldType::LastEditedTime => {
+          TimestampCellData::new(Some(row.modified_at)).to_cell(field_type)
+        },
+        _ => Cell::new(),
+      },
+    };
+    let reader = type_option_cell_reader(field_type_option(field), &field_type);
+    let value = match reader.json_cell(&cell) {
+      Value::Null => continue,
+      Value::String(value) => value,
+      Value::Array(values) if values.is_empty() => continue,
+      value => value.to_string(),
+    };
+    let value = value.trim();
+    if value.is_empty() {
+      continue;
+    }
+    paragraphs.push(format!("{}: {}", field.name, value));
+  }
+  paragraphs
+}
+
+fn field_type_option(field: &Field) -> TypeOptionData {
+  let field_type = FieldType::from(field.field_type);
+  match field.get_any_type_option(field_type.type_id()) {
+    Some(type_option) => type_option.clone(),
+    None => HashMap::new(),
+  }
+}
@@ -4,7 +4,7 @@ use crate::vector::open_ai::group_paragraphs_by_max_content_len;
 use anyhow::anyhow;
 use app_error::AppError;
 use appflowy_ai_client::dto::EmbeddingModel;
-use async_openai::types::{CreateEmbeddingRequestArgs, EmbeddingInput, EncodingFormat};
+use async_openai::types::EmbeddingInput;
 use async_trait::async_trait;
 use collab::preclude::Collab;
 use collab_document::document::DocumentBody;
@@ -22,7 +22,7 @@ impl Indexer for DocumentIndexer {
   fn create_embedded_chunks_from_collab(
     &self,
     collab: &Collab,
-    model: EmbeddingModel,
+    model: &EmbeddingModel,
   ) -> Result<Vec<AFCollabEmbeddedChunk>, AppError> {
     let object_id = collab.object_id().parse()?;
     let document = DocumentBody::from_collab(collab).ok_or_else(|| {
@@ -40,7 +40,7 @@ impl Indexer for DocumentIndexer {
     &self,
     object_id: Uuid,
     paragraphs: Vec<String>,
-    model: EmbeddingModel,
+    model: &EmbeddingModel,
   ) -> Result<Vec<AFCollabEmbeddedChunk>, AppError> {
     if paragraphs.is_empty() {
       warn!(
@@ -54,7 +54,7 @@ impl Indexer for DocumentIndexer {
     split_text_into_chunks(
       object_id,
       paragraphs,
-      model,
+      model.clone(),
       get_env_var("APPFLOWY_EMBEDDING_CHUNK_SIZE", "2000")
         .parse::<usize>()
         .unwrap_or(1000),
@@ -67,58 +67,60 @@ impl Indexer for DocumentIndexer {
   async fn embed(
     &self,
     embedder: &AFEmbedder,
-    mut chunks: Vec<AFCollabEmbeddedChunk>,
+    chunks: Vec<AFCollabEmbeddedChunk>,
   ) -> Result<Option<AFCollabEmbeddings>, AppError> {
-    let mut valid_indices = Vec::new();
-    for (i, chunk) in chunks.iter().enumerate() {
-      if let Some(ref content) = chunk.content {
-        if !content.is_empty() {
-          valid_indices.push(i);
-        }
-      }
-    }
+    embed_chunks(embedder, chunks).await
+  }
+}
 
-    if valid_indices.is_empty() {
-      return Ok(None);
+/// Embeds the chunks with new content. The chunks which weren't changed since they were last
+/// embedded have no content and are kept as they are.
+pub(crate) async fn embed_chunks(
+  embedder: &AFEmbedder,
+  mut chunks: Vec<AFCollabEmbeddedChunk>,
+) -> Result<Option<AFCollabEmbeddings>, AppError> {
+  let mut valid_indices = Vec::new();
+  for (i, chunk) in chunks.iter().enumerate() {
+    if let Some(ref content) = chunk.content {
+      if !content.is_empty() {
+        valid_indices.push(i);
+      }
     }
+  }
 
-    let mut contents = Vec::with_capacity(valid_indices.len());
-    for &i in &valid_indices {
-      contents.push(chunks[i].content.as_ref().unwrap().to_owned());
-    }
+  if valid_indices.is_empty() {
+    return Ok(None);
+  }
 
-    let request = CreateEmbeddingRequestArgs::default()
-      .model(embedder.model().name())
-      .input(EmbeddingInput::StringArray(contents))
-      .encoding_format(EncodingFormat::Float)
-      .dimensions(EmbeddingModel::default_model().default_dimensions())
-      .build()
-      .map_err(|err| AppError::Unhandled(err.to_string()))?;
-
-    let resp = embedder.async_embed(request).await?;
-    if resp.data.len() != valid_indices.len() {
-      error!(
-        "[Embedding] requested {} embeddings, received {} embeddings",
-        valid_indices.len(),
-        resp.data.len()
-      );
-      return Err(AppError::Unhandled(format!(
-        "Mismatch in number of embeddings requested and received: {} vs {}",
-        valid_indices.len(),
-        resp.data.len()
-      )));
-    }
+  let mut contents = Vec::with_capacity(valid_indices.len());
+  for &i in &valid_indices {
+    contents.push(chunks[i].content.as_ref().unwrap().to_owned());
+  }
 
-    for embedding in resp.data {
-      let chunk_idx = valid_indices[embedding.index as usize];
-      chunks[chunk_idx].embedding = Some(embedding.embedding);
-    }
+  let request = embedder.create_embedding_request(EmbeddingInput::StringArray(contents))?;
+  let resp = embedder.async_embed(request).await?;
+  if resp.data.len() != valid_indices.len() {
+    error!(
+      "[Embedding] requested {} embeddings, received {} embeddings",
+      valid_indices.len(),
+      resp.data.len()
+    );
+    return Err(AppError::Unhandled(format!(
+      "Mismatch in number of embeddings requested and received: {} vs {}",
+      valid_indices.len(),
+      resp.data.len()
+    )));
+  }
 
-    Ok(Some(AFCollabEmbeddings {
-      tokens_consumed: resp.usage.total_tokens,
-      chunks,
-    }))
+  for embedding in resp.data {
+    let chunk_idx = valid_indices[embedding.index as usize];
+    chunks[chunk_idx].embedding = Some(embedding.embedding);
   }
+
+  Ok(Some(AFCollabEmbeddings {
+    tokens_consumed: resp.usage.total_tokens,
+    chunks,
+  }))
 }
 
 /// chunk_size:
@@ -135,16 +137,15 @@ pub fn split_text_into_chunks(
   chunk_size: usize,
   overlap: usize,
 ) -> Result<Vec<AFCollabEmbeddedChunk>, AppError> {
-  // we only support text embedding 3 small for now
-  debug_assert!(matches!(
-    embedding_model,
-    EmbeddingModel::TextEmbedding3Small
-  ));
-
   if paragraphs.is_empty() {
     return Ok(vec![]);
   }
 
+  // A chunk must fit in the context of the model. Tokens are at least one character long, so
+  // limiting the number of characters to the maximum number of tokens is conservative.
+  let chunk_size = chunk_size.min(embedding_model.max_token());
+  let overlap = overlap.min(chunk_size / 2);
+
   trace!(
     "[Embedding] Splitting document `{}` into chunks with chunk_size: {}, overlap: {}, paragraphs: {:?}",
     object_id,
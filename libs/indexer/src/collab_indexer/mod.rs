@@ -1,5 +1,7 @@
+mod database_row_indexer;
 mod document_indexer;
 mod provider;
 
+pub use database_row_indexer::*;
 pub use document_indexer::*;
 pub use provider::*;
@@ -1,4 +1,4 @@
-use crate::collab_indexer::DocumentIndexer;
+use crate::collab_indexer::{DatabaseRowIndexer, DocumentIndexer};
 use crate::vector::embedder::AFEmbedder;
 use app_error::AppError;
 use appflowy_ai_client::dto::EmbeddingModel;
@@ -17,14 +17,14 @@ pub trait Indexer: Send + Sync {
   fn create_embedded_chunks_from_collab(
     &self,
     collab: &Collab,
-    model: EmbeddingModel,
+    model: &EmbeddingModel,
   ) -> Result<Vec<AFCollabEmbeddedChunk>, AppError>;
 
   fn create_embedded_chunks_from_text(
     &self,
     object_id: Uuid,
     paragraphs: Vec<String>,
-    model: EmbeddingModel,
+    model: &EmbeddingModel,
   ) -> Result<Vec<AFCollabEmbeddedChunk>, AppError>;
 
   async fn embed(
@@ -50,6 +50,7 @@ impl IndexerProvider {
     info!("Indexer is enabled: {}", enabled);
     if enabled {
       cache.insert(CollabType::Document, Arc::new(DocumentIndexer));
+      cache.insert(CollabType::DatabaseRow, Arc::new(DatabaseRowIndexer));
     }
     Arc::new(Self {
       indexer_cache: cache,
@@ -15,16 +15,24 @@ pub struct EmbeddingRecord {
   pub object_id: Uuid,
   pub collab_type: CollabType,
   pub tokens_used: u32,
+  /// Name of the model which generated the embeddings of the chunks.
+  pub embedding_model: String,
   pub chunks: Vec<AFCollabEmbeddedChunk>,
 }
 
 impl EmbeddingRecord {
-  pub fn empty(workspace_id: Uuid, object_id: Uuid, collab_type: CollabType) -> Self {
+  pub fn empty(
+    workspace_id: Uuid,
+    object_id: Uuid,
+    collab_type: CollabType,
+    embedding_model: String,
+  ) -> Self {
     Self {
       workspace_id,
       object_id,
       collab_type,
       tokens_used: 0,
+      embedding_model,
       chunks: vec![],
     }
   }
@@ -1,18 +1,27 @@
-use crate::collab_indexer::IndexerProvider;
+use crate::collab_indexer::{
+  database_row_paragraphs, DatabaseIndexState, IndexerProvider, UnindexedDatabaseRow,
+};
 use crate::entity::EmbeddingRecord;
 use crate::metrics::EmbeddingMetrics;
 use crate::queue::add_background_embed_task;
-use crate::vector::embedder::AFEmbedder;
-use crate::vector::open_ai;
+use crate::vector::embedder::{AFEmbedder, EmbedderConfig};
 use app_error::AppError;
-use async_openai::config::{AzureConfig, OpenAIConfig};
-use async_openai::types::{CreateEmbeddingRequest, CreateEmbeddingResponse};
+use appflowy_ai_client::dto::EmbeddingModel;
+use async_openai::types::{CreateEmbeddingResponse, EmbeddingInput};
+use collab::core::collab::{default_client_id, CollabOptions, DataSource};
+use collab::core::origin::CollabOrigin;
 use collab::preclude::Collab;
+use collab_database::database::DatabaseBody;
+use collab_database::database_trait::NoPersistenceDatabaseCollabService;
+use collab_database::fields::Field;
 use collab_document::document::DocumentBody;
 use collab_entity::CollabType;
-use database::collab::CollabStore;
+use database::collab::{CollabStore, GetCollabOrigin};
 use database::index::{
-  get_collab_embedding_fragment_ids, update_collab_indexed_at, upsert_collab_embeddings,
+  create_embedding_similarity_index, delete_database_row_index, get_collab_embedding_fragment_ids,
+  select_collabs_with_stale_embeddings, select_embedding_model_stats, update_collab_indexed_at,
+  upsert_collab_embeddings, upsert_collab_keyword_index, upsert_database_row_index,
+  EmbeddingModelStats,
 };
 use database::workspace::select_workspace_settings;
 use infra::env_util::get_env_var;
@@ -20,7 +29,7 @@ use redis::aio::ConnectionManager;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use std::cmp::max;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::ops::DerefMut;
 use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
@@ -33,6 +42,9 @@ use tokio::time::timeout;
 use tracing::{debug, error, info, instrument, trace, warn};
 use uuid::Uuid;
 
+/// Collab types whose embeddings [IndexerScheduler::reindex_stale_embeddings] can build again.
+const REINDEXABLE_COLLAB_TYPES: [CollabType; 2] = [CollabType::Document, CollabType::DatabaseRow];
+
 pub struct IndexerScheduler {
   pub(crate) indexer_provider: Arc<IndexerProvider>,
   pub(crate) pg_pool: PgPool,
@@ -48,8 +60,7 @@ pub struct IndexerScheduler {
 #[derive(Debug)]
 pub struct IndexerConfiguration {
   pub enable: bool,
-  pub open_ai_config: Option<OpenAIConfig>,
-  pub azure_ai_config: Option<AzureConfig>,
+  pub embedder_config: Option<EmbedderConfig>,
   /// High watermark for the number of embeddings that can be buffered before being written to the database.
   pub embedding_buffer_size: usize,
 }
@@ -92,6 +103,13 @@ impl IndexerScheduler {
 
     let latest_write_embedding_err = Arc::new(TokioRwLock::new(None));
     if this.index_enabled() {
+      if let Ok(embedder) = this.create_embedder() {
+        tokio::spawn(ensure_similarity_index(
+          this.pg_pool.clone(),
+          embedder.model(),
+        ));
+      }
+
       tokio::spawn(generate_embeddings_loop(
         generate_embedding_rx,
         Arc::downgrade(&this),
@@ -111,39 +129,238 @@ impl IndexerScheduler {
   }
 
   fn index_enabled(&self) -> bool {
+    self.config.enable && self.config.embedder_config.is_some()
+  }
+
+  /// The keyword index doesn't depend on an AI service, so it is kept up to date whenever
+  /// indexing is enabled, even if no embedder is configured.
+  fn keyword_index_enabled(&self) -> bool {
     self.config.enable
-      && (self.config.open_ai_config.is_some() || self.config.azure_ai_config.is_some())
   }
 
   pub fn is_indexing_enabled(&self, collab_type: CollabType) -> bool {
     self.indexer_provider.is_indexing_enabled(collab_type)
   }
 
-  pub(crate) fn create_embedder(&self) -> Result<AFEmbedder, AppError> {
-    if let Some(config) = &self.config.azure_ai_config {
-      return Ok(AFEmbedder::AzureOpenAI(open_ai::AzureOpenAIEmbedder::new(
-        config.clone(),
-      )));
+  /// Returns true if the search queries can be embedded, ie. vector search is available.
+  pub fn is_embedder_available(&self) -> bool {
+    self.create_embedder().is_ok()
+  }
+
+  /// Writes the plain text of the collabs to the keyword index in the background.
+  fn index_keywords(&self, pending_collabs: &[UnindexedCollabTask]) {
+    if !self.keyword_index_enabled() {
+      return;
     }
 
-    if let Some(config) = &self.config.open_ai_config {
-      return Ok(AFEmbedder::OpenAI(open_ai::OpenAIEmbedder::new(
-        config.clone(),
-      )));
+    let records = pending_collabs
+      .iter()
+      .filter(|collab| !collab.data.is_empty())
+      .map(|collab| {
+        (
+          collab.workspace_id,
+          collab.object_id,
+          collab.collab_type,
+          collab.data.to_plain_text(),
+        )
+      })
+      .collect::<Vec<_>>();
+    if records.is_empty() {
+      return;
     }
 
-    Err(AppError::AIServiceUnavailable(
-      "No embedder available".to_string(),
-    ))
+    let pg_pool = self.pg_pool.clone();
+    tokio::spawn(async move {
+      // Workspaces can opt out of search indexing, which covers the keyword index as well.
+      let mut indexable_workspaces = HashMap::new();
+      for (workspace_id, object_id, collab_type, content) in records {
+        let indexable = match indexable_workspaces.get(&workspace_id) {
+          Some(indexable) => *indexable,
+          None => {
+            let indexable = match select_workspace_settings(&pg_pool, &workspace_id).await {
+              Ok(settings) => settings.is_none_or(|settings| !settings.disable_search_indexing),
+              Err(err) => {
+                error!(
+                  "[Keyword] failed to get settings of workspace {}: {}",
+                  workspace_id, err
+                );
+                false
+              },
+            };
+            indexable_workspaces.insert(workspace_id, indexable);
+            indexable
+          },
+        };
+        if !indexable {
+          continue;
+        }
+
+        if let Err(err) =
+          upsert_collab_keyword_index(&pg_pool, &workspace_id, &object_id, collab_type, &content)
+            .await
+        {
+          error!(
+            "[Keyword] failed to index collab {}/{}: {}",
+            workspace_id, object_id, err
+          );
+        }
+      }
+    });
+  }
+
+  pub(crate) fn create_embedder(&self) -> Result<AFEmbedder, AppError> {
+    self
+      .config
+      .embedder_config
+      .as_ref()
+      .map(|config| config.create_embedder())
+      .ok_or_else(|| AppError::AIServiceUnavailable("No embedder available".to_string()))
   }
 
+  /// Embeds the search query with the configured model. The model is returned along with the
+  /// embeddings, so that only the documents embedded with the same model are searched.
   pub async fn create_search_embeddings(
     &self,
-    request: CreateEmbeddingRequest,
-  ) -> Result<CreateEmbeddingResponse, AppError> {
+    input: EmbeddingInput,
+  ) -> Result<(EmbeddingModel, CreateEmbeddingResponse), AppError> {
     let embedder = self.create_embedder()?;
+    let request = embedder.create_embedding_request(input)?;
     let embeddings = embedder.async_embed(request).await?;
-    Ok(embeddings)
+    Ok((embedder.model(), embeddings))
+  }
+
+  /// Returns the model the collabs are embedded with.
+  pub fn embedding_model(&self) -> Result<EmbeddingModel, AppError> {
+    Ok(self.create_embedder()?.model())
+  }
+
+  /// Counts the collabs of the workspace embedded with the configured model, and the ones which
+  /// were embedded with another model and have to be embedded again.
+  pub async fn embedding_model_stats(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<(EmbeddingModel, EmbeddingModelStats), AppError> {
+    let model = self.create_embedder()?.model();
+    let stats = select_embedding_model_stats(&self.pg_pool, workspace_id, model.name()).await?;
+    Ok((model, stats))
+  }
+
+  /// Queues the collabs of the workspace which were embedded with another model than the
+  /// configured one, eg. after the embedding provider changed. Their existing embeddings are
+  /// ignored by the search until they are replaced. Returns the number of queued collabs.
+  pub async fn reindex_stale_embeddings(&self, workspace_id: &Uuid) -> Result<usize, AppError> {
+    if !self.index_enabled() {
+      return Err(AppError::AIServiceUnavailable(
+        "Embedding is not enabled".to_string(),
+      ));
+    }
+
+    let model = self.create_embedder()?.model();
+    let limit = get_env_var("APPFLOWY_INDEXER_REINDEX_BATCH_SIZE", "1000")
+      .parse::<i64>()
+      .unwrap_or(1000);
+    let collab_types = REINDEXABLE_COLLAB_TYPES
+      .iter()
+      .copied()
+      .filter(|collab_type| self.is_indexing_enabled(*collab_type))
+      .collect::<Vec<_>>();
+    let collab_ids = select_collabs_with_stale_embeddings(
+      &self.pg_pool,
+      workspace_id,
+      model.name(),
+      &collab_types,
+      limit,
+    )
+    .await?;
+
+    let mut pending_collabs = Vec::with_capacity(collab_ids.len());
+    let mut fields_by_database: HashMap<Uuid, Vec<Field>> = HashMap::new();
+    for collab_id in collab_ids {
+      let collab = match self
+        .storage
+        .get_full_encode_collab(
+          GetCollabOrigin::Server,
+          &collab_id.workspace_id,
+          &collab_id.object_id,
+          collab_id.collab_type,
+        )
+        .await
+      {
+        Ok(collab) => collab.encoded_collab,
+        Err(err) => {
+          warn!(
+            "[Embedding] failed to load collab {} for reindexing: {}",
+            collab_id.object_id, err
+          );
+          continue;
+        },
+      };
+
+      let options = CollabOptions::new(collab_id.object_id.to_string(), default_client_id())
+        .with_data_source(DataSource::DocStateV1(collab.doc_state.into()));
+      let collab = match Collab::new_with_options(CollabOrigin::Empty, options) {
+        Ok(collab) => collab,
+        Err(err) => {
+          warn!(
+            "[Embedding] failed to decode collab {} for reindexing: {}",
+            collab_id.object_id, err
+          );
+          continue;
+        },
+      };
+
+      let paragraphs = match collab_id.collab_type {
+        CollabType::Document => DocumentBody::from_collab(&collab)
+          .map(|body| body.to_plain_text(collab.transact()))
+          .unwrap_or_default(),
+        CollabType::DatabaseRow => {
+          let Some(row) = UnindexedDatabaseRow::from_collab(collab_id.workspace_id, &collab) else {
+            continue;
+          };
+          let Some(database_id) = row.database_id() else {
+            continue;
+          };
+          if !fields_by_database.contains_key(&database_id) {
+            match self
+              .get_database_fields(collab_id.workspace_id, database_id)
+              .await
+            {
+              Ok(fields) => {
+                fields_by_database.insert(database_id, fields);
+              },
+              Err(err) => {
+                warn!(
+                  "[Embedding] failed to get fields of database {}: {}",
+                  database_id, err
+                );
+                continue;
+              },
+            }
+          }
+          database_row_paragraphs(&fields_by_database[&database_id], &row.row)
+        },
+        _ => continue,
+      };
+      if paragraphs.is_empty() {
+        continue;
+      }
+      pending_collabs.push(UnindexedCollabTask::new(
+        collab_id.workspace_id,
+        collab_id.object_id,
+        collab_id.collab_type,
+        UnindexedData::Paragraphs(paragraphs),
+      ));
+    }
+
+    let count = pending_collabs.len();
+    info!(
+      "[Embedding] reindexing {} collabs of workspace {} with {}",
+      count, workspace_id, model
+    );
+    if count > 0 {
+      self.embed_in_background(pending_collabs)?;
+    }
+    Ok(count)
   }
 
   pub fn embed_in_background(
@@ -184,10 +401,6 @@ impl IndexerScheduler {
     pending_collab: UnindexedCollabTask,
     background: bool,
   ) -> Result<(), AppError> {
-    if !self.index_enabled() {
-      return Ok(());
-    }
-
     let indexer = self
       .indexer_provider
       .indexer_for(pending_collab.collab_type);
@@ -195,6 +408,11 @@ impl IndexerScheduler {
       return Ok(());
     }
 
+    self.index_keywords(std::slice::from_ref(&pending_collab));
+    if !self.index_enabled() {
+      return Ok(());
+    }
+
     if background {
       let _ = self.embed_in_background(vec![pending_collab]);
     } else {
@@ -208,7 +426,7 @@ impl IndexerScheduler {
     &self,
     mut pending_collabs: Vec<UnindexedCollabTask>,
   ) -> Result<(), AppError> {
-    if !self.index_enabled() {
+    if !self.keyword_index_enabled() {
       return Ok(());
     }
 
@@ -217,12 +435,238 @@ impl IndexerScheduler {
       return Ok(());
     }
 
+    self.index_keywords(&pending_collabs);
+    if !self.index_enabled() {
+      return Ok(());
+    }
+
     info!("indexing {} collabs in background", pending_collabs.len());
     let _ = self.embed_in_background(pending_collabs);
 
     Ok(())
   }
 
+  /// Indexes the rows with the fields of their databases in the background. The rows are linked
+  /// to their database, so that search results can be resolved to a view of the database.
+  pub fn index_database_rows(self: &Arc<Self>, rows: Vec<UnindexedDatabaseRow>) {
+    if !self.keyword_index_enabled() || !self.is_indexing_enabled(CollabType::DatabaseRow) {
+      return;
+    }
+    if rows.is_empty() {
+      return;
+    }
+
+    let mut rows_by_database: HashMap<(Uuid, Uuid), Vec<UnindexedDatabaseRow>> = HashMap::new();
+    for row in rows {
+      match row.database_id() {
+        Some(database_id) => rows_by_database
+          .entry((row.workspace_id, database_id))
+          .or_default()
+          .push(row),
+        None => warn!("[Embedding] database row {} has no database", row.row.id),
+      }
+    }
+
+    let scheduler = self.clone();
+    tokio::spawn(async move {
+      let mut indexable_workspaces = HashMap::new();
+      for ((workspace_id, database_id), rows) in rows_by_database {
+        let indexable = match indexable_workspaces.get(&workspace_id) {
+          Some(indexable) => *indexable,
+          None => {
+            let indexable = scheduler
+              .can_index_workspace(&workspace_id)
+              .await
+              .unwrap_or_else(|err| {
+                error!(
+                  "[Embedding] failed to get settings of workspace {}: {}",
+                  workspace_id, err
+                );
+                false
+              });
+            indexable_workspaces.insert(workspace_id, indexable);
+            indexable
+          },
+        };
+        if !indexable {
+          continue;
+        }
+
+        let fields = match scheduler
+          .get_database_fields(workspace_id, database_id)
+          .await
+        {
+          Ok(fields) => fields,
+          Err(err) => {
+            warn!(
+              "[Embedding] failed to get fields of database {}: {}",
+              database_id, err
+            );
+            continue;
+          },
+        };
+
+        let mut pending_collabs = Vec::with_capacity(rows.len());
+        for row in rows {
+          let Some(row_id) = row.row_id() else {
+            continue;
+          };
+          let object_ids = [Some(row_id), row.document_id()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+          if let Err(err) = upsert_database_row_index(
+            &scheduler.pg_pool,
+            &workspace_id,
+            &database_id,
+            &row_id,
+            &object_ids,
+          )
+          .await
+          {
+            error!(
+              "[Embedding] failed to link row {} to database {}: {}",
+              row_id, database_id, err
+            );
+          }
+
+          let paragraphs = database_row_paragraphs(&fields, &row.row);
+          if !paragraphs.is_empty() {
+            pending_collabs.push(UnindexedCollabTask::new(
+              workspace_id,
+              row_id,
+              CollabType::DatabaseRow,
+              UnindexedData::Paragraphs(paragraphs),
+            ));
+          }
+        }
+
+        if let Err(err) = scheduler.index_pending_collabs(pending_collabs) {
+          error!(
+            "[Embedding] failed to index rows of database {}: {}",
+            database_id, err
+          );
+        }
+      }
+    });
+  }
+
+  /// Keeps the row index of a database in line with the realtime edits of the database, given its
+  /// state before and after the edits. Rows removed from the database are removed from the
+  /// search, and all the rows are indexed again when the fields changed, as the field names are
+  /// part of the indexed text.
+  pub fn update_database_index(
+    self: &Arc<Self>,
+    workspace_id: Uuid,
+    database_id: Uuid,
+    before: &DatabaseIndexState,
+    after: &DatabaseIndexState,
+  ) {
+    if !self.keyword_index_enabled() {
+      return;
+    }
+    let removed_rows = before.removed_rows(after);
+    let reindexed_rows = if before.fields_changed(after) {
+      after.row_ids().copied().collect::<Vec<_>>()
+    } else {
+      vec![]
+    };
+    if removed_rows.is_empty() && reindexed_rows.is_empty() {
+      return;
+    }
+
+    let scheduler = self.clone();
+    tokio::spawn(async move {
+      if !removed_rows.is_empty() {
+        if let Err(err) =
+          delete_database_row_index(&scheduler.pg_pool, &workspace_id, &removed_rows).await
+        {
+          error!(
+            "[Embedding] failed to remove rows of database {} from the index: {}",
+            database_id, err
+          );
+        }
+      }
+
+      if reindexed_rows.is_empty() {
+        return;
+      }
+      info!(
+        "[Embedding] fields of database {} changed, reindexing {} rows",
+        database_id,
+        reindexed_rows.len()
+      );
+      let mut rows = Vec::with_capacity(reindexed_rows.len());
+      for row_id in reindexed_rows {
+        match scheduler.get_database_row(workspace_id, row_id).await {
+          Ok(Some(row)) => rows.push(row),
+          Ok(None) => {},
+          Err(err) => warn!(
+            "[Embedding] failed to load row {} for reindexing: {}",
+            row_id, err
+          ),
+        }
+      }
+      scheduler.index_database_rows(rows);
+    });
+  }
+
+  async fn get_database_row(
+    &self,
+    workspace_id: Uuid,
+    row_id: Uuid,
+  ) -> Result<Option<UnindexedDatabaseRow>, AppError> {
+    let encoded_collab = self
+      .storage
+      .get_full_encode_collab(
+        GetCollabOrigin::Server,
+        &workspace_id,
+        &row_id,
+        CollabType::DatabaseRow,
+      )
+      .await?
+      .encoded_collab;
+    let options = CollabOptions::new(row_id.to_string(), default_client_id())
+      .with_data_source(DataSource::DocStateV1(encoded_collab.doc_state.into()));
+    let collab = Collab::new_with_options(CollabOrigin::Empty, options)
+      .map_err(|err| AppError::Internal(err.into()))?;
+    Ok(UnindexedDatabaseRow::from_collab(workspace_id, &collab))
+  }
+
+  async fn get_database_fields(
+    &self,
+    workspace_id: Uuid,
+    database_id: Uuid,
+  ) -> Result<Vec<Field>, AppError> {
+    let encoded_collab = self
+      .storage
+      .get_full_encode_collab(
+        GetCollabOrigin::Server,
+        &workspace_id,
+        &database_id,
+        CollabType::Database,
+      )
+      .await?
+      .encoded_collab;
+    let options = CollabOptions::new(database_id.to_string(), default_client_id())
+      .with_data_source(DataSource::DocStateV1(encoded_collab.doc_state.into()));
+    let collab = Collab::new_with_options(CollabOrigin::Empty, options)
+      .map_err(|err| AppError::Internal(err.into()))?;
+    let body = DatabaseBody::from_collab(
+      &collab,
+      Arc::new(NoPersistenceDatabaseCollabService::new(default_client_id())),
+      None,
+    )
+    .ok_or_else(|| {
+      AppError::Internal(anyhow::anyhow!(
+        "Failed to open database body {}",
+        database_id
+      ))
+    })?;
+    let fields = body.fields.get_all_fields(&collab.transact());
+    Ok(fields)
+  }
+
   pub async fn index_collab_immediately(
     &self,
     workspace_id: Uuid,
@@ -230,7 +674,7 @@ impl IndexerScheduler {
     collab: &Collab,
     collab_type: CollabType,
   ) -> Result<(), AppError> {
-    if !self.index_enabled() {
+    if !self.keyword_index_enabled() {
       return Ok(());
     }
 
@@ -252,6 +696,7 @@ impl IndexerScheduler {
             collab_type,
             UnindexedData::Paragraphs(text),
           );
+          self.index_keywords(std::slice::from_ref(&pending));
           self.embed_immediately(pending)?;
         }
       },
@@ -264,7 +709,7 @@ impl IndexerScheduler {
   }
 
   pub async fn can_index_workspace(&self, workspace_id: &Uuid) -> Result<bool, AppError> {
-    if !self.index_enabled() {
+    if !self.keyword_index_enabled() {
       return Ok(false);
     }
 
@@ -320,13 +765,15 @@ async fn generate_embeddings_loop(
     let embedder = scheduler.create_embedder();
     match embedder {
       Ok(embedder) => {
+        let model = embedder.model();
         let params: Vec<_> = records.iter().map(|r| r.object_id).collect();
-        let existing_embeddings = get_collab_embedding_fragment_ids(&scheduler.pg_pool, params)
-          .await
-          .unwrap_or_else(|err| {
-            error!("[Embedding] failed to get existing embeddings: {}", err);
-            Default::default()
-          });
+        let existing_embeddings =
+          get_collab_embedding_fragment_ids(&scheduler.pg_pool, params, model.name())
+            .await
+            .unwrap_or_else(|err| {
+              error!("[Embedding] failed to get existing embeddings: {}", err);
+              Default::default()
+            });
         let mut join_set = JoinSet::new();
         for record in records {
           if let Some(indexer) = indexer_provider.indexer_for(record.collab_type) {
@@ -336,11 +783,7 @@ async fn generate_embeddings_loop(
               UnindexedData::Text(text) => text.split('\n').map(|s| s.to_string()).collect(),
             };
             let embedder = embedder.clone();
-            match indexer.create_embedded_chunks_from_text(
-              record.object_id,
-              paragraphs,
-              embedder.model(),
-            ) {
+            match indexer.create_embedded_chunks_from_text(record.object_id, paragraphs, &model) {
               Ok(mut chunks) => {
                 if let Some(fragment_ids) = existing_embeddings.get(&record.object_id) {
                   for chunk in chunks.iter_mut() {
@@ -359,6 +802,7 @@ async fn generate_embeddings_loop(
                         object_id: record.object_id,
                         collab_type: record.collab_type,
                         tokens_used: embeddings.tokens_consumed,
+                        embedding_model: embedder.model().name().to_string(),
                         chunks: embeddings.chunks,
                       };
                       Ok(Some(record))
@@ -407,6 +851,25 @@ async fn generate_embeddings_loop(
   }
 }
 
+/// Creates the similarity index for the dimensions of the configured model, if it doesn't exist.
+async fn ensure_similarity_index(pg_pool: PgPool, model: EmbeddingModel) {
+  let dimensions = model.default_dimensions();
+  match create_embedding_similarity_index(&pg_pool, dimensions).await {
+    Ok(true) => info!(
+      "[Embedding] similarity index for {} ({} dimensions) is ready",
+      model, dimensions
+    ),
+    Ok(false) => warn!(
+      "[Embedding] {} dimensions of {} can't be indexed, search will be slower",
+      dimensions, model
+    ),
+    Err(err) => error!(
+      "[Embedding] failed to create similarity index for {}: {}",
+      model, err
+    ),
+  }
+}
+
 const EMBEDDING_RECORD_BUFFER_SIZE: usize = 10;
 pub async fn spawn_pg_write_embeddings(
   mut rx: UnboundedReceiver<EmbeddingRecord>,
@@ -480,6 +943,7 @@ pub(crate) async fn batch_insert_records(
       &record.workspace_id,
       &record.object_id,
       record.tokens_used,
+      &record.embedding_model,
       record.chunks,
     )
     .await?;
@@ -531,4 +995,11 @@ impl UnindexedData {
       UnindexedData::Paragraphs(text) => text.is_empty(),
     }
   }
+
+  pub fn to_plain_text(&self) -> String {
+    match self {
+      UnindexedData::Text(text) => text.clone(),
+      UnindexedData::Paragraphs(paragraphs) => paragraphs.join("\n"),
+    }
+  }
 }
@@ -86,7 +86,8 @@ async fn _index_then_write_embedding_to_disk(
       .iter()
       .map(|v| v.object_id)
       .collect::<Vec<_>>();
-    match get_collab_embedding_fragment_ids(&scheduler.pg_pool, object_ids).await {
+    let model = embedder.model();
+    match get_collab_embedding_fragment_ids(&scheduler.pg_pool, object_ids, model.name()).await {
       Ok(existing_embeddings) => {
         let embeddings = _create_embeddings(
           embedder,
@@ -180,7 +181,7 @@ async fn _create_embeddings(
   // 1. use parallel iteration since computing text chunks is CPU-intensive task
   let records = compute_embedding_records(
     indexer_provider,
-    embedder.model(),
+    &embedder.model(),
     unindexed_records,
     existing_embeddings,
   );
@@ -198,6 +199,7 @@ async fn _create_embeddings(
             object_id: record.object_id,
             collab_type: record.collab_type,
             tokens_used: embeddings.tokens_consumed,
+            embedding_model: record.embedding_model,
             chunks: embeddings.chunks,
           }),
           Err(err) => {
@@ -223,7 +225,7 @@ async fn _create_embeddings(
 
 fn compute_embedding_records(
   indexer_provider: &IndexerProvider,
-  model: EmbeddingModel,
+  model: &EmbeddingModel,
   unindexed_records: Vec<UnindexedCollab>,
   existing_embeddings: HashMap<Uuid, Vec<String>>,
 ) -> Vec<EmbeddingRecord> {
@@ -244,6 +246,7 @@ fn compute_embedding_records(
           unindexed.workspace_id,
           unindexed.object_id,
           unindexed.collab_type,
+          model.name().to_string(),
         ));
       }
 
@@ -261,6 +264,7 @@ fn compute_embedding_records(
         object_id: unindexed.object_id,
         collab_type: unindexed.collab_type,
         tokens_used: 0,
+        embedding_model: model.name().to_string(),
         chunks,
       })
     })
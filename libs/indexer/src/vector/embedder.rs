@@ -1,37 +1,188 @@
-use crate::vector::open_ai;
 use crate::vector::open_ai::async_embed;
+use crate::vector::{ollama, open_ai};
 use app_error::AppError;
-use appflowy_ai_client::dto::EmbeddingModel;
+use appflowy_ai_client::dto::{CustomEmbeddingModel, EmbeddingModel};
 pub use async_openai::config::{AzureConfig, OpenAIConfig};
 pub use async_openai::types::{
   CreateEmbeddingRequest, CreateEmbeddingRequestArgs, CreateEmbeddingResponse, EmbeddingInput,
   EncodingFormat,
 };
-use infra::env_util::get_env_var_opt;
+use infra::env_util::{get_env_var, get_env_var_opt};
 use tracing::{info, warn};
 
 #[derive(Debug, Clone)]
 pub enum AFEmbedder {
   OpenAI(open_ai::OpenAIEmbedder),
   AzureOpenAI(open_ai::AzureOpenAIEmbedder),
+  OpenAICompatible(open_ai::OpenAICompatibleEmbedder),
+  Ollama(ollama::OllamaEmbedder),
 }
 
 impl AFEmbedder {
+  /// Embeds the input. Fails if the vectors don't have the dimensions of the model: they would be
+  /// stored next to vectors they can't be compared with, and outside of the similarity index of
+  /// the model.
   pub async fn async_embed(
     &self,
     params: CreateEmbeddingRequest,
   ) -> Result<CreateEmbeddingResponse, AppError> {
-    match self {
+    let response = match self {
       Self::OpenAI(embedder) => async_embed(&embedder.client, params).await,
       Self::AzureOpenAI(embedder) => async_embed(&embedder.client, params).await,
+      Self::OpenAICompatible(embedder) => async_embed(&embedder.client, params).await,
+      Self::Ollama(embedder) => embedder.embed(params).await,
+    }?;
+    let model = self.model();
+    let dimensions = model.default_dimensions() as usize;
+    if let Some(embedding) = response
+      .data
+      .iter()
+      .find(|embedding| embedding.embedding.len() != dimensions)
+    {
+      return Err(AppError::Unhandled(format!(
+        "{} returned an embedding of {} dimensions, expected {}. Check AI_EMBEDDING_DIMENSIONS",
+        model.name(),
+        embedding.embedding.len(),
+        dimensions
+      )));
     }
+    Ok(response)
   }
 
   pub fn model(&self) -> EmbeddingModel {
-    EmbeddingModel::default_model()
+    match self {
+      Self::OpenAI(_) | Self::AzureOpenAI(_) => EmbeddingModel::default_model(),
+      Self::OpenAICompatible(embedder) => embedder.model.clone(),
+      Self::Ollama(embedder) => embedder.model.clone(),
+    }
+  }
+
+  /// Creates a request to embed `input` with the model of this embedder.
+  pub fn create_embedding_request(
+    &self,
+    input: EmbeddingInput,
+  ) -> Result<CreateEmbeddingRequest, AppError> {
+    let model = self.model();
+    let mut request = CreateEmbeddingRequestArgs::default()
+      .model(model.name())
+      .input(input)
+      .encoding_format(EncodingFormat::Float)
+      .build()
+      .map_err(|err| AppError::Unhandled(err.to_string()))?;
+    // Self-hosted models have a fixed number of dimensions, and most of the servers reject the
+    // `dimensions` parameter.
+    if !matches!(model, EmbeddingModel::Custom(_)) {
+      request.dimensions = Some(model.default_dimensions());
+    }
+    Ok(request)
+  }
+}
+
+/// Selects the service used to generate embeddings.
+#[derive(Debug, Clone)]
+pub enum EmbedderConfig {
+  OpenAI(OpenAIConfig),
+  AzureOpenAI(AzureConfig),
+  /// Any server implementing the OpenAI `/embeddings` API, eg. vLLM, LocalAI or LM Studio.
+  OpenAICompatible {
+    config: OpenAIConfig,
+    model: EmbeddingModel,
+  },
+  /// The native API of Ollama.
+  Ollama {
+    base_url: String,
+    model: EmbeddingModel,
+  },
+}
+
+impl EmbedderConfig {
+  pub fn create_embedder(&self) -> AFEmbedder {
+    match self {
+      Self::OpenAI(config) => AFEmbedder::OpenAI(open_ai::OpenAIEmbedder::new(config.clone())),
+      Self::AzureOpenAI(config) => {
+        AFEmbedder::AzureOpenAI(open_ai::AzureOpenAIEmbedder::new(config.clone()))
+      },
+      Self::OpenAICompatible { config, model } => AFEmbedder::OpenAICompatible(
+        open_ai::OpenAICompatibleEmbedder::new(config.clone(), model.clone()),
+      ),
+      Self::Ollama { base_url, model } => {
+        AFEmbedder::Ollama(ollama::OllamaEmbedder::new(base_url.clone(), model.clone()))
+      },
+    }
+  }
+}
+
+/// Reads the embedder from the environment.
+///
+/// `AI_EMBEDDING_PROVIDER` can be `openai`, `azure`, `openai_compatible` or `ollama`. When it is
+/// not set, the OpenAI or Azure OpenAI API is used depending on which API key is set.
+pub fn get_embedder_config() -> Option<EmbedderConfig> {
+  match get_env_var_opt("AI_EMBEDDING_PROVIDER").as_deref() {
+    None => {
+      let (open_ai_config, azure_ai_config) = get_open_ai_config();
+      open_ai_config
+        .map(EmbedderConfig::OpenAI)
+        .or_else(|| azure_ai_config.map(EmbedderConfig::AzureOpenAI))
+    },
+    Some("openai") => open_ai_config().map(EmbedderConfig::OpenAI),
+    Some("azure") => azure_open_ai_config().map(EmbedderConfig::AzureOpenAI),
+    Some("openai_compatible") => {
+      let Some(base_url) = get_env_var_opt("AI_EMBEDDING_BASE_URL") else {
+        warn!("AI_EMBEDDING_BASE_URL is required by the openai_compatible embedding provider");
+        return None;
+      };
+      let model = custom_embedding_model()?;
+      info!("Using OpenAI compatible embeddings at {}", base_url);
+      let mut config = OpenAIConfig::default().with_api_base(base_url);
+      if let Some(api_key) = get_env_var_opt("AI_EMBEDDING_API_KEY") {
+        config = config.with_api_key(api_key);
+      }
+      Some(EmbedderConfig::OpenAICompatible { config, model })
+    },
+    Some("ollama") => {
+      let base_url = get_env_var("AI_EMBEDDING_BASE_URL", "http://localhost:11434");
+      let model = custom_embedding_model()?;
+      info!("Using Ollama embeddings at {}", base_url);
+      Some(EmbedderConfig::Ollama { base_url, model })
+    },
+    Some(other) => {
+      warn!("Unsupported embedding provider: {}", other);
+      None
+    },
   }
 }
 
+/// Reads the model served by a self-hosted embedding provider. `AI_EMBEDDING_DIMENSIONS` is
+/// required unless the model is one of the OpenAI models.
+fn custom_embedding_model() -> Option<EmbeddingModel> {
+  let Some(name) = get_env_var_opt("AI_EMBEDDING_MODEL") else {
+    warn!("AI_EMBEDDING_MODEL is required by self-hosted embedding providers");
+    return None;
+  };
+  let dimensions = get_env_var_opt("AI_EMBEDDING_DIMENSIONS");
+  if dimensions.is_none() {
+    if let Some(model) = EmbeddingModel::from_name(&name) {
+      return Some(model);
+    }
+  }
+
+  let Some(dimensions) = dimensions.and_then(|v| v.parse::<u32>().ok()) else {
+    warn!(
+      "AI_EMBEDDING_DIMENSIONS must be set to the dimensions of {}",
+      name
+    );
+    return None;
+  };
+  let max_tokens = get_env_var("AI_EMBEDDING_MAX_TOKENS", "512")
+    .parse::<usize>()
+    .unwrap_or(512);
+  Some(EmbeddingModel::Custom(CustomEmbeddingModel {
+    name,
+    dimensions,
+    max_tokens,
+  }))
+}
+
 pub fn get_open_ai_config() -> (Option<OpenAIConfig>, Option<AzureConfig>) {
   let open_ai_config = open_ai_config();
   let azure_ai_config = azure_open_ai_config();
@@ -49,7 +200,12 @@ pub fn get_open_ai_config() -> (Option<OpenAIConfig>, Option<AzureConfig>) {
 }
 
 fn open_ai_config() -> Option<OpenAIConfig> {
-  get_env_var_opt("AI_OPENAI_API_KEY").map(|v| OpenAIConfig::default().with_api_key(v))
+  let config = OpenAIConfig::default().with_api_key(get_env_var_opt("AI_OPENAI_API_KEY")?);
+  // Any server implementing the OpenAI API can be used in place of the official one.
+  match get_env_var_opt("AI_OPENAI_API_BASE") {
+    Some(api_base) => Some(config.with_api_base(api_base)),
+    None => Some(config),
+  }
 }
 
 fn azure_open_ai_config() -> Option<AzureConfig> {
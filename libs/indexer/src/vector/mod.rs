@@ -1,2 +1,3 @@
 pub mod embedder;
+pub mod ollama;
 pub mod open_ai;
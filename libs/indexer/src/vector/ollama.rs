@@ -0,0 +1,104 @@
+use app_error::AppError;
+use appflowy_ai_client::dto::EmbeddingModel;
+use async_openai::types::{
+  CreateEmbeddingRequest, CreateEmbeddingResponse, Embedding, EmbeddingInput, EmbeddingUsage,
+};
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+
+/// Embeds with the native API of a local Ollama server.
+/// https://github.com/ollama/ollama/blob/main/docs/api.md#generate-embeddings
+#[derive(Debug, Clone)]
+pub struct OllamaEmbedder {
+  client: reqwest::Client,
+  base_url: String,
+  pub(crate) model: EmbeddingModel,
+}
+
+#[derive(Serialize)]
+struct OllamaEmbedRequest {
+  model: String,
+  input: Vec<String>,
+  truncate: bool,
+}
+
+#[derive(Deserialize)]
+struct OllamaEmbedResponse {
+  embeddings: Vec<Vec<f32>>,
+  #[serde(default)]
+  prompt_eval_count: u32,
+}
+
+impl OllamaEmbedder {
+  pub fn new(base_url: String, model: EmbeddingModel) -> Self {
+    Self {
+      client: reqwest::Client::new(),
+      base_url: base_url.trim_end_matches('/').to_string(),
+      model,
+    }
+  }
+
+  pub async fn embed(
+    &self,
+    request: CreateEmbeddingRequest,
+  ) -> Result<CreateEmbeddingResponse, AppError> {
+    let input = match request.input {
+      EmbeddingInput::String(text) => vec![text],
+      EmbeddingInput::StringArray(texts) => texts,
+      _ => {
+        return Err(AppError::InvalidRequest(
+          "Ollama only supports text input".to_string(),
+        ))
+      },
+    };
+    trace!(
+      "ollama embed with request: model:{}, inputs:{}, api_base:{}",
+      request.model,
+      input.len(),
+      self.base_url
+    );
+
+    let resp = self
+      .client
+      .post(format!("{}/api/embed", self.base_url))
+      .json(&OllamaEmbedRequest {
+        model: request.model.clone(),
+        input,
+        truncate: true,
+      })
+      .send()
+      .await
+      .map_err(|err| AppError::AIServiceUnavailable(err.to_string()))?;
+    if !resp.status().is_success() {
+      let status = resp.status();
+      let body = resp.text().await.unwrap_or_default();
+      return Err(AppError::Unhandled(format!(
+        "Ollama embed request failed with {}: {}",
+        status, body
+      )));
+    }
+    let resp = resp
+      .json::<OllamaEmbedResponse>()
+      .await
+      .map_err(|err| AppError::Unhandled(err.to_string()))?;
+
+    Ok(CreateEmbeddingResponse {
+      object: "list".to_string(),
+      model: request.model,
+      data: resp
+        .embeddings
+        .into_iter()
+        .enumerate()
+        .map(|(index, embedding)| Embedding {
+          index: index as u32,
+          object: "embedding".to_string(),
+          embedding,
+        })
+        .collect(),
+      usage: EmbeddingUsage {
+        prompt_tokens: resp.prompt_eval_count,
+        total_tokens: resp.prompt_eval_count,
+      },
+    })
+  }
+}
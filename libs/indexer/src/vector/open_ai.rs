@@ -38,6 +38,20 @@ impl AzureOpenAIEmbedder {
   }
 }
 
+/// Embeds with a self-hosted server implementing the OpenAI embeddings API.
+#[derive(Debug, Clone)]
+pub struct OpenAICompatibleEmbedder {
+  pub(crate) client: Client<OpenAIConfig>,
+  pub(crate) model: EmbeddingModel,
+}
+
+impl OpenAICompatibleEmbedder {
+  pub fn new(config: OpenAIConfig, model: EmbeddingModel) -> Self {
+    let client = Client::with_config(config);
+    Self { client, model }
+  }
+}
+
 pub async fn async_embed<C: Config>(
   client: &Client<C>,
   request: CreateEmbeddingRequest,
@@ -1,10 +1,14 @@
 use app_error::AppError;
 use async_openai::config::{AzureConfig, Config, OpenAIConfig};
 use async_openai::types::{
+  ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestMessage,
   ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
-  CreateChatCompletionRequestArgs, ResponseFormat, ResponseFormatJsonSchema,
+  CreateChatCompletionRequest, CreateChatCompletionRequestArgs, ResponseFormat,
+  ResponseFormatJsonSchema,
 };
 use async_openai::Client;
+use futures::stream::BoxStream;
+use futures::{future, StreamExt, TryStreamExt};
 use schemars::{schema_for, JsonSchema};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -54,6 +58,42 @@ impl AITool {
       },
     }
   }
+
+  /// Sends the conversation to the model and returns its whole answer.
+  pub async fn chat(
+    &self,
+    model_name: &str,
+    messages: Vec<LLMMessage>,
+  ) -> Result<String, AppError> {
+    trace!(
+      "Using model:{} to answer with {} messages",
+      model_name,
+      messages.len()
+    );
+    match self {
+      AITool::OpenAI(client) => chat_completion(&client.client, model_name, messages).await,
+      AITool::AzureOpenAI(client) => chat_completion(&client.client, model_name, messages).await,
+    }
+  }
+
+  /// Sends the conversation to the model and streams its answer as it is generated.
+  pub async fn stream_chat(
+    &self,
+    model_name: &str,
+    messages: Vec<LLMMessage>,
+  ) -> Result<LLMTextStream, AppError> {
+    trace!(
+      "Using model:{} to stream answer with {} messages",
+      model_name,
+      messages.len()
+    );
+    match self {
+      AITool::OpenAI(client) => stream_chat_completion(&client.client, model_name, messages).await,
+      AITool::AzureOpenAI(client) => {
+        stream_chat_completion(&client.client, model_name, messages).await
+      },
+    }
+  }
 }
 
 pub struct OpenAIChat {
@@ -248,3 +288,115 @@ pub async fn summarize_documents<C: Config>(
     summaries: vec![summary],
   })
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LLMRole {
+  System,
+  User,
+  Assistant,
+}
+
+#[derive(Debug, Clone)]
+pub struct LLMMessage {
+  pub role: LLMRole,
+  pub content: String,
+}
+
+impl LLMMessage {
+  pub fn system<T: Into<String>>(content: T) -> Self {
+    Self {
+      role: LLMRole::System,
+      content: content.into(),
+    }
+  }
+
+  pub fn user<T: Into<String>>(content: T) -> Self {
+    Self {
+      role: LLMRole::User,
+      content: content.into(),
+    }
+  }
+
+  pub fn assistant<T: Into<String>>(content: T) -> Self {
+    Self {
+      role: LLMRole::Assistant,
+      content: content.into(),
+    }
+  }
+}
+
+/// Pieces of the answer of the model, in the order they are generated.
+pub type LLMTextStream = BoxStream<'static, Result<String, AppError>>;
+
+fn chat_completion_request(
+  model_name: &str,
+  messages: Vec<LLMMessage>,
+) -> Result<CreateChatCompletionRequest, AppError> {
+  let messages = messages
+    .into_iter()
+    .map(
+      |message| -> Result<ChatCompletionRequestMessage, AppError> {
+        let message = match message.role {
+          LLMRole::System => ChatCompletionRequestSystemMessageArgs::default()
+            .content(message.content)
+            .build()?
+            .into(),
+          LLMRole::User => ChatCompletionRequestUserMessageArgs::default()
+            .content(message.content)
+            .build()?
+            .into(),
+          LLMRole::Assistant => ChatCompletionRequestAssistantMessageArgs::default()
+            .content(message.content)
+            .build()?
+            .into(),
+        };
+        Ok(message)
+      },
+    )
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let request = CreateChatCompletionRequestArgs::default()
+    .model(model_name)
+    .messages(messages)
+    .build()?;
+  Ok(request)
+}
+
+pub async fn chat_completion<C: Config>(
+  client: &Client<C>,
+  model_name: &str,
+  messages: Vec<LLMMessage>,
+) -> Result<String, AppError> {
+  let request = chat_completion_request(model_name, messages)?;
+  client
+    .chat()
+    .create(request)
+    .await?
+    .choices
+    .into_iter()
+    .next()
+    .and_then(|choice| choice.message.content)
+    .ok_or_else(|| AppError::Unhandled("No response from OpenAI".to_string()))
+}
+
+pub async fn stream_chat_completion<C: Config>(
+  client: &Client<C>,
+  model_name: &str,
+  messages: Vec<LLMMessage>,
+) -> Result<LLMTextStream, AppError> {
+  let request = chat_completion_request(model_name, messages)?;
+  let stream = client
+    .chat()
+    .create_stream(request)
+    .await?
+    .map(|response| -> Result<String, AppError> {
+      let text = response?
+        .choices
+        .into_iter()
+        .filter_map(|choice| choice.delta.content)
+        .collect::<String>();
+      Ok(text)
+    })
+    .try_filter(|text: &String| future::ready(!text.is_empty()));
+  Ok(stream.boxed())
+}
@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use database_entity::dto::AFApiTokenScope;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateApiTokenParams {
+  pub name: String,
+  /// The workspaces that the token can access. At least one workspace is required.
+  pub workspace_ids: Vec<Uuid>,
+  pub scope: AFApiTokenScope,
+  /// The token never expires if this is not set.
+  pub expires_in_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AFApiToken {
+  pub token_id: Uuid,
+  pub name: String,
+  /// The first characters of the token, to help users to tell their tokens apart.
+  pub token_prefix: String,
+  pub workspace_ids: Vec<Uuid>,
+  pub scope: AFApiTokenScope,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: Option<DateTime<Utc>>,
+  pub last_used_at: Option<DateTime<Utc>>,
+  /// Only returned when the token is created. The token is sent in the `Authorization` header
+  /// as a bearer token, in place of the access token of a session.
+  pub token: Option<String>,
+}
@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Security relevant actions recorded in the audit log of a workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AuditAction {
+  #[serde(rename = "member.invited")]
+  MemberInvited,
+  #[serde(rename = "member.removed")]
+  MemberRemoved,
+  #[serde(rename = "member.role_changed")]
+  MemberRoleChanged,
+  #[serde(rename = "page.published")]
+  PagePublished,
+  #[serde(rename = "page.unpublished")]
+  PageUnpublished,
+  #[serde(rename = "trash.emptied")]
+  TrashEmptied,
+  #[serde(rename = "workspace.settings_changed")]
+  WorkspaceSettingsChanged,
+}
+
+impl AuditAction {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AuditAction::MemberInvited => "member.invited",
+      AuditAction::MemberRemoved => "member.removed",
+      AuditAction::MemberRoleChanged => "member.role_changed",
+      AuditAction::PagePublished => "page.published",
+      AuditAction::PageUnpublished => "page.unpublished",
+      AuditAction::TrashEmptied => "trash.emptied",
+      AuditAction::WorkspaceSettingsChanged => "workspace.settings_changed",
+    }
+  }
+}
+
+impl Display for AuditAction {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl FromStr for AuditAction {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    serde_json::from_value(serde_json::Value::String(s.to_string()))
+      .map_err(|_| format!("unknown audit action: {}", s))
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AFAuditLogEntry {
+  pub event_id: i64,
+  pub workspace_id: Uuid,
+  pub actor_uid: Option<i64>,
+  pub actor_email: Option<String>,
+  pub action: String,
+  /// What the action applies to, such as the email of a member or the id of a page.
+  pub target: Option<String>,
+  /// Address of the client that made the request, rather than the one of the proxy in front of
+  /// the server.
+  pub ip_address: Option<String>,
+  pub user_agent: Option<String>,
+  pub before: Option<serde_json::Value>,
+  pub after: Option<serde_json::Value>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Filters of the audit log. Filters left to `None` match every entry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryAuditLogParams {
+  pub action: Option<AuditAction>,
+  pub actor_uid: Option<i64>,
+  pub target: Option<String>,
+  /// Entries created at or after this time.
+  pub from: Option<DateTime<Utc>>,
+  /// Entries created before this time.
+  pub to: Option<DateTime<Utc>>,
+  /// Pagination of the entries, most recent first. The CSV export is not paginated.
+  pub limit: Option<i64>,
+  pub offset: Option<i64>,
+}
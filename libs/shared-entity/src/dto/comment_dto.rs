@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use database_entity::dto::AFWebUser;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A comment thread on a workspace view. The thread is anchored to a block of the view, or to
+/// the view itself when `block_id` is `None`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewCommentThread {
+  pub thread_id: Uuid,
+  pub view_id: Uuid,
+  pub block_id: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub is_resolved: bool,
+  pub resolved_by: Option<Uuid>,
+  pub resolved_at: Option<DateTime<Utc>>,
+  /// Comments of the thread, oldest first. The first comment is the one that started the thread.
+  pub comments: Vec<ViewComment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewComment {
+  pub comment_id: Uuid,
+  pub thread_id: Uuid,
+  pub author: Option<AFWebUser>,
+  /// Empty if the comment has been deleted.
+  pub content: String,
+  /// The uuids of the persons mentioned in the comment.
+  pub mentions: Vec<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+  pub is_edited: bool,
+  pub is_deleted: bool,
+  pub can_be_edited: bool,
+  pub can_be_deleted: bool,
+}
+
+/// A previous version of an edited comment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewCommentEdit {
+  pub content: String,
+  pub edited_by: Option<Uuid>,
+  pub edited_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryViewCommentThreadParams {
+  pub block_id: Option<String>,
+  /// Resolved threads are only returned if this is set to true.
+  pub include_resolved: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateViewCommentThreadParams {
+  pub block_id: Option<String>,
+  pub content: String,
+  #[serde(default)]
+  pub mentions: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateViewCommentParams {
+  pub content: String,
+  #[serde(default)]
+  pub mentions: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateViewCommentParams {
+  pub content: String,
+  #[serde(default)]
+  pub mentions: Vec<Uuid>,
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateExportTaskResponse {
+  pub task_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserExportTask {
+  pub tasks: Vec<ExportTaskDetail>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportTaskDetail {
+  pub task_id: String,
+  pub workspace_id: String,
+  /// 0: pending, 1: completed, 2: failed
+  pub status: i16,
+  /// Size of the archive in bytes, set once the export is completed.
+  pub file_size: Option<i64>,
+  pub error: Option<String>,
+  pub created_at: i64,
+  pub updated_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportDownloadUrl {
+  pub download_url: String,
+}
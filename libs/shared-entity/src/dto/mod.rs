@@ -1,8 +1,12 @@
 pub mod access_request_dto;
 pub mod ai_dto;
+pub mod api_token_dto;
+pub mod audit_dto;
 pub mod auth_dto;
 pub mod billing_dto;
 pub mod chat_dto;
+pub mod comment_dto;
+pub mod export_dto;
 pub mod file_dto;
 pub mod guest_dto;
 pub mod history_dto;
@@ -10,4 +14,5 @@ pub mod import_dto;
 pub mod publish_dto;
 pub mod search_dto;
 pub mod server_info_dto;
+pub mod webhook_dto;
 pub mod workspace_dto;
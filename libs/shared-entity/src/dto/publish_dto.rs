@@ -1,9 +1,13 @@
 use std::collections::HashMap;
 
 use super::workspace_dto::{ViewIcon, ViewLayout};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// Header carrying the token returned when a password protected published page is unlocked.
+pub const PUBLISH_ACCESS_TOKEN_HEADER: &str = "X-Publish-Access-Token";
+
 /// Copied from AppFlowy-IO/AppFlowy/frontend/rust-lib/flowy-folder-pub/src/entities.rs
 /// TODO(zack): make AppFlowy use from this crate instead
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -108,3 +112,72 @@ impl From<PublishDatabaseDataWithNonUuidRelations> for PublishDatabaseData {
 pub struct DuplicatePublishedPageResponse {
   pub view_id: Uuid,
 }
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UnlockPublishedViewParams {
+  pub password: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct PublishedViewAccessToken {
+  /// Sent in the `X-Publish-Access-Token` header when reading the published page.
+  pub access_token: String,
+  pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Default, Deserialize, Serialize, Debug)]
+pub struct PublishedAnalyticsQuery {
+  /// First day of the report, inclusive. Defaults to 30 days before `to`.
+  pub from: Option<NaiveDate>,
+  /// Last day of the report, inclusive. Defaults to today (UTC).
+  pub to: Option<NaiveDate>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PublishedViewDailyCount {
+  pub date: NaiveDate,
+  pub views: i64,
+  pub unique_visitors: i64,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct PublishedPageAnalytics {
+  pub view_id: Uuid,
+  pub views: i64,
+  pub daily: Vec<PublishedViewDailyCount>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PublishedReferrerCount {
+  pub domain: String,
+  pub views: i64,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct PublishedAnalytics {
+  pub from: NaiveDate,
+  pub to: NaiveDate,
+  /// Counts across every page published under the workspace namespace.
+  pub daily: Vec<PublishedViewDailyCount>,
+  /// Pages with at least one view in the period, most viewed first.
+  pub pages: Vec<PublishedPageAnalytics>,
+  pub referrers: Vec<PublishedReferrerCount>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreatePublishCustomDomainParams {
+  pub domain: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct PublishCustomDomain {
+  pub domain: String,
+  /// Name of the DNS TXT record proving the ownership of the domain.
+  pub verification_record_name: String,
+  /// Value of the DNS TXT record proving the ownership of the domain.
+  pub verification_record_value: String,
+  /// Set when the TXT record was last found. Only verified domains serve the published pages. The
+  /// record is checked again daily, and the domain is no longer served once it is gone.
+  pub verified_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
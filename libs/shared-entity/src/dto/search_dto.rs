@@ -16,8 +16,28 @@ pub struct SearchDocumentRequest {
   #[serde(skip_serializing_if = "Option::is_none")]
   pub preview_size: Option<u32>,
 
+  /// Minimum similarity score of the vector search results. Not applied to keyword matches.
   #[serde(default = "default_search_score_limit")]
   pub score: f64,
+  /// How the documents are matched against the query. Default: [SearchMode::Vector].
+  #[serde(default)]
+  pub mode: SearchMode,
+}
+
+/// Ranking strategy used by the document search.
+/// See: [SearchDocumentRequest].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMode {
+  /// Rank documents by the cosine similarity of their embeddings to the query embedding.
+  /// Falls back to [SearchMode::Keyword] if no embedder is configured on the server.
+  #[default]
+  Vector,
+  /// Rank documents by full-text keyword matches. Doesn't require an AI service.
+  Keyword,
+  /// Combine the vector and keyword rankings using reciprocal rank fusion.
+  /// Falls back to [SearchMode::Keyword] if no embedder is configured on the server.
+  Hybrid,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -72,8 +92,10 @@ pub struct SearchDocumentResponseItem {
   pub object_id: Uuid,
   /// Workspace, result object belongs to.
   pub workspace_id: Uuid,
-  /// Match score of this search result to an original query. Score represents cosine distance
-  /// between the query and the document embedding [-1.0..1.0]. The higher, the better.
+  /// Match score of this search result to an original query. For [SearchMode::Vector], score
+  /// represents cosine distance between the query and the document embedding [-1.0..1.0].
+  /// For [SearchMode::Keyword] it's the full-text rank, and for [SearchMode::Hybrid] the
+  /// reciprocal rank fusion score. The higher, the better.
   /// List of results is sorted by this value by default.
   pub score: f64,
   /// Type of the content to be presented in preview field. This is a hint what
@@ -89,6 +111,26 @@ pub struct SearchDocumentResponseItem {
   pub created_by: String,
   /// Date when the document was created.
   pub created_at: DateTime<Utc>,
+  /// View the result can be opened in: for database rows and documents of the rows, a view of
+  /// the database, for attachments, the page the file was uploaded to.
+  #[serde(default)]
+  pub view_id: Option<Uuid>,
+  /// For database rows and documents of the rows: id of the row.
+  #[serde(default)]
+  pub row_id: Option<Uuid>,
+  /// For [SearchContentType::Attachment]: the file the content was extracted from.
+  #[serde(default)]
+  pub attachment: Option<SearchAttachment>,
+}
+
+/// File uploaded to a page, which can be downloaded from
+/// `/api/file_storage/{workspace_id}/v1/blob/{parent_dir}/{file_id}`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearchAttachment {
+  pub parent_dir: String,
+  pub file_id: String,
+  /// Mime type of the file.
+  pub content_type: String,
 }
 
 /// Type of the document content to be presented in the search results.
@@ -98,6 +140,8 @@ pub struct SearchDocumentResponseItem {
 pub enum SearchContentType {
   /// Document block contents displayed as plain text.
   PlainText = 0,
+  /// Text extracted from a file uploaded to the document, ie. a PDF or DOCX attachment.
+  Attachment = 1,
 }
 
 impl SearchContentType {
@@ -105,7 +149,31 @@ impl SearchContentType {
   pub fn from_record(content_type: i32) -> Option<Self> {
     match content_type {
       0 => Some(SearchContentType::PlainText),
+      1 => Some(SearchContentType::Attachment),
       _ => None,
     }
   }
 }
+
+/// State of the embeddings of a workspace with regard to the embedding model configured on the
+/// server. Embeddings generated by another model are not searched until they are migrated.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EmbeddingStatus {
+  /// Name of the embedding model configured on the server.
+  pub model: String,
+  /// Number of dimensions of the vectors generated by [EmbeddingStatus::model].
+  pub dimensions: u32,
+  /// Number of documents embedded with [EmbeddingStatus::model].
+  pub indexed: i64,
+  /// Number of documents embedded with another model, which have to be migrated.
+  pub stale: i64,
+}
+
+/// Response of the embedding migration request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EmbeddingMigrationResult {
+  /// Number of documents and attachments queued to be embedded with the configured model. Large
+  /// workspaces are migrated in batches, so the request should be repeated until
+  /// [EmbeddingStatus::stale] is 0.
+  pub queued: usize,
+}
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Workspace events that can be delivered to a webhook. The event name is sent in the
+/// `X-AppFlowy-Event` header and in the `event` field of the payload.
+///
+/// The page and database row events are only emitted for changes made through the REST API.
+/// Edits that the clients sync over the websocket, such as renaming a page in the app or typing in
+/// a database cell, do not emit them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEvent {
+  #[serde(rename = "page.created")]
+  PageCreated,
+  #[serde(rename = "page.renamed")]
+  PageRenamed,
+  #[serde(rename = "page.moved_to_trash")]
+  PageMovedToTrash,
+  #[serde(rename = "page.published")]
+  PagePublished,
+  #[serde(rename = "database.row_added")]
+  DatabaseRowAdded,
+  #[serde(rename = "database.row_changed")]
+  DatabaseRowChanged,
+  #[serde(rename = "member.joined")]
+  MemberJoined,
+  #[serde(rename = "member.left")]
+  MemberLeft,
+  #[serde(rename = "comment.added")]
+  CommentAdded,
+}
+
+impl WebhookEvent {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      WebhookEvent::PageCreated => "page.created",
+      WebhookEvent::PageRenamed => "page.renamed",
+      WebhookEvent::PageMovedToTrash => "page.moved_to_trash",
+      WebhookEvent::PagePublished => "page.published",
+      WebhookEvent::DatabaseRowAdded => "database.row_added",
+      WebhookEvent::DatabaseRowChanged => "database.row_changed",
+      WebhookEvent::MemberJoined => "member.joined",
+      WebhookEvent::MemberLeft => "member.left",
+      WebhookEvent::CommentAdded => "comment.added",
+    }
+  }
+}
+
+impl Display for WebhookEvent {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl FromStr for WebhookEvent {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    serde_json::from_value(serde_json::Value::String(s.to_string()))
+      .map_err(|_| format!("unknown webhook event: {}", s))
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWebhookParams {
+  /// The http or https endpoint the events are posted to. Its host must resolve to public
+  /// addresses only.
+  pub url: String,
+  /// Events delivered to the endpoint. Every event is delivered when empty.
+  #[serde(default)]
+  pub events: Vec<WebhookEvent>,
+}
+
+/// Fields left to `None` are not modified.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateWebhookParams {
+  pub url: Option<String>,
+  pub events: Option<Vec<WebhookEvent>>,
+  pub enabled: Option<bool>,
+  /// Generates a new signing secret. The new secret is returned in the response.
+  #[serde(default)]
+  pub rotate_secret: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AFWebhook {
+  pub webhook_id: Uuid,
+  pub workspace_id: Uuid,
+  pub url: String,
+  pub events: Vec<WebhookEvent>,
+  pub enabled: bool,
+  /// Only returned when the webhook is created or its secret is rotated.
+  pub secret: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+  Pending,
+  Delivered,
+  Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AFWebhookDelivery {
+  pub delivery_id: Uuid,
+  pub webhook_id: Uuid,
+  pub event: String,
+  pub payload: serde_json::Value,
+  pub status: WebhookDeliveryStatus,
+  pub attempts: i32,
+  /// Time of the next attempt while the delivery is pending.
+  pub next_attempt_at: DateTime<Utc>,
+  /// HTTP status returned by the endpoint on the last attempt.
+  pub response_status: Option<i32>,
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub delivered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryWebhookDeliveryParams {
+  pub limit: Option<i64>,
+  pub offset: Option<i64>,
+}
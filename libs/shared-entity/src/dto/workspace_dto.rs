@@ -137,11 +137,72 @@ pub struct EmbeddedCollabQuery {
   pub object_id: Uuid,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CollabJsonResponse {
   pub collab: serde_json::Value,
 }
 
+/// The last editor of each block of a document, in document order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentBlame {
+  pub blocks: Vec<DocumentBlockBlame>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentBlockBlame {
+  pub block_id: String,
+  pub block_type: String,
+  /// None when no recorded update touched the text of the block, e.g. for blocks written before
+  /// the authors of updates were recorded, or blocks without text.
+  pub last_edited_by: Option<i64>,
+  pub last_edited_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentChangesQuery {
+  pub since: DateTime<Utc>,
+  /// Defaults to now.
+  pub until: Option<DateTime<Utc>>,
+}
+
+/// The blocks of a document that changed between two points in time. Both ends are the
+/// snapshots closest to the requested times, see `from` and `to`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentChanges {
+  /// False when the document has no snapshot at or before one of the requested times, in which
+  /// case the changes are unknown and none is reported.
+  pub history_available: bool,
+  /// Time of the snapshot the changes are computed from.
+  pub from: Option<DateTime<Utc>>,
+  /// Time of the snapshot the changes are computed up to. None for the current document.
+  pub to: Option<DateTime<Utc>>,
+  /// Inserted and modified blocks in document order, followed by the deleted blocks.
+  pub changes: Vec<DocumentBlockChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentBlockChange {
+  pub block_id: String,
+  pub block_type: String,
+  pub kind: DocumentBlockChangeKind,
+  /// Plain text of the block after the change, None for deleted blocks.
+  pub text: Option<String>,
+  /// Plain text of the block before the change, None for inserted blocks.
+  pub previous_text: Option<String>,
+  /// Last editor of the block text. None for deleted blocks, and for changes that were not
+  /// attributed, see [DocumentBlockBlame].
+  pub edited_by: Option<i64>,
+  pub edited_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentBlockChangeKind {
+  Inserted,
+  Deleted,
+  Modified,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CollabResponse {
   #[serde(flatten)]
@@ -370,6 +431,12 @@ pub struct PublishPageParams {
   pub visible_database_view_ids: Option<Vec<Uuid>>,
   pub comments_enabled: Option<bool>,
   pub duplicate_enabled: Option<bool>,
+  /// Password required to view the published page.
+  #[serde(default)]
+  pub password: Option<String>,
+  /// After this time the published page is no longer accessible.
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Eq, PartialEq, Debug, Hash, Clone, Serialize_repr, Deserialize_repr)]
@@ -476,6 +543,132 @@ impl ListDatabaseRowDetailParam {
   }
 }
 
+/// Body of the database row query. Rows are returned in the order of the inline view unless
+/// `sorts` is set.
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct QueryDatabaseRowParams {
+  #[serde(default)]
+  pub filter: Option<DatabaseRowFilter>,
+  /// Rows are sorted by the first sort, then by the second one for equal values and so on.
+  #[serde(default)]
+  pub sorts: Vec<DatabaseRowSort>,
+  /// Maximum number of rows to return. Defaults to 100, at most 1000.
+  pub limit: Option<u32>,
+  /// The `next_cursor` of the previous page.
+  pub cursor: Option<String>,
+  /// if set to true, document data will be fetched (if exist) as markdown
+  #[serde(default)]
+  pub with_doc: bool,
+}
+
+/// Filter tree of a database row query. Fields can be referenced by id or by name.
+///
+/// Example:
+/// ```json
+/// {
+///   "type": "and",
+///   "filters": [
+///     { "type": "field", "field": "Status", "operator": "is", "value": "Done" },
+///     { "type": "field", "field": "Due", "operator": "before", "value": "2025-01-01T00:00:00Z" }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DatabaseRowFilter {
+  And {
+    filters: Vec<DatabaseRowFilter>,
+  },
+  Or {
+    filters: Vec<DatabaseRowFilter>,
+  },
+  Not {
+    filter: Box<DatabaseRowFilter>,
+  },
+  Field {
+    field: String,
+    operator: DatabaseFilterOperator,
+    #[serde(default)]
+    value: serde_json::Value,
+  },
+}
+
+impl DatabaseRowFilter {
+  pub fn field(
+    field: impl Into<String>,
+    operator: DatabaseFilterOperator,
+    value: serde_json::Value,
+  ) -> Self {
+    Self::Field {
+      field: field.into(),
+      operator,
+      value,
+    }
+  }
+}
+
+/// Operators supported by a filter. Which operators can be used depends on the type of the field:
+/// - text and url: `is`, `is_not`, `contains`, `does_not_contain`, `starts_with`, `ends_with`
+/// - number: `equal`, `not_equal`, `greater_than`, `greater_than_or_equal`, `less_than`,
+///   `less_than_or_equal`
+/// - checkbox: `is_checked`, `is_unchecked`
+/// - single and multi select: `is`, `is_not`, `contains_any`, `contains_all`, `does_not_contain`
+/// - date, created time and last edited time: `before`, `after`, `on_or_before`, `on_or_after`,
+///   `between`. Dates are unix timestamps in seconds or RFC 3339 strings.
+/// - any field: `is_empty`, `is_not_empty`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseFilterOperator {
+  Is,
+  IsNot,
+  Contains,
+  DoesNotContain,
+  StartsWith,
+  EndsWith,
+  Equal,
+  NotEqual,
+  GreaterThan,
+  GreaterThanOrEqual,
+  LessThan,
+  LessThanOrEqual,
+  IsChecked,
+  IsUnchecked,
+  ContainsAny,
+  ContainsAll,
+  Before,
+  After,
+  OnOrBefore,
+  OnOrAfter,
+  Between,
+  IsEmpty,
+  IsNotEmpty,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DatabaseRowSort {
+  /// Field id or name
+  pub field: String,
+  #[serde(default)]
+  pub direction: DatabaseSortDirection,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseSortDirection {
+  #[default]
+  Asc,
+  Desc,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct QueryDatabaseRowResponse {
+  pub rows: Vec<AFDatabaseRowDetail>,
+  /// Number of rows matching the filter
+  pub total: usize,
+  /// Pass it as the `cursor` of the next query to get the next page. None on the last page.
+  pub next_cursor: Option<String>,
+}
+
 #[derive(Default, Debug, Deserialize, Serialize)]
 pub struct QueryWorkspaceFolder {
   pub depth: Option<u32>,
@@ -503,6 +696,8 @@ pub struct PublishedViewInfo {
   pub publish_timestamp: DateTime<Utc>,
   pub comments_enabled: bool,
   pub duplicate_enabled: bool,
+  #[serde(default)]
+  pub password_protected: bool,
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
@@ -542,6 +737,21 @@ pub struct AFInsertDatabaseField {
   pub type_option_data: Option<serde_json::Value>, // TypeOptionData
 }
 
+/// Fields left to `None` are not modified. When the field type changes, the existing cells are
+/// read with the type option of the new type.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct AFUpdateDatabaseField {
+  pub name: Option<String>,
+  pub field_type: Option<i64>,                     // FieldType ID
+  pub type_option_data: Option<serde_json::Value>, // TypeOptionData
+}
+
+/// Fields that are not listed keep their relative order and are placed after the listed ones.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct AFReorderDatabaseFields {
+  pub field_ids: Vec<String>,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct AddDatatabaseRow {
   pub cells: HashMap<String, serde_json::Value>,
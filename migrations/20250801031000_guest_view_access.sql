@@ -0,0 +1,21 @@
+-- af_guest_view_access stores the views that have been shared with guests (users that are not
+-- a full member of the workspace). A grant made on a view is inherited by all of its child views.
+-- Each inherited grant is stored as its own row, with `shared_view_id` pointing to the view that
+-- was explicitly shared, so that revoking the grant on the shared view also revokes the inherited ones.
+CREATE TABLE IF NOT EXISTS af_guest_view_access (
+  workspace_id UUID NOT NULL,
+  view_id UUID NOT NULL,
+  shared_view_id UUID NOT NULL,
+  email TEXT NOT NULL,
+  access_level INT NOT NULL,
+  invited_by BIGINT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (view_id, shared_view_id, email),
+  FOREIGN KEY (invited_by) REFERENCES af_user(uid) ON DELETE SET NULL,
+  FOREIGN KEY (workspace_id) REFERENCES af_workspace(workspace_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_guest_view_access_workspace_email
+  ON af_guest_view_access (workspace_id, email);
+CREATE INDEX IF NOT EXISTS idx_af_guest_view_access_shared_view
+  ON af_guest_view_access (shared_view_id);
@@ -0,0 +1,20 @@
+-- af_collab_keyword_index keeps the plain text extracted by the collab indexer, so documents can be
+-- searched by keyword even when no embedding model is configured. The `simple` text search
+-- configuration is used on purpose: it doesn't stem or drop stop words, which keeps exact matches
+-- such as ticket ids or names working across languages. Rows go away with their collab, like the
+-- embeddings of the collab.
+CREATE TABLE IF NOT EXISTS af_collab_keyword_index (
+  oid UUID PRIMARY KEY,
+  workspace_id UUID NOT NULL,
+  partition_key INT NOT NULL,
+  content TEXT NOT NULL,
+  content_tsv TSVECTOR GENERATED ALWAYS AS (to_tsvector('simple', content)) STORED,
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  FOREIGN KEY (oid) REFERENCES af_collab(oid) ON DELETE CASCADE,
+  FOREIGN KEY (workspace_id) REFERENCES af_workspace(workspace_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_keyword_index_tsv
+  ON af_collab_keyword_index USING GIN (content_tsv);
+CREATE INDEX IF NOT EXISTS idx_af_collab_keyword_index_workspace_id
+  ON af_collab_keyword_index (workspace_id);
@@ -0,0 +1,18 @@
+-- af_export_task tracks the requests to export a workspace to a zip archive. The archive is
+-- built by the appflowy-worker and stored in S3 under `s3_key`.
+CREATE TABLE IF NOT EXISTS af_export_task (
+  task_id UUID NOT NULL PRIMARY KEY,
+  workspace_id UUID NOT NULL,
+  created_by BIGINT NOT NULL,
+  status SMALLINT NOT NULL,           -- 0 for pending, 1 for completed, 2 for failed
+  s3_key TEXT,
+  file_size BIGINT,
+  error TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  FOREIGN KEY (workspace_id) REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  FOREIGN KEY (created_by) REFERENCES af_user(uid) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_export_task_created_by_status
+  ON af_export_task (created_by, status, created_at);
@@ -0,0 +1,44 @@
+-- af_workspace_webhook stores the endpoints that receive the events of a workspace. An empty
+-- `events` array subscribes the endpoint to every event.
+CREATE TABLE IF NOT EXISTS af_workspace_webhook (
+  webhook_id UUID NOT NULL PRIMARY KEY,
+  workspace_id UUID NOT NULL,
+  url TEXT NOT NULL,
+  secret TEXT NOT NULL,
+  events TEXT[] NOT NULL DEFAULT '{}',
+  enabled BOOLEAN NOT NULL DEFAULT TRUE,
+  created_by BIGINT NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  FOREIGN KEY (workspace_id) REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  FOREIGN KEY (created_by) REFERENCES af_user(uid) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_workspace_webhook_workspace_id
+  ON af_workspace_webhook (workspace_id);
+
+-- af_webhook_delivery is both the durable delivery queue and the delivery log. Pending deliveries
+-- are picked up by the delivery worker once `next_attempt_at` is reached.
+CREATE TABLE IF NOT EXISTS af_webhook_delivery (
+  delivery_id UUID NOT NULL PRIMARY KEY DEFAULT uuid_generate_v4(),
+  webhook_id UUID NOT NULL,
+  workspace_id UUID NOT NULL,
+  event TEXT NOT NULL,
+  payload JSONB NOT NULL,
+  status SMALLINT NOT NULL DEFAULT 0,  -- 0 for pending, 1 for delivered, 2 for failed
+  attempts INT NOT NULL DEFAULT 0,
+  next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  response_status INT,
+  error TEXT,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  delivered_at TIMESTAMP WITH TIME ZONE,
+  FOREIGN KEY (webhook_id) REFERENCES af_workspace_webhook(webhook_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_webhook_delivery_pending
+  ON af_webhook_delivery (next_attempt_at) WHERE status = 0;
+CREATE INDEX IF NOT EXISTS idx_af_webhook_delivery_webhook_id_created_at
+  ON af_webhook_delivery (webhook_id, created_at DESC);
+-- used to delete the finished deliveries past their retention
+CREATE INDEX IF NOT EXISTS idx_af_webhook_delivery_finished_created_at
+  ON af_webhook_delivery (created_at) WHERE status <> 0;
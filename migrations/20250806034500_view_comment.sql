@@ -0,0 +1,48 @@
+-- af_view_comment_thread stores the comment threads of the workspace views. A thread is anchored
+-- to a block of the view, or to the view itself when `block_id` is null.
+CREATE TABLE IF NOT EXISTS af_view_comment_thread (
+  thread_id UUID NOT NULL PRIMARY KEY DEFAULT uuid_generate_v4(),
+  workspace_id UUID NOT NULL,
+  view_id UUID NOT NULL,
+  block_id TEXT,
+  created_by BIGINT,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  resolved_by BIGINT,
+  resolved_at TIMESTAMP WITH TIME ZONE,
+  FOREIGN KEY (workspace_id) REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  FOREIGN KEY (created_by) REFERENCES af_user(uid) ON DELETE SET NULL,
+  FOREIGN KEY (resolved_by) REFERENCES af_user(uid) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_view_comment_thread_workspace_view
+  ON af_view_comment_thread (workspace_id, view_id);
+
+CREATE TABLE IF NOT EXISTS af_view_comment (
+  comment_id UUID NOT NULL PRIMARY KEY DEFAULT uuid_generate_v4(),
+  thread_id UUID NOT NULL,
+  content TEXT NOT NULL,
+  mentions UUID[] NOT NULL DEFAULT '{}',
+  created_by BIGINT,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  is_deleted BOOLEAN NOT NULL DEFAULT FALSE,
+  FOREIGN KEY (thread_id) REFERENCES af_view_comment_thread(thread_id) ON DELETE CASCADE,
+  FOREIGN KEY (created_by) REFERENCES af_user(uid) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_view_comment_thread_id
+  ON af_view_comment (thread_id, created_at);
+
+-- af_view_comment_edit keeps the previous content of a comment every time it is edited.
+CREATE TABLE IF NOT EXISTS af_view_comment_edit (
+  edit_id BIGSERIAL PRIMARY KEY,
+  comment_id UUID NOT NULL,
+  content TEXT NOT NULL,
+  edited_by BIGINT,
+  edited_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  FOREIGN KEY (comment_id) REFERENCES af_view_comment(comment_id) ON DELETE CASCADE,
+  FOREIGN KEY (edited_by) REFERENCES af_user(uid) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_view_comment_edit_comment_id
+  ON af_view_comment_edit (comment_id, edited_at);
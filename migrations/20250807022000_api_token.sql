@@ -0,0 +1,17 @@
+-- af_api_token stores the personal API tokens of the users. Only the SHA-256 hash of a token is
+-- stored, the token itself is returned once when it is created.
+CREATE TABLE IF NOT EXISTS af_api_token (
+  token_id UUID NOT NULL PRIMARY KEY DEFAULT uuid_generate_v4(),
+  uid BIGINT NOT NULL,
+  name TEXT NOT NULL,
+  token_hash TEXT NOT NULL UNIQUE,
+  token_prefix TEXT NOT NULL,
+  workspace_ids UUID[] NOT NULL,
+  scope INT NOT NULL,  -- 10 for read, 20 for write, 30 for admin
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  expires_at TIMESTAMP WITH TIME ZONE,
+  last_used_at TIMESTAMP WITH TIME ZONE,
+  FOREIGN KEY (uid) REFERENCES af_user(uid) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_api_token_uid ON af_api_token (uid);
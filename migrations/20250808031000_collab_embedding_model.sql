@@ -0,0 +1,117 @@
+-- Embeddings can be generated by any configured model, so the embedding column no longer has a
+-- fixed dimension. Each fragment records the model it was embedded with, and only embeddings of the
+-- same model are compared when searching.
+DROP INDEX IF EXISTS af_collab_embeddings_similarity_idx;
+
+ALTER TABLE af_collab_embeddings
+    ALTER COLUMN embedding TYPE VECTOR;
+
+-- All the embeddings generated before this migration used the default model.
+ALTER TABLE af_collab_embeddings
+    ADD COLUMN IF NOT EXISTS embedding_model TEXT NOT NULL DEFAULT 'text-embedding-3-small';
+
+ALTER TABLE af_collab_embeddings
+    ADD COLUMN IF NOT EXISTS embedding_dimensions INT GENERATED ALWAYS AS (vector_dims(embedding)) STORED;
+
+CREATE INDEX IF NOT EXISTS af_collab_embeddings_model_idx
+    ON af_collab_embeddings (oid, embedding_model);
+
+-- HNSW indexes require a fixed dimension, so the vectors are indexed per dimension. The index for
+-- the default model is created here, other dimensions are indexed when the server starts with a
+-- model that uses them.
+CREATE INDEX IF NOT EXISTS af_collab_embeddings_similarity_1536_idx
+    ON af_collab_embeddings USING hnsw ((embedding::VECTOR(1536)) vector_cosine_ops)
+    WHERE embedding_dimensions = 1536;
+
+ALTER TYPE af_fragment_v3 ALTER ATTRIBUTE embedding TYPE VECTOR;
+
+CREATE OR REPLACE PROCEDURE af_collab_embeddings_upsert_v2(
+    IN p_workspace_id UUID,
+    IN p_oid UUID,
+    IN p_tokens_used INT,
+    IN p_embedding_model TEXT,
+    IN p_fragments af_fragment_v3[]
+)
+LANGUAGE plpgsql
+AS
+$$
+BEGIN
+-- Delete all fragments for p_oid that are not present in the new fragment list.
+DELETE
+FROM af_collab_embeddings
+WHERE oid = p_oid
+  AND fragment_id NOT IN (
+    SELECT fragment_id FROM UNNEST(p_fragments) AS f
+);
+
+MERGE INTO af_collab_embeddings AS t
+    USING (
+        SELECT
+            f.fragment_id,
+            p_oid AS oid,
+            f.content_type,
+            f.contents,
+            f.embedding,
+            f.metadata,
+            f.fragment_index,
+            f.embedder_type
+        FROM UNNEST(p_fragments) AS f
+    ) AS s
+    ON t.oid = s.oid AND t.fragment_id = s.fragment_id
+    -- the fragment was embedded again, e.g. because the model changed
+    WHEN MATCHED AND s.embedding IS NOT NULL THEN
+        UPDATE SET content = s.contents,
+                   embedding = s.embedding,
+                   embedding_model = p_embedding_model,
+                   metadata = s.metadata,
+                   fragment_index = s.fragment_index,
+                   embedder_type = s.embedder_type,
+                   indexed_at = NOW()
+    -- this fragment has not changed
+    WHEN MATCHED THEN
+        UPDATE SET indexed_at = NOW()
+    WHEN NOT MATCHED THEN
+        INSERT (
+                fragment_id,
+                oid,
+                content_type,
+                content,
+                embedding,
+                embedding_model,
+                indexed_at,
+                metadata,
+                fragment_index,
+                embedder_type
+            )
+            VALUES (
+                s.fragment_id,
+                s.oid,
+                s.content_type,
+                s.contents,
+                s.embedding,
+                p_embedding_model,
+                NOW(),
+                s.metadata,
+                s.fragment_index,
+                s.embedder_type
+            );
+
+INSERT INTO af_workspace_ai_usage(
+    created_at,
+    workspace_id,
+    search_requests,
+    search_tokens_consumed,
+    index_tokens_consumed
+)
+VALUES (
+           NOW()::date,
+           p_workspace_id,
+           0,
+           0,
+           p_tokens_used
+       )
+    ON CONFLICT (created_at, workspace_id)
+        DO UPDATE SET index_tokens_consumed = af_workspace_ai_usage.index_tokens_consumed + p_tokens_used;
+
+END
+$$;
@@ -0,0 +1,15 @@
+-- Database rows and the documents attached to them are not views, so they can't be matched
+-- against the views a user is allowed to search. af_database_row_index links every indexed row,
+-- and the document of the row, to the database it belongs to. Search results are then resolved to
+-- a view of that database.
+CREATE TABLE IF NOT EXISTS af_database_row_index (
+  oid UUID PRIMARY KEY,
+  workspace_id UUID NOT NULL,
+  database_id UUID NOT NULL,
+  row_id UUID NOT NULL,
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  FOREIGN KEY (workspace_id) REFERENCES af_workspace(workspace_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_database_row_index_database
+  ON af_database_row_index (workspace_id, database_id);
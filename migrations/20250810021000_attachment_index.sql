@@ -0,0 +1,38 @@
+-- Files uploaded to the workspace are not collabs, so the text extracted from them is indexed in
+-- separate tables. af_attachment_index keeps the whole text for keyword search and links the file
+-- to the page it was uploaded to (the `parent_dir` of the blob).
+CREATE TABLE IF NOT EXISTS af_attachment_index (
+  attachment_id UUID NOT NULL DEFAULT gen_random_uuid() UNIQUE,
+  workspace_id UUID NOT NULL,
+  parent_dir TEXT NOT NULL,
+  file_id TEXT NOT NULL,
+  view_id UUID,
+  content_type TEXT NOT NULL,
+  uid BIGINT NOT NULL,
+  content TEXT NOT NULL,
+  content_tsv TSVECTOR GENERATED ALWAYS AS (to_tsvector('simple', content)) STORED,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  indexed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (workspace_id, parent_dir, file_id),
+  FOREIGN KEY (workspace_id) REFERENCES af_workspace(workspace_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_attachment_index_tsv
+  ON af_attachment_index USING GIN (content_tsv);
+CREATE INDEX IF NOT EXISTS idx_af_attachment_index_view_id
+  ON af_attachment_index (workspace_id, view_id);
+
+-- Chunks of the extracted text, embedded the same way as the fragments of af_collab_embeddings.
+CREATE TABLE IF NOT EXISTS af_attachment_embeddings (
+  attachment_id UUID NOT NULL REFERENCES af_attachment_index(attachment_id) ON DELETE CASCADE,
+  fragment_index INT NOT NULL,
+  content TEXT NOT NULL,
+  embedding VECTOR NOT NULL,
+  embedding_model TEXT NOT NULL,
+  embedding_dimensions INT GENERATED ALWAYS AS (vector_dims(embedding)) STORED,
+  metadata JSONB,
+  PRIMARY KEY (attachment_id, fragment_index)
+);
+
+CREATE INDEX IF NOT EXISTS af_attachment_embeddings_model_idx
+  ON af_attachment_embeddings (attachment_id, embedding_model);
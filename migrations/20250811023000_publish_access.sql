@@ -0,0 +1,4 @@
+-- Published pages can be protected by a password and expire at a given time.
+ALTER TABLE af_published_collab
+ADD COLUMN password_hash TEXT,
+ADD COLUMN expires_at TIMESTAMP WITH TIME ZONE;
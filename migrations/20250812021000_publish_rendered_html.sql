@@ -0,0 +1,4 @@
+-- Static HTML rendering of published pages, cleared when the page is republished.
+ALTER TABLE af_published_collab
+ADD COLUMN rendered_html TEXT,
+ADD COLUMN rendered_summary TEXT;
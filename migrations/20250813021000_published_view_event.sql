@@ -0,0 +1,15 @@
+-- Read events of published pages. Visitors are only identified by a hash that changes every day,
+-- raw IP addresses and user agents are never stored.
+CREATE TABLE IF NOT EXISTS af_published_view_event (
+  event_id BIGSERIAL PRIMARY KEY,
+  workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  view_id UUID NOT NULL,
+  visitor_hash TEXT NOT NULL,
+  referrer_domain TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_published_view_event_workspace_created_at
+  ON af_published_view_event (workspace_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_af_published_view_event_created_at
+  ON af_published_view_event (created_at);
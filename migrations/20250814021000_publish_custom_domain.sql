@@ -0,0 +1,15 @@
+-- Custom domains serving the pages published by a workspace. A domain is claimed by adding a TXT
+-- record with the verification token, and is only served once verified. Several workspaces may
+-- claim the same domain, but only one can verify it.
+CREATE TABLE IF NOT EXISTS af_publish_custom_domain (
+  workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  domain TEXT NOT NULL,
+  verification_token TEXT NOT NULL,
+  verified_at TIMESTAMPTZ,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (workspace_id, domain)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_af_publish_custom_domain_verified_domain
+  ON af_publish_custom_domain (domain)
+  WHERE verified_at IS NOT NULL;
@@ -0,0 +1,34 @@
+-- Append-only record of security relevant actions in a workspace. The actor is not a foreign key,
+-- so that the record outlives the user account.
+CREATE TABLE IF NOT EXISTS af_workspace_audit_log (
+  event_id BIGSERIAL PRIMARY KEY,
+  workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  actor_uid BIGINT,
+  action TEXT NOT NULL,
+  target TEXT,
+  ip_address TEXT,
+  user_agent TEXT,
+  before_value JSONB,
+  after_value JSONB,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_workspace_audit_log_workspace_created_at
+  ON af_workspace_audit_log (workspace_id, created_at DESC);
+
+-- Entries can not be modified, and are only removed together with their workspace
+CREATE OR REPLACE FUNCTION prevent_workspace_audit_log_change()
+RETURNS TRIGGER AS $$
+BEGIN
+  IF TG_OP = 'DELETE'
+    AND NOT EXISTS (SELECT 1 FROM af_workspace WHERE workspace_id = OLD.workspace_id) THEN
+    RETURN OLD;
+  END IF;
+  RAISE EXCEPTION 'af_workspace_audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_prevent_workspace_audit_log_change
+BEFORE UPDATE OR DELETE ON af_workspace_audit_log
+FOR EACH ROW
+EXECUTE FUNCTION prevent_workspace_audit_log_change();
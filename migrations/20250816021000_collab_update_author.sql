@@ -0,0 +1,17 @@
+-- Who wrote which part of a document. Each row attributes the clocks [clock_start, clock_end) of a
+-- Yjs client to a user. Consecutive updates of the same client that are saved together are merged
+-- into a single row, and updated_at is the time the last of them was received by the server, taken
+-- from the id of its message in the update stream.
+CREATE TABLE IF NOT EXISTS af_collab_update_author (
+  workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  oid UUID NOT NULL,
+  client_id BIGINT NOT NULL,
+  clock_start BIGINT NOT NULL,
+  clock_end BIGINT NOT NULL,
+  uid BIGINT NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL,
+  PRIMARY KEY (oid, client_id, clock_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_update_author_clock_end
+  ON af_collab_update_author (oid, client_id, clock_end);
@@ -15,7 +15,7 @@ use collab::preclude::Collab;
 use collab_entity::CollabType;
 use collab_stream::model::UpdateStreamMessage;
 use dashmap::DashMap;
-use database::file::s3_client_impl::AwsS3BucketClientImpl;
+use database::file::app_bucket_client::AppBucketClient;
 use database_entity::dto::{
   CollabParams, CollabUpdateData, PendingCollabWrite, QueryCollab, QueryCollabResult,
 };
@@ -50,7 +50,7 @@ impl CollabCache {
     thread_pool: Arc<ThreadPoolNoAbort>,
     redis_conn_manager: redis::aio::ConnectionManager,
     pg_pool: PgPool,
-    s3: AwsS3BucketClientImpl,
+    s3: AppBucketClient,
     metrics: Arc<CollabMetrics>,
     s3_collab_threshold: usize,
   ) -> Arc<Self> {
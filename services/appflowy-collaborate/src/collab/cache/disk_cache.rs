@@ -11,8 +11,10 @@ use database::collab::{
   batch_select_collab_blob, insert_into_af_collab, insert_into_af_collab_bulk_for_user,
   is_collab_exists, select_blob_from_af_collab, select_collabs_created_since, AppResult,
 };
-use database::file::s3_client_impl::AwsS3BucketClientImpl;
+use database::collab_author::delete_collab_update_authors;
+use database::file::app_bucket_client::AppBucketClient;
 use database::file::{BucketClient, ResponseBlob};
+use database::index::delete_collab_keyword_index;
 use database_entity::dto::{
   CollabParams, CollabUpdateData, PendingCollabWrite, QueryCollab, QueryCollabResult,
   ZSTD_COMPRESSION_LEVEL,
@@ -32,7 +34,7 @@ use uuid::Uuid;
 pub struct CollabDiskCache {
   thread_pool: Arc<ThreadPoolNoAbort>,
   pg_pool: PgPool,
-  s3: AwsS3BucketClientImpl,
+  s3: AppBucketClient,
   s3_collab_threshold: usize,
   metrics: Arc<CollabMetrics>,
 }
@@ -41,7 +43,7 @@ impl CollabDiskCache {
   pub fn new(
     thread_pool: Arc<ThreadPoolNoAbort>,
     pg_pool: PgPool,
-    s3: AwsS3BucketClientImpl,
+    s3: AppBucketClient,
     s3_collab_threshold: usize,
     metrics: Arc<CollabMetrics>,
   ) -> Self {
@@ -103,7 +105,7 @@ impl CollabDiskCache {
     Ok(())
   }
 
-  pub fn s3_client(&self) -> AwsS3BucketClientImpl {
+  pub fn s3_client(&self) -> AppBucketClient {
     self.s3.clone()
   }
 
@@ -112,7 +114,7 @@ impl CollabDiskCache {
     uid: &i64,
     mut params: CollabParams,
     transaction: &mut Transaction<'_, sqlx::Postgres>,
-    s3: AwsS3BucketClientImpl,
+    s3: AppBucketClient,
     s3_collab_threshold: usize,
     metrics: &CollabMetrics,
   ) -> AppResult<()> {
@@ -228,7 +230,7 @@ impl CollabDiskCache {
   }
 
   async fn get_collab_from_s3(
-    s3: &AwsS3BucketClientImpl,
+    s3: &AppBucketClient,
     key: String,
   ) -> Result<(Rid, EncodedCollab), AppError> {
     match s3.get_blob(&key).await {
@@ -511,6 +513,9 @@ impl CollabDiskCache {
     )
     .execute(&self.pg_pool)
     .await?;
+    // the collab is no longer searchable, like when its row is removed
+    delete_collab_keyword_index(&self.pg_pool, object_id).await?;
+    delete_collab_update_authors(&self.pg_pool, object_id).await?;
 
     trace!("record {}:{} marked as deleted", workspace_id, object_id);
     let key = collab_key(workspace_id, object_id);
@@ -568,7 +573,7 @@ impl CollabDiskCache {
   }
 
   async fn insert_blob_with_retries(
-    s3: AwsS3BucketClientImpl,
+    s3: AppBucketClient,
     key: String,
     blob: Bytes,
     mut retries: usize,
@@ -611,7 +616,7 @@ impl CollabDiskCache {
 }
 
 async fn batch_put_collab_to_s3(
-  s3: &AwsS3BucketClientImpl,
+  s3: &AppBucketClient,
   collabs: HashMap<String, Bytes>,
 ) -> Result<(), AppError> {
   let mut join_set = JoinSet::<Result<(), AppError>>::new();
@@ -639,7 +644,7 @@ async fn batch_put_collab_to_s3(
 }
 
 async fn batch_get_collab_from_s3(
-  s3: &AwsS3BucketClientImpl,
+  s3: &AppBucketClient,
   workspace_id: &Uuid,
   params: Vec<QueryCollab>,
   results: &mut HashMap<Uuid, QueryCollabResult>,
@@ -1,5 +1,6 @@
 use crate::collab::cache::mem_cache::MillisSeconds;
 use crate::collab::cache::CollabCache;
+use crate::snapshot::SnapshotControl;
 use access_control::act::Action;
 use access_control::collab::CollabAccessControl;
 use anyhow::anyhow;
@@ -19,7 +20,9 @@ use collab_stream::lease::Lease;
 use collab_stream::model::{AwarenessStreamUpdate, MessageId, UpdateStreamMessage};
 use collab_stream::stream_router::StreamRouter;
 use database::collab::AppResult;
-use database_entity::dto::{CollabParams, CollabUpdateData, QueryCollab};
+use database::collab_author::{insert_collab_update_authors, CollabUpdateAuthorRange};
+use database_entity::dto::{CollabParams, CollabUpdateData, InsertSnapshotParams, QueryCollab};
+use indexer::collab_indexer::{DatabaseIndexState, UnindexedDatabaseRow};
 use indexer::scheduler::{IndexerScheduler, UnindexedCollabTask, UnindexedData};
 use infra::thread_pool::ThreadPoolNoAbort;
 use itertools::Itertools;
@@ -27,6 +30,7 @@ use rayon::prelude::*;
 use redis::aio::ConnectionManager;
 use redis::streams::{StreamTrimOptions, StreamTrimmingMode};
 use redis::AsyncCommands;
+use sqlx::PgPool;
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -38,7 +42,7 @@ use yrs::block::ClientID;
 use yrs::sync::AwarenessUpdate;
 use yrs::updates::decoder::Decode;
 use yrs::updates::encoder::Encode;
-use yrs::{ReadTxn, StateVector, Update};
+use yrs::{DeleteSet, ReadTxn, StateVector, Update};
 
 pub struct CollabManager {
   collab_cache: Arc<CollabCache>,
@@ -48,6 +52,8 @@ pub struct CollabManager {
   connection_manager: ConnectionManager,
   indexer_scheduler: Arc<IndexerScheduler>,
   snapshot_thread_pool: Arc<ThreadPoolNoAbort>,
+  snapshot_control: SnapshotControl,
+  pg_pool: PgPool,
 }
 
 impl CollabManager {
@@ -60,6 +66,8 @@ impl CollabManager {
     update_streams: Arc<StreamRouter>,
     awareness_broadcast: Arc<AwarenessGossip>,
     indexer_scheduler: Arc<IndexerScheduler>,
+    snapshot_control: SnapshotControl,
+    pg_pool: PgPool,
   ) -> Arc<Self> {
     Arc::new(Self {
       access_control,
@@ -69,6 +77,8 @@ impl CollabManager {
       connection_manager,
       indexer_scheduler,
       snapshot_thread_pool: thread_pool,
+      snapshot_control,
+      pg_pool,
     })
   }
 
@@ -349,6 +359,9 @@ impl CollabManager {
         workspace_id
       );
 
+      self
+        .record_update_authors(workspace_id, &all_object_updates)
+        .await;
       let snapshot_tasks = self
         .collect_snapshot_tasks(workspace_id, all_object_updates)
         .await?;
@@ -407,6 +420,36 @@ impl CollabManager {
       .map_err(|e| anyhow!("Failed to acquire workspace lock: {}", e))
   }
 
+  /// Attributes the blocks inserted by the document updates to the users who sent them, so that
+  /// the document blame can tell who wrote what. Failing to record them never fails the snapshot.
+  async fn record_update_authors(
+    &self,
+    workspace_id: WorkspaceId,
+    all_object_updates: &HashMap<Uuid, Vec<UpdateStreamMessage>>,
+  ) {
+    let ranges = self.snapshot_thread_pool.install(|| {
+      all_object_updates
+        .par_iter()
+        .flat_map_iter(|(object_id, updates)| update_author_ranges(*object_id, updates))
+        .collect::<Vec<_>>()
+    });
+    match ranges {
+      Ok(ranges) => {
+        if let Err(err) = insert_collab_update_authors(&self.pg_pool, &workspace_id, &ranges).await
+        {
+          warn!(
+            "failed to record update authors of workspace {}: {}",
+            workspace_id, err
+          );
+        }
+      },
+      Err(err) => warn!(
+        "Thread pool panic when collecting update authors of workspace {}: {}",
+        workspace_id, err
+      ),
+    }
+  }
+
   /// Collects all snapshot tasks for the workspace
   async fn collect_snapshot_tasks(
     &self,
@@ -461,22 +504,27 @@ impl CollabManager {
           .filter_map(|task| {
             match apply_updates_to_snapshot(
               client_id,
+              task.workspace_id,
               task.object_id,
               task.collab_type,
               task.rid,
               task.update_snapshot,
               task.updates,
             ) {
-              Ok((rid, full_state, state_vector, paragraphs)) => Some(ProcessedSnapshot {
-                workspace_id: task.workspace_id,
-                object_id: task.object_id,
-                collab_type: task.collab_type,
-                user_id: task.user_id,
-                rid,
-                full_state,
-                state_vector: state_vector.encode_v1().into(),
-                paragraphs,
-              }),
+              Ok((rid, full_state, state_vector, paragraphs, row, database)) => {
+                Some(ProcessedSnapshot {
+                  workspace_id: task.workspace_id,
+                  object_id: task.object_id,
+                  collab_type: task.collab_type,
+                  user_id: task.user_id,
+                  rid,
+                  full_state,
+                  state_vector: state_vector.encode_v1().into(),
+                  paragraphs,
+                  row,
+                  database,
+                })
+              },
               Err(err) => {
                 warn!(
                   "Failed to process collab {} snapshot: {}",
@@ -508,6 +556,8 @@ impl CollabManager {
       );
 
       let encoded_result = self.encode_collab_chunk(chunk)?;
+      self.create_history_snapshots(chunk);
+      self.index_database_rows(chunk);
 
       // Collect indexing tasks
       for task in encoded_result.indexing_tasks {
@@ -528,6 +578,55 @@ impl CollabManager {
     Ok(())
   }
 
+  /// Keeps a point-in-time copy of the documents, so that users can browse and restore their
+  /// previous versions. Snapshots are throttled per document by the [SnapshotControl].
+  fn create_history_snapshots(&self, chunk: &[ProcessedSnapshot]) {
+    for snapshot in chunk {
+      if snapshot.collab_type != CollabType::Document {
+        continue;
+      }
+      let params = InsertSnapshotParams {
+        object_id: snapshot.object_id,
+        doc_state: snapshot.full_state.clone(),
+        workspace_id: snapshot.workspace_id,
+        collab_type: snapshot.collab_type,
+      };
+      let snapshot_control = self.snapshot_control.clone();
+      tokio::spawn(async move {
+        let object_id = params.object_id;
+        if let Err(err) = snapshot_control.create_snapshot_if_due(params).await {
+          warn!(
+            "failed to create history snapshot for {}: {}",
+            object_id, err
+          );
+        }
+      });
+    }
+  }
+
+  /// Database rows are indexed separately from the other collabs, as the fields of their
+  /// database have to be loaded to serialize their cells. The rows removed from a database, and
+  /// the rows of a database whose fields changed, are updated in the index as well.
+  fn index_database_rows(&self, chunk: &[ProcessedSnapshot]) {
+    let rows = chunk
+      .iter()
+      .filter_map(|snapshot| snapshot.row.clone())
+      .collect::<Vec<_>>();
+    if !rows.is_empty() {
+      self.indexer_scheduler.index_database_rows(rows);
+    }
+    for snapshot in chunk {
+      if let Some((before, after)) = &snapshot.database {
+        self.indexer_scheduler.update_database_index(
+          snapshot.workspace_id,
+          snapshot.object_id,
+          before,
+          after,
+        );
+      }
+    }
+  }
+
   /// Encodes a chunk of collabs in parallel
   fn encode_collab_chunk(&self, chunk: &[ProcessedSnapshot]) -> anyhow::Result<EncodedChunkResult> {
     // Prepare data for parallel encoding
@@ -665,25 +764,41 @@ pub struct CollabState {
 
 fn apply_updates_to_snapshot(
   client_id: ClientID,
+  workspace_id: WorkspaceId,
   object_id: ObjectId,
   collab_type: CollabType,
   rid_snapshot: Rid,
   update_snapshot: Bytes,
   updates: Vec<UpdateStreamMessage>,
-) -> anyhow::Result<(Rid, Bytes, StateVector, Vec<String>)> {
+) -> anyhow::Result<(
+  Rid,
+  Bytes,
+  StateVector,
+  Vec<String>,
+  Option<UnindexedDatabaseRow>,
+  Option<(DatabaseIndexState, DatabaseIndexState)>,
+)> {
   let options = CollabOptions::new(object_id.to_string(), client_id);
   let mut collab = Collab::new_with_options(CollabOrigin::Server, options)
     .map_err(|err| anyhow!("failed to create collab: {}", err))?;
 
+  // First apply the snapshot
+  if !update_snapshot.is_empty() {
+    collab
+      .transact_mut()
+      .apply_update(decode_update(&update_snapshot)?)?;
+  }
+  // the row index of a database depends on the changes made by the updates
+  let database_before = if collab_type == CollabType::Database && !update_snapshot.is_empty() {
+    DatabaseIndexState::from_collab(&collab)
+  } else {
+    None
+  };
+
   // Apply all updates to build the full state
   let mut rid = rid_snapshot;
   {
     let mut tx = collab.transact_mut();
-    // First apply the snapshot
-    if !update_snapshot.is_empty() {
-      tx.apply_update(decode_update(&update_snapshot)?)?;
-    }
-
     // Then apply updates from redis stream
     trace!(
       "processing {} updates for {}:{}",
@@ -714,8 +829,85 @@ fn apply_updates_to_snapshot(
   } else {
     vec![]
   };
+  // the cells of a row are indexed later, with the fields of its database
+  let row = if collab_type == CollabType::DatabaseRow {
+    UnindexedDatabaseRow::from_collab(workspace_id, &collab)
+  } else {
+    None
+  };
+  let database = database_before.and_then(|before| {
+    let after = DatabaseIndexState::from_collab(&collab)?;
+    Some((before, after))
+  });
+
+  Ok((
+    rid,
+    full_state.into(),
+    state_vector,
+    paragraphs,
+    row,
+    database,
+  ))
+}
 
-  Ok((rid, full_state.into(), state_vector, paragraphs))
+/// Returns the clock ranges inserted by the document updates, with the user who sent them and the
+/// time of their message id. Each Yjs client is bound to the first user seen sending it, and
+/// consecutive ranges of a client are merged.
+fn update_author_ranges(
+  object_id: ObjectId,
+  updates: &[UpdateStreamMessage],
+) -> Vec<CollabUpdateAuthorRange> {
+  let mut ranges: Vec<CollabUpdateAuthorRange> = Vec::new();
+  let mut last_range_by_client: HashMap<ClientID, usize> = HashMap::new();
+  for message in updates {
+    if message.collab_type != CollabType::Document {
+      continue;
+    }
+    let Some(uid) = message.sender.client_user_id() else {
+      continue;
+    };
+    let updated_at =
+      DateTime::<Utc>::from_timestamp_millis(message.last_message_id.timestamp as i64)
+        .unwrap_or_else(Utc::now);
+    let update = match message.update_flags {
+      UpdateFlags::Lib0v1 => Update::decode_v1(&message.update),
+      UpdateFlags::Lib0v2 => Update::decode_v2(&message.update),
+    };
+    let update = match update {
+      Ok(update) => update,
+      Err(err) => {
+        warn!(
+          "failed to decode update of {} for attribution: {}",
+          object_id, err
+        );
+        continue;
+      },
+    };
+    for (&client, blocks) in DeleteSet::from(update.insertions(true)).iter() {
+      for block in blocks.iter() {
+        let (start, end) = (block.start as i64, block.end as i64);
+        match last_range_by_client.get(&client).copied() {
+          Some(last) if ranges[last].uid != uid => {},
+          Some(last) if ranges[last].clock_end == start => {
+            ranges[last].clock_end = end;
+            ranges[last].updated_at = updated_at;
+          },
+          _ => {
+            last_range_by_client.insert(client, ranges.len());
+            ranges.push(CollabUpdateAuthorRange {
+              oid: object_id,
+              client_id: client as i64,
+              clock_start: start,
+              clock_end: end,
+              uid,
+              updated_at,
+            });
+          },
+        }
+      }
+    }
+  }
+  ranges
 }
 
 pub fn decode_update(update: &[u8]) -> AppResult<Update> {
@@ -757,6 +949,9 @@ struct ProcessedSnapshot {
   full_state: Bytes,
   state_vector: Bytes,
   paragraphs: Vec<String>,
+  row: Option<UnindexedDatabaseRow>,
+  /// state of the row index of a database, before and after the updates
+  database: Option<(DatabaseIndexState, DatabaseIndexState)>,
 }
 struct EncodedChunkResult {
   /// Collab parameters grouped by user ID for batch insertion
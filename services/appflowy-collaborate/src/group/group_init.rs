@@ -32,6 +32,7 @@ use database::collab::{CollabStore, GetCollabOrigin};
 use database_entity::dto::CollabParams;
 use futures::{pin_mut, Sink, Stream};
 use futures_util::{SinkExt, StreamExt};
+use indexer::collab_indexer::{DatabaseIndexState, UnindexedDatabaseRow};
 use indexer::scheduler::{IndexerScheduler, UnindexedCollabTask, UnindexedData};
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
@@ -1064,6 +1065,7 @@ impl CollabPersister {
       collab,
       rid,
       applied_messages,
+      database_index: None,
     };
     Ok(snapshot)
   }
@@ -1090,6 +1092,12 @@ impl CollabPersister {
         (Rid::default(), collab)
       },
     };
+    // the row index of a database depends on the changes made by the pending updates
+    let database_index = if self.collab_type == CollabType::Database {
+      DatabaseIndexState::from_collab(&collab)
+    } else {
+      None
+    };
     let start = Instant::now();
     let mut i = 0;
     let mut applied_messages = Vec::new();
@@ -1130,6 +1138,7 @@ impl CollabPersister {
       collab,
       rid,
       applied_messages,
+      database_index,
     }))
   }
 
@@ -1182,6 +1191,22 @@ impl CollabPersister {
             self.index_collab_content(text);
           }
         },
+        CollabType::DatabaseRow => {
+          if let Some(row) = UnindexedDatabaseRow::from_collab(self.workspace_id, &collab) {
+            self.indexer_scheduler.index_database_rows(vec![row]);
+          }
+        },
+        CollabType::Database => {
+          let before = snapshot.database_index.as_ref();
+          if let Some((before, after)) = before.zip(DatabaseIndexState::from_collab(&collab)) {
+            self.indexer_scheduler.update_database_index(
+              self.workspace_id,
+              self.object_id,
+              before,
+              &after,
+            );
+          }
+        },
         _ => {
           // TODO(nathan): support other collab type
         },
@@ -1285,4 +1310,7 @@ pub struct CollabSnapshot {
   pub collab: Collab,
   pub rid: Rid,
   pub applied_messages: Vec<MessageId>,
+  /// State of the row index of a database before the applied messages, see
+  /// [IndexerScheduler::update_database_index].
+  pub database_index: Option<DatabaseIndexState>,
 }
@@ -3,7 +3,9 @@ use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use collab::entity::{EncodedCollab, EncoderVersion};
+use dashmap::DashMap;
 use sqlx::PgPool;
+use tokio::sync::Mutex;
 use tracing::debug;
 use uuid::Uuid;
 use validator::Validate;
@@ -11,8 +13,9 @@ use validator::Validate;
 use app_error::AppError;
 use database::collab::{
   get_all_collab_snapshot_meta, select_snapshot, AppResult, COLLAB_SNAPSHOT_LIMIT,
+  SNAPSHOT_PER_HOUR,
 };
-use database::file::s3_client_impl::AwsS3BucketClientImpl;
+use database::file::app_bucket_client::AppBucketClient;
 use database::file::{BucketClient, ResponseBlob};
 use database_entity::dto::{
   AFSnapshotMeta, AFSnapshotMetas, InsertSnapshotParams, SnapshotData, ZSTD_COMPRESSION_LEVEL,
@@ -22,6 +25,17 @@ use crate::metrics::CollabMetrics;
 
 pub const SNAPSHOT_TICK_INTERVAL: Duration = Duration::from_secs(2);
 
+/// Minimum time between two history snapshots of the same collab, created by
+/// [SnapshotControl::create_snapshot_if_due].
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(3600 / SNAPSHOT_PER_HOUR as u64);
+
+/// Maximum number of collabs whose latest snapshot time is kept in memory.
+const MAX_CACHED_LATEST_SNAPSHOTS: usize = 10_000;
+
+/// Number of the oldest entries evicted at once when the cache is full of entries that still
+/// throttle snapshots, so that the eviction doesn't run on every insert.
+const LATEST_SNAPSHOTS_EVICTION_BATCH: usize = MAX_CACHED_LATEST_SNAPSHOTS / 10;
+
 fn collab_snapshot_key(workspace_id: &Uuid, object_id: &Uuid, snapshot_id: i64) -> String {
   let snapshot_id = u64::MAX - snapshot_id as u64;
   format!(
@@ -62,21 +76,101 @@ fn get_meta(objct_key: String) -> Option<AFSnapshotMeta> {
 #[derive(Clone)]
 pub struct SnapshotControl {
   pg_pool: PgPool,
-  s3: AwsS3BucketClientImpl,
+  s3: AppBucketClient,
   collab_metrics: Arc<CollabMetrics>,
+  /// Creation time of the latest snapshot of recently snapshotted collabs, used to throttle the
+  /// snapshots. Bounded by [MAX_CACHED_LATEST_SNAPSHOTS], see [SnapshotControl::cache_latest_snapshot].
+  latest_snapshots: Arc<DashMap<Uuid, DateTime<Utc>>>,
+  /// Serializes [SnapshotControl::create_snapshot_if_due] per collab, so that concurrent calls
+  /// don't both find the snapshot due and create two of them.
+  snapshot_locks: Arc<DashMap<Uuid, Arc<Mutex<()>>>>,
 }
 
 impl SnapshotControl {
   pub async fn new(
     pg_pool: PgPool,
-    s3: AwsS3BucketClientImpl,
+    s3: AppBucketClient,
     collab_metrics: Arc<CollabMetrics>,
   ) -> Self {
     Self {
       pg_pool,
       s3,
       collab_metrics,
+      latest_snapshots: Arc::new(DashMap::new()),
+      snapshot_locks: Arc::new(DashMap::new()),
+    }
+  }
+
+  /// Creates a snapshot only if the latest snapshot of the collab is older than
+  /// [SNAPSHOT_INTERVAL]. Returns `None` if no snapshot was created.
+  pub async fn create_snapshot_if_due(
+    &self,
+    params: InsertSnapshotParams,
+  ) -> AppResult<Option<AFSnapshotMeta>> {
+    let object_id = params.object_id;
+    let lock = self
+      .snapshot_locks
+      .entry(object_id)
+      .or_insert_with(|| Arc::new(Mutex::new(())))
+      .clone();
+    let result = {
+      let _guard = lock.lock().await;
+      self.create_snapshot_if_due_locked(params).await
+    };
+    // the lock is only kept while someone is waiting for it
+    drop(lock);
+    self
+      .snapshot_locks
+      .remove_if(&object_id, |_, lock| Arc::strong_count(lock) == 1);
+    result
+  }
+
+  async fn create_snapshot_if_due_locked(
+    &self,
+    params: InsertSnapshotParams,
+  ) -> AppResult<Option<AFSnapshotMeta>> {
+    let latest = match self.latest_snapshots.get(&params.object_id) {
+      Some(created_at) => Some(*created_at),
+      None => self
+        .get_collab_snapshot_list(&params.workspace_id, &params.object_id)
+        .await?
+        .0
+        .first()
+        .map(|meta| meta.created_at),
+    };
+    if let Some(latest) = latest {
+      let elapsed = (Utc::now() - latest).to_std().unwrap_or_default();
+      if elapsed < SNAPSHOT_INTERVAL {
+        self.cache_latest_snapshot(params.object_id, latest);
+        return Ok(None);
+      }
+    }
+    self.create_snapshot(params).await.map(Some)
+  }
+
+  /// Remembers the creation time of the latest snapshot of a collab. Entries older than
+  /// [SNAPSHOT_INTERVAL] no longer throttle anything, so they are evicted once the cache is full.
+  /// If the cache is still full afterwards, the oldest entries are evicted: a miss only costs a
+  /// bucket listing.
+  fn cache_latest_snapshot(&self, object_id: Uuid, created_at: DateTime<Utc>) {
+    if self.latest_snapshots.len() >= MAX_CACHED_LATEST_SNAPSHOTS {
+      let now = Utc::now();
+      self
+        .latest_snapshots
+        .retain(|_, latest| (now - *latest).to_std().unwrap_or_default() < SNAPSHOT_INTERVAL);
+      if self.latest_snapshots.len() >= MAX_CACHED_LATEST_SNAPSHOTS {
+        let mut entries = self
+          .latest_snapshots
+          .iter()
+          .map(|entry| (*entry.value(), *entry.key()))
+          .collect::<Vec<_>>();
+        entries.sort_unstable();
+        for (_, object_id) in entries.into_iter().take(LATEST_SNAPSHOTS_EVICTION_BATCH) {
+          self.latest_snapshots.remove(&object_id);
+        }
+      }
     }
+    self.latest_snapshots.insert(object_id, created_at);
   }
 
   pub async fn create_snapshot(&self, params: InsertSnapshotParams) -> AppResult<AFSnapshotMeta> {
@@ -117,6 +211,7 @@ impl SnapshotControl {
       self.s3.delete_blobs(trimmed).await?;
     }
 
+    self.cache_latest_snapshot(params.object_id, timestamp);
     Ok(AFSnapshotMeta {
       snapshot_id,
       object_id: params.object_id.to_string(),
@@ -1,3 +1,4 @@
+mod presence;
 mod server;
 mod session;
 mod workspace;
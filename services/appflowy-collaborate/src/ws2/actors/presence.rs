@@ -0,0 +1,268 @@
+use appflowy_proto::{ObjectId, SessionPresence};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use yrs::block::ClientID;
+use yrs::sync::awareness::AwarenessUpdateEntry;
+use yrs::sync::AwarenessUpdate;
+
+/// Workspace presence is gossiped between server nodes as awareness updates of this object, one
+/// awareness client per connected session. No collab uses the nil object id.
+pub const PRESENCE_OBJECT_ID: ObjectId = Uuid::nil();
+
+/// JSON state of a removed awareness client.
+const NULL_STATE: &str = "null";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PresenceState {
+  uid: i64,
+  device_id: String,
+  object_id: Option<ObjectId>,
+}
+
+#[derive(Debug)]
+struct PresenceEntry {
+  state: PresenceState,
+  clock: u32,
+  /// Whether the session is connected to this server node.
+  local: bool,
+  refreshed_at: Instant,
+}
+
+/// Presence of the sessions connected to a workspace, across all server nodes.
+///
+/// Each node owns the entries of its own sessions and periodically announces them again, so
+/// that the entries of a node which went away without saying goodbye eventually expire.
+#[derive(Debug, Default)]
+pub struct WorkspacePresenceState {
+  entries: HashMap<ClientID, PresenceEntry>,
+  /// Clocks of the removed sessions, so that updates gossiped before the removal and received
+  /// after it don't bring them back.
+  removed: HashMap<ClientID, (u32, Instant)>,
+}
+
+impl WorkspacePresenceState {
+  /// Sets the presence of a session connected to this node, returning the update to gossip.
+  pub fn set_local(
+    &mut self,
+    session_id: ClientID,
+    uid: i64,
+    device_id: String,
+    object_id: Option<ObjectId>,
+  ) -> AwarenessUpdate {
+    let state = PresenceState {
+      uid,
+      device_id,
+      object_id,
+    };
+    let json = serde_json::to_string(&state).unwrap_or_else(|_| NULL_STATE.to_string());
+    // a reconnecting session must outrun the clock of its own removal
+    let clock = match self.entries.get(&session_id) {
+      Some(entry) => entry.clock + 1,
+      None => self
+        .removed
+        .remove(&session_id)
+        .map(|(clock, _)| clock + 1)
+        .unwrap_or(0),
+    };
+    self.entries.insert(
+      session_id,
+      PresenceEntry {
+        state,
+        clock,
+        local: true,
+        refreshed_at: Instant::now(),
+      },
+    );
+    awareness_update([(session_id, clock, json)])
+  }
+
+  /// Changes the object focused in a session connected to this node. Returns the update to
+  /// gossip, if the focus changed.
+  pub fn focus_local(
+    &mut self,
+    session_id: ClientID,
+    object_id: Option<ObjectId>,
+  ) -> Option<AwarenessUpdate> {
+    let entry = self.entries.get(&session_id).filter(|entry| entry.local)?;
+    if entry.state.object_id == object_id {
+      return None;
+    }
+    let (uid, device_id) = (entry.state.uid, entry.state.device_id.clone());
+    Some(self.set_local(session_id, uid, device_id, object_id))
+  }
+
+  /// Removes the presence of a session which left this node, returning the update to gossip.
+  pub fn remove_local(&mut self, session_id: ClientID) -> Option<AwarenessUpdate> {
+    let entry = self.entries.remove(&session_id)?;
+    let clock = entry.clock + 1;
+    self.removed.insert(session_id, (clock, Instant::now()));
+    Some(awareness_update([(
+      session_id,
+      clock,
+      NULL_STATE.to_string(),
+    )]))
+  }
+
+  /// Returns the update announcing again all sessions of this node.
+  pub fn refresh_local(&mut self) -> Option<AwarenessUpdate> {
+    let now = Instant::now();
+    let mut states = vec![];
+    for (session_id, entry) in self.entries.iter_mut().filter(|(_, entry)| entry.local) {
+      entry.clock += 1;
+      entry.refreshed_at = now;
+      if let Ok(json) = serde_json::to_string(&entry.state) {
+        states.push((*session_id, entry.clock, json));
+      }
+    }
+    if states.is_empty() {
+      None
+    } else {
+      Some(awareness_update(states))
+    }
+  }
+
+  /// Applies an update gossiped by any node, including this one. Returns true if the presence
+  /// of the workspace changed.
+  pub fn apply_remote(&mut self, update: &AwarenessUpdate) -> bool {
+    let now = Instant::now();
+    let mut changed = false;
+    for (session_id, remote) in update.clients.iter() {
+      if let Some((clock, _)) = self.removed.get(session_id) {
+        if remote.clock <= *clock {
+          continue;
+        }
+      }
+      if let Some(entry) = self.entries.get_mut(session_id) {
+        if entry.local || remote.clock < entry.clock {
+          continue;
+        }
+        if remote.clock == entry.clock {
+          entry.refreshed_at = now;
+          continue;
+        }
+      }
+      let json: &str = remote.json.as_ref();
+      match serde_json::from_str::<Option<PresenceState>>(json) {
+        Ok(Some(state)) => {
+          let previous = self.entries.insert(
+            *session_id,
+            PresenceEntry {
+              state: state.clone(),
+              clock: remote.clock,
+              local: false,
+              refreshed_at: now,
+            },
+          );
+          changed |= previous.map(|entry| entry.state != state).unwrap_or(true);
+        },
+        Ok(None) => {
+          self.removed.insert(*session_id, (remote.clock, now));
+          changed |= self.entries.remove(session_id).is_some();
+        },
+        Err(err) => tracing::warn!("invalid presence state of session {}: {}", session_id, err),
+      }
+    }
+    changed
+  }
+
+  /// Drops the entries of other nodes which were not announced again in time. Returns true if
+  /// any entry was dropped.
+  pub fn remove_expired(&mut self, timeout: Duration) -> bool {
+    let now = Instant::now();
+    let len = self.entries.len();
+    self
+      .entries
+      .retain(|_, entry| entry.local || now.duration_since(entry.refreshed_at) < timeout);
+    self
+      .removed
+      .retain(|_, (_, removed_at)| now.duration_since(*removed_at) < timeout);
+    self.entries.len() != len
+  }
+
+  pub fn sessions(&self) -> Vec<SessionPresence> {
+    let mut sessions: Vec<SessionPresence> = self
+      .entries
+      .values()
+      .map(|entry| SessionPresence {
+        uid: entry.state.uid,
+        device_id: entry.state.device_id.clone(),
+        object_id: entry.state.object_id,
+      })
+      .collect();
+    sessions.sort_by(|a, b| (a.uid, &a.device_id).cmp(&(b.uid, &b.device_id)));
+    sessions
+  }
+}
+
+fn awareness_update(states: impl IntoIterator<Item = (ClientID, u32, String)>) -> AwarenessUpdate {
+  AwarenessUpdate {
+    clients: states
+      .into_iter()
+      .map(|(session_id, clock, json)| {
+        (
+          session_id,
+          AwarenessUpdateEntry {
+            clock,
+            json: json.into(),
+          },
+        )
+      })
+      .collect(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn presence_is_gossiped_between_nodes() {
+    let object_id = Uuid::new_v4();
+    let mut node_a = WorkspacePresenceState::default();
+    let mut node_b = WorkspacePresenceState::default();
+
+    let update = node_a.set_local(1, 10, "desktop".to_string(), None);
+    assert!(!node_a.apply_remote(&update), "own updates are ignored");
+    assert!(node_b.apply_remote(&update));
+    assert!(!node_b.apply_remote(&update), "duplicates are ignored");
+
+    let update = node_a.focus_local(1, Some(object_id)).unwrap();
+    assert!(node_a.focus_local(1, Some(object_id)).is_none());
+    assert!(node_b.apply_remote(&update));
+    assert_eq!(node_b.sessions()[0].object_id, Some(object_id));
+
+    let stale = node_a.set_local(1, 10, "desktop".to_string(), None);
+    let update = node_a.remove_local(1).unwrap();
+    assert!(node_b.apply_remote(&update));
+    assert!(node_b.sessions().is_empty());
+    assert!(
+      !node_a.apply_remote(&stale),
+      "removed sessions don't come back"
+    );
+    assert!(node_a.sessions().is_empty());
+
+    let update = node_a.set_local(1, 10, "desktop".to_string(), None);
+    assert!(
+      node_b.apply_remote(&update),
+      "reconnected sessions come back"
+    );
+  }
+
+  #[test]
+  fn presence_of_silent_nodes_expires() {
+    let mut node_a = WorkspacePresenceState::default();
+    let mut node_b = WorkspacePresenceState::default();
+    node_a.set_local(1, 10, "desktop".to_string(), None);
+    node_b.apply_remote(&node_a.refresh_local().unwrap());
+
+    assert!(!node_b.remove_expired(Duration::from_secs(60)));
+    assert!(node_b.remove_expired(Duration::ZERO));
+    assert!(node_b.sessions().is_empty());
+    assert!(
+      !node_a.remove_expired(Duration::ZERO),
+      "local sessions never expire"
+    );
+  }
+}
@@ -5,7 +5,7 @@ use crate::collab::snapshot_scheduler::SnapshotScheduler;
 use crate::ws2::{BulkPermissionUpdate, PermissionUpdate};
 use actix::{Actor, Addr, Arbiter, AsyncContext, Handler, Recipient};
 use app_error::AppError;
-use appflowy_proto::{ObjectId, Rid, ServerMessage, WorkspaceId};
+use appflowy_proto::{ObjectId, Rid, ServerMessage, WorkspaceId, WorkspaceNotification};
 use collab::core::origin::CollabOrigin;
 use collab_entity::CollabType;
 use collab_folder::Folder;
@@ -83,6 +83,36 @@ impl Handler<Leave> for WsServer {
   }
 }
 
+impl Handler<FocusChanged> for WsServer {
+  type Result = ();
+
+  fn handle(&mut self, msg: FocusChanged, _ctx: &mut Self::Context) -> Self::Result {
+    if let Some(workspace) = self.workspaces.get(&msg.workspace_id) {
+      workspace.do_send(msg);
+    }
+  }
+}
+
+impl Handler<SubscribeCollab> for WsServer {
+  type Result = ();
+
+  fn handle(&mut self, msg: SubscribeCollab, _ctx: &mut Self::Context) -> Self::Result {
+    if let Some(workspace) = self.workspaces.get(&msg.workspace_id) {
+      workspace.do_send(msg);
+    }
+  }
+}
+
+impl Handler<UnsubscribeCollab> for WsServer {
+  type Result = ();
+
+  fn handle(&mut self, msg: UnsubscribeCollab, _ctx: &mut Self::Context) -> Self::Result {
+    if let Some(workspace) = self.workspaces.get(&msg.workspace_id) {
+      workspace.do_send(msg);
+    }
+  }
+}
+
 impl Handler<WsInput> for WsServer {
   type Result = ();
 
@@ -128,6 +158,37 @@ impl Handler<PublishUpdate> for WsServer {
   }
 }
 
+impl Handler<UpdateUserPermissions> for WsServer {
+  type Result = ();
+
+  fn handle(&mut self, msg: UpdateUserPermissions, _ctx: &mut Self::Context) -> Self::Result {
+    // If the workspace is not active, there are no sessions that need to be notified.
+    if let Some(workspace) = self.workspaces.get(&msg.workspace_id) {
+      workspace.do_send(msg);
+    }
+  }
+}
+
+impl Handler<BroadcastNotification> for WsServer {
+  type Result = ();
+
+  fn handle(&mut self, msg: BroadcastNotification, _ctx: &mut Self::Context) -> Self::Result {
+    // Inactive workspaces have no sessions to notify.
+    match msg.workspace_id {
+      Some(workspace_id) => {
+        if let Some(workspace) = self.workspaces.get(&workspace_id) {
+          workspace.do_send(msg);
+        }
+      },
+      None => {
+        for workspace in self.workspaces.values() {
+          workspace.do_send(msg.clone());
+        }
+      },
+    }
+  }
+}
+
 impl Handler<WorkspaceFolder> for WsServer {
   type Result = ();
 
@@ -152,20 +213,24 @@ pub struct Join {
   pub uid: i64,
   /// Current client session identifier.
   pub session_id: ClientID,
+  /// Device the session was opened from.
+  pub device_id: String,
   pub collab_origin: CollabOrigin,
   pub last_message_id: Option<Rid>,
   /// Actix WebSocket session actor address.
   pub addr: Addr<WsSession>,
   /// Workspace to join.
   pub workspace_id: WorkspaceId,
+  /// Observer sessions only receive the updates of the collabs they subscribed to.
+  pub observer: bool,
 }
 
 impl Display for Join {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     write!(
       f,
-      "Join(uid: {}, session_id: {}, workspace_id: {})",
-      self.uid, self.session_id, self.workspace_id
+      "Join(uid: {}, session_id: {}, workspace_id: {}, observer: {})",
+      self.uid, self.session_id, self.workspace_id, self.observer
     )
   }
 }
@@ -179,6 +244,35 @@ pub struct Leave {
   pub workspace_id: WorkspaceId,
 }
 
+#[derive(actix::Message)]
+#[rtype(result = "()")]
+pub struct FocusChanged {
+  /// Current client session identifier.
+  pub session_id: ClientID,
+  pub workspace_id: WorkspaceId,
+  /// Object now focused in the session, if any.
+  pub object_id: Option<ObjectId>,
+}
+
+#[derive(actix::Message)]
+#[rtype(result = "()")]
+pub struct SubscribeCollab {
+  /// Observer session identifier.
+  pub session_id: ClientID,
+  pub workspace_id: WorkspaceId,
+  pub object_id: ObjectId,
+  pub collab_type: CollabType,
+}
+
+#[derive(actix::Message)]
+#[rtype(result = "()")]
+pub struct UnsubscribeCollab {
+  /// Observer session identifier.
+  pub session_id: ClientID,
+  pub workspace_id: WorkspaceId,
+  pub object_id: ObjectId,
+}
+
 #[derive(actix::Message)]
 #[rtype(result = "()")]
 pub struct WsOutput {
@@ -188,6 +282,7 @@ pub struct WsOutput {
 #[derive(actix::Message)]
 #[rtype(result = "()")]
 pub struct UpdateUserPermissions {
+  pub workspace_id: WorkspaceId,
   pub uid: i64,
   pub updates: Vec<PermissionUpdate>,
 }
@@ -199,6 +294,17 @@ pub struct BroadcastPermissionChanges {
   pub exclude_uid: Option<i64>, // Don't send to the user who made the change
 }
 
+/// Sends a notification to the sessions connected to this server node.
+#[derive(actix::Message, Clone)]
+#[rtype(result = "()")]
+pub struct BroadcastNotification {
+  /// When not set, the sessions of `uid` receive the notification in every workspace.
+  pub workspace_id: Option<WorkspaceId>,
+  /// When set, only the sessions of this user receive the notification.
+  pub uid: Option<i64>,
+  pub notification: WorkspaceNotification,
+}
+
 #[derive(actix::Message)]
 #[rtype(result = "()")]
 pub struct PublishUpdate {
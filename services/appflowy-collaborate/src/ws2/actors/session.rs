@@ -1,4 +1,7 @@
-use super::server::{Join, Leave, WsOutput, WsServer};
+use super::server::{
+  FocusChanged, Join, Leave, SubscribeCollab, UnsubscribeCollab, WsOutput, WsServer,
+};
+use crate::ws2::{ObserverPermit, OBSERVER_LEASE_TTL};
 use actix::{
   fut, Actor, ActorContext, ActorFutureExt, Addr, AsyncContext, ContextFutureSpawner, Handler,
   Running, StreamHandler, WrapFuture,
@@ -10,6 +13,7 @@ use bytes::{Bytes, BytesMut};
 use collab::core::origin::{CollabClient, CollabOrigin};
 use collab_entity::CollabType;
 use collab_stream::model::MessageId;
+use std::collections::HashSet;
 use std::time::{Duration, Instant};
 use tracing::error;
 use yrs::block::ClientID;
@@ -56,6 +60,13 @@ pub struct WsSession {
   hb: Instant,
   buf: Option<BytesMut>,
   extra_message_rx: Option<ExtraMessageReceiver>,
+  /// Set for observer sessions, which only receive the updates of the collabs they subscribed to.
+  observer: Option<ObserverSession>,
+}
+
+struct ObserverSession {
+  permit: ObserverPermit,
+  subscriptions: HashSet<ObjectId>,
 }
 
 impl WsSession {
@@ -72,6 +83,29 @@ impl WsSession {
       hb: Instant::now(),
       buf: None,
       extra_message_rx: Some(extra_message_rx),
+      observer: None,
+    }
+  }
+
+  /// Creates a read-only session, which never publishes updates nor awareness. The reserved
+  /// observer session is released when the session stops.
+  pub fn new_observer(
+    workspace: WorkspaceId,
+    info: SessionInfo,
+    server: Addr<WsServer>,
+    permit: ObserverPermit,
+  ) -> Self {
+    WsSession {
+      info,
+      server,
+      current_workspace: workspace,
+      hb: Instant::now(),
+      buf: None,
+      extra_message_rx: None,
+      observer: Some(ObserverSession {
+        permit,
+        subscriptions: HashSet::new(),
+      }),
     }
   }
 
@@ -103,6 +137,25 @@ impl WsSession {
     });
   }
 
+  /// Keeps the observer session counted in the quota of its token while it is open.
+  fn refresh_observer_lease(&self, ctx: &mut ws::WebsocketContext<Self>) {
+    ctx.run_interval(OBSERVER_LEASE_TTL / 3, |act, _ctx| {
+      if let Some(observer) = act.observer.as_ref() {
+        let session_id = act.id();
+        let refresh = observer.permit.refresh();
+        actix::spawn(async move {
+          if let Err(err) = refresh.await {
+            tracing::warn!(
+              "failed to refresh the lease of observer session `{}`: {}",
+              session_id,
+              err
+            );
+          }
+        });
+      }
+    });
+  }
+
   fn handle_protocol(&mut self, bytes: Bytes, ctx: &mut ws::WebsocketContext<Self>) {
     match ClientMessage::from_bytes(&bytes) {
       Ok(message) => {
@@ -111,7 +164,20 @@ impl WsSession {
           self.id(),
           message
         );
-        let object_id = *message.object_id();
+        if self.observer.is_some() {
+          return self.handle_observer_message(message, ctx);
+        }
+        if let ClientMessage::FocusChanged { object_id } = message {
+          self.server.do_send(FocusChanged {
+            session_id: self.id(),
+            workspace_id: self.current_workspace,
+            object_id,
+          });
+          return;
+        }
+        let Some(object_id) = message.object_id().copied() else {
+          return;
+        };
         let message = match InputMessage::try_from(message) {
           Ok(msg) => msg,
           Err(err) => return ctx.close(Some(CloseReason::from((CloseCode::Invalid, err)))),
@@ -133,6 +199,55 @@ impl WsSession {
       },
     }
   }
+
+  fn handle_observer_message(
+    &mut self,
+    message: ClientMessage,
+    ctx: &mut ws::WebsocketContext<Self>,
+  ) {
+    let Some(observer) = self.observer.as_mut() else {
+      return;
+    };
+    match message {
+      ClientMessage::Subscribe {
+        object_id,
+        collab_type,
+      } => {
+        let max_subscriptions = observer.permit.max_subscriptions();
+        if !observer.subscriptions.contains(&object_id)
+          && observer.subscriptions.len() >= max_subscriptions
+        {
+          return ctx.close(Some(CloseReason::from((
+            CloseCode::Policy,
+            format!(
+              "observer sessions can't subscribe to more than {} collabs",
+              max_subscriptions
+            ),
+          ))));
+        }
+        observer.subscriptions.insert(object_id);
+        self.server.do_send(SubscribeCollab {
+          session_id: self.info.client_id,
+          workspace_id: self.current_workspace,
+          object_id,
+          collab_type,
+        });
+      },
+      ClientMessage::Unsubscribe { object_id } => {
+        if observer.subscriptions.remove(&object_id) {
+          self.server.do_send(UnsubscribeCollab {
+            session_id: self.info.client_id,
+            workspace_id: self.current_workspace,
+            object_id,
+          });
+        }
+      },
+      _ => ctx.close(Some(CloseReason::from((
+        CloseCode::Policy,
+        "observer sessions are read-only",
+      )))),
+    }
+  }
 }
 
 impl Actor for WsSession {
@@ -141,6 +256,9 @@ impl Actor for WsSession {
   fn started(&mut self, ctx: &mut Self::Context) {
     tracing::trace!("starting session `{}`", self.id());
     self.hb(ctx);
+    if self.observer.is_some() {
+      self.refresh_observer_lease(ctx);
+    }
 
     let recipient = ctx.address().recipient();
     if let Some(mut external_source) = self.extra_message_rx.take() {
@@ -150,17 +268,19 @@ impl Actor for WsSession {
           let _ = recipient.send(output).await;
         }
       });
-    } else {
+    } else if self.observer.is_none() {
       error!("extra_message_sender only take once");
     }
 
     let join = Join {
       uid: self.info.user_id,
       session_id: self.id(),
+      device_id: self.info.device_id.clone(),
       collab_origin: self.info.collab_origin(),
       addr: ctx.address(),
       last_message_id: self.info.last_message_id.map(MessageId::into),
       workspace_id: self.current_workspace,
+      observer: self.observer.is_some(),
     };
     self
       .server
@@ -195,12 +315,34 @@ impl Handler<WsOutput> for WsSession {
       self.id(),
       msg.message
     );
+    // the workspace already dropped the subscription to a collab the observer can't read anymore
+    if let (
+      Some(observer),
+      ServerMessage::AccessChanges {
+        object_id,
+        can_read: false,
+        ..
+      },
+    ) = (self.observer.as_mut(), &msg.message)
+    {
+      observer.subscriptions.remove(object_id);
+    }
     if let Ok(bytes) = msg.message.into_bytes() {
       ctx.binary(bytes);
     };
   }
 }
 
+impl Handler<CloseSession> for WsSession {
+  type Result = ();
+
+  fn handle(&mut self, msg: CloseSession, ctx: &mut Self::Context) {
+    tracing::trace!("closing session `{}`: {}", self.id(), msg.reason);
+    ctx.close(Some(CloseReason::from((CloseCode::Policy, msg.reason))));
+    ctx.stop();
+  }
+}
+
 impl Handler<GetUid> for WsSession {
   type Result = i64;
 
@@ -283,6 +425,13 @@ pub struct WsInput {
 #[rtype(result = "i64")]
 pub struct GetUid;
 
+/// Closes the session, for instance when the token it was opened with is no longer valid.
+#[derive(actix::Message)]
+#[rtype(result = "()")]
+pub struct CloseSession {
+  pub reason: String,
+}
+
 pub enum InputMessage {
   Manifest(CollabType, Rid, StateVector),
   Update(CollabType, UpdateFlags, Vec<u8>),
@@ -317,6 +466,10 @@ impl TryFrom<ClientMessage> for InputMessage {
         let awareness = AwarenessUpdate::decode_v1(&awareness).map_err(|err| err.to_string())?;
         Ok(InputMessage::AwarenessUpdate(awareness))
       },
+      ClientMessage::FocusChanged { .. } => Err("focus changes are not collab input".to_string()),
+      ClientMessage::Subscribe { .. } | ClientMessage::Unsubscribe { .. } => {
+        Err("only observer sessions can subscribe to collabs".to_string())
+      },
     }
   }
 }
@@ -1,9 +1,11 @@
-use super::server::{Join, Leave, WsOutput};
+use super::presence::{WorkspacePresenceState, PRESENCE_OBJECT_ID};
+use super::server::{FocusChanged, Join, Leave, SubscribeCollab, UnsubscribeCollab, WsOutput};
 use super::session::{InputMessage, WsInput, WsSession};
 use crate::collab::collab_manager::CollabManager;
 use crate::collab::snapshot_scheduler::SnapshotScheduler;
 use crate::ws2::{
-  BroadcastPermissionChanges, PublishUpdate, UpdateUserPermissions, WorkspaceFolder,
+  BroadcastNotification, BroadcastPermissionChanges, PublishUpdate, UpdateUserPermissions,
+  WorkspaceFolder,
 };
 use actix::ActorFutureExt;
 use actix::{
@@ -12,7 +14,10 @@ use actix::{
 };
 use anyhow::anyhow;
 use app_error::AppError;
-use appflowy_proto::{AccessChangedReason, ObjectId, Rid, ServerMessage, UpdateFlags, WorkspaceId};
+use appflowy_proto::{
+  AccessChangedReason, MemberChange, ObjectId, Rid, ServerMessage, UpdateFlags, WorkspaceId,
+  WorkspaceNotification, WorkspacePresence,
+};
 use chrono::DateTime;
 use collab::core::origin::CollabOrigin;
 use collab::entity::EncoderVersion;
@@ -20,11 +25,12 @@ use collab_entity::CollabType;
 use collab_stream::model::{AwarenessStreamUpdate, UpdateStreamMessage};
 use futures_util::future::join_all;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use yrs::block::ClientID;
+use yrs::sync::AwarenessUpdate;
 use yrs::updates::encoder::Encode;
 use yrs::{StateVector, Update};
 
@@ -35,17 +41,25 @@ pub struct Workspace {
   manager: Arc<CollabManager>,
   snapshot_scheduler: SnapshotScheduler,
   sessions_by_client_id: HashMap<ClientID, WorkspaceSessionHandle>,
+  /// Collabs subscribed to by the observer sessions, which are also listed in
+  /// `sessions_by_client_id`.
+  observers: HashMap<ClientID, HashSet<ObjectId>>,
+  presence: WorkspacePresenceState,
   updates_handle: Option<SpawnHandle>,
   awareness_handle: Option<SpawnHandle>,
   snapshot_handle: Option<SpawnHandle>,
   termination_handle: Option<SpawnHandle>,
   permission_cache_cleanup_handle: Option<SpawnHandle>,
+  presence_refresh_handle: Option<SpawnHandle>,
 }
 
 impl Workspace {
   pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60);
   pub const PERMISSION_CACHE_CLEANUP_INTERVAL: Duration = Duration::from_secs(180); // 3 minutes
   pub const PUBLISH_COLLAB_LIMIT: usize = 500;
+  pub const PRESENCE_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+  /// Presence of sessions connected to other server nodes is dropped if not refreshed in time.
+  pub const PRESENCE_TIMEOUT: Duration = Duration::from_secs(75);
 
   pub fn new(
     server: Recipient<Terminate>,
@@ -60,11 +74,14 @@ impl Workspace {
       snapshot_scheduler,
       last_message_id: Rid::default(),
       sessions_by_client_id: HashMap::new(),
+      observers: HashMap::new(),
+      presence: WorkspacePresenceState::default(),
       updates_handle: None,
       awareness_handle: None,
       snapshot_handle: None,
       termination_handle: None,
       permission_cache_cleanup_handle: None,
+      presence_refresh_handle: None,
     }
   }
 
@@ -204,6 +221,13 @@ impl Workspace {
         }
       },
       InputMessage::AwarenessUpdate(update) => {
+        if msg.object_id == PRESENCE_OBJECT_ID {
+          tracing::trace!(
+            "skipping awareness update for presence object from {}",
+            sender.uid
+          );
+          return;
+        }
         if let Err(err) = store
           .publish_awareness_update(
             msg.workspace_id,
@@ -219,6 +243,82 @@ impl Workspace {
     }
   }
 
+  /// Sends the current state of a collab an observer session subscribed to. Returns false when
+  /// the collab can't be observed, after letting the session know why.
+  async fn send_observed_state(
+    store: Arc<CollabManager>,
+    session: WorkspaceSessionHandle,
+    msg: SubscribeCollab,
+  ) -> bool {
+    if !matches!(
+      session.can_read_collab(&store, &msg.object_id).await,
+      Ok(true)
+    ) {
+      tracing::trace!(
+        "user {} lack of permission. skip observing collab {}",
+        session.uid,
+        msg.object_id,
+      );
+      session.conn.do_send(WsOutput {
+        message: ServerMessage::AccessChanges {
+          object_id: msg.object_id,
+          collab_type: msg.collab_type,
+          can_read: false,
+          can_write: false,
+          reason: AccessChangedReason::PermissionDenied,
+        },
+      });
+      return false;
+    }
+
+    match store
+      .get_latest_state(
+        msg.workspace_id,
+        msg.object_id,
+        msg.collab_type,
+        session.uid,
+        StateVector::default(),
+      )
+      .await
+    {
+      Ok(state) => {
+        session.conn.do_send(WsOutput {
+          message: ServerMessage::Update {
+            object_id: msg.object_id,
+            collab_type: msg.collab_type,
+            flags: state.flags,
+            last_message_id: state.rid,
+            update: state.update,
+          },
+        });
+        true
+      },
+      // the collab has no state yet, its updates will follow
+      Err(AppError::RecordNotFound(_)) => true,
+      Err(AppError::RecordDeleted(_)) => {
+        session.conn.do_send(WsOutput {
+          message: ServerMessage::AccessChanges {
+            object_id: msg.object_id,
+            collab_type: msg.collab_type,
+            can_read: false,
+            can_write: false,
+            reason: AccessChangedReason::ObjectDeleted,
+          },
+        });
+        false
+      },
+      Err(err) => {
+        tracing::error!(
+          "failed to resolve observed state of {}/{}: {}",
+          msg.workspace_id,
+          msg.object_id,
+          err
+        );
+        true
+      },
+    }
+  }
+
   async fn publish_collabs_created_since(
     store: Arc<CollabManager>,
     session_handle: WorkspaceSessionHandle,
@@ -323,6 +423,104 @@ impl Workspace {
     Ok(())
   }
 
+  /// Shares a presence change of a session connected to this node with the other server nodes,
+  /// and with the sessions of the workspace.
+  fn publish_presence(&self, update: AwarenessUpdate, ctx: &mut actix::Context<Self>) {
+    self.gossip_presence(update, ctx);
+    self.broadcast_presence(ctx);
+  }
+
+  fn gossip_presence(&self, update: AwarenessUpdate, ctx: &mut actix::Context<Self>) {
+    let store = self.manager.clone();
+    let workspace_id = self.workspace_id;
+    ctx.spawn(
+      async move {
+        if let Err(err) = store
+          .publish_awareness_update(
+            workspace_id,
+            PRESENCE_OBJECT_ID,
+            CollabOrigin::Server,
+            update,
+          )
+          .await
+        {
+          tracing::error!(
+            "failed to publish presence of workspace {}: {:?}",
+            workspace_id,
+            err
+          );
+        }
+      }
+      .into_actor(self),
+    );
+  }
+
+  /// Sends the presence of the workspace to all its sessions. Objects focused by other users are
+  /// only revealed to the sessions which can read them.
+  fn broadcast_presence(&self, ctx: &mut actix::Context<Self>) {
+    let all_sessions = self.presence.sessions();
+    let sessions: Vec<WorkspaceSessionHandle> = self
+      .sessions_by_client_id
+      .iter()
+      .filter(|(session_id, _)| !self.observers.contains_key(session_id))
+      .map(|(_, session)| session.clone())
+      .collect();
+    // Readability only depends on the user, so it is checked once per user and object, and the
+    // same presence is sent to every session of the user.
+    let mut sessions_by_uid: HashMap<i64, Vec<WorkspaceSessionHandle>> = HashMap::new();
+    for session in sessions {
+      sessions_by_uid
+        .entry(session.uid)
+        .or_default()
+        .push(session);
+    }
+    let store = self.manager.clone();
+    ctx.spawn(
+      async move {
+        let send_tasks = sessions_by_uid.into_values().map(|sessions| {
+          let store = Arc::clone(&store);
+          let mut presence = WorkspacePresence {
+            sessions: all_sessions.clone(),
+          };
+          async move {
+            let receiver = &sessions[0];
+            let object_ids: HashSet<ObjectId> = presence
+              .sessions
+              .iter()
+              .filter(|other| other.uid != receiver.uid)
+              .filter_map(|other| other.object_id)
+              .collect();
+            let mut readable = HashSet::with_capacity(object_ids.len());
+            for object_id in object_ids {
+              if matches!(receiver.can_read_collab(&store, &object_id).await, Ok(true)) {
+                readable.insert(object_id);
+              }
+            }
+            for other in presence.sessions.iter_mut() {
+              if other.uid != receiver.uid
+                && other
+                  .object_id
+                  .is_some_and(|object_id| !readable.contains(&object_id))
+              {
+                other.object_id = None;
+              }
+            }
+            for session in &sessions {
+              session.conn.do_send(WsOutput {
+                message: ServerMessage::Presence {
+                  presence: presence.clone(),
+                },
+              });
+            }
+          }
+        });
+        join_all(send_tasks).await;
+      }
+      .into_actor(self)
+      .map(|_, _, _| ()),
+    );
+  }
+
   /// Schedules a termination signal for the workspace in 1min.
   /// If there was a previous termination handle, it will be canceled.
   fn schedule_terminate(&mut self, ctx: &mut actix::Context<Self>) {
@@ -360,6 +558,8 @@ impl Actor for Workspace {
       CleanupPermissionCaches,
       Self::PERMISSION_CACHE_CLEANUP_INTERVAL,
     ));
+    self.presence_refresh_handle =
+      Some(ctx.notify_later(RefreshPresence, Self::PRESENCE_REFRESH_INTERVAL));
   }
 
   fn stopping(&mut self, ctx: &mut Self::Context) -> Running {
@@ -387,6 +587,9 @@ impl Actor for Workspace {
     if let Some(handle) = self.permission_cache_cleanup_handle.take() {
       ctx.cancel_future(handle);
     }
+    if let Some(handle) = self.presence_refresh_handle.take() {
+      ctx.cancel_future(handle);
+    }
     Running::Stop
   }
 }
@@ -420,11 +623,15 @@ impl StreamHandler<anyhow::Result<UpdateStreamMessage>> for Workspace {
     let update = msg.update.clone();
     store.mark_as_dirty(object_id, msg.last_message_id.timestamp);
 
+    // observers never publish updates, they only get the ones of the collabs they subscribed to
     let sessions: Vec<WorkspaceSessionHandle> = self
       .sessions_by_client_id
-      .values()
-      .filter(|s| s.collab_origin != msg.sender)
-      .cloned()
+      .iter()
+      .filter(|(session_id, s)| match self.observers.get(session_id) {
+        Some(subscriptions) => subscriptions.contains(&object_id),
+        None => s.collab_origin != msg.sender,
+      })
+      .map(|(_, s)| s.clone())
       .collect();
 
     ctx.spawn(
@@ -434,7 +641,7 @@ impl StreamHandler<anyhow::Result<UpdateStreamMessage>> for Workspace {
           let store = Arc::clone(&store);
           let update = update.clone();
           async move {
-            if session.can_read_collab(&store, &object_id).await.is_ok() {
+            if matches!(session.can_read_collab(&store, &object_id).await, Ok(true)) {
               session.conn.do_send(WsOutput {
                 message: ServerMessage::Update {
                   object_id,
@@ -465,16 +672,23 @@ impl StreamHandler<(ObjectId, Arc<AwarenessStreamUpdate>)> for Workspace {
   fn handle(
     &mut self,
     (object_id, msg): (ObjectId, Arc<AwarenessStreamUpdate>),
-    _: &mut Self::Context,
+    ctx: &mut Self::Context,
   ) {
+    if object_id == PRESENCE_OBJECT_ID {
+      // only server nodes are allowed to change the workspace presence
+      if msg.sender == CollabOrigin::Server && self.presence.apply_remote(&msg.data) {
+        self.broadcast_presence(ctx);
+      }
+      return;
+    }
     tracing::trace!(
       "received awareness update for {}/{}",
       self.workspace_id,
       object_id
     );
     for (session_id, sender) in self.sessions_by_client_id.iter() {
-      if sender.collab_origin == msg.sender {
-        continue; // skip the sender
+      if sender.collab_origin == msg.sender || self.observers.contains_key(session_id) {
+        continue; // skip the sender and the observers
       }
       tracing::trace!("sending awareness update to {}", session_id);
       sender.conn.do_send(WsOutput {
@@ -507,6 +721,15 @@ impl Handler<Join> for Workspace {
         msg.session_id,
         msg.workspace_id
       );
+      if msg.observer {
+        // observers are not part of the workspace presence and don't catch up on missed updates
+        self.observers.insert(msg.session_id, HashSet::new());
+        return;
+      }
+      let update = self
+        .presence
+        .set_local(msg.session_id, msg.uid, msg.device_id, None);
+      self.publish_presence(update, ctx);
       let store = self.manager.clone();
       let workspace_id = self.workspace_id;
       let session = msg.addr;
@@ -544,11 +767,15 @@ impl Handler<Leave> for Workspace {
   fn handle(&mut self, msg: Leave, ctx: &mut Self::Context) -> Self::Result {
     if msg.workspace_id == self.workspace_id {
       self.sessions_by_client_id.remove(&msg.session_id);
+      self.observers.remove(&msg.session_id);
       tracing::trace!(
         "detached session `{}` from workspace {}",
         msg.session_id,
         msg.workspace_id
       );
+      if let Some(update) = self.presence.remove_local(msg.session_id) {
+        self.publish_presence(update, ctx);
+      }
 
       if self.sessions_by_client_id.is_empty() {
         self.schedule_terminate(ctx);
@@ -557,6 +784,61 @@ impl Handler<Leave> for Workspace {
   }
 }
 
+impl Handler<FocusChanged> for Workspace {
+  type Result = ();
+
+  fn handle(&mut self, msg: FocusChanged, ctx: &mut Self::Context) -> Self::Result {
+    if msg.workspace_id == self.workspace_id {
+      if let Some(update) = self.presence.focus_local(msg.session_id, msg.object_id) {
+        self.publish_presence(update, ctx);
+      }
+    }
+  }
+}
+
+impl Handler<SubscribeCollab> for Workspace {
+  type Result = ();
+
+  fn handle(&mut self, msg: SubscribeCollab, ctx: &mut Self::Context) -> Self::Result {
+    if msg.workspace_id != self.workspace_id {
+      return;
+    }
+    let (Some(subscriptions), Some(session)) = (
+      self.observers.get_mut(&msg.session_id),
+      self.sessions_by_client_id.get(&msg.session_id),
+    ) else {
+      return;
+    };
+    // subscribe right away, so that no update is missed while the current state is read
+    subscriptions.insert(msg.object_id);
+    let (session_id, object_id) = (msg.session_id, msg.object_id);
+    ctx.spawn(
+      Self::send_observed_state(self.manager.clone(), session.clone(), msg)
+        .into_actor(self)
+        .map(move |observed, act, _| {
+          if !observed {
+            if let Some(subscriptions) = act.observers.get_mut(&session_id) {
+              subscriptions.remove(&object_id);
+            }
+          }
+        }),
+    );
+  }
+}
+
+impl Handler<UnsubscribeCollab> for Workspace {
+  type Result = ();
+
+  fn handle(&mut self, msg: UnsubscribeCollab, _: &mut Self::Context) -> Self::Result {
+    if msg.workspace_id != self.workspace_id {
+      return;
+    }
+    if let Some(subscriptions) = self.observers.get_mut(&msg.session_id) {
+      subscriptions.remove(&msg.object_id);
+    }
+  }
+}
+
 impl Handler<Terminate> for Workspace {
   type Result = ();
 
@@ -661,6 +943,21 @@ impl Handler<CleanupPermissionCaches> for Workspace {
   }
 }
 
+impl Handler<RefreshPresence> for Workspace {
+  type Result = ();
+
+  fn handle(&mut self, _: RefreshPresence, ctx: &mut Self::Context) -> Self::Result {
+    self.presence_refresh_handle =
+      Some(ctx.notify_later(RefreshPresence, Self::PRESENCE_REFRESH_INTERVAL));
+    if let Some(update) = self.presence.refresh_local() {
+      self.gossip_presence(update, ctx);
+    }
+    if self.presence.remove_expired(Self::PRESENCE_TIMEOUT) {
+      self.broadcast_presence(ctx);
+    }
+  }
+}
+
 #[derive(actix::Message)]
 #[rtype(result = "()")]
 pub struct Terminate {
@@ -675,24 +972,66 @@ struct Snapshot;
 #[rtype(result = "()")]
 struct CleanupPermissionCaches;
 
+#[derive(actix::Message)]
+#[rtype(result = "()")]
+struct RefreshPresence;
+
 impl Handler<UpdateUserPermissions> for Workspace {
   type Result = ();
 
   fn handle(&mut self, msg: UpdateUserPermissions, _: &mut Self::Context) -> Self::Result {
-    // TODO(nathan): send message when documents permission changed
-    // Find sessions for the specific user
-    let user_sessions: Vec<WorkspaceSessionHandle> = self
+    // Find sessions for the specific user, with the collabs observed by the observer sessions
+    let user_sessions: Vec<(WorkspaceSessionHandle, Option<HashSet<ObjectId>>)> = self
       .sessions_by_client_id
-      .values()
-      .filter(|session| session.uid == msg.uid)
-      .cloned()
+      .iter()
+      .filter(|(_, session)| session.uid == msg.uid)
+      .map(|(session_id, session)| (session.clone(), self.observers.get(session_id).cloned()))
       .collect();
 
+    // Observers stop receiving the updates of the collabs they can no longer access
+    let revoked: Vec<ObjectId> = msg
+      .updates
+      .iter()
+      .filter(|update| update.permission_type == PermissionType::NoAccess)
+      .map(|update| update.object_id)
+      .collect();
+    if !revoked.is_empty() {
+      for (session_id, subscriptions) in self.observers.iter_mut() {
+        let is_user_session = self
+          .sessions_by_client_id
+          .get(session_id)
+          .is_some_and(|session| session.uid == msg.uid);
+        if is_user_session {
+          for object_id in revoked.iter() {
+            subscriptions.remove(object_id);
+          }
+        }
+      }
+    }
+
     // Apply permission updates to user's sessions
     if !user_sessions.is_empty() {
       tokio::spawn(async move {
-        for session in user_sessions {
+        for (session, observed) in user_sessions {
           session.apply_permission_updates(msg.updates.clone()).await;
+          // Let the client know about the objects it can no longer access, so that it can stop
+          // syncing them.
+          for update in msg.updates.iter() {
+            let relevant = observed
+              .as_ref()
+              .is_none_or(|observed| observed.contains(&update.object_id));
+            if update.permission_type == PermissionType::NoAccess && relevant {
+              session.conn.do_send(WsOutput {
+                message: ServerMessage::AccessChanges {
+                  object_id: update.object_id,
+                  collab_type: CollabType::Unknown,
+                  can_read: false,
+                  can_write: false,
+                  reason: AccessChangedReason::PermissionDenied,
+                },
+              });
+            }
+          }
         }
       });
     }
@@ -734,6 +1073,79 @@ impl Handler<BroadcastPermissionChanges> for Workspace {
   }
 }
 
+impl Handler<BroadcastNotification> for Workspace {
+  type Result = ();
+
+  fn handle(&mut self, msg: BroadcastNotification, _: &mut Self::Context) -> Self::Result {
+    if msg.workspace_id.is_some_and(|id| id != self.workspace_id) {
+      return;
+    }
+    let sessions: Vec<WorkspaceSessionHandle> = self
+      .sessions_by_client_id
+      .iter()
+      .filter(|(session_id, session)| {
+        !self.observers.contains_key(session_id) && msg.uid.is_none_or(|uid| session.uid == uid)
+      })
+      .map(|(_, session)| session.clone())
+      .collect();
+    if sessions.is_empty() {
+      return;
+    }
+
+    // The cached permissions of a member whose role changed are no longer valid.
+    let role_changed_uid = match &msg.notification {
+      WorkspaceNotification::MemberChanged {
+        uid,
+        change: MemberChange::Removed | MemberChange::RoleChanged,
+        ..
+      } => Some(*uid),
+      _ => None,
+    };
+    let store = self.manager.clone();
+    tokio::spawn(async move {
+      // Readable published views, by user
+      let mut readable_views: HashMap<i64, Vec<ObjectId>> = HashMap::new();
+      for session in sessions {
+        if Some(session.uid) == role_changed_uid {
+          session.clear_permission_cache().await;
+        }
+        let notification = match &msg.notification {
+          // Only the views the user can read are disclosed.
+          WorkspaceNotification::PublishChanged {
+            view_ids,
+            published,
+          } => {
+            let view_ids = match readable_views.get(&session.uid) {
+              Some(view_ids) => view_ids.clone(),
+              None => {
+                let mut readable = Vec::with_capacity(view_ids.len());
+                for view_id in view_ids {
+                  if matches!(session.can_read_collab(&store, view_id).await, Ok(true)) {
+                    readable.push(*view_id);
+                  }
+                }
+                readable_views.insert(session.uid, readable.clone());
+                readable
+              },
+            };
+            if view_ids.is_empty() {
+              continue;
+            }
+            WorkspaceNotification::PublishChanged {
+              view_ids,
+              published: *published,
+            }
+          },
+          notification => notification.clone(),
+        };
+        session.conn.do_send(WsOutput {
+          message: ServerMessage::Notification { notification },
+        });
+      }
+    });
+  }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PermissionUpdate {
   pub object_id: ObjectId,
@@ -883,11 +1295,17 @@ impl WorkspaceSessionHandle {
     Ok(has_permission)
   }
 
-  /// Apply permission updates and send WebSocket notification
+  /// Apply permission updates to the permission cache.
   async fn apply_permission_updates(&self, updates: Vec<PermissionUpdate>) {
     let mut cache = self.permission_cache.write().await;
     let now = Instant::now();
     for update in updates {
+      // Revoked permissions must always take effect, regardless of what was cached before.
+      if update.permission_type == PermissionType::NoAccess {
+        cache.insert(update.object_id, (update.permission_type, now));
+        continue;
+      }
+
       // Always allow updates from NoAccess, but prevent downgrades from higher permissions
       if let Some((existing_permission, _)) = cache.get(&update.object_id) {
         // Allow update if:
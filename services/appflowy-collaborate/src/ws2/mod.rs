@@ -1,5 +1,7 @@
 mod actors;
+mod observer;
 
 pub use crate::collab::collab_manager::*;
 pub use crate::collab::snapshot_scheduler::*;
 pub use actors::*;
+pub use observer::*;
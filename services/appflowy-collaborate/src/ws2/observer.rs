@@ -0,0 +1,160 @@
+use redis::aio::ConnectionManager;
+use redis::RedisError;
+use std::future::Future;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How long an observer session stays counted in the quota of its token without being refreshed,
+/// so that the sessions of a server node that went down are eventually released.
+pub const OBSERVER_LEASE_TTL: Duration = Duration::from_secs(60);
+
+/// Removes the expired sessions of the token, then adds the new session if the token has less
+/// than `ARGV[1]` sessions left. Times are taken from the Redis server, so that every node agrees
+/// on them.
+const ACQUIRE_SCRIPT: &str = r#"
+local time = redis.call("TIME")
+local now = time[1] * 1000 + math.floor(time[2] / 1000)
+redis.call("ZREMRANGEBYSCORE", KEYS[1], "-inf", now)
+if redis.call("ZCARD", KEYS[1]) >= tonumber(ARGV[1]) then
+  return 0
+end
+redis.call("ZADD", KEYS[1], now + tonumber(ARGV[3]), ARGV[2])
+redis.call("PEXPIRE", KEYS[1], ARGV[3])
+return 1
+"#;
+
+const REFRESH_SCRIPT: &str = r#"
+local time = redis.call("TIME")
+local now = time[1] * 1000 + math.floor(time[2] / 1000)
+redis.call("ZADD", KEYS[1], "XX", now + tonumber(ARGV[2]), ARGV[1])
+redis.call("PEXPIRE", KEYS[1], ARGV[2])
+return 1
+"#;
+
+/// Limits the resources used by observer sessions. Sessions are counted per token in Redis, so
+/// that the limit holds across all server nodes, the key of a token being chosen by the caller.
+#[derive(Clone)]
+pub struct ObserverQuota {
+  redis: ConnectionManager,
+  max_sessions: usize,
+  max_subscriptions: usize,
+}
+
+impl ObserverQuota {
+  pub fn new(redis: ConnectionManager, max_sessions: usize, max_subscriptions: usize) -> Self {
+    Self {
+      redis,
+      max_sessions,
+      max_subscriptions,
+    }
+  }
+
+  /// Reserves an observer session for the token. Returns None when the token already has as many
+  /// sessions as allowed. The session is released when the returned permit is dropped, or when its
+  /// lease expires without being refreshed.
+  pub async fn acquire(&self, token_key: &str) -> Result<Option<ObserverPermit>, RedisError> {
+    let key = format!("af:observer_sessions:{}", token_key);
+    let session_id = Uuid::new_v4().to_string();
+    let mut conn = self.redis.clone();
+    let acquired: i32 = redis::Script::new(ACQUIRE_SCRIPT)
+      .key(&key)
+      .arg(self.max_sessions)
+      .arg(&session_id)
+      .arg(OBSERVER_LEASE_TTL.as_millis() as u64)
+      .invoke_async(&mut conn)
+      .await?;
+    if acquired == 0 {
+      return Ok(None);
+    }
+    Ok(Some(ObserverPermit {
+      conn: Some(conn),
+      key,
+      session_id,
+      max_subscriptions: self.max_subscriptions,
+    }))
+  }
+}
+
+/// An observer session reserved in the [ObserverQuota].
+pub struct ObserverPermit {
+  conn: Option<ConnectionManager>,
+  key: String,
+  session_id: String,
+  max_subscriptions: usize,
+}
+
+impl ObserverPermit {
+  /// Maximum number of collabs the session can observe at once.
+  pub fn max_subscriptions(&self) -> usize {
+    self.max_subscriptions
+  }
+
+  /// Extends the lease of the session by [OBSERVER_LEASE_TTL]. Open sessions must refresh it
+  /// before it expires, or they stop being counted in the quota.
+  pub fn refresh(&self) -> impl Future<Output = Result<(), RedisError>> + 'static {
+    let conn = self.conn.clone();
+    let key = self.key.clone();
+    let session_id = self.session_id.clone();
+    async move {
+      let Some(mut conn) = conn else {
+        return Ok(());
+      };
+      let _: i32 = redis::Script::new(REFRESH_SCRIPT)
+        .key(&key)
+        .arg(&session_id)
+        .arg(OBSERVER_LEASE_TTL.as_millis() as u64)
+        .invoke_async(&mut conn)
+        .await?;
+      Ok(())
+    }
+  }
+}
+
+impl Drop for ObserverPermit {
+  fn drop(&mut self) {
+    if let Some(mut conn) = self.conn.take() {
+      let key = std::mem::take(&mut self.key);
+      let session_id = std::mem::take(&mut self.session_id);
+      tokio::spawn(async move {
+        let result: Result<i32, RedisError> = redis::cmd("ZREM")
+          .arg(&key)
+          .arg(&session_id)
+          .query_async(&mut conn)
+          .await;
+        if let Err(err) = result {
+          tracing::error!("failed to release observer session {}: {}", session_id, err);
+        }
+      });
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use redis::Client;
+
+  #[tokio::test]
+  async fn observer_sessions_are_limited_per_token() {
+    let redis_client = Client::open("redis://localhost:6379").unwrap();
+    let conn = redis_client.get_connection_manager().await.unwrap();
+    let quota = ObserverQuota::new(conn, 2, 10);
+    let token_a = Uuid::new_v4().to_string();
+    let token_b = Uuid::new_v4().to_string();
+
+    let first = quota.acquire(&token_a).await.unwrap().unwrap();
+    let second = quota.acquire(&token_a).await.unwrap().unwrap();
+    assert!(quota.acquire(&token_a).await.unwrap().is_none());
+    assert!(quota.acquire(&token_b).await.unwrap().is_some());
+
+    // another node sharing the same redis sees the sessions of the token
+    let other_node = quota.clone();
+    assert!(other_node.acquire(&token_a).await.unwrap().is_none());
+
+    second.refresh().await.unwrap();
+    drop(first);
+    // the session is released in the background
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(other_node.acquire(&token_a).await.unwrap().is_some());
+  }
+}
@@ -1,14 +1,18 @@
-use crate::config::{Config, DatabaseSetting, Environment, S3Setting};
+use crate::config::{BucketStorageBackend, Config, DatabaseSetting, Environment, S3Setting};
 use anyhow::Error;
 use redis::aio::ConnectionManager;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 
+use crate::attachment_worker::worker::{run_attachment_indexer, AttachmentIndexerConfig};
 use crate::import_worker::worker::run_import_worker;
 use aws_sdk_s3::config::{Credentials, Region, SharedCredentialsProvider};
 
+use crate::export_worker::email_notifier::ExportEmailNotifier;
+use crate::export_worker::worker::{run_export_retention, run_export_worker};
 use crate::import_worker::email_notifier::EmailNotifier;
-use crate::s3_client::S3ClientImpl;
+use crate::s3_client::{FsS3ClientImpl, S3Client, S3ClientImpl};
+use database::file::fs_client_impl::FsBucketClientImpl;
 
 use axum::Router;
 
@@ -20,7 +24,7 @@ use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::routing::get;
 use indexer::metrics::EmbeddingMetrics;
-use indexer::vector::embedder::get_open_ai_config;
+use indexer::vector::embedder::get_embedder_config;
 use infra::env_util::get_env_var;
 use mailer::sender::Mailer;
 use secrecy::ExposeSecret;
@@ -89,7 +93,7 @@ pub async fn create_app(listener: TcpListener, config: Config) -> Result<(), Err
     .expect("failed to get redis connection manager");
 
   let mailer = get_worker_mailer(&config).await?;
-  let s3_client = get_aws_s3_client(&config.s3_setting).await?;
+  let s3_client = get_s3_client(&config).await?;
   let metrics = AppMetrics::new();
 
   let state = AppState {
@@ -101,7 +105,7 @@ pub async fn create_app(listener: TcpListener, config: Config) -> Result<(), Err
   };
 
   let local_set = LocalSet::new();
-  let email_notifier = EmailNotifier::new(mailer);
+  let email_notifier = EmailNotifier::new(mailer.clone());
   let tick_interval = get_env_var("APPFLOWY_WORKER_IMPORT_TICK_INTERVAL", "10")
     .parse::<u64>()
     .unwrap_or(10);
@@ -116,20 +120,33 @@ pub async fn create_app(listener: TcpListener, config: Config) -> Result<(), Err
     state.pg_pool.clone(),
     state.redis_client.clone(),
     Some(state.metrics.import_metrics.clone()),
-    Arc::new(state.s3_client.clone()),
+    state.s3_client.clone(),
     Arc::new(email_notifier),
     "import_task_stream",
     tick_interval,
     maximum_import_file_size,
   ));
 
-  let (open_ai_config, azure_ai_config) = get_open_ai_config();
-  let indexer_config = BackgroundIndexerConfig {
-    enable: appflowy_collaborate::config::get_env_var("APPFLOWY_INDEXER_ENABLED", "true")
+  tokio::spawn(run_export_retention(
+    state.pg_pool.clone(),
+    state.s3_client.clone(),
+  ));
+  let export_worker_fut = run_export_worker(
+    state.pg_pool.clone(),
+    state.redis_client.clone(),
+    state.s3_client.clone(),
+    Arc::new(ExportEmailNotifier::new(mailer)),
+    "export_task_stream",
+    tick_interval,
+  );
+
+  let indexer_config_enabled =
+    appflowy_collaborate::config::get_env_var("APPFLOWY_INDEXER_ENABLED", "true")
       .parse::<bool>()
-      .unwrap_or(true),
-    open_ai_config,
-    azure_ai_config,
+      .unwrap_or(true);
+  let indexer_config = BackgroundIndexerConfig {
+    enable: indexer_config_enabled,
+    embedder_config: get_embedder_config(),
     tick_interval_secs: 10,
   };
 
@@ -140,6 +157,22 @@ pub async fn create_app(listener: TcpListener, config: Config) -> Result<(), Err
     indexer_config,
   ));
 
+  let attachment_indexer_config = AttachmentIndexerConfig {
+    enable: indexer_config_enabled,
+    embedder_config: get_embedder_config(),
+    tick_interval_secs: tick_interval,
+    max_file_size: get_env_var("APPFLOWY_WORKER_MAX_ATTACHMENT_INDEX_SIZE", "50000000")
+      .parse::<i64>()
+      .unwrap_or(50_000_000),
+  };
+  let attachment_indexer_fut = run_attachment_indexer(
+    state.pg_pool.clone(),
+    state.redis_client.clone(),
+    state.s3_client.clone(),
+    "attachment_index_task_stream",
+    attachment_indexer_config,
+  );
+
   let app = Router::new()
     .route("/metrics", get(metrics_handler))
     .with_state(Arc::new(state));
@@ -148,6 +181,12 @@ pub async fn create_app(listener: TcpListener, config: Config) -> Result<(), Err
     _ = import_worker_fut => {
       info!("Notion importer stopped");
     },
+    _ = export_worker_fut => {
+      info!("Workspace exporter stopped");
+    },
+    _ = attachment_indexer_fut => {
+      info!("Attachment indexer stopped");
+    },
     _ = axum::serve(listener, app) => {
       info!("worker stopped");
     },
@@ -160,7 +199,7 @@ pub async fn create_app(listener: TcpListener, config: Config) -> Result<(), Err
 pub struct AppState {
   pub redis_client: ConnectionManager,
   pub pg_pool: PgPool,
-  pub s3_client: S3ClientImpl,
+  pub s3_client: Arc<dyn S3Client>,
   #[allow(dead_code)]
   pub mailer: AFWorkerMailer,
   pub metrics: AppMetrics,
@@ -195,6 +234,23 @@ async fn get_connection_pool(setting: &DatabaseSetting) -> Result<PgPool, Error>
     .map_err(|e| anyhow::anyhow!("Failed to connect to postgres database: {}", e))
 }
 
+async fn get_s3_client(config: &Config) -> Result<Arc<dyn S3Client>, Error> {
+  match config.bucket_storage.backend {
+    BucketStorageBackend::S3 => Ok(Arc::new(get_aws_s3_client(&config.s3_setting).await?)),
+    BucketStorageBackend::Filesystem => {
+      let fs_setting = &config.bucket_storage.fs;
+      info!("Using filesystem storage at {}", fs_setting.root);
+      let inner = FsBucketClientImpl::new(
+        &fs_setting.root,
+        fs_setting.presigned_url_endpoint.clone(),
+        fs_setting.presign_secret.expose_secret().clone(),
+      )
+      .await?;
+      Ok(Arc::new(FsS3ClientImpl { inner }))
+    },
+  }
+}
+
 pub async fn get_aws_s3_client(s3_setting: &S3Setting) -> Result<S3ClientImpl, Error> {
   let credentials = Credentials::new(
     s3_setting.access_key.clone(),
@@ -220,6 +276,8 @@ pub async fn get_aws_s3_client(s3_setting: &S3Setting) -> Result<S3ClientImpl, E
   Ok(S3ClientImpl {
     inner: client,
     bucket: s3_setting.bucket.clone(),
+    endpoint: s3_setting.minio_url.clone(),
+    presigned_url_endpoint: s3_setting.presigned_url_endpoint.clone(),
   })
 }
 
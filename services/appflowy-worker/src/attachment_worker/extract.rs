@@ -0,0 +1,228 @@
+use crate::error::AttachmentIndexError;
+use anyhow::anyhow;
+use async_zip::base::read::mem::ZipFileReader;
+use futures::AsyncReadExt;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::path::Path;
+
+/// Maximum size of the text part of a file, once decompressed. A small archive can expand to a
+/// huge part, so the limit of the uploaded file size is not enough.
+const MAX_EXTRACTED_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Kinds of files whose text can be extracted and indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentKind {
+  Pdf,
+  Docx,
+  Markdown,
+  PlainText,
+}
+
+impl AttachmentKind {
+  /// Resolves the kind of the file from its mime type. Files uploaded as generic binary data are
+  /// resolved from the extension of their file id instead.
+  pub fn from_content_type(content_type: &str, file_id: &str) -> Option<Self> {
+    let mime_type = content_type
+      .split(';')
+      .next()
+      .unwrap_or_default()
+      .trim()
+      .to_ascii_lowercase();
+    match mime_type.as_str() {
+      "application/pdf" => Some(Self::Pdf),
+      "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => Some(Self::Docx),
+      "text/markdown" | "text/x-markdown" => Some(Self::Markdown),
+      "text/plain" => Some(Self::PlainText),
+      "" | "application/octet-stream" => Self::from_extension(file_id),
+      _ => None,
+    }
+  }
+
+  fn from_extension(file_id: &str) -> Option<Self> {
+    let extension = Path::new(file_id)
+      .extension()?
+      .to_str()?
+      .to_ascii_lowercase();
+    match extension.as_str() {
+      "pdf" => Some(Self::Pdf),
+      "docx" => Some(Self::Docx),
+      "md" | "markdown" => Some(Self::Markdown),
+      "txt" => Some(Self::PlainText),
+      _ => None,
+    }
+  }
+}
+
+/// Extracts the text of the file, split into paragraphs.
+pub async fn extract_attachment_text(
+  kind: AttachmentKind,
+  data: Vec<u8>,
+) -> Result<Vec<String>, AttachmentIndexError> {
+  match kind {
+    AttachmentKind::Pdf => {
+      // Parsing a PDF is CPU bound, and the parser panics on some malformed files.
+      let text = tokio::task::spawn_blocking(move || pdf_extract::extract_text_from_mem(&data))
+        .await
+        .map_err(|err| anyhow!("failed to extract text from PDF: {}", err))?
+        .map_err(|err| anyhow!("failed to extract text from PDF: {}", err))?;
+      Ok(split_paragraphs(&text, true))
+    },
+    AttachmentKind::Docx => docx_paragraphs(data).await,
+    AttachmentKind::Markdown | AttachmentKind::PlainText => {
+      Ok(split_paragraphs(&String::from_utf8_lossy(&data), false))
+    },
+  }
+}
+
+/// Splits the text into paragraphs separated by blank lines. When `join_lines` is set, the lines
+/// of a paragraph are joined with spaces, since PDF text breaks lines in the middle of sentences.
+fn split_paragraphs(text: &str, join_lines: bool) -> Vec<String> {
+  let mut paragraphs = Vec::new();
+  let mut lines: Vec<&str> = Vec::new();
+  for line in text.lines().map(str::trim) {
+    if line.is_empty() {
+      push_paragraph(&mut paragraphs, &mut lines, join_lines);
+    } else {
+      lines.push(line);
+    }
+  }
+  push_paragraph(&mut paragraphs, &mut lines, join_lines);
+  paragraphs
+}
+
+fn push_paragraph(paragraphs: &mut Vec<String>, lines: &mut Vec<&str>, join_lines: bool) {
+  if lines.is_empty() {
+    return;
+  }
+  let separator = if join_lines { " " } else { "\n" };
+  paragraphs.push(lines.join(separator));
+  lines.clear();
+}
+
+/// Reads the paragraphs of the main document part of a DOCX file.
+async fn docx_paragraphs(data: Vec<u8>) -> Result<Vec<String>, AttachmentIndexError> {
+  let reader = ZipFileReader::new(data).await?;
+  let (index, size) = reader
+    .file()
+    .entries()
+    .iter()
+    .enumerate()
+    .find(|(_, entry)| entry.filename().as_str().ok() == Some("word/document.xml"))
+    .map(|(index, entry)| (index, entry.uncompressed_size()))
+    .ok_or_else(|| anyhow!("word/document.xml not found in DOCX file"))?;
+  if size > MAX_EXTRACTED_BYTES {
+    return Err(anyhow!("word/document.xml of {} bytes is too large", size).into());
+  }
+  // The size in the header can't be trusted, the decompressed data is limited as well.
+  let mut xml = Vec::new();
+  reader
+    .reader_with_entry(index)
+    .await?
+    .take(MAX_EXTRACTED_BYTES + 1)
+    .read_to_end(&mut xml)
+    .await
+    .map_err(anyhow::Error::from)?;
+  if xml.len() as u64 > MAX_EXTRACTED_BYTES {
+    return Err(anyhow!("word/document.xml is too large").into());
+  }
+  let xml = String::from_utf8(xml).map_err(anyhow::Error::from)?;
+
+  let mut reader = Reader::from_str(&xml);
+  let mut paragraphs = Vec::new();
+  let mut paragraph = String::new();
+  let mut in_text = false;
+  loop {
+    match reader.read_event().map_err(anyhow::Error::from)? {
+      Event::Start(tag) if tag.name().as_ref() == b"w:t" => in_text = true,
+      Event::End(tag) if tag.name().as_ref() == b"w:t" => in_text = false,
+      Event::Text(text) if in_text => {
+        paragraph.push_str(&text.unescape().map_err(anyhow::Error::from)?);
+      },
+      Event::Empty(tag) if tag.name().as_ref() == b"w:tab" => paragraph.push('\t'),
+      Event::Empty(tag) if matches!(tag.name().as_ref(), b"w:br" | b"w:cr") => paragraph.push('\n'),
+      Event::End(tag) if tag.name().as_ref() == b"w:p" => {
+        let text = paragraph.trim();
+        if !text.is_empty() {
+          paragraphs.push(text.to_string());
+        }
+        paragraph.clear();
+      },
+      Event::Eof => break,
+      _ => {},
+    }
+  }
+  Ok(paragraphs)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use async_zip::base::write::ZipFileWriter;
+  use async_zip::{Compression, ZipEntryBuilder};
+
+  #[test]
+  fn attachment_kind_from_content_type() {
+    assert_eq!(
+      AttachmentKind::from_content_type("application/pdf", "file"),
+      Some(AttachmentKind::Pdf)
+    );
+    assert_eq!(
+      AttachmentKind::from_content_type("text/plain; charset=utf-8", "file"),
+      Some(AttachmentKind::PlainText)
+    );
+    assert_eq!(
+      AttachmentKind::from_content_type("application/octet-stream", "notes.MD"),
+      Some(AttachmentKind::Markdown)
+    );
+    assert_eq!(
+      AttachmentKind::from_content_type("image/png", "a.txt"),
+      None
+    );
+  }
+
+  #[tokio::test]
+  async fn extract_markdown_paragraphs() {
+    let markdown = "# Title\n\nFirst line\nsecond line\n\n\n- item\n";
+    let paragraphs = extract_attachment_text(AttachmentKind::Markdown, markdown.into())
+      .await
+      .unwrap();
+    assert_eq!(
+      paragraphs,
+      vec!["# Title", "First line\nsecond line", "- item"]
+    );
+    assert_eq!(
+      split_paragraphs("broken\nsentence\n\nnext", true),
+      vec!["broken sentence", "next"]
+    );
+  }
+
+  #[tokio::test]
+  async fn extract_docx_paragraphs() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:body>
+    <w:p><w:r><w:t>Hello</w:t></w:r><w:r><w:t xml:space="preserve"> world &amp; friends</w:t></w:r></w:p>
+    <w:p></w:p>
+    <w:p><w:r><w:t>Second</w:t><w:tab/><w:t>paragraph</w:t></w:r></w:p>
+  </w:body>
+</w:document>"#;
+    let mut writer = ZipFileWriter::new(Vec::new());
+    writer
+      .write_entry_whole(
+        ZipEntryBuilder::new("word/document.xml".into(), Compression::Stored),
+        xml.as_bytes(),
+      )
+      .await
+      .unwrap();
+    let data = writer.close().await.unwrap();
+
+    let paragraphs = extract_attachment_text(AttachmentKind::Docx, data)
+      .await
+      .unwrap();
+    assert_eq!(
+      paragraphs,
+      vec!["Hello world & friends", "Second\tparagraph"]
+    );
+  }
+}
@@ -0,0 +1,253 @@
+use crate::attachment_worker::extract::{extract_attachment_text, AttachmentKind};
+use crate::error::{AttachmentIndexError, WorkerError};
+use crate::import_worker::worker::ensure_consumer_group;
+use crate::s3_client::S3Client;
+use anyhow::anyhow;
+use database::index::{
+  upsert_attachment_embeddings, upsert_attachment_index, AttachmentIndexParams,
+};
+use database::workspace::select_workspace_settings;
+use futures::AsyncReadExt;
+use indexer::collab_indexer::{DocumentIndexer, Indexer};
+use indexer::vector::embedder::{AFEmbedder, EmbedderConfig};
+use redis::aio::ConnectionManager;
+use redis::streams::{StreamId, StreamReadOptions, StreamReadReply};
+use redis::{AsyncCommands, Value};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::PgPool;
+use std::fmt::Display;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::{error, info, trace};
+use uuid::Uuid;
+
+const GROUP_NAME: &str = "attachment_index_group";
+const CONSUMER_NAME: &str = "appflowy_worker";
+
+pub struct AttachmentIndexerConfig {
+  pub enable: bool,
+  /// Attachments are only indexed for keyword search when no embedder is configured.
+  pub embedder_config: Option<EmbedderConfig>,
+  pub tick_interval_secs: u64,
+  /// Files larger than this are not indexed.
+  pub max_file_size: i64,
+}
+
+pub async fn run_attachment_indexer(
+  pg_pool: PgPool,
+  mut redis_client: ConnectionManager,
+  s3_client: Arc<dyn S3Client>,
+  stream_name: &str,
+  config: AttachmentIndexerConfig,
+) {
+  if !config.enable {
+    info!("Attachment indexer is disabled. Stop attachment indexer");
+    return;
+  }
+
+  info!("Starting attachment indexer...");
+  if let Err(err) = ensure_consumer_group(stream_name, GROUP_NAME, &mut redis_client).await {
+    error!("Failed to ensure consumer group: {:?}", err);
+  }
+
+  let context = TaskContext {
+    pg_pool,
+    s3_client,
+    embedder: config
+      .embedder_config
+      .as_ref()
+      .map(|config| config.create_embedder()),
+    max_file_size: config.max_file_size,
+  };
+  let options = StreamReadOptions::default()
+    .group(GROUP_NAME, CONSUMER_NAME)
+    .count(10);
+  let mut interval = interval(Duration::from_secs(config.tick_interval_secs));
+  interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+  interval.tick().await;
+
+  loop {
+    interval.tick().await;
+
+    let tasks: StreamReadReply = match redis_client
+      .xread_options(&[stream_name], &[">"], &options)
+      .await
+    {
+      Ok(tasks) => tasks,
+      Err(err) => {
+        error!(
+          "Failed to read attachment tasks from Redis stream: {:?}",
+          err
+        );
+        if err.code() == Some("NOGROUP") {
+          if let Err(err) = ensure_consumer_group(stream_name, GROUP_NAME, &mut redis_client).await
+          {
+            error!("Failed to ensure consumer group: {:?}", err);
+          }
+        }
+        continue;
+      },
+    };
+
+    for stream_key in tasks.keys {
+      for stream_id in stream_key.ids {
+        match AttachmentIndexTask::try_from(&stream_id) {
+          Ok(task) => match index_attachment(&context, &task).await {
+            Ok(()) => info!("[Attachment]: indexed {}", task),
+            Err(AttachmentIndexError::UnsupportedType(content_type)) => {
+              trace!("[Attachment]: skip {} of type {}", task, content_type)
+            },
+            Err(err) => error!("[Attachment]: failed to index {}: {}", task, err),
+          },
+          Err(err) => error!("Failed to deserialize attachment task: {:?}", err),
+        }
+
+        // Indexing is not retried: the attachment is indexed again when it is uploaded again.
+        let result: Result<(), redis::RedisError> =
+          redis_client.xdel(stream_name, &[&stream_id.id]).await;
+        if let Err(err) = result {
+          error!(
+            "[Attachment] failed to delete task:{} error:{:?}",
+            stream_id.id, err
+          );
+        }
+      }
+    }
+  }
+}
+
+struct TaskContext {
+  pg_pool: PgPool,
+  s3_client: Arc<dyn S3Client>,
+  embedder: Option<AFEmbedder>,
+  max_file_size: i64,
+}
+
+async fn index_attachment(
+  context: &TaskContext,
+  task: &AttachmentIndexTask,
+) -> Result<(), AttachmentIndexError> {
+  let settings = select_workspace_settings(&context.pg_pool, &task.workspace_id).await?;
+  if settings.is_some_and(|settings| settings.disable_search_indexing) {
+    trace!(
+      "[Attachment]: search indexing is disabled for {}",
+      task.workspace_id
+    );
+    return Ok(());
+  }
+
+  let object_key = task.object_key();
+  let meta = context.s3_client.get_blob_meta(&object_key).await?;
+  let content_type = meta.content_type.unwrap_or_default();
+  let kind = AttachmentKind::from_content_type(&content_type, &task.file_id)
+    .ok_or_else(|| AttachmentIndexError::UnsupportedType(content_type.clone()))?;
+  if meta.content_length > context.max_file_size {
+    return Err(AttachmentIndexError::TooLarge(meta.content_length));
+  }
+
+  let mut data = Vec::with_capacity(meta.content_length.max(0) as usize);
+  context
+    .s3_client
+    .get_blob_stream(&object_key)
+    .await?
+    .stream
+    .read_to_end(&mut data)
+    .await?;
+  let paragraphs = extract_attachment_text(kind, data).await?;
+  if paragraphs.is_empty() {
+    trace!("[Attachment]: no text found in {}", task);
+    return Ok(());
+  }
+
+  // Files are uploaded to the directory named after the page they are attached to.
+  let view_id = Uuid::parse_str(&task.parent_dir).ok();
+  let attachment_id = upsert_attachment_index(
+    &context.pg_pool,
+    &AttachmentIndexParams {
+      workspace_id: task.workspace_id,
+      parent_dir: task.parent_dir.clone(),
+      file_id: task.file_id.clone(),
+      view_id,
+      content_type,
+      uid: task.uid,
+      content: paragraphs.join("\n"),
+    },
+  )
+  .await?;
+
+  if let Some(embedder) = &context.embedder {
+    let model = embedder.model();
+    let mut chunks =
+      DocumentIndexer.create_embedded_chunks_from_text(attachment_id, paragraphs, &model)?;
+    for chunk in chunks.iter_mut() {
+      chunk.metadata = json!({
+          "id": view_id,
+          "attachment_id": attachment_id,
+          "file_id": task.file_id,
+          "source": "appflowy",
+          "name": "attachment",
+      });
+    }
+    if let Some(embeddings) = DocumentIndexer.embed(embedder, chunks).await? {
+      let mut txn = context.pg_pool.begin().await?;
+      upsert_attachment_embeddings(
+        &mut txn,
+        &task.workspace_id,
+        &attachment_id,
+        embeddings.tokens_consumed,
+        model.name(),
+        embeddings.chunks,
+      )
+      .await?;
+      txn.commit().await?;
+    }
+  }
+  Ok(())
+}
+
+/// Task queued by the server when a file is uploaded to the workspace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentIndexTask {
+  pub uid: i64,
+  pub workspace_id: Uuid,
+  pub parent_dir: String,
+  pub file_id: String,
+  pub created_at: i64,
+}
+
+impl AttachmentIndexTask {
+  pub fn object_key(&self) -> String {
+    format!("{}/{}/{}", self.workspace_id, self.parent_dir, self.file_id)
+  }
+}
+
+impl Display for AttachmentIndexTask {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "AttachmentIndexTask {{ workspace_id: {}, parent_dir: {}, file_id: {} }}",
+      self.workspace_id, self.parent_dir, self.file_id
+    )
+  }
+}
+
+impl TryFrom<&StreamId> for AttachmentIndexTask {
+  type Error = WorkerError;
+
+  fn try_from(stream_id: &StreamId) -> Result<Self, Self::Error> {
+    let task_str = match stream_id.map.get("task") {
+      Some(Value::SimpleString(value)) => value.to_string(),
+      Some(Value::BulkString(data)) => String::from_utf8_lossy(data).to_string(),
+      value => {
+        return Err(WorkerError::Internal(anyhow!(
+          "Unexpected value for task field: {:?}",
+          value
+        )))
+      },
+    };
+    serde_json::from_str::<AttachmentIndexTask>(&task_str)
+      .map_err(|err| WorkerError::Internal(err.into()))
+  }
+}
@@ -1,5 +1,5 @@
 use anyhow::{Context, Error};
-use infra::env_util::get_env_var;
+use infra::env_util::{get_env_var, get_env_var_opt};
 use mailer::config::MailerSetting;
 use secrecy::Secret;
 use serde::Deserialize;
@@ -12,6 +12,7 @@ pub struct Config {
   pub redis_url: String,
   pub db_settings: DatabaseSetting,
   pub s3_setting: S3Setting,
+  pub bucket_storage: BucketStorageSetting,
   pub mailer: MailerSetting,
 }
 
@@ -44,6 +45,18 @@ impl Config {
         secret_key: get_env_var("APPFLOWY_S3_SECRET_KEY", "minioadmin").into(),
         bucket: get_env_var("APPFLOWY_S3_BUCKET", "appflowy"),
         region: get_env_var("APPFLOWY_S3_REGION", ""),
+        presigned_url_endpoint: get_env_var_opt("APPFLOWY_S3_PRESIGNED_URL_ENDPOINT"),
+      },
+      bucket_storage: BucketStorageSetting {
+        backend: get_env_var("APPFLOWY_STORAGE_BACKEND", "s3").parse()?,
+        fs: FsStorageSetting {
+          root: get_env_var("APPFLOWY_STORAGE_FS_ROOT", "data/storage"),
+          presigned_url_endpoint: get_env_var(
+            "APPFLOWY_STORAGE_FS_PRESIGNED_URL_ENDPOINT",
+            "http://localhost:8000/api/bucket",
+          ),
+          presign_secret: get_env_var("APPFLOWY_STORAGE_FS_PRESIGN_SECRET", "").into(),
+        },
       },
       mailer: MailerSetting {
         smtp_host: get_env_var("APPFLOWY_MAILER_SMTP_HOST", "smtp.gmail.com"),
@@ -133,4 +146,43 @@ pub struct S3Setting {
   pub secret_key: Secret<String>,
   pub bucket: String,
   pub region: String,
+  /// Public endpoint that replaces `minio_url` in presigned urls, which are handed out to clients.
+  pub presigned_url_endpoint: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub enum BucketStorageBackend {
+  S3,
+  Filesystem,
+}
+
+impl FromStr for BucketStorageBackend {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "s3" => Ok(Self::S3),
+      "filesystem" => Ok(Self::Filesystem),
+      other => anyhow::bail!(
+        "{} is not a supported storage backend. Use either `s3` or `filesystem`.",
+        other
+      ),
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct BucketStorageSetting {
+  pub backend: BucketStorageBackend,
+  pub fs: FsStorageSetting,
+}
+
+/// Must match the filesystem storage setting of the AppFlowy Cloud server, which serves the
+/// presigned urls generated by the worker.
+#[derive(Clone, Debug)]
+pub struct FsStorageSetting {
+  pub root: String,
+  pub presigned_url_endpoint: String,
+  /// Signs the presigned urls. Has no default: the backend refuses to start when it is not set.
+  pub presign_secret: Secret<String>,
 }
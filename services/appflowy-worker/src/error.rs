@@ -56,6 +56,81 @@ pub enum ImportError {
   InvalidUuid(#[from] uuid::Error),
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum ExportError {
+  #[error("Can not open the workspace:{0}")]
+  CannotOpenWorkspace(String),
+
+  #[error("Can not open the collab {0}: {1}")]
+  CannotOpenCollab(String, String),
+
+  #[error(transparent)]
+  Zip(#[from] async_zip::error::ZipError),
+
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+
+  #[error(transparent)]
+  Internal(#[from] anyhow::Error),
+
+  #[error(transparent)]
+  InvalidUuid(#[from] uuid::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AttachmentIndexError {
+  #[error("Unsupported attachment type: {0}")]
+  UnsupportedType(String),
+
+  #[error("Attachment is too large to be indexed: {0} bytes")]
+  TooLarge(i64),
+
+  #[error(transparent)]
+  Zip(#[from] async_zip::error::ZipError),
+
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+
+  #[error(transparent)]
+  Internal(#[from] anyhow::Error),
+}
+
+impl From<WorkerError> for AttachmentIndexError {
+  fn from(err: WorkerError) -> AttachmentIndexError {
+    AttachmentIndexError::Internal(err.into())
+  }
+}
+
+impl From<app_error::AppError> for AttachmentIndexError {
+  fn from(err: app_error::AppError) -> AttachmentIndexError {
+    AttachmentIndexError::Internal(err.into())
+  }
+}
+
+impl From<sqlx::Error> for AttachmentIndexError {
+  fn from(err: sqlx::Error) -> AttachmentIndexError {
+    AttachmentIndexError::Internal(err.into())
+  }
+}
+
+impl From<WorkerError> for ExportError {
+  fn from(err: WorkerError) -> ExportError {
+    ExportError::Internal(err.into())
+  }
+}
+
+impl From<ImportError> for ExportError {
+  fn from(err: ImportError) -> ExportError {
+    ExportError::Internal(err.into())
+  }
+}
+
+impl From<app_error::AppError> for ExportError {
+  fn from(err: app_error::AppError) -> ExportError {
+    ExportError::Internal(err.into())
+  }
+}
+
 impl From<WorkerError> for ImportError {
   fn from(err: WorkerError) -> ImportError {
     match err {
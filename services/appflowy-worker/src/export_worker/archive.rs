@@ -0,0 +1,660 @@
+use crate::error::ExportError;
+use crate::export_worker::markdown::document_to_markdown;
+use crate::import_worker::worker::get_encode_collab_from_bytes;
+use crate::s3_client::S3Client;
+use anyhow::anyhow;
+use async_zip::tokio::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use collab::core::collab::{default_client_id, CollabOptions};
+use collab::core::origin::CollabOrigin;
+use collab::entity::EncodedCollab;
+use collab::preclude::Collab;
+use collab_database::database::DatabaseBody;
+use collab_database::database_trait::NoPersistenceDatabaseCollabService;
+use collab_database::entity::FieldType;
+use collab_database::fields::{type_option_cell_reader, Field, TypeOptionData};
+use collab_database::rows::{Cell, RowDetail};
+use collab_database::template::timestamp_parse::TimestampCellData;
+use collab_database::workspace_database::WorkspaceDatabase;
+use collab_document::document::Document;
+use collab_entity::CollabType;
+use collab_folder::{Folder, ViewLayout};
+use database::workspace::select_workspace_database_storage_id;
+use futures::{stream, StreamExt};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::fs::File;
+use tracing::{trace, warn};
+use uuid::Uuid;
+
+const MANIFEST_VERSION: u32 = 1;
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const FILES_DIR: &str = "files";
+const MAX_FILE_NAME_LEN: usize = 64;
+
+#[derive(Debug, Serialize)]
+pub struct ExportManifest {
+  pub version: u32,
+  pub workspace_id: String,
+  pub workspace_name: String,
+  pub exported_at: DateTime<Utc>,
+  pub views: Vec<ManifestView>,
+  /// Paths of the blobs bundled in the archive.
+  pub files: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ManifestView {
+  pub view_id: String,
+  pub name: String,
+  pub layout: String,
+  /// Path of the exported content. None if the view has no exportable content.
+  pub path: Option<String>,
+  /// Path of the JSON schema of a database view.
+  pub schema_path: Option<String>,
+  pub database_id: Option<String>,
+  pub children: Vec<ManifestView>,
+}
+
+/// A view of the folder hierarchy, with the path its content is exported to.
+struct ViewNode {
+  view_id: String,
+  name: String,
+  layout: ViewLayout,
+  /// Archive path of the view without extension. The children of the view are stored in a
+  /// directory with the same path.
+  stem: String,
+  children: Vec<ViewNode>,
+}
+
+#[derive(Default)]
+struct ExportedContent {
+  path: Option<String>,
+  schema_path: Option<String>,
+  database_id: Option<String>,
+}
+
+/// Exports the workspace visible to the user into a zip archive written at `zip_path`.
+/// Documents are exported as Markdown, databases as CSV with a JSON schema next to them, and the
+/// blobs referenced by the documents are bundled under `files/`. The `manifest.json` at the root
+/// of the archive preserves the view tree.
+pub async fn export_workspace_to_zip(
+  uid: i64,
+  workspace_id: &Uuid,
+  workspace_name: &str,
+  pg_pool: &PgPool,
+  s3_client: &Arc<dyn S3Client>,
+  zip_path: &Path,
+) -> Result<ExportManifest, ExportError> {
+  let folder_collab = get_encode_collab_from_bytes(
+    workspace_id,
+    workspace_id,
+    &CollabType::Folder,
+    pg_pool,
+    s3_client,
+  )
+  .await?;
+  let view_tree = {
+    let folder = Folder::from_collab_doc_state(
+      CollabOrigin::Server,
+      folder_collab.into(),
+      &workspace_id.to_string(),
+      default_client_id(),
+    )
+    .map_err(|err| ExportError::CannotOpenWorkspace(err.to_string()))?;
+    build_view_tree(&folder, uid, workspace_id)
+  };
+
+  let database_ids = if contains_database_view(&view_tree) {
+    database_id_by_view_id(workspace_id, pg_pool, s3_client).await?
+  } else {
+    HashMap::new()
+  };
+
+  let file = File::create(zip_path).await?;
+  let mut exporter = WorkspaceExporter {
+    workspace_id: *workspace_id,
+    pg_pool,
+    s3_client,
+    writer: ZipFileWriter::with_tokio(file),
+    database_ids,
+    blobs: BTreeMap::new(),
+    exported: HashMap::new(),
+  };
+
+  let mut stack = view_tree.iter().collect::<Vec<_>>();
+  while let Some(node) = stack.pop() {
+    exporter.export_view(node).await?;
+    stack.extend(node.children.iter());
+  }
+  let files = exporter.write_blobs().await?;
+
+  let manifest = ExportManifest {
+    version: MANIFEST_VERSION,
+    workspace_id: workspace_id.to_string(),
+    workspace_name: workspace_name.to_string(),
+    exported_at: Utc::now(),
+    views: to_manifest_views(view_tree, &mut exporter.exported),
+    files,
+  };
+  let manifest_json =
+    serde_json::to_vec_pretty(&manifest).map_err(|err| ExportError::Internal(err.into()))?;
+  exporter
+    .write_entry(MANIFEST_FILE_NAME.to_string(), &manifest_json)
+    .await?;
+  exporter.writer.close().await?;
+  Ok(manifest)
+}
+
+struct WorkspaceExporter<'a> {
+  workspace_id: Uuid,
+  pg_pool: &'a PgPool,
+  s3_client: &'a Arc<dyn S3Client>,
+  writer: ZipFileWriter<File>,
+  database_ids: HashMap<String, String>,
+  /// Object keys of the referenced blobs, mapped to their path in the archive.
+  blobs: BTreeMap<String, String>,
+  exported: HashMap<String, ExportedContent>,
+}
+
+impl WorkspaceExporter<'_> {
+  async fn write_entry(&mut self, path: String, data: &[u8]) -> Result<(), ExportError> {
+    trace!("[Export]: write {} ({} bytes)", path, data.len());
+    let entry = ZipEntryBuilder::new(path.into(), Compression::Deflate);
+    self.writer.write_entry_whole(entry, data).await?;
+    Ok(())
+  }
+
+  async fn export_view(&mut self, node: &ViewNode) -> Result<(), ExportError> {
+    let content = match node.layout {
+      ViewLayout::Document => self.export_document(node).await,
+      ref layout if layout.is_database() => self.export_database(node).await,
+      _ => Ok(ExportedContent::default()),
+    };
+
+    // A view that can't be exported shouldn't fail the whole export, it is kept in the manifest
+    // without content instead.
+    let content = content.unwrap_or_else(|err| {
+      warn!("[Export]: failed to export view {}: {}", node.view_id, err);
+      ExportedContent::default()
+    });
+    self.exported.insert(node.view_id.clone(), content);
+    Ok(())
+  }
+
+  async fn export_document(&mut self, node: &ViewNode) -> Result<ExportedContent, ExportError> {
+    let object_id = Uuid::parse_str(&node.view_id)?;
+    let encoded_collab = self
+      .get_encoded_collab(&object_id, CollabType::Document)
+      .await?;
+    let collab = open_collab(&node.view_id, encoded_collab)?;
+    let document_data = Document::open(collab)
+      .and_then(|document| document.get_document_data())
+      .map_err(|err| ExportError::CannotOpenCollab(node.view_id.clone(), err.to_string()))?;
+
+    let path = format!("{}.md", node.stem);
+    let relative_root = "../".repeat(path.matches('/').count());
+    let workspace_id = self.workspace_id;
+    let blobs = &mut self.blobs;
+    let markdown = document_to_markdown(&document_data, &mut |url| match blob_object_key(
+      &workspace_id,
+      url,
+    ) {
+      Some((object_key, file_path)) => {
+        blobs.insert(object_key, file_path.clone());
+        format!("{}{}", relative_root, file_path)
+      },
+      None => url.to_string(),
+    });
+
+    self.write_entry(path.clone(), markdown.as_bytes()).await?;
+    Ok(ExportedContent {
+      path: Some(path),
+      ..Default::default()
+    })
+  }
+
+  async fn export_database(&mut self, node: &ViewNode) -> Result<ExportedContent, ExportError> {
+    let database_id = self
+      .database_ids
+      .get(&node.view_id)
+      .cloned()
+      .ok_or_else(|| anyhow!("database of view {} not found", node.view_id))?;
+    let encoded_collab = self
+      .get_encoded_collab(&Uuid::parse_str(&database_id)?, CollabType::Database)
+      .await?;
+
+    let (fields, row_ids) = {
+      let collab = open_collab(&database_id, encoded_collab)?;
+      let body = DatabaseBody::from_collab(
+        &collab,
+        Arc::new(NoPersistenceDatabaseCollabService::new(default_client_id())),
+        None,
+      )
+      .ok_or_else(|| ExportError::CannotOpenCollab(database_id.clone(), "no body".to_string()))?;
+      let txn = collab.transact();
+      let mut fields_by_id = body
+        .fields
+        .get_all_fields(&txn)
+        .into_iter()
+        .map(|field| (field.id.clone(), field))
+        .collect::<HashMap<_, _>>();
+      let fields = body
+        .views
+        .get_field_orders(&txn, &node.view_id)
+        .into_iter()
+        .filter_map(|field_order| fields_by_id.remove(&field_order.id))
+        .collect::<Vec<_>>();
+      let row_ids = body
+        .views
+        .get_row_orders(&txn, &node.view_id)
+        .into_iter()
+        .map(|row_order| row_order.id.to_string())
+        .collect::<Vec<_>>();
+      (fields, row_ids)
+    };
+
+    let exporter = &*self;
+    let rows = stream::iter(row_ids)
+      .map(|row_id| async move {
+        let object_id = Uuid::parse_str(&row_id).ok()?;
+        let encoded_collab = exporter
+          .get_encoded_collab(&object_id, CollabType::DatabaseRow)
+          .await
+          .ok()?;
+        let collab = open_collab(&row_id, encoded_collab).ok()?;
+        RowDetail::from_collab(&collab)
+      })
+      .buffered(10)
+      .filter_map(|row| async move { row })
+      .collect::<Vec<_>>()
+      .await;
+
+    let csv = database_to_csv(&fields, rows)?;
+    let path = format!("{}.csv", node.stem);
+    self.write_entry(path.clone(), &csv).await?;
+
+    let schema = json!({
+      "database_id": database_id,
+      "view_id": node.view_id,
+      "fields": fields.iter().map(field_schema).collect::<Vec<_>>(),
+    });
+    let schema_path = format!("{}.schema.json", node.stem);
+    let schema =
+      serde_json::to_vec_pretty(&schema).map_err(|err| ExportError::Internal(err.into()))?;
+    self.write_entry(schema_path.clone(), &schema).await?;
+
+    Ok(ExportedContent {
+      path: Some(path),
+      schema_path: Some(schema_path),
+      database_id: Some(database_id),
+    })
+  }
+
+  /// Streams the referenced blobs into the archive. Returns the paths of the blobs that could be
+  /// downloaded. A download failing halfway fails the export, since the entry would be truncated.
+  async fn write_blobs(&mut self) -> Result<Vec<String>, ExportError> {
+    let mut files = vec![];
+    for (object_key, path) in std::mem::take(&mut self.blobs) {
+      let mut resp = match self.s3_client.get_blob_stream(&object_key).await {
+        Ok(resp) => resp,
+        Err(err) => {
+          warn!("[Export]: failed to download blob {}: {}", object_key, err);
+          continue;
+        },
+      };
+      trace!("[Export]: write {}", path);
+      let entry = ZipEntryBuilder::new(path.clone().into(), Compression::Deflate);
+      let mut entry_writer = self.writer.write_entry_stream(entry).await?;
+      futures::io::copy(&mut resp.stream, &mut entry_writer).await?;
+      entry_writer.close().await?;
+      files.push(path);
+    }
+    Ok(files)
+  }
+
+  async fn get_encoded_collab(
+    &self,
+    object_id: &Uuid,
+    collab_type: CollabType,
+  ) -> Result<EncodedCollab, ExportError> {
+    let encoded_collab = get_encode_collab_from_bytes(
+      &self.workspace_id,
+      object_id,
+      &collab_type,
+      self.pg_pool,
+      self.s3_client,
+    )
+    .await?;
+    Ok(encoded_collab)
+  }
+}
+
+fn open_collab(object_id: &str, encoded_collab: EncodedCollab) -> Result<Collab, ExportError> {
+  let options = CollabOptions::new(object_id.to_string(), default_client_id())
+    .with_data_source(encoded_collab.into());
+  Collab::new_with_options(CollabOrigin::Server, options)
+    .map_err(|err| ExportError::CannotOpenCollab(object_id.to_string(), err.to_string()))
+}
+
+/// Builds the tree of the views the user can see, skipping the views in the trash and the
+/// private spaces of other members.
+fn build_view_tree(folder: &Folder, uid: i64, workspace_id: &Uuid) -> Vec<ViewNode> {
+  let mut excluded = folder
+    .get_all_trash_sections(uid)
+    .into_iter()
+    .map(|section| section.id)
+    .collect::<HashSet<_>>();
+  let my_private_space_ids = folder
+    .get_my_private_sections(uid)
+    .into_iter()
+    .map(|section| section.id)
+    .collect::<HashSet<_>>();
+  excluded.extend(
+    folder
+      .get_all_private_sections(uid)
+      .into_iter()
+      .map(|section| section.id)
+      .filter(|id| !my_private_space_ids.contains(id)),
+  );
+
+  let mut visited = HashSet::new();
+  child_view_nodes(
+    folder,
+    uid,
+    &workspace_id.to_string(),
+    "",
+    &excluded,
+    &mut visited,
+  )
+}
+
+fn child_view_nodes(
+  folder: &Folder,
+  uid: i64,
+  parent_view_id: &str,
+  parent_dir: &str,
+  excluded: &HashSet<String>,
+  visited: &mut HashSet<String>,
+) -> Vec<ViewNode> {
+  let parent = match folder.get_view(parent_view_id, uid) {
+    Some(parent) => parent,
+    None => return vec![],
+  };
+
+  let mut nodes = vec![];
+  for child in parent.children.iter() {
+    if excluded.contains(&child.id) || !visited.insert(child.id.clone()) {
+      continue;
+    }
+    let view = match folder.get_view(&child.id, uid) {
+      Some(view) => view,
+      None => continue,
+    };
+    let file_name = format!(
+      "{} {}",
+      sanitize_file_name(&view.name),
+      view.id.chars().take(8).collect::<String>()
+    );
+    let stem = if parent_dir.is_empty() {
+      file_name
+    } else {
+      format!("{}/{}", parent_dir, file_name)
+    };
+    let children = child_view_nodes(folder, uid, &view.id, &stem, excluded, visited);
+    nodes.push(ViewNode {
+      view_id: view.id.clone(),
+      name: view.name.clone(),
+      layout: view.layout.clone(),
+      stem,
+      children,
+    });
+  }
+  nodes
+}
+
+fn contains_database_view(nodes: &[ViewNode]) -> bool {
+  nodes
+    .iter()
+    .any(|node| node.layout.is_database() || contains_database_view(&node.children))
+}
+
+async fn database_id_by_view_id(
+  workspace_id: &Uuid,
+  pg_pool: &PgPool,
+  s3_client: &Arc<dyn S3Client>,
+) -> Result<HashMap<String, String>, ExportError> {
+  let w_database_id =
+    select_workspace_database_storage_id(pg_pool, &workspace_id.to_string()).await?;
+  let w_database_collab = get_encode_collab_from_bytes(
+    workspace_id,
+    &w_database_id,
+    &CollabType::WorkspaceDatabase,
+    pg_pool,
+    s3_client,
+  )
+  .await?;
+  let w_database = WorkspaceDatabase::from_collab_doc_state(
+    &w_database_id.to_string(),
+    CollabOrigin::Server,
+    w_database_collab.into(),
+    default_client_id(),
+  )
+  .map_err(|err| ExportError::CannotOpenWorkspace(err.to_string()))?;
+
+  let mut database_ids = HashMap::new();
+  for meta in w_database.get_all_database_meta() {
+    for view_id in meta.linked_views {
+      database_ids.insert(view_id, meta.database_id.clone());
+    }
+  }
+  Ok(database_ids)
+}
+
+fn to_manifest_views(
+  nodes: Vec<ViewNode>,
+  exported: &mut HashMap<String, ExportedContent>,
+) -> Vec<ManifestView> {
+  nodes
+    .into_iter()
+    .map(|node| {
+      let content = exported.remove(&node.view_id).unwrap_or_default();
+      ManifestView {
+        layout: format!("{:?}", node.layout),
+        view_id: node.view_id,
+        name: node.name,
+        path: content.path,
+        schema_path: content.schema_path,
+        database_id: content.database_id,
+        children: to_manifest_views(node.children, exported),
+      }
+    })
+    .collect()
+}
+
+fn database_to_csv(fields: &[Field], rows: Vec<RowDetail>) -> Result<Vec<u8>, ExportError> {
+  let mut writer = csv::Writer::from_writer(vec![]);
+  writer
+    .write_record(fields.iter().map(|field| field.name.as_str()))
+    .map_err(|err| ExportError::Internal(err.into()))?;
+
+  let readers = fields
+    .iter()
+    .map(|field| {
+      let field_type = FieldType::from(field.field_type);
+      type_option_cell_reader(field_type_option(field), &field_type)
+    })
+    .collect::<Vec<_>>();
+  for row_detail in rows {
+    let row = row_detail.row;
+    let record = fields.iter().zip(readers.iter()).map(|(field, reader)| {
+      let field_type = FieldType::from(field.field_type);
+      let cell = match row.cells.get(&field.id) {
+        Some(cell) => cell.clone(),
+        None => match field_type {
+          FieldType::CreatedTime => {
+            TimestampCellData::new(Some(row.created_at)).to_cell(field_type)
+          },
+          FieldType::LastEditedTime => {
+            TimestampCellData::new(Some(row.modified_at)).to_cell(field_type)
+          },
+          _ => Cell::new(),
+        },
+      };
+      match reader.json_cell(&cell) {
+        Value::Null => String::new(),
+        Value::String(value) => value,
+        value => value.to_string(),
+      }
+    });
+    writer
+      .write_record(record)
+      .map_err(|err| ExportError::Internal(err.into()))?;
+  }
+
+  writer
+    .into_inner()
+    .map_err(|err| ExportError::Internal(anyhow!("failed to write csv: {}", err)))
+}
+
+fn field_type_option(field: &Field) -> TypeOptionData {
+  let field_type = FieldType::from(field.field_type);
+  match field.get_any_type_option(field_type.type_id()) {
+    Some(type_option) => type_option.clone(),
+    None => HashMap::new(),
+  }
+}
+
+fn field_schema(field: &Field) -> Value {
+  // Some type options, such as the select options, are stored as stringified JSON.
+  let type_option = field_type_option(field)
+    .into_iter()
+    .map(|(key, value)| {
+      let value = match serde_json::to_value(&value).unwrap_or_default() {
+        Value::String(s) => serde_json::from_str::<Value>(&s)
+          .ok()
+          .filter(|v| v.is_object() || v.is_array())
+          .unwrap_or(Value::String(s)),
+        value => value,
+      };
+      (key, value)
+    })
+    .collect::<serde_json::Map<_, _>>();
+
+  json!({
+    "id": field.id,
+    "name": field.name,
+    "field_type": field.field_type,
+    "field_type_name": format!("{:?}", FieldType::from(field.field_type)),
+    "is_primary": field.is_primary,
+    "type_option": type_option,
+  })
+}
+
+/// Maps a url pointing to the file storage of the workspace to the object key of the blob and
+/// its path in the archive.
+fn blob_object_key(workspace_id: &Uuid, url: &str) -> Option<(String, String)> {
+  let prefix = format!("/api/file_storage/{}/", workspace_id);
+  let start = url.find(&prefix)? + prefix.len();
+  let path = url[start..].split(['?', '#']).next()?;
+  let segments = path.split('/').collect::<Vec<_>>();
+  match segments.as_slice() {
+    ["v1", "blob", parent_dir, file_id]
+      if is_name_segment(parent_dir) && is_name_segment(file_id) =>
+    {
+      Some((
+        format!("{}/{}/{}", workspace_id, parent_dir, file_id),
+        format!("{}/{}/{}", FILES_DIR, parent_dir, file_id),
+      ))
+    },
+    ["blob", file_id] if is_name_segment(file_id) => Some((
+      format!("{}/{}", workspace_id, file_id),
+      format!("{}/{}", FILES_DIR, file_id),
+    )),
+    _ => None,
+  }
+}
+
+/// Rejects empty and relative (`.` and `..`) segments, which would let a url escape the
+/// workspace in the object key or the files directory in the archive.
+fn is_name_segment(segment: &str) -> bool {
+  !matches!(segment, "" | "." | "..")
+}
+
+fn sanitize_file_name(name: &str) -> String {
+  let name = name
+    .chars()
+    .map(|c| match c {
+      '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+      c if c.is_control() => '_',
+      c => c,
+    })
+    .take(MAX_FILE_NAME_LEN)
+    .collect::<String>();
+  let name = name.trim().trim_start_matches('.');
+  if name.is_empty() {
+    "Untitled".to_string()
+  } else {
+    name.to_string()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn map_file_storage_url_to_blob() {
+    let workspace_id = Uuid::new_v4();
+    let url = format!(
+      "https://appflowy.cloud/api/file_storage/{}/v1/blob/{}/abc.png?x=1",
+      workspace_id, "parent"
+    );
+    assert_eq!(
+      blob_object_key(&workspace_id, &url),
+      Some((
+        format!("{}/parent/abc.png", workspace_id),
+        "files/parent/abc.png".to_string()
+      ))
+    );
+
+    let other_workspace_url = format!(
+      "https://appflowy.cloud/api/file_storage/{}/v1/blob/parent/abc.png",
+      Uuid::new_v4()
+    );
+    assert_eq!(blob_object_key(&workspace_id, &other_workspace_url), None);
+    assert_eq!(
+      blob_object_key(&workspace_id, "https://example.com/a.png"),
+      None
+    );
+  }
+
+  #[test]
+  fn reject_relative_segments_in_file_storage_url() {
+    let workspace_id = Uuid::new_v4();
+    for path in [
+      "v1/blob/../abc.png",
+      "v1/blob/parent/..",
+      "v1/blob/./abc.png",
+      "blob/..",
+    ] {
+      let url = format!(
+        "https://appflowy.cloud/api/file_storage/{}/{}",
+        workspace_id, path
+      );
+      assert_eq!(blob_object_key(&workspace_id, &url), None, "{}", path);
+    }
+  }
+
+  #[test]
+  fn sanitize_view_name() {
+    assert_eq!(sanitize_file_name("a/b:c"), "a_b_c");
+    assert_eq!(sanitize_file_name("  "), "Untitled");
+    assert_eq!(sanitize_file_name("..hidden"), "hidden");
+  }
+}
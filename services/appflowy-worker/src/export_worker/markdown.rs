@@ -0,0 +1,270 @@
+use collab_document::blocks::{Block, DocumentData};
+use serde_json::Value;
+
+const INDENT: &str = "  ";
+
+/// Renders the document as Markdown. Every url found in the document (links, images and files)
+/// is passed through `resolve_url`, which allows the caller to point them to files bundled next
+/// to the Markdown file.
+pub fn document_to_markdown(
+  data: &DocumentData,
+  resolve_url: &mut dyn FnMut(&str) -> String,
+) -> String {
+  let mut writer = MarkdownWriter { data, resolve_url };
+  let mut markdown = match data.blocks.get(&data.page_id) {
+    Some(page) => writer.write_children(page, 0).join("\n\n"),
+    None => String::new(),
+  };
+  markdown.push('\n');
+  markdown
+}
+
+struct MarkdownWriter<'a, 'b> {
+  data: &'a DocumentData,
+  resolve_url: &'b mut dyn FnMut(&str) -> String,
+}
+
+impl<'a> MarkdownWriter<'a, '_> {
+  fn children(&self, block: &Block) -> Vec<&'a Block> {
+    let data = self.data;
+    data
+      .meta
+      .children_map
+      .get(&block.children)
+      .map(|ids| ids.iter().filter_map(|id| data.blocks.get(id)).collect())
+      .unwrap_or_default()
+  }
+
+  fn write_children(&mut self, parent: &Block, depth: usize) -> Vec<String> {
+    let mut number = 0;
+    let mut output = vec![];
+    for child in self.children(parent) {
+      number = if child.ty == "numbered_list" {
+        number + 1
+      } else {
+        0
+      };
+      output.push(self.write_block(child, depth, number));
+    }
+    output
+  }
+
+  fn write_block(&mut self, block: &Block, depth: usize, number: usize) -> String {
+    let text = self.block_text(block, true);
+    let line = match block.ty.as_str() {
+      "heading" => {
+        let level = data_u64(block, "level").unwrap_or(1).clamp(1, 6) as usize;
+        format!("{} {}", "#".repeat(level), text)
+      },
+      "todo_list" => {
+        let checked = block
+          .data
+          .get("checked")
+          .and_then(Value::as_bool)
+          .unwrap_or(false);
+        format!("- [{}] {}", if checked { "x" } else { " " }, text)
+      },
+      "bulleted_list" | "toggle_list" => format!("- {}", text),
+      "numbered_list" => format!("{}. {}", number, text),
+      "quote" | "callout" => format!("> {}", text),
+      "code" => format!(
+        "```{}\n{}\n```",
+        data_str(block, "language").unwrap_or_default(),
+        self.block_text(block, false)
+      ),
+      "divider" => "---".to_string(),
+      "math_equation" => format!("$$\n{}\n$$", data_str(block, "formula").unwrap_or_default()),
+      "image" => match data_str(block, "url") {
+        Some(url) => format!("![]({})", (self.resolve_url)(url)),
+        None => String::new(),
+      },
+      "file" => match data_str(block, "url") {
+        Some(url) => format!(
+          "[{}]({})",
+          data_str(block, "name").unwrap_or(url),
+          (self.resolve_url)(url)
+        ),
+        None => String::new(),
+      },
+      _ => text,
+    };
+
+    let indent = INDENT.repeat(depth);
+    let mut lines = line
+      .lines()
+      .map(|line| format!("{}{}", indent, line))
+      .collect::<Vec<_>>();
+    lines.extend(self.write_children(block, depth + 1));
+    lines.join("\n")
+  }
+
+  /// Returns the text of the block. When `formatted` is true, the inline attributes of the delta
+  /// are rendered as Markdown.
+  fn block_text(&mut self, block: &Block, formatted: bool) -> String {
+    let delta = block
+      .external_id
+      .as_ref()
+      .and_then(|id| self.data.meta.text_map.as_ref()?.get(id))
+      .and_then(|delta| serde_json::from_str::<Vec<Value>>(delta).ok())
+      .unwrap_or_default();
+
+    let mut text = String::new();
+    for op in delta {
+      let insert = match op.get("insert").and_then(Value::as_str) {
+        Some(insert) => insert,
+        None => continue,
+      };
+      match op.get("attributes").filter(|_| formatted) {
+        Some(attributes) => text.push_str(&self.format_insert(insert, attributes)),
+        None => text.push_str(insert),
+      }
+    }
+    text
+  }
+
+  fn format_insert(&mut self, insert: &str, attributes: &Value) -> String {
+    let is_set = |key: &str| attributes.get(key).and_then(Value::as_bool) == Some(true);
+    let mut text = insert.to_string();
+    if is_set("code") {
+      text = format!("`{}`", text);
+    }
+    if is_set("bold") {
+      text = format!("**{}**", text);
+    }
+    if is_set("italic") {
+      text = format!("_{}_", text);
+    }
+    if is_set("strikethrough") {
+      text = format!("~~{}~~", text);
+    }
+    if let Some(href) = attributes.get("href").and_then(Value::as_str) {
+      text = format!("[{}]({})", text, (self.resolve_url)(href));
+    }
+    text
+  }
+}
+
+fn data_str<'a>(block: &'a Block, key: &str) -> Option<&'a str> {
+  block
+    .data
+    .get(key)
+    .and_then(Value::as_str)
+    .filter(|value| !value.is_empty())
+}
+
+fn data_u64(block: &Block, key: &str) -> Option<u64> {
+  let value = block.data.get(key)?;
+  value
+    .as_u64()
+    .or_else(|| value.as_f64().map(|value| value as u64))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use collab_document::blocks::DocumentMeta;
+  use serde_json::json;
+  use std::collections::HashMap;
+
+  fn block(id: &str, ty: &str, data: Value, delta: Option<Value>) -> (Block, Option<String>) {
+    let data = serde_json::from_value::<HashMap<String, Value>>(data).unwrap();
+    let block = Block {
+      id: id.to_string(),
+      ty: ty.to_string(),
+      parent: "page".to_string(),
+      children: format!("{}_children", id),
+      external_id: delta.as_ref().map(|_| format!("{}_text", id)),
+      external_type: delta.as_ref().map(|_| "text".to_string()),
+      data,
+    };
+    (block, delta.map(|delta| delta.to_string()))
+  }
+
+  fn document(children: Vec<(Block, Option<String>)>) -> DocumentData {
+    let mut blocks = HashMap::new();
+    let mut text_map = HashMap::new();
+    let mut child_ids = vec![];
+    for (block, delta) in children {
+      if let (Some(external_id), Some(delta)) = (&block.external_id, delta) {
+        text_map.insert(external_id.clone(), delta);
+      }
+      child_ids.push(block.id.clone());
+      blocks.insert(block.id.clone(), block);
+    }
+    let (page, _) = block("page", "page", json!({}), None);
+    blocks.insert("page".to_string(), page);
+    DocumentData {
+      page_id: "page".to_string(),
+      blocks,
+      meta: DocumentMeta {
+        children_map: HashMap::from([("page_children".to_string(), child_ids)]),
+        text_map: Some(text_map),
+      },
+    }
+  }
+
+  #[test]
+  fn render_document_blocks_as_markdown() {
+    let data = document(vec![
+      block(
+        "h1",
+        "heading",
+        json!({"level": 2}),
+        Some(json!([{"insert": "Title"}])),
+      ),
+      block(
+        "p1",
+        "paragraph",
+        json!({}),
+        Some(json!([
+          {"insert": "plain "},
+          {"insert": "bold", "attributes": {"bold": true}},
+          {"insert": " "},
+          {"insert": "link", "attributes": {"href": "https://appflowy.io"}}
+        ])),
+      ),
+      block(
+        "n1",
+        "numbered_list",
+        json!({}),
+        Some(json!([{"insert": "one"}])),
+      ),
+      block(
+        "n2",
+        "numbered_list",
+        json!({}),
+        Some(json!([{"insert": "two"}])),
+      ),
+      block(
+        "t1",
+        "todo_list",
+        json!({"checked": true}),
+        Some(json!([{"insert": "done"}])),
+      ),
+      block(
+        "c1",
+        "code",
+        json!({"language": "rust"}),
+        Some(json!([{"insert": "let a = 1;", "attributes": {"bold": true}}])),
+      ),
+      block(
+        "i1",
+        "image",
+        json!({"url": "https://example.com/a.png"}),
+        None,
+      ),
+    ]);
+
+    let markdown = document_to_markdown(&data, &mut |url| url.replace("https://", "local/"));
+    assert_eq!(
+      markdown,
+      "## Title\n\n\
+       plain **bold** [link](local/appflowy.io)\n\n\
+       1. one\n\n\
+       2. two\n\n\
+       - [x] done\n\n\
+       ```rust\nlet a = 1;\n```\n\n\
+       ![](local/example.com/a.png)\n"
+    );
+  }
+}
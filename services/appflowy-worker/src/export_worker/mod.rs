@@ -0,0 +1,5 @@
+pub mod archive;
+pub mod email_notifier;
+pub mod markdown;
+pub mod report;
+pub mod worker;
@@ -0,0 +1,14 @@
+use axum::async_trait;
+
+#[async_trait]
+pub trait ExportNotifier: Send + Sync + 'static {
+  async fn notify_finished(&self, result: ExportResult);
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportResult {
+  pub user_name: String,
+  pub user_email: String,
+  pub is_success: bool,
+  pub value: serde_json::Value,
+}
@@ -0,0 +1,390 @@
+use crate::error::{ExportError, WorkerError};
+use crate::export_worker::archive::export_workspace_to_zip;
+use crate::export_worker::report::{ExportNotifier, ExportResult};
+use crate::import_worker::worker::ensure_consumer_group;
+use crate::mailer::ExportWorkspaceMailerParam;
+use crate::s3_client::S3Client;
+use anyhow::anyhow;
+use aws_sdk_s3::primitives::ByteStream;
+use database::workspace::{
+  delete_export_task, select_expired_export_tasks, select_export_task,
+  update_export_task_completed, update_export_task_failed, ExportTaskState,
+};
+use infra::env_util::get_env_var;
+use redis::aio::ConnectionManager;
+use redis::streams::{
+  StreamClaimOptions, StreamClaimReply, StreamId, StreamPendingReply, StreamReadOptions,
+  StreamReadReply,
+};
+use redis::{AsyncCommands, Value};
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{self, Utc};
+use sqlx::PgPool;
+use std::env::temp_dir;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::fs;
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::{error, info, trace};
+use uuid::Uuid;
+
+const GROUP_NAME: &str = "export_task_group";
+const CONSUMER_NAME: &str = "appflowy_worker";
+
+/// How often the archives whose download link expired are deleted.
+const EXPORT_RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const EXPORT_RETENTION_BATCH_SIZE: i64 = 100;
+
+pub async fn run_export_worker(
+  pg_pool: PgPool,
+  mut redis_client: ConnectionManager,
+  s3_client: Arc<dyn S3Client>,
+  notifier: Arc<dyn ExportNotifier>,
+  stream_name: &str,
+  tick_interval_secs: u64,
+) -> Result<(), ExportError> {
+  info!("Starting export worker");
+  if let Err(err) = ensure_consumer_group(stream_name, GROUP_NAME, &mut redis_client).await {
+    error!("Failed to ensure consumer group: {:?}", err);
+  }
+
+  let context = TaskContext {
+    storage_dir: temp_dir(),
+    redis_client,
+    s3_client,
+    pg_pool,
+    notifier,
+  };
+
+  // when server restarts, we need to check if there are any unacknowledged tasks
+  match get_un_ack_tasks(stream_name, &mut context.redis_client.clone()).await {
+    Ok(un_ack_tasks) => {
+      info!("Found {} unacknowledged export tasks", un_ack_tasks.len());
+      for (entry_id, task) in un_ack_tasks {
+        consume_task(context.clone(), task, stream_name, &entry_id).await;
+      }
+    },
+    Err(err) => error!("Failed to get unacknowledged export tasks: {:?}", err),
+  }
+
+  process_upcoming_tasks(context, stream_name, tick_interval_secs).await
+}
+
+#[derive(Clone)]
+struct TaskContext {
+  storage_dir: PathBuf,
+  redis_client: ConnectionManager,
+  s3_client: Arc<dyn S3Client>,
+  pg_pool: PgPool,
+  notifier: Arc<dyn ExportNotifier>,
+}
+
+async fn process_upcoming_tasks(
+  mut context: TaskContext,
+  stream_name: &str,
+  interval_secs: u64,
+) -> Result<(), ExportError> {
+  let options = StreamReadOptions::default()
+    .group(GROUP_NAME, CONSUMER_NAME)
+    .count(10);
+  let mut interval = interval(Duration::from_secs(interval_secs));
+  interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+  interval.tick().await;
+
+  loop {
+    interval.tick().await;
+
+    let tasks: StreamReadReply = match context
+      .redis_client
+      .xread_options(&[stream_name], &[">"], &options)
+      .await
+    {
+      Ok(tasks) => tasks,
+      Err(err) => {
+        error!("Failed to read export tasks from Redis stream: {:?}", err);
+        if err.code() == Some("NOGROUP") {
+          if let Err(err) =
+            ensure_consumer_group(stream_name, GROUP_NAME, &mut context.redis_client).await
+          {
+            error!("Failed to ensure consumer group: {:?}", err);
+          }
+        }
+        continue;
+      },
+    };
+
+    // Exports are processed one by one since building an archive reads the whole workspace.
+    for stream_key in tasks.keys {
+      for stream_id in stream_key.ids {
+        match WorkspaceExportTask::try_from(&stream_id) {
+          Ok(task) => consume_task(context.clone(), task, stream_name, &stream_id.id).await,
+          Err(err) => error!("Failed to deserialize export task: {:?}", err),
+        }
+      }
+    }
+  }
+}
+
+async fn consume_task(
+  mut context: TaskContext,
+  task: WorkspaceExportTask,
+  stream_name: &str,
+  entry_id: &str,
+) {
+  info!("[Export]: Processing task: {}", task);
+  match select_export_task(&context.pg_pool, &task.task_id).await {
+    Ok(record)
+      if matches!(
+        ExportTaskState::from(record.status),
+        ExportTaskState::Pending
+      ) =>
+    {
+      let result = process_task(&context, &task).await;
+      notify_user(&context, &task, result).await;
+    },
+    Ok(_) => info!("[Export]: task {} was already processed", task.task_id),
+    Err(err) => error!("[Export]: failed to get task {}: {:?}", task.task_id, err),
+  }
+
+  let result: Result<(), redis::RedisError> =
+    context.redis_client.xdel(stream_name, &[entry_id]).await;
+  if let Err(err) = result {
+    error!(
+      "[Export] failed to delete task:{} error:{:?}",
+      task.task_id, err
+    );
+  }
+}
+
+/// Builds the archive and uploads it to S3. Returns the url the archive can be downloaded from.
+async fn process_task(
+  context: &TaskContext,
+  task: &WorkspaceExportTask,
+) -> Result<String, ExportError> {
+  let zip_path = context
+    .storage_dir
+    .join(format!("export_{}.zip", task.task_id));
+  let result = export_and_upload(context, task, &zip_path).await;
+  if let Err(err) = fs::remove_file(&zip_path).await {
+    trace!("[Export]: failed to remove {:?}: {}", zip_path, err);
+  }
+  let (s3_key, file_size) = result?;
+
+  let url = context
+    .s3_client
+    .gen_presigned_download_url(&s3_key, export_link_expire_secs())
+    .await?;
+  // Only mark the task as completed once the download link exists, otherwise a failed presign
+  // leaves a completed task that the user was never notified about.
+  update_export_task_completed(&task.task_id, &s3_key, file_size, &context.pg_pool).await?;
+  Ok(url)
+}
+
+async fn export_and_upload(
+  context: &TaskContext,
+  task: &WorkspaceExportTask,
+  zip_path: &Path,
+) -> Result<(String, i64), ExportError> {
+  let manifest = export_workspace_to_zip(
+    task.uid,
+    &task.workspace_id,
+    &task.workspace_name,
+    &context.pg_pool,
+    &context.s3_client,
+    zip_path,
+  )
+  .await?;
+  info!(
+    "[Export]: {} archive created with {} files",
+    task.workspace_id,
+    manifest.files.len()
+  );
+
+  let file_size = fs::metadata(zip_path).await?.len() as i64;
+  let s3_key = export_object_key(&task.workspace_id, &task.task_id);
+  let byte_stream = ByteStream::from_path(zip_path)
+    .await
+    .map_err(|err| ExportError::Internal(err.into()))?;
+  context
+    .s3_client
+    .put_blob(&s3_key, byte_stream, Some("application/zip"))
+    .await?;
+  Ok((s3_key, file_size))
+}
+
+async fn notify_user(
+  context: &TaskContext,
+  task: &WorkspaceExportTask,
+  result: Result<String, ExportError>,
+) {
+  let (download_url, error) = match result {
+    Ok(url) => {
+      info!("[Export]: successfully exported:{}", task);
+      (Some(url), None)
+    },
+    Err(err) => {
+      error!("[Export]: failed to export:{}: error:{:?}", task, err);
+      if let Err(err) =
+        update_export_task_failed(&task.task_id, &err.to_string(), &context.pg_pool).await
+      {
+        error!("[Export]: failed to update task status: {:?}", err);
+      }
+      (None, Some(err.to_string()))
+    },
+  };
+
+  let is_success = error.is_none();
+  let value = serde_json::to_value(ExportWorkspaceMailerParam {
+    export_task_id: task.task_id.to_string(),
+    user_name: task.user_name.clone(),
+    workspace_id: task.workspace_id.to_string(),
+    workspace_name: task.workspace_name.clone(),
+    download_url,
+    error,
+  })
+  .unwrap();
+
+  context
+    .notifier
+    .notify_finished(ExportResult {
+      user_name: task.user_name.clone(),
+      user_email: task.user_email.clone(),
+      is_success,
+      value,
+    })
+    .await;
+}
+
+pub fn export_object_key(workspace_id: &Uuid, task_id: &Uuid) -> String {
+  format!("export/{}/{}.zip", workspace_id, task_id)
+}
+
+fn export_link_expire_secs() -> u64 {
+  get_env_var("APPFLOWY_WORKER_EXPORT_LINK_EXPIRE_SECS", "604800")
+    .parse::<u64>()
+    .unwrap_or(604800)
+}
+
+/// Periodically deletes the archives and the tasks of the exports whose download link expired.
+/// The archive can't be downloaded anymore at that point, so keeping it only costs storage.
+pub async fn run_export_retention(pg_pool: PgPool, s3_client: Arc<dyn S3Client>) {
+  let mut interval = interval(EXPORT_RETENTION_INTERVAL);
+  interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+  loop {
+    interval.tick().await;
+    let expired_before =
+      Utc::now() - chrono::Duration::seconds(export_link_expire_secs().min(i64::MAX as u64) as i64);
+    loop {
+      let tasks =
+        match select_expired_export_tasks(&pg_pool, expired_before, EXPORT_RETENTION_BATCH_SIZE)
+          .await
+        {
+          Ok(tasks) => tasks,
+          Err(err) => {
+            error!("[Export]: failed to get expired export tasks: {:?}", err);
+            break;
+          },
+        };
+      let mut is_last_batch = (tasks.len() as i64) < EXPORT_RETENTION_BATCH_SIZE;
+      for task in tasks {
+        if let Some(s3_key) = &task.s3_key {
+          // the task is kept if its archive can't be deleted, so that it is retried on the next
+          // tick rather than selected again by the next batch
+          if let Err(err) = s3_client.delete_blob(s3_key).await {
+            error!("[Export]: failed to delete archive {}: {:?}", s3_key, err);
+            is_last_batch = true;
+            continue;
+          }
+        }
+        if let Err(err) = delete_export_task(&pg_pool, &task.task_id).await {
+          error!(
+            "[Export]: failed to delete export task {}: {:?}",
+            task.task_id, err
+          );
+          is_last_batch = true;
+        }
+      }
+      if is_last_batch {
+        break;
+      }
+    }
+  }
+}
+
+async fn get_un_ack_tasks(
+  stream_key: &str,
+  redis_client: &mut ConnectionManager,
+) -> Result<Vec<(String, WorkspaceExportTask)>, anyhow::Error> {
+  let reply: StreamPendingReply = redis_client.xpending(stream_key, GROUP_NAME).await?;
+  match reply {
+    StreamPendingReply::Empty => Ok(vec![]),
+    StreamPendingReply::Data(pending) => {
+      let opts = StreamClaimOptions::default()
+        .idle(500)
+        .with_force()
+        .retry(2);
+
+      let mut ids = vec![pending.start_id.clone()];
+      if pending.start_id != pending.end_id {
+        ids.push(pending.end_id);
+      }
+
+      let result: StreamClaimReply = redis_client
+        .xclaim_options(stream_key, GROUP_NAME, CONSUMER_NAME, 500, &ids, opts)
+        .await?;
+      Ok(
+        result
+          .ids
+          .into_iter()
+          .filter_map(|stream_id| {
+            WorkspaceExportTask::try_from(&stream_id)
+              .map(|task| (stream_id.id, task))
+              .ok()
+          })
+          .collect(),
+      )
+    },
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceExportTask {
+  pub uid: i64,
+  pub user_name: String,
+  pub user_email: String,
+  pub task_id: Uuid,
+  pub workspace_id: Uuid,
+  pub workspace_name: String,
+  pub created_at: i64,
+}
+
+impl Display for WorkspaceExportTask {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "WorkspaceExportTask {{ task_id: {}, workspace_id: {}, workspace_name: {}, user_email: {} }}",
+      self.task_id, self.workspace_id, self.workspace_name, self.user_email
+    )
+  }
+}
+
+impl TryFrom<&StreamId> for WorkspaceExportTask {
+  type Error = WorkerError;
+
+  fn try_from(stream_id: &StreamId) -> Result<Self, Self::Error> {
+    let task_str = match stream_id.map.get("task") {
+      Some(Value::SimpleString(value)) => value.to_string(),
+      Some(Value::BulkString(data)) => String::from_utf8_lossy(data).to_string(),
+      value => {
+        return Err(WorkerError::Internal(anyhow!(
+          "Unexpected value for task field: {:?}",
+          value
+        )))
+      },
+    };
+    serde_json::from_str::<WorkspaceExportTask>(&task_str)
+      .map_err(|err| WorkerError::Internal(err.into()))
+  }
+}
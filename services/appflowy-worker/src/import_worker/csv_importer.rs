@@ -0,0 +1,247 @@
+use crate::error::ImportError;
+use crate::import_worker::markdown_importer::add_import_space;
+use crate::import_worker::worker::ImportedWorkspaceData;
+use anyhow::anyhow;
+use bytes::Bytes;
+use collab::core::collab::default_client_id;
+use collab_database::database::{gen_row_id, timestamp, Database, DatabaseContext};
+use collab_database::database_trait::NoPersistenceDatabaseCollabService;
+use collab_database::entity::{CreateDatabaseParams, CreateViewParams, FieldType};
+use collab_database::fields::{default_field_settings_for_fields, Field};
+use collab_database::rows::{new_cell_builder, CreateRowParams};
+use collab_database::template::entity::CELL_DATA;
+use collab_database::views::DatabaseLayout;
+use collab_entity::CollabType;
+use collab_folder::hierarchy_builder::NestedChildViewBuilder;
+use collab_folder::ViewLayout;
+use database_entity::dto::CollabParams;
+use sqlx::types::chrono::Utc;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs;
+use uuid::Uuid;
+
+/// Imports the CSV file found in `dir` as a grid database. The first column becomes the primary
+/// field. Columns whose values are all numbers become number fields, the others text fields.
+pub async fn import_csv_file(
+  uid: i64,
+  workspace_id: &Uuid,
+  workspace_name: &str,
+  dir: &Path,
+) -> Result<ImportedWorkspaceData, ImportError> {
+  let file_path = find_csv_file(dir).await?;
+  let content = fs::read(&file_path)
+    .await
+    .map_err(|err| ImportError::Internal(err.into()))?;
+  let table = CsvTable::parse(&content)?;
+
+  let database_id = Uuid::new_v4();
+  let view_id = Uuid::new_v4();
+  let field_types = table.field_types();
+  let fields = table
+    .headers
+    .iter()
+    .zip(field_types.iter())
+    .enumerate()
+    .map(|(index, (name, field_type))| Field::from_field_type(name, *field_type, index == 0))
+    .collect::<Vec<_>>();
+  let rows = table
+    .rows
+    .iter()
+    .map(|values| {
+      let mut row = CreateRowParams::new(gen_row_id(), database_id.to_string());
+      for ((field, field_type), value) in fields.iter().zip(field_types.iter()).zip(values) {
+        if value.is_empty() {
+          continue;
+        }
+        let mut cell = new_cell_builder(*field_type);
+        cell.insert(CELL_DATA.into(), value.clone().into());
+        row.cells.insert(field.id.clone(), cell);
+      }
+      row
+    })
+    .collect::<Vec<_>>();
+
+  let timestamp = timestamp();
+  let field_settings = default_field_settings_for_fields(&fields, DatabaseLayout::Grid);
+  let params = CreateDatabaseParams {
+    database_id: database_id.to_string(),
+    fields,
+    rows,
+    views: vec![CreateViewParams {
+      database_id: database_id.to_string(),
+      view_id: view_id.to_string(),
+      name: workspace_name.to_string(),
+      layout: DatabaseLayout::Grid,
+      field_settings,
+      created_at: timestamp,
+      modified_at: timestamp,
+      ..Default::default()
+    }],
+  };
+  let service = Arc::new(NoPersistenceDatabaseCollabService::new(default_client_id()));
+  let context = DatabaseContext::new(service.clone(), service);
+  let encoded_database = Database::create_with_view(params, context)
+    .await
+    .map_err(|err| ImportError::Internal(anyhow!("Failed to create database: {}", err)))?
+    .encode_database_collabs()
+    .await
+    .map_err(|err| ImportError::Internal(anyhow!("Failed to encode database: {}", err)))?;
+
+  let mut data = ImportedWorkspaceData::default();
+  let space_id = Uuid::new_v4();
+  add_import_space(&mut data, uid, workspace_id, &space_id)?;
+  data.views.push(
+    NestedChildViewBuilder::new(uid, space_id.to_string())
+      .with_view_id(view_id)
+      .with_name(workspace_name)
+      .with_layout(ViewLayout::Grid)
+      .build()
+      .view,
+  );
+
+  let updated_at = Utc::now();
+  let database_collab = encoded_database.encoded_database_collab;
+  data.collab_params_list.push(CollabParams {
+    object_id: database_collab.object_id,
+    collab_type: CollabType::Database,
+    encoded_collab_v1: Bytes::from(
+      database_collab
+        .encoded_collab
+        .encode_to_bytes()
+        .map_err(|err| ImportError::Internal(err.into()))?,
+    ),
+    updated_at: Some(updated_at),
+  });
+  for row_collab in encoded_database.encoded_row_collabs {
+    data.collab_params_list.push(CollabParams {
+      object_id: row_collab.object_id,
+      collab_type: CollabType::DatabaseRow,
+      encoded_collab_v1: Bytes::from(
+        row_collab
+          .encoded_collab
+          .encode_to_bytes()
+          .map_err(|err| ImportError::Internal(err.into()))?,
+      ),
+      updated_at: Some(updated_at),
+    });
+  }
+  data
+    .database_view_ids_by_database_id
+    .insert(database_id.to_string(), vec![view_id.to_string()]);
+  Ok(data)
+}
+
+async fn find_csv_file(dir: &Path) -> Result<PathBuf, ImportError> {
+  let mut entries = fs::read_dir(dir)
+    .await
+    .map_err(|err| ImportError::Internal(err.into()))?;
+  while let Some(entry) = entries
+    .next_entry()
+    .await
+    .map_err(|err| ImportError::Internal(err.into()))?
+  {
+    let path = entry.path();
+    if path.extension().map(|ext| ext == "csv").unwrap_or(false) {
+      return Ok(path);
+    }
+  }
+  Err(ImportError::Internal(anyhow!(
+    "No CSV file found in the uploaded file"
+  )))
+}
+
+#[derive(Debug)]
+struct CsvTable {
+  headers: Vec<String>,
+  rows: Vec<Vec<String>>,
+}
+
+impl CsvTable {
+  fn parse(content: &[u8]) -> Result<Self, ImportError> {
+    let mut reader = csv::ReaderBuilder::new()
+      .flexible(true)
+      .trim(csv::Trim::All)
+      .from_reader(content);
+    let headers = reader
+      .headers()
+      .map_err(|err| ImportError::Internal(anyhow!("Invalid CSV header: {}", err)))?
+      .iter()
+      .enumerate()
+      .map(
+        |(index, header)| match header.trim_start_matches('\u{feff}') {
+          "" => format!("Column {}", index + 1),
+          header => header.to_string(),
+        },
+      )
+      .collect::<Vec<_>>();
+    if headers.is_empty() {
+      return Err(ImportError::Internal(anyhow!(
+        "The CSV file has no columns"
+      )));
+    }
+
+    let mut rows = vec![];
+    for record in reader.records() {
+      let record =
+        record.map_err(|err| ImportError::Internal(anyhow!("Invalid CSV record: {}", err)))?;
+      let mut row = record
+        .iter()
+        .take(headers.len())
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>();
+      row.resize(headers.len(), String::new());
+      rows.push(row);
+    }
+    Ok(Self { headers, rows })
+  }
+
+  /// The primary field is always a text field.
+  fn field_types(&self) -> Vec<FieldType> {
+    (0..self.headers.len())
+      .map(|index| {
+        let mut values = self
+          .rows
+          .iter()
+          .map(|row| row[index].as_str())
+          .filter(|value| !value.is_empty())
+          .peekable();
+        let is_number = values.peek().is_some() && values.all(|value| value.parse::<f64>().is_ok());
+        if index > 0 && is_number {
+          FieldType::Number
+        } else {
+          FieldType::RichText
+        }
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_csv_and_infer_field_types() {
+    let content = "\u{feff}Name,Price,,Note\nApple, 1.5 ,x\nPear,2,y,ripe,extra\nKiwi,,z,\n";
+    let table = CsvTable::parse(content.as_bytes()).unwrap();
+    assert_eq!(table.headers, vec!["Name", "Price", "Column 3", "Note"]);
+    assert_eq!(
+      table.rows,
+      vec![
+        vec!["Apple", "1.5", "x", ""],
+        vec!["Pear", "2", "y", "ripe"],
+        vec!["Kiwi", "", "z", ""],
+      ]
+    );
+    assert_eq!(
+      table.field_types(),
+      vec![
+        FieldType::RichText,
+        FieldType::Number,
+        FieldType::RichText,
+        FieldType::RichText
+      ]
+    );
+  }
+}
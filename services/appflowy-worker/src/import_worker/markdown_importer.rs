@@ -0,0 +1,506 @@
+use crate::error::ImportError;
+use crate::import_worker::worker::ImportedWorkspaceData;
+use anyhow::anyhow;
+use bytes::Bytes;
+use collab::core::collab::default_client_id;
+use collab_document::blocks::DocumentData;
+use collab_document::document::Document;
+use collab_document::document_data::default_document_data;
+use collab_document::importer::md_importer::MDImporter;
+use collab_entity::CollabType;
+use collab_folder::hierarchy_builder::NestedChildViewBuilder;
+use collab_folder::{SpaceInfo, ViewLayout};
+use collab_importer::notion::page::CollabResource;
+use collab_importer::util::FileId;
+use database_entity::dto::CollabParams;
+use sqlx::types::chrono::Utc;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs;
+use tracing::trace;
+use uuid::Uuid;
+
+const IMPORTED_SPACE_NAME: &str = "Imported Space";
+
+/// A page created from a Markdown file, or from a directory that has no Markdown file with the
+/// same name next to it.
+struct MarkdownPage {
+  view_id: Uuid,
+  parent_view_id: Uuid,
+  name: String,
+  /// None when the page was created from a directory.
+  file_path: Option<PathBuf>,
+}
+
+/// Imports the Markdown files found in `root` into a new space.
+///
+/// Every `.md` file becomes a document and every directory becomes a page holding the files in
+/// it. When a directory has a Markdown file with the same name next to it, as in `Page.md` and
+/// `Page/`, the files of the directory become children of that document instead. Relative links
+/// to other Markdown files point to the imported pages, and relative images are uploaded as files
+/// of the document they appear in.
+pub async fn import_markdown_files(
+  uid: i64,
+  workspace_id: &Uuid,
+  host: &str,
+  root: &Path,
+) -> Result<ImportedWorkspaceData, ImportError> {
+  let space_id = Uuid::new_v4();
+  let canonical_root = fs::canonicalize(root)
+    .await
+    .map_err(|err| ImportError::Internal(err.into()))?;
+  let root_dir = root.to_path_buf();
+  let pages = tokio::task::spawn_blocking(move || collect_markdown_pages(&root_dir, space_id))
+    .await
+    .map_err(|err| ImportError::Internal(err.into()))??;
+  if pages.iter().all(|page| page.file_path.is_none()) {
+    return Err(ImportError::Internal(anyhow!(
+      "No Markdown file found in the uploaded file"
+    )));
+  }
+
+  let view_id_by_path = pages
+    .iter()
+    .filter_map(|page| Some((page.file_path.clone()?, page.view_id)))
+    .collect::<HashMap<_, _>>();
+
+  let mut data = ImportedWorkspaceData::default();
+  add_import_space(&mut data, uid, workspace_id, &space_id)?;
+
+  for page in pages {
+    let object_id = page.view_id.to_string();
+    let document_data = match &page.file_path {
+      None => default_document_data(&object_id),
+      Some(file_path) => {
+        let markdown = fs::read_to_string(file_path)
+          .await
+          .map_err(|err| ImportError::Internal(err.into()))?;
+        let base_dir = file_path.parent().unwrap_or(root);
+
+        // Files are uploaded under the document that references them. The file id is computed
+        // from the content of the file, so it is resolved before rewriting the links.
+        let mut file_url_by_target = HashMap::new();
+        let mut files = vec![];
+        for target in markdown_link_targets(&markdown) {
+          let path = match resolve_relative_path(base_dir, target) {
+            Some(path) if !view_id_by_path.contains_key(&path) => path,
+            _ => continue,
+          };
+          let path = match resolve_file_in_root(&canonical_root, &path) {
+            Some(path) => path,
+            None => continue,
+          };
+          let file_id = FileId::from_path(&path).await?;
+          file_url_by_target.insert(
+            target.to_string(),
+            format!(
+              "{}/api/file_storage/{}/v1/blob/{}/{}",
+              host, workspace_id, object_id, file_id
+            ),
+          );
+          let path = path.to_string_lossy().to_string();
+          if !files.contains(&path) {
+            files.push(path);
+          }
+        }
+        if !files.is_empty() {
+          data.resources.push(CollabResource {
+            object_id: object_id.clone(),
+            files,
+          });
+        }
+
+        let markdown = rewrite_markdown_links(&markdown, &mut |target| {
+          if let Some(url) = file_url_by_target.get(target) {
+            return Some(url.clone());
+          }
+          let path = resolve_relative_path(base_dir, target)?;
+          view_id_by_path
+            .get(&path)
+            .map(|view_id| format!("{}/app/{}/{}", host, workspace_id, view_id))
+        });
+        MDImporter::new(None)
+          .import(&object_id, markdown)
+          .map_err(|err| ImportError::Internal(anyhow!("Failed to import markdown: {:?}", err)))?
+      },
+    };
+
+    trace!("[Import]: imported markdown page: {}", page.name);
+    data
+      .collab_params_list
+      .push(document_collab_params(&page.view_id, document_data)?);
+    data.views.push(
+      NestedChildViewBuilder::new(uid, page.parent_view_id.to_string())
+        .with_view_id(page.view_id)
+        .with_name(&page.name)
+        .with_layout(ViewLayout::Document)
+        .build()
+        .view,
+    );
+  }
+
+  Ok(data)
+}
+
+/// Adds the space that holds the imported views.
+pub(crate) fn add_import_space(
+  data: &mut ImportedWorkspaceData,
+  uid: i64,
+  workspace_id: &Uuid,
+  space_id: &Uuid,
+) -> Result<(), ImportError> {
+  data.views.push(
+    NestedChildViewBuilder::new(uid, workspace_id.to_string())
+      .with_view_id(space_id)
+      .with_name(IMPORTED_SPACE_NAME)
+      .with_extra(|builder| builder.with_space_info(SpaceInfo::default()).build())
+      .build()
+      .view,
+  );
+  let space_id_str = space_id.to_string();
+  data.collab_params_list.push(document_collab_params(
+    space_id,
+    default_document_data(&space_id_str),
+  )?);
+  Ok(())
+}
+
+fn document_collab_params(
+  view_id: &Uuid,
+  document_data: DocumentData,
+) -> Result<CollabParams, ImportError> {
+  let document = Document::create(&view_id.to_string(), document_data, default_client_id())
+    .map_err(|err| ImportError::Internal(anyhow!("Failed to create document: {:?}", err)))?;
+  let encoded_collab = document
+    .encode_collab()
+    .map_err(|err| ImportError::Internal(anyhow!("Failed to encode document: {:?}", err)))?;
+  Ok(CollabParams {
+    object_id: *view_id,
+    collab_type: CollabType::Document,
+    encoded_collab_v1: Bytes::from(
+      encoded_collab
+        .encode_to_bytes()
+        .map_err(|err| ImportError::Internal(err.into()))?,
+    ),
+    updated_at: Some(Utc::now()),
+  })
+}
+
+/// Returns the pages in the order they must be inserted into the folder, parents first.
+fn collect_markdown_pages(root: &Path, space_id: Uuid) -> Result<Vec<MarkdownPage>, ImportError> {
+  // Archives usually wrap all the files in a single top level directory.
+  let mut root = root.to_path_buf();
+  loop {
+    match read_sorted_dir(&root)?.as_slice() {
+      [path] if path.is_dir() => root = path.clone(),
+      _ => break,
+    }
+  }
+
+  let mut pages = vec![];
+  collect_pages_in_dir(&root, space_id, &mut pages)?;
+  Ok(pages)
+}
+
+fn collect_pages_in_dir(
+  dir: &Path,
+  parent_view_id: Uuid,
+  pages: &mut Vec<MarkdownPage>,
+) -> Result<(), ImportError> {
+  let paths = read_sorted_dir(dir)?;
+  let mut view_id_by_name = HashMap::new();
+  for path in paths.iter().filter(|path| is_markdown_file(path)) {
+    let name = path
+      .file_stem()
+      .map(|name| name.to_string_lossy().to_string())
+      .unwrap_or_default();
+    let view_id = Uuid::new_v4();
+    view_id_by_name.insert(name.clone(), view_id);
+    pages.push(MarkdownPage {
+      view_id,
+      parent_view_id,
+      name,
+      file_path: Some(path.clone()),
+    });
+  }
+
+  for path in paths.iter().filter(|path| path.is_dir()) {
+    if !contains_markdown_file(path)? {
+      continue;
+    }
+    let name = path
+      .file_name()
+      .map(|name| name.to_string_lossy().to_string())
+      .unwrap_or_default();
+    let view_id = match view_id_by_name.get(&name) {
+      Some(view_id) => *view_id,
+      None => {
+        let view_id = Uuid::new_v4();
+        pages.push(MarkdownPage {
+          view_id,
+          parent_view_id,
+          name,
+          file_path: None,
+        });
+        view_id
+      },
+    };
+    collect_pages_in_dir(path, view_id, pages)?;
+  }
+  Ok(())
+}
+
+fn contains_markdown_file(dir: &Path) -> Result<bool, ImportError> {
+  for path in read_sorted_dir(dir)? {
+    if is_markdown_file(&path) || (path.is_dir() && contains_markdown_file(&path)?) {
+      return Ok(true);
+    }
+  }
+  Ok(false)
+}
+
+fn read_sorted_dir(dir: &Path) -> Result<Vec<PathBuf>, ImportError> {
+  let mut paths = std::fs::read_dir(dir)
+    .map_err(|err| ImportError::Internal(err.into()))?
+    .filter_map(|entry| entry.ok())
+    .map(|entry| entry.path())
+    .filter(|path| {
+      let name = path.file_name().unwrap_or_default().to_string_lossy();
+      !name.starts_with('.') && name != "__MACOSX"
+    })
+    .collect::<Vec<_>>();
+  paths.sort();
+  Ok(paths)
+}
+
+fn is_markdown_file(path: &Path) -> bool {
+  path.is_file()
+    && path
+      .extension()
+      .map(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
+      .unwrap_or(false)
+}
+
+/// Resolves a link target relative to `base_dir`. Returns None for absolute urls, anchors and
+/// absolute paths.
+fn resolve_relative_path(base_dir: &Path, target: &str) -> Option<PathBuf> {
+  let target = target.split(['#', '?']).next().unwrap_or_default();
+  let is_url = target
+    .find(':')
+    .map(|index| !target[..index].contains('/'))
+    .unwrap_or(false);
+  if target.is_empty() || target.starts_with('/') || is_url {
+    return None;
+  }
+
+  let mut path = base_dir.to_path_buf();
+  for component in Path::new(&percent_decode(target)).components() {
+    match component {
+      Component::Normal(name) => path.push(name),
+      Component::ParentDir => {
+        path.pop();
+      },
+      _ => {},
+    }
+  }
+  Some(path)
+}
+
+/// Returns the canonical path of `path` if it is a file inside `canonical_root`. Links such as
+/// `../../etc/passwd`, or symlinks in the uploaded archive, must not leak files of the worker.
+fn resolve_file_in_root(canonical_root: &Path, path: &Path) -> Option<PathBuf> {
+  let path = std::fs::canonicalize(path).ok()?;
+  (path.starts_with(canonical_root) && path.is_file()).then_some(path)
+}
+
+fn percent_decode(value: &str) -> String {
+  let bytes = value.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut index = 0;
+  while index < bytes.len() {
+    let hex = bytes
+      .get(index + 1..index + 3)
+      .and_then(|hex| std::str::from_utf8(hex).ok())
+      .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+    match (bytes[index], hex) {
+      (b'%', Some(byte)) => {
+        decoded.push(byte);
+        index += 3;
+      },
+      (byte, _) => {
+        decoded.push(byte);
+        index += 1;
+      },
+    }
+  }
+  String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Returns the targets of the inline links and images in the Markdown text.
+fn markdown_link_targets(markdown: &str) -> Vec<&str> {
+  let mut targets = vec![];
+  let mut rest = markdown;
+  while let Some(link) = next_link_target(rest) {
+    targets.push(link.target);
+    rest = link.after;
+  }
+  targets
+}
+
+/// Replaces the target of every inline link and image in the Markdown text with the value
+/// returned by `resolve`. Targets for which `resolve` returns None are kept as they are.
+fn rewrite_markdown_links(
+  markdown: &str,
+  resolve: &mut dyn FnMut(&str) -> Option<String>,
+) -> String {
+  let mut output = String::with_capacity(markdown.len());
+  let mut rest = markdown;
+  while let Some(link) = next_link_target(rest) {
+    output.push_str(link.before);
+    match resolve(link.target) {
+      Some(url) if url.contains(' ') => output.push_str(&format!("<{}>", url)),
+      Some(url) => output.push_str(&url),
+      None => output.push_str(link.raw_target),
+    }
+    rest = link.after;
+  }
+  output.push_str(rest);
+  output
+}
+
+struct LinkTarget<'a> {
+  /// The text up to and including `](`.
+  before: &'a str,
+  /// The target without its angle brackets.
+  target: &'a str,
+  /// The target as written in the text.
+  raw_target: &'a str,
+  /// The text after the target, starting with the optional title.
+  after: &'a str,
+}
+
+/// Finds the next `](target)` in the text.
+fn next_link_target(text: &str) -> Option<LinkTarget<'_>> {
+  let start = text.find("](")? + 2;
+  let rest = &text[start..];
+  let (target, end) = match rest.strip_prefix('<') {
+    Some(inner) => {
+      let end = inner.find('>')?;
+      (&inner[..end], end + 2)
+    },
+    None => {
+      let end = rest.find(|c: char| c == ')' || c.is_whitespace())?;
+      (&rest[..end], end)
+    },
+  };
+  Some(LinkTarget {
+    before: &text[..start],
+    target,
+    raw_target: &rest[..end],
+    after: &rest[end..],
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn rewrite_relative_links_and_images() {
+    let markdown = "See [other](Other%20Page.md) and [site](https://appflowy.io).\n\
+                    ![image](<images/a b.png> \"title\")\n\
+                    [anchor](#top)";
+    let mut targets = vec![];
+    let output = rewrite_markdown_links(markdown, &mut |target| {
+      targets.push(target.to_string());
+      let path = resolve_relative_path(Path::new("/import/docs"), target)?;
+      Some(format!("local:{}", path.display()))
+    });
+
+    assert_eq!(
+      targets,
+      vec![
+        "Other%20Page.md",
+        "https://appflowy.io",
+        "images/a b.png",
+        "#top"
+      ]
+    );
+    assert_eq!(
+      output,
+      "See [other](<local:/import/docs/Other Page.md>) and [site](https://appflowy.io).\n\
+       ![image](<local:/import/docs/images/a b.png> \"title\")\n\
+       [anchor](#top)"
+    );
+  }
+
+  #[test]
+  fn resolve_parent_directory_links() {
+    let base_dir = Path::new("/import/docs/guides");
+    assert_eq!(
+      resolve_relative_path(base_dir, "../README.md#install"),
+      Some(PathBuf::from("/import/docs/README.md"))
+    );
+    assert_eq!(
+      resolve_relative_path(base_dir, "./img/logo.png?raw=true"),
+      Some(PathBuf::from("/import/docs/guides/img/logo.png"))
+    );
+    assert_eq!(resolve_relative_path(base_dir, "mailto:a@b.c"), None);
+    assert_eq!(resolve_relative_path(base_dir, "/absolute.md"), None);
+  }
+
+  #[test]
+  #[cfg(unix)]
+  fn reject_files_outside_of_import_root() {
+    let dir = std::env::temp_dir().join(format!("markdown_import_{}", Uuid::new_v4()));
+    let root = dir.join("import");
+    std::fs::create_dir_all(root.join("docs")).unwrap();
+    std::fs::write(root.join("docs/logo.png"), [0u8; 4]).unwrap();
+    std::fs::write(dir.join("secret.txt"), "secret").unwrap();
+    std::os::unix::fs::symlink(dir.join("secret.txt"), root.join("docs/link.png")).unwrap();
+    let canonical_root = std::fs::canonicalize(&root).unwrap();
+    let base_dir = root.join("docs");
+
+    let resolve = |target: &str| {
+      let path = resolve_relative_path(&base_dir, target)?;
+      resolve_file_in_root(&canonical_root, &path)
+    };
+    let logo = resolve("logo.png");
+    let outside = resolve("../../secret.txt");
+    let encoded_outside = resolve("..%2F..%2Fsecret.txt");
+    let symlink = resolve("link.png");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(logo, Some(canonical_root.join("docs/logo.png")));
+    assert_eq!(outside, None);
+    assert_eq!(encoded_outside, None);
+    assert_eq!(symlink, None);
+  }
+
+  #[test]
+  fn nest_directory_under_markdown_file_with_same_name() {
+    let root = std::env::temp_dir().join(format!("markdown_import_{}", Uuid::new_v4()));
+    let dir = root.join("export");
+    std::fs::create_dir_all(dir.join("Guide/Nested")).unwrap();
+    std::fs::create_dir_all(dir.join("assets")).unwrap();
+    std::fs::write(dir.join("Guide.md"), "# Guide").unwrap();
+    std::fs::write(dir.join("Guide/Install.md"), "# Install").unwrap();
+    std::fs::write(dir.join("Guide/Nested/Deep.md"), "# Deep").unwrap();
+    std::fs::write(dir.join("assets/logo.png"), [0u8; 4]).unwrap();
+
+    let space_id = Uuid::new_v4();
+    let pages = collect_markdown_pages(&root, space_id).unwrap();
+    std::fs::remove_dir_all(&root).unwrap();
+
+    let names = pages
+      .iter()
+      .map(|page| page.name.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Guide", "Install", "Nested", "Deep"]);
+    assert_eq!(pages[0].parent_view_id, space_id);
+    assert_eq!(pages[1].parent_view_id, pages[0].view_id);
+    assert_eq!(pages[2].parent_view_id, pages[0].view_id);
+    assert!(pages[2].file_path.is_none());
+    assert_eq!(pages[3].parent_view_id, pages[2].view_id);
+  }
+}
@@ -1,3 +1,5 @@
+pub mod csv_importer;
 pub mod email_notifier;
+pub mod markdown_importer;
 pub mod report;
 pub mod worker;
@@ -1,6 +1,11 @@
+use crate::import_worker::csv_importer::import_csv_file;
+use crate::import_worker::markdown_importer::import_markdown_files;
 use crate::import_worker::report::{ImportNotifier, ImportProgress, ImportResult};
-use crate::s3_client::{download_file, AutoRemoveDownloadedFile, S3StreamResponse};
+use crate::s3_client::{
+  download_file, write_stream_to_file, AutoRemoveDownloadedFile, S3StreamResponse,
+};
 use anyhow::anyhow;
+use appflowy_proto::WorkspaceNotification;
 use aws_sdk_s3::primitives::ByteStream;
 
 use crate::error::{ImportError, WorkerError};
@@ -24,7 +29,7 @@ use database::workspace::{
   update_import_task_status, update_updated_at_of_workspace_with_uid, update_workspace_status,
   ImportTaskState,
 };
-use database_entity::dto::CollabParams;
+use database_entity::dto::{CollabParams, ImportSource};
 
 use crate::metric::ImportMetrics;
 use async_zip::base::read::stream::{Ready, ZipFileReader};
@@ -42,7 +47,8 @@ use redis::streams::{
 use redis::{AsyncCommands, RedisResult, Value};
 
 use collab::core::collab::default_client_id;
-use database::pg_row::AFImportTask;
+use database::notification::notify_workspace_sessions;
+use database::pg_row::{AFImportTask, AFWorkspaceNotification};
 use serde::{Deserialize, Serialize};
 use serde_json::from_str;
 use sqlx::types::chrono::{DateTime, TimeZone, Utc};
@@ -274,7 +280,7 @@ async fn consume_task(
   group_name: &str,
   entry_id: String,
 ) -> Result<(), ImportError> {
-  if let ImportTask::Notion(task) = &mut import_task {
+  if let Some(task) = import_task.file_task_mut() {
     // If no created_at timestamp, proceed directly to processing
     if task.created_at.is_none() {
       return process_and_ack_task(context, import_task, stream_name, group_name, &entry_id).await;
@@ -357,7 +363,7 @@ async fn consume_task(
       Ok(())
     }
   } else {
-    // If the task is not a notion task, proceed directly to processing
+    // If the task doesn't import an uploaded file, proceed directly to processing
     process_and_ack_task(context, import_task, stream_name, group_name, &entry_id).await
   }
 }
@@ -400,7 +406,14 @@ async fn handle_failed_task(
       task.workspace_id, err
     );
   }
-  notify_user(task, Err(error), context.notifier.clone(), &context.metrics).await?;
+  notify_user(
+    task,
+    Err(error),
+    context.notifier.clone(),
+    &context.pg_pool,
+    &context.metrics,
+  )
+  .await?;
   Ok(())
 }
 
@@ -549,76 +562,10 @@ async fn process_task(
 
   info!("[Import]: Processing task: {}", import_task);
 
-  match import_task {
-    ImportTask::Notion(task) => {
-      // 1. download zip file
-      let unzip_result = download_and_unzip_file_retry(
-        &context.storage_dir,
-        &task,
-        &context.s3_client,
-        3,
-        Duration::from_secs(retry_interval),
-        streaming,
-        &context.metrics,
-      )
-      .await;
-
-      trace!(
-        "[Import]: {} download and unzip file result: {:?}",
-        task.workspace_id,
-        unzip_result
-      );
-      match unzip_result {
-        Ok(unzip_dir_path) => {
-          // 2. process unzip file
-          let result = process_unzip_file(
-            &task,
-            &unzip_dir_path,
-            &context.pg_pool,
-            &mut context.redis_client,
-            &context.s3_client,
-          )
-          .await;
-
-          // If there is any errors when processing the unzip file, we will remove the workspace and notify the user.
-          if result.is_err() {
-            info!(
-              "[Import]: failed to import notion file, delete workspace:{}",
-              task.workspace_id
-            );
-            remove_workspace(&task.workspace_id, &context.pg_pool).await;
-          }
-
-          clean_up(&context.s3_client, &task).await;
-          notify_user(&task, result, context.notifier, &context.metrics).await?;
-
-          tokio::spawn(async move {
-            match fs::remove_dir_all(&unzip_dir_path).await {
-              Ok(_) => info!(
-                "[Import]: {} deleted unzip file: {:?}",
-                task.workspace_id, unzip_dir_path
-              ),
-              Err(err) => {
-                if err.kind() != ErrorKind::NotFound {
-                  error!("Failed to delete unzip file: {:?}", err);
-                }
-              },
-            }
-          });
-        },
-        Err(err) => {
-          // If there is any errors when download or unzip the file, we will remove the file from S3 and notify the user.
-          if let Err(err) = &context.s3_client.delete_blob(task.s3_key.as_str()).await {
-            error!("Failed to delete zip file from S3: {:?}", err);
-          }
-          remove_workspace(&task.workspace_id, &context.pg_pool).await;
-          clean_up(&context.s3_client, &task).await;
-          notify_user(&task, Err(err), context.notifier, &context.metrics).await?;
-        },
-      }
-
-      Ok(())
-    },
+  let (source, task) = match import_task {
+    ImportTask::Notion(task) => (ImportSource::Notion, task),
+    ImportTask::Markdown(task) => (ImportSource::Markdown, task),
+    ImportTask::Csv(task) => (ImportSource::Csv, task),
     ImportTask::Custom(value) => {
       trace!("Custom task: {:?}", value);
       let result = ImportResult {
@@ -631,18 +578,119 @@ async fn process_task(
         .notifier
         .notify_progress(ImportProgress::Finished(result))
         .await;
-      Ok(())
+      return Ok(());
+    },
+  };
+
+  // 1. download zip file
+  let unzip_result = download_and_unzip_file_retry(
+    &context.storage_dir,
+    &task,
+    source,
+    &context.s3_client,
+    3,
+    Duration::from_secs(retry_interval),
+    streaming,
+    &context.metrics,
+  )
+  .await;
+
+  trace!(
+    "[Import]: {} download and unzip file result: {:?}",
+    task.workspace_id,
+    unzip_result
+  );
+  match unzip_result {
+    Ok(unzip_dir_path) => {
+      // 2. process unzip file
+      let result = match source {
+        ImportSource::Notion => {
+          process_unzip_file(
+            &task,
+            &unzip_dir_path,
+            &context.pg_pool,
+            &mut context.redis_client,
+            &context.s3_client,
+          )
+          .await
+        },
+        ImportSource::Markdown | ImportSource::Csv => {
+          process_unzip_files_with_importer(
+            &task,
+            source,
+            &unzip_dir_path,
+            &context.pg_pool,
+            &mut context.redis_client,
+            &context.s3_client,
+          )
+          .await
+        },
+      };
+
+      // If there is any errors when processing the unzip file, we will remove the workspace and notify the user.
+      if result.is_err() {
+        info!(
+          "[Import]: failed to import {:?} file, delete workspace:{}",
+          source, task.workspace_id
+        );
+        remove_workspace(&task.workspace_id, &context.pg_pool).await;
+      }
+
+      clean_up(&context.s3_client, &task).await;
+      notify_user(
+        &task,
+        result,
+        context.notifier,
+        &context.pg_pool,
+        &context.metrics,
+      )
+      .await?;
+
+      tokio::spawn(async move {
+        match fs::remove_dir_all(&unzip_dir_path).await {
+          Ok(_) => info!(
+            "[Import]: {} deleted unzip file: {:?}",
+            task.workspace_id, unzip_dir_path
+          ),
+          Err(err) => {
+            if err.kind() != ErrorKind::NotFound {
+              error!("Failed to delete unzip file: {:?}", err);
+            }
+          },
+        }
+      });
+    },
+    Err(err) => {
+      // If there is any errors when download or unzip the file, we will remove the file from S3 and notify the user.
+      if let Err(err) = &context.s3_client.delete_blob(task.s3_key.as_str()).await {
+        error!("Failed to delete zip file from S3: {:?}", err);
+      }
+      remove_workspace(&task.workspace_id, &context.pg_pool).await;
+      clean_up(&context.s3_client, &task).await;
+      notify_user(
+        &task,
+        Err(err),
+        context.notifier,
+        &context.pg_pool,
+        &context.metrics,
+      )
+      .await?;
     },
   }
+
+  Ok(())
 }
+
 /// Retries the download and unzipping of a file from an S3 source.
 ///
 /// This function attempts to download a zip file from an S3 bucket and unzip it to a local directory.
 /// If the operation fails, it will retry up to `max_retries` times, waiting for `interval` between each attempt.
 ///
-pub async fn download_and_unzip_file_retry(
+#[allow(clippy::too_many_arguments)]
+async fn download_and_unzip_file_retry(
   storage_dir: &Path,
   import_task: &NotionImportTask,
+  source: ImportSource,
   s3_client: &Arc<dyn S3Client>,
   max_retries: usize,
   interval: Duration,
@@ -652,7 +700,16 @@ pub async fn download_and_unzip_file_retry(
   let mut attempt = 0;
   loop {
     attempt += 1;
-    match download_and_unzip_file(storage_dir, import_task, s3_client, streaming, metrics).await {
+    match download_and_unzip_file(
+      storage_dir,
+      import_task,
+      source,
+      s3_client,
+      streaming,
+      metrics,
+    )
+    .await
+    {
       Ok(result) => return Ok(result),
       Err(err) => {
         // If the Upload file not found error occurs, we will not retry.
@@ -685,6 +742,7 @@ pub async fn download_and_unzip_file_retry(
 async fn download_and_unzip_file(
   storage_dir: &Path,
   import_task: &NotionImportTask,
+  source: ImportSource,
   s3_client: &Arc<dyn S3Client>,
   streaming: bool,
   metrics: &Option<Arc<ImportMetrics>>,
@@ -704,8 +762,19 @@ async fn download_and_unzip_file(
         "multipart/x-zip",
         "application/x-compressed",
       ];
+      let valid_csv_types = [
+        "text/csv",
+        "application/csv",
+        "text/plain",
+        "application/vnd.ms-excel",
+        "application/octet-stream",
+      ];
+      let valid_types: &[&str] = match source {
+        ImportSource::Csv => &valid_csv_types,
+        ImportSource::Notion | ImportSource::Markdown => &valid_zip_types,
+      };
 
-      if !valid_zip_types.contains(&content_type.as_str()) {
+      if !valid_types.contains(&content_type.as_str()) {
         return Err(ImportError::Internal(anyhow!(
           "Invalid content type: {}",
           content_type
@@ -747,6 +816,18 @@ async fn download_and_unzip_file(
   if let Some(metrics) = metrics {
     metrics.record_import_size_bytes(buffer_size);
   }
+
+  // A CSV file is not compressed. It is written into its own directory so that it can be
+  // cleaned up the same way as an unzipped directory.
+  if source == ImportSource::Csv {
+    let output_dir_path = storage_dir.join(Uuid::new_v4().to_string());
+    fs::create_dir_all(&output_dir_path)
+      .await
+      .map_err(|err| ImportError::Internal(err.into()))?;
+    let file_path = output_dir_path.join(format!("{}.csv", Uuid::new_v4()));
+    write_stream_to_file(&file_path, &import_task.md5_base64, stream).await?;
+    return Ok(output_dir_path);
+  }
   if streaming {
     let zip_reader = get_zip_reader(buffer_size, StreamOrFile::Stream(stream)).await?;
     let unique_file_name = Uuid::new_v4().to_string();
@@ -873,7 +954,6 @@ async fn process_unzip_file(
   redis_client: &mut ConnectionManager,
   s3_client: &Arc<dyn S3Client>,
 ) -> Result<(), ImportError> {
-  let client_id = default_client_id();
   let _ =
     Uuid::parse_str(&import_task.workspace_id).map_err(|err| ImportError::Internal(err.into()))?;
   let notion_importer = NotionImporter::new(
@@ -901,21 +981,7 @@ async fn process_unzip_file(
 
   // 1. Open the workspace folder
   let workspace_id = Uuid::parse_str(&imported.workspace_id)?;
-  let folder_collab = get_encode_collab_from_bytes(
-    &workspace_id,
-    &workspace_id,
-    &CollabType::Folder,
-    pg_pool,
-    s3_client,
-  )
-  .await?;
-  let mut folder = Folder::from_collab_doc_state(
-    CollabOrigin::Server,
-    folder_collab.into(),
-    &imported.workspace_id,
-    client_id,
-  )
-  .map_err(|err| ImportError::CannotOpenWorkspace(err.to_string()))?;
+  let mut folder = open_workspace_folder(&workspace_id, pg_pool, s3_client).await?;
 
   // 2. Insert collabs' views into the folder
   trace!(
@@ -925,10 +991,7 @@ async fn process_unzip_file(
   );
   folder.insert_nested_views(nested_views.into_inner(), import_task.uid);
 
-  let mut resources = vec![];
-  let mut collab_params_list = vec![];
-  let mut database_view_ids_by_database_id: HashMap<String, Vec<String>> = HashMap::new();
-  let mut orphan_view_ids = HashSet::new();
+  let mut data = ImportedWorkspaceData::default();
 
   // 3. Collect all collabs and resources
   let mut stream = imported.into_collab_stream().await;
@@ -939,8 +1002,8 @@ async fn process_unzip_file(
       import_task.workspace_id,
       imported_collab_info
     );
-    resources.extend(imported_collab_info.resources);
-    collab_params_list.extend(
+    data.resources.extend(imported_collab_info.resources);
+    data.collab_params_list.extend(
       imported_collab_info
         .imported_collabs
         .into_iter()
@@ -959,8 +1022,10 @@ async fn process_unzip_file(
         view_ids,
         row_document_ids,
       } => {
-        database_view_ids_by_database_id.insert(database_id, view_ids);
-        orphan_view_ids.extend(row_document_ids);
+        data
+          .database_view_ids_by_database_id
+          .insert(database_id, view_ids);
+        data.orphan_view_ids.extend(row_document_ids);
       },
       ImportType::Document => {
         // do nothing
@@ -968,6 +1033,107 @@ async fn process_unzip_file(
     }
   }
 
+  save_imported_data(import_task, folder, data, pg_pool, redis_client, s3_client).await
+}
+
+/// Imports the Markdown files or the CSV file found in the given directory.
+async fn process_unzip_files_with_importer(
+  import_task: &NotionImportTask,
+  source: ImportSource,
+  unzip_dir_path: &Path,
+  pg_pool: &PgPool,
+  redis_client: &mut ConnectionManager,
+  s3_client: &Arc<dyn S3Client>,
+) -> Result<(), ImportError> {
+  let workspace_id = Uuid::parse_str(&import_task.workspace_id)?;
+  trace!(
+    "[Import]: {} start import {:?} data",
+    import_task.workspace_id,
+    source
+  );
+  let data = match source {
+    ImportSource::Csv => {
+      import_csv_file(
+        import_task.uid,
+        &workspace_id,
+        &import_task.workspace_name,
+        unzip_dir_path,
+      )
+      .await?
+    },
+    _ => {
+      import_markdown_files(
+        import_task.uid,
+        &workspace_id,
+        &import_task.host,
+        unzip_dir_path,
+      )
+      .await?
+    },
+  };
+
+  let folder = open_workspace_folder(&workspace_id, pg_pool, s3_client).await?;
+  save_imported_data(import_task, folder, data, pg_pool, redis_client, s3_client).await
+}
+
+/// Views, collabs and files produced by an importer. They are written into the imported
+/// workspace by [save_imported_data].
+#[derive(Default)]
+pub(crate) struct ImportedWorkspaceData {
+  /// Views to insert into the folder. A parent view must come before its children.
+  pub views: Vec<View>,
+  pub collab_params_list: Vec<CollabParams>,
+  pub resources: Vec<CollabResource>,
+  pub database_view_ids_by_database_id: HashMap<String, Vec<String>>,
+  pub orphan_view_ids: HashSet<String>,
+}
+
+async fn open_workspace_folder(
+  workspace_id: &Uuid,
+  pg_pool: &PgPool,
+  s3_client: &Arc<dyn S3Client>,
+) -> Result<Folder, ImportError> {
+  let folder_collab = get_encode_collab_from_bytes(
+    workspace_id,
+    workspace_id,
+    &CollabType::Folder,
+    pg_pool,
+    s3_client,
+  )
+  .await?;
+  Folder::from_collab_doc_state(
+    CollabOrigin::Server,
+    folder_collab.into(),
+    &workspace_id.to_string(),
+    default_client_id(),
+  )
+  .map_err(|err| ImportError::CannotOpenWorkspace(err.to_string()))
+}
+
+/// Writes the imported views, collabs and files into the workspace, marks the import task as
+/// completed and uploads the files to S3.
+async fn save_imported_data(
+  import_task: &NotionImportTask,
+  mut folder: Folder,
+  data: ImportedWorkspaceData,
+  pg_pool: &PgPool,
+  redis_client: &mut ConnectionManager,
+  s3_client: &Arc<dyn S3Client>,
+) -> Result<(), ImportError> {
+  let client_id = default_client_id();
+  let workspace_id = Uuid::parse_str(&import_task.workspace_id)?;
+  let ImportedWorkspaceData {
+    views,
+    mut collab_params_list,
+    resources,
+    database_view_ids_by_database_id,
+    orphan_view_ids,
+  } = data;
+  let updated_at = Utc::now();
+  if !views.is_empty() {
+    folder.insert_views(views, import_task.uid);
+  }
+
   let w_database_id = select_workspace_database_storage_id(pg_pool, &import_task.workspace_id)
     .await
     .map_err(|err| {
@@ -1229,6 +1395,7 @@ async fn notify_user(
   import_task: &NotionImportTask,
   result: Result<(), ImportError>,
   notifier: Arc<dyn ImportNotifier>,
+  pg_pool: &PgPool,
   metrics: &Option<Arc<ImportMetrics>>,
 ) -> Result<(), ImportError> {
   let task_id = import_task.task_id.to_string();
@@ -1254,6 +1421,7 @@ async fn notify_user(
   };
 
   let is_success = error.is_none();
+  notify_user_sessions(import_task, is_success, pg_pool).await;
 
   let value = serde_json::to_value(ImportNotionMailerParam {
     import_task_id: task_id,
@@ -1278,6 +1446,29 @@ async fn notify_user(
   Ok(())
 }
 
+/// Lets the connected clients of the user know that the import finished, whichever workspace
+/// they are connected to.
+async fn notify_user_sessions(import_task: &NotionImportTask, is_success: bool, pg_pool: &PgPool) {
+  let Ok(workspace_id) = Uuid::from_str(&import_task.workspace_id) else {
+    return;
+  };
+  let notification = AFWorkspaceNotification {
+    workspace_id: None,
+    uid: Some(import_task.uid),
+    notification: WorkspaceNotification::ImportTaskFinished {
+      task_id: import_task.task_id,
+      workspace_id,
+      success: is_success,
+    },
+  };
+  if let Err(err) = notify_workspace_sessions(pg_pool, &notification).await {
+    error!(
+      "[Import]: {} failed to notify user sessions: {:?}",
+      import_task.workspace_id, err
+    );
+  }
+}
+
 async fn batch_upload_files_to_s3(
   workspace_id: &str,
   client: &Arc<dyn S3Client>,
@@ -1354,7 +1545,7 @@ async fn upload_file_to_s3(
   ))
 }
 
-async fn get_encode_collab_from_bytes(
+pub(crate) async fn get_encode_collab_from_bytes(
   workspace_id: &Uuid,
   object_id: &Uuid,
   collab_type: &CollabType,
@@ -1393,7 +1584,7 @@ async fn get_encode_collab_from_bytes(
 }
 
 /// Ensure the consumer group exists, if not, create it.
-async fn ensure_consumer_group(
+pub(crate) async fn ensure_consumer_group(
   stream_key: &str,
   group_name: &str,
   redis_client: &mut ConnectionManager,
@@ -1497,14 +1688,30 @@ impl Display for NotionImportTask {
   }
 }
 
+/// The Markdown and CSV imports share the payload of the Notion import. Only the way the uploaded
+/// file is turned into collabs differs.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum ImportTask {
   // boxing the large fields to reduce the total size of the enum
   Notion(Box<NotionImportTask>),
+  /// A zip of Markdown files. The folder structure becomes the view hierarchy.
+  Markdown(Box<NotionImportTask>),
+  /// A single CSV file that becomes a grid database.
+  Csv(Box<NotionImportTask>),
   Custom(serde_json::Value),
 }
 
+impl ImportTask {
+  /// Returns the payload of the tasks that import an uploaded file.
+  fn file_task_mut(&mut self) -> Option<&mut NotionImportTask> {
+    match self {
+      ImportTask::Notion(task) | ImportTask::Markdown(task) | ImportTask::Csv(task) => Some(task),
+      ImportTask::Custom(_) => None,
+    }
+  }
+}
+
 impl Display for ImportTask {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
@@ -1513,6 +1720,16 @@ impl Display for ImportTask {
         "NotionImportTask {{ workspace_id: {}, workspace_name: {} }}",
         task.workspace_id, task.workspace_name
       ),
+      ImportTask::Markdown(task) => write!(
+        f,
+        "MarkdownImportTask {{ workspace_id: {}, workspace_name: {} }}",
+        task.workspace_id, task.workspace_name
+      ),
+      ImportTask::Csv(task) => write!(
+        f,
+        "CsvImportTask {{ workspace_id: {}, workspace_name: {} }}",
+        task.workspace_id, task.workspace_name
+      ),
       ImportTask::Custom(value) => write!(f, "CustomTask {{ {} }}", value),
     }
   }
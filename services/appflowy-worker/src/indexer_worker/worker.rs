@@ -9,8 +9,7 @@ use indexer::queue::{
   read_background_embed_tasks,
 };
 use indexer::scheduler::{spawn_pg_write_embeddings, UnindexedCollabTask, UnindexedData};
-use indexer::vector::embedder::{AFEmbedder, AzureConfig, OpenAIConfig};
-use indexer::vector::open_ai;
+use indexer::vector::embedder::{AFEmbedder, EmbedderConfig};
 use redis::aio::ConnectionManager;
 use sqlx::PgPool;
 use std::sync::Arc;
@@ -23,8 +22,7 @@ use tracing::{error, info, trace, warn};
 
 pub struct BackgroundIndexerConfig {
   pub enable: bool,
-  pub open_ai_config: Option<OpenAIConfig>,
-  pub azure_ai_config: Option<AzureConfig>,
+  pub embedder_config: Option<EmbedderConfig>,
   pub tick_interval_secs: u64,
 }
 
@@ -39,8 +37,8 @@ pub async fn run_background_indexer(
     return;
   }
 
-  if config.open_ai_config.is_none() && config.azure_ai_config.is_none() {
-    error!("OpenAI API key is not set. Stop background indexer");
+  if config.embedder_config.is_none() {
+    error!("Embedding provider is not configured. Stop background indexer");
     return;
   }
 
@@ -150,9 +148,14 @@ async fn process_upcoming_tasks(
 
           let start = Instant::now();
           let num_tasks = tasks.len();
-          let existing_embeddings = get_collab_embedding_fragment_ids(&pg_pool, collab_ids)
-            .await
-            .unwrap_or_default();
+          let existing_embeddings = match create_embedder(&config) {
+            Ok(embedder) => {
+              get_collab_embedding_fragment_ids(&pg_pool, collab_ids, embedder.model().name())
+                .await
+                .unwrap_or_default()
+            },
+            Err(_) => Default::default(),
+          };
           let mut join_set = JoinSet::new();
           for task in tasks {
             if let Some(indexer) = indexer_provider.indexer_for(task.collab_type) {
@@ -170,7 +173,7 @@ async fn process_upcoming_tasks(
                 let mut chunks = match indexer.create_embedded_chunks_from_text(
                   task.object_id,
                   paragraphs,
-                  embedder.model(),
+                  &embedder.model(),
                 ) {
                   Ok(chunks) => chunks,
                   Err(err) => {
@@ -197,6 +200,7 @@ async fn process_upcoming_tasks(
                     object_id: task.object_id,
                     collab_type: task.collab_type,
                     tokens_used: embeddings.tokens_consumed,
+                    embedding_model: embedder.model().name().to_string(),
                     chunks: embeddings.chunks,
                   }))
                 });
@@ -255,19 +259,9 @@ async fn process_upcoming_tasks(
 }
 
 fn create_embedder(config: &BackgroundIndexerConfig) -> Result<AFEmbedder, AppError> {
-  if let Some(config) = &config.azure_ai_config {
-    return Ok(AFEmbedder::AzureOpenAI(open_ai::AzureOpenAIEmbedder::new(
-      config.clone(),
-    )));
-  }
-
-  if let Some(config) = &config.open_ai_config {
-    return Ok(AFEmbedder::OpenAI(open_ai::OpenAIEmbedder::new(
-      config.clone(),
-    )));
-  }
-
-  Err(AppError::AIServiceUnavailable(
-    "No embedder available".to_string(),
-  ))
+  config
+    .embedder_config
+    .as_ref()
+    .map(|config| config.create_embedder())
+    .ok_or_else(|| AppError::AIServiceUnavailable("No embedder available".to_string()))
 }
@@ -1,4 +1,6 @@
+pub mod attachment_worker;
 pub mod error;
+pub mod export_worker;
 pub mod import_worker;
 pub mod indexer_worker;
 mod mailer;
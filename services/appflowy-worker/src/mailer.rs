@@ -3,6 +3,8 @@ use std::ops::Deref;
 
 pub const IMPORT_SUCCESS_TEMPLATE: &str = "import_notion_success";
 pub const IMPORT_FAIL_TEMPLATE: &str = "import_notion_fail";
+pub const EXPORT_SUCCESS_TEMPLATE: &str = "export_workspace_success";
+pub const EXPORT_FAIL_TEMPLATE: &str = "export_workspace_fail";
 #[derive(Clone)]
 pub struct AFWorkerMailer(Mailer);
 
@@ -22,9 +24,17 @@ impl AFWorkerMailer {
     let import_data_fail =
       include_str!("../../../assets/mailer_templates/build_production/import_data_fail.html");
 
+    let export_data_success =
+      include_str!("../../../assets/mailer_templates/build_production/export_data_success.html");
+
+    let export_data_fail =
+      include_str!("../../../assets/mailer_templates/build_production/export_data_fail.html");
+
     for (name, template) in [
       (IMPORT_SUCCESS_TEMPLATE, import_data_success),
       (IMPORT_FAIL_TEMPLATE, import_data_fail),
+      (EXPORT_SUCCESS_TEMPLATE, export_data_success),
+      (EXPORT_FAIL_TEMPLATE, export_data_fail),
     ] {
       mailer
         .register_template(name, template)
@@ -50,6 +60,16 @@ pub struct ImportNotionMailerParam {
   pub error_detail: Option<String>,
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExportWorkspaceMailerParam {
+  pub export_task_id: String,
+  pub user_name: String,
+  pub workspace_id: String,
+  pub workspace_name: String,
+  pub download_url: Option<String>,
+  pub error: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
   use crate::mailer::{AFWorkerMailer, ImportNotionMailerParam, IMPORT_SUCCESS_TEMPLATE};
@@ -1,6 +1,8 @@
 mod application;
+mod attachment_worker;
 mod config;
 pub mod error;
+pub mod export_worker;
 pub mod import_worker;
 pub(crate) mod s3_client;
 
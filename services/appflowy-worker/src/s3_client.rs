@@ -1,11 +1,16 @@
 use crate::error::WorkerError;
 use anyhow::{anyhow, Context};
+use app_error::AppError;
 use aws_sdk_s3::error::SdkError;
+use database::file::fs_client_impl::FsBucketClientImpl;
+use database::file::BucketClient;
 use std::fs::Permissions;
+use std::io::ErrorKind;
 
 use anyhow::Result;
 use aws_sdk_s3::operation::get_object::GetObjectError;
 use aws_sdk_s3::operation::head_object::{HeadObjectError, HeadObjectOutput};
+use aws_sdk_s3::presigning::PresigningConfig;
 use aws_sdk_s3::primitives::ByteStream;
 use axum::async_trait;
 use base64::engine::general_purpose::STANDARD;
@@ -14,9 +19,10 @@ use futures::AsyncReadExt;
 use std::ops::Deref;
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
 use tokio::fs::OpenOptions;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncWriteExt, BufReader};
 use tokio_util::compat::TokioAsyncReadCompatExt;
 use tracing::{error, trace};
 use uuid::Uuid;
@@ -34,6 +40,12 @@ pub trait S3Client: Send + Sync {
 
   async fn is_blob_exist(&self, object_key: &str) -> Result<bool, WorkerError>;
   async fn get_blob_meta(&self, object_key: &str) -> Result<BlobMeta, WorkerError>;
+  /// Returns a url that allows downloading the blob without authentication until it expires.
+  async fn gen_presigned_download_url(
+    &self,
+    object_key: &str,
+    expires_in_secs: u64,
+  ) -> Result<String, WorkerError>;
 }
 
 pub struct BlobMeta {
@@ -45,9 +57,20 @@ pub struct BlobMeta {
 pub struct S3ClientImpl {
   pub inner: aws_sdk_s3::Client,
   pub bucket: String,
+  pub endpoint: String,
+  pub presigned_url_endpoint: Option<String>,
 }
 
 impl S3ClientImpl {
+  /// Rewrites the internal S3 endpoint of a presigned url to the public one, the same way the
+  /// AppFlowy Cloud server does for its presigned urls.
+  fn public_presigned_url(&self, url: String) -> String {
+    match &self.presigned_url_endpoint {
+      Some(presigned) => url.replace(&self.endpoint, presigned),
+      None => url,
+    }
+  }
+
   async fn get_head_object(&self, object_key: &str) -> Result<HeadObjectOutput, WorkerError> {
     self
       .inner
@@ -198,6 +221,129 @@ impl S3Client for S3ClientImpl {
       content_type,
     })
   }
+
+  async fn gen_presigned_download_url(
+    &self,
+    object_key: &str,
+    expires_in_secs: u64,
+  ) -> Result<String, WorkerError> {
+    let config = PresigningConfig::expires_in(Duration::from_secs(expires_in_secs))
+      .map_err(|err| WorkerError::from(anyhow!("Invalid presigning config: {}", err)))?;
+    let request = self
+      .inner
+      .get_object()
+      .bucket(&self.bucket)
+      .key(object_key)
+      .presigned(config)
+      .await
+      .map_err(|err| WorkerError::from(anyhow!("Failed to presign object: {}", err)))?;
+    Ok(self.public_presigned_url(request.uri().to_string()))
+  }
+}
+
+/// Reads and writes blobs in the same directory as the filesystem bucket storage backend of the
+/// AppFlowy Cloud server.
+#[derive(Clone)]
+pub struct FsS3ClientImpl {
+  pub inner: FsBucketClientImpl,
+}
+
+fn from_app_error(err: AppError) -> WorkerError {
+  match err {
+    AppError::RecordNotFound(msg) => WorkerError::RecordNotFound(msg),
+    err => WorkerError::Internal(anyhow!(err)),
+  }
+}
+
+#[async_trait]
+impl S3Client for FsS3ClientImpl {
+  async fn get_blob_stream(&self, object_key: &str) -> Result<S3StreamResponse, WorkerError> {
+    let path = self.inner.object_path(object_key).map_err(from_app_error)?;
+    let file = match fs::File::open(&path).await {
+      Ok(file) => file,
+      Err(err) if err.kind() == ErrorKind::NotFound => {
+        return Err(WorkerError::RecordNotFound(format!(
+          "blob not found for key:{object_key}"
+        )))
+      },
+      Err(err) => return Err(err.into()),
+    };
+    let content_length = file.metadata().await?.len() as i64;
+    let content_type = self
+      .inner
+      .content_type(object_key)
+      .await
+      .map_err(from_app_error)?;
+    trace!(
+      "get object from filesystem: {} ({} bytes)",
+      object_key,
+      content_length
+    );
+    Ok(S3StreamResponse {
+      stream: Box::new(BufReader::new(file).compat()),
+      content_type,
+      content_length: Some(content_length),
+    })
+  }
+
+  async fn put_blob(
+    &self,
+    object_key: &str,
+    content: ByteStream,
+    content_type: Option<&str>,
+  ) -> Result<(), WorkerError> {
+    self
+      .inner
+      .put_blob(object_key, content, content_type)
+      .await
+      .map_err(from_app_error)
+  }
+
+  async fn delete_blob(&self, object_key: &str) -> Result<(), WorkerError> {
+    self
+      .inner
+      .delete_blob(object_key)
+      .await
+      .map_err(from_app_error)?;
+    Ok(())
+  }
+
+  async fn is_blob_exist(&self, object_key: &str) -> Result<bool, WorkerError> {
+    let path = self.inner.object_path(object_key).map_err(from_app_error)?;
+    Ok(fs::try_exists(path).await?)
+  }
+
+  async fn get_blob_meta(&self, object_key: &str) -> Result<BlobMeta, WorkerError> {
+    let path = self.inner.object_path(object_key).map_err(from_app_error)?;
+    let metadata = match fs::metadata(&path).await {
+      Ok(metadata) => metadata,
+      Err(err) if err.kind() == ErrorKind::NotFound => {
+        return Err(WorkerError::RecordNotFound("blob not found".to_string()))
+      },
+      Err(err) => return Err(err.into()),
+    };
+    let content_type = self
+      .inner
+      .content_type(object_key)
+      .await
+      .map_err(from_app_error)?;
+    Ok(BlobMeta {
+      content_length: metadata.len() as i64,
+      content_type,
+    })
+  }
+
+  async fn gen_presigned_download_url(
+    &self,
+    object_key: &str,
+    expires_in_secs: u64,
+  ) -> Result<String, WorkerError> {
+    self
+      .inner
+      .gen_presigned_download_url(object_key, expires_in_secs)
+      .await
+      .map_err(from_app_error)
+  }
 }
 
 pub struct S3StreamResponse {
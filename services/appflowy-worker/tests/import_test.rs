@@ -228,6 +228,14 @@ impl S3Client for MockS3Client {
   async fn get_blob_meta(&self, _object_key: &str) -> Result<BlobMeta, WorkerError> {
     todo!()
   }
+
+  async fn gen_presigned_download_url(
+    &self,
+    _object_key: &str,
+    _expires_in_secs: u64,
+  ) -> Result<String, WorkerError> {
+    todo!()
+  }
 }
 
 pub fn setup_log() {
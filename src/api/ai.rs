@@ -1,6 +1,9 @@
 use crate::api::util::ai_model_from_header;
+use crate::biz::authentication::jwt::UserUuid;
+use crate::biz::chat::ops::filter_readable_rag_ids;
 use crate::state::AppState;
 
+use access_control::act::Action;
 use actix_web::web::{Data, Json};
 use actix_web::{web, HttpRequest, HttpResponse, Scope};
 use app_error::AppError;
@@ -18,6 +21,7 @@ use shared_entity::dto::ai_dto::{
 use shared_entity::response::AppResponse;
 
 use tracing::{error, instrument, trace};
+use uuid::Uuid;
 
 pub fn ai_completion_scope() -> Scope {
   web::scope("/api/ai/{workspace_id}")
@@ -33,12 +37,16 @@ pub fn ai_completion_scope() -> Scope {
 }
 
 async fn stream_complete_text_handler(
+  user_uuid: UserUuid,
+  path: web::Path<Uuid>,
   state: Data<AppState>,
   payload: Json<CompleteTextParams>,
   req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
   let ai_model = ai_model_from_header(&req);
-  let params = payload.into_inner();
+  let params =
+    authorize_completion_params(&state, &user_uuid, path.into_inner(), payload.into_inner())
+      .await?;
   state.metrics.ai_metrics.record_total_completion_count(1);
 
   if let Some(prompt_id) = params
@@ -73,12 +81,16 @@ async fn stream_complete_text_handler(
 }
 
 async fn stream_complete_v2_handler(
+  user_uuid: UserUuid,
+  path: web::Path<Uuid>,
   state: Data<AppState>,
   payload: Json<CompleteTextParams>,
   req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
   let ai_model = ai_model_from_header(&req);
-  let params = payload.into_inner();
+  let params =
+    authorize_completion_params(&state, &user_uuid, path.into_inner(), payload.into_inner())
+      .await?;
   state.metrics.ai_metrics.record_total_completion_count(1);
 
   match state.ai_client.stream_completion_v2(params, ai_model).await {
@@ -96,6 +108,30 @@ async fn stream_complete_v2_handler(
     ),
   }
 }
+
+/// Checks that the user can read the workspace of the path. The completion is then bound to that
+/// workspace, and the rag ids the user can't read are dropped from its metadata.
+async fn authorize_completion_params(
+  state: &AppState,
+  user_uuid: &UserUuid,
+  workspace_id: Uuid,
+  mut params: CompleteTextParams,
+) -> Result<CompleteTextParams, AppError> {
+  let uid = state.user_cache.get_user_uid(user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id, Action::Read)
+    .await?;
+  if let Some(metadata) = params.metadata.as_mut() {
+    metadata.workspace_id = Some(workspace_id);
+    if let Some(rag_ids) = metadata.rag_ids.take() {
+      metadata.rag_ids =
+        Some(filter_readable_rag_ids(&state.ws_server, uid, &workspace_id, rag_ids).await?);
+    }
+  }
+  Ok(params)
+}
+
 #[instrument(level = "debug", skip(state, payload), err)]
 async fn summarize_row_handler(
   state: Data<AppState>,
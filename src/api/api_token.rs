@@ -0,0 +1,47 @@
+use crate::biz::authentication::api_token::{create_api_token, list_api_tokens, revoke_api_token};
+use crate::biz::authentication::jwt::UserUuid;
+use crate::state::AppState;
+use actix_web::web::{Data, Json};
+use actix_web::{web, Result, Scope};
+use shared_entity::dto::api_token_dto::{AFApiToken, CreateApiTokenParams};
+use shared_entity::response::{AppResponse, JsonAppResponse};
+use uuid::Uuid;
+
+pub fn api_token_scope() -> Scope {
+  web::scope("/api/token")
+    .service(
+      web::resource("")
+        .route(web::get().to(list_api_tokens_handler))
+        .route(web::post().to(create_api_token_handler)),
+    )
+    .service(web::resource("/{token_id}").route(web::delete().to(revoke_api_token_handler)))
+}
+
+async fn create_api_token_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  payload: Json<CreateApiTokenParams>,
+) -> Result<JsonAppResponse<AFApiToken>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let token = create_api_token(&state, uid, payload.into_inner()).await?;
+  Ok(Json(AppResponse::Ok().with_data(token)))
+}
+
+async fn list_api_tokens_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<Vec<AFApiToken>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let tokens = list_api_tokens(&state.pg_pool, uid).await?;
+  Ok(Json(AppResponse::Ok().with_data(tokens)))
+}
+
+async fn revoke_api_token_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<Uuid>,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  revoke_api_token(&state.pg_pool, uid, &path.into_inner()).await?;
+  Ok(Json(AppResponse::Ok()))
+}
@@ -0,0 +1,61 @@
+use crate::biz::audit::ops::{export_audit_logs_csv, list_audit_logs};
+use crate::biz::authentication::jwt::UserUuid;
+use crate::state::AppState;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::web::{Data, Json};
+use actix_web::{web, HttpResponse, Result, Scope};
+use database_entity::dto::AFRole;
+use shared_entity::dto::audit_dto::{AFAuditLogEntry, QueryAuditLogParams};
+use shared_entity::response::{AppResponse, JsonAppResponse};
+use uuid::Uuid;
+
+pub fn audit_scope() -> Scope {
+  web::scope("/api/audit")
+    .service(web::resource("/{workspace_id}").route(web::get().to(list_audit_logs_handler)))
+    .service(
+      web::resource("/{workspace_id}/export").route(web::get().to(export_audit_logs_handler)),
+    )
+}
+
+async fn list_audit_logs_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<Uuid>,
+  query: web::Query<QueryAuditLogParams>,
+) -> Result<JsonAppResponse<Vec<AFAuditLogEntry>>> {
+  let workspace_id = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let entries = list_audit_logs(&state.pg_pool, &workspace_id, &query).await?;
+  Ok(Json(AppResponse::Ok().with_data(entries)))
+}
+
+async fn export_audit_logs_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<Uuid>,
+  query: web::Query<QueryAuditLogParams>,
+) -> Result<HttpResponse> {
+  let workspace_id = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let csv = export_audit_logs_csv(&state.pg_pool, &workspace_id, &query).await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("text/csv; charset=utf-8")
+      .insert_header(ContentDisposition {
+        disposition: DispositionType::Attachment,
+        parameters: vec![DispositionParam::Filename(format!(
+          "audit-log-{}.csv",
+          workspace_id
+        ))],
+      })
+      .body(csv),
+  )
+}
@@ -0,0 +1,91 @@
+use actix_web::http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use actix_web::web::{self, Data, Payload, Query};
+use actix_web::{HttpRequest, HttpResponse, Result, Scope};
+use app_error::AppError;
+use aws_sdk_s3::primitives::ByteStream;
+use database::file::fs_client_impl::{FsBucketClientImpl, PresignedQuery};
+use database::file::{BucketClient, ResponseBlob};
+use tokio_stream::StreamExt;
+use tracing::{instrument, trace};
+
+use crate::biz::data_import::LimitedPayload;
+use crate::state::AppState;
+
+/// Serves the presigned urls generated by the filesystem bucket storage backend. The requests
+/// are authorized by the signature in the query rather than by the user token.
+pub fn bucket_scope() -> Scope {
+  web::scope("/api/bucket").service(
+    web::resource("/{object_key:.*}")
+      .route(web::get().to(get_object_handler))
+      .route(web::put().to(put_object_handler)),
+  )
+}
+
+fn fs_bucket_client(state: &AppState) -> Result<&FsBucketClientImpl, AppError> {
+  state
+    .bucket_client
+    .as_fs()
+    .ok_or_else(|| AppError::RecordNotFound("bucket endpoint is not enabled".to_string()))
+}
+
+#[instrument(level = "debug", skip(state, query))]
+async fn get_object_handler(
+  state: Data<AppState>,
+  object_key: web::Path<String>,
+  query: Query<PresignedQuery>,
+) -> Result<HttpResponse> {
+  let client = fs_bucket_client(&state)?;
+  client.verify_presigned("GET", &object_key, &query)?;
+  let blob = client.get_blob(&object_key).await?;
+  let content_type = blob
+    .content_type()
+    .unwrap_or_else(|| "application/octet-stream".to_string());
+  Ok(
+    HttpResponse::Ok()
+      .append_header((CONTENT_TYPE, content_type))
+      .body(blob.to_blob()),
+  )
+}
+
+#[instrument(level = "debug", skip(state, query, payload))]
+async fn put_object_handler(
+  state: Data<AppState>,
+  object_key: web::Path<String>,
+  query: Query<PresignedQuery>,
+  payload: Payload,
+  req: HttpRequest,
+) -> Result<HttpResponse> {
+  let client = fs_bucket_client(&state)?;
+  client.verify_presigned("PUT", &object_key, &query)?;
+  let limit = query
+    .content_length
+    .ok_or_else(|| AppError::InvalidRequest("content_length is required".to_string()))?;
+
+  let mut body = Vec::with_capacity(
+    req
+      .headers()
+      .get(CONTENT_LENGTH)
+      .and_then(|len| len.to_str().ok())
+      .and_then(|len| len.parse::<usize>().ok())
+      .unwrap_or_default()
+      .min(limit as usize),
+  );
+  let mut payload = LimitedPayload::new(payload, limit as usize);
+  while let Some(chunk) = payload.next().await {
+    body.extend_from_slice(&chunk?);
+  }
+
+  let content_type = req
+    .headers()
+    .get(CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok());
+  trace!(
+    "put object {} ({} bytes) with presigned url",
+    object_key,
+    body.len()
+  );
+  client
+    .put_blob(&object_key, ByteStream::from(body), content_type)
+    .await?;
+  Ok(HttpResponse::Ok().finish())
+}
@@ -1,7 +1,8 @@
 use crate::biz::authentication::jwt::UserUuid;
 use crate::biz::chat::ops::{
   create_chat, create_chat_message, delete_chat, generate_chat_message_answer,
-  get_chat_messages_with_author_uuid, get_question_message, update_chat_message,
+  get_chat_messages_with_author_uuid, get_question_message, select_readable_chat_rag_ids,
+  update_chat_message,
 };
 use crate::state::AppState;
 use actix_web::web::{Data, Json};
@@ -9,6 +10,7 @@ use actix_web::{web, HttpRequest, HttpResponse, Scope};
 use serde::Deserialize;
 
 use crate::api::util::ai_model_from_header;
+use access_control::act::Action;
 use app_error::AppError;
 use appflowy_ai_client::dto::{
   ChatQuestion, ChatQuestionQuery, CreateChatContext, MessageData, QuestionMetadata,
@@ -138,6 +140,7 @@ async fn create_chat_context_handler(
 }
 
 async fn update_question_handler(
+  user_uuid: UserUuid,
   path: web::Path<(String, String)>,
   state: Data<AppState>,
   payload: Json<UpdateChatMessageContentParams>,
@@ -145,11 +148,13 @@ async fn update_question_handler(
 ) -> actix_web::Result<JsonAppResponse<()>> {
   let (workspace_id, _chat_id) = path.into_inner();
   let params = payload.into_inner();
+  let rag_ids = readable_chat_rag_ids(&state, &user_uuid, &workspace_id, &params.chat_id).await?;
   let ai_model = ai_model_from_header(&req);
   update_chat_message(
     workspace_id,
     &state.pg_pool,
     params,
+    rag_ids,
     state.ai_client.clone(),
     ai_model,
   )
@@ -216,11 +221,13 @@ async fn create_answer_handler(
   Ok(AppResponse::Ok().with_data(message).into())
 }
 async fn answer_handler(
+  user_uuid: UserUuid,
   path: web::Path<(String, String, i64)>,
   state: Data<AppState>,
   req: HttpRequest,
 ) -> actix_web::Result<JsonAppResponse<ChatMessage>> {
   let (workspace_id, chat_id, message_id) = path.into_inner();
+  let rag_ids = readable_chat_rag_ids(&state, &user_uuid, &workspace_id, &chat_id).await?;
   let ai_model = ai_model_from_header(&req);
   let message = generate_chat_message_answer(
     workspace_id,
@@ -228,6 +235,7 @@ async fn answer_handler(
     state.ai_client.clone(),
     message_id,
     &chat_id,
+    rag_ids,
     ai_model,
   )
   .await?;
@@ -236,6 +244,7 @@ async fn answer_handler(
 
 #[instrument(level = "debug", skip_all, err)]
 async fn answer_stream_handler(
+  user_uuid: UserUuid,
   path: web::Path<(String, String, i64)>,
   state: Data<AppState>,
   req: HttpRequest,
@@ -243,7 +252,7 @@ async fn answer_stream_handler(
   let (workspace_id, chat_id, question_id) = path.into_inner();
   let (content, metadata) =
     chat::chat_ops::select_chat_message_content(&state.pg_pool, question_id).await?;
-  let rag_ids = chat::chat_ops::select_chat_rag_ids(&state.pg_pool, &chat_id).await?;
+  let rag_ids = readable_chat_rag_ids(&state, &user_uuid, &workspace_id, &chat_id).await?;
   let ai_model = ai_model_from_header(&req);
   state.metrics.ai_metrics.record_total_stream_count(1);
   match state
@@ -281,6 +290,7 @@ async fn answer_stream_handler(
 
 #[instrument(level = "debug", skip_all, err)]
 async fn answer_stream_v2_handler(
+  user_uuid: UserUuid,
   path: web::Path<(String, String, i64)>,
   state: Data<AppState>,
   req: HttpRequest,
@@ -288,7 +298,7 @@ async fn answer_stream_v2_handler(
   let (workspace_id, chat_id, question_id) = path.into_inner();
   let (content, metadata) =
     chat::chat_ops::select_chat_message_content(&state.pg_pool, question_id).await?;
-  let rag_ids = chat::chat_ops::select_chat_rag_ids(&state.pg_pool, &chat_id).await?;
+  let rag_ids = readable_chat_rag_ids(&state, &user_uuid, &workspace_id, &chat_id).await?;
   let ai_model = ai_model_from_header(&req);
 
   state.metrics.ai_metrics.record_total_stream_count(1);
@@ -335,6 +345,7 @@ async fn answer_stream_v2_handler(
 
 #[instrument(level = "debug", skip_all, err)]
 async fn answer_stream_v3_handler(
+  user_uuid: UserUuid,
   path: web::Path<(String, String)>,
   payload: Json<ChatQuestionQuery>,
   state: Data<AppState>,
@@ -344,7 +355,7 @@ async fn answer_stream_v3_handler(
   let payload = payload.into_inner();
   let (content, metadata) =
     chat::chat_ops::select_chat_message_content(&state.pg_pool, payload.question_id).await?;
-  let rag_ids = chat::chat_ops::select_chat_rag_ids(&state.pg_pool, &payload.chat_id).await?;
+  let rag_ids = readable_chat_rag_ids(&state, &user_uuid, &workspace_id, &payload.chat_id).await?;
   let ai_model = ai_model_from_header(&req);
   state.metrics.ai_metrics.record_total_stream_count(1);
   if payload.format.output_content.is_image() {
@@ -589,3 +600,27 @@ where
 struct FindQuestionParams {
   answer_message_id: i64,
 }
+
+/// Checks that the user can read the workspace, then returns the rag ids of the chat without the
+/// views hidden from the user.
+async fn readable_chat_rag_ids(
+  state: &AppState,
+  user_uuid: &UserUuid,
+  workspace_id: &str,
+  chat_id: &str,
+) -> Result<Vec<String>, AppError> {
+  let workspace_id = Uuid::parse_str(workspace_id)?;
+  let uid = state.user_cache.get_user_uid(user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id, Action::Read)
+    .await?;
+  select_readable_chat_rag_ids(
+    &state.pg_pool,
+    &state.ws_server,
+    uid,
+    &workspace_id,
+    chat_id,
+  )
+  .await
+}
@@ -0,0 +1,151 @@
+use crate::biz::authentication::jwt::UserUuid;
+use crate::biz::workspace::ops::create_export_task;
+use crate::state::AppState;
+use actix_web::web::Data;
+use actix_web::{web, Scope};
+use app_error::AppError;
+use database::user::select_name_and_email_from_uuid;
+use database::workspace::{
+  num_pending_export_task, select_export_task, select_export_tasks,
+  select_workspace_name_from_workspace_id, ExportTaskState,
+};
+use database_entity::dto::AFRole;
+use infra::env_util::get_env_var;
+use serde_json::json;
+use shared_entity::dto::export_dto::{
+  CreateExportTaskResponse, ExportDownloadUrl, ExportTaskDetail, UserExportTask,
+};
+use shared_entity::response::{AppResponse, JsonAppResponse};
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+pub fn data_export_scope() -> Scope {
+  web::scope("/api/export")
+    .service(
+      web::resource("/{workspace_id}")
+        .route(web::post().to(create_export_handler))
+        .route(web::get().to(list_export_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/{task_id}/download")
+        .route(web::get().to(get_export_download_url_handler)),
+    )
+}
+
+#[instrument(level = "debug", skip_all)]
+async fn create_export_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<Uuid>,
+) -> actix_web::Result<JsonAppResponse<CreateExportTaskResponse>> {
+  let workspace_id = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  check_maximum_task(&state, uid).await?;
+
+  let (user_name, user_email) = select_name_and_email_from_uuid(&state.pg_pool, &user_uuid).await?;
+  let workspace_name = select_workspace_name_from_workspace_id(&state.pg_pool, &workspace_id)
+    .await?
+    .unwrap_or_default();
+
+  info!("User:{} export workspace:{}", uid, workspace_id);
+  // This task will be deserialized into WorkspaceExportTask by the appflowy worker
+  let task_id = Uuid::new_v4();
+  let task = json!({
+    "uid": uid,
+    "user_name": user_name,
+    "user_email": user_email,
+    "task_id": task_id,
+    "workspace_id": workspace_id,
+    "workspace_name": workspace_name,
+    "created_at": chrono::Utc::now().timestamp(),
+  });
+  create_export_task(
+    uid,
+    task_id,
+    task,
+    &workspace_id,
+    &state.redis_connection_manager,
+    &state.pg_pool,
+  )
+  .await?;
+
+  let data = CreateExportTaskResponse {
+    task_id: task_id.to_string(),
+  };
+  Ok(AppResponse::Ok().with_data(data).into())
+}
+
+async fn list_export_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<Uuid>,
+) -> actix_web::Result<JsonAppResponse<UserExportTask>> {
+  let workspace_id = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let tasks = select_export_tasks(&state.pg_pool, &workspace_id, uid)
+    .await?
+    .into_iter()
+    .map(|task| ExportTaskDetail {
+      task_id: task.task_id.to_string(),
+      workspace_id: task.workspace_id.to_string(),
+      status: task.status,
+      file_size: task.file_size,
+      error: task.error,
+      created_at: task.created_at.timestamp(),
+      updated_at: task.updated_at.timestamp(),
+    })
+    .collect();
+  Ok(AppResponse::Ok().with_data(UserExportTask { tasks }).into())
+}
+
+async fn get_export_download_url_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<(Uuid, Uuid)>,
+) -> actix_web::Result<JsonAppResponse<ExportDownloadUrl>> {
+  let (workspace_id, task_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let task = select_export_task(&state.pg_pool, &task_id).await?;
+  if task.workspace_id != workspace_id || task.created_by != uid {
+    return Err(AppError::RecordNotFound(format!("export task {} not found", task_id)).into());
+  }
+
+  let s3_key = match (ExportTaskState::from(task.status), task.s3_key) {
+    (ExportTaskState::Completed, Some(s3_key)) => s3_key,
+    _ => {
+      return Err(
+        AppError::InvalidRequest(format!("export task {} is not completed", task_id)).into(),
+      )
+    },
+  };
+
+  // The download url is valid for 10 minutes
+  let download_url = state
+    .bucket_client
+    .gen_presigned_download_url(&s3_key, 600)
+    .await?;
+  Ok(
+    AppResponse::Ok()
+      .with_data(ExportDownloadUrl { download_url })
+      .into(),
+  )
+}
+
+async fn check_maximum_task(state: &Data<AppState>, uid: i64) -> Result<(), AppError> {
+  let count = num_pending_export_task(uid, &state.pg_pool).await?;
+  let maximum_pending_task = get_env_var("MAXIMUM_EXPORT_PENDING_TASK", "1")
+    .parse::<i64>()
+    .unwrap_or(1);
+
+  if count >= maximum_pending_task {
+    return Err(AppError::InvalidRequest(format!(
+      "{} export tasks are pending. Please wait until they are completed",
+      count
+    )));
+  }
+  Ok(())
+}
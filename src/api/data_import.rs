@@ -14,7 +14,7 @@ use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
 use database::user::select_name_and_email_from_uuid;
 use database::workspace::select_import_task_by_state;
-use database_entity::dto::{CreateImportTask, CreateImportTaskResponse};
+use database_entity::dto::{CreateImportTask, CreateImportTaskResponse, ImportSource};
 use futures_util::StreamExt;
 use infra::env_util::get_env_var;
 use serde_json::json;
@@ -79,7 +79,7 @@ async fn create_import_handler(
   let timestamp = chrono::Utc::now().timestamp();
   let task_id = Uuid::new_v4();
   let task = json!({
-      "notion": {
+      params.source.as_str(): {
          "uid": uid,
          "user_name": user_name,
          "user_email": user_email,
@@ -167,7 +167,16 @@ async fn import_data_handler(
     .and_then(|h| h.to_str().ok())
     .unwrap_or("");
 
-  let file_path = temp_dir().join(format!("import_data_{}.zip", Uuid::new_v4()));
+  let source = match req.headers().get("X-Import-Source") {
+    None => ImportSource::Notion,
+    Some(value) => value
+      .to_str()
+      .map_err(|err| AppError::InvalidRequest(err.to_string()))?
+      .parse::<ImportSource>()
+      .map_err(AppError::InvalidRequest)?,
+  };
+
+  let file_path = temp_dir().join(format!("import_data_{}", Uuid::new_v4()));
   let file = write_multiple_part(&mut payload, file_path).await?;
 
   trace!(
@@ -224,12 +233,12 @@ async fn import_data_handler(
     uid, file.size, workspace_id, file.name,
   );
 
-  upload_file_with_retry(&state, &workspace_id, &file.file_path).await?;
+  upload_file_with_retry(&state, &workspace_id, &file.file_path, source).await?;
 
   // This task will be deserialized into ImportTask
   let task_id = Uuid::new_v4();
   let task = json!({
-      "notion": {
+      source.as_str(): {
          "uid": uid,
          "user_name": user_name,
          "user_email": user_email,
@@ -262,6 +271,7 @@ async fn upload_file_with_retry(
   state: &AppState,
   workspace_id: &str,
   file_path: &PathBuf,
+  source: ImportSource,
 ) -> Result<(), AppError> {
   let mut attempt = 0;
   let max_retries = 3;
@@ -272,7 +282,7 @@ async fn upload_file_with_retry(
     })?;
     let result = state
       .bucket_client
-      .put_blob_with_content_type(workspace_id, stream, "application/zip")
+      .put_blob_with_content_type(workspace_id, stream, source.content_type())
       .await;
 
     match result {
@@ -13,18 +13,21 @@ use actix_web::{HttpResponse, Result};
 use app_error::AppError;
 
 use chrono::DateTime;
+use collab_entity::CollabType;
 use database::file::BlobKey;
+use database::index::delete_attachment_index;
 use database::resource_usage::{get_all_workspace_blob_metadata, get_workspace_usage_size};
 use database_entity::file_dto::{
   CompleteUploadRequest, CreateUploadRequest, CreateUploadResponse, UploadPartData,
   UploadPartResponse,
 };
 
+use crate::biz::ai::provider::AIProvider;
 use crate::biz::authentication::jwt::UserUuid;
 use crate::biz::data_import::LimitedPayload;
+use crate::biz::search::{is_indexable_attachment, queue_attachment_indexing};
 use crate::state::AppState;
 use anyhow::anyhow;
-use appflowy_ai_client::client::AppFlowyAIClient;
 use aws_sdk_s3::primitives::ByteStream;
 use collab_importer::util::FileId;
 use database::pg_row::{AFBlobSource, AFBlobStatus};
@@ -37,7 +40,7 @@ use std::pin::Pin;
 use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio_stream::StreamExt;
 use tokio_util::io::StreamReader;
-use tracing::{error, event, info, instrument, trace};
+use tracing::{error, event, info, instrument, trace, warn};
 
 pub fn file_storage_scope() -> Scope {
   web::scope("/api/file_storage")
@@ -214,10 +217,23 @@ async fn complete_upload_handler(
   };
   state
     .bucket_storage
-    .complete_upload(key, req)
+    .complete_upload(key.clone(), req)
     .await
     .map_err(AppResponseError::from)?;
 
+  match state
+    .bucket_storage
+    .get_blob_metadata(&workspace_id, &key.blob_metadata_key())
+    .await
+  {
+    Ok(meta) => index_attachment(&state, uid, &key, &meta.file_type).await,
+    Err(err) => warn!(
+      "failed to get metadata of uploaded blob {}: {}",
+      key.object_key(),
+      err
+    ),
+  }
+
   Ok(AppResponse::Ok().into())
 }
 
@@ -343,11 +359,15 @@ async fn delete_blob_v1_handler(
     .workspace_access_control
     .enforce_action(&uid, &workspace_id, Action::Write)
     .await?;
+  let (parent_dir, file_id) = (path.parent_dir.clone(), path.file_id.clone());
   state
     .bucket_storage
     .delete_blob(path)
     .await
     .map_err(AppResponseError::from)?;
+  delete_attachment_index(&state.pg_pool, &workspace_id, &parent_dir, &file_id)
+    .await
+    .map_err(AppError::from)?;
 
   Ok(AppResponse::Ok().into())
 }
@@ -377,13 +397,12 @@ async fn get_blob_by_object_key(
   match source {
     AFBlobSource::UserUpload => {},
     AFBlobSource::AIGen => {
-      let spawn_regenerate_image =
-        |client: AppFlowyAIClient, source_metadata: serde_json::Value| {
-          tokio::spawn(async move {
-            info!("Regenerate ai image: {:?}", source_metadata);
-            let _ = client.regenerate_image(source_metadata).await;
-          });
-        };
+      let spawn_regenerate_image = |client: AIProvider, source_metadata: serde_json::Value| {
+        tokio::spawn(async move {
+          info!("Regenerate ai image: {:?}", source_metadata);
+          let _ = client.regenerate_image(source_metadata).await;
+        });
+      };
       let source_metadata = metadata.source_metadata;
       let status = AFBlobStatus::from(metadata.status);
       trace!("AI image {}: {:?}", key.object_key(), status);
@@ -598,19 +617,48 @@ async fn put_blob_handler_v1(
   );
 
   let file_stream = ByteStream::from(content);
+  let key = BlobPathV1::from((path, file_id));
   state
     .bucket_storage
     .put_blob_with_content_type(
-      BlobPathV1::from((path, file_id)),
+      key.clone(),
       file_stream,
-      content_type,
+      content_type.clone(),
       content_length,
     )
     .await
     .map_err(AppResponseError::from)?;
+  index_attachment(&state, uid, &key, &content_type).await;
   Ok(AppResponse::Ok().with_data(resp_data).into())
 }
 
+/// Queues the uploaded file for the attachment indexer, if the text of the file can be extracted.
+/// The upload itself doesn't fail when the file can't be queued.
+async fn index_attachment(state: &AppState, uid: i64, key: &BlobPathV1, content_type: &str) {
+  if !state
+    .indexer_scheduler
+    .is_indexing_enabled(CollabType::Document)
+    || !is_indexable_attachment(content_type)
+  {
+    return;
+  }
+  if let Err(err) = queue_attachment_indexing(
+    &state.redis_connection_manager,
+    uid,
+    &key.workspace_id,
+    &key.parent_dir,
+    &key.file_id,
+  )
+  .await
+  {
+    error!(
+      "failed to queue attachment {} for indexing: {}",
+      key.object_key(),
+      err
+    );
+  }
+}
+
 /// Use [BlobPathV0] when get/put object by single part
 #[derive(Deserialize, Debug)]
 struct BlobPathV0 {
@@ -637,7 +685,7 @@ impl BlobKey for BlobPathV0 {
 }
 
 /// Use [BlobPathV1] when put/get object by multiple upload parts
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct BlobPathV1 {
   pub workspace_id: Uuid,
   pub parent_dir: String,
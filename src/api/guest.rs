@@ -2,13 +2,12 @@ use actix_web::{
   web::{Data, Json},
   Result,
 };
-use app_error::ErrorCode;
 use shared_entity::{
   dto::guest_dto::{
     RevokeSharedViewAccessRequest, ShareViewWithGuestRequest, SharedViewDetails,
     SharedViewDetailsRequest, SharedViews,
   },
-  response::{AppResponseError, JsonAppResponse},
+  response::{AppResponse, JsonAppResponse},
 };
 
 use actix_web::{
@@ -18,6 +17,9 @@ use actix_web::{
 use uuid::Uuid;
 
 use crate::biz::authentication::jwt::UserUuid;
+use crate::biz::guest::ops::{
+  get_shared_view_details, list_shared_views, revoke_shared_view_access, share_view_with_guests,
+};
 use crate::state::AppState;
 
 pub fn sharing_scope() -> Scope {
@@ -38,60 +40,55 @@ pub fn sharing_scope() -> Scope {
 }
 
 async fn list_shared_views_handler(
-  _user_uuid: UserUuid,
-  _state: Data<AppState>,
-  _path: web::Path<Uuid>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<Uuid>,
 ) -> Result<JsonAppResponse<SharedViews>> {
-  Err(
-    AppResponseError::new(
-      ErrorCode::FeatureNotAvailable,
-      "this version of appflowy cloud server does not support guest editors",
-    )
-    .into(),
-  )
+  let workspace_id = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let shared_views = list_shared_views(&state, uid, workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(shared_views)))
 }
 
 async fn put_shared_view_handler(
-  _user_uuid: UserUuid,
-  _state: Data<AppState>,
-  _payload: web::Json<ShareViewWithGuestRequest>,
-  _path: web::Path<Uuid>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  payload: web::Json<ShareViewWithGuestRequest>,
+  path: web::Path<Uuid>,
 ) -> Result<JsonAppResponse<()>> {
-  Err(
-    AppResponseError::new(
-      ErrorCode::FeatureNotAvailable,
-      "this version of appflowy cloud server does not support guest editors",
-    )
-    .into(),
-  )
+  let workspace_id = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  share_view_with_guests(&state, uid, &user_uuid, workspace_id, payload.into_inner()).await?;
+  Ok(Json(AppResponse::Ok()))
 }
 
 async fn shared_view_access_details_handler(
-  _user_uuid: UserUuid,
-  _state: Data<AppState>,
-  _json: Json<SharedViewDetailsRequest>,
-  _path: web::Path<(Uuid, Uuid)>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  json: Json<SharedViewDetailsRequest>,
+  path: web::Path<(Uuid, Uuid)>,
 ) -> Result<JsonAppResponse<SharedViewDetails>> {
-  Err(
-    AppResponseError::new(
-      ErrorCode::FeatureNotAvailable,
-      "this version of appflowy cloud server does not support guest editors",
-    )
-    .into(),
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let details = get_shared_view_details(
+    &state,
+    uid,
+    workspace_id,
+    view_id,
+    json.into_inner().ancestor_view_ids,
   )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(details)))
 }
 
 async fn revoke_shared_view_access_handler(
-  _user_uuid: UserUuid,
-  _state: Data<AppState>,
-  _payload: web::Json<RevokeSharedViewAccessRequest>,
-  _path: web::Path<(Uuid, Uuid)>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  payload: web::Json<RevokeSharedViewAccessRequest>,
+  path: web::Path<(Uuid, Uuid)>,
 ) -> Result<JsonAppResponse<()>> {
-  Err(
-    AppResponseError::new(
-      ErrorCode::FeatureNotAvailable,
-      "this version of appflowy cloud server does not support guest editors",
-    )
-    .into(),
-  )
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  revoke_shared_view_access(&state, uid, workspace_id, view_id, payload.into_inner()).await?;
+  Ok(Json(AppResponse::Ok()))
 }
@@ -1,6 +1,10 @@
 pub mod access_request;
 pub mod ai;
+pub mod api_token;
+pub mod audit;
+pub mod bucket;
 pub mod chat;
+pub mod data_export;
 pub mod data_import;
 pub mod file_storage;
 pub mod guest;
@@ -11,5 +15,6 @@ pub mod server_info;
 pub mod template;
 pub mod user;
 pub mod util;
+pub mod webhook;
 pub mod workspace;
 pub mod ws;
@@ -1,15 +1,17 @@
 use crate::biz::authentication::jwt::Authorization;
-use crate::biz::search::{search_document, summarize_search_results};
+use crate::biz::search::{reindex_stale_attachments, search_document, summarize_search_results};
 use crate::state::AppState;
 use access_control::act::Action;
 use actix_web::web::{Data, Json, Query};
 use actix_web::{web, Scope};
 use async_openai::config::{AzureConfig, OpenAIConfig};
+use collab_entity::CollabType;
 
+use database_entity::dto::AFRole;
 use llm_client::chat::{AITool, AzureOpenAIChat, OpenAIChat};
 use shared_entity::dto::search_dto::{
-  SearchDocumentRequest, SearchDocumentResponseItem, SearchSummaryResult,
-  SummarySearchResultRequest,
+  EmbeddingMigrationResult, EmbeddingStatus, SearchDocumentRequest, SearchDocumentResponseItem,
+  SearchSummaryResult, SummarySearchResultRequest,
 };
 use shared_entity::response::{AppResponse, JsonAppResponse};
 use uuid::Uuid;
@@ -20,6 +22,13 @@ pub fn search_scope() -> Scope {
     .service(
       web::resource("/{workspace_id}/summary").route(web::get().to(summary_search_results_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/embedding").route(web::get().to(embedding_status_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/embedding/migrate")
+        .route(web::post().to(migrate_embeddings_handler)),
+    )
 }
 
 #[tracing::instrument(skip(state, auth, payload), err)]
@@ -41,6 +50,7 @@ async fn document_search_handler(
   let resp = search_document(
     &state.pg_pool,
     &state.ws_server,
+    &state.collab_storage,
     &state.indexer_scheduler,
     uid,
     workspace_id,
@@ -51,6 +61,72 @@ async fn document_search_handler(
   Ok(AppResponse::Ok().with_data(resp).into())
 }
 
+#[tracing::instrument(skip(state, auth), err)]
+async fn embedding_status_handler(
+  auth: Authorization,
+  path: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> actix_web::Result<JsonAppResponse<EmbeddingStatus>> {
+  let workspace_id = path.into_inner();
+  let user_uuid = auth.uuid()?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let (model, stats) = state
+    .indexer_scheduler
+    .embedding_model_stats(&workspace_id)
+    .await?;
+  let status = EmbeddingStatus {
+    model: model.name().to_string(),
+    dimensions: model.default_dimensions(),
+    indexed: stats.indexed,
+    stale: stats.stale,
+  };
+  Ok(AppResponse::Ok().with_data(status).into())
+}
+
+/// Embeds again the documents of the workspace which were embedded with another model than the
+/// configured one, eg. after switching the embedding provider.
+#[tracing::instrument(skip(state, auth), err)]
+async fn migrate_embeddings_handler(
+  auth: Authorization,
+  path: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> actix_web::Result<JsonAppResponse<EmbeddingMigrationResult>> {
+  let workspace_id = path.into_inner();
+  let user_uuid = auth.uuid()?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let mut queued = state
+    .indexer_scheduler
+    .reindex_stale_embeddings(&workspace_id)
+    .await?;
+  // attachments are embedded by the appflowy worker, along with the extraction of their text
+  if state
+    .indexer_scheduler
+    .is_indexing_enabled(CollabType::Document)
+  {
+    let model = state.indexer_scheduler.embedding_model()?;
+    queued += reindex_stale_attachments(
+      &state.pg_pool,
+      &state.redis_connection_manager,
+      &workspace_id,
+      model.name(),
+    )
+    .await?;
+  }
+  Ok(
+    AppResponse::Ok()
+      .with_data(EmbeddingMigrationResult { queued })
+      .into(),
+  )
+}
+
 #[tracing::instrument(skip(state, auth, payload), err)]
 async fn summary_search_results_handler(
   auth: Authorization,
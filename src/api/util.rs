@@ -1,6 +1,9 @@
+use crate::biz::audit::ops::AuditActor;
 use crate::domain::compression::{CompressionType, X_COMPRESSION_BUFFER_SIZE, X_COMPRESSION_TYPE};
+use crate::state::AppState;
 use actix_http::header::HeaderMap;
-use actix_web::web::Payload;
+use actix_web::http::header::{USER_AGENT, X_FORWARDED_FOR};
+use actix_web::web::{Data, Payload};
 use app_error::AppError;
 
 use actix_web::HttpRequest;
@@ -10,6 +13,8 @@ use chrono::Utc;
 use collab_rt_entity::user::RealtimeUser;
 use collab_rt_protocol::validate_encode_collab;
 use database_entity::dto::CollabParams;
+use ipnet::IpNet;
+use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
 use tokio_stream::StreamExt;
 use uuid::Uuid;
@@ -110,6 +115,65 @@ pub fn realtime_user_for_web_request(
   Ok(user)
 }
 
+/// Address of the client behind the request. `X-Forwarded-For` can be set by the client itself,
+/// so it is only used when the request comes from one of the trusted proxies of the server, see
+/// [crate::config::config::ApplicationSetting::trusted_proxies].
+pub fn client_ip_address(req: &HttpRequest) -> Option<IpAddr> {
+  let trusted_proxies = req
+    .app_data::<Data<AppState>>()
+    .map(|state| state.config.application.trusted_proxies.as_slice())
+    .unwrap_or_default();
+  forwarded_client_ip_address(req, trusted_proxies)
+}
+
+/// Walks the `X-Forwarded-For` addresses from the peer of the connection back to the client, and
+/// returns the first address that is not a trusted proxy. Each proxy appends the address of its
+/// own peer, so the addresses before the last untrusted one may have been made up by the client.
+fn forwarded_client_ip_address(req: &HttpRequest, trusted_proxies: &[IpNet]) -> Option<IpAddr> {
+  let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|proxy| proxy.contains(ip));
+  let mut client_ip = req.peer_addr()?.ip();
+  if !is_trusted(&client_ip) {
+    return Some(client_ip);
+  }
+
+  let forwarded = req
+    .headers()
+    .get_all(X_FORWARDED_FOR)
+    .filter_map(|value| value.to_str().ok())
+    .flat_map(|value| value.split(','))
+    .collect::<Vec<_>>();
+  for addr in forwarded.into_iter().rev() {
+    let addr = addr.trim();
+    let Some(ip) = addr
+      .parse::<IpAddr>()
+      .or_else(|_| addr.parse::<SocketAddr>().map(|addr| addr.ip()))
+      .ok()
+    else {
+      break;
+    };
+    client_ip = ip;
+    if !is_trusted(&client_ip) {
+      break;
+    }
+  }
+  Some(client_ip)
+}
+
+/// Identify the user and client behind a request, for the workspace audit log
+pub fn audit_actor_for_request(req: &HttpRequest, uid: i64) -> AuditActor {
+  let ip_address = client_ip_address(req).map(|ip| ip.to_string());
+  let user_agent = req
+    .headers()
+    .get(USER_AGENT)
+    .and_then(|value| value.to_str().ok())
+    .map(|value| value.to_string());
+  AuditActor {
+    uid,
+    ip_address,
+    user_agent,
+  }
+}
+
 #[async_trait]
 pub trait CollabValidator {
   async fn check_encode_collab(&self) -> Result<(), AppError>;
@@ -328,4 +392,42 @@ mod tests {
     // Should return the first matching key's value
     assert_eq!(result.unwrap(), "value1");
   }
+
+  fn forwarded_request(peer: &str, forwarded_for: &str) -> HttpRequest {
+    actix_web::test::TestRequest::default()
+      .peer_addr(peer.parse().unwrap())
+      .insert_header((X_FORWARDED_FOR, forwarded_for))
+      .to_http_request()
+  }
+
+  #[test]
+  fn test_forwarded_client_ip_from_trusted_proxy() {
+    let trusted_proxies = vec!["172.16.0.0/12".parse::<IpNet>().unwrap()];
+    let req = forwarded_request("172.18.0.5:40000", "203.0.113.7");
+    assert_eq!(
+      forwarded_client_ip_address(&req, &trusted_proxies),
+      "203.0.113.7".parse().ok()
+    );
+  }
+
+  #[test]
+  fn test_forwarded_client_ip_from_untrusted_peer() {
+    let trusted_proxies = vec!["172.16.0.0/12".parse::<IpNet>().unwrap()];
+    let req = forwarded_request("198.51.100.1:40000", "203.0.113.7");
+    assert_eq!(
+      forwarded_client_ip_address(&req, &trusted_proxies),
+      "198.51.100.1".parse().ok()
+    );
+  }
+
+  #[test]
+  fn test_forwarded_client_ip_ignores_spoofed_addresses() {
+    let trusted_proxies = vec!["172.16.0.0/12".parse::<IpNet>().unwrap()];
+    // The client sent `X-Forwarded-For: 10.0.0.1` and the proxy appended the client address
+    let req = forwarded_request("172.18.0.5:40000", "10.0.0.1, 203.0.113.7, 172.18.0.9");
+    assert_eq!(
+      forwarded_client_ip_address(&req, &trusted_proxies),
+      "203.0.113.7".parse().ok()
+    );
+  }
 }
@@ -0,0 +1,123 @@
+use crate::biz::authentication::jwt::UserUuid;
+use crate::biz::webhook::ops::{
+  create_webhook, delete_workspace_webhook, list_webhook_deliveries, list_webhooks,
+  update_workspace_webhook,
+};
+use crate::state::AppState;
+use actix_web::web::{Data, Json};
+use actix_web::{web, Result, Scope};
+use database_entity::dto::AFRole;
+use shared_entity::dto::webhook_dto::{
+  AFWebhook, AFWebhookDelivery, CreateWebhookParams, QueryWebhookDeliveryParams,
+  UpdateWebhookParams,
+};
+use shared_entity::response::{AppResponse, JsonAppResponse};
+use uuid::Uuid;
+
+pub fn webhook_scope() -> Scope {
+  web::scope("/api/webhook")
+    .service(
+      web::resource("/{workspace_id}")
+        .route(web::get().to(list_webhooks_handler))
+        .route(web::post().to(create_webhook_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/{webhook_id}")
+        .route(web::patch().to(update_webhook_handler))
+        .route(web::delete().to(delete_webhook_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/{webhook_id}/delivery")
+        .route(web::get().to(list_webhook_deliveries_handler)),
+    )
+}
+
+async fn create_webhook_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<Uuid>,
+  payload: Json<CreateWebhookParams>,
+) -> Result<JsonAppResponse<AFWebhook>> {
+  let workspace_id = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let webhook = create_webhook(&state.pg_pool, uid, &workspace_id, payload.into_inner()).await?;
+  Ok(Json(AppResponse::Ok().with_data(webhook)))
+}
+
+async fn list_webhooks_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<Uuid>,
+) -> Result<JsonAppResponse<Vec<AFWebhook>>> {
+  let workspace_id = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let webhooks = list_webhooks(&state.pg_pool, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(webhooks)))
+}
+
+async fn update_webhook_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdateWebhookParams>,
+) -> Result<JsonAppResponse<AFWebhook>> {
+  let (workspace_id, webhook_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let webhook = update_workspace_webhook(
+    &state.pg_pool,
+    &workspace_id,
+    &webhook_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(webhook)))
+}
+
+async fn delete_webhook_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<(Uuid, Uuid)>,
+) -> Result<JsonAppResponse<()>> {
+  let (workspace_id, webhook_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  delete_workspace_webhook(&state.pg_pool, &workspace_id, &webhook_id).await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn list_webhook_deliveries_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<(Uuid, Uuid)>,
+  query: web::Query<QueryWebhookDeliveryParams>,
+) -> Result<JsonAppResponse<Vec<AFWebhookDelivery>>> {
+  let (workspace_id, webhook_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let deliveries = list_webhook_deliveries(
+    &state.pg_pool,
+    &workspace_id,
+    &webhook_id,
+    query.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(deliveries)))
+}
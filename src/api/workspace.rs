@@ -1,13 +1,21 @@
-use crate::api::util::{client_version_from_headers, realtime_user_for_web_request, PayloadReader};
+use crate::api::util::{
+  audit_actor_for_request, client_ip_address, client_version_from_headers,
+  realtime_user_for_web_request, PayloadReader,
+};
 use crate::api::util::{compress_type_from_header_value, device_id_from_headers};
 use crate::api::ws::RealtimeServerAddr;
 use crate::biz;
 use crate::biz::authentication::jwt::{Authorization, OptionalUserUuid, UserUuid};
+use crate::biz::collab::blame::get_document_blame;
+use crate::biz::collab::changes::get_document_changes;
 use crate::biz::collab::database::check_if_row_document_collab_exists;
+use crate::biz::collab::history as collab_history;
 use crate::biz::collab::ops::{
   get_user_favorite_folder_views, get_user_recent_folder_views, get_user_trash_folder_views,
 };
 use crate::biz::collab::utils::{collab_from_doc_state, DUMMY_UID};
+use crate::biz::guest::ops::load_guest_view_policies;
+use crate::biz::notification::workspace::notify_publish_changed;
 use crate::biz::workspace;
 use crate::biz::workspace::duplicate::duplicate_view_tree_and_collab;
 use crate::biz::workspace::invite::{
@@ -24,10 +32,23 @@ use crate::biz::workspace::page_view::{
   create_orphaned_view, create_page, create_space, delete_all_pages_from_trash, delete_trash,
   favorite_page, get_page_view_collab, move_page, move_page_to_trash, publish_page,
   reorder_favorite_page, restore_all_pages_from_trash, restore_page_from_trash, unpublish_page,
-  update_page, update_page_collab_data, update_page_extra, update_page_icon, update_page_name,
-  update_space,
+  unpublish_pages, update_page, update_page_collab_data, update_page_extra, update_page_icon,
+  update_page_name, update_space,
+};
+use crate::biz::workspace::publish::{
+  check_published_collab_access, check_published_view_access,
+  get_workspace_default_publish_view_info_meta, unlock_published_collab,
+};
+use crate::biz::workspace::publish_analytics::{
+  get_published_analytics, record_published_view, PublishedViewVisitor,
+};
+use crate::biz::workspace::publish_domain::{
+  create_publish_custom_domain, list_publish_custom_domains, remove_publish_custom_domain,
+  resolve_publish_namespace_for_host, verify_publish_custom_domain, VerifiedPublishDomains,
+};
+use crate::biz::workspace::publish_html::{
+  get_published_collab_html, published_page_url, PUBLISHED_HTML_CONTENT_SECURITY_POLICY,
 };
-use crate::biz::workspace::publish::get_workspace_default_publish_view_info_meta;
 use crate::biz::workspace::quick_note::{
   create_quick_note, delete_quick_note, list_quick_notes, update_quick_note,
 };
@@ -36,9 +57,10 @@ use crate::domain::compression::{
 };
 use crate::state::AppState;
 use access_control::act::Action;
+use actix_web::http::header::{self, HeaderName};
 use actix_web::web::{Bytes, Path, Payload};
 use actix_web::web::{Data, Json, PayloadConfig};
-use actix_web::{web, HttpResponse, ResponseError, Scope};
+use actix_web::{guard, web, HttpResponse, ResponseError, Scope};
 use actix_web::{HttpRequest, Result};
 use anyhow::{anyhow, Context};
 use app_error::{AppError, ErrorCode};
@@ -72,7 +94,15 @@ use rayon::prelude::*;
 
 use semver::Version;
 use sha2::{Digest, Sha256};
-use shared_entity::dto::publish_dto::DuplicatePublishedPageResponse;
+use shared_entity::dto::comment_dto::{
+  CreateViewCommentParams, CreateViewCommentThreadParams, QueryViewCommentThreadParams,
+  UpdateViewCommentParams, ViewCommentEdit, ViewCommentThread,
+};
+use shared_entity::dto::publish_dto::{
+  CreatePublishCustomDomainParams, DuplicatePublishedPageResponse, PublishCustomDomain,
+  PublishedAnalytics, PublishedAnalyticsQuery, PublishedViewAccessToken, UnlockPublishedViewParams,
+  PUBLISH_ACCESS_TOKEN_HEADER,
+};
 use shared_entity::dto::workspace_dto::*;
 use shared_entity::response::AppResponseError;
 use shared_entity::response::{AppResponse, JsonAppResponse};
@@ -177,6 +207,30 @@ pub fn workspace_scope() -> Scope {
       web::resource("/v1/{workspace_id}/collab/{object_id}/json")
         .route(web::get().to(get_collab_json_handler)),
     )
+    .service(
+      web::resource("/v1/{workspace_id}/collab/{object_id}/blame")
+        .route(web::get().to(get_document_blame_handler)),
+    )
+    .service(
+      web::resource("/v1/{workspace_id}/collab/{object_id}/changes")
+        .route(web::get().to(get_document_changes_handler)),
+    )
+    .service(
+      web::resource("/v1/{workspace_id}/collab/{object_id}/snapshot")
+        .route(web::get().to(list_collab_snapshots_handler)),
+    )
+    .service(
+      web::resource("/v1/{workspace_id}/collab/{object_id}/snapshot/{snapshot_id}")
+        .route(web::get().to(get_collab_snapshot_handler)),
+    )
+    .service(
+      web::resource("/v1/{workspace_id}/collab/{object_id}/snapshot/{snapshot_id}/json")
+        .route(web::get().to(get_collab_snapshot_json_handler)),
+    )
+    .service(
+      web::resource("/v1/{workspace_id}/collab/{object_id}/snapshot/{snapshot_id}/restore")
+        .route(web::post().to(restore_collab_snapshot_handler)),
+    )
     .service(
       web::resource("/v1/{workspace_id}/collab/{object_id}/full-sync")
         .route(web::post().to(collab_full_sync_handler)),
@@ -224,6 +278,32 @@ pub fn workspace_scope() -> Scope {
       web::resource("/{workspace_id}/page-view/{view_id}/page-mention")
         .route(web::put().to(put_page_mention_handler))
     )
+    .service(
+      web::resource("/{workspace_id}/page-view/{view_id}/comment-thread")
+        .route(web::get().to(list_view_comment_threads_handler))
+        .route(web::post().to(post_view_comment_thread_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/page-view/{view_id}/comment-thread/{thread_id}/comment")
+        .route(web::post().to(post_view_comment_reply_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/page-view/{view_id}/comment-thread/{thread_id}/resolve")
+        .route(web::post().to(resolve_view_comment_thread_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/page-view/{view_id}/comment-thread/{thread_id}/reopen")
+        .route(web::post().to(reopen_view_comment_thread_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/page-view/{view_id}/comment/{comment_id}")
+        .route(web::patch().to(update_view_comment_handler))
+        .route(web::delete().to(delete_view_comment_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/page-view/{view_id}/comment/{comment_id}/history")
+        .route(web::get().to(list_view_comment_edits_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/page-view/{view_id}/update-name")
         .route(web::post().to(update_page_name_handler)),
@@ -315,6 +395,14 @@ pub fn workspace_scope() -> Scope {
       web::resource("/published/{publish_namespace}/{publish_name}/blob")
         .route(web::get().to(get_published_collab_blob_handler)),
     )
+    .service(
+      web::resource("/published/{publish_namespace}/{publish_name}/html")
+        .route(web::get().to(get_published_collab_html_handler)),
+    )
+    .service(
+      web::resource("/published/{publish_namespace}/{publish_name}/unlock")
+        .route(web::post().to(post_unlock_published_collab_handler)),
+    )
     .service(
       web::resource("{workspace_id}/published-duplicate")
         .route(web::post().to(post_published_duplicate_handler)),
@@ -323,6 +411,10 @@ pub fn workspace_scope() -> Scope {
       web::resource("/{workspace_id}/published-info")
         .route(web::get().to(list_published_collab_info_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/published-analytics")
+        .route(web::get().to(get_published_analytics_handler)),
+    )
     .service(
       // deprecated since 0.7.4
       web::resource("/published-info/{view_id}")
@@ -349,6 +441,19 @@ pub fn workspace_scope() -> Scope {
         .route(web::put().to(put_publish_namespace_handler))
         .route(web::get().to(get_publish_namespace_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/publish-domain")
+        .route(web::get().to(list_publish_custom_domains_handler))
+        .route(web::post().to(post_publish_custom_domain_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/publish-domain/{domain}")
+        .route(web::delete().to(delete_publish_custom_domain_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/publish-domain/{domain}/verify")
+        .route(web::post().to(post_verify_publish_custom_domain_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/publish-default")
         .route(web::put().to(put_workspace_default_published_view_handler))
@@ -395,6 +500,15 @@ pub fn workspace_scope() -> Scope {
         .route(web::get().to(get_database_fields_handler))
         .route(web::post().to(post_database_fields_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/database/{database_id}/fields/order")
+        .route(web::put().to(reorder_database_fields_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/database/{database_id}/fields/{field_id}")
+        .route(web::patch().to(patch_database_field_handler))
+        .route(web::delete().to(delete_database_field_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/database/{database_id}/row/updated")
         .route(web::get().to(list_database_row_id_updated_handler)),
@@ -403,6 +517,14 @@ pub fn workspace_scope() -> Scope {
       web::resource("/{workspace_id}/database/{database_id}/row/detail")
         .route(web::get().to(list_database_row_details_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/database/{database_id}/row/query")
+        .route(web::post().to(query_database_rows_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/database/{database_id}/row/{row_id}")
+        .route(web::delete().to(delete_database_row_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/quick-note")
         .route(web::get().to(list_quick_notes_handler))
@@ -421,6 +543,29 @@ pub fn workspace_scope() -> Scope {
     )
 }
 
+/// Serves the published pages on the verified custom domains of their workspace. Registered last,
+/// as it matches any single segment path, and guarded so that it only sees requests to one of the
+/// verified domains.
+pub fn published_custom_domain_scope(verified_domains: VerifiedPublishDomains) -> Scope {
+  web::scope("")
+    .guard(guard::fn_guard(move |ctx| {
+      let host = ctx
+        .head()
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| ctx.head().uri.host());
+      host.is_some_and(|host| verified_domains.contains_host(host))
+    }))
+    .service(
+      web::resource("/").route(web::get().to(get_custom_domain_default_published_html_handler)),
+    )
+    .service(
+      web::resource("/{publish_name}")
+        .route(web::get().to(get_custom_domain_published_html_handler)),
+    )
+}
+
 pub fn collab_scope() -> Scope {
   web::scope("/api/realtime").service(
     web::resource("post/stream")
@@ -540,6 +685,7 @@ async fn post_workspace_invite_handler(
   workspace_id: web::Path<Uuid>,
   payload: Json<Vec<WorkspaceMemberInvitation>>,
   state: Data<AppState>,
+  req: HttpRequest,
 ) -> Result<JsonAppResponse<()>> {
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   let workspace_id = workspace_id.into_inner();
@@ -553,6 +699,7 @@ async fn post_workspace_invite_handler(
     &state.mailer,
     &state.pg_pool,
     &user_uuid,
+    &audit_actor_for_request(&req, uid),
     &workspace_id,
     invitations,
     &state.config.appflowy_web_url,
@@ -593,7 +740,7 @@ async fn post_accept_workspace_invite_handler(
   let user_uuid = auth.uuid()?;
   let user_uid = state.user_cache.get_user_uid(&user_uuid).await?;
   let invite_id = invite_id.into_inner();
-  workspace::ops::accept_workspace_invite(
+  let invitation = workspace::ops::accept_workspace_invite(
     &state.pg_pool,
     state.workspace_access_control.clone(),
     user_uid,
@@ -601,6 +748,9 @@ async fn post_accept_workspace_invite_handler(
     &invite_id,
   )
   .await?;
+  if invitation.role == AFRole::Guest {
+    load_guest_view_policies(&state, invitation.workspace_id, user_uid).await?;
+  }
   Ok(AppResponse::Ok().into())
 }
 
@@ -647,6 +797,7 @@ async fn post_workspace_settings_handler(
   state: Data<AppState>,
   workspace_id: web::Path<Uuid>,
   data: Json<AFWorkspaceSettingsChange>,
+  req: HttpRequest,
 ) -> Result<JsonAppResponse<AFWorkspaceSettings>> {
   let data = data.into_inner();
   trace!("workspace settings: {:?}", data);
@@ -656,8 +807,13 @@ async fn post_workspace_settings_handler(
     .workspace_access_control
     .enforce_action(&uid, &workspace_id, Action::Write)
     .await?;
-  let settings =
-    workspace::ops::update_workspace_settings(&state.pg_pool, &workspace_id, data).await?;
+  let settings = workspace::ops::update_workspace_settings(
+    &state.pg_pool,
+    &workspace_id,
+    &audit_actor_for_request(&req, uid),
+    data,
+  )
+  .await?;
   Ok(AppResponse::Ok().with_data(settings).into())
 }
 
@@ -697,6 +853,7 @@ async fn remove_workspace_member_handler(
   payload: Json<WorkspaceMembers>,
   state: Data<AppState>,
   workspace_id: web::Path<Uuid>,
+  req: HttpRequest,
 ) -> Result<JsonAppResponse<()>> {
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   let workspace_id = workspace_id.into_inner();
@@ -715,7 +872,10 @@ async fn remove_workspace_member_handler(
     &state.pg_pool,
     &workspace_id,
     &member_emails,
+    &audit_actor_for_request(&req, uid),
     state.workspace_access_control.clone(),
+    state.collab_access_control.clone(),
+    &state.ws_server,
   )
   .await?;
 
@@ -799,6 +959,147 @@ async fn put_page_mention_handler(
   Ok(AppResponse::Ok().into())
 }
 
+async fn list_view_comment_threads_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  query: web::Query<QueryViewCommentThreadParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<Vec<ViewCommentThread>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let threads = workspace::comment::list_view_comment_threads(
+    &state,
+    uid,
+    &workspace_id,
+    &view_id,
+    &query.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(threads)))
+}
+
+async fn post_view_comment_thread_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<CreateViewCommentThreadParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<ViewCommentThread>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let thread = workspace::comment::create_view_comment_thread(
+    &state,
+    uid,
+    &workspace_id,
+    &view_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(thread)))
+}
+
+async fn post_view_comment_reply_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, Uuid)>,
+  payload: Json<CreateViewCommentParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<ViewCommentThread>> {
+  let (workspace_id, view_id, thread_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let thread = workspace::comment::reply_to_view_comment_thread(
+    &state,
+    uid,
+    &workspace_id,
+    &view_id,
+    &thread_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(thread)))
+}
+
+async fn resolve_view_comment_thread_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<ViewCommentThread>> {
+  let (workspace_id, view_id, thread_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let thread = workspace::comment::set_view_comment_thread_resolved(
+    &state,
+    uid,
+    &workspace_id,
+    &view_id,
+    &thread_id,
+    true,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(thread)))
+}
+
+async fn reopen_view_comment_thread_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<ViewCommentThread>> {
+  let (workspace_id, view_id, thread_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let thread = workspace::comment::set_view_comment_thread_resolved(
+    &state,
+    uid,
+    &workspace_id,
+    &view_id,
+    &thread_id,
+    false,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(thread)))
+}
+
+async fn update_view_comment_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, Uuid)>,
+  payload: Json<UpdateViewCommentParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let (workspace_id, view_id, comment_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  workspace::comment::update_view_comment(
+    &state,
+    uid,
+    &workspace_id,
+    &view_id,
+    &comment_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn delete_view_comment_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let (workspace_id, view_id, comment_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  workspace::comment::delete_view_comment(&state, uid, &workspace_id, &view_id, &comment_id)
+    .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn list_view_comment_edits_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<Vec<ViewCommentEdit>>> {
+  let (workspace_id, view_id, comment_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let edits =
+    workspace::comment::list_view_comment_edits(&state, uid, &workspace_id, &view_id, &comment_id)
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(edits)))
+}
+
 #[instrument(skip_all, err)]
 async fn get_workspace_mentionable_person_handler(
   user_uuid: UserUuid,
@@ -928,13 +1229,18 @@ async fn leave_workspace_handler(
   user_uuid: UserUuid,
   state: Data<AppState>,
   workspace_id: web::Path<Uuid>,
+  req: HttpRequest,
 ) -> Result<JsonAppResponse<()>> {
   let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   workspace::ops::leave_workspace(
     &state.pg_pool,
     &workspace_id,
     &user_uuid,
+    &audit_actor_for_request(&req, uid),
     state.workspace_access_control.clone(),
+    state.collab_access_control.clone(),
+    &state.ws_server,
   )
   .await?;
   Ok(AppResponse::Ok().into())
@@ -946,6 +1252,7 @@ async fn update_workspace_member_handler(
   payload: Json<WorkspaceMemberChangeset>,
   state: Data<AppState>,
   workspace_id: web::Path<Uuid>,
+  req: HttpRequest,
 ) -> Result<JsonAppResponse<()>> {
   let workspace_id = workspace_id.into_inner();
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
@@ -965,6 +1272,7 @@ async fn update_workspace_member_handler(
       &state.pg_pool,
       &workspace_id,
       &changeset,
+      &audit_actor_for_request(&req, uid),
       state.workspace_access_control.clone(),
     )
     .await?;
@@ -1319,6 +1627,141 @@ async fn get_collab_json_handler(
   Ok(Json(AppResponse::Ok().with_data(resp)))
 }
 
+#[instrument(level = "trace", skip_all)]
+async fn get_document_blame_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<DocumentBlame>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .collab_access_control
+    .enforce_action(&workspace_id, &uid, &object_id, Action::Read)
+    .await?;
+  let blame = get_document_blame(
+    &state.pg_pool,
+    &state.collab_storage,
+    uid,
+    workspace_id,
+    object_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(blame)))
+}
+
+#[instrument(level = "trace", skip_all)]
+async fn get_document_changes_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  query: web::Query<DocumentChangesQuery>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<DocumentChanges>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .collab_access_control
+    .enforce_action(&workspace_id, &uid, &object_id, Action::Read)
+    .await?;
+  let changes = get_document_changes(
+    &state.pg_pool,
+    &state.collab_storage,
+    &state.snapshot_control,
+    uid,
+    workspace_id,
+    object_id,
+    query.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(changes)))
+}
+
+#[instrument(level = "trace", skip_all)]
+async fn list_collab_snapshots_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<AFSnapshotMetas>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .collab_access_control
+    .enforce_action(&workspace_id, &uid, &object_id, Action::Read)
+    .await?;
+  let metas =
+    collab_history::list_collab_snapshots(&state.snapshot_control, &workspace_id, &object_id)
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(metas)))
+}
+
+#[instrument(level = "trace", skip_all)]
+async fn get_collab_snapshot_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, i64)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<SnapshotData>>> {
+  let (workspace_id, object_id, snapshot_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .collab_access_control
+    .enforce_action(&workspace_id, &uid, &object_id, Action::Read)
+    .await?;
+  let snapshot = state
+    .snapshot_control
+    .get_snapshot(workspace_id, object_id, &snapshot_id)
+    .await?;
+  Ok(Json(AppResponse::Ok().with_data(snapshot)))
+}
+
+#[instrument(level = "trace", skip_all)]
+async fn get_collab_snapshot_json_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, i64)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabJsonResponse>>> {
+  let (workspace_id, object_id, snapshot_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .collab_access_control
+    .enforce_action(&workspace_id, &uid, &object_id, Action::Read)
+    .await?;
+  let resp = collab_history::get_collab_snapshot_json(
+    &state.snapshot_control,
+    workspace_id,
+    object_id,
+    snapshot_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(resp)))
+}
+
+#[instrument(level = "debug", skip_all)]
+async fn restore_collab_snapshot_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, i64)>,
+  payload: Json<CollabTypeParam>,
+  state: Data<AppState>,
+  req: HttpRequest,
+) -> Result<Json<AppResponse<AFSnapshotMeta>>> {
+  let (workspace_id, object_id, snapshot_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .collab_access_control
+    .enforce_action(&workspace_id, &uid, &object_id, Action::Write)
+    .await?;
+  let user = realtime_user_for_web_request(req.headers(), uid)?;
+  let backup = collab_history::restore_collab_snapshot(
+    &state,
+    user,
+    workspace_id,
+    object_id,
+    payload.into_inner().collab_type,
+    snapshot_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(backup)))
+}
+
 #[instrument(level = "debug", skip_all)]
 async fn post_web_update_handler(
   user_uuid: UserUuid,
@@ -1679,7 +2122,13 @@ async fn delete_all_pages_from_trash_handler(
     .enforce_action(&uid, &workspace_id, Action::Write)
     .await?;
   let user = realtime_user_for_web_request(req.headers(), uid)?;
-  delete_all_pages_from_trash(&state, user, workspace_id).await?;
+  delete_all_pages_from_trash(
+    &state,
+    user,
+    &audit_actor_for_request(&req, uid),
+    workspace_id,
+  )
+  .await?;
   Ok(Json(AppResponse::Ok()))
 }
 
@@ -1689,6 +2138,7 @@ async fn publish_page_handler(
   path: web::Path<(Uuid, Uuid)>,
   payload: Json<PublishPageParams>,
   state: Data<AppState>,
+  req: HttpRequest,
 ) -> Result<Json<AppResponse<()>>> {
   let (workspace_id, view_id) = path.into_inner();
   let uid = state
@@ -1705,17 +2155,22 @@ async fn publish_page_handler(
     visible_database_view_ids,
     comments_enabled,
     duplicate_enabled,
+    password,
+    expires_at,
   } = payload.into_inner();
   publish_page(
     &state,
     uid,
     *user_uuid,
+    &audit_actor_for_request(&req, uid),
     workspace_id,
     view_id,
     visible_database_view_ids,
     publish_name,
     comments_enabled.unwrap_or(true),
     duplicate_enabled.unwrap_or(true),
+    password,
+    expires_at,
   )
   .await?;
   Ok(Json(AppResponse::Ok()))
@@ -1725,6 +2180,7 @@ async fn unpublish_page_handler(
   user_uuid: UserUuid,
   path: web::Path<(Uuid, Uuid)>,
   state: Data<AppState>,
+  req: HttpRequest,
 ) -> Result<Json<AppResponse<()>>> {
   let (workspace_uuid, view_uuid) = path.into_inner();
   let uid = state
@@ -1737,9 +2193,11 @@ async fn unpublish_page_handler(
     .enforce_role_weak(&uid, &workspace_uuid, AFRole::Member)
     .await?;
   unpublish_page(
+    &state.pg_pool,
     state.published_collab_store.as_ref(),
     workspace_uuid,
     *user_uuid,
+    &audit_actor_for_request(&req, uid),
     view_uuid,
   )
   .await?;
@@ -2101,35 +2559,173 @@ async fn get_publish_namespace_handler(
   Ok(Json(AppResponse::Ok().with_data(namespace)))
 }
 
+async fn list_publish_custom_domains_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PublishCustomDomain>>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let domains = list_publish_custom_domains(&state.pg_pool, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(domains)))
+}
+
+async fn post_publish_custom_domain_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<CreatePublishCustomDomainParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishCustomDomain>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let domain = create_publish_custom_domain(
+    &state.pg_pool,
+    &state.config.appflowy_web_url,
+    &workspace_id,
+    &payload.domain,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(domain)))
+}
+
+async fn post_verify_publish_custom_domain_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishCustomDomain>>> {
+  let (workspace_id, domain) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let domain = verify_publish_custom_domain(
+    &state.pg_pool,
+    &state.config.published_collab.custom_domain_dns_resolver_url,
+    &workspace_id,
+    &domain,
+  )
+  .await?;
+  if domain.verified_at.is_some() {
+    state.verified_publish_domains.insert(&domain.domain);
+  }
+  Ok(Json(AppResponse::Ok().with_data(domain)))
+}
+
+async fn delete_publish_custom_domain_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, domain) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  remove_publish_custom_domain(&state.pg_pool, &workspace_id, &domain).await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+fn get_publish_access_token(req: &HttpRequest) -> Option<&str> {
+  req
+    .headers()
+    .get(PUBLISH_ACCESS_TOKEN_HEADER)
+    .and_then(|value| value.to_str().ok())
+}
+
+fn record_published_view_from_request(
+  req: &HttpRequest,
+  state: &AppState,
+  workspace_id: Uuid,
+  view_id: Uuid,
+) {
+  let header_value = |name: HeaderName| {
+    req
+      .headers()
+      .get(name)
+      .and_then(|value| value.to_str().ok())
+  };
+  record_published_view(
+    &state.pg_pool,
+    &state.config.published_collab.analytics_secret,
+    state.config.published_collab.analytics_retention_days,
+    workspace_id,
+    view_id,
+    PublishedViewVisitor {
+      client_ip: client_ip_address(req),
+      user_agent: header_value(header::USER_AGENT),
+      referer: header_value(header::REFERER),
+    },
+  );
+}
+
 async fn get_default_published_collab_info_meta_handler(
+  req: HttpRequest,
   publish_namespace: web::Path<String>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<PublishInfoMeta<serde_json::Value>>>> {
   let publish_namespace = publish_namespace.into_inner();
   let (info, meta) =
     get_workspace_default_publish_view_info_meta(&state.pg_pool, &publish_namespace).await?;
+  check_published_collab_access(
+    &state.pg_pool,
+    &state.config.gotrue.jwt_secret,
+    &publish_namespace,
+    &info.publish_name,
+    get_publish_access_token(&req),
+  )
+  .await?;
+  // Not a read of the page: the web app then fetches the page itself, which is recorded
   Ok(Json(
     AppResponse::Ok().with_data(PublishInfoMeta { info, meta }),
   ))
 }
 
 async fn get_v1_published_collab_handler(
+  req: HttpRequest,
   path_param: web::Path<(String, String)>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<serde_json::Value>>> {
   let (workspace_namespace, publish_name) = path_param.into_inner();
+  let access = check_published_collab_access(
+    &state.pg_pool,
+    &state.config.gotrue.jwt_secret,
+    &workspace_namespace,
+    &publish_name,
+    get_publish_access_token(&req),
+  )
+  .await?;
   let metadata = state
     .published_collab_store
     .get_collab_metadata(&workspace_namespace, &publish_name)
     .await?;
+  record_published_view_from_request(&req, &state, access.workspace_id, access.view_id);
   Ok(Json(AppResponse::Ok().with_data(metadata)))
 }
 
 async fn get_published_collab_blob_handler(
+  req: HttpRequest,
   path_param: web::Path<(String, String)>,
   state: Data<AppState>,
 ) -> Result<Vec<u8>> {
   let (publish_namespace, publish_name) = path_param.into_inner();
+  check_published_collab_access(
+    &state.pg_pool,
+    &state.config.gotrue.jwt_secret,
+    &publish_namespace,
+    &publish_name,
+    get_publish_access_token(&req),
+  )
+  .await?;
   let collab_data = state
     .published_collab_store
     .get_collab_blob_by_publish_namespace(&publish_namespace, &publish_name)
@@ -2137,7 +2733,123 @@ async fn get_published_collab_blob_handler(
   Ok(collab_data)
 }
 
+async fn get_published_collab_html_handler(
+  req: HttpRequest,
+  path_param: web::Path<(String, String)>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let (publish_namespace, publish_name) = path_param.into_inner();
+  let page_url = published_page_url(
+    &state.config.appflowy_web_url,
+    &publish_namespace,
+    &publish_name,
+  );
+  published_html_response(&req, &state, &publish_namespace, &publish_name, &page_url).await
+}
+
+async fn get_custom_domain_default_published_html_handler(
+  req: HttpRequest,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let Some(publish_namespace) = resolve_custom_domain_namespace(&req, &state).await? else {
+    return Ok(HttpResponse::NotFound().finish());
+  };
+  let (info, _) =
+    get_workspace_default_publish_view_info_meta(&state.pg_pool, &publish_namespace).await?;
+  let page_url = format!("{}/", custom_domain_base_url(&req));
+  published_html_response(
+    &req,
+    &state,
+    &publish_namespace,
+    &info.publish_name,
+    &page_url,
+  )
+  .await
+}
+
+async fn get_custom_domain_published_html_handler(
+  req: HttpRequest,
+  publish_name: web::Path<String>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let Some(publish_namespace) = resolve_custom_domain_namespace(&req, &state).await? else {
+    return Ok(HttpResponse::NotFound().finish());
+  };
+  let publish_name = publish_name.into_inner();
+  let page_url = format!("{}/{}", custom_domain_base_url(&req), publish_name);
+  published_html_response(&req, &state, &publish_namespace, &publish_name, &page_url).await
+}
+
+async fn resolve_custom_domain_namespace(
+  req: &HttpRequest,
+  state: &AppState,
+) -> Result<Option<String>, AppError> {
+  let host = req.connection_info().host().to_string();
+  resolve_publish_namespace_for_host(&state.pg_pool, &host).await
+}
+
+fn custom_domain_base_url(req: &HttpRequest) -> String {
+  let connection_info = req.connection_info();
+  format!("{}://{}", connection_info.scheme(), connection_info.host())
+}
+
+async fn published_html_response(
+  req: &HttpRequest,
+  state: &AppState,
+  publish_namespace: &str,
+  publish_name: &str,
+  page_url: &str,
+) -> Result<HttpResponse> {
+  let access = check_published_collab_access(
+    &state.pg_pool,
+    &state.config.gotrue.jwt_secret,
+    publish_namespace,
+    publish_name,
+    get_publish_access_token(req),
+  )
+  .await?;
+  let html = get_published_collab_html(
+    &state.pg_pool,
+    state.published_collab_store.as_ref(),
+    page_url,
+    publish_namespace,
+    publish_name,
+  )
+  .await?;
+  record_published_view_from_request(req, state, access.workspace_id, access.view_id);
+  Ok(
+    HttpResponse::Ok()
+      .content_type("text/html; charset=utf-8")
+      .insert_header((
+        header::CONTENT_SECURITY_POLICY,
+        PUBLISHED_HTML_CONTENT_SECURITY_POLICY,
+      ))
+      .body(html),
+  )
+}
+
+async fn post_unlock_published_collab_handler(
+  req: HttpRequest,
+  path_param: web::Path<(String, String)>,
+  payload: Json<UnlockPublishedViewParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedViewAccessToken>>> {
+  let (publish_namespace, publish_name) = path_param.into_inner();
+  let access_token = unlock_published_collab(
+    &state.pg_pool,
+    &state.redis_connection_manager,
+    &state.config.gotrue.jwt_secret,
+    client_ip_address(&req),
+    &publish_namespace,
+    &publish_name,
+    payload.into_inner().password,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(access_token)))
+}
+
 async fn post_published_duplicate_handler(
+  req: HttpRequest,
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
@@ -2157,6 +2869,7 @@ async fn post_published_duplicate_handler(
       params.published_view_id,
       workspace_id,
       params.dest_view_id,
+      get_publish_access_token(&req),
     )
     .await?;
 
@@ -2167,6 +2880,23 @@ async fn post_published_duplicate_handler(
   )))
 }
 
+async fn get_published_analytics_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  query: web::Query<PublishedAnalyticsQuery>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedAnalytics>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let workspace_id = workspace_id.into_inner();
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let analytics =
+    get_published_analytics(&state.pg_pool, &workspace_id, query.into_inner()).await?;
+  Ok(Json(AppResponse::Ok().with_data(analytics)))
+}
+
 async fn list_published_collab_info_handler(
   workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
@@ -2211,12 +2941,30 @@ async fn get_v1_published_collab_info_handler(
   Ok(Json(AppResponse::Ok().with_data(collab_data)))
 }
 
+/// Comments and reactions are visible and accepted only where the published page itself is.
+async fn check_published_view_request_access(
+  state: &AppState,
+  req: &HttpRequest,
+  view_id: &Uuid,
+) -> Result<(), AppError> {
+  check_published_view_access(
+    &state.pg_pool,
+    &state.config.gotrue.jwt_secret,
+    view_id,
+    get_publish_access_token(req),
+  )
+  .await?;
+  Ok(())
+}
+
 async fn get_published_collab_comment_handler(
+  req: HttpRequest,
   view_id: web::Path<Uuid>,
   optional_user_uuid: OptionalUserUuid,
   state: Data<AppState>,
 ) -> Result<JsonAppResponse<GlobalComments>> {
   let view_id = view_id.into_inner();
+  check_published_view_request_access(&state, &req, &view_id).await?;
   let comments =
     get_comments_on_published_view(&state.pg_pool, &view_id, &optional_user_uuid).await?;
   let resp = GlobalComments { comments };
@@ -2224,12 +2972,14 @@ async fn get_published_collab_comment_handler(
 }
 
 async fn post_published_collab_comment_handler(
+  req: HttpRequest,
   user_uuid: UserUuid,
   view_id: web::Path<Uuid>,
   state: Data<AppState>,
   data: Json<CreateGlobalCommentParams>,
 ) -> Result<JsonAppResponse<()>> {
   let view_id = view_id.into_inner();
+  check_published_view_request_access(&state, &req, &view_id).await?;
   create_comment_on_published_view(
     &state.pg_pool,
     &view_id,
@@ -2242,22 +2992,26 @@ async fn post_published_collab_comment_handler(
 }
 
 async fn delete_published_collab_comment_handler(
+  req: HttpRequest,
   user_uuid: UserUuid,
   view_id: web::Path<Uuid>,
   state: Data<AppState>,
   data: Json<DeleteGlobalCommentParams>,
 ) -> Result<JsonAppResponse<()>> {
   let view_id = view_id.into_inner();
+  check_published_view_request_access(&state, &req, &view_id).await?;
   remove_comment_on_published_view(&state.pg_pool, &view_id, &data.comment_id, &user_uuid).await?;
   Ok(Json(AppResponse::Ok()))
 }
 
 async fn get_published_collab_reaction_handler(
+  req: HttpRequest,
   view_id: web::Path<Uuid>,
   query: web::Query<GetReactionQueryParams>,
   state: Data<AppState>,
 ) -> Result<JsonAppResponse<Reactions>> {
   let view_id = view_id.into_inner();
+  check_published_view_request_access(&state, &req, &view_id).await?;
   let reactions =
     get_reactions_on_published_view(&state.pg_pool, &view_id, &query.comment_id).await?;
   let resp = Reactions { reactions };
@@ -2265,12 +3019,14 @@ async fn get_published_collab_reaction_handler(
 }
 
 async fn post_published_collab_reaction_handler(
+  req: HttpRequest,
   user_uuid: UserUuid,
   view_id: web::Path<Uuid>,
   data: Json<CreateReactionParams>,
   state: Data<AppState>,
 ) -> Result<JsonAppResponse<()>> {
   let view_id = view_id.into_inner();
+  check_published_view_request_access(&state, &req, &view_id).await?;
   create_reaction_on_comment(
     &state.pg_pool,
     &data.comment_id,
@@ -2283,10 +3039,13 @@ async fn post_published_collab_reaction_handler(
 }
 
 async fn delete_published_collab_reaction_handler(
+  req: HttpRequest,
   user_uuid: UserUuid,
+  view_id: web::Path<Uuid>,
   data: Json<DeleteReactionParams>,
   state: Data<AppState>,
 ) -> Result<JsonAppResponse<()>> {
+  check_published_view_request_access(&state, &req, &view_id).await?;
   remove_reaction_on_comment(
     &state.pg_pool,
     &data.comment_id,
@@ -2346,6 +3105,8 @@ async fn post_publish_collabs_handler(
       data,
       comments_enabled: true,
       duplicate_enabled: true,
+      password: None,
+      expires_at: None,
     });
   }
 
@@ -2354,10 +3115,12 @@ async fn post_publish_collabs_handler(
       AppError::InvalidRequest(String::from("did not receive any data to publish")).into(),
     );
   }
+  let view_ids: Vec<Uuid> = accumulator.iter().map(|item| item.meta.view_id).collect();
   state
     .published_collab_store
-    .publish_collabs(accumulator, &workspace_id, &user_uuid)
+    .publish_collabs(accumulator, &workspace_id, &user_uuid, &[])
     .await?;
+  notify_publish_changed(&state.pg_pool, &workspace_id, &view_ids, true).await;
   Ok(Json(AppResponse::Ok()))
 }
 
@@ -2383,16 +3146,23 @@ async fn delete_published_collabs_handler(
   user_uuid: UserUuid,
   state: Data<AppState>,
   view_ids: Json<Vec<Uuid>>,
+  req: HttpRequest,
 ) -> Result<Json<AppResponse<()>>> {
   let workspace_id = workspace_id.into_inner();
   let view_ids = view_ids.into_inner();
   if view_ids.is_empty() {
     return Err(AppError::InvalidRequest("No view_ids provided".to_string()).into());
   }
-  state
-    .published_collab_store
-    .unpublish_collabs(&workspace_id, &view_ids, &user_uuid)
-    .await?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  unpublish_pages(
+    &state.pg_pool,
+    state.published_collab_store.as_ref(),
+    workspace_id,
+    *user_uuid,
+    &audit_actor_for_request(&req, uid),
+    &view_ids,
+  )
+  .await?;
   Ok(Json(AppResponse::Ok()))
 }
 
@@ -2652,6 +3422,22 @@ async fn put_database_row_handler(
   Ok(Json(AppResponse::Ok().with_data(row_id.to_string())))
 }
 
+async fn delete_database_row_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, db_id, row_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id, Action::Write)
+    .await?;
+
+  biz::collab::ops::delete_database_row(&state, workspace_id, db_id, uid, row_id).await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
 async fn get_database_fields_handler(
   user_uuid: UserUuid,
   path_param: web::Path<(Uuid, Uuid)>,
@@ -2689,6 +3475,64 @@ async fn post_database_fields_handler(
   Ok(Json(AppResponse::Ok().with_data(field_id)))
 }
 
+async fn patch_database_field_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid, String)>,
+  state: Data<AppState>,
+  field: Json<AFUpdateDatabaseField>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, db_id, field_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id, Action::Write)
+    .await?;
+
+  biz::collab::ops::update_database_field(
+    &state,
+    workspace_id,
+    db_id,
+    &field_id,
+    field.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn delete_database_field_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, db_id, field_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id, Action::Write)
+    .await?;
+
+  biz::collab::ops::delete_database_field(&state, workspace_id, db_id, &field_id).await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn reorder_database_fields_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+  payload: Json<AFReorderDatabaseFields>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, db_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id, Action::Write)
+    .await?;
+
+  biz::collab::ops::reorder_database_fields(&state, workspace_id, db_id, &payload.field_ids)
+    .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
 async fn list_database_row_id_updated_handler(
   user_uuid: UserUuid,
   path_param: web::Path<(Uuid, Uuid)>,
@@ -2751,6 +3595,33 @@ async fn list_database_row_details_handler(
   Ok(Json(AppResponse::Ok().with_data(db_rows)))
 }
 
+async fn query_database_rows_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+  payload: Json<QueryDatabaseRowParams>,
+) -> Result<Json<AppResponse<QueryDatabaseRowResponse>>> {
+  let (workspace_id, db_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id, Action::Read)
+    .await?;
+
+  static UNSUPPORTED_FIELD_TYPES: &[FieldType] = &[FieldType::Relation];
+
+  let resp = biz::collab::ops::query_database_rows(
+    &state.collab_storage,
+    uid,
+    workspace_id,
+    db_id,
+    payload.into_inner(),
+    UNSUPPORTED_FIELD_TYPES,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(resp)))
+}
+
 #[inline]
 async fn parser_realtime_msg(
   payload: Bytes,
@@ -3004,7 +3875,7 @@ async fn update_quick_note_handler(
     .workspace_access_control
     .enforce_role_strong(&uid, &workspace_id, AFRole::Member)
     .await?;
-  update_quick_note(&state.pg_pool, quick_note_id, &data.data).await?;
+  update_quick_note(&state.pg_pool, uid, workspace_id, quick_note_id, &data.data).await?;
   Ok(Json(AppResponse::Ok()))
 }
 
@@ -3019,7 +3890,7 @@ async fn delete_quick_note_handler(
     .workspace_access_control
     .enforce_role_strong(&uid, &workspace_id, AFRole::Member)
     .await?;
-  delete_quick_note(&state.pg_pool, quick_note_id).await?;
+  delete_quick_note(&state.pg_pool, uid, workspace_id, quick_note_id).await?;
   Ok(Json(AppResponse::Ok()))
 }
 
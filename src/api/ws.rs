@@ -2,6 +2,7 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::time::Duration;
 
+use crate::biz::authentication::api_token::{authenticate_api_token, is_api_token};
 use crate::biz::authentication::jwt::{authorization_from_token, UserUuid};
 use crate::state::AppState;
 use actix::Addr;
@@ -12,8 +13,9 @@ use actix_web_actors::ws;
 use app_error::AppError;
 use appflowy_collaborate::actix_ws::client::rt_client::RealtimeClient;
 use appflowy_collaborate::actix_ws::server::RealtimeServerActor;
-use appflowy_collaborate::ws2::{SessionInfo, WsSession};
+use appflowy_collaborate::ws2::{CloseSession, SessionInfo, WsSession};
 use appflowy_proto::{ServerMessage, WorkspaceNotification};
+use chrono::Utc;
 use collab_rt_entity::user::{AFUserChange, RealtimeUser, UserMessage};
 use collab_rt_entity::{max_sync_message_size, RealtimeMessage};
 use collab_stream::model::MessageId;
@@ -22,7 +24,7 @@ use semver::Version;
 use shared_entity::response::AppResponseError;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::Sender;
-use tracing::{debug, error, instrument, trace};
+use tracing::{debug, error, instrument, trace, warn};
 use uuid::Uuid;
 
 pub fn ws_scope() -> Scope {
@@ -32,6 +34,7 @@ pub fn ws_scope() -> Scope {
     .service(web::resource("/v2/{workspace_id}").route(web::get().to(establish_ws_connection_v2)))
 }
 const MAX_FRAME_SIZE: usize = 65_536; // 64 KiB
+const OBSERVER_DEVICE_ID: &str = "observer";
 
 pub type RealtimeServerAddr = Addr<RealtimeServerActor>;
 
@@ -116,6 +119,10 @@ pub async fn establish_ws_connection_v2(
   let workspace_id = path.into_inner();
   let ws_server = state.ws_server.clone();
   let params = WsConnectionV2Params::parse(&request)?;
+  if params.observer {
+    return start_observer_session(&request, payload, &state, &jwt_secret, workspace_id, params)
+      .await;
+  }
   let auth = authorization_from_token(params.access_token.as_str(), &jwt_secret)?;
   let user_uuid = UserUuid::from_auth(auth)?;
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
@@ -159,6 +166,129 @@ pub async fn establish_ws_connection_v2(
   .start()
 }
 
+/// How often the token of an observer session is checked again, so that sessions opened with a
+/// token that was revoked or has expired since are closed.
+const OBSERVER_TOKEN_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Token an observer session was opened with.
+enum ObserverCredential {
+  ApiToken(String),
+  /// Expiration of the user access token, in seconds since the UNIX epoch.
+  AccessToken(Option<i64>),
+}
+
+/// Starts a read-only session for bots and dashboards. Besides user access tokens, observers can
+/// authenticate with the API tokens of their owner, as long as the token grants access to the
+/// workspace.
+async fn start_observer_session(
+  request: &HttpRequest,
+  payload: Payload,
+  state: &Data<AppState>,
+  jwt_secret: &Data<Secret<String>>,
+  workspace_id: Uuid,
+  params: WsConnectionV2Params,
+) -> Result<HttpResponse> {
+  let (uid, quota_key, credential) = if is_api_token(&params.access_token) {
+    let owner = authenticate_api_token(&state.pg_pool, &params.access_token)
+      .await?
+      .ok_or_else(|| AppError::UserUnAuthorized("Invalid or expired api token".to_string()))?;
+    if !owner.workspace_ids.contains(&workspace_id) {
+      return Err(AppError::NotEnoughPermissions.into());
+    }
+    (
+      owner.uid,
+      format!("token:{}", owner.token_id),
+      ObserverCredential::ApiToken(params.access_token.clone()),
+    )
+  } else {
+    let auth = authorization_from_token(params.access_token.as_str(), jwt_secret)?;
+    let expires_at = auth.claims.exp;
+    let user_uuid = UserUuid::from_auth(auth)?;
+    let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+    (
+      uid,
+      format!("user:{}", uid),
+      ObserverCredential::AccessToken(expires_at),
+    )
+  };
+  let permit = state
+    .observer_quota
+    .acquire(&quota_key)
+    .await
+    .map_err(|err| AppError::Internal(err.into()))?
+    .ok_or_else(|| {
+      AppError::Connect(format!(
+        "Too many observer sessions, at most {} are allowed per token",
+        state.config.websocket.observer_max_sessions
+      ))
+    })?;
+  let info = SessionInfo::new(params.client_id, uid, params.device_id, None);
+  tracing::debug!(
+    "accepting new observer session {} (client id: {}) for workspace: {}",
+    info.collab_origin(),
+    params.client_id,
+    workspace_id
+  );
+
+  let (addr, response) = ws::WsResponseBuilder::new(
+    WsSession::new_observer(workspace_id, info, state.ws_server.clone(), permit),
+    request,
+    payload,
+  )
+  .frame_size(max_sync_message_size())
+  .start_with_addr()?;
+  close_observer_session_on_invalid_token(state, addr, credential, workspace_id);
+  Ok(response)
+}
+
+/// Checks the token of the observer session every [OBSERVER_TOKEN_CHECK_INTERVAL] while the
+/// session is open, and closes the session once the token is revoked, expires or stops granting
+/// access to the workspace.
+fn close_observer_session_on_invalid_token(
+  state: &Data<AppState>,
+  addr: Addr<WsSession>,
+  credential: ObserverCredential,
+  workspace_id: Uuid,
+) {
+  let pg_pool = state.pg_pool.clone();
+  actix::spawn(async move {
+    let mut interval = tokio::time::interval(OBSERVER_TOKEN_CHECK_INTERVAL);
+    // the token was just checked when the session was opened
+    interval.tick().await;
+    loop {
+      interval.tick().await;
+      if !addr.connected() {
+        break;
+      }
+      let reason = match &credential {
+        ObserverCredential::ApiToken(token) => {
+          match authenticate_api_token(&pg_pool, token).await {
+            Ok(Some(owner)) if owner.workspace_ids.contains(&workspace_id) => None,
+            Ok(_) => Some("api token was revoked or has expired"),
+            Err(err) => {
+              warn!(
+                "failed to check the api token of an observer session: {}",
+                err
+              );
+              None
+            },
+          }
+        },
+        ObserverCredential::AccessToken(Some(expires_at)) => {
+          (Utc::now().timestamp() >= *expires_at).then_some("access token has expired")
+        },
+        ObserverCredential::AccessToken(None) => None,
+      };
+      if let Some(reason) = reason {
+        addr.do_send(CloseSession {
+          reason: reason.to_string(),
+        });
+        break;
+      }
+    }
+  });
+}
+
 #[allow(clippy::too_many_arguments)]
 #[inline]
 async fn start_connect(
@@ -328,6 +458,8 @@ struct WsConnectionV2Params {
   device_id: String,
   client_id: u64,
   last_message_id: Option<MessageId>,
+  /// Negotiated with `mode=observer`. Observers don't need to identify their device nor client.
+  observer: bool,
 }
 
 impl WsConnectionV2Params {
@@ -335,13 +467,30 @@ impl WsConnectionV2Params {
     let url = req.full_url();
     let query = url.query_pairs().collect::<HashMap<_, _>>();
 
+    let observer = match Self::from_url(&query, "mode").as_deref() {
+      None | Some("full") => false,
+      Some("observer") => true,
+      Some(mode) => {
+        return Err(AppError::InvalidRequest(format!(
+          "Unknown connection mode: {}",
+          mode
+        )))
+      },
+    };
     let access_token = Self::from_url(&query, "token")
       .ok_or_else(|| AppError::InvalidRequest("Missing access token".into()))?;
     let device_id = Self::from_url(&query, "deviceId")
+      .or_else(|| observer.then(|| OBSERVER_DEVICE_ID.to_string()))
       .ok_or_else(|| AppError::InvalidRequest("Missing device id".into()))?;
-    let client_id: u64 = Self::from_url(&query, "clientId")
-      .and_then(|id| id.parse().ok())
-      .ok_or_else(|| AppError::InvalidRequest("Missing client id".into()))?;
+    // observer session ids are picked by the server, so that they can't clash with the ids of
+    // the clients
+    let client_id: u64 = if observer {
+      rand::random()
+    } else {
+      Self::from_url(&query, "clientId")
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| AppError::InvalidRequest("Missing client id".into()))?
+    };
     let last_message_id = Self::from_url(&query, "lastMessageId");
     let last_message_id = match last_message_id {
       None => None,
@@ -354,6 +503,7 @@ impl WsConnectionV2Params {
       device_id,
       client_id,
       last_message_id,
+      observer,
     })
   }
 
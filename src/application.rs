@@ -31,21 +31,24 @@ use mailer::config::MailerSetting;
 use secrecy::ExposeSecret;
 use sqlx::{postgres::PgPoolOptions, PgPool};
 use tokio::sync::RwLock;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use appflowy_ai_client::client::AppFlowyAIClient;
 use appflowy_collaborate::actix_ws::server::RealtimeServerActor;
 use appflowy_collaborate::collab::cache::CollabCache;
 use appflowy_collaborate::collab::collab_store::CollabStoreImpl;
-use appflowy_collaborate::ws2::{CollabManager, WsServer};
+use appflowy_collaborate::snapshot::SnapshotControl;
+use appflowy_collaborate::ws2::{CollabManager, ObserverQuota, WsServer};
 use appflowy_collaborate::CollaborationServer;
 use collab_stream::awareness_gossip::AwarenessGossip;
 use collab_stream::metrics::CollabStreamMetrics;
 use collab_stream::stream_router::{StreamRouter, StreamRouterOptions};
-use database::file::s3_client_impl::{AwsS3BucketClientImpl, S3BucketStorage};
+use database::file::app_bucket_client::{AppBucketClient, AppBucketStorage};
+use database::file::fs_client_impl::{run_expired_upload_cleanup, FsBucketClientImpl};
+use database::file::s3_client_impl::AwsS3BucketClientImpl;
 use indexer::collab_indexer::IndexerProvider;
 use indexer::scheduler::{IndexerConfiguration, IndexerScheduler};
-use indexer::vector::embedder::get_open_ai_config;
+use indexer::vector::embedder::get_embedder_config;
 use infra::env_util::get_env_var;
 use infra::thread_pool::ThreadPoolNoAbortBuilder;
 use mailer::sender::Mailer;
@@ -53,27 +56,41 @@ use snowflake::Snowflake;
 
 use crate::api::access_request::access_request_scope;
 use crate::api::ai::ai_completion_scope;
+use crate::api::api_token::api_token_scope;
+use crate::api::bucket::bucket_scope;
 use crate::api::chat::chat_scope;
+use crate::api::data_export::data_export_scope;
 use crate::api::data_import::data_import_scope;
 use crate::api::file_storage::file_storage_scope;
 use crate::api::guest::sharing_scope;
 use crate::api::invite_code::invite_code_scope;
 use crate::api::metrics::metrics_scope;
-use crate::api::search::search_scope;
+use crate::api::search::{create_ai_tool, search_scope};
 use crate::api::server_info::server_info_scope;
 use crate::api::template::template_scope;
 use crate::api::user::user_scope;
-use crate::api::workspace::{collab_scope, workspace_scope};
+use crate::api::webhook::webhook_scope;
+use crate::api::workspace::{collab_scope, published_custom_domain_scope, workspace_scope};
 use crate::api::ws::ws_scope;
+use crate::biz::ai::llm_provider::LLMProvider;
+use crate::biz::ai::provider::AIProvider;
 use crate::biz::notification::email::EmailNotificationWorker;
+use crate::biz::notification::workspace::forward_workspace_notifications;
 use crate::biz::pg_listener::PgListeners;
+use crate::biz::webhook::worker::{run_webhook_delivery_cleanup, WebhookDeliveryWorker};
 use crate::biz::workspace::publish::{
   PublishedCollabPostgresStore, PublishedCollabS3StoreWithPostgresFallback, PublishedCollabStore,
 };
+use crate::biz::workspace::publish_analytics::run_published_view_event_cleanup;
+use crate::biz::workspace::publish_domain::{
+  run_publish_custom_domain_refresh, VerifiedPublishDomains,
+};
 use crate::config::config::{
-  Config, DatabaseSetting, GoTrueSetting, PublishedCollabStorageBackend, S3Setting,
+  AIProviderKind, BucketStorageBackend, Config, DatabaseSetting, GoTrueSetting,
+  PublishedCollabStorageBackend, S3Setting,
 };
 use crate::mailer::AFCloudMailer;
+use crate::middleware::api_token_mw::ApiTokenMiddleware;
 use crate::middleware::metrics_mw::MetricsMiddleware;
 use crate::middleware::request_id::RequestIdMiddleware;
 use crate::state::{AppMetrics, AppState, GoTrueAdmin, UserCache};
@@ -140,6 +157,7 @@ pub async fn run_actix_server(
       .wrap(NormalizePath::trim())
        // Middleware is registered for each App, scope, or Resource and executed in opposite order as registration
       .wrap(MetricsMiddleware)
+      .wrap(ApiTokenMiddleware)
       .wrap(IdentityMiddleware::default())
       .wrap(
         SessionMiddleware::builder(redis_store.clone(), Key::generate())
@@ -164,9 +182,17 @@ pub async fn run_actix_server(
       .service(search_scope())
       .service(template_scope())
       .service(data_import_scope())
+      .service(data_export_scope())
       .service(access_request_scope())
       .service(sharing_scope())
+      .service(webhook_scope())
+      .service(api_token_scope())
+      .service(audit_scope())
+      .service(bucket_scope())
       .route("/health", web::get().to(health_check))
+      .service(published_custom_domain_scope(
+        state.verified_publish_domains.clone(),
+      ))
       .app_data(Data::new(state.metrics.registry.clone()))
       .app_data(Data::new(state.metrics.request_metrics.clone()))
       .app_data(Data::new(state.metrics.realtime_metrics.clone()))
@@ -194,15 +220,9 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
   migrate(&pg_pool).await?;
 
   // Bucket storage
-  info!("Setting up S3 bucket...");
-  let s3_client = AwsS3BucketClientImpl::new(
-    get_aws_s3_client(&config.s3).await?,
-    config.s3.bucket.clone(),
-    config.s3.minio_url.clone(),
-    config.s3.presigned_url_endpoint.clone(),
-  );
-  let bucket_storage = Arc::new(S3BucketStorage::from_bucket_impl(
-    s3_client.clone(),
+  let bucket_client = get_bucket_client(config).await?;
+  let bucket_storage = Arc::new(AppBucketStorage::new(
+    bucket_client.clone(),
     pg_pool.clone(),
   ));
 
@@ -222,7 +242,7 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
         Arc::new(PublishedCollabS3StoreWithPostgresFallback::new(
           metrics.published_collab_metrics.clone(),
           pg_pool.clone(),
-          s3_client.clone(),
+          bucket_client.clone(),
         ))
       },
     };
@@ -241,8 +261,6 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
   )
   .await?;
 
-  info!("Setup AppFlowy AI: {}", config.appflowy_ai.url());
-  let appflowy_ai_client = AppFlowyAIClient::new(&config.appflowy_ai.url());
   // Pg listeners
   info!("Setting up Pg listeners...");
   let pg_listeners = Arc::new(PgListeners::new(&pg_pool).await?);
@@ -303,11 +321,18 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
     thread_pool.clone(),
     redis_conn_manager.clone(),
     pg_pool.clone(),
-    s3_client.clone(),
+    bucket_client.clone(),
     metrics.collab_metrics.clone(),
     config.collab.s3_collab_threshold as usize,
   );
 
+  let snapshot_control = SnapshotControl::new(
+    pg_pool.clone(),
+    bucket_client.clone(),
+    metrics.collab_metrics.clone(),
+  )
+  .await;
+
   let collab_access_control_storage = Arc::new(CollabStoreImpl::new(
     collab_cache.clone(),
     collab_access_control.clone(),
@@ -334,14 +359,68 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
     });
   }
 
+  info!("Setting up webhook delivery worker...");
+  let webhook_delivery_interval = get_env_var("APPFLOWY_WEBHOOK_DELIVERY_INTERVAL_SECS", "5")
+    .parse::<u64>()
+    .unwrap_or(5);
+  let webhook_max_attempts = get_env_var("APPFLOWY_WEBHOOK_MAX_ATTEMPTS", "8")
+    .parse::<i32>()
+    .unwrap_or(8);
+  let webhook_pg_pool = pg_pool.clone();
+  tokio::spawn(async move {
+    let webhook_delivery_worker = WebhookDeliveryWorker::new(
+      webhook_pg_pool,
+      webhook_delivery_interval,
+      webhook_max_attempts,
+    );
+    webhook_delivery_worker.start_task().await;
+  });
+  let webhook_delivery_retention_days =
+    get_env_var("APPFLOWY_WEBHOOK_DELIVERY_RETENTION_DAYS", "30")
+      .parse::<u32>()
+      .unwrap_or(30);
+  if webhook_delivery_retention_days > 0 {
+    tokio::spawn(run_webhook_delivery_cleanup(
+      pg_pool.clone(),
+      webhook_delivery_retention_days,
+    ));
+  }
+
+  let publish_analytics_retention_days = config.published_collab.analytics_retention_days;
+  if publish_analytics_retention_days > 0
+    && config
+      .published_collab
+      .analytics_secret
+      .expose_secret()
+      .is_empty()
+  {
+    warn!("APPFLOWY_PUBLISH_ANALYTICS_SECRET is not set, published page views are not recorded");
+  }
+  if publish_analytics_retention_days > 0 {
+    info!("Setting up published view event cleanup...");
+    let task_pg_pool = pg_pool.clone();
+    tokio::spawn(run_published_view_event_cleanup(
+      task_pg_pool,
+      publish_analytics_retention_days,
+    ));
+  }
+
+  let verified_publish_domains = VerifiedPublishDomains::default();
+  tokio::spawn(run_publish_custom_domain_refresh(
+    pg_pool.clone(),
+    config
+      .published_collab
+      .custom_domain_dns_resolver_url
+      .clone(),
+    verified_publish_domains.clone(),
+  ));
+
   info!("Setting up Indexer scheduler...");
-  let (open_ai_config, azure_ai_config) = get_open_ai_config();
   let embedder_config = IndexerConfiguration {
     enable: get_env_var("APPFLOWY_INDEXER_ENABLED", "true")
       .parse::<bool>()
       .unwrap_or(true),
-    open_ai_config,
-    azure_ai_config,
+    embedder_config: get_embedder_config(),
     embedding_buffer_size: appflowy_collaborate::config::get_env_var(
       "APPFLOWY_INDEXER_EMBEDDING_BUFFER_SIZE",
       "5000",
@@ -365,8 +444,20 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
     redis_stream_router.clone(),
     awareness_gossip.clone(),
     indexer_scheduler.clone(),
+    snapshot_control.clone(),
+    pg_pool.clone(),
   );
   let ws_server = WsServer::new(manager).start();
+  tokio::spawn(forward_workspace_notifications(
+    pg_listeners.clone(),
+    ws_server.clone(),
+  ));
+  let ai_client = get_ai_provider(config, &pg_pool, &indexer_scheduler)?;
+  let observer_quota = ObserverQuota::new(
+    redis_conn_manager.clone(),
+    config.websocket.observer_max_sessions,
+    config.websocket.observer_max_subscriptions,
+  );
 
   info!("Application state initialized");
   Ok(AppState {
@@ -385,14 +476,17 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
     realtime_access_control,
     bucket_storage,
     published_collab_store,
-    bucket_client: s3_client,
+    bucket_client,
     pg_listeners,
     metrics,
     gotrue_admin,
     mailer,
-    ai_client: appflowy_ai_client,
+    ai_client,
     indexer_scheduler,
     ws_server,
+    observer_quota,
+    snapshot_control,
+    verified_publish_domains,
   })
 }
 
@@ -441,6 +535,70 @@ async fn get_redis_client(
   Ok((manager, router.into(), awareness_gossip.into()))
 }
 
+fn get_ai_provider(
+  config: &Config,
+  pg_pool: &PgPool,
+  indexer_scheduler: &Arc<IndexerScheduler>,
+) -> Result<AIProvider, Error> {
+  match config.appflowy_ai.provider {
+    AIProviderKind::AppFlowyAI => {
+      info!("Setup AppFlowy AI: {}", config.appflowy_ai.url());
+      Ok(AppFlowyAIClient::new(&config.appflowy_ai.url()).into())
+    },
+    AIProviderKind::OpenAI => {
+      let ai_tool = create_ai_tool(&config.azure_ai_config, &config.open_ai_config).context(
+        "AI_PROVIDER=openai requires AI_OPENAI_API_KEY or the Azure OpenAI API settings",
+      )?;
+      info!(
+        "Setup in-process AI with model: {}",
+        config.appflowy_ai.chat_model
+      );
+      Ok(
+        LLMProvider::new(
+          ai_tool,
+          config.appflowy_ai.chat_model.clone(),
+          pg_pool.clone(),
+          indexer_scheduler.clone(),
+        )
+        .into(),
+      )
+    },
+  }
+}
+
+async fn get_bucket_client(config: &Config) -> Result<AppBucketClient, Error> {
+  match config.bucket_storage.backend {
+    BucketStorageBackend::S3 => {
+      info!("Setting up S3 bucket...");
+      Ok(
+        AwsS3BucketClientImpl::new(
+          get_aws_s3_client(&config.s3).await?,
+          config.s3.bucket.clone(),
+          config.s3.minio_url.clone(),
+          config.s3.presigned_url_endpoint.clone(),
+        )
+        .into(),
+      )
+    },
+    BucketStorageBackend::Filesystem => {
+      let fs_setting = &config.bucket_storage.fs;
+      info!("Setting up filesystem storage at {}...", fs_setting.root);
+      let client = FsBucketClientImpl::new(
+        &fs_setting.root,
+        fs_setting.presigned_url_endpoint.clone(),
+        fs_setting.presign_secret.expose_secret().clone(),
+      )
+      .await?;
+      // Multipart uploads are only handled by this server, so the worker doesn't clean them.
+      tokio::spawn(run_expired_upload_cleanup(
+        client.clone(),
+        Duration::from_secs(fs_setting.upload_expire_secs),
+      ));
+      Ok(client.into())
+    },
+  }
+}
+
 pub async fn get_aws_s3_client(s3_setting: &S3Setting) -> Result<aws_sdk_s3::Client, Error> {
   let credentials = Credentials::new(
     s3_setting.access_key.clone(),
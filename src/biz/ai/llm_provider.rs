@@ -0,0 +1,513 @@
+use app_error::AppError;
+use appflowy_ai_client::dto::{
+  AvailableModel, ChatAnswer, ChatQuestion, CompleteTextParams, CompletionType, ModelList,
+  OutputLayout, RelatedQuestion, RepeatedRelatedQuestion, ResponseFormat, SummarizeRowResponse,
+  TranslateRowData, TranslateRowResponse, STREAM_ANSWER_KEY, STREAM_METADATA_KEY,
+};
+use bytes::Bytes;
+use database::chat::chat_ops::select_chat_messages;
+use database::index::{search_attachments, search_documents, SearchDocumentParams};
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+use indexer::scheduler::IndexerScheduler;
+use indexer::vector::embedder::EmbeddingInput;
+use llm_client::chat::{AITool, LLMMessage, LLMTextStream};
+use serde_json::{json, Map, Value};
+use shared_entity::dto::chat_dto::{ChatAuthorType, GetChatMessageParams, MessageCursor};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tracing::{error, trace};
+use uuid::Uuid;
+
+use super::provider::AIByteStream;
+
+/// Number of previous messages of the chat sent along with a question.
+const CHAT_HISTORY_LIMIT: u64 = 10;
+/// Number of workspace documents sent to the model as the context of a question.
+const RAG_DOCUMENT_LIMIT: i32 = 5;
+/// Documents less similar to the question than this are not used as its context.
+const RAG_MIN_SCORE: f64 = 0.3;
+const RELATED_QUESTION_COUNT: usize = 3;
+
+const CHAT_SYSTEM_PROMPT: &str = r#"
+You are AppFlowy AI, a helpful assistant answering the questions of the user about their workspace.
+When context from the workspace is provided, base your answer on it. If the context does not
+contain the answer, answer from your own knowledge and say so. Answer in the language of the question.
+"#;
+
+const RELATED_QUESTION_PROMPT: &str = r#"
+Suggest follow-up questions the user could ask after the conversation below. Reply with one
+question per line, without numbering or any other text.
+"#;
+
+/// Answers chat and completion requests in-process with the OpenAI or Azure OpenAI API, for
+/// deployments which don't run the AppFlowy AI service. Questions are answered with the documents
+/// of the workspace found by the embeddings search as their context.
+pub struct LLMProvider {
+  ai_tool: AITool,
+  chat_model: String,
+  pg_pool: PgPool,
+  indexer_scheduler: Arc<IndexerScheduler>,
+}
+
+impl LLMProvider {
+  pub fn new(
+    ai_tool: AITool,
+    chat_model: String,
+    pg_pool: PgPool,
+    indexer_scheduler: Arc<IndexerScheduler>,
+  ) -> Self {
+    Self {
+      ai_tool,
+      chat_model,
+      pg_pool,
+      indexer_scheduler,
+    }
+  }
+
+  pub fn model_list(&self) -> ModelList {
+    ModelList {
+      models: vec![AvailableModel {
+        name: self.chat_model.clone(),
+        metadata: None,
+      }],
+    }
+  }
+
+  /// Streams the completion as plain text.
+  pub async fn stream_completion_text(
+    &self,
+    params: CompleteTextParams,
+  ) -> Result<AIByteStream, AppError> {
+    let stream = self.stream_completion(params).await?;
+    Ok(stream.map_ok(Bytes::from).boxed())
+  }
+
+  /// Streams the completion as JSON objects keyed by [STREAM_ANSWER_KEY].
+  pub async fn stream_completion_v2(
+    &self,
+    params: CompleteTextParams,
+  ) -> Result<AIByteStream, AppError> {
+    let stream = self.stream_completion(params).await?;
+    Ok(
+      stream
+        .map_ok(|text| stream_value(STREAM_ANSWER_KEY, Value::String(text)))
+        .boxed(),
+    )
+  }
+
+  async fn stream_completion(&self, params: CompleteTextParams) -> Result<LLMTextStream, AppError> {
+    if params.text.is_empty() {
+      return Err(AppError::InvalidRequest("Empty text".to_string()));
+    }
+
+    let mut system = completion_instruction(&params);
+    system.push_str(format_instruction(&params.format));
+    let mut history = vec![];
+    if let Some(metadata) = params.metadata {
+      if let (Some(workspace_id), Some(rag_ids)) = (metadata.workspace_id, metadata.rag_ids) {
+        let (context, _) = self
+          .related_context(&workspace_id, &rag_ids, &params.text)
+          .await?;
+        system.push_str(&context);
+      }
+      for message in metadata.completion_history.unwrap_or_default() {
+        history.push(match message.role.as_str() {
+          "ai" => LLMMessage::assistant(message.content),
+          "system" => LLMMessage::system(message.content),
+          _ => LLMMessage::user(message.content),
+        });
+      }
+    }
+
+    let mut messages = vec![LLMMessage::system(system)];
+    messages.extend(history);
+    messages.push(LLMMessage::user(params.text));
+    self.ai_tool.stream_chat(&self.chat_model, messages).await
+  }
+
+  pub async fn summarize_row(
+    &self,
+    content: &Map<String, Value>,
+  ) -> Result<SummarizeRowResponse, AppError> {
+    if content.is_empty() {
+      return Err(AppError::InvalidRequest("Empty content".to_string()));
+    }
+    let messages = vec![
+      LLMMessage::system(
+        "Summarize the database row below, given as a JSON object of its cells, in one or two \
+         sentences. Reply with the summary only.",
+      ),
+      LLMMessage::user(Value::Object(content.clone()).to_string()),
+    ];
+    let text = self.ai_tool.chat(&self.chat_model, messages).await?;
+    Ok(SummarizeRowResponse { text })
+  }
+
+  pub async fn translate_row(
+    &self,
+    data: TranslateRowData,
+  ) -> Result<TranslateRowResponse, AppError> {
+    let cells = data
+      .cells
+      .iter()
+      .map(|cell| (cell.title.clone(), cell.content.clone()))
+      .collect::<Map<String, Value>>();
+    let target = if data.include_header {
+      "Translate both the keys and the values"
+    } else {
+      "Translate the values only and keep the keys unchanged"
+    };
+    let messages = vec![
+      LLMMessage::system(format!(
+        "Translate the cells of the database row below, given as a JSON object, to {}. {}. \
+         Reply with the translated JSON object only.",
+        data.language, target
+      )),
+      LLMMessage::user(Value::Object(cells).to_string()),
+    ];
+    let answer = self.ai_tool.chat(&self.chat_model, messages).await?;
+    let items = serde_json::from_str::<HashMap<String, String>>(strip_code_fence(&answer))
+      .map_err(|err| AppError::Unhandled(format!("Invalid row translation: {}", err)))?;
+    Ok(TranslateRowResponse { items: vec![items] })
+  }
+
+  pub async fn send_question(
+    &self,
+    workspace_id: &str,
+    chat_id: &str,
+    question_id: i64,
+    content: &str,
+    rag_ids: &[String],
+  ) -> Result<ChatAnswer, AppError> {
+    let (messages, sources) = self
+      .question_messages(
+        workspace_id,
+        chat_id,
+        Some(question_id),
+        content,
+        rag_ids,
+        None,
+      )
+      .await?;
+    let content = self.ai_tool.chat(&self.chat_model, messages).await?;
+    Ok(ChatAnswer {
+      content,
+      metadata: Some(sources),
+    })
+  }
+
+  /// Streams the answer as plain text.
+  pub async fn stream_question(
+    &self,
+    workspace_id: &str,
+    chat_id: &str,
+    content: &str,
+    rag_ids: &[String],
+  ) -> Result<AIByteStream, AppError> {
+    let (messages, _) = self
+      .question_messages(workspace_id, chat_id, None, content, rag_ids, None)
+      .await?;
+    let stream = self.ai_tool.stream_chat(&self.chat_model, messages).await?;
+    Ok(stream.map_ok(Bytes::from).boxed())
+  }
+
+  /// Streams the sources of the answer, keyed by [STREAM_METADATA_KEY], followed by the answer,
+  /// keyed by [STREAM_ANSWER_KEY].
+  pub async fn stream_question_v3(&self, question: ChatQuestion) -> Result<AIByteStream, AppError> {
+    if question.format.output_content.is_image() {
+      return Err(AppError::FeatureNotAvailable(
+        "Image generation is not supported by the OpenAI provider".to_string(),
+      ));
+    }
+
+    let question_id = question
+      .data
+      .message_id
+      .as_deref()
+      .and_then(|id| id.parse::<i64>().ok());
+    let (messages, sources) = self
+      .question_messages(
+        &question.metadata.workspace_id,
+        &question.chat_id,
+        question_id,
+        &question.data.content,
+        &question.metadata.rag_ids,
+        Some(&question.format),
+      )
+      .await?;
+    let answer = self.ai_tool.stream_chat(&self.chat_model, messages).await?;
+    let metadata =
+      stream::once(async move { Ok::<_, AppError>(stream_value(STREAM_METADATA_KEY, sources)) });
+    let answer = answer.map_ok(|text| stream_value(STREAM_ANSWER_KEY, Value::String(text)));
+    Ok(metadata.chain(answer).boxed())
+  }
+
+  pub async fn get_related_question(
+    &self,
+    chat_id: &str,
+    message_id: i64,
+  ) -> Result<RepeatedRelatedQuestion, AppError> {
+    let mut messages = vec![LLMMessage::system(RELATED_QUESTION_PROMPT)];
+    // the conversation up to, and including, the message
+    messages.extend(self.chat_history(chat_id, message_id + 1).await?);
+
+    let answer = self.ai_tool.chat(&self.chat_model, messages).await?;
+    Ok(RepeatedRelatedQuestion {
+      message_id,
+      items: parse_related_questions(&answer),
+    })
+  }
+
+  /// Builds the messages sent to the model to answer the question: the instructions with the
+  /// context found in the workspace, the previous messages of the chat, then the question itself.
+  /// Returns the messages along with the sources of the context.
+  async fn question_messages(
+    &self,
+    workspace_id: &str,
+    chat_id: &str,
+    question_id: Option<i64>,
+    content: &str,
+    rag_ids: &[String],
+    format: Option<&ResponseFormat>,
+  ) -> Result<(Vec<LLMMessage>, Value), AppError> {
+    let workspace_id = Uuid::parse_str(workspace_id)?;
+    let (context, sources) = self
+      .related_context(&workspace_id, rag_ids, content)
+      .await?;
+
+    let mut system = CHAT_SYSTEM_PROMPT.to_string();
+    if let Some(format) = format {
+      system.push_str(format_instruction(format));
+    }
+    system.push_str(&context);
+
+    let mut messages = vec![LLMMessage::system(system)];
+    if let Some(question_id) = question_id {
+      messages.extend(self.chat_history(chat_id, question_id).await?);
+    }
+    messages.push(LLMMessage::user(content));
+    Ok((messages, sources))
+  }
+
+  /// Previous messages of the chat, oldest first.
+  async fn chat_history(
+    &self,
+    chat_id: &str,
+    before_message_id: i64,
+  ) -> Result<Vec<LLMMessage>, AppError> {
+    let mut txn = self.pg_pool.begin().await?;
+    let mut messages = select_chat_messages(
+      &mut txn,
+      chat_id,
+      GetChatMessageParams {
+        cursor: MessageCursor::BeforeMessageId(before_message_id),
+        limit: CHAT_HISTORY_LIMIT,
+      },
+    )
+    .await?
+    .messages;
+    txn.commit().await?;
+
+    messages.sort_by_key(|message| message.message_id);
+    Ok(
+      messages
+        .into_iter()
+        .map(|message| match message.author.author_type {
+          ChatAuthorType::AI => LLMMessage::assistant(message.content),
+          _ => LLMMessage::user(message.content),
+        })
+        .collect(),
+    )
+  }
+
+  /// Searches the documents referenced by `rag_ids` for the passages most similar to the question.
+  /// Returns them formatted as the context of the question, along with their sources.
+  async fn related_context(
+    &self,
+    workspace_id: &Uuid,
+    rag_ids: &[String],
+    question: &str,
+  ) -> Result<(String, Value), AppError> {
+    let searchable_view_ids = rag_ids
+      .iter()
+      .filter_map(|id| Uuid::parse_str(id).ok())
+      .collect::<Vec<_>>();
+    if searchable_view_ids.is_empty() || !self.indexer_scheduler.is_embedder_available() {
+      return Ok((String::new(), json!([])));
+    }
+
+    let (embedding_model, mut embeddings_resp) = self
+      .indexer_scheduler
+      .create_search_embeddings(EmbeddingInput::String(question.to_string()))
+      .await?;
+    let total_tokens = embeddings_resp.usage.total_tokens;
+    let embedding = match embeddings_resp.data.pop() {
+      Some(embedding) => embedding.embedding,
+      None => return Ok((String::new(), json!([]))),
+    };
+    let params = SearchDocumentParams {
+      user_id: 0,
+      workspace_id: *workspace_id,
+      limit: RAG_DOCUMENT_LIMIT,
+      preview: 0,
+      embedding,
+      embedding_model: embedding_model.name().to_string(),
+      searchable_view_ids,
+      searchable_database_ids: vec![],
+      score: RAG_MIN_SCORE,
+    };
+    let mut results = search_documents(&self.pg_pool, params.clone(), total_tokens).await?;
+    match search_attachments(&self.pg_pool, params).await {
+      Ok(attachments) => results.extend(attachments),
+      Err(err) => error!("[Chat] failed to search attachments: {}", err),
+    }
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results.truncate(RAG_DOCUMENT_LIMIT as usize);
+    trace!("[Chat] found {} related documents", results.len());
+    if results.is_empty() {
+      return Ok((String::new(), json!([])));
+    }
+
+    let mut context = "\n##Context##\n".to_string();
+    let mut sources = vec![];
+    let mut seen = HashSet::new();
+    for result in results {
+      context.push_str(&result.content);
+      context.push_str("\n\n");
+      let (id, name) = match &result.attachment {
+        Some(attachment) => (attachment.view_id.unwrap_or(result.object_id), "attachment"),
+        None => (result.object_id, "document"),
+      };
+      if seen.insert(id) {
+        sources.push(json!({ "id": id, "source": "appflowy", "name": name }));
+      }
+    }
+    Ok((context, Value::Array(sources)))
+  }
+}
+
+fn completion_instruction(params: &CompleteTextParams) -> String {
+  let instruction = match params.completion_type {
+    Some(CompletionType::ImproveWriting) => {
+      "Improve the writing of the text given by the user. Reply with the improved text only."
+    },
+    Some(CompletionType::SpellingAndGrammar) => {
+      "Fix the spelling and grammar of the text given by the user. Reply with the corrected text \
+       only."
+    },
+    Some(CompletionType::MakeShorter) => {
+      "Make the text given by the user shorter while keeping its meaning. Reply with the shortened \
+       text only."
+    },
+    Some(CompletionType::MakeLonger) => {
+      "Make the text given by the user longer by elaborating on it. Reply with the lengthened text \
+       only."
+    },
+    Some(CompletionType::ContinueWriting) => {
+      "Continue writing the text given by the user. Reply with the continuation only."
+    },
+    Some(CompletionType::Explain) => "Explain the text given by the user in simple terms.",
+    Some(CompletionType::CustomPrompt) => {
+      if let Some(prompt) = params
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.custom_prompt.as_ref())
+      {
+        return prompt.system.clone();
+      }
+      "Follow the instructions given by the user."
+    },
+    Some(CompletionType::AskAI) | None => "Answer the request of the user.",
+  };
+  format!(
+    "{} Answer in the language of the text given by the user.",
+    instruction
+  )
+}
+
+fn format_instruction(format: &ResponseFormat) -> &'static str {
+  match format.output_layout {
+    OutputLayout::Paragraph => "\nFormat the answer as paragraphs.",
+    OutputLayout::BulletList => "\nFormat the answer as a bullet list.",
+    OutputLayout::NumberedList => "\nFormat the answer as a numbered list.",
+    OutputLayout::SimpleTable => "\nFormat the answer as a markdown table.",
+    OutputLayout::Flex => "",
+  }
+}
+
+/// Reads one question per line, ignoring the list markers the model may add despite the prompt.
+fn parse_related_questions(answer: &str) -> Vec<RelatedQuestion> {
+  answer
+    .lines()
+    .map(|line| {
+      let line = line.trim();
+      let line = match line.split_once(['.', ')']) {
+        Some((number, rest))
+          if number.chars().all(|c| c.is_ascii_digit()) && !number.is_empty() =>
+        {
+          rest
+        },
+        _ => line,
+      };
+      line.trim_start_matches(['-', '*']).trim()
+    })
+    .filter(|line| !line.is_empty())
+    .take(RELATED_QUESTION_COUNT)
+    .map(|line| RelatedQuestion {
+      content: line.to_string(),
+      metadata: None,
+    })
+    .collect()
+}
+
+/// Models often wrap JSON answers in a markdown code block.
+fn strip_code_fence(answer: &str) -> &str {
+  let answer = answer.trim();
+  match answer.strip_prefix("```") {
+    Some(rest) => rest
+      .trim_start_matches("json")
+      .trim_end_matches("```")
+      .trim(),
+    None => answer,
+  }
+}
+
+/// Encodes a value of the stream, as read by the `QuestionStream` and `CompletionStream` of the
+/// client.
+fn stream_value(key: &str, value: Value) -> Bytes {
+  let mut object = Map::new();
+  object.insert(key.to_string(), value);
+  Bytes::from(Value::Object(object).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_related_questions_without_list_markers() {
+    let answer = "1. What is AppFlowy?\n\n- How do I share a page?\n* Can I self-host it?\nExtra?";
+    let questions = parse_related_questions(answer)
+      .into_iter()
+      .map(|question| question.content)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      questions,
+      vec![
+        "What is AppFlowy?",
+        "How do I share a page?",
+        "Can I self-host it?"
+      ]
+    );
+  }
+
+  #[test]
+  fn strip_json_code_fence() {
+    assert_eq!(
+      strip_code_fence("```json\n{\"a\": \"b\"}\n```"),
+      "{\"a\": \"b\"}"
+    );
+    assert_eq!(strip_code_fence(" {\"a\": \"b\"} "), "{\"a\": \"b\"}");
+  }
+}
@@ -0,0 +1,2 @@
+pub mod llm_provider;
+pub mod provider;
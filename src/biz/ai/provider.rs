@@ -0,0 +1,273 @@
+use crate::biz::ai::llm_provider::LLMProvider;
+use app_error::AppError;
+use appflowy_ai_client::client::AppFlowyAIClient;
+use appflowy_ai_client::dto::{
+  CalculateSimilarityParams, ChatAnswer, ChatQuestion, CompleteTextParams, CreateChatContext,
+  LocalAIConfig, MessageData, ModelList, QuestionMetadata, RepeatedRelatedQuestion, ResponseFormat,
+  SimilarityResponse, SummarizeRowResponse, TranslateRowData, TranslateRowResponse,
+};
+use bytes::Bytes;
+use futures_util::stream::BoxStream;
+use futures_util::{StreamExt, TryStreamExt};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+pub type AIByteStream = BoxStream<'static, Result<Bytes, AppError>>;
+
+/// Answers the chat and completion requests, either through the AppFlowy AI service or
+/// in-process. Selected per deployment with the `AI_PROVIDER` environment variable.
+#[derive(Clone)]
+pub enum AIProvider {
+  AppFlowyAI(AppFlowyAIClient),
+  OpenAI(Arc<LLMProvider>),
+}
+
+impl From<AppFlowyAIClient> for AIProvider {
+  fn from(client: AppFlowyAIClient) -> Self {
+    AIProvider::AppFlowyAI(client)
+  }
+}
+
+impl From<LLMProvider> for AIProvider {
+  fn from(provider: LLMProvider) -> Self {
+    AIProvider::OpenAI(Arc::new(provider))
+  }
+}
+
+impl AIProvider {
+  pub async fn stream_completion_text(
+    &self,
+    params: CompleteTextParams,
+    model: &str,
+  ) -> Result<AIByteStream, AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => Ok(
+        client
+          .stream_completion_text(params, model)
+          .await?
+          .map_err(AppError::from)
+          .boxed(),
+      ),
+      AIProvider::OpenAI(provider) => provider.stream_completion_text(params).await,
+    }
+  }
+
+  pub async fn stream_completion_v2(
+    &self,
+    params: CompleteTextParams,
+    model: &str,
+  ) -> Result<AIByteStream, AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => Ok(
+        client
+          .stream_completion_v2(params, model)
+          .await?
+          .map_err(AppError::from)
+          .boxed(),
+      ),
+      AIProvider::OpenAI(provider) => provider.stream_completion_v2(params).await,
+    }
+  }
+
+  pub async fn summarize_row(
+    &self,
+    params: &Map<String, Value>,
+    model: &str,
+  ) -> Result<SummarizeRowResponse, AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => Ok(client.summarize_row(params, model).await?),
+      AIProvider::OpenAI(provider) => provider.summarize_row(params).await,
+    }
+  }
+
+  pub async fn translate_row(
+    &self,
+    data: TranslateRowData,
+    model: &str,
+  ) -> Result<TranslateRowResponse, AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => Ok(client.translate_row(data, model).await?),
+      AIProvider::OpenAI(provider) => provider.translate_row(data).await,
+    }
+  }
+
+  pub async fn create_chat_text_context(&self, context: CreateChatContext) -> Result<(), AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => Ok(client.create_chat_text_context(context).await?),
+      AIProvider::OpenAI(_) => Err(unsupported("Chat text context")),
+    }
+  }
+
+  pub async fn send_question(
+    &self,
+    workspace_id: &str,
+    chat_id: &str,
+    question_id: i64,
+    content: &str,
+    rag_ids: Vec<String>,
+    model: &str,
+    metadata: Option<Value>,
+  ) -> Result<ChatAnswer, AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => Ok(
+        client
+          .send_question(workspace_id, chat_id, question_id, content, model, metadata)
+          .await?,
+      ),
+      AIProvider::OpenAI(provider) => {
+        provider
+          .send_question(workspace_id, chat_id, question_id, content, &rag_ids)
+          .await
+      },
+    }
+  }
+
+  pub async fn stream_question(
+    &self,
+    workspace_id: String,
+    chat_id: &str,
+    content: &str,
+    metadata: Option<Value>,
+    rag_ids: Vec<String>,
+    model: &str,
+  ) -> Result<AIByteStream, AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => Ok(
+        client
+          .stream_question(workspace_id, chat_id, content, metadata, rag_ids, model)
+          .await?
+          .map_err(AppError::from)
+          .boxed(),
+      ),
+      AIProvider::OpenAI(provider) => {
+        provider
+          .stream_question(&workspace_id, chat_id, content, &rag_ids)
+          .await
+      },
+    }
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  pub async fn stream_question_v2(
+    &self,
+    workspace_id: String,
+    chat_id: &str,
+    question_id: i64,
+    content: &str,
+    metadata: Option<Value>,
+    rag_ids: Vec<String>,
+    model: &str,
+  ) -> Result<AIByteStream, AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => Ok(
+        client
+          .stream_question_v2(
+            workspace_id,
+            chat_id,
+            question_id,
+            content,
+            metadata,
+            rag_ids,
+            model,
+          )
+          .await?
+          .map_err(AppError::from)
+          .boxed(),
+      ),
+      AIProvider::OpenAI(provider) => {
+        let question = ChatQuestion {
+          chat_id: chat_id.to_string(),
+          data: MessageData {
+            content: content.to_string(),
+            metadata,
+            message_id: Some(question_id.to_string()),
+          },
+          format: ResponseFormat::default(),
+          metadata: QuestionMetadata {
+            workspace_id,
+            rag_ids,
+          },
+        };
+        provider.stream_question_v3(question).await
+      },
+    }
+  }
+
+  pub async fn stream_question_v3(
+    &self,
+    model: &str,
+    question: ChatQuestion,
+    timeout_secs: Option<u64>,
+  ) -> Result<AIByteStream, AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => Ok(
+        client
+          .stream_question_v3(model, question, timeout_secs)
+          .await?
+          .map_err(AppError::from)
+          .boxed(),
+      ),
+      AIProvider::OpenAI(provider) => provider.stream_question_v3(question).await,
+    }
+  }
+
+  pub async fn get_related_question(
+    &self,
+    chat_id: &str,
+    message_id: &i64,
+    model: &str,
+  ) -> Result<RepeatedRelatedQuestion, AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => Ok(
+        client
+          .get_related_question(chat_id, message_id, model)
+          .await?,
+      ),
+      AIProvider::OpenAI(provider) => provider.get_related_question(chat_id, *message_id).await,
+    }
+  }
+
+  pub async fn regenerate_image(&self, source_metadata: Value) -> Result<(), AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => Ok(client.regenerate_image(source_metadata).await?),
+      AIProvider::OpenAI(_) => Err(unsupported("Image generation")),
+    }
+  }
+
+  pub async fn get_local_ai_config(
+    &self,
+    platform: &str,
+    app_version: Option<String>,
+  ) -> Result<LocalAIConfig, AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => {
+        Ok(client.get_local_ai_config(platform, app_version).await?)
+      },
+      AIProvider::OpenAI(_) => Err(unsupported("Local AI")),
+    }
+  }
+
+  pub async fn get_model_list(&self) -> Result<ModelList, AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => Ok(client.get_model_list().await?),
+      AIProvider::OpenAI(provider) => Ok(provider.model_list()),
+    }
+  }
+
+  pub async fn calculate_similarity(
+    &self,
+    params: CalculateSimilarityParams,
+  ) -> Result<SimilarityResponse, AppError> {
+    match self {
+      AIProvider::AppFlowyAI(client) => Ok(client.calculate_similarity(params).await?),
+      AIProvider::OpenAI(_) => Err(unsupported("Similarity calculation")),
+    }
+  }
+}
+
+fn unsupported(feature: &str) -> AppError {
+  AppError::FeatureNotAvailable(format!(
+    "{} is not supported by the OpenAI provider",
+    feature
+  ))
+}
@@ -0,0 +1,214 @@
+use app_error::AppError;
+use database::audit::{insert_audit_log, select_audit_logs, AuditLogFilter, AuditLogInsert};
+use database::pg_row::AFAuditLogRow;
+use shared_entity::dto::audit_dto::{AFAuditLogEntry, AuditAction, QueryAuditLogParams};
+use sqlx::{Executor, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+const DEFAULT_AUDIT_LOG_PAGE_SIZE: i64 = 50;
+const MAX_AUDIT_LOG_PAGE_SIZE: i64 = 500;
+const MAX_AUDIT_LOG_EXPORT_SIZE: i64 = 100_000;
+
+const CSV_HEADER: [&str; 10] = [
+  "created_at",
+  "action",
+  "actor_uid",
+  "actor_email",
+  "target",
+  "ip_address",
+  "user_agent",
+  "before",
+  "after",
+  "event_id",
+];
+
+/// The user behind an audited action, with the client the request came from.
+#[derive(Debug, Clone)]
+pub struct AuditActor {
+  pub uid: i64,
+  /// Address of the client, forwarded by the trusted proxies of the server when it is behind them.
+  pub ip_address: Option<String>,
+  pub user_agent: Option<String>,
+}
+
+/// Appends an entry to the audit log of the workspace. Pass the transaction of the audited
+/// action when there is one, so that the action is not committed without its entry.
+pub async fn record_audit_event<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  actor: &AuditActor,
+  action: AuditAction,
+  target: Option<&str>,
+  before: Option<serde_json::Value>,
+  after: Option<serde_json::Value>,
+) -> Result<(), AppError> {
+  insert_audit_log(
+    executor,
+    AuditLogInsert {
+      workspace_id,
+      actor_uid: Some(actor.uid),
+      action: action.as_str(),
+      target,
+      ip_address: actor.ip_address.as_deref(),
+      user_agent: actor.user_agent.as_deref(),
+      before_value: before.as_ref(),
+      after_value: after.as_ref(),
+    },
+  )
+  .await
+}
+
+/// An audit log entry prepared before its action runs, for actions whose transaction is opened
+/// further down, see [record_audit_events].
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+  pub actor: AuditActor,
+  pub action: AuditAction,
+  pub target: Option<String>,
+  pub before: Option<serde_json::Value>,
+  pub after: Option<serde_json::Value>,
+}
+
+pub async fn record_audit_events(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  events: &[AuditEvent],
+) -> Result<(), AppError> {
+  for event in events {
+    record_audit_event(
+      txn.as_mut(),
+      workspace_id,
+      &event.actor,
+      event.action,
+      event.target.as_deref(),
+      event.before.clone(),
+      event.after.clone(),
+    )
+    .await?;
+  }
+  Ok(())
+}
+
+pub async fn list_audit_logs(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  params: &QueryAuditLogParams,
+) -> Result<Vec<AFAuditLogEntry>, AppError> {
+  let limit = params
+    .limit
+    .unwrap_or(DEFAULT_AUDIT_LOG_PAGE_SIZE)
+    .clamp(1, MAX_AUDIT_LOG_PAGE_SIZE);
+  let offset = params.offset.unwrap_or(0).max(0);
+  let entries = select_audit_logs(pg_pool, workspace_id, &to_filter(params), limit, offset)
+    .await?
+    .into_iter()
+    .map(to_af_audit_log_entry)
+    .collect();
+  Ok(entries)
+}
+
+/// Returns the matching entries as CSV, most recent first, ignoring the pagination of the
+/// query.
+pub async fn export_audit_logs_csv(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  params: &QueryAuditLogParams,
+) -> Result<String, AppError> {
+  let rows = select_audit_logs(
+    pg_pool,
+    workspace_id,
+    &to_filter(params),
+    MAX_AUDIT_LOG_EXPORT_SIZE,
+    0,
+  )
+  .await?;
+
+  let mut csv = csv_line(CSV_HEADER.iter().map(|s| s.to_string()));
+  for row in rows {
+    let json =
+      |value: Option<serde_json::Value>| value.map(|value| value.to_string()).unwrap_or_default();
+    csv.push_str(&csv_line([
+      row.created_at.to_rfc3339(),
+      row.action,
+      row.actor_uid.map(|uid| uid.to_string()).unwrap_or_default(),
+      row.actor_email.unwrap_or_default(),
+      row.target.unwrap_or_default(),
+      row.ip_address.unwrap_or_default(),
+      row.user_agent.unwrap_or_default(),
+      json(row.before_value),
+      json(row.after_value),
+      row.event_id.to_string(),
+    ]));
+  }
+  Ok(csv)
+}
+
+fn to_filter(params: &QueryAuditLogParams) -> AuditLogFilter<'_> {
+  AuditLogFilter {
+    action: params.action.map(|action| action.as_str()),
+    actor_uid: params.actor_uid,
+    target: params.target.as_deref(),
+    from: params.from,
+    to: params.to,
+  }
+}
+
+fn csv_line(fields: impl IntoIterator<Item = String>) -> String {
+  let mut line = fields
+    .into_iter()
+    .map(|field| escape_csv_field(&field))
+    .collect::<Vec<_>>()
+    .join(",");
+  line.push_str("\r\n");
+  line
+}
+
+/// Quotes the field when needed, and neutralizes values that spreadsheet applications would
+/// evaluate as formulas, as targets and user agents are chosen by users.
+fn escape_csv_field(field: &str) -> String {
+  let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+    format!("'{}", field)
+  } else {
+    field.to_string()
+  };
+  if field.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", field.replace('"', "\"\""))
+  } else {
+    field
+  }
+}
+
+fn to_af_audit_log_entry(row: AFAuditLogRow) -> AFAuditLogEntry {
+  AFAuditLogEntry {
+    event_id: row.event_id,
+    workspace_id: row.workspace_id,
+    actor_uid: row.actor_uid,
+    actor_email: row.actor_email,
+    action: row.action,
+    target: row.target,
+    ip_address: row.ip_address,
+    user_agent: row.user_agent,
+    before: row.before_value,
+    after: row.after_value,
+    created_at: row.created_at,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn csv_fields_are_escaped() {
+    assert_eq!(escape_csv_field("plain"), "plain");
+    assert_eq!(
+      escape_csv_field("{\"role\":1,\"x\":2}"),
+      "\"{\"\"role\"\":1,\"\"x\"\":2}\""
+    );
+    assert_eq!(escape_csv_field("=1+1"), "'=1+1");
+    assert_eq!(
+      csv_line(["a".to_string(), "b,c".to_string()]),
+      "a,\"b,c\"\r\n"
+    );
+  }
+}
@@ -0,0 +1,132 @@
+use app_error::AppError;
+use chrono::{Duration, Utc};
+use database::api_token::{
+  delete_api_token, insert_api_token, select_api_token_owner_and_touch, select_api_tokens,
+};
+use database::pg_row::{AFApiTokenOwnerRow, AFApiTokenRow};
+use database_entity::dto::{AFApiTokenScope, AFRole};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use shared_entity::dto::api_token_dto::{AFApiToken, CreateApiTokenParams};
+use sqlx::PgPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Personal API tokens start with this prefix, which distinguishes them from the session access
+/// tokens issued by GoTrue.
+pub const API_TOKEN_PREFIX: &str = "afp_";
+const API_TOKEN_LENGTH: usize = 40;
+const API_TOKEN_DISPLAY_PREFIX_LENGTH: usize = 8;
+
+#[instrument(level = "debug", skip_all, err)]
+pub async fn create_api_token(
+  state: &AppState,
+  uid: i64,
+  params: CreateApiTokenParams,
+) -> Result<AFApiToken, AppError> {
+  let name = params.name.trim();
+  if name.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "api token name cannot be empty".to_string(),
+    ));
+  }
+  if params.workspace_ids.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "api token must be scoped to at least one workspace".to_string(),
+    ));
+  }
+  for workspace_id in &params.workspace_ids {
+    state
+      .workspace_access_control
+      .enforce_role_weak(&uid, workspace_id, AFRole::Guest)
+      .await?;
+  }
+
+  let token = gen_api_token();
+  let expires_at = params
+    .expires_in_days
+    .map(|days| Utc::now() + Duration::days(days as i64));
+  let row = insert_api_token(
+    &state.pg_pool,
+    uid,
+    name,
+    &hash_api_token(&token),
+    &token[..API_TOKEN_DISPLAY_PREFIX_LENGTH],
+    &params.workspace_ids,
+    params.scope as i32,
+    expires_at,
+  )
+  .await?;
+  let mut api_token = to_af_api_token(row);
+  api_token.token = Some(token);
+  Ok(api_token)
+}
+
+pub async fn list_api_tokens(pg_pool: &PgPool, uid: i64) -> Result<Vec<AFApiToken>, AppError> {
+  let tokens = select_api_tokens(pg_pool, uid)
+    .await?
+    .into_iter()
+    .map(to_af_api_token)
+    .collect();
+  Ok(tokens)
+}
+
+pub async fn revoke_api_token(pg_pool: &PgPool, uid: i64, token_id: &Uuid) -> Result<(), AppError> {
+  delete_api_token(pg_pool, uid, token_id).await
+}
+
+/// Returns the owner of the token, or `None` if the token does not exist or has expired.
+pub async fn authenticate_api_token(
+  pg_pool: &PgPool,
+  token: &str,
+) -> Result<Option<AFApiTokenOwnerRow>, AppError> {
+  select_api_token_owner_and_touch(pg_pool, &hash_api_token(token)).await
+}
+
+pub fn is_api_token(token: &str) -> bool {
+  token.starts_with(API_TOKEN_PREFIX)
+}
+
+fn gen_api_token() -> String {
+  let token: String = rand::thread_rng()
+    .sample_iter(&Alphanumeric)
+    .take(API_TOKEN_LENGTH)
+    .map(char::from)
+    .collect();
+  format!("{}{}", API_TOKEN_PREFIX, token)
+}
+
+fn hash_api_token(token: &str) -> String {
+  hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn to_af_api_token(row: AFApiTokenRow) -> AFApiToken {
+  AFApiToken {
+    token_id: row.token_id,
+    name: row.name,
+    token_prefix: row.token_prefix,
+    workspace_ids: row.workspace_ids,
+    scope: AFApiTokenScope::from(row.scope),
+    created_at: row.created_at,
+    expires_at: row.expires_at,
+    last_used_at: row.last_used_at,
+    token: None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn api_token_format_test() {
+    let token = gen_api_token();
+    assert!(is_api_token(&token));
+    assert_eq!(token.len(), API_TOKEN_PREFIX.len() + API_TOKEN_LENGTH);
+    assert_eq!(hash_api_token(&token), hash_api_token(&token));
+    assert_ne!(hash_api_token(&token), hash_api_token(&gen_api_token()));
+  }
+}
@@ -1,5 +1,5 @@
 use actix_http::Payload;
-use actix_web::{web::Data, FromRequest, HttpRequest};
+use actix_web::{web::Data, FromRequest, HttpMessage, HttpRequest};
 
 use gotrue_entity::gotrue_jwt::GoTrueJWTClaims;
 use secrecy::{ExposeSecret, Secret};
@@ -123,7 +123,46 @@ impl FromRequest for Authorization {
   }
 }
 
+/// Inserted into the request extensions by the `ApiTokenMiddleware` when the request is
+/// authenticated with a personal API token instead of a GoTrue session.
+#[derive(Debug, Clone)]
+pub struct ApiTokenAuthorization {
+  pub token: String,
+  pub uuid: Uuid,
+  pub email: String,
+  pub expires_at: Option<i64>,
+}
+
+impl ApiTokenAuthorization {
+  fn to_authorization(&self) -> Authorization {
+    Authorization {
+      token: self.token.clone(),
+      claims: GoTrueJWTClaims {
+        aud: None,
+        exp: self.expires_at,
+        jti: None,
+        iat: None,
+        iss: None,
+        nbf: None,
+        sub: Some(self.uuid.to_string()),
+        email: self.email.clone(),
+        phone: String::new(),
+        app_metadata: serde_json::Value::Null,
+        user_metadata: serde_json::Value::Null,
+        role: "authenticated".to_string(),
+        aal: None,
+        amr: None,
+        session_id: None,
+      },
+    }
+  }
+}
+
 fn get_auth_from_request(req: &HttpRequest) -> Result<Authorization, actix_web::Error> {
+  if let Some(api_token) = req.extensions().get::<ApiTokenAuthorization>() {
+    return Ok(api_token.to_authorization());
+  }
+
   let jwt_secret_data =
     req
       .app_data::<Data<Secret<String>>>()
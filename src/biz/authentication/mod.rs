@@ -1 +1,2 @@
+pub mod api_token;
 pub mod jwt;
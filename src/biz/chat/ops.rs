@@ -1,7 +1,9 @@
 use anyhow::anyhow;
 
+use crate::biz::ai::provider::AIProvider;
+use crate::biz::collab::folder_view::{is_view_readable, private_space_and_trash_view_ids};
 use app_error::AppError;
-use appflowy_ai_client::client::AppFlowyAIClient;
+use appflowy_collaborate::ws2::WorkspaceCollabInstanceCache;
 use database::chat;
 use database::chat::chat_ops::{
   delete_answer_message_by_question_message_id, insert_answer_message,
@@ -20,6 +22,49 @@ use tracing::{info, trace};
 use uuid::Uuid;
 use validator::Validate;
 
+/// Keeps the rag ids which are views of the workspace the user can read. The others are dropped,
+/// so that the answers of the AI never contain the content of pages hidden from the user.
+pub async fn filter_readable_rag_ids(
+  collab_instance_cache: &impl WorkspaceCollabInstanceCache,
+  uid: i64,
+  workspace_id: &Uuid,
+  rag_ids: Vec<String>,
+) -> Result<Vec<String>, AppError> {
+  if rag_ids.is_empty() {
+    return Ok(rag_ids);
+  }
+  let folder = collab_instance_cache.get_folder(*workspace_id).await?;
+  let private_space_and_trash_views = private_space_and_trash_view_ids(uid, &folder)?;
+  Ok(
+    rag_ids
+      .into_iter()
+      .filter(|rag_id| {
+        Uuid::parse_str(rag_id).is_ok_and(|view_id| {
+          is_view_readable(
+            &folder,
+            &private_space_and_trash_views,
+            workspace_id,
+            &view_id,
+            uid,
+          )
+        })
+      })
+      .collect(),
+  )
+}
+
+/// Returns the rag ids of the chat which the user can read, see [filter_readable_rag_ids].
+pub async fn select_readable_chat_rag_ids(
+  pg_pool: &PgPool,
+  collab_instance_cache: &impl WorkspaceCollabInstanceCache,
+  uid: i64,
+  workspace_id: &Uuid,
+  chat_id: &str,
+) -> Result<Vec<String>, AppError> {
+  let rag_ids = chat::chat_ops::select_chat_rag_ids(pg_pool, chat_id).await?;
+  filter_readable_rag_ids(collab_instance_cache, uid, workspace_id, rag_ids).await
+}
+
 pub(crate) async fn create_chat(
   pg_pool: &PgPool,
   params: CreateChatParams,
@@ -43,7 +88,8 @@ pub async fn update_chat_message(
   workspace_id: String,
   pg_pool: &PgPool,
   params: UpdateChatMessageContentParams,
-  ai_client: AppFlowyAIClient,
+  rag_ids: Vec<String>,
+  ai_client: AIProvider,
   ai_model: &str,
 ) -> Result<(), AppError> {
   let mut txn = pg_pool.begin().await?;
@@ -63,6 +109,7 @@ pub async fn update_chat_message(
       &params.chat_id,
       params.message_id,
       &params.content,
+      rag_ids,
       ai_model,
       None,
     )
@@ -83,9 +130,10 @@ pub async fn update_chat_message(
 pub async fn generate_chat_message_answer(
   workspace_id: String,
   pg_pool: &PgPool,
-  ai_client: AppFlowyAIClient,
+  ai_client: AIProvider,
   question_message_id: i64,
   chat_id: &str,
+  rag_ids: Vec<String>,
   ai_model: &str,
 ) -> Result<ChatMessage, AppError> {
   let (content, metadata) =
@@ -96,6 +144,7 @@ pub async fn generate_chat_message_answer(
       chat_id,
       question_message_id,
       &content,
+      rag_ids,
       ai_model,
       Some(metadata),
     )
@@ -0,0 +1,221 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use collab_document::blocks::{Block, DocumentData};
+use database::collab::{CollabStore, GetCollabOrigin};
+use database::collab_author::select_collab_update_authors;
+use database::pg_row::AFCollabUpdateAuthorRow;
+use shared_entity::dto::workspace_dto::{DocumentBlame, DocumentBlockBlame};
+use sqlx::PgPool;
+use uuid::Uuid;
+use yrs::types::text::YChange;
+use yrs::{Any, DeleteSet, Map, Out, ReadTxn, Snapshot, StateVector, Text, Transact, ID};
+
+use super::history::{apply_doc_state, new_doc};
+use super::utils::get_latest_collab_document;
+
+/// Path of the text map of a document, from the root map of the collab.
+const TEXT_MAP_PATH: [&str; 3] = ["document", "meta", "text_map"];
+const DATA_SECTION: &str = "data";
+
+/// Returns the last editor and edit time of each block of the document, in document order.
+///
+/// Only the text of the blocks is attributed: the recorded authors are matched against the
+/// Yjs items that make up the current text of each block. Changes to the block data alone,
+/// such as toggling a checkbox, and deletions of text are not attributed.
+pub async fn get_document_blame(
+  pg_pool: &PgPool,
+  collab_storage: &Arc<dyn CollabStore>,
+  uid: i64,
+  workspace_id: Uuid,
+  object_id: Uuid,
+) -> Result<DocumentBlame, AppError> {
+  let document = get_latest_collab_document(
+    collab_storage,
+    GetCollabOrigin::User { uid },
+    workspace_id,
+    object_id,
+  )
+  .await?;
+  let document_data = document.get_document_data().map_err(|err| {
+    AppError::Internal(anyhow::anyhow!("invalid document {}: {}", object_id, err))
+  })?;
+  let doc_state = document
+    .encode_collab()
+    .map_err(|err| AppError::Internal(anyhow::anyhow!("failed to encode document: {}", err)))?
+    .doc_state;
+  let text_edits = get_document_text_edits(pg_pool, &object_id, doc_state.to_vec()).await?;
+  Ok(DocumentBlame {
+    blocks: blame_blocks(&document_data, &text_edits),
+  })
+}
+
+/// The last edit of each text of a document, as the uid of the author and the edit time, keyed
+/// by text id.
+pub type TextEdits = HashMap<String, (i64, DateTime<Utc>)>;
+
+/// Returns the last edit of each text of the document in the given state. Texts without any
+/// recorded author are left out.
+pub async fn get_document_text_edits(
+  pg_pool: &PgPool,
+  object_id: &Uuid,
+  doc_state: Vec<u8>,
+) -> Result<TextEdits, AppError> {
+  let authors = select_collab_update_authors(pg_pool, object_id).await?;
+  tokio::task::spawn_blocking(move || last_text_edits(&doc_state, &AuthorIndex::new(authors)))
+    .await?
+}
+
+/// Attributed ranges, keyed by client id and first clock. The ranges of a client don't overlap.
+struct AuthorIndex(BTreeMap<(i64, i64), AFCollabUpdateAuthorRow>);
+
+impl AuthorIndex {
+  fn new(rows: Vec<AFCollabUpdateAuthorRow>) -> Self {
+    Self(
+      rows
+        .into_iter()
+        .map(|row| ((row.client_id, row.clock_start), row))
+        .collect(),
+    )
+  }
+
+  /// Returns the most recent edit among the ranges overlapping the clocks [start, end) of the
+  /// client.
+  fn last_edit(&self, client_id: i64, start: i64, end: i64) -> Option<(i64, DateTime<Utc>)> {
+    self
+      .0
+      .range(..(client_id, end))
+      .rev()
+      .take_while(|((client, _), row)| *client == client_id && row.clock_end > start)
+      .map(|(_, row)| (row.uid, row.updated_at))
+      .max_by_key(|(_, updated_at)| *updated_at)
+  }
+}
+
+/// Returns the last edit of each text of the document, keyed by text id.
+fn last_text_edits(doc_state: &[u8], authors: &AuthorIndex) -> Result<TextEdits, AppError> {
+  let doc = new_doc();
+  apply_doc_state(&doc, doc_state)?;
+  let root = doc.get_or_insert_map(DATA_SECTION);
+  let mut txn = doc.transact_mut();
+
+  let mut text_map = root;
+  for key in TEXT_MAP_PATH {
+    match text_map.get(&txn, key) {
+      Some(Out::YMap(map)) => text_map = map,
+      _ => return Ok(HashMap::new()),
+    }
+  }
+  let texts: Vec<_> = text_map
+    .iter(&txn)
+    .filter_map(|(text_id, value)| match value {
+      Out::YText(text) => Some((text_id.to_string(), text)),
+      _ => None,
+    })
+    .collect();
+
+  // Diffing the current state against an empty snapshot marks every visible item as added,
+  // with the id of its first character.
+  let current = txn.snapshot();
+  let empty = Snapshot::new(StateVector::default(), DeleteSet::new());
+  let mut edits = HashMap::new();
+  for (text_id, text) in texts {
+    let chunks = text.diff_range(&mut txn, Some(&current), Some(&empty), |change: YChange| {
+      change.id
+    });
+    let last_edit = chunks
+      .iter()
+      .filter_map(|chunk| {
+        let ID { client, clock } = chunk.ychange?;
+        let len = match &chunk.insert {
+          Out::Any(Any::String(s)) => s.encode_utf16().count() as i64,
+          _ => 1,
+        };
+        let start = clock as i64;
+        authors.last_edit(client as i64, start, start + len)
+      })
+      .max_by_key(|(_, updated_at)| *updated_at);
+    if let Some(last_edit) = last_edit {
+      edits.insert(text_id, last_edit);
+    }
+  }
+  Ok(edits)
+}
+
+fn blame_blocks(data: &DocumentData, text_edits: &TextEdits) -> Vec<DocumentBlockBlame> {
+  blocks_in_document_order(data)
+    .into_iter()
+    .map(|block| {
+      let last_edit = block
+        .external_id
+        .as_ref()
+        .and_then(|text_id| text_edits.get(text_id));
+      DocumentBlockBlame {
+        block_id: block.id.clone(),
+        block_type: block.ty.clone(),
+        last_edited_by: last_edit.map(|(uid, _)| *uid),
+        last_edited_at: last_edit.map(|(_, updated_at)| *updated_at),
+      }
+    })
+    .collect()
+}
+
+/// Walks the blocks from the page block down, in the order they are displayed. Blocks which are
+/// not reachable from the page block are left out.
+pub fn blocks_in_document_order(data: &DocumentData) -> Vec<&Block> {
+  let mut blocks = vec![];
+  let mut visited = HashSet::new();
+  let mut stack = vec![&data.page_id];
+  while let Some(block_id) = stack.pop() {
+    // children maps are edited concurrently, don't trust them to form a tree
+    if !visited.insert(block_id) {
+      continue;
+    }
+    let Some(block) = data.blocks.get(block_id) else {
+      continue;
+    };
+    blocks.push(block);
+    if let Some(children) = data.meta.children_map.get(&block.children) {
+      stack.extend(children.iter().rev());
+    }
+  }
+  blocks
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeZone;
+
+  fn row(
+    client_id: i64,
+    clock_start: i64,
+    clock_end: i64,
+    uid: i64,
+    secs: i64,
+  ) -> AFCollabUpdateAuthorRow {
+    AFCollabUpdateAuthorRow {
+      client_id,
+      clock_start,
+      clock_end,
+      uid,
+      updated_at: Utc.timestamp_opt(secs, 0).unwrap(),
+    }
+  }
+
+  #[test]
+  fn last_edit_of_overlapping_ranges() {
+    let authors = AuthorIndex::new(vec![
+      row(1, 0, 10, 100, 1),
+      row(1, 10, 20, 200, 2),
+      row(2, 0, 5, 300, 3),
+    ]);
+    assert_eq!(authors.last_edit(1, 2, 4).map(|(uid, _)| uid), Some(100));
+    assert_eq!(authors.last_edit(1, 8, 12).map(|(uid, _)| uid), Some(200));
+    assert_eq!(authors.last_edit(1, 20, 25), None);
+    assert_eq!(authors.last_edit(2, 4, 6).map(|(uid, _)| uid), Some(300));
+    assert_eq!(authors.last_edit(3, 0, 1), None);
+  }
+}
@@ -0,0 +1,628 @@
+use app_error::AppError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::DateTime;
+use collab_database::entity::FieldType;
+use collab_database::fields::Field;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use shared_entity::dto::workspace_dto::{
+  AFDatabaseRowDetail, DatabaseFilterOperator, DatabaseRowFilter, DatabaseRowSort,
+  DatabaseSortDirection,
+};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+pub const DEFAULT_QUERY_ROW_LIMIT: u32 = 100;
+pub const MAX_QUERY_ROW_LIMIT: u32 = 1000;
+
+/// A filter and sorts validated against the fields of a database. Cells are read from the
+/// serialized row details returned by [super::utils::get_row_details_serde], which are keyed by
+/// the unique field name.
+pub struct DatabaseRowQuery {
+  filter: Option<RowFilter>,
+  sorts: Vec<RowSort>,
+}
+
+impl DatabaseRowQuery {
+  pub fn new(
+    field_by_id: &HashMap<String, Field>,
+    filter: Option<&DatabaseRowFilter>,
+    sorts: &[DatabaseRowSort],
+  ) -> Result<Self, AppError> {
+    let filter = filter
+      .map(|filter| RowFilter::new(field_by_id, filter))
+      .transpose()?;
+    let sorts = sorts
+      .iter()
+      .map(|sort| {
+        let (name, field_type) = find_field(field_by_id, &sort.field)?;
+        Ok(RowSort {
+          name,
+          kind: FieldKind::from(field_type),
+          direction: sort.direction,
+        })
+      })
+      .collect::<Result<Vec<_>, AppError>>()?;
+    Ok(Self { filter, sorts })
+  }
+
+  /// Returns true if the query keeps all the rows in their original order.
+  pub fn is_empty(&self) -> bool {
+    self.filter.is_none() && self.sorts.is_empty()
+  }
+
+  /// Keeps the rows matching the filter and sorts them. Rows with equal sort values are ordered
+  /// by id, so that a cursor can point between them. Without sorts, rows keep their original
+  /// order.
+  pub fn apply(&self, rows: Vec<AFDatabaseRowDetail>) -> Vec<AFDatabaseRowDetail> {
+    let mut rows = rows
+      .into_iter()
+      .filter(|row| {
+        self
+          .filter
+          .as_ref()
+          .map(|filter| filter.matches(&row.cells))
+          .unwrap_or(true)
+      })
+      .collect::<Vec<_>>();
+    if !self.sorts.is_empty() {
+      rows.sort_by(|a, b| self.compare(&a.cells, &a.id, &b.cells, &b.id));
+    }
+    rows
+  }
+
+  fn compare(
+    &self,
+    a_cells: &HashMap<String, Value>,
+    a_id: &str,
+    b_cells: &HashMap<String, Value>,
+    b_id: &str,
+  ) -> Ordering {
+    self
+      .sorts
+      .iter()
+      .map(|sort| sort.compare(a_cells, b_cells))
+      .find(|ordering| ordering.is_ne())
+      .unwrap_or_else(|| a_id.cmp(b_id))
+  }
+
+  /// Returns the cursor pointing at the row, `position` being the position of the row in the
+  /// view.
+  pub fn cursor(&self, row: &AFDatabaseRowDetail, position: usize) -> RowCursor {
+    let cells = self
+      .sorts
+      .iter()
+      .filter_map(|sort| Some((sort.name.clone(), row.cells.get(&sort.name)?.clone())))
+      .collect();
+    RowCursor {
+      row_id: row.id.clone(),
+      position,
+      cells,
+    }
+  }
+
+  /// Returns the index of the first row after the cursor. The rows are ordered by [Self::apply]
+  /// and `position` returns their position in the view. The cursor row may have been deleted
+  /// since the previous page, in which case the next page starts after where it would be.
+  pub fn start_after(
+    &self,
+    rows: &[AFDatabaseRowDetail],
+    cursor: &RowCursor,
+    position: impl Fn(&AFDatabaseRowDetail) -> usize,
+  ) -> usize {
+    if let Some(index) = rows.iter().position(|row| row.id == cursor.row_id) {
+      return index + 1;
+    }
+    if self.sorts.is_empty() {
+      rows.partition_point(|row| position(row) < cursor.position)
+    } else {
+      rows.partition_point(|row| {
+        self
+          .compare(&row.cells, &row.id, &cursor.cells, &cursor.row_id)
+          .is_lt()
+      })
+    }
+  }
+}
+
+/// Opaque position of the last row of a page, returned as the `next_cursor` of a query. It holds
+/// the sort values and the id of the row rather than an offset, so that rows added or deleted
+/// between two pages don't shift the next page.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RowCursor {
+  row_id: String,
+  /// Position of the row in the view, used to resume a query without sorts after the row was
+  /// deleted.
+  position: usize,
+  /// Cells of the sort fields of the row, keyed by the unique field name.
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  cells: HashMap<String, Value>,
+}
+
+impl RowCursor {
+  /// Returns the cursor of a query without filter and sorts.
+  pub fn at_position(row_id: String, position: usize) -> Self {
+    Self {
+      row_id,
+      position,
+      cells: HashMap::new(),
+    }
+  }
+
+  pub fn row_id(&self) -> &str {
+    &self.row_id
+  }
+
+  pub fn position(&self) -> usize {
+    self.position
+  }
+
+  pub fn encode(&self) -> String {
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+  }
+
+  pub fn decode(cursor: &str) -> Result<Self, AppError> {
+    URL_SAFE_NO_PAD
+      .decode(cursor)
+      .ok()
+      .and_then(|data| serde_json::from_slice(&data).ok())
+      .ok_or_else(|| AppError::InvalidRequest(format!("Invalid cursor: {}", cursor)))
+  }
+}
+
+/// Returns the unique name and the type of the field referenced by id or name.
+fn find_field(
+  field_by_id: &HashMap<String, Field>,
+  id_or_name: &str,
+) -> Result<(String, FieldType), AppError> {
+  field_by_id
+    .get(id_or_name)
+    .or_else(|| field_by_id.values().find(|field| field.name == id_or_name))
+    .map(|field| (field.name.clone(), FieldType::from(field.field_type)))
+    .ok_or_else(|| AppError::InvalidRequest(format!("Field not found: {}", id_or_name)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+  Text,
+  Number,
+  Checkbox,
+  Select,
+  Date,
+  Other,
+}
+
+impl From<FieldType> for FieldKind {
+  fn from(field_type: FieldType) -> Self {
+    match field_type {
+      FieldType::RichText | FieldType::URL => FieldKind::Text,
+      FieldType::Number => FieldKind::Number,
+      FieldType::Checkbox => FieldKind::Checkbox,
+      FieldType::SingleSelect | FieldType::MultiSelect => FieldKind::Select,
+      FieldType::DateTime | FieldType::CreatedTime | FieldType::LastEditedTime => FieldKind::Date,
+      _ => FieldKind::Other,
+    }
+  }
+}
+
+enum RowFilter {
+  And(Vec<RowFilter>),
+  Or(Vec<RowFilter>),
+  Not(Box<RowFilter>),
+  Field { name: String, condition: Condition },
+}
+
+enum Condition {
+  IsEmpty,
+  IsNotEmpty,
+  Text(DatabaseFilterOperator, String),
+  Number(DatabaseFilterOperator, f64),
+  Checkbox(bool),
+  Select(DatabaseFilterOperator, Vec<String>),
+  Date(DatabaseFilterOperator, i64, i64),
+}
+
+impl RowFilter {
+  fn new(
+    field_by_id: &HashMap<String, Field>,
+    filter: &DatabaseRowFilter,
+  ) -> Result<Self, AppError> {
+    let filter = match filter {
+      DatabaseRowFilter::And { filters } => RowFilter::And(
+        filters
+          .iter()
+          .map(|filter| RowFilter::new(field_by_id, filter))
+          .collect::<Result<_, _>>()?,
+      ),
+      DatabaseRowFilter::Or { filters } => RowFilter::Or(
+        filters
+          .iter()
+          .map(|filter| RowFilter::new(field_by_id, filter))
+          .collect::<Result<_, _>>()?,
+      ),
+      DatabaseRowFilter::Not { filter } => {
+        RowFilter::Not(Box::new(RowFilter::new(field_by_id, filter)?))
+      },
+      DatabaseRowFilter::Field {
+        field,
+        operator,
+        value,
+      } => {
+        let (name, field_type) = find_field(field_by_id, field)?;
+        let condition =
+          Condition::new(FieldKind::from(field_type), *operator, value).map_err(|err| {
+            AppError::InvalidRequest(format!("Invalid filter on {}: {}", name, err))
+          })?;
+        RowFilter::Field { name, condition }
+      },
+    };
+    Ok(filter)
+  }
+
+  fn matches(&self, cells: &HashMap<String, Value>) -> bool {
+    match self {
+      RowFilter::And(filters) => filters.iter().all(|filter| filter.matches(cells)),
+      RowFilter::Or(filters) => filters.iter().any(|filter| filter.matches(cells)),
+      RowFilter::Not(filter) => !filter.matches(cells),
+      RowFilter::Field { name, condition } => {
+        condition.matches(cells.get(name).unwrap_or(&Value::Null))
+      },
+    }
+  }
+}
+
+impl Condition {
+  fn new(kind: FieldKind, operator: DatabaseFilterOperator, value: &Value) -> Result<Self, String> {
+    use DatabaseFilterOperator::*;
+    let condition = match (kind, operator) {
+      (_, IsEmpty) => Condition::IsEmpty,
+      (_, IsNotEmpty) => Condition::IsNotEmpty,
+      (FieldKind::Text, Is | IsNot | Contains | DoesNotContain | StartsWith | EndsWith) => {
+        let value = value.as_str().ok_or("expected a string value")?;
+        Condition::Text(operator, value.to_lowercase())
+      },
+      (
+        FieldKind::Number,
+        Equal | NotEqual | GreaterThan | GreaterThanOrEqual | LessThan | LessThanOrEqual,
+      ) => Condition::Number(
+        operator,
+        cell_number(value).ok_or("expected a number value")?,
+      ),
+      (FieldKind::Checkbox, IsChecked) => Condition::Checkbox(true),
+      (FieldKind::Checkbox, IsUnchecked) => Condition::Checkbox(false),
+      (FieldKind::Select, Is | IsNot | ContainsAny | ContainsAll | DoesNotContain) => {
+        let options = cell_options(value);
+        if options.is_empty() {
+          return Err("expected an option name or a list of option names".to_string());
+        }
+        Condition::Select(operator, options)
+      },
+      (FieldKind::Date, Before | After | OnOrBefore | OnOrAfter) => {
+        let timestamp = cell_timestamp(value).ok_or("expected a timestamp or a RFC 3339 date")?;
+        Condition::Date(operator, timestamp, timestamp)
+      },
+      (FieldKind::Date, Between) => {
+        let range = value
+          .as_array()
+          .filter(|range| range.len() == 2)
+          .and_then(|range| Some((cell_timestamp(&range[0])?, cell_timestamp(&range[1])?)))
+          .ok_or("expected a list of two dates")?;
+        Condition::Date(operator, range.0, range.1)
+      },
+      (kind, operator) => {
+        return Err(format!(
+          "operator {:?} is not supported by {:?} fields",
+          operator, kind
+        ))
+      },
+    };
+    Ok(condition)
+  }
+
+  fn matches(&self, cell: &Value) -> bool {
+    use DatabaseFilterOperator::*;
+    match self {
+      Condition::IsEmpty => is_empty_cell(cell),
+      Condition::IsNotEmpty => !is_empty_cell(cell),
+      Condition::Text(operator, expected) => {
+        let text = cell_text(cell).to_lowercase();
+        match operator {
+          Is => text == *expected,
+          IsNot => text != *expected,
+          Contains => text.contains(expected.as_str()),
+          DoesNotContain => !text.contains(expected.as_str()),
+          StartsWith => text.starts_with(expected.as_str()),
+          EndsWith => text.ends_with(expected.as_str()),
+          _ => false,
+        }
+      },
+      Condition::Number(operator, expected) => match cell_number(cell) {
+        None => *operator == NotEqual,
+        Some(number) => match operator {
+          Equal => number == *expected,
+          NotEqual => number != *expected,
+          GreaterThan => number > *expected,
+          GreaterThanOrEqual => number >= *expected,
+          LessThan => number < *expected,
+          LessThanOrEqual => number <= *expected,
+          _ => false,
+        },
+      },
+      Condition::Checkbox(expected) => cell_checked(cell) == *expected,
+      Condition::Select(operator, expected) => {
+        let options = cell_options(cell);
+        match operator {
+          Is => options == *expected,
+          IsNot => options != *expected,
+          ContainsAny => expected.iter().any(|option| options.contains(option)),
+          ContainsAll => expected.iter().all(|option| options.contains(option)),
+          DoesNotContain => !expected.iter().any(|option| options.contains(option)),
+          _ => false,
+        }
+      },
+      Condition::Date(operator, start, end) => match cell_timestamp(cell) {
+        None => false,
+        Some(timestamp) => match operator {
+          Before => timestamp < *start,
+          After => timestamp > *start,
+          OnOrBefore => timestamp <= *start,
+          OnOrAfter => timestamp >= *start,
+          Between => *start <= timestamp && timestamp <= *end,
+          _ => false,
+        },
+      },
+    }
+  }
+}
+
+struct RowSort {
+  name: String,
+  kind: FieldKind,
+  direction: DatabaseSortDirection,
+}
+
+impl RowSort {
+  /// Empty cells are always placed last, whatever the direction.
+  fn compare(&self, a: &HashMap<String, Value>, b: &HashMap<String, Value>) -> Ordering {
+    let a = a.get(&self.name).unwrap_or(&Value::Null);
+    let b = b.get(&self.name).unwrap_or(&Value::Null);
+    match (is_empty_cell(a), is_empty_cell(b)) {
+      (true, true) => return Ordering::Equal,
+      (true, false) => return Ordering::Greater,
+      (false, true) => return Ordering::Less,
+      (false, false) => {},
+    }
+
+    let ordering = match self.kind {
+      FieldKind::Number => cell_number(a)
+        .partial_cmp(&cell_number(b))
+        .unwrap_or(Ordering::Equal),
+      FieldKind::Date => cell_timestamp(a).cmp(&cell_timestamp(b)),
+      FieldKind::Checkbox => cell_checked(a).cmp(&cell_checked(b)),
+      FieldKind::Select => cell_options(a).cmp(&cell_options(b)),
+      FieldKind::Text | FieldKind::Other => cell_text(a)
+        .to_lowercase()
+        .cmp(&cell_text(b).to_lowercase()),
+    };
+    match self.direction {
+      DatabaseSortDirection::Asc => ordering,
+      DatabaseSortDirection::Desc => ordering.reverse(),
+    }
+  }
+}
+
+fn is_empty_cell(cell: &Value) -> bool {
+  match cell {
+    Value::Null => true,
+    Value::String(text) => text.is_empty(),
+    Value::Array(values) => values.is_empty(),
+    Value::Object(map) => map.values().all(Value::is_null),
+    Value::Bool(_) | Value::Number(_) => false,
+  }
+}
+
+fn cell_text(cell: &Value) -> String {
+  match cell {
+    Value::Null => String::new(),
+    Value::String(text) => text.clone(),
+    Value::Array(values) => values.iter().map(cell_text).collect::<Vec<_>>().join(","),
+    value => value.to_string(),
+  }
+}
+
+/// Number cells are serialized as strings.
+fn cell_number(cell: &Value) -> Option<f64> {
+  match cell {
+    Value::Number(number) => number.as_f64(),
+    Value::String(text) => text.trim().parse().ok().or_else(|| {
+      text
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.' || *c == '-')
+        .collect::<String>()
+        .parse()
+        .ok()
+    }),
+    _ => None,
+  }
+}
+
+fn cell_checked(cell: &Value) -> bool {
+  match cell {
+    Value::Bool(checked) => *checked,
+    Value::String(text) => text.eq_ignore_ascii_case("yes") || text.eq_ignore_ascii_case("true"),
+    _ => false,
+  }
+}
+
+/// Single select cells are serialized as the option name and multi select cells as a list of
+/// option names. Names are compared case-insensitively.
+fn cell_options(cell: &Value) -> Vec<String> {
+  match cell {
+    Value::String(name) if !name.is_empty() => vec![name.to_lowercase()],
+    Value::Array(values) => values
+      .iter()
+      .filter_map(|value| value.as_str().or_else(|| value.get("name")?.as_str()))
+      .map(|name| name.to_lowercase())
+      .collect(),
+    _ => vec![],
+  }
+}
+
+/// Date cells are serialized as an object whose `start` is a RFC 3339 date.
+fn cell_timestamp(cell: &Value) -> Option<i64> {
+  match cell {
+    Value::Number(number) => number.as_i64(),
+    Value::String(text) => text.parse().ok().or_else(|| {
+      DateTime::parse_from_rfc3339(text)
+        .ok()
+        .map(|date| date.timestamp())
+    }),
+    Value::Object(map) => ["start", "timestamp"]
+      .iter()
+      .find_map(|key| cell_timestamp(map.get(*key)?)),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn fields() -> HashMap<String, Field> {
+    [
+      Field::from_field_type("Name", FieldType::RichText, true),
+      Field::from_field_type("Status", FieldType::SingleSelect, false),
+      Field::from_field_type("Points", FieldType::Number, false),
+      Field::from_field_type("Due", FieldType::DateTime, false),
+      Field::from_field_type("Done", FieldType::Checkbox, false),
+    ]
+    .into_iter()
+    .map(|field| (field.id.clone(), field))
+    .collect()
+  }
+
+  fn row(id: &str, cells: Value) -> AFDatabaseRowDetail {
+    AFDatabaseRowDetail {
+      id: id.to_string(),
+      cells: serde_json::from_value(cells).unwrap(),
+      has_doc: false,
+      doc: None,
+    }
+  }
+
+  fn rows() -> Vec<AFDatabaseRowDetail> {
+    vec![
+      row(
+        "1",
+        json!({"Name": "Write docs", "Status": "Done", "Points": "3", "Done": true,
+          "Due": {"start": "2024-12-03T07:17:01+00:00"}}),
+      ),
+      row(
+        "2",
+        json!({"Name": "Fix bug", "Status": "Doing", "Points": "8", "Done": false,
+          "Due": {"start": "2024-11-01T00:00:00+00:00"}}),
+      ),
+      row(
+        "3",
+        json!({"Name": "Release", "Status": "Done", "Points": "", "Done": false, "Due": null}),
+      ),
+    ]
+  }
+
+  fn query(filter: Value, sorts: Value) -> Result<Vec<String>, AppError> {
+    let filter = serde_json::from_value::<DatabaseRowFilter>(filter).unwrap();
+    let sorts = serde_json::from_value::<Vec<DatabaseRowSort>>(sorts).unwrap();
+    let query = DatabaseRowQuery::new(&fields(), Some(&filter), &sorts)?;
+    Ok(query.apply(rows()).into_iter().map(|row| row.id).collect())
+  }
+
+  #[test]
+  fn filter_rows_by_field_type() {
+    let status_done =
+      json!({"type": "field", "field": "Status", "operator": "is", "value": "done"});
+    assert_eq!(
+      query(status_done.clone(), json!([])).unwrap(),
+      vec!["1", "3"]
+    );
+
+    let filter = json!({"type": "and", "filters": [
+      status_done,
+      {"type": "field", "field": "Points", "operator": "greater_than", "value": 1}
+    ]});
+    assert_eq!(query(filter, json!([])).unwrap(), vec!["1"]);
+
+    let filter = json!({"type": "or", "filters": [
+      {"type": "field", "field": "Done", "operator": "is_checked"},
+      {"type": "field", "field": "Due", "operator": "before", "value": "2024-12-01T00:00:00Z"}
+    ]});
+    assert_eq!(query(filter, json!([])).unwrap(), vec!["1", "2"]);
+
+    let filter = json!({"type": "not", "filter":
+      {"type": "field", "field": "Name", "operator": "contains", "value": "BUG"}
+    });
+    assert_eq!(query(filter, json!([])).unwrap(), vec!["1", "3"]);
+
+    let filter = json!({"type": "field", "field": "Due", "operator": "is_empty"});
+    assert_eq!(query(filter, json!([])).unwrap(), vec!["3"]);
+  }
+
+  #[test]
+  fn sort_rows_with_empty_cells_last() {
+    let all = json!({"type": "field", "field": "Name", "operator": "is_not_empty"});
+    assert_eq!(
+      query(
+        all.clone(),
+        json!([{"field": "Points", "direction": "desc"}])
+      )
+      .unwrap(),
+      vec!["2", "1", "3"]
+    );
+    assert_eq!(
+      query(
+        all,
+        json!([{"field": "Status"}, {"field": "Name", "direction": "desc"}])
+      )
+      .unwrap(),
+      vec!["2", "1", "3"]
+    );
+  }
+
+  #[test]
+  fn resume_after_a_deleted_cursor_row() {
+    let sorts =
+      serde_json::from_value::<Vec<DatabaseRowSort>>(json!([{"field": "Status"}])).unwrap();
+    let query = DatabaseRowQuery::new(&fields(), None, &sorts).unwrap();
+    let rows = query.apply(rows());
+    let ids = rows.iter().map(|row| row.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["2", "1", "3"]);
+
+    let cursor = RowCursor::decode(&query.cursor(&rows[1], 0).encode()).unwrap();
+    assert_eq!(query.start_after(&rows, &cursor, |_| 0), 2);
+    // the next page starts at the rows sorted after the deleted row
+    let remaining = rows
+      .into_iter()
+      .filter(|row| row.id != "1")
+      .collect::<Vec<_>>();
+    assert_eq!(query.start_after(&remaining, &cursor, |_| 0), 1);
+    assert!(matches!(
+      RowCursor::decode("1"),
+      Err(AppError::InvalidRequest(_))
+    ));
+  }
+
+  #[test]
+  fn reject_operator_not_supported_by_field() {
+    let filter = json!({"type": "field", "field": "Points", "operator": "contains", "value": "1"});
+    assert!(matches!(
+      query(filter, json!([])),
+      Err(AppError::InvalidRequest(_))
+    ));
+    let filter = json!({"type": "field", "field": "Unknown", "operator": "is_empty"});
+    assert!(matches!(
+      query(filter, json!([])),
+      Err(AppError::InvalidRequest(_))
+    ));
+  }
+}
@@ -59,6 +59,38 @@ pub fn private_space_and_trash_view_ids(
   })
 }
 
+/// Returns true if the view belongs to the folder and none of its ancestors is in the trash or
+/// is the private space of another member.
+pub fn is_view_readable(
+  folder: &Folder,
+  private_space_and_trash_views: &PrivateSpaceAndTrashViews,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  uid: i64,
+) -> bool {
+  let mut visited = HashSet::new();
+  let mut current_view_id = *view_id;
+  while current_view_id != *workspace_id && visited.insert(current_view_id) {
+    if private_space_and_trash_views
+      .other_private_space_ids
+      .contains(&current_view_id)
+      || private_space_and_trash_views
+        .view_ids_in_trash
+        .contains(&current_view_id)
+    {
+      return false;
+    }
+    let parent_view_id = folder
+      .get_view(&current_view_id.to_string(), uid)
+      .and_then(|view| Uuid::parse_str(&view.parent_view_id).ok());
+    match parent_view_id {
+      Some(parent_view_id) => current_view_id = parent_view_id,
+      None => return false,
+    }
+  }
+  current_view_id == *workspace_id
+}
+
 /// Return all folders belonging to a workspace, excluding private sections which the user does not have access to.
 pub fn collab_folder_to_folder_view(
   workspace_id: Uuid,
@@ -0,0 +1,175 @@
+use app_error::AppError;
+use appflowy_collaborate::snapshot::SnapshotControl;
+use collab::core::collab::default_client_id;
+use collab::entity::EncodedCollab;
+use collab_entity::CollabType;
+use collab_rt_entity::user::RealtimeUser;
+use database::collab::GetCollabOrigin;
+use database_entity::dto::{AFSnapshotMeta, AFSnapshotMetas, InsertSnapshotParams};
+use shared_entity::dto::workspace_dto::CollabJsonResponse;
+use tracing::instrument;
+use uuid::Uuid;
+use yrs::undo::Options as UndoOptions;
+use yrs::updates::decoder::Decode;
+use yrs::{Doc, Options, Out, ReadTxn, Transact, UndoManager, Update};
+
+use crate::biz::collab::utils::collab_from_doc_state;
+use crate::biz::workspace::page_view::update_page_collab_data;
+use crate::state::AppState;
+
+/// Origin of the transaction that replays the changes made after a snapshot, so that the
+/// [UndoManager] only reverts those changes.
+const RESTORE_ORIGIN: &str = "snapshot-restore";
+
+pub async fn list_collab_snapshots(
+  snapshot_control: &SnapshotControl,
+  workspace_id: &Uuid,
+  object_id: &Uuid,
+) -> Result<AFSnapshotMetas, AppError> {
+  snapshot_control
+    .get_collab_snapshot_list(workspace_id, object_id)
+    .await
+}
+
+/// Returns the doc state of the collab at the time the snapshot was taken.
+pub async fn get_collab_snapshot_doc_state(
+  snapshot_control: &SnapshotControl,
+  workspace_id: Uuid,
+  object_id: Uuid,
+  snapshot_id: i64,
+) -> Result<Vec<u8>, AppError> {
+  let snapshot = snapshot_control
+    .get_snapshot(workspace_id, object_id, &snapshot_id)
+    .await?;
+  let encoded_collab = EncodedCollab::decode_from_bytes(&snapshot.encoded_collab_v1)
+    .map_err(|err| AppError::Internal(err.into()))?;
+  Ok(encoded_collab.doc_state.to_vec())
+}
+
+pub async fn get_collab_snapshot_json(
+  snapshot_control: &SnapshotControl,
+  workspace_id: Uuid,
+  object_id: Uuid,
+  snapshot_id: i64,
+) -> Result<CollabJsonResponse, AppError> {
+  let doc_state =
+    get_collab_snapshot_doc_state(snapshot_control, workspace_id, object_id, snapshot_id).await?;
+  let collab = collab_from_doc_state(doc_state, &object_id, default_client_id())?;
+  Ok(CollabJsonResponse {
+    collab: collab.to_json_value(),
+  })
+}
+
+/// Restores the collab to the state it had when the snapshot was taken. The restoration is
+/// applied as a regular update on top of the current state, so connected clients converge
+/// without reloading the collab. The current state is snapshotted beforehand, which makes the
+/// restoration itself reversible. Returns the metadata of that snapshot.
+#[instrument(level = "debug", skip(state, user))]
+pub async fn restore_collab_snapshot(
+  state: &AppState,
+  user: RealtimeUser,
+  workspace_id: Uuid,
+  object_id: Uuid,
+  collab_type: CollabType,
+  snapshot_id: i64,
+) -> Result<AFSnapshotMeta, AppError> {
+  let snapshot_doc_state = get_collab_snapshot_doc_state(
+    &state.snapshot_control,
+    workspace_id,
+    object_id,
+    snapshot_id,
+  )
+  .await?;
+  let current_doc_state = state
+    .collab_storage
+    .get_full_encode_collab(
+      GetCollabOrigin::User { uid: user.uid },
+      &workspace_id,
+      &object_id,
+      collab_type,
+    )
+    .await?
+    .encoded_collab
+    .doc_state;
+
+  let backup = state
+    .snapshot_control
+    .create_snapshot(InsertSnapshotParams {
+      object_id,
+      doc_state: current_doc_state.clone(),
+      workspace_id,
+      collab_type,
+    })
+    .await?;
+
+  let update = tokio::task::spawn_blocking(move || {
+    revert_to_snapshot_update(&current_doc_state, &snapshot_doc_state)
+  })
+  .await??;
+  update_page_collab_data(state, user, workspace_id, object_id, collab_type, update).await?;
+  Ok(backup)
+}
+
+pub fn new_doc() -> Doc {
+  Doc::with_options(Options {
+    client_id: default_client_id(),
+    skip_gc: true,
+    ..Options::default()
+  })
+}
+
+pub fn apply_doc_state(doc: &Doc, doc_state: &[u8]) -> Result<(), AppError> {
+  let update = Update::decode_v1(doc_state)
+    .map_err(|err| AppError::Internal(anyhow::anyhow!("invalid doc state: {}", err)))?;
+  doc
+    .transact_mut()
+    .apply_update(update)
+    .map_err(|err| AppError::Internal(anyhow::anyhow!("failed to apply doc state: {}", err)))
+}
+
+/// Computes the update that brings a document from `current_doc_state` back to
+/// `snapshot_doc_state`. The changes made since the snapshot are replayed on top of the snapshot
+/// and then undone, which produces the inverse operations. Only those operations, encoded
+/// against the current state vector, are returned.
+fn revert_to_snapshot_update(
+  current_doc_state: &[u8],
+  snapshot_doc_state: &[u8],
+) -> Result<Vec<u8>, AppError> {
+  let current = new_doc();
+  apply_doc_state(&current, current_doc_state)?;
+  let restored = new_doc();
+  apply_doc_state(&restored, snapshot_doc_state)?;
+
+  let changes = current
+    .transact()
+    .encode_state_as_update_v1(&restored.transact().state_vector());
+
+  let mut undo_manager: UndoManager<()> =
+    UndoManager::with_options(&restored, UndoOptions::default());
+  for (name, value) in current.transact().root_refs() {
+    match value {
+      Out::YMap(_) => undo_manager.expand_scope(&restored.get_or_insert_map(name)),
+      Out::YArray(_) => undo_manager.expand_scope(&restored.get_or_insert_array(name)),
+      Out::YText(_) => undo_manager.expand_scope(&restored.get_or_insert_text(name)),
+      Out::YXmlFragment(_) => undo_manager.expand_scope(&restored.get_or_insert_xml_fragment(name)),
+      _ => {},
+    }
+  }
+  undo_manager.include_origin(RESTORE_ORIGIN);
+
+  {
+    let update = Update::decode_v1(&changes)
+      .map_err(|err| AppError::Internal(anyhow::anyhow!("invalid update: {}", err)))?;
+    restored
+      .transact_mut_with(RESTORE_ORIGIN)
+      .apply_update(update)
+      .map_err(|err| AppError::Internal(anyhow::anyhow!("failed to apply update: {}", err)))?;
+  }
+  undo_manager.undo_blocking();
+
+  let current_state_vector = current.transact().state_vector();
+  let update = restored
+    .transact()
+    .encode_state_as_update_v1(&current_state_vector);
+  Ok(update)
+}
@@ -1,5 +1,9 @@
+pub mod blame;
+pub mod changes;
 pub mod database;
+pub mod database_query;
 pub mod folder_view;
+pub mod history;
 pub mod ops;
 pub mod publish_outline;
 pub mod utils;
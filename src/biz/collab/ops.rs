@@ -5,8 +5,10 @@ use chrono::DateTime;
 use chrono::Utc;
 use collab::preclude::Collab;
 use collab_database::database::gen_field_id;
+use collab_database::database::DatabaseBody;
 use collab_database::entity::FieldType;
 use collab_database::fields::Field;
+use collab_database::fields::TypeOptionCellReader;
 use collab_database::fields::TypeOptions;
 use collab_database::rows::meta_id_from_row_id;
 use collab_database::rows::CreateRowParams;
@@ -15,6 +17,9 @@ use collab_database::rows::Row;
 use collab_database::rows::RowDetail;
 use collab_database::rows::RowId;
 use collab_database::rows::RowMetaKey;
+use collab_database::views::DatabaseView;
+use collab_database::views::DatabaseViewUpdate;
+use collab_database::views::FilterMap;
 use collab_database::views::OrderObjectPosition;
 use collab_database::workspace_database::WorkspaceDatabase;
 use collab_database::workspace_database::WorkspaceDatabaseBody;
@@ -29,27 +34,38 @@ use collab_rt_entity::user::RealtimeUser;
 use database::collab::select_last_updated_database_row_ids;
 use database::collab::select_workspace_database_oid;
 use database::collab::{CollabStore, GetCollabOrigin};
+use database::index::delete_database_row_index;
 use database::publish::select_published_view_ids_for_workspace;
 use database::publish::select_published_view_ids_with_publish_info_for_workspace;
 use database::publish::select_workspace_id_for_publish_namespace;
 use database_entity::dto::CollabParams;
 use database_entity::dto::QueryCollab;
 use database_entity::dto::QueryCollabResult;
+use indexer::collab_indexer::UnindexedDatabaseRow;
 
+use serde_json::json;
+use shared_entity::dto::webhook_dto::WebhookEvent;
 use shared_entity::dto::workspace_dto::AFDatabase;
 use shared_entity::dto::workspace_dto::AFDatabaseField;
 use shared_entity::dto::workspace_dto::AFDatabaseRow;
 use shared_entity::dto::workspace_dto::AFDatabaseRowDetail;
 use shared_entity::dto::workspace_dto::AFInsertDatabaseField;
+use shared_entity::dto::workspace_dto::AFUpdateDatabaseField;
 use shared_entity::dto::workspace_dto::DatabaseRowUpdatedItem;
 use shared_entity::dto::workspace_dto::FavoriteFolderView;
 use shared_entity::dto::workspace_dto::FolderViewMinimal;
 use shared_entity::dto::workspace_dto::PublishedViewInfo;
+use shared_entity::dto::workspace_dto::QueryDatabaseRowParams;
+use shared_entity::dto::workspace_dto::QueryDatabaseRowResponse;
 use shared_entity::dto::workspace_dto::RecentFolderView;
 use shared_entity::dto::workspace_dto::TrashFolderView;
 use sqlx::PgPool;
+use yrs::Any;
 use yrs::Map;
 
+use super::database_query::DEFAULT_QUERY_ROW_LIMIT;
+use super::database_query::MAX_QUERY_ROW_LIMIT;
+use super::database_query::{DatabaseRowQuery, RowCursor};
 use super::folder_view::collab_folder_to_folder_view;
 use super::folder_view::section_items_to_favorite_folder_view;
 use super::folder_view::section_items_to_recent_folder_view;
@@ -73,6 +89,7 @@ use super::utils::DEFAULT_SPACE_ICON_COLOR;
 use crate::api::metrics::AppFlowyWebMetrics;
 use crate::biz::collab::folder_view::check_if_view_is_space;
 use crate::biz::collab::utils::get_database_row_doc_changes;
+use crate::biz::webhook::ops::publish_webhook_event;
 use crate::biz::workspace::page_view::update_workspace_folder_data;
 use crate::state::AppState;
 use appflowy_collaborate::ws2::{CollabUpdatePublisher, WorkspaceCollabInstanceCache};
@@ -331,6 +348,7 @@ pub async fn get_published_view(
             publish_timestamp: pv.publish_timestamp,
             comments_enabled: pv.comments_enabled,
             duplicate_enabled: pv.duplicate_enabled,
+            password_protected: pv.password_protected,
           },
         )
       })
@@ -513,6 +531,7 @@ pub async fn insert_database_row(
     .await
     .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to create row: {:?}", e)))?;
 
+  let unindexed_row = UnindexedDatabaseRow::from_collab(workspace_uuid, &new_db_row_collab);
   let new_db_row_ec_v1 = collab_to_bin(new_db_row_collab, CollabType::DatabaseRow).await?;
 
   // For each database view, add the new row order
@@ -589,6 +608,18 @@ pub async fn insert_database_row(
     .await?;
 
   db_txn.commit().await?;
+  // the row is written directly to the storage, so it doesn't go through the collab indexing
+  if let Some(row) = unindexed_row {
+    state.indexer_scheduler.index_database_rows(vec![row]);
+  }
+  publish_webhook_event(
+    &state.pg_pool,
+    &workspace_uuid,
+    WebhookEvent::DatabaseRowAdded,
+    Some(uid),
+    json!({ "database_id": database_uuid, "row_id": new_db_row_id }),
+  )
+  .await;
   Ok(new_db_row_id.to_string())
 }
 
@@ -751,6 +782,14 @@ pub async fn upsert_database_row(
   }
 
   db_txn.commit().await?;
+  publish_webhook_event(
+    &state.pg_pool,
+    &workspace_uuid,
+    WebhookEvent::DatabaseRowChanged,
+    Some(uid),
+    json!({ "database_id": database_uuid, "row_id": row_id }),
+  )
+  .await;
   Ok(())
 }
 
@@ -838,6 +877,311 @@ pub async fn add_database_field(
   Ok(new_id)
 }
 
+/// Removes the row from every view of the database and deletes the row collab.
+pub async fn delete_database_row(
+  state: &AppState,
+  workspace_id: Uuid,
+  database_id: Uuid,
+  uid: i64,
+  row_id: Uuid,
+) -> Result<(), AppError> {
+  let (mut db_collab, db_body) =
+    get_latest_collab_database_body(&state.collab_storage, workspace_id, database_id).await?;
+
+  let row_uuid = row_id;
+  let row_id = row_id.to_string();
+  let db_collab_update = {
+    let mut txn = db_collab.transact_mut();
+    let mut found = false;
+    update_all_database_views(&db_body, &mut txn, |view, update| {
+      if view
+        .row_orders
+        .iter()
+        .any(|row_order| row_order.id.to_string() == row_id)
+      {
+        update.remove_row_order(&row_id);
+        found = true;
+      }
+    });
+    if !found {
+      return Err(AppError::RecordNotFound(format!(
+        "row {} not found in database {}",
+        row_id, database_id
+      )));
+    }
+    txn.encode_update_v1()
+  };
+
+  state
+    .ws_server
+    .publish_update(
+      workspace_id,
+      database_id,
+      CollabType::Database,
+      &CollabOrigin::Server,
+      db_collab_update,
+    )
+    .await?;
+  state
+    .collab_storage
+    .delete_collab(&workspace_id, &uid, &row_uuid)
+    .await?;
+  delete_database_row_index(&state.pg_pool, &workspace_id, &[row_uuid]).await?;
+  Ok(())
+}
+
+pub async fn update_database_field(
+  state: &AppState,
+  workspace_id: Uuid,
+  database_id: Uuid,
+  field_id: &str,
+  update_field: AFUpdateDatabaseField,
+) -> Result<(), AppError> {
+  let (mut db_collab, db_body) =
+    get_latest_collab_database_body(&state.collab_storage, workspace_id, database_id).await?;
+
+  let db_collab_update = {
+    let mut txn = db_collab.transact_mut();
+    let field = db_body
+      .fields
+      .get_field(&txn, field_id)
+      .ok_or_else(|| AppError::RecordNotFound(format!("field {} not found", field_id)))?;
+    let field_type = update_field.field_type.unwrap_or(field.field_type);
+    if field.is_primary && field_type != field.field_type {
+      return Err(AppError::InvalidRequest(
+        "The type of the primary field cannot be changed".to_string(),
+      ));
+    }
+    let type_option_data = match update_field.type_option_data {
+      Some(type_option_data) => Some(serde_json::from_value(type_option_data).map_err(|err| {
+        AppError::InvalidRequest(format!("Failed to parse type option: {:?}", err))
+      })?),
+      None => None,
+    };
+
+    db_body
+      .fields
+      .update_field(&mut txn, field_id, |mut update| {
+        if let Some(name) = update_field.name {
+          update = update.set_name(name);
+        }
+        if field_type != field.field_type {
+          update = update.set_field_type(field_type);
+        }
+        if type_option_data.is_some() {
+          update.set_type_option(field_type, type_option_data);
+        }
+      });
+    txn.encode_update_v1()
+  };
+
+  state
+    .ws_server
+    .publish_update(
+      workspace_id,
+      database_id,
+      CollabType::Database,
+      &CollabOrigin::Server,
+      db_collab_update,
+    )
+    .await?;
+  Ok(())
+}
+
+/// Deletes the field and removes it from every view of the database, along with the filters,
+/// sorts and settings of the views on the field. The primary field cannot be deleted.
+pub async fn delete_database_field(
+  state: &AppState,
+  workspace_id: Uuid,
+  database_id: Uuid,
+  field_id: &str,
+) -> Result<(), AppError> {
+  let (mut db_collab, db_body) =
+    get_latest_collab_database_body(&state.collab_storage, workspace_id, database_id).await?;
+
+  let db_collab_update = {
+    let mut txn = db_collab.transact_mut();
+    let field = db_body
+      .fields
+      .get_field(&txn, field_id)
+      .ok_or_else(|| AppError::RecordNotFound(format!("field {} not found", field_id)))?;
+    if field.is_primary {
+      return Err(AppError::InvalidRequest(
+        "The primary field cannot be deleted".to_string(),
+      ));
+    }
+
+    db_body.fields.delete_field(&mut txn, field_id);
+    update_all_database_views(&db_body, &mut txn, |view, mut update| {
+      if view
+        .field_orders
+        .iter()
+        .any(|field_order| field_order.id == field_id)
+      {
+        update = update.remove_field_order(field_id);
+      }
+      update = update.remove_field_setting(field_id);
+      let filters = view
+        .filters
+        .iter()
+        .filter_map(|filter| remove_field_from_filter(filter, field_id))
+        .collect::<Vec<_>>();
+      if filters != view.filters {
+        update = update.set_filters(filters);
+      }
+      if view
+        .sorts
+        .iter()
+        .any(|sort| is_field_reference(sort, field_id))
+      {
+        let sorts = view
+          .sorts
+          .iter()
+          .filter(|sort| !is_field_reference(sort, field_id))
+          .cloned()
+          .collect();
+        update.set_sorts(sorts);
+      }
+    });
+    txn.encode_update_v1()
+  };
+
+  state
+    .ws_server
+    .publish_update(
+      workspace_id,
+      database_id,
+      CollabType::Database,
+      &CollabOrigin::Server,
+      db_collab_update,
+    )
+    .await?;
+  Ok(())
+}
+
+/// Applies the order of `field_ids` to the fields of every view of the database.
+pub async fn reorder_database_fields(
+  state: &AppState,
+  workspace_id: Uuid,
+  database_id: Uuid,
+  field_ids: &[String],
+) -> Result<(), AppError> {
+  let (mut db_collab, db_body) =
+    get_latest_collab_database_body(&state.collab_storage, workspace_id, database_id).await?;
+
+  let db_collab_update = {
+    let mut txn = db_collab.transact_mut();
+    let all_field_ids = db_body
+      .fields
+      .get_all_fields(&txn)
+      .into_iter()
+      .map(|field| field.id)
+      .collect::<HashSet<_>>();
+    if let Some(field_id) = field_ids.iter().find(|id| !all_field_ids.contains(*id)) {
+      return Err(AppError::RecordNotFound(format!(
+        "field {} not found",
+        field_id
+      )));
+    }
+
+    let position_by_id = field_ids
+      .iter()
+      .enumerate()
+      .map(|(position, id)| (id.as_str(), position))
+      .collect::<HashMap<_, _>>();
+    update_all_database_views(&db_body, &mut txn, |view, mut update| {
+      let mut current = field_order_ids(view);
+      let mut target = current.clone();
+      // sort is stable, so the fields that are not listed keep their relative order
+      target.sort_by_key(|id| {
+        position_by_id
+          .get(id.as_str())
+          .copied()
+          .unwrap_or(usize::MAX)
+      });
+      // Each field is moved to its target position one by one, rather than rewriting the whole
+      // order, so that fields added concurrently are kept.
+      for (index, id) in target.iter().enumerate() {
+        if current[index] == *id {
+          continue;
+        }
+        update = update.move_field_order(id, &current[index]);
+        if let Some(from) = current.iter().position(|current_id| current_id == id) {
+          let moved = current.remove(from);
+          current.insert(index, moved);
+        }
+      }
+    });
+    txn.encode_update_v1()
+  };
+
+  state
+    .ws_server
+    .publish_update(
+      workspace_id,
+      database_id,
+      CollabType::Database,
+      &CollabOrigin::Server,
+      db_collab_update,
+    )
+    .await?;
+  Ok(())
+}
+
+/// Calls `f` with every view of the database and an update of the view. The views are changed
+/// item by item through the update, so the other properties of the views and concurrent edits to
+/// them are kept.
+fn update_all_database_views(
+  db_body: &DatabaseBody,
+  txn: &mut yrs::TransactionMut<'_>,
+  mut f: impl FnMut(&DatabaseView, DatabaseViewUpdate),
+) {
+  for db_view in db_body.views.get_all_views(txn) {
+    db_body
+      .views
+      .update_database_view(txn, &db_view.id, |update| f(&db_view, update));
+  }
+}
+
+/// Returns true if the filter or the sort is on the field.
+fn is_field_reference(map: &HashMap<String, Any>, field_id: &str) -> bool {
+  matches!(map.get("field_id"), Some(Any::String(id)) if id.as_ref() == field_id)
+}
+
+/// Removes the conditions on the field from the filter, including the ones nested in filter
+/// groups. Returns None if nothing is left of the filter.
+fn remove_field_from_filter(filter: &FilterMap, field_id: &str) -> Option<FilterMap> {
+  if is_field_reference(filter, field_id) {
+    return None;
+  }
+  let Some(Any::Array(children)) = filter.get("children") else {
+    return Some(filter.clone());
+  };
+  let remaining = children
+    .iter()
+    .filter_map(|child| match child {
+      Any::Map(child) => {
+        remove_field_from_filter(child, field_id).map(|child| Any::Map(Arc::new(child)))
+      },
+      child => Some(child.clone()),
+    })
+    .collect::<Vec<_>>();
+  if remaining.is_empty() && !children.is_empty() {
+    return None;
+  }
+  let mut filter = filter.clone();
+  filter.insert("children".to_string(), Any::Array(remaining.into()));
+  Some(filter)
+}
+
+fn field_order_ids(view: &DatabaseView) -> Vec<String> {
+  view
+    .field_orders
+    .iter()
+    .map(|field_order| field_order.id.clone())
+    .collect()
+}
+
 pub async fn list_database_row_ids_updated(
   collab_storage: &Arc<dyn CollabStore>,
   pg_pool: &PgPool,
@@ -880,6 +1224,168 @@ pub async fn list_database_row_details(
 
   let type_option_reader_by_id = type_option_reader_by_id(&all_fields);
   let field_by_id = field_by_id_name_uniq(all_fields);
+  let mut db_row_details = batch_get_database_row_details(
+    collab_storage,
+    uid,
+    workspace_uuid,
+    row_ids,
+    &field_by_id,
+    &type_option_reader_by_id,
+  )
+  .await;
+
+  // Fill in the document content if requested and exists
+  if with_doc {
+    fill_in_db_row_docs(collab_storage, uid, workspace_uuid, &mut db_row_details).await;
+  }
+
+  Ok(db_row_details)
+}
+
+/// Filters, sorts and paginates the rows of the database inline view. The cursor is the opaque
+/// [RowCursor] of the last row of the previous page. Only the rows of the page are loaded when
+/// the query has no filter and sorts, otherwise all the rows have to be read to be matched and
+/// ordered.
+pub async fn query_database_rows(
+  collab_storage: &Arc<dyn CollabStore>,
+  uid: i64,
+  workspace_uuid: Uuid,
+  database_uuid: Uuid,
+  params: QueryDatabaseRowParams,
+  unsupported_field_types: &[FieldType],
+) -> Result<QueryDatabaseRowResponse, AppError> {
+  let cursor = params
+    .cursor
+    .as_deref()
+    .map(RowCursor::decode)
+    .transpose()?;
+  let limit = params
+    .limit
+    .unwrap_or(DEFAULT_QUERY_ROW_LIMIT)
+    .clamp(1, MAX_QUERY_ROW_LIMIT) as usize;
+
+  let (database_collab, db_body) =
+    get_latest_collab_database_body(collab_storage, workspace_uuid, database_uuid).await?;
+  let (all_fields, row_ids) = {
+    let txn = database_collab.transact();
+    let iid = db_body.get_inline_view_id(&txn);
+    let iview = db_body.views.get_view(&txn, &iid).ok_or_else(|| {
+      AppError::Internal(anyhow::anyhow!("Failed to get inline view, iid: {}", iid))
+    })?;
+    let all_fields: Vec<Field> = db_body
+      .fields
+      .get_all_fields(&txn)
+      .into_iter()
+      .filter(|field| !unsupported_field_types.contains(&FieldType::from(field.field_type)))
+      .collect();
+    let row_ids: Vec<Uuid> = iview
+      .row_orders
+      .into_iter()
+      .flat_map(|row_order| Uuid::parse_str(&row_order.id))
+      .collect();
+    (all_fields, row_ids)
+  };
+
+  let type_option_reader_by_id = type_option_reader_by_id(&all_fields);
+  let field_by_id = field_by_id_name_uniq(all_fields);
+  let query = DatabaseRowQuery::new(&field_by_id, params.filter.as_ref(), &params.sorts)?;
+
+  let total;
+  let next_cursor;
+  let mut rows = if query.is_empty() {
+    let start = match &cursor {
+      Some(cursor) => row_ids
+        .iter()
+        .position(|row_id| row_id.to_string() == cursor.row_id())
+        .map(|index| index + 1)
+        .unwrap_or(cursor.position()),
+      None => 0,
+    }
+    .min(row_ids.len());
+    let end = (start + limit).min(row_ids.len());
+    let page_row_ids = &row_ids[start..end];
+    let mut row_details_by_id = batch_get_database_row_details(
+      collab_storage,
+      uid,
+      workspace_uuid,
+      page_row_ids,
+      &field_by_id,
+      &type_option_reader_by_id,
+    )
+    .await
+    .into_iter()
+    .map(|row| (row.id.clone(), row))
+    .collect::<HashMap<_, _>>();
+
+    total = row_ids.len();
+    next_cursor =
+      (end < total).then(|| RowCursor::at_position(row_ids[end - 1].to_string(), end - 1).encode());
+    page_row_ids
+      .iter()
+      .flat_map(|row_id| row_details_by_id.remove(&row_id.to_string()))
+      .collect::<Vec<_>>()
+  } else {
+    let mut row_details_by_id = batch_get_database_row_details(
+      collab_storage,
+      uid,
+      workspace_uuid,
+      &row_ids,
+      &field_by_id,
+      &type_option_reader_by_id,
+    )
+    .await
+    .into_iter()
+    .map(|row| (row.id.clone(), row))
+    .collect::<HashMap<_, _>>();
+    let position_by_id = row_ids
+      .iter()
+      .enumerate()
+      .map(|(position, row_id)| (row_id.to_string(), position))
+      .collect::<HashMap<_, _>>();
+    let row_details = row_ids
+      .iter()
+      .flat_map(|row_id| row_details_by_id.remove(&row_id.to_string()))
+      .collect::<Vec<_>>();
+
+    let matched_rows = query.apply(row_details);
+    let position = |row: &AFDatabaseRowDetail| position_by_id.get(&row.id).copied().unwrap_or(0);
+    let start = cursor
+      .as_ref()
+      .map(|cursor| query.start_after(&matched_rows, cursor, position))
+      .unwrap_or(0);
+    let end = (start + limit).min(matched_rows.len());
+    total = matched_rows.len();
+    next_cursor = (end < total).then(|| {
+      let last_row = &matched_rows[end - 1];
+      query.cursor(last_row, position(last_row)).encode()
+    });
+    matched_rows
+      .into_iter()
+      .skip(start)
+      .take(end - start)
+      .collect::<Vec<_>>()
+  };
+  if params.with_doc {
+    fill_in_db_row_docs(collab_storage, uid, workspace_uuid, &mut rows).await;
+  }
+
+  Ok(QueryDatabaseRowResponse {
+    rows,
+    total,
+    next_cursor,
+  })
+}
+
+/// Returns the serialized cells of the given rows. Rows that cannot be loaded are skipped, and
+/// the returned rows are not guaranteed to follow the order of `row_ids`.
+async fn batch_get_database_row_details(
+  collab_storage: &Arc<dyn CollabStore>,
+  uid: i64,
+  workspace_uuid: Uuid,
+  row_ids: &[Uuid],
+  field_by_id: &HashMap<String, Field>,
+  type_option_reader_by_id: &HashMap<String, Box<dyn TypeOptionCellReader>>,
+) -> Vec<AFDatabaseRowDetail> {
   let client_id = default_client_id();
   let query_collabs: Vec<QueryCollab> = row_ids
     .iter()
@@ -888,7 +1394,7 @@ pub async fn list_database_row_details(
       collab_type: CollabType::DatabaseRow,
     })
     .collect();
-  let mut db_row_details = collab_storage
+  collab_storage
     .batch_get_collab(&uid, workspace_uuid, query_collabs)
     .await
     .into_iter()
@@ -920,7 +1426,7 @@ pub async fn list_database_row_details(
         };
 
         let has_doc = !row_detail.meta.is_document_empty;
-        let cells = get_row_details_serde(row_detail, &field_by_id, &type_option_reader_by_id);
+        let cells = get_row_details_serde(row_detail, field_by_id, type_option_reader_by_id);
         Some(AFDatabaseRowDetail {
           id,
           cells,
@@ -933,43 +1439,45 @@ pub async fn list_database_row_details(
         None
       },
     })
-    .collect::<Vec<AFDatabaseRowDetail>>();
+    .collect::<Vec<AFDatabaseRowDetail>>()
+}
 
-  // Fill in the document content if requested and exists
-  if with_doc {
-    let doc_id_by_row_id = db_row_details
-      .iter()
-      .filter(|row| row.has_doc)
-      .flat_map(|row| {
-        row.id.parse::<Uuid>().ok().map(|row_uuid| {
-          (
-            row_uuid,
-            meta_id_from_row_id(&row_uuid, RowMetaKey::DocumentId)
-              .parse::<Uuid>()
-              .unwrap(),
-          )
-        })
-      })
-      .collect::<HashMap<_, _>>();
-    let query_db_docs = doc_id_by_row_id
-      .values()
-      .map(|doc_id| QueryCollab {
-        object_id: *doc_id,
-        collab_type: CollabType::Document,
+async fn fill_in_db_row_docs(
+  collab_storage: &Arc<dyn CollabStore>,
+  uid: i64,
+  workspace_uuid: Uuid,
+  db_row_details: &mut [AFDatabaseRowDetail],
+) {
+  let client_id = default_client_id();
+  let doc_id_by_row_id = db_row_details
+    .iter()
+    .filter(|row| row.has_doc)
+    .flat_map(|row| {
+      row.id.parse::<Uuid>().ok().map(|row_uuid| {
+        (
+          row_uuid,
+          meta_id_from_row_id(&row_uuid, RowMetaKey::DocumentId)
+            .parse::<Uuid>()
+            .unwrap(),
+        )
       })
-      .collect::<Vec<_>>();
-    let mut query_res = collab_storage
-      .batch_get_collab(&uid, workspace_uuid, query_db_docs)
-      .await;
-    for row_detail in &mut db_row_details {
-      if let Err(err) = fill_in_db_row_doc(client_id, row_detail, &doc_id_by_row_id, &mut query_res)
-      {
-        tracing::error!("Failed to fill in document content: {:?}", err);
-      };
-    }
+    })
+    .collect::<HashMap<_, _>>();
+  let query_db_docs = doc_id_by_row_id
+    .values()
+    .map(|doc_id| QueryCollab {
+      object_id: *doc_id,
+      collab_type: CollabType::Document,
+    })
+    .collect::<Vec<_>>();
+  let mut query_res = collab_storage
+    .batch_get_collab(&uid, workspace_uuid, query_db_docs)
+    .await;
+  for row_detail in db_row_details.iter_mut() {
+    if let Err(err) = fill_in_db_row_doc(client_id, row_detail, &doc_id_by_row_id, &mut query_res) {
+      tracing::error!("Failed to fill in document content: {:?}", err);
+    };
   }
-
-  Ok(db_row_details)
 }
 
 fn fill_in_db_row_doc(
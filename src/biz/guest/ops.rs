@@ -0,0 +1,453 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::DerefMut;
+
+use anyhow::Context;
+use app_error::AppError;
+use appflowy_collaborate::ws2::{
+  PermissionType, PermissionUpdate, UpdateUserPermissions, WorkspaceCollabInstanceCache,
+};
+use database::guest::{
+  delete_guest_view_access, delete_inherited_guest_view_access, insert_inherited_guest_view_access,
+  select_guest_access_levels, select_guest_access_levels_for_views,
+  select_guests_with_access_to_views, select_views_shared_with_any_guest,
+  select_views_shared_with_guest, upsert_guest_view_access,
+};
+use database::user::select_uid_from_email;
+use database::workspace::{
+  insert_workspace_invitation, select_workspace_member, select_workspace_member_list_exclude_guest,
+  select_workspace_pending_invitations, upsert_workspace_member_with_txn,
+};
+use database_entity::dto::{AFAccessLevel, AFRole};
+use shared_entity::dto::guest_dto::{
+  RevokeSharedViewAccessRequest, ShareViewWithGuestRequest, SharedUser, SharedView,
+  SharedViewDetails, SharedViews,
+};
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+use crate::biz::collab::folder_view::{
+  get_view_and_children, private_space_and_trash_view_ids, ViewTree,
+};
+use crate::mailer::WorkspaceInviteMailerParam;
+use crate::state::AppState;
+
+fn flatten_view_tree(view_tree: &ViewTree, view_ids: &mut Vec<Uuid>) {
+  if let Ok(view_id) = Uuid::parse_str(&view_tree.view.id) {
+    view_ids.push(view_id);
+  }
+  for child in &view_tree.children {
+    flatten_view_tree(child, view_ids);
+  }
+}
+
+fn to_permission_type(access_level: Option<AFAccessLevel>) -> PermissionType {
+  match access_level {
+    None => PermissionType::NoAccess,
+    Some(access_level) if access_level.can_write() => PermissionType::Write,
+    Some(_) => PermissionType::Read,
+  }
+}
+
+/// Returns the views that were shared with guests. A guest only sees the views that were shared
+/// with them, while a workspace member sees every view of the workspace that was shared with a guest.
+pub async fn list_shared_views(
+  state: &AppState,
+  uid: i64,
+  workspace_id: Uuid,
+) -> Result<SharedViews, AppError> {
+  let member = select_workspace_member(&state.pg_pool, uid, &workspace_id)
+    .await?
+    .ok_or(AppError::NotEnoughPermissions)?;
+  let candidates: Vec<(Uuid, AFAccessLevel)> = match member.role {
+    AFRole::Guest => select_views_shared_with_guest(&state.pg_pool, &workspace_id, uid)
+      .await?
+      .into_iter()
+      .map(|row| (row.view_id, AFAccessLevel::from(row.access_level)))
+      .collect(),
+    role => {
+      let access_level = AFAccessLevel::from(&role);
+      select_views_shared_with_any_guest(&state.pg_pool, &workspace_id)
+        .await?
+        .into_iter()
+        .map(|view_id| (view_id, access_level))
+        .collect()
+    },
+  };
+
+  let folder = state.ws_server.get_folder(workspace_id).await?;
+  let private_space_and_trash_views = private_space_and_trash_view_ids(uid, &folder)?;
+  let mut shared_views = vec![];
+  let mut view_id_with_no_access = vec![];
+  for (view_id, access_level) in candidates {
+    let is_accessible = folder.get_view(&view_id.to_string(), uid).is_some()
+      && !private_space_and_trash_views
+        .view_ids_in_trash
+        .contains(&view_id)
+      && !private_space_and_trash_views
+        .other_private_space_ids
+        .contains(&view_id);
+    if is_accessible {
+      shared_views.push(SharedView {
+        view_id,
+        access_level,
+      });
+    } else {
+      view_id_with_no_access.push(view_id);
+    }
+  }
+
+  Ok(SharedViews {
+    shared_views,
+    view_id_with_no_access,
+  })
+}
+
+/// Shares a view, and all of its child views, with the given emails. Users that are already
+/// members of the workspace are skipped, as their role already grants them access. Users who are
+/// not part of the workspace yet receive an invitation, unless `auto_confirm` is set and they
+/// already have an account.
+#[instrument(level = "debug", skip_all, err)]
+pub async fn share_view_with_guests(
+  state: &AppState,
+  uid: i64,
+  user_uuid: &Uuid,
+  workspace_id: Uuid,
+  request: ShareViewWithGuestRequest,
+) -> Result<(), AppError> {
+  let view_id = request.view_id;
+  state
+    .collab_access_control
+    .enforce_access_level(&workspace_id, &uid, &view_id, AFAccessLevel::FullAccess)
+    .await?;
+
+  let folder = state.ws_server.get_folder(workspace_id).await?;
+  let view_tree = get_view_and_children(&folder, &view_id.to_string(), uid)?
+    .ok_or_else(|| AppError::RecordNotFound(format!("view {} not found", view_id)))?;
+  let mut view_ids = vec![];
+  flatten_view_tree(&view_tree, &mut view_ids);
+
+  let member_emails: HashSet<String> =
+    select_workspace_member_list_exclude_guest(&state.pg_pool, &workspace_id)
+      .await?
+      .into_iter()
+      .map(|row| row.email.to_lowercase())
+      .collect();
+  let pending_invitations: HashMap<String, Uuid> =
+    select_workspace_pending_invitations(&state.pg_pool, &workspace_id)
+      .await?
+      .into_iter()
+      .map(|(email, invite_id)| (email.to_lowercase(), invite_id))
+      .collect();
+
+  let mut txn = state
+    .pg_pool
+    .begin()
+    .await
+    .context("Begin transaction to share view with guests")?;
+  let mut confirmed_guests = vec![];
+  let mut new_guests = vec![];
+  let mut invitations = vec![];
+  for email in request.emails {
+    let email = email.trim().to_lowercase();
+    if member_emails.contains(&email) {
+      warn!(
+        "{} is already a member of workspace {}",
+        email, workspace_id
+      );
+      continue;
+    }
+    upsert_guest_view_access(
+      txn.deref_mut(),
+      &workspace_id,
+      &view_id,
+      &view_ids,
+      &email,
+      request.access_level,
+      uid,
+    )
+    .await?;
+
+    let guest_uid = select_uid_from_email(txn.deref_mut(), &email).await.ok();
+    let is_guest = match guest_uid {
+      Some(guest_uid) => select_workspace_member(txn.deref_mut(), guest_uid, &workspace_id)
+        .await?
+        .is_some(),
+      None => false,
+    };
+    match guest_uid {
+      Some(guest_uid) if is_guest => confirmed_guests.push(guest_uid),
+      Some(guest_uid) if request.auto_confirm => {
+        upsert_workspace_member_with_txn(&mut txn, &workspace_id, &email, AFRole::Guest).await?;
+        new_guests.push(guest_uid);
+        confirmed_guests.push(guest_uid);
+      },
+      _ => {
+        let invite_id = match pending_invitations.get(&email) {
+          Some(invite_id) => *invite_id,
+          None => {
+            let invite_id = Uuid::new_v4();
+            insert_workspace_invitation(
+              &mut txn,
+              &invite_id,
+              &workspace_id,
+              user_uuid,
+              &email,
+              &AFRole::Guest,
+            )
+            .await?;
+            invite_id
+          },
+        };
+        invitations.push((email, invite_id));
+      },
+    }
+  }
+  txn
+    .commit()
+    .await
+    .context("Commit transaction to share view with guests")?;
+
+  for guest_uid in new_guests {
+    state
+      .workspace_access_control
+      .insert_role(&guest_uid, &workspace_id, AFRole::Guest)
+      .await?;
+  }
+  for guest_uid in confirmed_guests {
+    sync_guest_view_policies(state, workspace_id, guest_uid, &view_ids).await?;
+  }
+  if !invitations.is_empty() {
+    send_guest_invitations(state, user_uuid, workspace_id, invitations).await?;
+  }
+  Ok(())
+}
+
+async fn send_guest_invitations(
+  state: &AppState,
+  inviter: &Uuid,
+  workspace_id: Uuid,
+  invitations: Vec<(String, Uuid)>,
+) -> Result<(), AppError> {
+  let inviter_name = database::user::select_name_from_uuid(&state.pg_pool, inviter).await?;
+  let workspace_name =
+    database::workspace::select_workspace_name_from_workspace_id(&state.pg_pool, &workspace_id)
+      .await?
+      .unwrap_or_default();
+  let workspace_member_count =
+    database::workspace::select_workspace_member_count_from_workspace_id(
+      &state.pg_pool,
+      &workspace_id,
+    )
+    .await?
+    .unwrap_or_default();
+  for (email, invite_id) in invitations {
+    // use default icon until we have workspace icon
+    let workspace_icon_url =
+      "https://miro.medium.com/v2/resize:fit:2400/1*mTPfm7CwU31-tLhtLNkyJw.png".to_string();
+    let user_icon_url =
+      "https://cdn.pixabay.com/photo/2015/10/05/22/37/blank-profile-picture-973460_1280.png"
+        .to_string();
+    let accept_url = format!(
+      "{}/accept-invitation?invited_id={}",
+      state.config.appflowy_web_url, invite_id
+    );
+    let mailer = state.mailer.clone();
+    let param = WorkspaceInviteMailerParam {
+      user_icon_url,
+      username: inviter_name.clone(),
+      workspace_name: workspace_name.clone(),
+      workspace_icon_url,
+      workspace_member_count: workspace_member_count.to_string(),
+      accept_url,
+    };
+    tokio::spawn(async move {
+      if let Err(err) = mailer.send_workspace_invite(&email, param).await {
+        warn!("Failed to send guest invitation to {}: {}", email, err);
+      }
+    });
+  }
+  Ok(())
+}
+
+/// Returns the users that have access to the view through a share, including the shares made on
+/// the ancestors of the view.
+pub async fn get_shared_view_details(
+  state: &AppState,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: Uuid,
+  ancestor_view_ids: Vec<Uuid>,
+) -> Result<SharedViewDetails, AppError> {
+  state
+    .collab_access_control
+    .enforce_access_level(&workspace_id, &uid, &view_id, AFAccessLevel::ReadOnly)
+    .await?;
+  let mut view_ids = ancestor_view_ids;
+  view_ids.push(view_id);
+  let shared_with =
+    select_guests_with_access_to_views(&state.pg_pool, &workspace_id, &view_id, &view_ids)
+      .await?
+      .into_iter()
+      .map(|row| SharedUser {
+        view_id: row.view_id,
+        name: row.name.unwrap_or_else(|| row.email.clone()),
+        email: row.email,
+        access_level: AFAccessLevel::from(row.access_level),
+        role: row.role_id.map(AFRole::from).unwrap_or(AFRole::Guest),
+        avatar_url: row.avatar_url,
+        pending_invitation: row.role_id.is_none(),
+      })
+      .collect();
+  Ok(SharedViewDetails {
+    view_id,
+    shared_with,
+  })
+}
+
+/// Revokes the access of the given emails to a shared view, and to the child views that inherited
+/// the share. Guests that are currently connected are notified that they lost access.
+#[instrument(level = "debug", skip_all, err)]
+pub async fn revoke_shared_view_access(
+  state: &AppState,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: Uuid,
+  request: RevokeSharedViewAccessRequest,
+) -> Result<(), AppError> {
+  state
+    .collab_access_control
+    .enforce_access_level(&workspace_id, &uid, &view_id, AFAccessLevel::FullAccess)
+    .await?;
+  for email in request.emails {
+    let email = email.trim().to_lowercase();
+    let view_ids =
+      delete_guest_view_access(&state.pg_pool, &workspace_id, &view_id, &email).await?;
+    if view_ids.is_empty() {
+      continue;
+    }
+    if let Ok(guest_uid) = select_uid_from_email(&state.pg_pool, &email).await {
+      sync_guest_view_policies(state, workspace_id, guest_uid, &view_ids).await?;
+    }
+  }
+  Ok(())
+}
+
+/// Shares newly created views with the guests that have access to their parent view. The views
+/// must all be descendants of the parent, e.g. a new page or a duplicated subtree.
+///
+/// Only the views created through the REST API inherit the access of their parent. Views created
+/// or moved by clients through folder sync are not visible to the guests of their parent until
+/// they are shared explicitly.
+pub async fn inherit_guest_view_access(
+  state: &AppState,
+  workspace_id: Uuid,
+  parent_view_id: &Uuid,
+  view_ids: &[Uuid],
+) -> Result<(), AppError> {
+  let guest_uids =
+    insert_inherited_guest_view_access(&state.pg_pool, &workspace_id, parent_view_id, view_ids)
+      .await?;
+  for guest_uid in guest_uids {
+    sync_guest_view_policies(state, workspace_id, guest_uid, view_ids).await?;
+  }
+  Ok(())
+}
+
+/// Replaces the access the moved views inherited from their previous ancestors with the access
+/// of their new parent. `view_ids` are the moved view and all its descendants. The views that
+/// were shared explicitly keep their guests.
+pub async fn reinherit_guest_view_access(
+  state: &AppState,
+  workspace_id: Uuid,
+  new_parent_view_id: &Uuid,
+  view_ids: &[Uuid],
+) -> Result<(), AppError> {
+  let mut txn = state
+    .pg_pool
+    .begin()
+    .await
+    .context("Begin transaction to move guest view access")?;
+  let mut guest_uids: HashSet<i64> =
+    delete_inherited_guest_view_access(txn.deref_mut(), &workspace_id, view_ids)
+      .await?
+      .into_iter()
+      .collect();
+  guest_uids.extend(
+    insert_inherited_guest_view_access(
+      txn.deref_mut(),
+      &workspace_id,
+      new_parent_view_id,
+      view_ids,
+    )
+    .await?,
+  );
+  txn
+    .commit()
+    .await
+    .context("Commit transaction to move guest view access")?;
+  for guest_uid in guest_uids {
+    sync_guest_view_policies(state, workspace_id, guest_uid, view_ids).await?;
+  }
+  Ok(())
+}
+
+/// Recomputes the access level of the guest on the given views, updates the access control
+/// policies accordingly, and notifies the active sessions of the guest.
+async fn sync_guest_view_policies(
+  state: &AppState,
+  workspace_id: Uuid,
+  guest_uid: i64,
+  view_ids: &[Uuid],
+) -> Result<(), AppError> {
+  let access_levels: HashMap<Uuid, AFAccessLevel> =
+    select_guest_access_levels_for_views(&state.pg_pool, &workspace_id, guest_uid, view_ids)
+      .await?
+      .into_iter()
+      .map(|row| (row.view_id, AFAccessLevel::from(row.access_level)))
+      .collect();
+
+  let mut updates = Vec::with_capacity(view_ids.len());
+  for view_id in view_ids {
+    let access_level = access_levels.get(view_id).copied();
+    match access_level {
+      Some(access_level) => {
+        state
+          .collab_access_control
+          .update_access_level_policy(&guest_uid, view_id, access_level)
+          .await?
+      },
+      None => {
+        state
+          .collab_access_control
+          .remove_access_level(&guest_uid, view_id)
+          .await?
+      },
+    }
+    updates.push(PermissionUpdate {
+      object_id: *view_id,
+      permission_type: to_permission_type(access_level),
+    });
+  }
+
+  state.ws_server.do_send(UpdateUserPermissions {
+    workspace_id,
+    uid: guest_uid,
+    updates,
+  });
+  Ok(())
+}
+
+/// Loads the access control policies of every view shared with the guest. Called once the guest
+/// accepts the invitation to the workspace.
+pub async fn load_guest_view_policies(
+  state: &AppState,
+  workspace_id: Uuid,
+  guest_uid: i64,
+) -> Result<(), AppError> {
+  let view_ids: Vec<Uuid> = select_guest_access_levels(&state.pg_pool, &workspace_id, guest_uid)
+    .await?
+    .into_iter()
+    .map(|row| row.view_id)
+    .collect();
+  sync_guest_view_policies(state, workspace_id, guest_uid, &view_ids).await
+}
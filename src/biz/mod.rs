@@ -1,11 +1,15 @@
 pub mod access_request;
+pub mod ai;
+pub mod audit;
 pub mod authentication;
 pub mod chat;
 pub mod collab;
 pub mod data_import;
+pub mod guest;
 pub mod notification;
 pub mod pg_listener;
 pub mod search;
 pub mod template;
 pub mod user;
+pub mod webhook;
 pub mod workspace;
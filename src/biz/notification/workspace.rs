@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use actix::Addr;
+use appflowy_collaborate::ws2::{BroadcastNotification, WsServer};
+use appflowy_proto::WorkspaceNotification;
+use database::notification::notify_workspace_sessions;
+use database::pg_row::AFWorkspaceNotification;
+use sqlx::PgPool;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, trace, warn};
+use uuid::Uuid;
+
+use crate::biz::pg_listener::PgListeners;
+
+/// Maximum number of views in a single [WorkspaceNotification::PublishChanged]. Keeps the payload
+/// well under the 8000 bytes accepted by `pg_notify`, at about 40 bytes per view id.
+const MAX_PUBLISH_CHANGED_VIEW_IDS: usize = 100;
+
+/// Notifies the sessions connected to the workspace, on every server node. Failing to send the
+/// notification must not fail the request that produced it, so errors are only logged.
+pub async fn notify_workspace(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  notification: WorkspaceNotification,
+) {
+  send_workspace_notification(
+    pg_pool,
+    AFWorkspaceNotification {
+      workspace_id: Some(*workspace_id),
+      uid: None,
+      notification,
+    },
+  )
+  .await
+}
+
+/// Notifies the sessions connected to the workspace that the views were published or unpublished,
+/// in as many notifications as needed to stay under the size limit of `pg_notify`. Each session
+/// only receives the views it can read.
+pub async fn notify_publish_changed(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+  published: bool,
+) {
+  for view_ids in view_ids.chunks(MAX_PUBLISH_CHANGED_VIEW_IDS) {
+    notify_workspace(
+      pg_pool,
+      workspace_id,
+      WorkspaceNotification::PublishChanged {
+        view_ids: view_ids.to_vec(),
+        published,
+      },
+    )
+    .await;
+  }
+}
+
+/// Notifies the sessions of the user connected to the workspace, or to any workspace when no
+/// workspace is given.
+pub async fn notify_workspace_user(
+  pg_pool: &PgPool,
+  workspace_id: Option<&Uuid>,
+  uid: i64,
+  notification: WorkspaceNotification,
+) {
+  send_workspace_notification(
+    pg_pool,
+    AFWorkspaceNotification {
+      workspace_id: workspace_id.copied(),
+      uid: Some(uid),
+      notification,
+    },
+  )
+  .await
+}
+
+async fn send_workspace_notification(pg_pool: &PgPool, notification: AFWorkspaceNotification) {
+  if let Err(err) = notify_workspace_sessions(pg_pool, &notification).await {
+    error!(
+      "failed to send workspace notification {:?}: {}",
+      notification, err
+    );
+  }
+}
+
+/// Passes the workspace notifications sent by any server node, or by the workers, to the
+/// sessions connected to this node.
+pub async fn forward_workspace_notifications(
+  pg_listeners: Arc<PgListeners>,
+  ws_server: Addr<WsServer>,
+) {
+  let mut notifications = pg_listeners.subscribe_workspace_notification();
+  loop {
+    match notifications.recv().await {
+      Ok(notification) => {
+        trace!("forwarding workspace notification: {:?}", notification);
+        ws_server.do_send(BroadcastNotification {
+          workspace_id: notification.workspace_id,
+          uid: notification.uid,
+          notification: notification.notification,
+        });
+      },
+      Err(RecvError::Lagged(count)) => {
+        warn!("dropped {} workspace notifications", count);
+      },
+      Err(RecvError::Closed) => break,
+    }
+  }
+}
@@ -1,16 +1,23 @@
 use anyhow::Error;
 use database::listener::PostgresDBListener;
-use database::pg_row::AFUserNotification;
+use database::notification::WORKSPACE_NOTIFICATION_CHANNEL;
+use database::pg_row::{AFUserNotification, AFWorkspaceNotification};
 use sqlx::PgPool;
 
 pub struct PgListeners {
   user_listener: UserListener,
+  workspace_notification_listener: WorkspaceNotificationListener,
 }
 
 impl PgListeners {
   pub async fn new(pg_pool: &PgPool) -> Result<Self, Error> {
     let user_listener = UserListener::new(pg_pool, "af_user_channel").await?;
-    Ok(Self { user_listener })
+    let workspace_notification_listener =
+      WorkspaceNotificationListener::new(pg_pool, WORKSPACE_NOTIFICATION_CHANNEL).await?;
+    Ok(Self {
+      user_listener,
+      workspace_notification_listener,
+    })
   }
 
   pub fn subscribe_user_change(&self, uid: i64) -> tokio::sync::mpsc::Receiver<AFUserNotification> {
@@ -27,6 +34,13 @@ impl PgListeners {
     });
     rx
   }
+
+  pub fn subscribe_workspace_notification(
+    &self,
+  ) -> tokio::sync::broadcast::Receiver<AFWorkspaceNotification> {
+    self.workspace_notification_listener.notify.subscribe()
+  }
 }
 
 pub type UserListener = PostgresDBListener<AFUserNotification>;
+pub type WorkspaceNotificationListener = PostgresDBListener<AFWorkspaceNotification>;
@@ -1,37 +1,57 @@
 use crate::biz::collab::folder_view::PrivateSpaceAndTrashViews;
+use crate::biz::collab::utils::get_latest_collab;
+use crate::state::RedisConnectionManager;
 use crate::{
   api::metrics::RequestMetrics, biz::collab::folder_view::private_space_and_trash_view_ids,
 };
 use app_error::AppError;
-use appflowy_ai_client::dto::EmbeddingModel;
 use appflowy_collaborate::ws2::WorkspaceCollabInstanceCache;
+use collab::core::collab::default_client_id;
+use collab_database::workspace_database::WorkspaceDatabaseBody;
+use collab_entity::CollabType;
 use collab_folder::{Folder, View};
-use database::index::{search_documents, SearchDocumentParams};
+use database::collab::{select_workspace_database_oid, CollabStore, GetCollabOrigin};
+use database::index::{
+  search_attachments, search_attachments_by_keyword, search_documents, search_documents_by_keyword,
+  select_attachments_with_stale_embeddings, KeywordSearchDocumentParams, SearchDocumentParams,
+  SearchDocumentResult,
+};
 use indexer::scheduler::IndexerScheduler;
-use indexer::vector::embedder::{CreateEmbeddingRequestArgs, EmbeddingInput, EncodingFormat};
+use indexer::vector::embedder::EmbeddingInput;
 use infra::env_util::get_env_var;
 use llm_client::chat::{AITool, LLMDocument};
+use redis::AsyncCommands;
+use serde_json::json;
 use shared_entity::dto::search_dto::{
-  SearchContentType, SearchDocumentRequest, SearchDocumentResponseItem, SearchSummaryResult,
-  Summary, SummarySearchResultRequest,
+  SearchAttachment, SearchContentType, SearchDocumentRequest, SearchDocumentResponseItem,
+  SearchMode, SearchSummaryResult, Summary, SummarySearchResultRequest,
 };
 use sqlx::PgPool;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tracing::{error, trace};
 use uuid::Uuid;
 
 static MAX_SEARCH_DEPTH: i32 = 10;
 
+/// Redis stream read by the attachment indexer of the appflowy worker.
+const ATTACHMENT_INDEX_TASK_STREAM: &str = "attachment_index_task_stream";
+
 fn is_view_searchable(view: &View, workspace_id: &str) -> bool {
   view.id != workspace_id && view.parent_view_id != workspace_id && view.layout.is_document()
 }
 
+/// Database views are not indexed themselves, but their rows are returned in the search results.
+fn is_database_view_searchable(view: &View, workspace_id: &str) -> bool {
+  view.id != workspace_id && view.parent_view_id != workspace_id && view.layout.is_database()
+}
+
 #[allow(clippy::too_many_arguments)]
 fn populate_searchable_view_ids(
   folder: &Folder,
   private_space_and_trash_views: &PrivateSpaceAndTrashViews,
   searchable_view_ids: &mut HashSet<Uuid>,
+  searchable_database_view_ids: &mut HashSet<Uuid>,
   workspace_id: &Uuid,
   current_view_id: &Uuid,
   depth: i32,
@@ -57,6 +77,8 @@ fn populate_searchable_view_ids(
 
   if is_view_searchable(&view, &workspace_id.to_string()) {
     searchable_view_ids.insert(*current_view_id);
+  } else if is_database_view_searchable(&view, &workspace_id.to_string()) {
+    searchable_database_view_ids.insert(*current_view_id);
   }
   for child in view.children.iter() {
     let child_id = Uuid::parse_str(&child.id).unwrap();
@@ -64,6 +86,7 @@ fn populate_searchable_view_ids(
       folder,
       private_space_and_trash_views,
       searchable_view_ids,
+      searchable_database_view_ids,
       workspace_id,
       &child_id,
       depth + 1,
@@ -77,77 +100,117 @@ fn populate_searchable_view_ids(
 pub async fn search_document(
   pg_pool: &PgPool,
   collab_instance_cache: &impl WorkspaceCollabInstanceCache,
+  collab_storage: &Arc<dyn CollabStore>,
   indexer_scheduler: &Arc<IndexerScheduler>,
   uid: i64,
   workspace_uuid: Uuid,
   request: SearchDocumentRequest,
   metrics: &RequestMetrics,
 ) -> Result<Vec<SearchDocumentResponseItem>, AppError> {
-  // Set up the embedding model and create an embedding request.
-  let default_model = EmbeddingModel::default_model();
-  let embeddings_request = CreateEmbeddingRequestArgs::default()
-    .model(default_model.to_string())
-    .input(EmbeddingInput::String(request.query.clone()))
-    .encoding_format(EncodingFormat::Float)
-    .dimensions(default_model.default_dimensions())
-    .build()
-    .map_err(|err| AppError::Unhandled(err.to_string()))?;
-
-  // Create embeddings using the indexer scheduler.
-  let mut embeddings_resp = indexer_scheduler
-    .create_search_embeddings(embeddings_request)
-    .await?;
-  let total_tokens = embeddings_resp.usage.total_tokens;
-  metrics.record_search_tokens_used(&workspace_uuid, total_tokens);
-  tracing::info!(
-    "workspace {} OpenAI API search tokens used: {}",
-    workspace_uuid,
-    total_tokens
-  );
-
-  // Extract the embedding from the response.
-  let embedding = embeddings_resp
-    .data
-    .pop()
-    .ok_or_else(|| AppError::Internal(anyhow::anyhow!("OpenAI returned no embeddings")))?;
+  let mode = match request.mode {
+    SearchMode::Vector | SearchMode::Hybrid if !indexer_scheduler.is_embedder_available() => {
+      trace!("[Search] no embedder available, fallback to keyword search");
+      SearchMode::Keyword
+    },
+    mode => mode,
+  };
 
   // Obtain the latest collab folder and gather searchable view IDs.
   let folder = collab_instance_cache.get_folder(workspace_uuid).await?;
   let private_views = private_space_and_trash_view_ids(uid, &folder)?;
   let mut searchable_view_ids = HashSet::new();
+  let mut searchable_database_view_ids = HashSet::new();
   populate_searchable_view_ids(
     &folder,
     &private_views,
     &mut searchable_view_ids,
+    &mut searchable_database_view_ids,
     &workspace_uuid,
     &workspace_uuid,
     0,
     MAX_SEARCH_DEPTH,
     uid,
   );
+  let searchable_view_ids: Vec<Uuid> = searchable_view_ids.into_iter().collect();
+  let database_view_ids = if searchable_database_view_ids.is_empty() {
+    HashMap::new()
+  } else {
+    searchable_database_views(
+      pg_pool,
+      collab_storage,
+      workspace_uuid,
+      &searchable_database_view_ids,
+    )
+    .await?
+  };
+  let searchable_database_ids: Vec<Uuid> = database_view_ids.keys().copied().collect();
 
   // Set default preview size and search parameters.
   let preview_size = request.preview_size.unwrap_or(500) as i32;
-  let params = SearchDocumentParams {
-    user_id: uid,
-    workspace_id: workspace_uuid,
-    limit: request.limit.unwrap_or(10) as i32,
-    preview: preview_size,
-    embedding: embedding.embedding,
-    searchable_view_ids: searchable_view_ids.into_iter().collect(),
-    score: request.score,
-  };
-
+  let limit = request.limit.unwrap_or(10) as i32;
   trace!(
-    "[Search] query: {}, limit: {}, score: {:?}, workspace: {}",
+    "[Search] query: {}, mode: {:?}, limit: {}, score: {:?}, workspace: {}",
     request.query,
-    params.limit,
-    params.score,
-    params.workspace_id,
+    mode,
+    limit,
+    request.score,
+    workspace_uuid,
   );
 
   // Perform document search.
-  let results = search_documents(pg_pool, params, total_tokens).await?;
+  let results = match mode {
+    SearchMode::Vector => {
+      vector_search(
+        pg_pool,
+        indexer_scheduler,
+        uid,
+        workspace_uuid,
+        &request,
+        limit,
+        preview_size,
+        searchable_view_ids,
+        searchable_database_ids,
+        metrics,
+      )
+      .await?
+    },
+    SearchMode::Keyword => {
+      keyword_search(
+        pg_pool,
+        workspace_uuid,
+        &request,
+        limit,
+        searchable_view_ids,
+        searchable_database_ids,
+      )
+      .await?
+    },
+    SearchMode::Hybrid => {
+      let vector_results = vector_search(
+        pg_pool,
+        indexer_scheduler,
+        uid,
+        workspace_uuid,
+        &request,
+        limit,
+        preview_size,
+        searchable_view_ids.clone(),
+        searchable_database_ids.clone(),
+        metrics,
+      )
+      .await?;
+      let keyword_results = keyword_search(
+        pg_pool,
+        workspace_uuid,
+        &request,
+        limit,
+        searchable_view_ids,
+        searchable_database_ids,
+      )
+      .await?;
+      reciprocal_rank_fusion(vector_results, keyword_results, limit as usize)
+    },
+  };
   trace!(
     "[Search] query:{}, got {} results",
     request.query,
@@ -157,21 +220,264 @@ pub async fn search_document(
   // Build and return the search result, mapping each document to its response item.
   let items = results
     .into_iter()
-    .map(|item| SearchDocumentResponseItem {
-      object_id: item.object_id,
-      workspace_id: item.workspace_id,
-      score: item.score,
-      content_type: SearchContentType::from_record(item.content_type),
-      preview: Some(item.content.chars().take(preview_size as usize).collect()),
-      created_by: item.created_by,
-      created_at: item.created_at,
-      content: item.content,
+    .map(|item| {
+      let view_id = match &item.attachment {
+        Some(attachment) => Some(attachment.view_id),
+        None => item
+          .database_id
+          .and_then(|database_id| database_view_ids.get(&database_id).copied()),
+      };
+      SearchDocumentResponseItem {
+        object_id: item.object_id,
+        workspace_id: item.workspace_id,
+        score: item.score,
+        content_type: SearchContentType::from_record(item.content_type),
+        preview: Some(item.content.chars().take(preview_size as usize).collect()),
+        created_by: item.created_by,
+        created_at: item.created_at,
+        content: item.content,
+        view_id,
+        row_id: item.row_id,
+        attachment: item.attachment.map(|attachment| SearchAttachment {
+          parent_dir: attachment.parent_dir,
+          file_id: attachment.file_id,
+          content_type: attachment.content_type,
+        }),
+      }
     })
     .collect();
 
   Ok(items)
 }
 
+#[allow(clippy::too_many_arguments)]
+async fn vector_search(
+  pg_pool: &PgPool,
+  indexer_scheduler: &Arc<IndexerScheduler>,
+  uid: i64,
+  workspace_uuid: Uuid,
+  request: &SearchDocumentRequest,
+  limit: i32,
+  preview_size: i32,
+  searchable_view_ids: Vec<Uuid>,
+  searchable_database_ids: Vec<Uuid>,
+  metrics: &RequestMetrics,
+) -> Result<Vec<SearchDocumentResult>, AppError> {
+  // Create embeddings with the model used to index the documents.
+  let (embedding_model, mut embeddings_resp) = indexer_scheduler
+    .create_search_embeddings(EmbeddingInput::String(request.query.clone()))
+    .await?;
+  let total_tokens = embeddings_resp.usage.total_tokens;
+  metrics.record_search_tokens_used(&workspace_uuid, total_tokens);
+  tracing::info!(
+    "workspace {} embedding search tokens used: {}",
+    workspace_uuid,
+    total_tokens
+  );
+
+  // Extract the embedding from the response.
+  let embedding = embeddings_resp
+    .data
+    .pop()
+    .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Embedder returned no embeddings")))?;
+
+  let params = SearchDocumentParams {
+    user_id: uid,
+    workspace_id: workspace_uuid,
+    limit,
+    preview: preview_size,
+    embedding: embedding.embedding,
+    embedding_model: embedding_model.name().to_string(),
+    searchable_view_ids,
+    searchable_database_ids,
+    score: request.score,
+  };
+  let mut results = search_documents(pg_pool, params.clone(), total_tokens).await?;
+  results.extend(search_attachments(pg_pool, params).await?);
+  Ok(merge_search_results(results, limit))
+}
+
+async fn keyword_search(
+  pg_pool: &PgPool,
+  workspace_uuid: Uuid,
+  request: &SearchDocumentRequest,
+  limit: i32,
+  searchable_view_ids: Vec<Uuid>,
+  searchable_database_ids: Vec<Uuid>,
+) -> Result<Vec<SearchDocumentResult>, AppError> {
+  let params = KeywordSearchDocumentParams {
+    workspace_id: workspace_uuid,
+    query: request.query.clone(),
+    limit,
+    searchable_view_ids,
+    searchable_database_ids,
+  };
+  let mut results = search_documents_by_keyword(pg_pool, params.clone()).await?;
+  results.extend(search_attachments_by_keyword(pg_pool, params).await?);
+  Ok(merge_search_results(results, limit))
+}
+
+/// Orders the documents and attachments found by the same search by their score.
+fn merge_search_results(
+  mut results: Vec<SearchDocumentResult>,
+  limit: i32,
+) -> Vec<SearchDocumentResult> {
+  results.sort_by(|a, b| b.score.total_cmp(&a.score));
+  results.truncate(limit.max(0) as usize);
+  results
+}
+
+/// Maps the databases which have at least one searchable view to one of these views, so that
+/// rows found by the search can be opened in their database.
+async fn searchable_database_views(
+  pg_pool: &PgPool,
+  collab_storage: &Arc<dyn CollabStore>,
+  workspace_id: Uuid,
+  searchable_database_view_ids: &HashSet<Uuid>,
+) -> Result<HashMap<Uuid, Uuid>, AppError> {
+  let ws_db_oid = select_workspace_database_oid(pg_pool, &workspace_id).await?;
+  let mut collab = get_latest_collab(
+    collab_storage,
+    GetCollabOrigin::Server,
+    workspace_id,
+    ws_db_oid,
+    CollabType::WorkspaceDatabase,
+    default_client_id(),
+  )
+  .await?;
+  let ws_body = WorkspaceDatabaseBody::open(&mut collab).map_err(|err| {
+    AppError::Internal(anyhow::anyhow!(
+      "Failed to open workspace database body: {:?}",
+      err
+    ))
+  })?;
+
+  let mut database_view_ids = HashMap::new();
+  for meta in ws_body.get_all_meta(&collab.transact()) {
+    let database_id = match Uuid::parse_str(&meta.database_id) {
+      Ok(database_id) => database_id,
+      Err(_) => continue,
+    };
+    let view_id = meta
+      .linked_views
+      .iter()
+      .filter_map(|view_id| Uuid::parse_str(view_id).ok())
+      .find(|view_id| searchable_database_view_ids.contains(view_id));
+    if let Some(view_id) = view_id {
+      database_view_ids.insert(database_id, view_id);
+    }
+  }
+  Ok(database_view_ids)
+}
+
+/// Constant used to dampen the impact of the top ranked results, as suggested in the original
+/// reciprocal rank fusion paper.
+const RRF_K: f64 = 60.0;
+
+/// Merges the vector and keyword search results using reciprocal rank fusion: each document
+/// scores `1 / (RRF_K + rank)` for every result list it appears in. This rewards documents that
+/// rank well in both lists, without having to compare cosine similarities with full-text ranks.
+fn reciprocal_rank_fusion(
+  vector_results: Vec<SearchDocumentResult>,
+  keyword_results: Vec<SearchDocumentResult>,
+  limit: usize,
+) -> Vec<SearchDocumentResult> {
+  let mut fused: HashMap<Uuid, SearchDocumentResult> = HashMap::new();
+  for results in [vector_results, keyword_results] {
+    for (rank, mut result) in results.into_iter().enumerate() {
+      let score = 1.0 / (RRF_K + rank as f64 + 1.0);
+      match fused.get_mut(&result.object_id) {
+        Some(existing) => existing.score += score,
+        None => {
+          result.score = score;
+          fused.insert(result.object_id, result);
+        },
+      }
+    }
+  }
+
+  // ties are broken by object id, so that the order doesn't depend on the hash map
+  let mut results: Vec<_> = fused.into_values().collect();
+  results.sort_by(|a, b| {
+    b.score
+      .total_cmp(&a.score)
+      .then_with(|| a.object_id.cmp(&b.object_id))
+  });
+  results.truncate(limit);
+  results
+}
+
+/// Returns true if the appflowy worker can extract the text of files with the given mime type.
+pub fn is_indexable_attachment(content_type: &str) -> bool {
+  let mime_type = content_type
+    .split(';')
+    .next()
+    .unwrap_or_default()
+    .trim()
+    .to_ascii_lowercase();
+  matches!(
+    mime_type.as_str(),
+    "application/pdf"
+      | "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+      | "text/markdown"
+      | "text/x-markdown"
+      | "text/plain"
+  )
+}
+
+/// Queues a file uploaded to the workspace for the appflowy worker, which extracts the text of
+/// supported files (PDF, DOCX, Markdown and plain text) and indexes it for search.
+pub async fn queue_attachment_indexing(
+  redis_client: &RedisConnectionManager,
+  uid: i64,
+  workspace_id: &Uuid,
+  parent_dir: &str,
+  file_id: &str,
+) -> Result<(), AppError> {
+  let task = json!({
+    "uid": uid,
+    "workspace_id": workspace_id,
+    "parent_dir": parent_dir,
+    "file_id": file_id,
+    "created_at": chrono::Utc::now().timestamp(),
+  });
+  let _: () = redis_client
+    .clone()
+    .xadd(
+      ATTACHMENT_INDEX_TASK_STREAM,
+      "*",
+      &[("task", task.to_string())],
+    )
+    .await
+    .map_err(|err| {
+      AppError::Internal(anyhow::anyhow!(
+        "Failed to push task to Redis stream: {}",
+        err
+      ))
+    })?;
+  Ok(())
+}
+
+/// Queues the attachments of the workspace which were embedded with another model than
+/// `embedding_model` for the attachment indexer, which embeds them again. Returns the number of
+/// queued attachments.
+pub async fn reindex_stale_attachments(
+  pg_pool: &PgPool,
+  redis_client: &RedisConnectionManager,
+  workspace_id: &Uuid,
+  embedding_model: &str,
+) -> Result<usize, AppError> {
+  let limit = get_env_var("APPFLOWY_INDEXER_REINDEX_BATCH_SIZE", "1000")
+    .parse::<i64>()
+    .unwrap_or(1000);
+  let attachments =
+    select_attachments_with_stale_embeddings(pg_pool, workspace_id, embedding_model, limit).await?;
+  for (uid, parent_dir, file_id) in &attachments {
+    queue_attachment_indexing(redis_client, *uid, workspace_id, parent_dir, file_id).await?;
+  }
+  Ok(attachments.len())
+}
+
 pub async fn summarize_search_results(
   ai_tool: Option<AITool>,
   request: SummarySearchResultRequest,
@@ -227,3 +533,66 @@ pub async fn summarize_search_results(
 
   Ok(SearchSummaryResult { summaries })
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::Utc;
+
+  fn result(object_id: Uuid) -> SearchDocumentResult {
+    SearchDocumentResult {
+      object_id,
+      workspace_id: Uuid::nil(),
+      collab_type: 0,
+      content_type: 0,
+      content: String::new(),
+      created_by: String::new(),
+      created_at: Utc::now(),
+      score: 0.0,
+      database_id: None,
+      row_id: None,
+      attachment: None,
+    }
+  }
+
+  fn ids(results: &[SearchDocumentResult]) -> Vec<Uuid> {
+    results.iter().map(|result| result.object_id).collect()
+  }
+
+  #[test]
+  fn rrf_rewards_documents_found_by_both_searches() {
+    let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let fused = reciprocal_rank_fusion(vec![result(a), result(b)], vec![result(c), result(b)], 10);
+    assert_eq!(fused[0].object_id, b);
+    assert_eq!(fused.len(), 3);
+  }
+
+  #[test]
+  fn rrf_keeps_disjoint_results_and_breaks_ties_by_id() {
+    let mut vector_ids = [Uuid::new_v4(), Uuid::new_v4()];
+    let mut keyword_ids = [Uuid::new_v4(), Uuid::new_v4()];
+    vector_ids.sort();
+    keyword_ids.sort();
+    let fused = reciprocal_rank_fusion(
+      vector_ids.iter().copied().map(result).collect(),
+      keyword_ids.iter().copied().map(result).collect(),
+      10,
+    );
+
+    // documents with the same rank in either list tie, and are ordered by id
+    let mut first_ranked = vec![vector_ids[0], keyword_ids[0]];
+    first_ranked.sort();
+    let mut second_ranked = vec![vector_ids[1], keyword_ids[1]];
+    second_ranked.sort();
+    assert_eq!(ids(&fused), [first_ranked, second_ranked].concat());
+    assert_eq!(fused[0].score, fused[1].score);
+    assert!(fused[1].score > fused[2].score);
+
+    let truncated = reciprocal_rank_fusion(
+      vector_ids.iter().copied().map(result).collect(),
+      keyword_ids.iter().copied().map(result).collect(),
+      3,
+    );
+    assert_eq!(ids(&truncated), &ids(&fused)[..3]);
+  }
+}
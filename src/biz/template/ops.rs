@@ -9,7 +9,7 @@ use anyhow::Context;
 use app_error::ErrorCode;
 use aws_sdk_s3::primitives::ByteStream;
 use database::{
-  file::{s3_client_impl::AwsS3BucketClientImpl, BucketClient, ResponseBlob},
+  file::{app_bucket_client::AppBucketClient, BucketClient, ResponseBlob},
   publish::{select_publish_info_for_view_ids, select_published_collab_info},
   template::*,
 };
@@ -390,7 +390,7 @@ fn avatar_object_key(file_id: &str) -> String {
 }
 
 pub async fn get_avatar(
-  client: AwsS3BucketClientImpl,
+  client: AppBucketClient,
   file_id: String,
 ) -> Result<AvatarContent, AppResponseError> {
   let object_key = avatar_object_key(&file_id);
@@ -406,7 +406,7 @@ pub async fn get_avatar(
 }
 
 pub async fn upload_avatar(
-  client: AwsS3BucketClientImpl,
+  client: AppBucketClient,
   avatar: &MPBytes,
 ) -> Result<String, AppResponseError> {
   let content_type = match &avatar.content_type {
@@ -3,7 +3,7 @@ use std::path::Path;
 use actix_multipart::form::bytes::Bytes;
 use app_error::AppError;
 use aws_sdk_s3::primitives::ByteStream;
-use database::file::{s3_client_impl::AwsS3BucketClientImpl, BucketClient, ResponseBlob};
+use database::file::{app_bucket_client::AppBucketClient, BucketClient, ResponseBlob};
 use database_entity::dto::UserImageAssetContent;
 use uuid::Uuid;
 
@@ -12,7 +12,7 @@ fn user_image_asset_object_key(person_id: &Uuid, file_id: &str) -> String {
 }
 
 pub async fn upload_user_image_asset(
-  client: AwsS3BucketClientImpl,
+  client: AppBucketClient,
   image_content: &Bytes,
   person_id: &Uuid,
 ) -> Result<String, AppError> {
@@ -56,7 +56,7 @@ pub async fn upload_user_image_asset(
 }
 
 pub async fn get_user_image_asset(
-  client: AwsS3BucketClientImpl,
+  client: AppBucketClient,
   person_id: &Uuid,
   file_id: String,
 ) -> Result<UserImageAssetContent, AppError> {
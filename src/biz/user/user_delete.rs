@@ -2,7 +2,7 @@ use crate::biz::authentication::jwt::Authorization;
 use crate::state::GoTrueAdmin;
 use crate::{biz::workspace::ops::delete_workspace_for_user, config::config::AppleOAuthSetting};
 use app_error::ErrorCode;
-use database::file::s3_client_impl::S3BucketStorage;
+use database::file::app_bucket_client::AppBucketStorage;
 use database::workspace::{insert_workspace_ids_to_deleted_table, select_user_owned_workspaces_id};
 use gotrue::params::AdminDeleteUserParams;
 use redis::aio::ConnectionManager;
@@ -16,7 +16,7 @@ use uuid::Uuid;
 pub async fn delete_user(
   pg_pool: &sqlx::PgPool,
   connection_manager: &ConnectionManager,
-  bucket_storage: &Arc<S3BucketStorage>,
+  bucket_storage: &Arc<AppBucketStorage>,
   gotrue_client: &gotrue::api::Client,
   gotrue_admin: &GoTrueAdmin,
   apple_oauth: &AppleOAuthSetting,
@@ -0,0 +1,199 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use app_error::AppError;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::Url;
+use tokio::net::lookup_host;
+
+/// A webhook url together with the addresses its host resolved to.
+pub struct ResolvedWebhookUrl {
+  pub url: Url,
+  /// The domain of the url, or `None` when the host is an ip address.
+  pub domain: Option<String>,
+  pub addrs: Vec<SocketAddr>,
+}
+
+/// Parses the webhook url and resolves its host. Webhooks must not reach the internal network of
+/// the server, so the url is rejected unless every address of the host is globally routable.
+///
+/// The url is resolved when the webhook is registered. Deliveries resolve it again through
+/// [GlobalAddressResolver], so a domain re-bound to an internal address after the check is never
+/// requested.
+pub async fn resolve_webhook_url(url: &str) -> Result<ResolvedWebhookUrl, AppError> {
+  let url = parse_webhook_url(url)?;
+  let host = url.host_str().unwrap_or_default();
+  let port = url.port_or_known_default().unwrap_or(80);
+
+  let (domain, addrs) = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+    Ok(ip) => (None, vec![SocketAddr::new(ip, port)]),
+    Err(_) => {
+      let addrs = lookup_host((host, port))
+        .await
+        .map_err(|err| {
+          AppError::InvalidRequest(format!("Failed to resolve webhook host {}: {}", host, err))
+        })?
+        .collect::<Vec<_>>();
+      (Some(host.to_string()), addrs)
+    },
+  };
+  if addrs.is_empty() {
+    return Err(AppError::InvalidRequest(format!(
+      "Webhook host {} has no address",
+      host
+    )));
+  }
+  if addrs.iter().any(|addr| !is_global_ip(addr.ip())) {
+    return Err(AppError::InvalidRequest(format!(
+      "Webhook url must not point to a private or reserved address: {}",
+      url
+    )));
+  }
+
+  Ok(ResolvedWebhookUrl { url, domain, addrs })
+}
+
+/// Parses the webhook url without resolving its host. A host given as an ip address has to be
+/// globally routable, a domain is checked when it is resolved.
+pub fn parse_webhook_url(url: &str) -> Result<Url, AppError> {
+  let url = Url::parse(url)
+    .map_err(|err| AppError::InvalidRequest(format!("Invalid webhook url {}: {}", url, err)))?;
+  let host = match url.host_str() {
+    Some(host) if matches!(url.scheme(), "http" | "https") => host,
+    _ => {
+      return Err(AppError::InvalidRequest(format!(
+        "Webhook url must be an http or https url: {}",
+        url
+      )))
+    },
+  };
+  if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse() {
+    if !is_global_ip(ip) {
+      return Err(AppError::InvalidRequest(format!(
+        "Webhook url must not point to a private or reserved address: {}",
+        url
+      )));
+    }
+  }
+  Ok(url)
+}
+
+/// DNS resolver of the webhook http client. It fails the resolution of a domain unless every
+/// address of the domain is globally routable, so the check and the connection use the same
+/// addresses.
+pub struct GlobalAddressResolver;
+
+impl Resolve for GlobalAddressResolver {
+  fn resolve(&self, name: Name) -> Resolving {
+    Box::pin(async move {
+      let host = name.as_str();
+      let addrs = lookup_host((host, 0)).await?.collect::<Vec<_>>();
+      if addrs.is_empty() || addrs.iter().any(|addr| !is_global_ip(addr.ip())) {
+        return Err(
+          format!(
+            "webhook host {} must only resolve to public addresses",
+            host
+          )
+          .into(),
+        );
+      }
+      let addrs: Addrs = Box::new(addrs.into_iter());
+      Ok(addrs)
+    })
+  }
+}
+
+/// Returns true if the address is publicly routable. Mirrors the unstable `IpAddr::is_global`.
+pub fn is_global_ip(ip: IpAddr) -> bool {
+  match ip {
+    IpAddr::V4(ip) => is_global_ipv4(ip),
+    IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+      Some(ip) => is_global_ipv4(ip),
+      None => is_global_ipv6(ip),
+    },
+  }
+}
+
+fn is_global_ipv4(ip: Ipv4Addr) -> bool {
+  let [a, b, c, _] = ip.octets();
+  !(ip.is_unspecified()
+    || ip.is_private()
+    || ip.is_loopback()
+    || ip.is_link_local()
+    || ip.is_broadcast()
+    || ip.is_documentation()
+    || ip.is_multicast()
+    // 0.0.0.0/8, "this network"
+    || a == 0
+    // 100.64.0.0/10, shared address space
+    || (a == 100 && (b & 0b1100_0000) == 0b0100_0000)
+    // 192.0.0.0/24, IETF protocol assignments
+    || (a == 192 && b == 0 && c == 0)
+    // 198.18.0.0/15, benchmarking
+    || (a == 198 && (b & 0xfe) == 18)
+    // 240.0.0.0/4, reserved
+    || a >= 240)
+}
+
+fn is_global_ipv6(ip: Ipv6Addr) -> bool {
+  let segments = ip.segments();
+  !(ip.is_unspecified()
+    || ip.is_loopback()
+    || ip.is_multicast()
+    // ::/96, deprecated ipv4-compatible addresses
+    || segments[..6].iter().all(|segment| *segment == 0)
+    // 64:ff9b::/96, ipv4/ipv6 translation, which can embed any ipv4 address
+    || (segments[0] == 0x64 && segments[1] == 0xff9b)
+    // fc00::/7, unique local
+    || (segments[0] & 0xfe00) == 0xfc00
+    // fe80::/10, link local
+    || (segments[0] & 0xffc0) == 0xfe80
+    // 2001:db8::/32, documentation
+    || (segments[0] == 0x2001 && segments[1] == 0xdb8))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn reject_non_global_addresses() {
+    for ip in [
+      "127.0.0.1",
+      "10.1.2.3",
+      "172.16.0.1",
+      "192.168.1.1",
+      "169.254.169.254",
+      "100.64.0.1",
+      "0.0.0.0",
+      "255.255.255.255",
+      "::1",
+      "::",
+      "fd00::1",
+      "fe80::1",
+      "::ffff:127.0.0.1",
+      "::ffff:10.0.0.1",
+      "64:ff9b::a00:1",
+    ] {
+      assert!(!is_global_ip(ip.parse().unwrap()), "{}", ip);
+    }
+    for ip in ["1.1.1.1", "93.184.216.34", "2606:4700:4700::1111"] {
+      assert!(is_global_ip(ip.parse().unwrap()), "{}", ip);
+    }
+  }
+
+  #[tokio::test]
+  async fn reject_webhook_urls_of_the_internal_network() {
+    for url in [
+      "ftp://1.1.1.1/hook",
+      "http://127.0.0.1:8000/hook",
+      "http://[::1]/hook",
+      "http://169.254.169.254/latest/meta-data",
+      "http://localhost/hook",
+    ] {
+      assert!(resolve_webhook_url(url).await.is_err(), "{}", url);
+    }
+    let resolved = resolve_webhook_url("https://1.1.1.1/hook").await.unwrap();
+    assert_eq!(resolved.domain, None);
+    assert_eq!(resolved.addrs, vec!["1.1.1.1:443".parse().unwrap()]);
+  }
+}
@@ -0,0 +1,194 @@
+use crate::biz::webhook::address::resolve_webhook_url;
+use app_error::AppError;
+use chrono::Utc;
+use database::pg_row::{AFWebhookDeliveryRow, AFWebhookRow};
+use database::webhook::{
+  delete_webhook, insert_webhook, insert_webhook_deliveries, select_webhook,
+  select_webhook_deliveries, select_webhooks, update_webhook, WebhookDeliveryState,
+};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde_json::json;
+use shared_entity::dto::webhook_dto::{
+  AFWebhook, AFWebhookDelivery, CreateWebhookParams, QueryWebhookDeliveryParams,
+  UpdateWebhookParams, WebhookDeliveryStatus, WebhookEvent,
+};
+use sqlx::PgPool;
+use tracing::{error, trace};
+use uuid::Uuid;
+
+const DEFAULT_DELIVERY_PAGE_SIZE: i64 = 50;
+const MAX_DELIVERY_PAGE_SIZE: i64 = 200;
+
+/// Queues the event for the webhooks of the workspace. Failing to queue an event must not fail
+/// the request that produced it, so errors are only logged.
+///
+/// Events are only queued by the REST handlers. Folder and database edits that the clients sync
+/// over the websocket are merged by the collab server without going through them, so they don't
+/// fire webhooks, see [WebhookEvent].
+pub async fn publish_webhook_event(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  event: WebhookEvent,
+  actor_uid: Option<i64>,
+  data: serde_json::Value,
+) {
+  let payload = json!({
+    "event_id": Uuid::new_v4(),
+    "event": event,
+    "workspace_id": workspace_id,
+    "actor_uid": actor_uid,
+    "occurred_at": Utc::now(),
+    "data": data,
+  });
+  match insert_webhook_deliveries(pg_pool, workspace_id, event.as_str(), &payload).await {
+    Ok(count) => trace!(
+      "queued {} webhook deliveries of {} for workspace {}",
+      count,
+      event,
+      workspace_id
+    ),
+    Err(err) => error!(
+      "failed to queue webhook event {} for workspace {}: {}",
+      event, workspace_id, err
+    ),
+  }
+}
+
+pub async fn create_webhook(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  params: CreateWebhookParams,
+) -> Result<AFWebhook, AppError> {
+  resolve_webhook_url(&params.url).await?;
+  let secret = gen_webhook_secret();
+  let events = event_names(&params.events);
+  let row = insert_webhook(
+    pg_pool,
+    &Uuid::new_v4(),
+    workspace_id,
+    &params.url,
+    &secret,
+    &events,
+    uid,
+  )
+  .await?;
+  Ok(to_af_webhook(row, true))
+}
+
+pub async fn list_webhooks(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFWebhook>, AppError> {
+  let webhooks = select_webhooks(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .map(|row| to_af_webhook(row, false))
+    .collect();
+  Ok(webhooks)
+}
+
+pub async fn update_workspace_webhook(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  webhook_id: &Uuid,
+  params: UpdateWebhookParams,
+) -> Result<AFWebhook, AppError> {
+  if let Some(url) = &params.url {
+    resolve_webhook_url(url).await?;
+  }
+  let secret = params.rotate_secret.then(gen_webhook_secret);
+  let events = params.events.as_deref().map(event_names);
+  let row = update_webhook(
+    pg_pool,
+    workspace_id,
+    webhook_id,
+    params.url.as_deref(),
+    secret.as_deref(),
+    events.as_deref(),
+    params.enabled,
+  )
+  .await?;
+  Ok(to_af_webhook(row, params.rotate_secret))
+}
+
+pub async fn delete_workspace_webhook(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  webhook_id: &Uuid,
+) -> Result<(), AppError> {
+  delete_webhook(pg_pool, workspace_id, webhook_id).await
+}
+
+pub async fn list_webhook_deliveries(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  webhook_id: &Uuid,
+  params: QueryWebhookDeliveryParams,
+) -> Result<Vec<AFWebhookDelivery>, AppError> {
+  // ensures the webhook belongs to the workspace
+  select_webhook(pg_pool, workspace_id, webhook_id).await?;
+  let limit = params
+    .limit
+    .unwrap_or(DEFAULT_DELIVERY_PAGE_SIZE)
+    .clamp(1, MAX_DELIVERY_PAGE_SIZE);
+  let offset = params.offset.unwrap_or(0).max(0);
+  let deliveries = select_webhook_deliveries(pg_pool, webhook_id, limit, offset)
+    .await?
+    .into_iter()
+    .map(to_af_webhook_delivery)
+    .collect();
+  Ok(deliveries)
+}
+
+fn gen_webhook_secret() -> String {
+  let secret: String = rand::thread_rng()
+    .sample_iter(&Alphanumeric)
+    .take(32)
+    .map(char::from)
+    .collect();
+  format!("whsec_{}", secret)
+}
+
+fn event_names(events: &[WebhookEvent]) -> Vec<String> {
+  events.iter().map(|event| event.to_string()).collect()
+}
+
+fn to_af_webhook(row: AFWebhookRow, with_secret: bool) -> AFWebhook {
+  AFWebhook {
+    webhook_id: row.webhook_id,
+    workspace_id: row.workspace_id,
+    url: row.url,
+    events: row
+      .events
+      .iter()
+      .filter_map(|event| event.parse().ok())
+      .collect(),
+    enabled: row.enabled,
+    secret: with_secret.then_some(row.secret),
+    created_at: row.created_at,
+    updated_at: row.updated_at,
+  }
+}
+
+fn to_af_webhook_delivery(row: AFWebhookDeliveryRow) -> AFWebhookDelivery {
+  let status = match WebhookDeliveryState::from(row.status) {
+    WebhookDeliveryState::Pending => WebhookDeliveryStatus::Pending,
+    WebhookDeliveryState::Delivered => WebhookDeliveryStatus::Delivered,
+    WebhookDeliveryState::Failed => WebhookDeliveryStatus::Failed,
+  };
+  AFWebhookDelivery {
+    delivery_id: row.delivery_id,
+    webhook_id: row.webhook_id,
+    event: row.event,
+    payload: row.payload,
+    status,
+    attempts: row.attempts,
+    next_attempt_at: row.next_attempt_at,
+    response_status: row.response_status,
+    error: row.error,
+    created_at: row.created_at,
+    delivered_at: row.delivered_at,
+  }
+}
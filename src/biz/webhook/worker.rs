@@ -0,0 +1,233 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use database::pg_row::AFPendingWebhookDeliveryRow;
+use database::webhook::{
+  claim_pending_webhook_deliveries, delete_webhook_deliveries_before,
+  update_webhook_delivery_attempt, WebhookDeliveryState,
+};
+use futures_util::future::join_all;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::PgPool;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+
+use crate::biz::webhook::address::{parse_webhook_url, GlobalAddressResolver};
+
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-AppFlowy-Signature";
+pub const WEBHOOK_EVENT_HEADER: &str = "X-AppFlowy-Event";
+pub const WEBHOOK_DELIVERY_HEADER: &str = "X-AppFlowy-Delivery";
+
+const CLAIM_BATCH_SIZE: i64 = 50;
+/// Must be longer than [REQUEST_TIMEOUT] so that a delivery in flight is not claimed twice.
+const CLAIM_LEASE_SECS: i64 = 60;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const BASE_RETRY_DELAY_SECS: i64 = 30;
+const MAX_RETRY_DELAY_SECS: i64 = 6 * 60 * 60;
+/// Stored errors are truncated to keep the delivery log small.
+const MAX_ERROR_LEN: usize = 512;
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Sends the queued webhook deliveries. Failed deliveries are retried with an exponential
+/// backoff until `max_attempts` is reached, after which they are marked as failed.
+pub struct WebhookDeliveryWorker {
+  pg_pool: PgPool,
+  http_client: reqwest::Client,
+  interval_secs: u64,
+  max_attempts: i32,
+}
+
+impl WebhookDeliveryWorker {
+  pub fn new(pg_pool: PgPool, interval_secs: u64, max_attempts: i32) -> Self {
+    // Redirects and proxies would connect to addresses that the resolver didn't check.
+    let http_client = reqwest::Client::builder()
+      .timeout(REQUEST_TIMEOUT)
+      .redirect(reqwest::redirect::Policy::none())
+      .no_proxy()
+      .dns_resolver(Arc::new(GlobalAddressResolver))
+      .build()
+      .expect("Failed to build the webhook http client");
+    Self {
+      pg_pool,
+      http_client,
+      interval_secs,
+      max_attempts: max_attempts.max(1),
+    }
+  }
+
+  pub async fn start_task(&self) {
+    info!(
+      "Starting webhook delivery worker, interval: {}s, max attempts: {}",
+      self.interval_secs, self.max_attempts
+    );
+    let mut interval = interval(Duration::from_secs(self.interval_secs));
+    loop {
+      interval.tick().await;
+      loop {
+        let deliveries =
+          match claim_pending_webhook_deliveries(&self.pg_pool, CLAIM_BATCH_SIZE, CLAIM_LEASE_SECS)
+            .await
+          {
+            Ok(deliveries) => deliveries,
+            Err(err) => {
+              error!("Failed to claim webhook deliveries: {}", err);
+              break;
+            },
+          };
+        let is_last_batch = (deliveries.len() as i64) < CLAIM_BATCH_SIZE;
+        join_all(
+          deliveries
+            .into_iter()
+            .map(|delivery| self.deliver(delivery)),
+        )
+        .await;
+        if is_last_batch {
+          break;
+        }
+      }
+    }
+  }
+
+  async fn deliver(&self, delivery: AFPendingWebhookDeliveryRow) {
+    let (response_status, error) = match send_delivery(&self.http_client, &delivery).await {
+      Ok(resp) if resp.status().is_success() => (Some(resp.status().as_u16() as i32), None),
+      Ok(resp) => (
+        Some(resp.status().as_u16() as i32),
+        Some(format!("endpoint responded with {}", resp.status())),
+      ),
+      Err(err) => (None, Some(err)),
+    };
+
+    let attempts = delivery.attempts + 1;
+    let (state, retry_in_secs) = match &error {
+      None => (WebhookDeliveryState::Delivered, None),
+      Some(_) if attempts >= self.max_attempts => (WebhookDeliveryState::Failed, None),
+      Some(_) => (
+        WebhookDeliveryState::Pending,
+        Some(retry_delay_secs(attempts)),
+      ),
+    };
+    if let Some(error) = &error {
+      warn!(
+        "webhook delivery {} to {} failed, attempt {}: {}",
+        delivery.delivery_id, delivery.url, attempts, error
+      );
+    }
+
+    let error = error.map(|mut error| {
+      if error.len() > MAX_ERROR_LEN {
+        let mut end = MAX_ERROR_LEN;
+        while !error.is_char_boundary(end) {
+          end -= 1;
+        }
+        error.truncate(end);
+      }
+      error
+    });
+    if let Err(err) = update_webhook_delivery_attempt(
+      &self.pg_pool,
+      &delivery.delivery_id,
+      state,
+      response_status,
+      error.as_deref(),
+      retry_in_secs,
+    )
+    .await
+    {
+      error!(
+        "Failed to record webhook delivery {}: {}",
+        delivery.delivery_id, err
+      );
+    }
+  }
+}
+
+/// Deletes the delivered and failed deliveries older than `retention_days` from the delivery log.
+pub async fn run_webhook_delivery_cleanup(pg_pool: PgPool, retention_days: u32) {
+  info!(
+    "Starting webhook delivery cleanup, retention: {} days",
+    retention_days
+  );
+  let mut interval = interval(CLEANUP_INTERVAL);
+  loop {
+    interval.tick().await;
+    let before = Utc::now() - chrono::Duration::days(retention_days as i64);
+    match delete_webhook_deliveries_before(&pg_pool, before).await {
+      Ok(0) => {},
+      Ok(count) => info!("Removed {} expired webhook deliveries", count),
+      Err(err) => error!("Failed to remove expired webhook deliveries: {}", err),
+    }
+  }
+}
+
+/// Posts the delivery to the webhook url. The host is resolved and checked again by the
+/// [GlobalAddressResolver] of the client, as its addresses may have changed since the webhook was
+/// registered.
+async fn send_delivery(
+  http_client: &reqwest::Client,
+  delivery: &AFPendingWebhookDeliveryRow,
+) -> Result<reqwest::Response, String> {
+  let url = parse_webhook_url(&delivery.url).map_err(|err| err.to_string())?;
+  let body = delivery.payload.to_string();
+  let timestamp = Utc::now().timestamp();
+  let signature = sign_payload(&delivery.secret, timestamp, &body);
+  http_client
+    .post(url)
+    .header(reqwest::header::CONTENT_TYPE, "application/json")
+    .header(WEBHOOK_SIGNATURE_HEADER, signature)
+    .header(WEBHOOK_EVENT_HEADER, &delivery.event)
+    .header(WEBHOOK_DELIVERY_HEADER, delivery.delivery_id.to_string())
+    .body(body)
+    .send()
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Returns the value of the signature header: `t=<unix timestamp>,v1=<hex signature>`, where the
+/// signature is the HMAC-SHA256 of `<unix timestamp>.<body>` keyed with the webhook secret.
+/// Receivers should recompute the signature and reject old timestamps to prevent replays.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+  let mut mac =
+    Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+  mac.update(timestamp.to_string().as_bytes());
+  mac.update(b".");
+  mac.update(body.as_bytes());
+  let signature = hex::encode(mac.finalize().into_bytes());
+  format!("t={},v1={}", timestamp, signature)
+}
+
+/// Delay before the next attempt, doubling after each failed attempt.
+fn retry_delay_secs(attempts: i32) -> i64 {
+  let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+  BASE_RETRY_DELAY_SECS
+    .saturating_mul(2_i64.pow(exponent))
+    .min(MAX_RETRY_DELAY_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sign_payload_with_hmac_sha256() {
+    let signature = sign_payload("whsec_test", 1700000000, r#"{"event":"page.created"}"#);
+    let mut mac = Hmac::<Sha256>::new_from_slice(b"whsec_test").unwrap();
+    mac.update(br#"1700000000.{"event":"page.created"}"#);
+    let expected = hex::encode(mac.finalize().into_bytes());
+    assert_eq!(signature, format!("t=1700000000,v1={}", expected));
+    assert_ne!(
+      signature,
+      sign_payload("whsec_other", 1700000000, r#"{"event":"page.created"}"#)
+    );
+  }
+
+  #[test]
+  fn retry_delay_grows_exponentially_up_to_a_limit() {
+    assert_eq!(retry_delay_secs(1), 30);
+    assert_eq!(retry_delay_secs(2), 60);
+    assert_eq!(retry_delay_secs(5), 480);
+    assert_eq!(retry_delay_secs(30), MAX_RETRY_DELAY_SECS);
+  }
+}
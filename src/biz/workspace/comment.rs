@@ -0,0 +1,448 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::DerefMut;
+
+use app_error::AppError;
+use appflowy_collaborate::ws2::WorkspaceCollabInstanceCache;
+use database::comment::{
+  insert_view_comment, insert_view_comment_thread, select_view_comment, select_view_comment_edits,
+  select_view_comment_thread, select_view_comment_threads, select_view_comments_for_threads,
+  update_view_comment_content, update_view_comment_deletion_status,
+  update_view_comment_thread_resolution,
+};
+use database::pg_row::{AFViewCommentRow, AFViewCommentThreadRow};
+use database::user::select_uuid_from_uid;
+use database::workspace::upsert_page_mention;
+use database_entity::dto::{AFAccessLevel, AFWebUser, PageMentionUpdate};
+use serde_json::json;
+use shared_entity::dto::comment_dto::{
+  CreateViewCommentParams, CreateViewCommentThreadParams, QueryViewCommentThreadParams,
+  UpdateViewCommentParams, ViewComment, ViewCommentEdit, ViewCommentThread,
+};
+use shared_entity::dto::webhook_dto::WebhookEvent;
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+use crate::biz::webhook::ops::publish_webhook_event;
+use crate::biz::workspace::page_view::get_all_user_uuids_with_access_to_page;
+use crate::state::AppState;
+
+pub async fn list_view_comment_threads(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  query: &QueryViewCommentThreadParams,
+) -> Result<Vec<ViewCommentThread>, AppError> {
+  state
+    .collab_access_control
+    .enforce_access_level(workspace_id, &uid, view_id, AFAccessLevel::ReadOnly)
+    .await?;
+  let threads = select_view_comment_threads(
+    &state.pg_pool,
+    workspace_id,
+    view_id,
+    query.block_id.as_deref(),
+    query.include_resolved.unwrap_or(false),
+  )
+  .await?;
+  let can_moderate = can_moderate_comments(state, uid, workspace_id, view_id).await?;
+  build_view_comment_threads(state, uid, can_moderate, threads).await
+}
+
+#[instrument(level = "debug", skip_all, err)]
+pub async fn create_view_comment_thread(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  params: CreateViewCommentThreadParams,
+) -> Result<ViewCommentThread, AppError> {
+  state
+    .collab_access_control
+    .enforce_access_level(workspace_id, &uid, view_id, AFAccessLevel::ReadAndComment)
+    .await?;
+  validate_comment_content(&params.content)?;
+  let view_name = get_view_name(state, uid, workspace_id, view_id).await?;
+
+  let mut txn = state.pg_pool.begin().await?;
+  let thread_id = insert_view_comment_thread(
+    txn.deref_mut(),
+    workspace_id,
+    view_id,
+    params.block_id.as_deref(),
+    uid,
+  )
+  .await?;
+  let comment_id = insert_view_comment(
+    txn.deref_mut(),
+    &thread_id,
+    uid,
+    &params.content,
+    &params.mentions,
+  )
+  .await?;
+  txn.commit().await?;
+
+  let thread =
+    select_view_comment_thread(&state.pg_pool, workspace_id, view_id, &thread_id).await?;
+  on_comment_added(
+    state,
+    uid,
+    workspace_id,
+    &thread,
+    &comment_id,
+    &params.content,
+    &view_name,
+    &params.mentions,
+  )
+  .await;
+  get_view_comment_thread(state, uid, workspace_id, view_id, thread).await
+}
+
+#[instrument(level = "debug", skip_all, err)]
+pub async fn reply_to_view_comment_thread(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  thread_id: &Uuid,
+  params: CreateViewCommentParams,
+) -> Result<ViewCommentThread, AppError> {
+  state
+    .collab_access_control
+    .enforce_access_level(workspace_id, &uid, view_id, AFAccessLevel::ReadAndComment)
+    .await?;
+  validate_comment_content(&params.content)?;
+  let thread = select_view_comment_thread(&state.pg_pool, workspace_id, view_id, thread_id).await?;
+  let view_name = get_view_name(state, uid, workspace_id, view_id).await?;
+  let comment_id = insert_view_comment(
+    &state.pg_pool,
+    thread_id,
+    uid,
+    &params.content,
+    &params.mentions,
+  )
+  .await?;
+  on_comment_added(
+    state,
+    uid,
+    workspace_id,
+    &thread,
+    &comment_id,
+    &params.content,
+    &view_name,
+    &params.mentions,
+  )
+  .await;
+  get_view_comment_thread(state, uid, workspace_id, view_id, thread).await
+}
+
+/// Resolves the thread if `resolved` is true, reopens it otherwise.
+pub async fn set_view_comment_thread_resolved(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  thread_id: &Uuid,
+  resolved: bool,
+) -> Result<ViewCommentThread, AppError> {
+  state
+    .collab_access_control
+    .enforce_access_level(workspace_id, &uid, view_id, AFAccessLevel::ReadAndComment)
+    .await?;
+  select_view_comment_thread(&state.pg_pool, workspace_id, view_id, thread_id).await?;
+  update_view_comment_thread_resolution(&state.pg_pool, thread_id, resolved.then_some(uid)).await?;
+  let thread = select_view_comment_thread(&state.pg_pool, workspace_id, view_id, thread_id).await?;
+  get_view_comment_thread(state, uid, workspace_id, view_id, thread).await
+}
+
+/// Only the author of a comment can edit it. The previous content is kept in the edit history,
+/// and persons that were not mentioned before are notified.
+#[instrument(level = "debug", skip_all, err)]
+pub async fn update_view_comment(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  comment_id: &Uuid,
+  params: UpdateViewCommentParams,
+) -> Result<(), AppError> {
+  state
+    .collab_access_control
+    .enforce_access_level(workspace_id, &uid, view_id, AFAccessLevel::ReadAndComment)
+    .await?;
+  validate_comment_content(&params.content)?;
+  let comment = select_view_comment(&state.pg_pool, workspace_id, view_id, comment_id).await?;
+  if comment.created_by != Some(uid) {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  if comment.is_deleted {
+    return Err(AppError::RecordNotFound(format!(
+      "comment {} not found",
+      comment_id
+    )));
+  }
+  update_view_comment_content(
+    &state.pg_pool,
+    comment_id,
+    uid,
+    &params.content,
+    &params.mentions,
+  )
+  .await?;
+
+  let new_mentions: Vec<Uuid> = params
+    .mentions
+    .iter()
+    .filter(|person_id| !comment.mentions.contains(person_id))
+    .cloned()
+    .collect();
+  if !new_mentions.is_empty() {
+    let thread =
+      select_view_comment_thread(&state.pg_pool, workspace_id, view_id, &comment.thread_id).await?;
+    let view_name = get_view_name(state, uid, workspace_id, view_id).await?;
+    notify_mentioned_persons(state, uid, workspace_id, &thread, &view_name, &new_mentions).await;
+  }
+  Ok(())
+}
+
+/// A comment can be deleted by its author, or by anyone with full access to the view.
+pub async fn delete_view_comment(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  comment_id: &Uuid,
+) -> Result<(), AppError> {
+  state
+    .collab_access_control
+    .enforce_access_level(workspace_id, &uid, view_id, AFAccessLevel::ReadAndComment)
+    .await?;
+  let comment = select_view_comment(&state.pg_pool, workspace_id, view_id, comment_id).await?;
+  if comment.created_by != Some(uid)
+    && !can_moderate_comments(state, uid, workspace_id, view_id).await?
+  {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  update_view_comment_deletion_status(&state.pg_pool, comment_id).await?;
+  Ok(())
+}
+
+pub async fn list_view_comment_edits(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  comment_id: &Uuid,
+) -> Result<Vec<ViewCommentEdit>, AppError> {
+  state
+    .collab_access_control
+    .enforce_access_level(workspace_id, &uid, view_id, AFAccessLevel::ReadOnly)
+    .await?;
+  let comment = select_view_comment(&state.pg_pool, workspace_id, view_id, comment_id).await?;
+  if comment.is_deleted {
+    return Ok(vec![]);
+  }
+  let edits = select_view_comment_edits(&state.pg_pool, comment_id)
+    .await?
+    .into_iter()
+    .map(|edit| ViewCommentEdit {
+      content: edit.content,
+      edited_by: edit.edited_by,
+      edited_at: edit.edited_at,
+    })
+    .collect();
+  Ok(edits)
+}
+
+async fn get_view_comment_thread(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  thread: AFViewCommentThreadRow,
+) -> Result<ViewCommentThread, AppError> {
+  let can_moderate = can_moderate_comments(state, uid, workspace_id, view_id).await?;
+  build_view_comment_threads(state, uid, can_moderate, vec![thread])
+    .await?
+    .pop()
+    .ok_or_else(|| AppError::Internal(anyhow::anyhow!("comment thread is missing")))
+}
+
+async fn build_view_comment_threads(
+  state: &AppState,
+  uid: i64,
+  can_moderate: bool,
+  threads: Vec<AFViewCommentThreadRow>,
+) -> Result<Vec<ViewCommentThread>, AppError> {
+  let thread_ids: Vec<Uuid> = threads.iter().map(|thread| thread.thread_id).collect();
+  let mut comments_by_thread: HashMap<Uuid, Vec<ViewComment>> = HashMap::new();
+  for comment in select_view_comments_for_threads(&state.pg_pool, &thread_ids).await? {
+    comments_by_thread
+      .entry(comment.thread_id)
+      .or_default()
+      .push(to_view_comment(comment, uid, can_moderate));
+  }
+  let threads = threads
+    .into_iter()
+    .map(|thread| ViewCommentThread {
+      thread_id: thread.thread_id,
+      view_id: thread.view_id,
+      block_id: thread.block_id,
+      created_at: thread.created_at,
+      is_resolved: thread.resolved_at.is_some(),
+      resolved_by: thread.resolved_by,
+      resolved_at: thread.resolved_at,
+      comments: comments_by_thread
+        .remove(&thread.thread_id)
+        .unwrap_or_default(),
+    })
+    .collect();
+  Ok(threads)
+}
+
+fn to_view_comment(row: AFViewCommentRow, uid: i64, can_moderate: bool) -> ViewComment {
+  let is_author = row.created_by == Some(uid);
+  let author = match (row.author_uuid, row.author_name) {
+    (Some(uuid), Some(name)) => Some(AFWebUser {
+      uuid,
+      name,
+      avatar_url: row.author_avatar_url,
+    }),
+    _ => None,
+  };
+  ViewComment {
+    comment_id: row.comment_id,
+    thread_id: row.thread_id,
+    author,
+    content: if row.is_deleted {
+      String::new()
+    } else {
+      row.content
+    },
+    mentions: if row.is_deleted { vec![] } else { row.mentions },
+    created_at: row.created_at,
+    updated_at: row.updated_at,
+    is_edited: row.edit_count > 0,
+    is_deleted: row.is_deleted,
+    can_be_edited: !row.is_deleted && is_author,
+    can_be_deleted: !row.is_deleted && (is_author || can_moderate),
+  }
+}
+
+async fn can_moderate_comments(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<bool, AppError> {
+  match state
+    .collab_access_control
+    .enforce_access_level(workspace_id, &uid, view_id, AFAccessLevel::FullAccess)
+    .await
+  {
+    Ok(_) => Ok(true),
+    Err(AppError::NotEnoughPermissions) => Ok(false),
+    Err(err) => Err(err),
+  }
+}
+
+fn validate_comment_content(content: &str) -> Result<(), AppError> {
+  if content.trim().is_empty() {
+    return Err(AppError::InvalidRequest(
+      "comment content cannot be empty".to_string(),
+    ));
+  }
+  Ok(())
+}
+
+async fn get_view_name(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<String, AppError> {
+  let folder = state.ws_server.get_folder(*workspace_id).await?;
+  let view = folder
+    .get_view(&view_id.to_string(), uid)
+    .ok_or_else(|| AppError::RecordNotFound(format!("view {} not found", view_id)))?;
+  Ok(view.name.clone())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn on_comment_added(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  thread: &AFViewCommentThreadRow,
+  comment_id: &Uuid,
+  content: &str,
+  view_name: &str,
+  mentions: &[Uuid],
+) {
+  notify_mentioned_persons(state, uid, workspace_id, thread, view_name, mentions).await;
+  publish_webhook_event(
+    &state.pg_pool,
+    workspace_id,
+    WebhookEvent::CommentAdded,
+    Some(uid),
+    json!({
+      "view_id": thread.view_id,
+      "block_id": thread.block_id,
+      "thread_id": thread.thread_id,
+      "comment_id": comment_id,
+      "content": content,
+    }),
+  )
+  .await;
+}
+
+/// Records the mentions as page mentions, so that the mentioned persons are notified by the
+/// email notification worker. The author and persons without access to the view are skipped.
+async fn notify_mentioned_persons(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  thread: &AFViewCommentThreadRow,
+  view_name: &str,
+  mentions: &[Uuid],
+) {
+  if mentions.is_empty() {
+    return;
+  }
+  let persons_with_access: HashSet<Uuid> = match get_all_user_uuids_with_access_to_page(
+    &state.ws_server,
+    &state.pg_pool,
+    workspace_id,
+    &thread.view_id,
+  )
+  .await
+  {
+    Ok(uuids) => uuids.into_iter().collect(),
+    Err(err) => {
+      warn!("Failed to get the persons with access to the page: {}", err);
+      return;
+    },
+  };
+  let author_uuid = select_uuid_from_uid(&state.pg_pool, uid).await.ok();
+  let mentions: HashSet<&Uuid> = mentions.iter().collect();
+  for person_id in mentions {
+    if Some(*person_id) == author_uuid || !persons_with_access.contains(person_id) {
+      continue;
+    }
+    let update = PageMentionUpdate {
+      person_id: *person_id,
+      block_id: thread.block_id.clone(),
+      require_notification: true,
+      view_name: view_name.to_string(),
+    };
+    if let Err(err) =
+      upsert_page_mention(&state.pg_pool, workspace_id, &thread.view_id, uid, &update).await
+    {
+      warn!(
+        "Failed to record the mention of {} in a comment: {}",
+        person_id, err
+      );
+    }
+  }
+}
@@ -1,5 +1,6 @@
 use super::page_view::{update_workspace_database_data, update_workspace_folder_data};
 use crate::biz::collab::utils::get_latest_collab;
+use crate::biz::guest::ops::inherit_guest_view_access;
 use crate::state::AppState;
 use crate::{
   api::metrics::AppFlowyWebMetrics,
@@ -117,6 +118,15 @@ pub async fn duplicate_view_tree_and_collab(
     encoded_folder_update,
   )
   .await?;
+  if let Some(root_view) = duplicate_context.duplicated_views.first() {
+    let parent_view_id = Uuid::parse_str(&root_view.parent_view_id)?;
+    let duplicated_view_ids = duplicate_context
+      .duplicated_views
+      .iter()
+      .filter_map(|view| Uuid::parse_str(&view.id).ok())
+      .collect_vec();
+    inherit_guest_view_access(state, workspace_id, &parent_view_id, &duplicated_view_ids).await?;
+  }
   Ok(())
 }
 
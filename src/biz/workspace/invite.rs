@@ -1,14 +1,20 @@
 use app_error::AppError;
+use appflowy_proto::{MemberChange, WorkspaceNotification};
 use database::workspace::{
   delete_all_invite_code_for_workspace, insert_workspace_invite_code, select_invitation_code_info,
   select_invite_code_for_workspace_id, select_invited_workspace_id, upsert_workspace_member_uid,
 };
 use rand::{distributions::Alphanumeric, Rng};
+use serde_json::json;
+use shared_entity::dto::webhook_dto::WebhookEvent;
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use database_entity::dto::{AFRole, InvitationCodeInfo, WorkspaceInviteToken};
 
+use crate::biz::notification::workspace::notify_workspace;
+use crate::biz::webhook::ops::publish_webhook_event;
+
 const INVITE_LINK_CODE_LENGTH: usize = 16;
 
 pub async fn generate_workspace_invite_token(
@@ -40,6 +46,24 @@ pub async fn join_workspace_invite_by_code(
 ) -> Result<Uuid, AppError> {
   let invited_workspace_id = select_invited_workspace_id(pg_pool, invitation_code).await?;
   upsert_workspace_member_uid(pg_pool, &invited_workspace_id, uid, AFRole::Member).await?;
+  publish_webhook_event(
+    pg_pool,
+    &invited_workspace_id,
+    WebhookEvent::MemberJoined,
+    Some(uid),
+    json!({ "uid": uid, "role": AFRole::Member }),
+  )
+  .await;
+  notify_workspace(
+    pg_pool,
+    &invited_workspace_id,
+    WorkspaceNotification::MemberChanged {
+      uid,
+      change: MemberChange::Added,
+      role: Some(i32::from(AFRole::Member)),
+    },
+  )
+  .await;
   Ok(invited_workspace_id)
 }
 
@@ -1,7 +1,11 @@
+pub mod comment;
 pub mod duplicate;
 pub mod invite;
 pub mod ops;
 pub mod page_view;
 pub mod publish;
+pub mod publish_analytics;
+pub mod publish_domain;
 pub mod publish_dup;
+pub mod publish_html;
 pub mod quick_note;
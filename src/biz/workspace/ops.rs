@@ -3,7 +3,9 @@ use database_entity::dto::{
 };
 use std::collections::HashMap;
 
+use actix::Addr;
 use anyhow::{anyhow, Context};
+use appflowy_proto::{MemberChange, WorkspaceNotification};
 use redis::AsyncCommands;
 use serde_json::json;
 use sqlx::{types::uuid, PgPool};
@@ -13,27 +15,38 @@ use std::time::Instant;
 use tracing::instrument;
 use uuid::Uuid;
 
+use access_control::collab::CollabAccessControl;
 use access_control::workspace::WorkspaceAccessControl;
 use app_error::{AppError, ErrorCode};
+use appflowy_collaborate::ws2::{
+  PermissionType, PermissionUpdate, UpdateUserPermissions, WsServer,
+};
 use appflowy_collaborate::CollabMetrics;
 use collab_stream::model::UpdateStreamMessage;
 use database::collab::CollabStore;
-use database::file::s3_client_impl::S3BucketStorage;
-use database::pg_row::AFWorkspaceMemberRow;
-use database::user::select_uid_from_email;
+use database::file::app_bucket_client::AppBucketStorage;
+use database::guest::delete_all_guest_view_access_for_email;
+use database::pg_row::{AFWorkspaceInvitationMinimal, AFWorkspaceMemberRow};
+use database::publish::select_workspace_id_for_published_view;
+use database::user::{select_uid_from_email, select_uid_from_uuid};
 use database::workspace::*;
 use database_entity::dto::{
   AFRole, AFWorkspace, AFWorkspaceInvitation, AFWorkspaceInvitationStatus, AFWorkspaceSettings,
   GlobalComment, Reaction, WorkspaceMemberProfile, WorkspaceUsage,
 };
 
+use crate::biz::audit::ops::{record_audit_event, AuditActor};
 use crate::biz::authentication::jwt::OptionalUserUuid;
+use crate::biz::notification::workspace::notify_workspace;
 use crate::biz::user::user_init::{
   create_user_awareness, create_workspace_collab, create_workspace_database_collab,
   initialize_workspace_for_user,
 };
+use crate::biz::webhook::ops::publish_webhook_event;
 use crate::mailer::{AFCloudMailer, WorkspaceInviteMailerParam};
 use crate::state::RedisConnectionManager;
+use shared_entity::dto::audit_dto::AuditAction;
+use shared_entity::dto::webhook_dto::WebhookEvent;
 use shared_entity::dto::workspace_dto::{
   CreateWorkspaceMember, WorkspaceMemberChangeset, WorkspaceMemberInvitation,
 };
@@ -46,7 +59,7 @@ pub async fn delete_workspace_for_user(
   pg_pool: PgPool,
   mut connection_manager: RedisConnectionManager,
   workspace_id: Uuid,
-  bucket_storage: Arc<S3BucketStorage>,
+  bucket_storage: Arc<AppBucketStorage>,
 ) -> Result<(), AppResponseError> {
   // remove files from s3
   bucket_storage
@@ -165,6 +178,17 @@ pub async fn patch_workspace(
     change_workspace_icon(&mut tx, workspace_id, workspace_icon).await?;
   }
   tx.commit().await?;
+  if workspace_name.is_some() || workspace_icon.is_some() {
+    notify_workspace(
+      pg_pool,
+      workspace_id,
+      WorkspaceNotification::WorkspaceChanged {
+        name: workspace_name.map(|name| name.to_string()),
+        icon: workspace_icon.map(|icon| icon.to_string()),
+      },
+    )
+    .await;
+  }
   Ok(())
 }
 
@@ -197,6 +221,21 @@ pub async fn create_comment_on_published_view(
     ));
   }
   insert_comment_to_published_view(pg_pool, view_id, user_uuid, content, reply_comment_id).await?;
+  if let Some(workspace_id) = select_workspace_id_for_published_view(pg_pool, view_id).await? {
+    let uid = select_uid_from_uuid(pg_pool, user_uuid).await.ok();
+    publish_webhook_event(
+      pg_pool,
+      &workspace_id,
+      WebhookEvent::CommentAdded,
+      uid,
+      json!({
+        "view_id": view_id,
+        "reply_comment_id": reply_comment_id,
+        "content": content,
+      }),
+    )
+    .await;
+  }
   Ok(())
 }
 
@@ -330,7 +369,7 @@ pub async fn accept_workspace_invite(
   user_uid: i64,
   user_uuid: &Uuid,
   invite_id: &Uuid,
-) -> Result<(), AppError> {
+) -> Result<AFWorkspaceInvitationMinimal, AppError> {
   let mut txn = pg_pool.begin().await?;
   let inv = get_invitation_by_id(&mut txn, invite_id).await?;
   if let Some(invitee_uid) = inv.invitee_uid {
@@ -346,10 +385,28 @@ pub async fn accept_workspace_invite(
     .invitee_uid
     .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Invitee uid is missing for {:?}", inv)))?;
   workspace_access_control
-    .insert_role(&invited_uid, &inv.workspace_id, inv.role)
+    .insert_role(&invited_uid, &inv.workspace_id, inv.role.clone())
     .await?;
   txn.commit().await?;
-  Ok(())
+  publish_webhook_event(
+    pg_pool,
+    &inv.workspace_id,
+    WebhookEvent::MemberJoined,
+    Some(invited_uid),
+    json!({ "uid": invited_uid, "role": inv.role }),
+  )
+  .await;
+  notify_workspace(
+    pg_pool,
+    &inv.workspace_id,
+    WorkspaceNotification::MemberChanged {
+      uid: invited_uid,
+      change: MemberChange::Added,
+      role: Some(i32::from(&inv.role)),
+    },
+  )
+  .await;
+  Ok(inv)
 }
 
 #[instrument(level = "debug", skip_all, err)]
@@ -358,6 +415,7 @@ pub async fn invite_workspace_members(
   mailer: &AFCloudMailer,
   pg_pool: &PgPool,
   inviter: &Uuid,
+  actor: &AuditActor,
   workspace_id: &Uuid,
   invitations: Vec<WorkspaceMemberInvitation>,
   appflowy_web_url: &str,
@@ -426,6 +484,16 @@ pub async fn invite_workspace_members(
         *invite_id
       },
     };
+    record_audit_event(
+      txn.deref_mut(),
+      workspace_id,
+      actor,
+      AuditAction::MemberInvited,
+      Some(invitation.email.as_str()),
+      None,
+      Some(json!({ "role": invitation.role, "invite_id": invite_id })),
+    )
+    .await?;
 
     // Generate a link such that when clicked, the user is added to the workspace.
     let accept_url = format!(
@@ -524,32 +592,69 @@ pub async fn leave_workspace(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
   user_uuid: &Uuid,
+  actor: &AuditActor,
   workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  ws_server: &Addr<WsServer>,
 ) -> Result<(), AppResponseError> {
   let email = database::user::select_email_from_user_uuid(pg_pool, user_uuid).await?;
-  remove_workspace_members(pg_pool, workspace_id, &[email], workspace_access_control).await
+  remove_workspace_members(
+    pg_pool,
+    workspace_id,
+    &[email],
+    actor,
+    workspace_access_control,
+    collab_access_control,
+    ws_server,
+  )
+  .await
 }
 
 pub async fn remove_workspace_members(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
   member_emails: &[String],
+  actor: &AuditActor,
   workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  ws_server: &Addr<WsServer>,
 ) -> Result<(), AppResponseError> {
   let mut txn = pg_pool
     .begin()
     .await
     .context("Begin transaction to delete workspace members")?;
 
+  let mut removed_members = vec![];
   for email in member_emails {
     if let Ok(uid) = select_uid_from_email(txn.deref_mut(), email)
       .await
       .map_err(AppResponseError::from)
     {
+      let role = select_workspace_member(txn.deref_mut(), uid, workspace_id)
+        .await?
+        .map(|member| member.role);
       delete_workspace_members(&mut txn, workspace_id, email.as_str()).await?;
+      record_audit_event(
+        txn.deref_mut(),
+        workspace_id,
+        actor,
+        AuditAction::MemberRemoved,
+        Some(email.as_str()),
+        Some(json!({ "uid": uid, "role": role })),
+        None,
+      )
+      .await?;
       workspace_access_control
         .remove_user_from_workspace(&uid, workspace_id)
         .await?;
+      let shared_view_ids =
+        delete_all_guest_view_access_for_email(txn.deref_mut(), workspace_id, email).await?;
+      for view_id in &shared_view_ids {
+        collab_access_control
+          .remove_access_level(&uid, view_id)
+          .await?;
+      }
+      removed_members.push((uid, email, shared_view_ids));
 
       // TODO: Add permission cache invalidation for removed user
       // if let Some(realtime_server) = get_realtime_server_handle() {
@@ -565,6 +670,41 @@ pub async fn remove_workspace_members(
     .commit()
     .await
     .context("Commit transaction to delete workspace members")?;
+  for (uid, email, shared_view_ids) in removed_members {
+    if !shared_view_ids.is_empty() {
+      // A removed guest loses access to the views shared with them, so their open sessions
+      // have to be told in the same way as when the access is revoked view by view.
+      ws_server.do_send(UpdateUserPermissions {
+        workspace_id: *workspace_id,
+        uid,
+        updates: shared_view_ids
+          .into_iter()
+          .map(|object_id| PermissionUpdate {
+            object_id,
+            permission_type: PermissionType::NoAccess,
+          })
+          .collect(),
+      });
+    }
+    publish_webhook_event(
+      pg_pool,
+      workspace_id,
+      WebhookEvent::MemberLeft,
+      Some(uid),
+      json!({ "uid": uid, "email": email }),
+    )
+    .await;
+    notify_workspace(
+      pg_pool,
+      workspace_id,
+      WorkspaceNotification::MemberChanged {
+        uid,
+        change: MemberChange::Removed,
+        role: None,
+      },
+    )
+    .await;
+  }
   Ok(())
 }
 
@@ -615,13 +755,45 @@ pub async fn update_workspace_member(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
   changeset: &WorkspaceMemberChangeset,
+  actor: &AuditActor,
   workspace_access_control: Arc<dyn WorkspaceAccessControl>,
 ) -> Result<(), AppError> {
   if let Some(role) = &changeset.role {
-    upsert_workspace_member(pg_pool, workspace_id, &changeset.email, role.clone()).await?;
+    let mut txn = pg_pool
+      .begin()
+      .await
+      .context("Begin transaction to update workspace member")?;
+    let old_role = select_workspace_member(txn.deref_mut(), *uid, workspace_id)
+      .await?
+      .map(|member| member.role);
+    upsert_workspace_member(&mut txn, workspace_id, &changeset.email, role.clone()).await?;
+    record_audit_event(
+      txn.deref_mut(),
+      workspace_id,
+      actor,
+      AuditAction::MemberRoleChanged,
+      Some(changeset.email.as_str()),
+      Some(json!({ "role": old_role })),
+      Some(json!({ "role": role })),
+    )
+    .await?;
+    txn
+      .commit()
+      .await
+      .context("Commit transaction to update workspace member")?;
     workspace_access_control
       .insert_role(uid, workspace_id, role.clone())
       .await?;
+    notify_workspace(
+      pg_pool,
+      workspace_id,
+      WorkspaceNotification::MemberChanged {
+        uid: *uid,
+        change: MemberChange::RoleChanged,
+        role: Some(i32::from(role)),
+      },
+    )
+    .await;
   }
 
   Ok(())
@@ -648,12 +820,14 @@ pub async fn get_workspace_settings(
 pub async fn update_workspace_settings(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
+  actor: &AuditActor,
   change: AFWorkspaceSettingsChange,
 ) -> Result<AFWorkspaceSettings, AppResponseError> {
   let mut tx = pg_pool.begin().await?;
   let mut setting = select_workspace_settings(tx.deref_mut(), workspace_id)
     .await?
     .unwrap_or_default();
+  let before = serde_json::to_value(&setting)?;
   if let Some(disable_search_indexing) = change.disable_search_indexing {
     setting.disable_search_indexing = disable_search_indexing;
   }
@@ -664,7 +838,26 @@ pub async fn update_workspace_settings(
 
   // Update the workspace settings in the database
   upsert_workspace_settings(&mut tx, workspace_id, &setting).await?;
+  record_audit_event(
+    tx.deref_mut(),
+    workspace_id,
+    actor,
+    AuditAction::WorkspaceSettingsChanged,
+    None,
+    Some(before),
+    Some(serde_json::to_value(&setting)?),
+  )
+  .await?;
   tx.commit().await?;
+  notify_workspace(
+    pg_pool,
+    workspace_id,
+    WorkspaceNotification::WorkspaceSettingsChanged {
+      disable_search_indexing: setting.disable_search_indexing,
+      ai_model: setting.ai_model.clone(),
+    },
+  )
+  .await;
   Ok(setting)
 }
 
@@ -738,6 +931,27 @@ pub async fn num_pending_task(uid: i64, pg_pool: &PgPool) -> Result<i64, AppErro
   Ok(count)
 }
 
+/// Records the export task and queues it for the appflowy worker, which builds the archive and
+/// emails the download link to the user.
+pub async fn create_export_task(
+  uid: i64,
+  task_id: Uuid,
+  task: serde_json::Value,
+  workspace_id: &Uuid,
+  redis_client: &RedisConnectionManager,
+  pg_pool: &PgPool,
+) -> Result<(), AppError> {
+  insert_export_task(task_id, workspace_id, uid, pg_pool).await?;
+
+  let _: () = redis_client
+    .clone()
+    .xadd("export_task_stream", "*", &[("task", task.to_string())])
+    .await
+    .map_err(|err| AppError::Internal(anyhow!("Failed to push task to Redis stream: {}", err)))?;
+
+  Ok(())
+}
+
 pub async fn list_workspace_mentionable_persons(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
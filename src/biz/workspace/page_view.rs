@@ -1,5 +1,6 @@
 use super::publish::PublishedCollabStore;
 use crate::api::metrics::AppFlowyWebMetrics;
+use crate::biz::audit::ops::{record_audit_event, AuditActor, AuditEvent};
 use crate::biz::chat::ops::create_chat;
 use crate::biz::collab::database::{
   resolve_dependencies_when_create_database_linked_view, LinkedViewDependencies,
@@ -14,11 +15,14 @@ use crate::biz::collab::utils::{
   batch_get_latest_collab_encoded, collab_to_doc_state, get_latest_collab,
   get_latest_collab_database_body, DUMMY_UID,
 };
+use crate::biz::guest::ops::{inherit_guest_view_access, reinherit_guest_view_access};
+use crate::biz::notification::workspace::notify_publish_changed;
+use crate::biz::webhook::ops::publish_webhook_event;
 use crate::state::AppState;
 use anyhow::anyhow;
 use app_error::AppError;
 use appflowy_collaborate::ws2::{CollabUpdatePublisher, WorkspaceCollabInstanceCache};
-use chrono::DateTime;
+use chrono::{DateTime, Utc};
 use collab::core::collab::{default_client_id, Collab, CollabOptions};
 use collab::core::origin::CollabClient;
 use collab_database::database::{
@@ -49,6 +53,7 @@ use collab_rt_entity::user::RealtimeUser;
 use database::collab::{
   select_collab_meta_from_af_collab, select_workspace_database_oid, CollabStore, GetCollabOrigin,
 };
+use database::guest::select_guest_uuids_with_access_to_view;
 use database::publish::select_published_view_ids_for_workspace;
 use database::user::{select_uuid_from_uid, select_web_user_from_uid};
 use database::workspace::{
@@ -57,14 +62,16 @@ use database::workspace::{
 };
 use database_entity::dto::{
   CollabParams, MentionablePerson, MentionablePersonWithAccess, PageMentionUpdate,
-  PublishCollabItem, PublishCollabMetadata, QueryCollab, QueryCollabResult,
+  PublishCollabItem, PublishCollabMetadata, PublishInfo, QueryCollab, QueryCollabResult,
 };
 use fancy_regex::Regex;
 use itertools::Itertools;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use serde_json::json;
+use shared_entity::dto::audit_dto::AuditAction;
 use shared_entity::dto::chat_dto::CreateChatParams;
 use shared_entity::dto::publish_dto::{PublishDatabaseData, PublishViewInfo, PublishViewMetaData};
+use shared_entity::dto::webhook_dto::WebhookEvent;
 use shared_entity::dto::workspace_dto::{
   FolderView, Page, PageCollab, PageCollabData, Space, SpacePermission, ViewIcon, ViewLayout,
 };
@@ -177,6 +184,7 @@ pub async fn create_folder_view(
   database_id: Option<Uuid>,
 ) -> Result<Page, AppError> {
   let view_id = view_id.unwrap_or_else(Uuid::new_v4);
+  let uid = user.uid;
   let collab_origin = GetCollabOrigin::User { uid: user.uid };
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
   let folder_update = add_new_view_to_folder(
@@ -229,6 +237,15 @@ pub async fn create_folder_view(
     )
     .await?;
   }
+  inherit_guest_view_access(state, workspace_id, parent_view_id, &[view_id]).await?;
+  publish_webhook_event(
+    &state.pg_pool,
+    &workspace_id,
+    WebhookEvent::PageCreated,
+    Some(uid),
+    json!({ "view_id": view_id, "parent_view_id": parent_view_id, "name": name }),
+  )
+  .await;
   Ok(Page { view_id })
 }
 
@@ -244,7 +261,8 @@ pub async fn create_page(
   view_id: Option<Uuid>,
   collab_id: Option<Uuid>,
 ) -> Result<Page, AppError> {
-  match view_layout {
+  let uid = user.uid;
+  let page = match view_layout {
     ViewLayout::Document => {
       create_document_page(
         state,
@@ -265,7 +283,17 @@ pub async fn create_page(
     },
     ViewLayout::Board => create_board_page(state, user, workspace_id, parent_view_id, name).await,
     ViewLayout::Chat => create_chat_page(state, user, workspace_id, parent_view_id, name).await,
-  }
+  }?;
+  inherit_guest_view_access(state, workspace_id, parent_view_id, &[page.view_id]).await?;
+  publish_webhook_event(
+    &state.pg_pool,
+    &workspace_id,
+    WebhookEvent::PageCreated,
+    Some(uid),
+    json!({ "view_id": page.view_id, "parent_view_id": parent_view_id, "name": name }),
+  )
+  .await;
+  Ok(page)
 }
 
 async fn prepare_document_collab_param_with_initial_data(
@@ -1367,6 +1395,11 @@ pub async fn move_page(
   prev_view_id: Option<String>,
 ) -> Result<(), AppError> {
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
+  let moved_view_ids = folder
+    .get_view_recursively(view_id, user.uid)
+    .iter()
+    .filter_map(|view| Uuid::parse_str(&view.id).ok())
+    .collect_vec();
   let folder_update = move_view(
     view_id,
     new_parent_view_id,
@@ -1383,6 +1416,8 @@ pub async fn move_page(
     folder_update,
   )
   .await?;
+  let new_parent_view_id = Uuid::parse_str(new_parent_view_id)?;
+  reinherit_guest_view_access(state, workspace_id, &new_parent_view_id, &moved_view_ids).await?;
   Ok(())
 }
 
@@ -1419,7 +1454,8 @@ pub async fn move_page_to_trash(
   if trash_info.into_iter().any(|info| info.id == view_id) {
     return Ok(());
   }
-  let folder_update = move_view_to_trash(view_id, &mut folder, user.uid).await?;
+  let uid = user.uid;
+  let folder_update = move_view_to_trash(view_id, &mut folder, uid).await?;
   update_workspace_folder_data(
     &state.metrics.appflowy_web_metrics,
     &state.ws_server,
@@ -1428,6 +1464,14 @@ pub async fn move_page_to_trash(
     folder_update,
   )
   .await?;
+  publish_webhook_event(
+    &state.pg_pool,
+    &workspace_id,
+    WebhookEvent::PageMovedToTrash,
+    Some(uid),
+    json!({ "view_id": view_id }),
+  )
+  .await;
   Ok(())
 }
 
@@ -1509,9 +1553,15 @@ pub async fn delete_trash(
 pub async fn delete_all_pages_from_trash(
   state: &AppState,
   user: RealtimeUser,
+  actor: &AuditActor,
   workspace_id: Uuid,
 ) -> Result<(), AppError> {
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
+  let trash_view_ids: Vec<String> = folder
+    .get_all_trash_sections(user.uid)
+    .into_iter()
+    .map(|section| section.id)
+    .collect();
   let update = delete_all_views_from_trash(&mut folder, user.uid).await?;
   update_workspace_folder_data(
     &state.metrics.appflowy_web_metrics,
@@ -1521,6 +1571,16 @@ pub async fn delete_all_pages_from_trash(
     update,
   )
   .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    actor,
+    AuditAction::TrashEmptied,
+    None,
+    Some(json!({ "view_ids": trash_view_ids })),
+    None,
+  )
+  .await?;
   Ok(())
 }
 
@@ -1536,8 +1596,10 @@ pub async fn update_page(
   extra: Option<impl AsRef<str>>,
 ) -> Result<(), AppError> {
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
+  let uid = user.uid;
+  let old_name = folder.get_view(view_id, uid).map(|view| view.name.clone());
   let folder_update =
-    update_view_properties(view_id, &mut folder, name, icon, is_locked, extra, user.uid).await?;
+    update_view_properties(view_id, &mut folder, name, icon, is_locked, extra, uid).await?;
   update_workspace_folder_data(
     &state.metrics.appflowy_web_metrics,
     &state.ws_server,
@@ -1546,6 +1608,9 @@ pub async fn update_page(
     folder_update,
   )
   .await?;
+  if old_name.as_deref() != Some(name) {
+    publish_page_renamed_event(state, workspace_id, uid, view_id, name).await;
+  }
 
   Ok(())
 }
@@ -1558,7 +1623,8 @@ pub async fn update_page_name(
   name: &str,
 ) -> Result<(), AppError> {
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
-  let folder_update = update_view_name(view_id, &mut folder, name, user.uid).await?;
+  let uid = user.uid;
+  let folder_update = update_view_name(view_id, &mut folder, name, uid).await?;
   update_workspace_folder_data(
     &state.metrics.appflowy_web_metrics,
     &state.ws_server,
@@ -1567,10 +1633,28 @@ pub async fn update_page_name(
     folder_update,
   )
   .await?;
+  publish_page_renamed_event(state, workspace_id, uid, view_id, name).await;
 
   Ok(())
 }
 
+async fn publish_page_renamed_event(
+  state: &AppState,
+  workspace_id: Uuid,
+  uid: i64,
+  view_id: &str,
+  name: &str,
+) {
+  publish_webhook_event(
+    &state.pg_pool,
+    &workspace_id,
+    WebhookEvent::PageRenamed,
+    Some(uid),
+    json!({ "view_id": view_id, "name": name }),
+  )
+  .await;
+}
+
 pub async fn update_page_icon(
   state: &AppState,
   user: RealtimeUser,
@@ -1664,12 +1748,15 @@ pub async fn publish_page(
   state: &AppState,
   uid: i64,
   user_uuid: Uuid,
+  actor: &AuditActor,
   workspace_id: Uuid,
   view_id: Uuid,
   visible_database_view_ids: Option<Vec<Uuid>>,
   publish_name: Option<impl ToString>,
   comments_enabled: bool,
   duplicate_enabled: bool,
+  password: Option<String>,
+  expires_at: Option<DateTime<Utc>>,
 ) -> Result<(), AppError> {
   let folder = state.ws_server.get_folder(workspace_id).await?;
   let view = folder
@@ -1701,6 +1788,28 @@ pub async fn publish_page(
     ancestor_views: vec![],
   };
 
+  let publish_name = publish_name
+    .map(|name| name.to_string())
+    .unwrap_or_else(|| generate_publish_name(&view.id, &view.name));
+  let previous_publish_info = state
+    .published_collab_store
+    .get_collab_publish_info(&view_id)
+    .await
+    .ok()
+    .filter(|info| info.unpublished_timestamp.is_none());
+  let audit_event = AuditEvent {
+    actor: actor.clone(),
+    action: AuditAction::PagePublished,
+    target: Some(view_id.to_string()),
+    before: previous_publish_info.map(|info| audit_publish_info(&info)),
+    after: Some(json!({
+      "publish_name": publish_name,
+      "comments_enabled": comments_enabled,
+      "duplicate_enabled": duplicate_enabled,
+      "password_protected": password.as_deref().is_some_and(|password| !password.is_empty()),
+      "expires_at": expires_at,
+    })),
+  };
   let publish_data = match view.layout {
     collab_folder::ViewLayout::Document => {
       generate_publish_data_for_document(&state.collab_storage, uid, workspace_id, view_id).await
@@ -1728,22 +1837,42 @@ pub async fn publish_page(
       vec![PublishCollabItem {
         meta: PublishCollabMetadata {
           view_id,
-          publish_name: publish_name
-            .map(|name| name.to_string())
-            .unwrap_or_else(|| generate_publish_name(&view.id, &view.name)),
+          publish_name: publish_name.clone(),
           metadata: serde_json::value::to_value(metadata).unwrap(),
         },
         data: publish_data,
         comments_enabled,
         duplicate_enabled,
+        password,
+        expires_at,
       }],
       &workspace_id,
       &user_uuid,
+      &[audit_event],
     )
     .await?;
+  publish_webhook_event(
+    &state.pg_pool,
+    &workspace_id,
+    WebhookEvent::PagePublished,
+    Some(uid),
+    json!({ "view_id": view_id, "name": view.name, "publish_name": publish_name }),
+  )
+  .await;
+  notify_publish_changed(&state.pg_pool, &workspace_id, &[view_id], true).await;
   Ok(())
 }
 
+fn audit_publish_info(info: &PublishInfo) -> serde_json::Value {
+  json!({
+    "publish_name": info.publish_name,
+    "comments_enabled": info.comments_enabled,
+    "duplicate_enabled": info.duplicate_enabled,
+    "password_protected": info.password_protected,
+    "expires_at": info.expires_at,
+  })
+}
+
 async fn generate_publish_data_for_document(
   collab_storage: &Arc<dyn CollabStore>,
   uid: i64,
@@ -1848,14 +1977,51 @@ async fn generate_publish_data_for_database(
 }
 
 pub async fn unpublish_page(
+  pg_pool: &PgPool,
   publish_collab_store: &dyn PublishedCollabStore,
   workspace_id: Uuid,
   user_uuid: Uuid,
+  actor: &AuditActor,
   view_id: Uuid,
 ) -> Result<(), AppError> {
+  unpublish_pages(
+    pg_pool,
+    publish_collab_store,
+    workspace_id,
+    user_uuid,
+    actor,
+    &[view_id],
+  )
+  .await
+}
+
+pub async fn unpublish_pages(
+  pg_pool: &PgPool,
+  publish_collab_store: &dyn PublishedCollabStore,
+  workspace_id: Uuid,
+  user_uuid: Uuid,
+  actor: &AuditActor,
+  view_ids: &[Uuid],
+) -> Result<(), AppError> {
+  let mut audit_events = Vec::with_capacity(view_ids.len());
+  for view_id in view_ids {
+    let publish_info = publish_collab_store
+      .get_collab_publish_info(view_id)
+      .await
+      .ok();
+    audit_events.push(AuditEvent {
+      actor: actor.clone(),
+      action: AuditAction::PageUnpublished,
+      target: Some(view_id.to_string()),
+      before: publish_info.as_ref().map(audit_publish_info),
+      after: None,
+    });
+  }
   publish_collab_store
-    .unpublish_collabs(&workspace_id, &[view_id], &user_uuid)
-    .await
+    .unpublish_collabs(&workspace_id, view_ids, &user_uuid, &audit_events)
+    .await?;
+  notify_publish_changed(pg_pool, &workspace_id, view_ids, false).await;
+  Ok(())
 }
 
 pub async fn get_page_view_collab(
@@ -2501,18 +2667,18 @@ pub async fn get_all_user_uuids_with_access_to_page(
     select_workspace_member_uuid_exclude_guest(pg_pool, workspace_id).await?
   };
   let guest_access =
-    get_all_user_uuids_with_guest_access_to_page(&folder, pg_pool, workspace_id, view_id).await?;
+    get_all_user_uuids_with_guest_access_to_page(pg_pool, workspace_id, view_id).await?;
   all_access.extend(member_access);
   all_access.extend(guest_access);
   Ok(all_access)
 }
 
 async fn get_all_user_uuids_with_guest_access_to_page(
-  _folder: &Folder,
-  _pg_pool: &PgPool,
-  _workspace_id: &Uuid,
-  _view_id: &Uuid,
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
 ) -> Result<Vec<Uuid>, AppError> {
-  // Note: the open source version of AppFlowy Cloud does not support guest access, hence this will always return empty.
-  Ok(vec![])
+  // Grants inherited from the ancestors are stored for each child view, so there is no need to
+  // walk up the folder hierarchy.
+  select_guest_uuids_with_access_to_view(pg_pool, workspace_id, view_id).await
 }
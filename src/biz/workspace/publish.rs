@@ -1,7 +1,11 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Duration, Utc};
 use database::{
+  pg_row::AFPublishedCollabAccessRow,
   publish::{
     insert_non_orginal_workspace_publish_namespace, select_all_published_collab_info,
     select_default_published_view_id, select_default_published_view_id_for_namespace,
+    select_published_collab_access, select_published_collab_access_for_view_id,
     select_workspace_publish_namespace, select_workspace_publish_namespaces,
     update_published_collabs, update_workspace_default_publish_view,
     update_workspace_default_publish_view_set_null,
@@ -9,6 +13,10 @@ use database::{
   workspace::{select_publish_name_exists, select_view_id_from_publish_name},
 };
 use database_entity::dto::PatchPublishedCollab;
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, Secret};
+use sha2::Sha256;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use app_error::AppError;
@@ -16,7 +24,7 @@ use async_trait::async_trait;
 use aws_sdk_s3::primitives::ByteStream;
 use database_entity::dto::{PublishCollabItem, PublishInfo};
 use shared_entity::dto::{
-  publish_dto::PublishViewMetaData,
+  publish_dto::{PublishViewMetaData, PublishedViewAccessToken},
   workspace_dto::{FolderViewMinimal, PublishInfoView},
 };
 use sqlx::PgPool;
@@ -24,19 +32,23 @@ use tracing::debug;
 use uuid::Uuid;
 
 use database::{
-  file::{s3_client_impl::AwsS3BucketClientImpl, BucketClient, ResponseBlob},
+  file::{app_bucket_client::AppBucketClient, BucketClient, ResponseBlob},
   publish::{
     insert_or_replace_publish_collabs, select_publish_collab_meta, select_published_collab_blob,
     select_published_collab_info, select_published_collab_workspace_view_id,
     select_published_data_for_view_id, select_published_metadata_for_view_id,
     select_user_is_collab_publisher_for_all_views, select_workspace_publish_namespace_exists,
     set_published_collabs_as_unpublished, update_non_orginal_workspace_publish_namespace,
+    verify_publish_password,
   },
   workspace::select_user_is_workspace_owner,
 };
 
 use crate::{
-  api::metrics::PublishedCollabMetrics, biz::collab::folder_view::to_dto_folder_view_miminal,
+  api::metrics::PublishedCollabMetrics,
+  biz::audit::ops::{record_audit_events, AuditEvent},
+  biz::collab::folder_view::to_dto_folder_view_miminal,
+  state::RedisConnectionManager,
 };
 
 use appflowy_collaborate::ws2::WorkspaceCollabInstanceCache;
@@ -61,6 +73,20 @@ async fn check_workspace_owner_or_publisher(
   Ok(())
 }
 
+async fn insert_publish_collabs_with_audit_events(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  user_uuid: &Uuid,
+  publish_items: Vec<PublishCollabItem<serde_json::Value, Vec<u8>>>,
+  audit_events: &[AuditEvent],
+) -> Result<(), AppError> {
+  let mut txn = pg_pool.begin().await?;
+  insert_or_replace_publish_collabs(&mut txn, workspace_id, user_uuid, publish_items).await?;
+  record_audit_events(&mut txn, workspace_id, audit_events).await?;
+  txn.commit().await?;
+  Ok(())
+}
+
 fn check_collab_publish_name(publish_name: &str) -> Result<(), AppError> {
   const MAX_PUBLISH_NAME_LENGTH: usize = 128;
 
@@ -82,6 +108,207 @@ fn check_collab_publish_name(publish_name: &str) -> Result<(), AppError> {
   Ok(())
 }
 
+/// How long a published page stays unlocked after the password has been entered.
+const PUBLISH_ACCESS_TOKEN_TTL_HOURS: i64 = 24;
+/// Password attempts allowed in each window, per client address and per published page.
+const UNLOCK_ATTEMPTS_WINDOW_SECS: i64 = 5 * 60;
+const UNLOCK_ATTEMPTS_PER_CLIENT: u64 = 10;
+const UNLOCK_ATTEMPTS_PER_PAGE: u64 = 100;
+
+/// Rejects access to an expired published page, or to a password protected one unless a valid
+/// access token obtained from [unlock_published_collab] is provided. Returns the workspace and view
+/// of the published page.
+pub async fn check_published_collab_access(
+  pg_pool: &PgPool,
+  jwt_secret: &Secret<String>,
+  publish_namespace: &str,
+  publish_name: &str,
+  access_token: Option<&str>,
+) -> Result<AFPublishedCollabAccessRow, AppError> {
+  let access = select_published_collab_access(pg_pool, publish_namespace, publish_name).await?;
+  verify_published_collab_access(jwt_secret, &access, access_token)?;
+  Ok(access)
+}
+
+/// Same as [check_published_collab_access], for a published page identified by its view id.
+pub async fn check_published_view_access(
+  pg_pool: &PgPool,
+  jwt_secret: &Secret<String>,
+  view_id: &Uuid,
+  access_token: Option<&str>,
+) -> Result<AFPublishedCollabAccessRow, AppError> {
+  let access = select_published_collab_access_for_view_id(pg_pool, view_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("View {} is not published", view_id)))?;
+  verify_published_collab_access(jwt_secret, &access, access_token)?;
+  Ok(access)
+}
+
+pub fn verify_published_collab_access(
+  jwt_secret: &Secret<String>,
+  access: &AFPublishedCollabAccessRow,
+  access_token: Option<&str>,
+) -> Result<(), AppError> {
+  if access
+    .expires_at
+    .is_some_and(|expires_at| expires_at <= Utc::now())
+  {
+    return Err(AppError::PublishedViewExpired);
+  }
+  if let Some(password_hash) = &access.password_hash {
+    let unlocked = access_token.is_some_and(|token| {
+      verify_publish_access_token(jwt_secret, &access.view_id, password_hash, token)
+    });
+    if !unlocked {
+      return Err(AppError::PublishedViewLocked);
+    }
+  }
+  Ok(())
+}
+
+/// Exchanges the password of a published page for a short-lived access token.
+pub async fn unlock_published_collab(
+  pg_pool: &PgPool,
+  redis_client: &RedisConnectionManager,
+  jwt_secret: &Secret<String>,
+  client_ip: Option<IpAddr>,
+  publish_namespace: &str,
+  publish_name: &str,
+  password: String,
+) -> Result<PublishedViewAccessToken, AppError> {
+  let access = select_published_collab_access(pg_pool, publish_namespace, publish_name).await?;
+  let now = Utc::now();
+  if access
+    .expires_at
+    .is_some_and(|expires_at| expires_at <= now)
+  {
+    return Err(AppError::PublishedViewExpired);
+  }
+  let password_hash = access.password_hash.ok_or_else(|| {
+    AppError::InvalidRequest("Published page is not protected by a password".to_string())
+  })?;
+  check_unlock_attempts(redis_client, client_ip, &access.view_id).await?;
+
+  let verified = {
+    let password_hash = password_hash.clone();
+    tokio::task::spawn_blocking(move || verify_publish_password(&password, &password_hash)).await?
+  };
+  if !verified {
+    return Err(AppError::InvalidPassword(
+      "Incorrect password for published page".to_string(),
+    ));
+  }
+
+  let mut expires_at = now + Duration::hours(PUBLISH_ACCESS_TOKEN_TTL_HOURS);
+  if let Some(page_expires_at) = access.expires_at {
+    expires_at = expires_at.min(page_expires_at);
+  }
+  let access_token = sign_publish_access_token(
+    jwt_secret,
+    &access.view_id,
+    &password_hash,
+    expires_at.timestamp(),
+  );
+  Ok(PublishedViewAccessToken {
+    access_token,
+    expires_at,
+  })
+}
+
+/// Counts the password attempts in Redis, so that they are limited across all the servers.
+/// Passwords are hashed with Argon2, which is slow on purpose, so attempts are counted before the
+/// password is checked.
+async fn check_unlock_attempts(
+  redis_client: &RedisConnectionManager,
+  client_ip: Option<IpAddr>,
+  view_id: &Uuid,
+) -> Result<(), AppError> {
+  let mut counters = vec![(
+    format!("af:publish_unlock:view:{}", view_id),
+    UNLOCK_ATTEMPTS_PER_PAGE,
+  )];
+  if let Some(client_ip) = client_ip {
+    counters.push((
+      format!("af:publish_unlock:ip:{}", client_ip),
+      UNLOCK_ATTEMPTS_PER_CLIENT,
+    ));
+  }
+
+  let mut redis_client = redis_client.clone();
+  for (key, max_attempts) in counters {
+    // the window starts with the first attempt, later attempts don't extend it
+    let (attempts,): (u64,) = redis::pipe()
+      .atomic()
+      .cmd("SET")
+      .arg(&key)
+      .arg(0)
+      .arg("NX")
+      .arg("EX")
+      .arg(UNLOCK_ATTEMPTS_WINDOW_SECS)
+      .ignore()
+      .incr(&key, 1)
+      .query_async(&mut redis_client)
+      .await
+      .map_err(|err| AppError::Internal(err.into()))?;
+    if attempts > max_attempts {
+      return Err(AppError::RetryLater(anyhow!(
+        "Too many password attempts for published page, try again later"
+      )));
+    }
+  }
+  Ok(())
+}
+
+/// The token is `<unix expiry>.<hex signature>`, where the signature is the HMAC-SHA256 of the
+/// view id, the expiry and the password hash. Changing the password invalidates issued tokens.
+fn sign_publish_access_token(
+  secret: &Secret<String>,
+  view_id: &Uuid,
+  password_hash: &str,
+  expires_at: i64,
+) -> String {
+  let signature = hex::encode(
+    publish_access_token_mac(secret, view_id, password_hash, expires_at)
+      .finalize()
+      .into_bytes(),
+  );
+  format!("{}.{}", expires_at, signature)
+}
+
+fn verify_publish_access_token(
+  secret: &Secret<String>,
+  view_id: &Uuid,
+  password_hash: &str,
+  token: &str,
+) -> bool {
+  let Some((expires_at, signature)) = token.split_once('.') else {
+    return false;
+  };
+  let (Ok(expires_at), Ok(signature)) = (expires_at.parse::<i64>(), hex::decode(signature)) else {
+    return false;
+  };
+  if DateTime::from_timestamp(expires_at, 0).is_none_or(|expires_at| expires_at <= Utc::now()) {
+    return false;
+  }
+  publish_access_token_mac(secret, view_id, password_hash, expires_at)
+    .verify_slice(&signature)
+    .is_ok()
+}
+
+fn publish_access_token_mac(
+  secret: &Secret<String>,
+  view_id: &Uuid,
+  password_hash: &str,
+  expires_at: i64,
+) -> Hmac<Sha256> {
+  let mut mac = Hmac::<Sha256>::new_from_slice(secret.expose_secret().as_bytes())
+    .expect("HMAC can take key of any size");
+  mac.update(view_id.as_bytes());
+  mac.update(expires_at.to_string().as_bytes());
+  mac.update(password_hash.as_bytes());
+  mac
+}
+
 fn get_collab_s3_key(workspace_id: &Uuid, view_id: &Uuid) -> String {
   format!("published-collab/{}/{}", workspace_id, view_id)
 }
@@ -252,11 +479,13 @@ async fn check_workspace_namespace(new_namespace: &str) -> Result<(), AppError>
 
 #[async_trait]
 pub trait PublishedCollabStore: Sync + Send + 'static {
+  /// Publishes the collabs, and writes the audit events in the same transaction.
   async fn publish_collabs(
     &self,
     published_items: Vec<PublishCollabItem<serde_json::Value, Vec<u8>>>,
     workspace_id: &Uuid,
     user_uuid: &Uuid,
+    audit_events: &[AuditEvent],
   ) -> Result<(), AppError>;
 
   async fn get_collab_with_view_metadata_by_view_id(
@@ -283,11 +512,13 @@ pub trait PublishedCollabStore: Sync + Send + 'static {
     publish_name: &str,
   ) -> Result<Vec<u8>, AppError>;
 
+  /// Unpublishes the collabs, and writes the audit events in the same transaction.
   async fn unpublish_collabs(
     &self,
     workspace_id: &Uuid,
     view_ids: &[Uuid],
     user_uuid: &Uuid,
+    audit_events: &[AuditEvent],
   ) -> Result<(), AppError>;
 
   async fn patch_collabs(
@@ -316,6 +547,7 @@ impl PublishedCollabStore for PublishedCollabPostgresStore {
     publish_items: Vec<PublishCollabItem<serde_json::Value, Vec<u8>>>,
     workspace_id: &Uuid,
     user_uuid: &Uuid,
+    audit_events: &[AuditEvent],
   ) -> Result<(), AppError> {
     for publish_item in &publish_items {
       check_collab_publish_name(publish_item.meta.publish_name.as_str())?;
@@ -328,9 +560,14 @@ impl PublishedCollabStore for PublishedCollabPostgresStore {
       .await?;
     }
     let publish_items_batch_size = publish_items.len() as i64;
-    let result =
-      insert_or_replace_publish_collabs(&self.pg_pool, workspace_id, user_uuid, publish_items)
-        .await;
+    let result = insert_publish_collabs_with_audit_events(
+      &self.pg_pool,
+      workspace_id,
+      user_uuid,
+      publish_items,
+      audit_events,
+    )
+    .await;
     if result.is_err() {
       self
         .metrics
@@ -402,9 +639,13 @@ impl PublishedCollabStore for PublishedCollabPostgresStore {
     workspace_id: &Uuid,
     view_ids: &[Uuid],
     user_uuid: &Uuid,
+    audit_events: &[AuditEvent],
   ) -> Result<(), AppError> {
     check_workspace_owner_or_publisher(&self.pg_pool, user_uuid, workspace_id, view_ids).await?;
-    set_published_collabs_as_unpublished(&self.pg_pool, workspace_id, view_ids).await?;
+    let mut txn = self.pg_pool.begin().await?;
+    set_published_collabs_as_unpublished(txn.as_mut(), workspace_id, view_ids).await?;
+    record_audit_events(&mut txn, workspace_id, audit_events).await?;
+    txn.commit().await?;
     Ok(())
   }
 
@@ -421,14 +662,14 @@ impl PublishedCollabStore for PublishedCollabPostgresStore {
 pub struct PublishedCollabS3StoreWithPostgresFallback {
   metrics: Arc<PublishedCollabMetrics>,
   pg_pool: PgPool,
-  bucket_client: AwsS3BucketClientImpl,
+  bucket_client: AppBucketClient,
 }
 
 impl PublishedCollabS3StoreWithPostgresFallback {
   pub fn new(
     metrics: Arc<PublishedCollabMetrics>,
     pg_pool: PgPool,
-    bucket_client: AwsS3BucketClientImpl,
+    bucket_client: AppBucketClient,
   ) -> Self {
     Self {
       metrics,
@@ -445,6 +686,7 @@ impl PublishedCollabStore for PublishedCollabS3StoreWithPostgresFallback {
     publish_items: Vec<PublishCollabItem<serde_json::Value, Vec<u8>>>,
     workspace_id: &Uuid,
     user_uuid: &Uuid,
+    audit_events: &[AuditEvent],
   ) -> Result<(), AppError> {
     let publish_items_batch_size = publish_items.len() as i64;
     let mut handles: Vec<tokio::task::JoinHandle<()>> = vec![];
@@ -477,9 +719,14 @@ impl PublishedCollabStore for PublishedCollabS3StoreWithPostgresFallback {
       handle.await?;
     }
 
-    let result =
-      insert_or_replace_publish_collabs(&self.pg_pool, workspace_id, user_uuid, publish_items)
-        .await;
+    let result = insert_publish_collabs_with_audit_events(
+      &self.pg_pool,
+      workspace_id,
+      user_uuid,
+      publish_items,
+      audit_events,
+    )
+    .await;
     if result.is_err() {
       self
         .metrics
@@ -588,6 +835,7 @@ impl PublishedCollabStore for PublishedCollabS3StoreWithPostgresFallback {
     workspace_id: &Uuid,
     view_ids: &[Uuid],
     user_uuid: &Uuid,
+    audit_events: &[AuditEvent],
   ) -> Result<(), AppError> {
     check_workspace_owner_or_publisher(&self.pg_pool, user_uuid, workspace_id, view_ids).await?;
     let object_keys = view_ids
@@ -595,7 +843,10 @@ impl PublishedCollabStore for PublishedCollabS3StoreWithPostgresFallback {
       .map(|view_id| get_collab_s3_key(workspace_id, view_id))
       .collect::<Vec<String>>();
     self.bucket_client.delete_blobs(object_keys).await?;
-    set_published_collabs_as_unpublished(&self.pg_pool, workspace_id, view_ids).await?;
+    let mut txn = self.pg_pool.begin().await?;
+    set_published_collabs_as_unpublished(txn.as_mut(), workspace_id, view_ids).await?;
+    record_audit_events(&mut txn, workspace_id, audit_events).await?;
+    txn.commit().await?;
     Ok(())
   }
 
@@ -0,0 +1,240 @@
+use std::net::IpAddr;
+use std::time::Duration;
+
+use app_error::AppError;
+use chrono::{Days, NaiveDate, NaiveTime, Utc};
+use database::publish_analytics::{
+  delete_published_view_events_before, insert_published_view_event,
+  select_published_namespace_daily_counts, select_published_top_referrers,
+  select_published_view_daily_counts,
+};
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, Secret};
+use sha2::Sha256;
+use shared_entity::dto::publish_dto::{
+  PublishedAnalytics, PublishedAnalyticsQuery, PublishedPageAnalytics, PublishedReferrerCount,
+  PublishedViewDailyCount,
+};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+const DEFAULT_REPORT_DAYS: u64 = 30;
+const MAX_REPORT_DAYS: i64 = 366;
+const MAX_REFERRERS: i64 = 20;
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// What is known about the reader of a published page. None of it is stored as is.
+pub struct PublishedViewVisitor<'a> {
+  pub client_ip: Option<IpAddr>,
+  pub user_agent: Option<&'a str>,
+  pub referer: Option<&'a str>,
+}
+
+/// Records a read of a published page in the background, so that the read itself is never slowed
+/// down or failed by the analytics. Does nothing when the analytics are disabled.
+pub fn record_published_view(
+  pg_pool: &PgPool,
+  secret: &Secret<String>,
+  retention_days: u32,
+  workspace_id: Uuid,
+  view_id: Uuid,
+  visitor: PublishedViewVisitor<'_>,
+) {
+  if retention_days == 0 || secret.expose_secret().is_empty() {
+    return;
+  }
+  let visitor_hash = visitor_hash(
+    secret,
+    Utc::now().date_naive(),
+    visitor.client_ip,
+    visitor.user_agent,
+  );
+  let referrer_domain = visitor.referer.and_then(referrer_domain);
+  let pg_pool = pg_pool.clone();
+  tokio::spawn(async move {
+    if let Err(err) = insert_published_view_event(
+      &pg_pool,
+      &workspace_id,
+      &view_id,
+      &visitor_hash,
+      referrer_domain.as_deref(),
+    )
+    .await
+    {
+      warn!(
+        "Failed to record view of published page {}: {}",
+        view_id, err
+      );
+    }
+  });
+}
+
+pub async fn get_published_analytics(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  query: PublishedAnalyticsQuery,
+) -> Result<PublishedAnalytics, AppError> {
+  let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+  let from = match query.from {
+    Some(from) => from,
+    None => to
+      .checked_sub_days(Days::new(DEFAULT_REPORT_DAYS - 1))
+      .unwrap_or(to),
+  };
+  if from > to {
+    return Err(AppError::InvalidRequest(
+      "The start of the period must not be after its end".to_string(),
+    ));
+  }
+  if (to - from).num_days() >= MAX_REPORT_DAYS {
+    return Err(AppError::InvalidRequest(format!(
+      "The period can not be longer than {} days",
+      MAX_REPORT_DAYS
+    )));
+  }
+
+  let start = from.and_time(NaiveTime::MIN).and_utc();
+  let end = to
+    .checked_add_days(Days::new(1))
+    .ok_or_else(|| AppError::InvalidRequest("The end of the period is out of range".to_string()))?
+    .and_time(NaiveTime::MIN)
+    .and_utc();
+
+  let daily = select_published_namespace_daily_counts(pg_pool, workspace_id, start, end)
+    .await?
+    .into_iter()
+    .map(|row| PublishedViewDailyCount {
+      date: row.date,
+      views: row.views,
+      unique_visitors: row.unique_visitors,
+    })
+    .collect();
+
+  // Rows are ordered by view id, so the days of a page are adjacent
+  let mut pages: Vec<PublishedPageAnalytics> = vec![];
+  for row in select_published_view_daily_counts(pg_pool, workspace_id, start, end).await? {
+    let count = PublishedViewDailyCount {
+      date: row.date,
+      views: row.views,
+      unique_visitors: row.unique_visitors,
+    };
+    match pages.last_mut() {
+      Some(page) if page.view_id == row.view_id => {
+        page.views += count.views;
+        page.daily.push(count);
+      },
+      _ => pages.push(PublishedPageAnalytics {
+        view_id: row.view_id,
+        views: count.views,
+        daily: vec![count],
+      }),
+    }
+  }
+  pages.sort_by(|a, b| b.views.cmp(&a.views));
+
+  let referrers = select_published_top_referrers(pg_pool, workspace_id, start, end, MAX_REFERRERS)
+    .await?
+    .into_iter()
+    .map(|row| PublishedReferrerCount {
+      domain: row.referrer_domain,
+      views: row.views,
+    })
+    .collect();
+
+  Ok(PublishedAnalytics {
+    from,
+    to,
+    daily,
+    pages,
+    referrers,
+  })
+}
+
+/// Periodically removes the view events older than the retention period.
+pub async fn run_published_view_event_cleanup(pg_pool: PgPool, retention_days: u32) {
+  info!(
+    "Starting published view event cleanup, retention: {} days",
+    retention_days
+  );
+  let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+  loop {
+    interval.tick().await;
+    let before = Utc::now() - chrono::Duration::days(retention_days as i64);
+    match delete_published_view_events_before(&pg_pool, before).await {
+      Ok(0) => {},
+      Ok(count) => info!("Removed {} expired published view events", count),
+      Err(err) => error!("Failed to remove expired published view events: {}", err),
+    }
+  }
+}
+
+/// Identifies a visitor for one day only: the date is part of the hashed input, so the same
+/// visitor can not be followed across days, and the raw address never leaves this function.
+fn visitor_hash(
+  secret: &Secret<String>,
+  date: NaiveDate,
+  client_ip: Option<IpAddr>,
+  user_agent: Option<&str>,
+) -> String {
+  let client_ip = client_ip.map(|ip| ip.to_string()).unwrap_or_default();
+  let mut mac = Hmac::<Sha256>::new_from_slice(secret.expose_secret().as_bytes())
+    .expect("HMAC can take key of any size");
+  mac.update(date.to_string().as_bytes());
+  mac.update(b"\n");
+  mac.update(client_ip.as_bytes());
+  mac.update(b"\n");
+  mac.update(user_agent.unwrap_or_default().as_bytes());
+  hex::encode(mac.finalize().into_bytes())
+}
+
+fn referrer_domain(referer: &str) -> Option<String> {
+  let url = reqwest::Url::parse(referer).ok()?;
+  if url.scheme() != "http" && url.scheme() != "https" {
+    return None;
+  }
+  url.host_str().map(|host| host.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn visitor_hash_changes_daily() {
+    let secret = Secret::new("secret".to_string());
+    let day = NaiveDate::from_ymd_opt(2025, 8, 13).unwrap();
+    let next_day = day.succ_opt().unwrap();
+    let ip = "10.0.0.1".parse().ok();
+    let hash = visitor_hash(&secret, day, ip, Some("Mozilla/5.0"));
+    assert_eq!(hash, visitor_hash(&secret, day, ip, Some("Mozilla/5.0")));
+    assert_ne!(
+      hash,
+      visitor_hash(&secret, next_day, ip, Some("Mozilla/5.0"))
+    );
+    assert_ne!(
+      hash,
+      visitor_hash(&secret, day, "10.0.0.2".parse().ok(), Some("Mozilla/5.0"))
+    );
+    assert_ne!(
+      hash,
+      visitor_hash(
+        &Secret::new("other".to_string()),
+        day,
+        ip,
+        Some("Mozilla/5.0")
+      )
+    );
+    assert!(!hash.contains("10.0.0.1"));
+  }
+
+  #[test]
+  fn referrer_domain_keeps_only_the_host() {
+    assert_eq!(
+      referrer_domain("https://news.ycombinator.com/item?id=1").as_deref(),
+      Some("news.ycombinator.com")
+    );
+    assert_eq!(referrer_domain("android-app://com.slack"), None);
+    assert_eq!(referrer_domain("not a url"), None);
+  }
+}
@@ -0,0 +1,369 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use app_error::AppError;
+use chrono::Utc;
+use dashmap::DashSet;
+use database::pg_row::AFPublishCustomDomainRow;
+use database::publish_domain::{
+  delete_publish_custom_domain, insert_publish_custom_domain, select_publish_custom_domain,
+  select_publish_custom_domains, select_publish_custom_domains_verified_before,
+  select_verified_publish_domains, select_workspace_id_for_verified_publish_domain,
+  update_publish_custom_domain_reverified, update_publish_custom_domain_verified,
+};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::Deserialize;
+use shared_entity::dto::publish_dto::PublishCustomDomain;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use super::publish::get_workspace_publish_namespace;
+
+const VERIFICATION_RECORD_PREFIX: &str = "_appflowy-verification";
+const VERIFICATION_VALUE_PREFIX: &str = "appflowy-verification=";
+const DNS_LOOKUP_TIMEOUT: Duration = Duration::from_secs(10);
+const DNS_RECORD_TYPE_TXT: u16 = 16;
+/// How often the verified domains are reloaded, and the oldest verifications checked again.
+const VERIFIED_DOMAINS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+/// A verified domain is checked again once its verification is older than this.
+const REVERIFY_AFTER_HOURS: i64 = 24;
+/// Maximum number of domains checked again on each refresh.
+const REVERIFY_BATCH_SIZE: i64 = 50;
+
+/// The verified custom domains of all workspaces, reloaded from the database periodically. Lets
+/// the catch-all routes serving the custom domains skip requests to any other host without a
+/// query. The database stays the authority on which workspace a domain belongs to.
+#[derive(Clone, Default)]
+pub struct VerifiedPublishDomains {
+  domains: Arc<DashSet<String>>,
+}
+
+impl VerifiedPublishDomains {
+  pub fn contains_host(&self, host: &str) -> bool {
+    normalize_custom_domain(strip_port(host)).is_ok_and(|domain| self.domains.contains(&domain))
+  }
+
+  pub fn insert(&self, domain: &str) {
+    self.domains.insert(domain.to_string());
+  }
+
+  fn replace(&self, domains: Vec<String>) {
+    let domains = domains.into_iter().collect::<HashSet<_>>();
+    self.domains.retain(|domain| domains.contains(domain));
+    for domain in domains {
+      self.domains.insert(domain);
+    }
+  }
+}
+
+pub async fn create_publish_custom_domain(
+  pg_pool: &PgPool,
+  appflowy_web_url: &str,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<PublishCustomDomain, AppError> {
+  let domain = normalize_custom_domain(domain)?;
+  let web_host = reqwest::Url::parse(appflowy_web_url)
+    .ok()
+    .and_then(|url| url.host_str().map(|host| host.to_lowercase()));
+  if web_host.as_deref() == Some(domain.as_str()) {
+    return Err(AppError::InvalidRequest(format!(
+      "{} is the AppFlowy publish host and can not be used as a custom domain",
+      domain
+    )));
+  }
+  let row =
+    insert_publish_custom_domain(pg_pool, workspace_id, &domain, &gen_verification_token()).await?;
+  Ok(to_publish_custom_domain(row))
+}
+
+pub async fn list_publish_custom_domains(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<PublishCustomDomain>, AppError> {
+  let domains = select_publish_custom_domains(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .map(to_publish_custom_domain)
+    .collect();
+  Ok(domains)
+}
+
+pub async fn remove_publish_custom_domain(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<(), AppError> {
+  let domain = normalize_custom_domain(domain)?;
+  delete_publish_custom_domain(pg_pool, workspace_id, &domain).await
+}
+
+/// Looks up the verification TXT record of the domain, and marks the domain as verified when the
+/// record holds the token of the workspace.
+pub async fn verify_publish_custom_domain(
+  pg_pool: &PgPool,
+  dns_resolver_url: &str,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<PublishCustomDomain, AppError> {
+  let domain = normalize_custom_domain(domain)?;
+  let row = select_publish_custom_domain(pg_pool, workspace_id, &domain).await?;
+  if row.verified_at.is_some() {
+    return Ok(to_publish_custom_domain(row));
+  }
+  if let Some(owner) = select_workspace_id_for_verified_publish_domain(pg_pool, &domain).await? {
+    if owner != *workspace_id {
+      return Err(AppError::PublishDomainAlreadyTaken(format!(
+        "{} is already used by another workspace",
+        domain
+      )));
+    }
+  }
+
+  let record_name = verification_record_name(&domain);
+  let expected_value = verification_record_value(&row.verification_token);
+  let records = lookup_txt_records(dns_resolver_url, &record_name).await?;
+  if !records.iter().any(|record| record.trim() == expected_value) {
+    return Err(AppError::PublishDomainNotVerified(format!(
+      "TXT record {} with value {} was not found",
+      record_name, expected_value
+    )));
+  }
+
+  let row = update_publish_custom_domain_verified(pg_pool, workspace_id, &domain).await?;
+  Ok(to_publish_custom_domain(row))
+}
+
+/// Returns the publish namespace served on the host, if the host is a verified custom domain.
+pub async fn resolve_publish_namespace_for_host(
+  pg_pool: &PgPool,
+  host: &str,
+) -> Result<Option<String>, AppError> {
+  let Ok(domain) = normalize_custom_domain(strip_port(host)) else {
+    return Ok(None);
+  };
+  match select_workspace_id_for_verified_publish_domain(pg_pool, &domain).await? {
+    Some(workspace_id) => Ok(Some(
+      get_workspace_publish_namespace(pg_pool, &workspace_id).await?,
+    )),
+    None => Ok(None),
+  }
+}
+
+/// Periodically checks again the TXT record of the domains verified more than
+/// [REVERIFY_AFTER_HOURS] ago, so that a domain handed over to someone else stops serving the pages
+/// of the workspace, then reloads the verified domains.
+pub async fn run_publish_custom_domain_refresh(
+  pg_pool: PgPool,
+  dns_resolver_url: String,
+  verified_domains: VerifiedPublishDomains,
+) {
+  info!("Starting publish custom domain refresh");
+  let mut interval = tokio::time::interval(VERIFIED_DOMAINS_REFRESH_INTERVAL);
+  loop {
+    interval.tick().await;
+    if let Err(err) = reverify_publish_custom_domains(&pg_pool, &dns_resolver_url).await {
+      error!(
+        "Failed to check the verified publish custom domains: {}",
+        err
+      );
+    }
+    match select_verified_publish_domains(&pg_pool).await {
+      Ok(domains) => verified_domains.replace(domains),
+      Err(err) => error!(
+        "Failed to load the verified publish custom domains: {}",
+        err
+      ),
+    }
+  }
+}
+
+async fn reverify_publish_custom_domains(
+  pg_pool: &PgPool,
+  dns_resolver_url: &str,
+) -> Result<(), AppError> {
+  let before = Utc::now() - chrono::Duration::hours(REVERIFY_AFTER_HOURS);
+  let rows =
+    select_publish_custom_domains_verified_before(pg_pool, before, REVERIFY_BATCH_SIZE).await?;
+  for row in rows {
+    let record_name = verification_record_name(&row.domain);
+    let expected_value = verification_record_value(&row.verification_token);
+    let found = match lookup_txt_records(dns_resolver_url, &record_name).await {
+      Ok(records) => records.iter().any(|record| record.trim() == expected_value),
+      Err(err) => {
+        // The domain is checked again on the next refresh
+        warn!("Failed to look up {}: {}", record_name, err);
+        continue;
+      },
+    };
+    if !found {
+      info!(
+        "TXT record {} is gone, custom domain {} is no longer verified",
+        record_name, row.domain
+      );
+    }
+    update_publish_custom_domain_reverified(pg_pool, &row.workspace_id, &row.domain, found).await?;
+  }
+  Ok(())
+}
+
+#[derive(Deserialize)]
+struct DnsJsonResponse {
+  #[serde(rename = "Status")]
+  status: u32,
+  #[serde(rename = "Answer", default)]
+  answer: Vec<DnsJsonAnswer>,
+}
+
+#[derive(Deserialize)]
+struct DnsJsonAnswer {
+  #[serde(rename = "type")]
+  record_type: u16,
+  data: String,
+}
+
+/// Queries the TXT records of `name` through a DNS over HTTPS resolver speaking the JSON API,
+/// such as Cloudflare or Google public DNS.
+async fn lookup_txt_records(dns_resolver_url: &str, name: &str) -> Result<Vec<String>, AppError> {
+  let resp = reqwest::Client::new()
+    .get(dns_resolver_url)
+    .query(&[("name", name), ("type", "TXT")])
+    .header(reqwest::header::ACCEPT, "application/dns-json")
+    .timeout(DNS_LOOKUP_TIMEOUT)
+    .send()
+    .await?
+    .error_for_status()?
+    .json::<DnsJsonResponse>()
+    .await?;
+  // NXDOMAIN and other failures mean that the record does not exist yet
+  if resp.status != 0 {
+    return Ok(vec![]);
+  }
+  let records = resp
+    .answer
+    .into_iter()
+    .filter(|answer| answer.record_type == DNS_RECORD_TYPE_TXT)
+    .map(|answer| join_txt_data(&answer.data))
+    .collect();
+  Ok(records)
+}
+
+/// TXT data is returned as one or more quoted strings, which form a single value together.
+fn join_txt_data(data: &str) -> String {
+  let data = data.trim();
+  if !data.starts_with('"') {
+    return data.to_string();
+  }
+  data
+    .split('"')
+    .enumerate()
+    .filter(|(i, _)| i % 2 == 1)
+    .map(|(_, part)| part)
+    .collect()
+}
+
+fn normalize_custom_domain(domain: &str) -> Result<String, AppError> {
+  let domain = domain.trim().trim_end_matches('.').to_lowercase();
+  let invalid = || AppError::InvalidRequest(format!("Invalid custom domain: {}", domain));
+  if domain.len() > 253 {
+    return Err(invalid());
+  }
+  let labels: Vec<&str> = domain.split('.').collect();
+  if labels.len() < 2 {
+    return Err(invalid());
+  }
+  for label in &labels {
+    let valid = !label.is_empty()
+      && label.len() <= 63
+      && !label.starts_with('-')
+      && !label.ends_with('-')
+      && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !valid {
+      return Err(invalid());
+    }
+  }
+  Ok(domain)
+}
+
+fn strip_port(host: &str) -> &str {
+  match host.rsplit_once(':') {
+    Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+    _ => host,
+  }
+}
+
+fn verification_record_name(domain: &str) -> String {
+  format!("{}.{}", VERIFICATION_RECORD_PREFIX, domain)
+}
+
+fn verification_record_value(token: &str) -> String {
+  format!("{}{}", VERIFICATION_VALUE_PREFIX, token)
+}
+
+fn gen_verification_token() -> String {
+  rand::thread_rng()
+    .sample_iter(&Alphanumeric)
+    .take(32)
+    .map(char::from)
+    .collect()
+}
+
+fn to_publish_custom_domain(row: AFPublishCustomDomainRow) -> PublishCustomDomain {
+  PublishCustomDomain {
+    verification_record_name: verification_record_name(&row.domain),
+    verification_record_value: verification_record_value(&row.verification_token),
+    domain: row.domain,
+    verified_at: row.verified_at,
+    created_at: row.created_at,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn custom_domain_is_normalized() {
+    assert_eq!(
+      normalize_custom_domain(" Docs.OurCompany.com. ").unwrap(),
+      "docs.ourcompany.com"
+    );
+    assert!(normalize_custom_domain("localhost").is_err());
+    assert!(normalize_custom_domain("docs..ourcompany.com").is_err());
+    assert!(normalize_custom_domain("-docs.ourcompany.com").is_err());
+    assert!(normalize_custom_domain("docs.ourcompany.com/page").is_err());
+  }
+
+  #[test]
+  fn host_port_is_stripped() {
+    assert_eq!(
+      strip_port("docs.ourcompany.com:8000"),
+      "docs.ourcompany.com"
+    );
+    assert_eq!(strip_port("docs.ourcompany.com"), "docs.ourcompany.com");
+  }
+
+  #[test]
+  fn verified_domains_match_the_normalized_host() {
+    let verified_domains = VerifiedPublishDomains::default();
+    verified_domains.replace(vec!["docs.ourcompany.com".to_string()]);
+    assert!(verified_domains.contains_host("Docs.OurCompany.com:443"));
+    assert!(!verified_domains.contains_host("appflowy.cloud"));
+
+    verified_domains.replace(vec!["blog.ourcompany.com".to_string()]);
+    assert!(!verified_domains.contains_host("docs.ourcompany.com"));
+    assert!(verified_domains.contains_host("blog.ourcompany.com"));
+  }
+
+  #[test]
+  fn txt_data_strings_are_joined() {
+    assert_eq!(
+      join_txt_data("\"appflowy-verification=\" \"abc\""),
+      "appflowy-verification=abc"
+    );
+    assert_eq!(join_txt_data("plain"), "plain");
+  }
+}
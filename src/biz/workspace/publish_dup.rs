@@ -14,9 +14,10 @@ use collab_entity::CollabType;
 use collab_folder::{CollabOrigin, RepeatedViewIdentifier, View};
 use database::collab::GetCollabOrigin;
 use database::collab::{select_workspace_database_oid, CollabStore};
-use database::file::s3_client_impl::AwsS3BucketClientImpl;
+use database::file::app_bucket_client::AppBucketClient;
 use database::file::BucketClient;
 use database::file::ResponseBlob;
+use database::publish::select_published_collab_access_for_view_id;
 use database::publish::select_published_data_for_view_id;
 use database::publish::select_published_metadata_for_view_id;
 use database_entity::dto::CollabParams;
@@ -31,6 +32,8 @@ use std::{collections::HashMap, sync::Arc};
 use crate::biz::collab::folder_view::to_folder_view_icon;
 use crate::biz::collab::folder_view::to_folder_view_layout;
 use crate::biz::collab::utils::{collab_from_doc_state, get_latest_collab};
+use crate::biz::workspace::publish::{check_published_view_access, verify_published_collab_access};
+use secrecy::Secret;
 use tracing::error;
 use uuid::Uuid;
 use workspace_template::gen_view_id;
@@ -55,7 +58,15 @@ pub async fn duplicate_published_collab_to_workspace(
   publish_view_id: Uuid,
   dest_workspace_id: Uuid,
   dest_view_id: Uuid,
+  access_token: Option<&str>,
 ) -> Result<Uuid, AppError> {
+  check_published_view_access(
+    &state.pg_pool,
+    &state.config.gotrue.jwt_secret,
+    &publish_view_id,
+    access_token,
+  )
+  .await?;
   let copier = PublishCollabDuplicator::new(
     state.pg_pool.clone(),
     state.bucket_client.clone(),
@@ -65,6 +76,8 @@ pub async fn duplicate_published_collab_to_workspace(
     dest_workspace_id,
     dest_view_id,
     state.metrics.collab_metrics.clone(),
+    state.config.gotrue.jwt_secret.clone(),
+    access_token.map(str::to_string),
   );
 
   let time_now = chrono::Utc::now().timestamp_millis();
@@ -103,7 +116,7 @@ pub struct PublishCollabDuplicator {
   /// and writing them to dest workspace
   pg_pool: PgPool,
   /// for fetching published data from s3
-  bucket_client: AwsS3BucketClientImpl,
+  bucket_client: AppBucketClient,
   /// user initiating the duplication
   duplicator_uid: i64,
   /// workspace to duplicate into
@@ -112,9 +125,14 @@ pub struct PublishCollabDuplicator {
   dest_view_id: Uuid,
   collab_update_publisher: Box<dyn CollabUpdatePublisher>,
   collab_metrics: Arc<CollabMetrics>,
+  /// for verifying the access token of password protected published views
+  jwt_secret: Secret<String>,
+  /// access token of the published view being duplicated. Nested published views which are
+  /// expired, or protected by a password this token doesn't unlock, are not copied.
+  access_token: Option<String>,
 }
 
-fn deserialize_publish_database_data(
+pub fn deserialize_publish_database_data(
   published_blob: &[u8],
 ) -> Result<PublishDatabaseData, AppError> {
   match serde_json::from_slice::<PublishDatabaseData>(published_blob) {
@@ -132,7 +150,7 @@ impl PublishCollabDuplicator {
   #[allow(clippy::too_many_arguments)]
   pub fn new(
     pg_pool: PgPool,
-    bucket_client: AwsS3BucketClientImpl,
+    bucket_client: AppBucketClient,
     collab_storage: Arc<dyn CollabStore>,
 
     collab_update_publisher: Box<dyn CollabUpdatePublisher>,
@@ -140,6 +158,8 @@ impl PublishCollabDuplicator {
     dest_workspace_id: Uuid,
     dest_view_id: Uuid,
     collab_metrics: Arc<CollabMetrics>,
+    jwt_secret: Secret<String>,
+    access_token: Option<String>,
   ) -> Self {
     let ts_now = chrono::Utc::now().timestamp();
     Self {
@@ -159,6 +179,8 @@ impl PublishCollabDuplicator {
       dest_view_id,
       collab_update_publisher,
       collab_metrics,
+      jwt_secret,
+      access_token,
     }
   }
 
@@ -1092,6 +1114,20 @@ impl PublishCollabDuplicator {
     &self,
     view_id: &uuid::Uuid,
   ) -> Result<Option<(PublishViewMetaData, Vec<u8>)>, AppError> {
+    let access = match select_published_collab_access_for_view_id(&self.pg_pool, view_id).await? {
+      Some(access) => access,
+      None => return Ok(None),
+    };
+    if let Err(err) =
+      verify_published_collab_access(&self.jwt_secret, &access, self.access_token.as_deref())
+    {
+      tracing::warn!(
+        "skip published view {} which can't be accessed: {}",
+        view_id,
+        err
+      );
+      return Ok(None);
+    }
     let result = select_published_metadata_for_view_id(&self.pg_pool, view_id).await?;
     match result {
       Some((workspace_id, js_val)) => {
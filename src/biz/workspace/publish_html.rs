@@ -0,0 +1,585 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Arc;
+
+use app_error::AppError;
+use collab::core::collab::default_client_id;
+use collab_database::database::DatabaseBody;
+use collab_database::database_trait::NoPersistenceDatabaseCollabService;
+use collab_database::fields::Field;
+use collab_database::rows::RowDetail;
+use collab_document::blocks::{Block, DocumentData};
+use collab_document::document::Document;
+use database::publish::{
+  select_published_collab_rendered_html, update_published_collab_rendered_html,
+};
+use serde_json::Value;
+use shared_entity::dto::publish_dto::PublishViewMetaData;
+use shared_entity::dto::workspace_dto::{IconType, ViewLayout};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::collab::utils::{
+  collab_from_doc_state, field_by_id_name_uniq, get_row_details_serde, type_option_reader_by_id,
+};
+use crate::biz::workspace::publish::PublishedCollabStore;
+use crate::biz::workspace::publish_dup::deserialize_publish_database_data;
+
+/// Maximum number of characters of the page text used as description in the meta tags.
+const MAX_DESCRIPTION_LENGTH: usize = 200;
+
+/// The rendered page is static: no script may run, whatever content ends up in it.
+pub const PUBLISHED_HTML_CONTENT_SECURITY_POLICY: &str = concat!(
+  "default-src 'none'; img-src http: https: data:; style-src 'unsafe-inline'; ",
+  "base-uri 'none'; form-action 'none'; frame-ancestors 'none'"
+);
+
+/// Static rendering of the content of a published page, cached until the page is republished.
+/// The page shell is rendered on each request, as it depends on the namespace and publish name.
+struct RenderedPublishedView {
+  body: String,
+  summary: String,
+}
+
+/// URL of the published page on the AppFlowy web app.
+pub fn published_page_url(
+  appflowy_web_url: &str,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> String {
+  format!(
+    "{}/{}/{}",
+    appflowy_web_url.trim_end_matches('/'),
+    publish_namespace,
+    publish_name
+  )
+}
+
+/// Returns the published page as a standalone HTML page with OpenGraph and Twitter meta tags,
+/// for crawlers, link unfurlers and readers without JavaScript. `page_url` is the canonical URL
+/// of the page.
+pub async fn get_published_collab_html(
+  pg_pool: &PgPool,
+  publish_collab_store: &dyn PublishedCollabStore,
+  page_url: &str,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<String, AppError> {
+  let metadata: PublishViewMetaData = serde_json::from_value(
+    publish_collab_store
+      .get_collab_metadata(publish_namespace, publish_name)
+      .await?,
+  )?;
+
+  let rendered =
+    match select_published_collab_rendered_html(pg_pool, publish_namespace, publish_name).await? {
+      Some((body, summary)) => RenderedPublishedView { body, summary },
+      None => {
+        let blob = publish_collab_store
+          .get_collab_blob_by_publish_namespace(publish_namespace, publish_name)
+          .await?;
+        let layout = metadata.view.layout.clone();
+        let view_id = metadata.view.view_id.clone();
+        let rendered =
+          tokio::task::spawn_blocking(move || render_published_view(&layout, &view_id, blob))
+            .await??;
+        if let Err(err) = update_published_collab_rendered_html(
+          pg_pool,
+          publish_namespace,
+          publish_name,
+          &rendered.body,
+          &rendered.summary,
+        )
+        .await
+        {
+          tracing::warn!(
+            "Failed to cache rendered published page {}/{}: {}",
+            publish_namespace,
+            publish_name,
+            err
+          );
+        }
+        rendered
+      },
+    };
+
+  Ok(render_page(&metadata, &rendered, page_url))
+}
+
+fn render_published_view(
+  layout: &ViewLayout,
+  view_id: &str,
+  blob: Vec<u8>,
+) -> Result<RenderedPublishedView, AppError> {
+  match layout {
+    ViewLayout::Document => render_document(blob),
+    ViewLayout::Grid | ViewLayout::Board | ViewLayout::Calendar => Ok(RenderedPublishedView {
+      body: render_database(&blob, view_id)?,
+      summary: String::new(),
+    }),
+    ViewLayout::Chat => Ok(RenderedPublishedView {
+      body: String::new(),
+      summary: String::new(),
+    }),
+  }
+}
+
+fn render_page(
+  metadata: &PublishViewMetaData,
+  rendered: &RenderedPublishedView,
+  url: &str,
+) -> String {
+  let view = &metadata.view;
+  let title = if view.name.is_empty() {
+    "Untitled".to_string()
+  } else {
+    view.name.clone()
+  };
+  let description = summarize(&rendered.summary);
+  let cover_url = view.extra.as_deref().and_then(cover_image_url);
+  let icon = view
+    .icon
+    .as_ref()
+    .filter(|icon| icon.ty == IconType::Emoji)
+    .map(|icon| format!("{} ", escape_html(&icon.value)))
+    .unwrap_or_default();
+
+  let mut html = String::with_capacity(rendered.body.len() + 2048);
+  html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+  html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+  let _ = writeln!(html, "<title>{}</title>", escape_html(&title));
+  let _ = writeln!(
+    html,
+    "<link rel=\"canonical\" href=\"{}\">",
+    escape_html(url)
+  );
+  let mut meta = |attr: &str, key: &str, value: &str| {
+    let _ = writeln!(
+      html,
+      "<meta {}=\"{}\" content=\"{}\">",
+      attr,
+      key,
+      escape_html(value)
+    );
+  };
+  meta("name", "description", &description);
+  meta("property", "og:type", "article");
+  meta("property", "og:site_name", "AppFlowy");
+  meta("property", "og:title", &title);
+  meta("property", "og:description", &description);
+  meta("property", "og:url", url);
+  let twitter_card = match &cover_url {
+    Some(cover_url) => {
+      meta("property", "og:image", cover_url);
+      meta("name", "twitter:image", cover_url);
+      "summary_large_image"
+    },
+    None => "summary",
+  };
+  meta("name", "twitter:card", twitter_card);
+  meta("name", "twitter:title", &title);
+  meta("name", "twitter:description", &description);
+  html.push_str("</head>\n<body>\n<main>\n");
+  if let Some(cover_url) = &cover_url {
+    let _ = writeln!(
+      html,
+      "<img class=\"cover\" src=\"{}\" alt=\"\">",
+      escape_html(cover_url)
+    );
+  }
+  let _ = writeln!(html, "<h1>{}{}</h1>", icon, escape_html(&title));
+  html.push_str(&rendered.body);
+  html.push_str("\n</main>\n</body>\n</html>\n");
+  html
+}
+
+/// The view extra is a JSON string such as `{"cover":{"type":"custom","value":"<url>"}}`.
+/// Only covers pointing to an image URL are used, built-in colors and gradients are ignored.
+fn cover_image_url(extra: &str) -> Option<String> {
+  let extra: Value = serde_json::from_str(extra).ok()?;
+  let value = extra.get("cover")?.get("value")?.as_str()?;
+  (value.starts_with("https://") || value.starts_with("http://")).then(|| value.to_string())
+}
+
+fn summarize(text: &str) -> String {
+  let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+  match text.char_indices().nth(MAX_DESCRIPTION_LENGTH) {
+    Some((end, _)) => format!("{}…", text[..end].trim_end()),
+    None => text,
+  }
+}
+
+fn render_document(doc_state: Vec<u8>) -> Result<RenderedPublishedView, AppError> {
+  let collab = collab_from_doc_state(doc_state, &Uuid::default(), default_client_id())?;
+  let document = Document::open(collab).map_err(|err| AppError::Unhandled(err.to_string()))?;
+  let summary = document.paragraphs().join(" ");
+  let data = document
+    .get_document_data()
+    .map_err(|err| AppError::Unhandled(err.to_string()))?;
+  let mut html = String::new();
+  DocumentRenderer { data: &data }.render_children(&data.page_id, &mut html);
+  Ok(RenderedPublishedView {
+    body: html,
+    summary,
+  })
+}
+
+struct DocumentRenderer<'a> {
+  data: &'a DocumentData,
+}
+
+impl DocumentRenderer<'_> {
+  fn children(&self, block_id: &str) -> &[String] {
+    self
+      .data
+      .blocks
+      .get(block_id)
+      .and_then(|block| self.data.meta.children_map.get(&block.children))
+      .map(|children| children.as_slice())
+      .unwrap_or_default()
+  }
+
+  /// Renders the children of the block. Consecutive list items are grouped into one list.
+  fn render_children(&self, block_id: &str, html: &mut String) {
+    let mut open_list: Option<&str> = None;
+    for child_id in self.children(block_id) {
+      let Some(block) = self.data.blocks.get(child_id) else {
+        continue;
+      };
+      let list = match block.ty.as_str() {
+        "bulleted_list" | "todo_list" => Some("ul"),
+        "numbered_list" => Some("ol"),
+        _ => None,
+      };
+      if open_list != list {
+        if let Some(tag) = open_list {
+          let _ = write!(html, "</{}>", tag);
+        }
+        if let Some(tag) = list {
+          let _ = write!(html, "<{}>", tag);
+        }
+        open_list = list;
+      }
+      self.render_block(block, html);
+    }
+    if let Some(tag) = open_list {
+      let _ = write!(html, "</{}>", tag);
+    }
+  }
+
+  fn render_block(&self, block: &Block, html: &mut String) {
+    let text = self.text(block);
+    let mut children = String::new();
+    self.render_children(&block.id, &mut children);
+    match block.ty.as_str() {
+      "heading" => {
+        let level = block
+          .data
+          .get("level")
+          .and_then(|level| level.as_u64())
+          .unwrap_or(1)
+          .clamp(1, 6);
+        let _ = write!(html, "<h{level}>{text}</h{level}>{children}");
+      },
+      "bulleted_list" | "numbered_list" => {
+        let _ = write!(html, "<li>{text}{children}</li>");
+      },
+      "todo_list" => {
+        let checked = block
+          .data
+          .get("checked")
+          .and_then(|checked| checked.as_bool())
+          .unwrap_or(false);
+        let checked = if checked { " checked" } else { "" };
+        let _ = write!(
+          html,
+          "<li><input type=\"checkbox\" disabled{checked}> {text}{children}</li>"
+        );
+      },
+      "quote" => {
+        let _ = write!(html, "<blockquote>{text}{children}</blockquote>");
+      },
+      "callout" => {
+        let icon = block
+          .data
+          .get("icon")
+          .and_then(|icon| icon.as_str())
+          .map(|icon| format!("{} ", escape_html(icon)))
+          .unwrap_or_default();
+        let _ = write!(html, "<aside>{icon}{text}{children}</aside>");
+      },
+      "toggle_list" => {
+        let _ = write!(
+          html,
+          "<details><summary>{text}</summary>{children}</details>"
+        );
+      },
+      "code" => {
+        let _ = write!(html, "<pre><code>{text}</code></pre>{children}");
+      },
+      "math_equation" => {
+        let formula = block
+          .data
+          .get("formula")
+          .and_then(|formula| formula.as_str())
+          .unwrap_or_default();
+        let _ = write!(html, "<pre>{}</pre>", escape_html(formula));
+      },
+      "divider" => html.push_str("<hr>"),
+      "image" => {
+        if let Some(url) = block.data.get("url").and_then(|url| url.as_str()) {
+          if url.starts_with("https://") || url.starts_with("http://") {
+            let _ = write!(
+              html,
+              "<figure><img src=\"{}\" alt=\"\"></figure>",
+              escape_html(url)
+            );
+          }
+        }
+      },
+      _ => {
+        if !text.is_empty() {
+          let _ = write!(html, "<p>{text}</p>");
+        }
+        html.push_str(&children);
+      },
+    }
+  }
+
+  /// Renders the delta of the block text, e.g. `[{"insert":"Hi","attributes":{"bold":true}}]`.
+  fn text(&self, block: &Block) -> String {
+    let delta = block
+      .external_id
+      .as_ref()
+      .and_then(|text_id| self.data.meta.text_map.as_ref()?.get(text_id))
+      .and_then(|delta| serde_json::from_str::<Vec<Value>>(delta).ok())
+      .unwrap_or_default();
+    let mut html = String::new();
+    for op in delta {
+      let Some(insert) = op.get("insert").and_then(|insert| insert.as_str()) else {
+        continue;
+      };
+      let attributes = op.get("attributes");
+      let attribute = |key: &str| attributes.and_then(|attributes| attributes.get(key));
+      // Mentions are rendered by the web app from the attributes, the inserted text is a placeholder
+      if attribute("mention").is_some() {
+        continue;
+      }
+      let mut text = escape_html(insert);
+      for (key, tag) in [
+        ("code", "code"),
+        ("bold", "strong"),
+        ("italic", "em"),
+        ("underline", "u"),
+        ("strikethrough", "s"),
+      ] {
+        if attribute(key).and_then(|value| value.as_bool()) == Some(true) {
+          text = format!("<{tag}>{text}</{tag}>");
+        }
+      }
+      if let Some(href) = attribute("href")
+        .and_then(|href| href.as_str())
+        .filter(|href| is_safe_href(href))
+      {
+        text = format!("<a href=\"{}\">{}</a>", escape_html(href), text);
+      }
+      html.push_str(&text);
+    }
+    html
+  }
+}
+
+/// Renders the rows of the published database view as a table.
+fn render_database(blob: &[u8], view_id: &str) -> Result<String, AppError> {
+  let data = deserialize_publish_database_data(blob)?;
+  let client_id = default_client_id();
+  let db_collab = collab_from_doc_state(data.database_collab, &Uuid::default(), client_id)?;
+  let db_body = DatabaseBody::from_collab(
+    &db_collab,
+    Arc::new(NoPersistenceDatabaseCollabService::new(client_id)),
+    None,
+  )
+  .ok_or_else(|| AppError::RecordNotFound("no database body found".to_string()))?;
+
+  let txn = db_collab.transact();
+  let view = match db_body.views.get_view(&txn, view_id) {
+    Some(view) => view,
+    None => {
+      let inline_view_id = db_body.get_inline_view_id(&txn);
+      db_body
+        .views
+        .get_view(&txn, &inline_view_id)
+        .ok_or_else(|| AppError::RecordNotFound(format!("database view not found: {}", view_id)))?
+    },
+  };
+  let fields = db_body.fields.get_all_fields(&txn);
+  let type_option_reader_by_id = type_option_reader_by_id(&fields);
+  let field_by_id: HashMap<String, Field> = field_by_id_name_uniq(fields);
+  let columns: Vec<&Field> = view
+    .field_orders
+    .iter()
+    .filter_map(|field_order| field_by_id.get(&field_order.id))
+    .collect();
+
+  let mut html = String::from("<table>\n<thead><tr>");
+  for field in &columns {
+    let _ = write!(html, "<th>{}</th>", escape_html(&field.name));
+  }
+  html.push_str("</tr></thead>\n<tbody>\n");
+  for row_order in &view.row_orders {
+    // Rows hidden from the published view are not part of the published data
+    let Some(row_doc_state) = Uuid::parse_str(&row_order.id)
+      .ok()
+      .and_then(|row_id| data.database_row_collabs.get(&row_id))
+    else {
+      continue;
+    };
+    let row_collab = collab_from_doc_state(row_doc_state.clone(), &Uuid::default(), client_id)?;
+    let Some(row_detail) = RowDetail::from_collab(&row_collab) else {
+      continue;
+    };
+    let cells = get_row_details_serde(row_detail, &field_by_id, &type_option_reader_by_id);
+    html.push_str("<tr>");
+    for field in &columns {
+      let text = cells.get(&field.name).map(cell_text).unwrap_or_default();
+      let _ = write!(html, "<td>{}</td>", escape_html(&text));
+    }
+    html.push_str("</tr>\n");
+  }
+  html.push_str("</tbody>\n</table>");
+  Ok(html)
+}
+
+fn cell_text(value: &Value) -> String {
+  match value {
+    Value::Null => String::new(),
+    Value::String(text) => text.clone(),
+    Value::Array(items) => join_cell_texts(items.iter()),
+    Value::Object(map) => join_cell_texts(map.values()),
+    value => value.to_string(),
+  }
+}
+
+fn join_cell_texts<'a>(values: impl Iterator<Item = &'a Value>) -> String {
+  values
+    .map(cell_text)
+    .filter(|text| !text.is_empty())
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// Links are rendered for web and mail urls, and relative ones. Any other scheme, such as
+/// `javascript:`, is rendered as plain text.
+fn is_safe_href(href: &str) -> bool {
+  // Browsers ignore whitespace and control characters in the scheme, e.g. `java\tscript:`
+  let href = href
+    .chars()
+    .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+    .collect::<String>();
+  match href.find([':', '/', '?', '#']) {
+    Some(index) if href[index..].starts_with(':') => matches!(
+      href[..index].to_ascii_lowercase().as_str(),
+      "http" | "https" | "mailto"
+    ),
+    _ => true,
+  }
+}
+
+fn escape_html(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&#39;"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use collab_document::blocks::DocumentMeta;
+  use serde_json::json;
+
+  #[test]
+  fn escape_html_special_characters() {
+    assert_eq!(
+      escape_html(r#"<a href="x">Tom & 'Jerry'</a>"#),
+      "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+    );
+  }
+
+  #[test]
+  fn render_unsafe_links_as_plain_text() {
+    let links = [
+      ("https://appflowy.io", true),
+      ("mailto:hello@appflowy.io", true),
+      ("/docs#intro", true),
+      ("javascript:alert(1)", false),
+      (" JavaScript:alert(1)", false),
+      ("java\tscript:alert(1)", false),
+      ("data:text/html,<script>alert(1)</script>", false),
+    ];
+    let delta = links
+      .iter()
+      .map(|(href, _)| json!({"insert": "link", "attributes": {"href": href}}))
+      .collect::<Vec<_>>();
+    let block = Block {
+      id: "paragraph".to_string(),
+      ty: "paragraph".to_string(),
+      parent: "page".to_string(),
+      children: "paragraph_children".to_string(),
+      external_id: Some("paragraph_text".to_string()),
+      external_type: Some("text".to_string()),
+      data: HashMap::new(),
+    };
+    let data = DocumentData {
+      page_id: "page".to_string(),
+      blocks: HashMap::new(),
+      meta: DocumentMeta {
+        children_map: HashMap::new(),
+        text_map: Some(HashMap::from([(
+          "paragraph_text".to_string(),
+          Value::Array(delta).to_string(),
+        )])),
+      },
+    };
+
+    let html = DocumentRenderer { data: &data }.text(&block);
+    for (href, is_safe) in links {
+      assert_eq!(is_safe_href(href), is_safe, "{}", href);
+    }
+    assert_eq!(html.matches("<a href=").count(), 3, "{}", html);
+    assert!(!html.to_ascii_lowercase().contains("script:"), "{}", html);
+    assert!(!html.contains("data:"), "{}", html);
+  }
+
+  #[test]
+  fn cover_image_url_only_accepts_urls() {
+    assert_eq!(
+      cover_image_url(r#"{"cover":{"type":"custom","value":"https://example.com/a.png"}}"#),
+      Some("https://example.com/a.png".to_string())
+    );
+    assert_eq!(
+      cover_image_url(r#"{"cover":{"type":"none","value":""}}"#),
+      None
+    );
+    assert_eq!(
+      cover_image_url(r#"{"cover":{"type":"color","value":"0xFFA34AFD"}}"#),
+      None
+    );
+  }
+
+  #[test]
+  fn summarize_truncates_long_text() {
+    assert_eq!(summarize("  hello \n world "), "hello world");
+    let summary = summarize(&"a ".repeat(300));
+    assert!(summary.chars().count() <= MAX_DESCRIPTION_LENGTH + 1);
+    assert!(summary.ends_with('…'));
+  }
+}
@@ -1,4 +1,5 @@
 use app_error::AppError;
+use appflowy_proto::{QuickNoteChange, WorkspaceNotification};
 use database::quick_note::{
   delete_quick_note_by_id, insert_new_quick_note, select_quick_notes_with_one_more_than_limit,
   update_quick_note_by_id,
@@ -9,6 +10,8 @@ use uuid::Uuid;
 
 use database_entity::dto::{QuickNote, QuickNotes};
 
+use crate::biz::notification::workspace::notify_workspace_user;
+
 pub async fn create_quick_note(
   pg_pool: &PgPool,
   uid: i64,
@@ -25,19 +28,72 @@ pub async fn create_quick_note(
   ]);
   let new_data = data.unwrap_or(&default_data);
   let quick_note = insert_new_quick_note(pg_pool, workspace_id, uid, new_data).await?;
+  notify_quick_note_changed(
+    pg_pool,
+    uid,
+    workspace_id,
+    quick_note.id,
+    QuickNoteChange::Created,
+  )
+  .await;
   Ok(quick_note)
 }
 
 pub async fn update_quick_note(
   pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: Uuid,
   quick_note_id: Uuid,
   data: &serde_json::Value,
 ) -> Result<(), AppError> {
-  update_quick_note_by_id(pg_pool, quick_note_id, data).await
+  update_quick_note_by_id(pg_pool, quick_note_id, data).await?;
+  notify_quick_note_changed(
+    pg_pool,
+    uid,
+    workspace_id,
+    quick_note_id,
+    QuickNoteChange::Updated,
+  )
+  .await;
+  Ok(())
 }
 
-pub async fn delete_quick_note(pg_pool: &PgPool, quick_note_id: Uuid) -> Result<(), AppError> {
-  delete_quick_note_by_id(pg_pool, quick_note_id).await
+pub async fn delete_quick_note(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: Uuid,
+  quick_note_id: Uuid,
+) -> Result<(), AppError> {
+  delete_quick_note_by_id(pg_pool, quick_note_id).await?;
+  notify_quick_note_changed(
+    pg_pool,
+    uid,
+    workspace_id,
+    quick_note_id,
+    QuickNoteChange::Deleted,
+  )
+  .await;
+  Ok(())
+}
+
+/// Quick notes are private, so only the sessions of their owner are notified.
+async fn notify_quick_note_changed(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: Uuid,
+  quick_note_id: Uuid,
+  change: QuickNoteChange,
+) {
+  notify_workspace_user(
+    pg_pool,
+    Some(&workspace_id),
+    uid,
+    WorkspaceNotification::QuickNoteChanged {
+      quick_note_id,
+      change,
+    },
+  )
+  .await;
 }
 
 pub async fn list_quick_notes(
@@ -1,10 +1,12 @@
 use std::fmt::Display;
+use std::net::IpAddr;
 use std::str::FromStr;
 
 use anyhow::{anyhow, Context};
 use async_openai::config::{AzureConfig, OpenAIConfig};
 use indexer::vector::embedder::get_open_ai_config;
 use infra::env_util::{get_env_var, get_env_var_opt};
+use ipnet::IpNet;
 use mailer::config::MailerSetting;
 use secrecy::{ExposeSecret, Secret};
 use semver::Version;
@@ -22,6 +24,7 @@ pub struct Config {
   pub redis_uri: Secret<String>,
   pub redis_worker_count: usize,
   pub s3: S3Setting,
+  pub bucket_storage: BucketStorageSetting,
   pub appflowy_ai: AppFlowyAISetting,
   pub collab: CollabSetting,
   pub published_collab: PublishedCollabSetting,
@@ -65,6 +68,42 @@ pub struct S3Setting {
   pub presigned_url_endpoint: Option<String>,
 }
 
+#[derive(Clone, Debug)]
+pub enum BucketStorageBackend {
+  S3,
+  Filesystem,
+}
+
+impl TryFrom<&str> for BucketStorageBackend {
+  type Error = anyhow::Error;
+
+  fn try_from(value: &str) -> Result<Self, Self::Error> {
+    match value {
+      "s3" => Ok(BucketStorageBackend::S3),
+      "filesystem" => Ok(BucketStorageBackend::Filesystem),
+      _ => Err(anyhow::anyhow!("Invalid BucketStorageBackend")),
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct BucketStorageSetting {
+  pub backend: BucketStorageBackend,
+  pub fs: FsStorageSetting,
+}
+
+/// Used when the bucket storage backend is [BucketStorageBackend::Filesystem].
+#[derive(Clone, Debug)]
+pub struct FsStorageSetting {
+  pub root: String,
+  /// Public url of the `/api/bucket` endpoints, used to build presigned urls.
+  pub presigned_url_endpoint: String,
+  /// Signs the presigned urls. Has no default: the backend refuses to start when it is not set.
+  pub presign_secret: Secret<String>,
+  /// Multipart uploads that are not completed within this many seconds are removed.
+  pub upload_expire_secs: u64,
+}
+
 #[derive(serde::Deserialize, Clone, Debug)]
 pub struct GoTrueSetting {
   pub base_url: String,
@@ -72,10 +111,33 @@ pub struct GoTrueSetting {
   pub service_role: String,
 }
 
-#[derive(serde::Deserialize, Clone, Debug)]
+#[derive(Clone, Debug)]
+pub enum AIProviderKind {
+  /// Chat and completion are forwarded to the AppFlowy AI service.
+  AppFlowyAI,
+  /// Chat and completion are answered in-process with the OpenAI or Azure OpenAI API.
+  OpenAI,
+}
+
+impl TryFrom<&str> for AIProviderKind {
+  type Error = anyhow::Error;
+
+  fn try_from(value: &str) -> Result<Self, Self::Error> {
+    match value {
+      "appflowy_ai" => Ok(AIProviderKind::AppFlowyAI),
+      "openai" => Ok(AIProviderKind::OpenAI),
+      _ => Err(anyhow::anyhow!("Invalid AIProviderKind")),
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
 pub struct AppFlowyAISetting {
   pub port: Secret<String>,
   pub host: Secret<String>,
+  pub provider: AIProviderKind,
+  /// Model used by the [AIProviderKind::OpenAI] provider.
+  pub chat_model: String,
 }
 
 impl AppFlowyAISetting {
@@ -100,6 +162,9 @@ impl AppFlowyAISetting {
 pub struct ApplicationSetting {
   pub port: u16,
   pub host: String,
+  /// Proxies in front of the server, eg. nginx, whose `X-Forwarded-For` header is trusted to
+  /// carry the address of the client.
+  pub trusted_proxies: Vec<IpNet>,
 }
 
 #[derive(Clone, Debug)]
@@ -154,6 +219,13 @@ pub enum PublishedCollabStorageBackend {
 #[derive(Clone, Debug)]
 pub struct PublishedCollabSetting {
   pub storage_backend: PublishedCollabStorageBackend,
+  /// Number of days the read events of published pages are kept. 0 disables the analytics.
+  pub analytics_retention_days: u32,
+  /// Key of the HMAC turning the address and user agent of a reader into a daily visitor id.
+  /// The analytics are not recorded while it is empty.
+  pub analytics_secret: Secret<String>,
+  /// DNS over HTTPS endpoint (JSON API) used to look up the TXT records of custom domains.
+  pub custom_domain_dns_resolver_url: String,
 }
 
 impl TryFrom<&str> for PublishedCollabStorageBackend {
@@ -176,6 +248,26 @@ pub struct NotificationSetting {
 }
 
 // Default values favor local development.
+/// Parses a comma separated list of addresses and networks, eg. `10.0.0.1,172.16.0.0/12`.
+fn parse_trusted_proxies(value: &str) -> Result<Vec<IpNet>, anyhow::Error> {
+  value
+    .split(',')
+    .map(str::trim)
+    .filter(|proxy| !proxy.is_empty())
+    .map(|proxy| {
+      proxy
+        .parse::<IpNet>()
+        .or_else(|_| proxy.parse::<IpAddr>().map(IpNet::from))
+        .with_context(|| {
+          format!(
+            "invalid trusted proxy in APPFLOWY_TRUSTED_PROXIES: {}",
+            proxy
+          )
+        })
+    })
+    .collect()
+}
+
 pub fn get_configuration() -> Result<Config, anyhow::Error> {
   let (open_ai_config, azure_ai_config) = get_open_ai_config();
   let config = Config {
@@ -216,11 +308,19 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
     application: ApplicationSetting {
       port: get_env_var("APPFLOWY_APPLICATION_PORT", "8000").parse()?,
       host: get_env_var("APPFLOWY_APPLICATION_HOST", "[::]"),
+      trusted_proxies: parse_trusted_proxies(&get_env_var("APPFLOWY_TRUSTED_PROXIES", ""))?,
     },
     websocket: WebsocketSetting {
       heartbeat_interval: get_env_var("APPFLOWY_WEBSOCKET_HEARTBEAT_INTERVAL", "6").parse()?,
       client_timeout: get_env_var("APPFLOWY_WEBSOCKET_CLIENT_TIMEOUT", "60").parse()?,
       min_client_version: get_env_var("APPFLOWY_WEBSOCKET_CLIENT_MIN_VERSION", "0.5.0").parse()?,
+      observer_max_sessions: get_env_var("APPFLOWY_WEBSOCKET_OBSERVER_MAX_SESSIONS", "5")
+        .parse()?,
+      observer_max_subscriptions: get_env_var(
+        "APPFLOWY_WEBSOCKET_OBSERVER_MAX_SUBSCRIPTIONS",
+        "100",
+      )
+      .parse()?,
     },
     redis_uri: get_env_var("APPFLOWY_REDIS_URI", "redis://localhost:6379").into(),
     redis_worker_count: get_env_var("APPFLOWY_REDIS_WORKERS", "60").parse()?,
@@ -238,9 +338,29 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       region: get_env_var("APPFLOWY_S3_REGION", ""),
       presigned_url_endpoint: get_env_var_opt("APPFLOWY_S3_PRESIGNED_URL_ENDPOINT"),
     },
+    bucket_storage: BucketStorageSetting {
+      backend: get_env_var("APPFLOWY_STORAGE_BACKEND", "s3")
+        .as_str()
+        .try_into()?,
+      fs: FsStorageSetting {
+        root: get_env_var("APPFLOWY_STORAGE_FS_ROOT", "data/storage"),
+        presigned_url_endpoint: get_env_var(
+          "APPFLOWY_STORAGE_FS_PRESIGNED_URL_ENDPOINT",
+          "http://localhost:8000/api/bucket",
+        ),
+        presign_secret: get_env_var("APPFLOWY_STORAGE_FS_PRESIGN_SECRET", "").into(),
+        upload_expire_secs: get_env_var("APPFLOWY_STORAGE_FS_UPLOAD_EXPIRE_SECS", "86400")
+          .parse()
+          .context("fail to get APPFLOWY_STORAGE_FS_UPLOAD_EXPIRE_SECS")?,
+      },
+    },
     appflowy_ai: AppFlowyAISetting {
       port: get_env_var("AI_SERVER_PORT", "5001").into(),
       host: get_env_var("AI_SERVER_HOST", "localhost").into(),
+      provider: get_env_var("AI_PROVIDER", "appflowy_ai")
+        .as_str()
+        .try_into()?,
+      chat_model: get_env_var("AI_CHAT_MODEL", "gpt-4o-mini"),
     },
     collab: CollabSetting {
       group_persistence_interval_secs: get_env_var(
@@ -258,6 +378,13 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       storage_backend: get_env_var("APPFLOWY_PUBLISHED_COLLAB_STORAGE_BACKEND", "postgres")
         .as_str()
         .try_into()?,
+      analytics_retention_days: get_env_var("APPFLOWY_PUBLISH_ANALYTICS_RETENTION_DAYS", "90")
+        .parse()?,
+      analytics_secret: get_env_var("APPFLOWY_PUBLISH_ANALYTICS_SECRET", "").into(),
+      custom_domain_dns_resolver_url: get_env_var(
+        "APPFLOWY_PUBLISH_CUSTOM_DOMAIN_DNS_RESOLVER_URL",
+        "https://cloudflare-dns.com/dns-query",
+      ),
     },
     mailer: MailerSetting {
       smtp_host: get_env_var("APPFLOWY_MAILER_SMTP_HOST", "smtp.gmail.com"),
@@ -329,4 +456,8 @@ pub struct WebsocketSetting {
   pub heartbeat_interval: u8,
   pub client_timeout: u8,
   pub min_client_version: Version,
+  /// Maximum number of observer sessions opened with the same token, across all server nodes.
+  pub observer_max_sessions: usize,
+  /// Maximum number of collabs observed at once by an observer session.
+  pub observer_max_subscriptions: usize,
 }
@@ -0,0 +1,389 @@
+use access_control::scope::AccessScope;
+use actix_service::{forward_ready, Service, Transform};
+use actix_web::dev::{Path, ResourceDef, ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
+use actix_web::web::Data;
+use actix_web::{Error, HttpMessage};
+use database_entity::dto::AFApiTokenScope;
+use futures_util::future::LocalBoxFuture;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::LazyLock;
+use uuid::Uuid;
+
+use crate::biz::authentication::api_token::{authenticate_api_token, is_api_token};
+use crate::biz::authentication::jwt::ApiTokenAuthorization;
+use crate::state::AppState;
+
+/// Routes that accept a personal API token, with the scope the token needs for each of them.
+/// Tokens are denied by default: a route that is not listed here, such as managing the tokens,
+/// publishing or deleting the account, is rejected even if the owner of the token could use it.
+static API_TOKEN_ROUTES: LazyLock<Vec<(Method, ResourceDef, AFApiTokenScope)>> =
+  LazyLock::new(|| {
+    use AFApiTokenScope::{Admin, Read, Write};
+    [
+      // workspace and pages
+      (Method::GET, "/api/workspace/{workspace_id}/folder", Read),
+      (Method::GET, "/api/workspace/{workspace_id}/recent", Read),
+      (Method::GET, "/api/workspace/{workspace_id}/favorite", Read),
+      (Method::GET, "/api/workspace/{workspace_id}/trash", Read),
+      (Method::GET, "/api/workspace/{workspace_id}/member", Read),
+      (
+        Method::GET,
+        "/api/workspace/{workspace_id}/page-view/{view_id}",
+        Read,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/page-view",
+        Write,
+      ),
+      (
+        Method::PATCH,
+        "/api/workspace/{workspace_id}/page-view/{view_id}",
+        Write,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/page-view/{view_id}/update-name",
+        Write,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/page-view/{view_id}/update-icon",
+        Write,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/page-view/{view_id}/update-extra",
+        Write,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/page-view/{view_id}/remove-icon",
+        Write,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/page-view/{view_id}/append-block",
+        Write,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/page-view/{view_id}/move",
+        Write,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/page-view/{view_id}/duplicate",
+        Write,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/page-view/{view_id}/move-to-trash",
+        Write,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/page-view/{view_id}/restore-from-trash",
+        Write,
+      ),
+      // comments
+      (
+        Method::GET,
+        "/api/workspace/{workspace_id}/page-view/{view_id}/comment-thread",
+        Read,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/page-view/{view_id}/comment-thread",
+        Write,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/page-view/{view_id}/comment-thread/{thread_id}/comment",
+        Write,
+      ),
+      // collabs
+      (
+        Method::GET,
+        "/api/workspace/{workspace_id}/collab/{object_id}",
+        Read,
+      ),
+      (
+        Method::GET,
+        "/api/workspace/v1/{workspace_id}/collab/{object_id}",
+        Read,
+      ),
+      (
+        Method::GET,
+        "/api/workspace/v1/{workspace_id}/collab/{object_id}/json",
+        Read,
+      ),
+      (
+        Method::GET,
+        "/api/workspace/v1/{workspace_id}/collab/{object_id}/blame",
+        Read,
+      ),
+      (
+        Method::GET,
+        "/api/workspace/v1/{workspace_id}/collab/{object_id}/changes",
+        Read,
+      ),
+      (
+        Method::GET,
+        "/api/workspace/v1/{workspace_id}/collab/{object_id}/snapshot",
+        Read,
+      ),
+      // databases
+      (Method::GET, "/api/workspace/{workspace_id}/database", Read),
+      (
+        Method::GET,
+        "/api/workspace/{workspace_id}/database/{database_id}/row",
+        Read,
+      ),
+      (
+        Method::GET,
+        "/api/workspace/{workspace_id}/database/{database_id}/row/updated",
+        Read,
+      ),
+      (
+        Method::GET,
+        "/api/workspace/{workspace_id}/database/{database_id}/row/detail",
+        Read,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/database/{database_id}/row/query",
+        Read,
+      ),
+      (
+        Method::GET,
+        "/api/workspace/{workspace_id}/database/{database_id}/fields",
+        Read,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/database/{database_id}/row",
+        Write,
+      ),
+      (
+        Method::PUT,
+        "/api/workspace/{workspace_id}/database/{database_id}/row",
+        Write,
+      ),
+      (
+        Method::DELETE,
+        "/api/workspace/{workspace_id}/database/{database_id}/row/{row_id}",
+        Write,
+      ),
+      (
+        Method::POST,
+        "/api/workspace/{workspace_id}/database/{database_id}/fields",
+        Write,
+      ),
+      (
+        Method::PUT,
+        "/api/workspace/{workspace_id}/database/{database_id}/fields/order",
+        Write,
+      ),
+      (
+        Method::PATCH,
+        "/api/workspace/{workspace_id}/database/{database_id}/fields/{field_id}",
+        Write,
+      ),
+      (
+        Method::DELETE,
+        "/api/workspace/{workspace_id}/database/{database_id}/fields/{field_id}",
+        Write,
+      ),
+      // search and files
+      (Method::GET, "/api/search/{workspace_id}", Read),
+      (
+        Method::GET,
+        "/api/file_storage/{workspace_id}/v1/blob/{parent_dir}/{file_id}",
+        Read,
+      ),
+      (
+        Method::PUT,
+        "/api/file_storage/{workspace_id}/v1/blob/{parent_dir}",
+        Write,
+      ),
+      // workspace administration
+      (Method::GET, "/api/webhook/{workspace_id}", Admin),
+      (Method::POST, "/api/webhook/{workspace_id}", Admin),
+      (
+        Method::PATCH,
+        "/api/webhook/{workspace_id}/{webhook_id}",
+        Admin,
+      ),
+      (
+        Method::DELETE,
+        "/api/webhook/{workspace_id}/{webhook_id}",
+        Admin,
+      ),
+      (
+        Method::GET,
+        "/api/webhook/{workspace_id}/{webhook_id}/delivery",
+        Admin,
+      ),
+      (Method::GET, "/api/audit/{workspace_id}", Admin),
+      (Method::GET, "/api/audit/{workspace_id}/export", Admin),
+    ]
+    .into_iter()
+    .map(|(method, path, scope)| (method, ResourceDef::new(path), scope))
+    .collect()
+  });
+
+/// Authenticates the requests that carry a personal API token instead of a GoTrue access token.
+/// The owner of the token is made available to the `Authorization` and `UserUuid` extractors,
+/// and the request is handled within the [AccessScope] of the token. Only the routes listed in
+/// [API_TOKEN_ROUTES] accept a token.
+pub struct ApiTokenMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for ApiTokenMiddleware
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Transform = ApiTokenMiddlewareService<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(ApiTokenMiddlewareService {
+      service: Rc::new(service),
+    }))
+  }
+}
+
+pub struct ApiTokenMiddlewareService<S> {
+  service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiTokenMiddlewareService<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let token = match get_api_token(&req) {
+      Some(token) => token,
+      None => return Box::pin(self.service.call(req)),
+    };
+    let service = self.service.clone();
+    Box::pin(async move {
+      let state = req
+        .app_data::<Data<AppState>>()
+        .cloned()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("app state not found"))?;
+      let owner = authenticate_api_token(&state.pg_pool, &token)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("Invalid or expired api token"))?;
+
+      req.extensions_mut().insert(ApiTokenAuthorization {
+        token,
+        uuid: owner.uuid,
+        email: owner.email,
+        expires_at: owner.expires_at.map(|expires_at| expires_at.timestamp()),
+      });
+      let scope = AccessScope {
+        workspace_ids: owner.workspace_ids,
+        scope: AFApiTokenScope::from(owner.scope),
+      };
+      if !is_route_allowed(req.method(), req.path(), &scope) {
+        return Err(actix_web::error::ErrorForbidden(
+          "the api token does not grant access to this route",
+        ));
+      }
+      scope.run(service.call(req)).await
+    })
+  }
+}
+
+fn get_api_token(req: &ServiceRequest) -> Option<String> {
+  let bearer = req.headers().get("Authorization")?.to_str().ok()?;
+  let (_, token) = bearer.split_once("Bearer ")?;
+  is_api_token(token).then(|| token.to_string())
+}
+
+/// Returns true if the route is listed in [API_TOKEN_ROUTES] with a scope covered by the token.
+/// The workspace in the path, if any, must also be one of the workspaces of the token.
+fn is_route_allowed(method: &Method, path: &str, scope: &AccessScope) -> bool {
+  API_TOKEN_ROUTES
+    .iter()
+    .any(|(route_method, route, required)| {
+      let mut path = Path::new(path);
+      route_method == method
+        && route.capture_match_info(&mut path)
+        && scope.scope >= *required
+        && path.get("workspace_id").is_none_or(|workspace_id| {
+          Uuid::parse_str(workspace_id)
+            .is_ok_and(|workspace_id| scope.workspace_ids.contains(&workspace_id))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn api_token_routes_are_denied_by_default() {
+    let workspace_id = Uuid::new_v4();
+    let read = AccessScope {
+      workspace_ids: vec![workspace_id],
+      scope: AFApiTokenScope::Read,
+    };
+    let folder = format!("/api/workspace/{}/folder", workspace_id);
+    assert!(is_route_allowed(&Method::GET, &folder, &read));
+    assert!(!is_route_allowed(
+      &Method::GET,
+      &format!("/api/workspace/{}/folder", Uuid::new_v4()),
+      &read
+    ));
+    assert!(!is_route_allowed(
+      &Method::POST,
+      &format!("/api/workspace/{}/page-view", workspace_id),
+      &read
+    ));
+    assert!(!is_route_allowed(
+      &Method::POST,
+      &format!("/api/workspace/{}/quick-note", workspace_id),
+      &read
+    ));
+    assert!(!is_route_allowed(&Method::DELETE, "/api/user", &read));
+    assert!(!is_route_allowed(&Method::GET, "/api/token", &read));
+
+    let write = AccessScope {
+      scope: AFApiTokenScope::Write,
+      ..read
+    };
+    assert!(is_route_allowed(
+      &Method::POST,
+      &format!("/api/workspace/{}/page-view", workspace_id),
+      &write
+    ));
+    assert!(!is_route_allowed(
+      &Method::POST,
+      &format!("/api/workspace/{}/publish", workspace_id),
+      &write
+    ));
+    assert!(!is_route_allowed(
+      &Method::GET,
+      &format!("/api/webhook/{}", workspace_id),
+      &write
+    ));
+  }
+}
@@ -1,2 +1,3 @@
+pub mod api_token_mw;
 pub mod metrics_mw;
 pub mod request_id;
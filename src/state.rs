@@ -13,25 +13,27 @@ use uuid::Uuid;
 
 use access_control::metrics::AccessControlMetrics;
 use app_error::AppError;
-use appflowy_ai_client::client::AppFlowyAIClient;
 use appflowy_collaborate::collab::cache::CollabCache;
 use appflowy_collaborate::metrics::CollabMetrics;
-use appflowy_collaborate::ws2::WsServer;
+use appflowy_collaborate::snapshot::SnapshotControl;
+use appflowy_collaborate::ws2::{ObserverQuota, WsServer};
 use appflowy_collaborate::CollabRealtimeMetrics;
 use collab_stream::awareness_gossip::AwarenessGossip;
 use collab_stream::metrics::CollabStreamMetrics;
 use collab_stream::stream_router::StreamRouter;
 use database::collab::CollabStore;
-use database::file::s3_client_impl::{AwsS3BucketClientImpl, S3BucketStorage};
+use database::file::app_bucket_client::{AppBucketClient, AppBucketStorage};
 use database::user::{select_all_uid_uuid, select_uid_from_uuid};
 use indexer::metrics::EmbeddingMetrics;
 use indexer::scheduler::IndexerScheduler;
 use snowflake::Snowflake;
 
 use crate::api::metrics::{AppFlowyWebMetrics, PublishedCollabMetrics, RequestMetrics};
+use crate::biz::ai::provider::AIProvider;
 use crate::biz::chat::metrics::AIMetrics;
 use crate::biz::pg_listener::PgListeners;
 use crate::biz::workspace::publish::PublishedCollabStore;
+use crate::biz::workspace::publish_domain::VerifiedPublishDomains;
 use crate::config::config::Config;
 use crate::mailer::AFCloudMailer;
 
@@ -51,16 +53,19 @@ pub struct AppState {
   pub collab_access_control: Arc<dyn CollabAccessControl>,
   pub workspace_access_control: Arc<dyn WorkspaceAccessControl>,
   pub realtime_access_control: Arc<dyn RealtimeAccessControl>,
-  pub bucket_storage: Arc<S3BucketStorage>,
+  pub bucket_storage: Arc<AppBucketStorage>,
   pub published_collab_store: Arc<dyn PublishedCollabStore>,
-  pub bucket_client: AwsS3BucketClientImpl,
+  pub bucket_client: AppBucketClient,
   pub pg_listeners: Arc<PgListeners>,
   pub metrics: AppMetrics,
   pub gotrue_admin: GoTrueAdmin,
   pub mailer: AFCloudMailer,
-  pub ai_client: AppFlowyAIClient,
+  pub ai_client: AIProvider,
   pub indexer_scheduler: Arc<IndexerScheduler>,
   pub ws_server: Addr<WsServer>,
+  pub observer_quota: ObserverQuota,
+  pub snapshot_control: SnapshotControl,
+  pub verified_publish_domains: VerifiedPublishDomains,
 }
 
 impl AppState {
@@ -63,6 +63,55 @@ async fn viewing_document_editing_users_test() {
   assert_num_connected_client_within_secs(&owner, &object_id, 2, 30).await;
 }
 
+#[cfg(feature = "sync-v2")]
+#[tokio::test]
+async fn workspace_presence_test() {
+  use tokio::time::timeout;
+
+  let mut owner = TestClient::new_user().await;
+  let guest = TestClient::new_user().await;
+
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &guest, AFRole::Member)
+    .await
+    .unwrap();
+  let object_id = owner
+    .create_and_edit_collab(workspace_id, CollabType::Unknown)
+    .await;
+  let owner_uid = owner.uid().await;
+  let guest_uid = guest.uid().await;
+
+  let mut presence = owner.subscribe_workspace_presence(&workspace_id).await;
+  guest.open_workspace(&workspace_id).await;
+  guest
+    .set_focused_object(&workspace_id, Some(object_id))
+    .await;
+
+  let online = timeout(
+    Duration::from_secs(30),
+    presence.wait_for(|presence| presence.users_on(&object_id) == vec![guest_uid]),
+  )
+  .await
+  .expect("guest focus should be visible to the owner")
+  .unwrap()
+  .clone();
+  assert_eq!(online.user_count(), 2);
+  assert!(online
+    .sessions
+    .iter()
+    .any(|session| session.uid == owner_uid && session.object_id.is_none()));
+
+  guest.set_focused_object(&workspace_id, None).await;
+  timeout(
+    Duration::from_secs(30),
+    presence.wait_for(|presence| presence.users_on(&object_id).is_empty()),
+  )
+  .await
+  .expect("guest focus should be cleared")
+  .unwrap();
+}
+
 async fn assert_num_connected_client_within_secs(
   client: &TestClient,
   object_id: &Uuid,
@@ -1,9 +1,22 @@
 use std::collections::HashMap;
 
+use app_error::ErrorCode;
+use appflowy_cloud::biz::collab::utils::collab_from_doc_state;
+use client_api::Client;
 use client_api_test::{generate_unique_registered_user_client, workspace_id_from_client};
+use collab::core::collab::default_client_id;
+use collab::core::origin::CollabOrigin;
+use collab::util::MapExt;
 use collab_database::entity::FieldType;
+use collab_database::views::DatabaseViews;
+use collab_entity::CollabType;
+use database_entity::dto::QueryCollabParams;
 use serde_json::json;
-use shared_entity::dto::workspace_dto::AFInsertDatabaseField;
+use shared_entity::dto::workspace_dto::{
+  AFDatabaseRowDetail, AFInsertDatabaseField, AFUpdateDatabaseField, DatabaseFilterOperator,
+  DatabaseRowFilter, DatabaseRowSort, DatabaseSortDirection, QueryDatabaseRowParams,
+};
+use uuid::Uuid;
 
 #[tokio::test]
 async fn database_row_upsert_with_doc() {
@@ -345,3 +358,241 @@ async fn database_insert_row_with_doc() {
     Some("This is a document of a database row".to_string())
   );
 }
+
+#[tokio::test]
+async fn database_query_rows_with_filter_sort_and_cursor() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let databases = c.list_databases(&workspace_id).await.unwrap();
+  let todo_db = &databases[0];
+
+  for (description, status) in [
+    ("query task a", "Done"),
+    ("query task b", "To Do"),
+    ("query task c", "Done"),
+    ("query task d", "Done"),
+  ] {
+    c.add_database_item(
+      &workspace_id,
+      &todo_db.id,
+      HashMap::from([
+        ("Description".to_string(), json!(description)),
+        ("Status".to_string(), json!(status)),
+      ]),
+      None,
+    )
+    .await
+    .unwrap();
+  }
+
+  let mut params = QueryDatabaseRowParams {
+    filter: Some(DatabaseRowFilter::And {
+      filters: vec![
+        DatabaseRowFilter::field(
+          "Description",
+          DatabaseFilterOperator::StartsWith,
+          json!("query task"),
+        ),
+        DatabaseRowFilter::field("Status", DatabaseFilterOperator::Is, json!("Done")),
+      ],
+    }),
+    sorts: vec![DatabaseRowSort {
+      field: "Description".to_string(),
+      direction: DatabaseSortDirection::Desc,
+    }],
+    limit: Some(2),
+    ..Default::default()
+  };
+  let first_page = c
+    .query_database_rows(&workspace_id, &todo_db.id, &params)
+    .await
+    .unwrap();
+  assert_eq!(first_page.total, 3);
+  let descriptions = |rows: &[AFDatabaseRowDetail]| {
+    rows
+      .iter()
+      .map(|row| row.cells["Description"].as_str().unwrap().to_string())
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(
+    descriptions(&first_page.rows),
+    vec!["query task d", "query task c"]
+  );
+
+  params.cursor = first_page.next_cursor;
+  let second_page = c
+    .query_database_rows(&workspace_id, &todo_db.id, &params)
+    .await
+    .unwrap();
+  assert_eq!(descriptions(&second_page.rows), vec!["query task a"]);
+  assert!(second_page.next_cursor.is_none());
+
+  // numeric operators are not supported by select fields
+  let params = QueryDatabaseRowParams {
+    filter: Some(DatabaseRowFilter::field(
+      "Status",
+      DatabaseFilterOperator::GreaterThan,
+      json!(1),
+    )),
+    ..Default::default()
+  };
+  let err = c
+    .query_database_rows(&workspace_id, &todo_db.id, &params)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn database_fields_update_reorder_and_delete() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let databases = c.list_databases(&workspace_id).await.unwrap();
+  let todo_db = &databases[0];
+
+  let field_id = c
+    .add_database_field(
+      &workspace_id,
+      &todo_db.id,
+      &AFInsertDatabaseField {
+        name: "Estimate".to_string(),
+        field_type: FieldType::RichText.into(),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+
+  c.update_database_field(
+    &workspace_id,
+    &todo_db.id,
+    &field_id,
+    &AFUpdateDatabaseField {
+      name: Some("Points".to_string()),
+      field_type: Some(FieldType::Number.into()),
+      ..Default::default()
+    },
+  )
+  .await
+  .unwrap();
+  let fields = c
+    .get_database_fields(&workspace_id, &todo_db.id)
+    .await
+    .unwrap();
+  let field = fields.iter().find(|field| field.id == field_id).unwrap();
+  assert_eq!(field.name, "Points");
+  assert_eq!(field.field_type, format!("{:?}", FieldType::Number));
+
+  let mut field_ids = fields
+    .iter()
+    .map(|field| field.id.clone())
+    .collect::<Vec<_>>();
+  field_ids.reverse();
+  c.reorder_database_fields(&workspace_id, &todo_db.id, field_ids.clone())
+    .await
+    .unwrap();
+  let view_field_ids = database_view_field_ids(&c, workspace_id, &todo_db.id).await;
+  assert!(!view_field_ids.is_empty());
+  for view_field_ids in view_field_ids {
+    let expected = field_ids
+      .iter()
+      .filter(|id| view_field_ids.contains(id))
+      .cloned()
+      .collect::<Vec<_>>();
+    assert_eq!(view_field_ids, expected);
+  }
+  let err = c
+    .reorder_database_fields(&workspace_id, &todo_db.id, vec!["unknown".to_string()])
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  c.delete_database_field(&workspace_id, &todo_db.id, &field_id)
+    .await
+    .unwrap();
+  let fields = c
+    .get_database_fields(&workspace_id, &todo_db.id)
+    .await
+    .unwrap();
+  assert!(fields.iter().all(|field| field.id != field_id));
+
+  // the primary field cannot be deleted
+  let primary_field = fields.iter().find(|field| field.is_primary).unwrap();
+  let err = c
+    .delete_database_field(&workspace_id, &todo_db.id, &primary_field.id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+/// Returns the ids of the fields of each view of the database, in the order they are displayed.
+async fn database_view_field_ids(
+  c: &Client,
+  workspace_id: Uuid,
+  database_id: &str,
+) -> Vec<Vec<String>> {
+  let database_id = Uuid::parse_str(database_id).unwrap();
+  let resp = c
+    .get_collab(QueryCollabParams::new(
+      database_id,
+      CollabType::Database,
+      workspace_id,
+    ))
+    .await
+    .unwrap();
+  let collab = collab_from_doc_state(
+    resp.encode_collab.doc_state.to_vec(),
+    &database_id,
+    default_client_id(),
+  )
+  .unwrap();
+  let txn = collab.transact();
+  let map_ref = collab
+    .data
+    .get_with_path(&txn, ["database", "views"])
+    .unwrap();
+  DatabaseViews::new(CollabOrigin::Empty, map_ref, None)
+    .get_all_views(&txn)
+    .into_iter()
+    .map(|view| {
+      view
+        .field_orders
+        .into_iter()
+        .map(|field_order| field_order.id)
+        .collect()
+    })
+    .collect()
+}
+
+#[tokio::test]
+async fn database_row_delete() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let databases = c.list_databases(&workspace_id).await.unwrap();
+  let todo_db = &databases[0];
+
+  let row_id = c
+    .add_database_item(
+      &workspace_id,
+      &todo_db.id,
+      HashMap::from([("Description".to_string(), json!("row to delete"))]),
+      None,
+    )
+    .await
+    .unwrap();
+  c.delete_database_row(&workspace_id, &todo_db.id, &row_id)
+    .await
+    .unwrap();
+
+  let row_ids = c
+    .list_database_row_ids(&workspace_id, &todo_db.id)
+    .await
+    .unwrap();
+  assert!(row_ids.iter().all(|row| row.id != row_id));
+
+  let err = c
+    .delete_database_row(&workspace_id, &todo_db.id, &row_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
@@ -5,6 +5,7 @@ mod database_crud;
 mod multi_devices_edit;
 mod permission_test;
 mod single_device_edit;
+mod snapshot_history_test;
 mod storage_test;
 mod stress_test;
 pub mod util;
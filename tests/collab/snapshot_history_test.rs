@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use client_api::entity::AFSnapshotMetas;
+use client_api_test::{assert_server_collab, TestClient};
+use collab_entity::CollabType;
+use serde_json::json;
+use uuid::Uuid;
+
+async fn wait_for_snapshots(
+  client: &TestClient,
+  workspace_id: &Uuid,
+  object_id: &Uuid,
+) -> AFSnapshotMetas {
+  for _ in 0..60 {
+    let metas = client
+      .api_client
+      .list_collab_snapshots(workspace_id, object_id)
+      .await
+      .unwrap();
+    if !metas.0.is_empty() {
+      return metas;
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+  }
+  panic!("no snapshot was created for {}", object_id);
+}
+
+#[tokio::test]
+async fn preview_and_restore_document_snapshot_test() {
+  let collab_type = CollabType::Document;
+  let mut client = TestClient::new_user().await;
+  let workspace_id = client.workspace_id().await;
+  let object_id = client
+    .create_and_edit_collab(workspace_id, collab_type)
+    .await;
+  client.insert_into(&object_id, "title", "first").await;
+  client.wait_object_sync_complete(&object_id).await.unwrap();
+
+  let metas = wait_for_snapshots(&client, &workspace_id, &object_id).await;
+  let snapshot_id = metas.0[0].snapshot_id;
+  let preview = client
+    .api_client
+    .get_collab_snapshot_json(&workspace_id, &object_id, snapshot_id)
+    .await
+    .unwrap();
+  assert_eq!(preview.collab["title"], json!("first"));
+
+  client.insert_into(&object_id, "title", "second").await;
+  client.insert_into(&object_id, "body", "content").await;
+  client.wait_object_sync_complete(&object_id).await.unwrap();
+  assert_server_collab(
+    workspace_id,
+    &mut client.api_client,
+    object_id,
+    &collab_type,
+    10,
+    json!({
+      "title": "second",
+      "body": "content",
+    }),
+  )
+  .await
+  .unwrap();
+
+  let backup = client
+    .api_client
+    .restore_collab_snapshot(&workspace_id, &object_id, snapshot_id, collab_type)
+    .await
+    .unwrap();
+  assert_server_collab(
+    workspace_id,
+    &mut client.api_client,
+    object_id,
+    &collab_type,
+    10,
+    json!({
+      "title": "first",
+    }),
+  )
+  .await
+  .unwrap();
+
+  // The state before the restoration is kept, so the restoration can be reverted
+  let backup_preview = client
+    .api_client
+    .get_collab_snapshot_json(&workspace_id, &object_id, backup.snapshot_id)
+    .await
+    .unwrap();
+  assert_eq!(backup_preview.collab["title"], json!("second"));
+}
+
+#[tokio::test]
+async fn snapshot_history_requires_access_test() {
+  let mut owner = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  let object_id = owner
+    .create_and_edit_collab(workspace_id, CollabType::Document)
+    .await;
+
+  let other = TestClient::new_user().await;
+  let result = other
+    .api_client
+    .list_collab_snapshots(&workspace_id, &object_id)
+    .await;
+  assert!(result.is_err());
+}
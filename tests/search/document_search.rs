@@ -1,6 +1,8 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::time::Duration;
 
+use app_error::ErrorCode;
 use appflowy_ai_client::dto::CalculateSimilarityParams;
 use client_api_test::{ai_test_enabled, collect_answer, TestClient};
 use collab::core::collab::{default_client_id, CollabOptions};
@@ -10,8 +12,10 @@ use collab_document::document::Document;
 use collab_document::importer::md_importer::MDImporter;
 use collab_entity::CollabType;
 use collab_folder::ViewLayout;
+use database_entity::dto::AFRole;
+use serde_json::json;
 use shared_entity::dto::chat_dto::{CreateChatMessageParams, CreateChatParams};
-use shared_entity::dto::search_dto::SearchResult;
+use shared_entity::dto::search_dto::{SearchMode, SearchResult};
 use tokio::time::sleep;
 use uuid::Uuid;
 use workspace_template::document::getting_started::getting_started_document_data;
@@ -171,6 +175,137 @@ async fn test_document_indexing_and_search() {
   assert!(preview.contains("Welcome to AppFlowy"));
 }
 
+#[tokio::test]
+async fn test_document_keyword_search() {
+  let mut test_client = TestClient::new_user().await;
+  let uid = test_client.uid().await;
+  let workspace_id = test_client.workspace_id().await;
+  let object_id = add_document_collab(
+    &mut test_client,
+    &workspace_id,
+    "the_five_dysfunctions_of_a_team.md",
+    "five dysfunctional",
+    false,
+    uid,
+  )
+  .await;
+
+  // keyword index is written in the background, so retry until the document shows up
+  let mut items = vec![];
+  for _ in 0..10 {
+    items = test_client
+      .api_client
+      .search_documents_with_mode(
+        &workspace_id,
+        "DecisionTech",
+        5,
+        100,
+        None,
+        SearchMode::Keyword,
+      )
+      .await
+      .unwrap();
+    if !items.is_empty() {
+      break;
+    }
+    sleep(Duration::from_millis(500)).await;
+  }
+  assert_eq!(items.len(), 1);
+  assert_eq!(items[0].object_id, object_id);
+
+  let items = test_client
+    .api_client
+    .search_documents_with_mode(
+      &workspace_id,
+      "NonExistingKeyword",
+      5,
+      100,
+      None,
+      SearchMode::Keyword,
+    )
+    .await
+    .unwrap();
+  assert!(items.is_empty());
+}
+
+#[tokio::test]
+async fn test_database_row_keyword_search() {
+  let test_client = TestClient::new_user().await;
+  let workspace_id = test_client.workspace_id().await;
+  let databases = test_client
+    .api_client
+    .list_databases(&workspace_id)
+    .await
+    .unwrap();
+  let todo_db = &databases[0];
+  let row_id = test_client
+    .api_client
+    .add_database_item(
+      &workspace_id,
+      &todo_db.id,
+      HashMap::from([(
+        String::from("Description"),
+        json!("Quarterly zeppelin inspection"),
+      )]),
+      None,
+    )
+    .await
+    .unwrap();
+
+  // rows are indexed in the background, so retry until the row shows up
+  let mut items = vec![];
+  for _ in 0..10 {
+    items = test_client
+      .api_client
+      .search_documents_with_mode(&workspace_id, "zeppelin", 5, 100, None, SearchMode::Keyword)
+      .await
+      .unwrap();
+    if !items.is_empty() {
+      break;
+    }
+    sleep(Duration::from_millis(500)).await;
+  }
+  assert_eq!(items.len(), 1);
+  assert_eq!(items[0].row_id, Some(Uuid::parse_str(&row_id).unwrap()));
+  assert!(items[0].content.contains("Quarterly zeppelin inspection"));
+  let view_id = items[0].view_id.unwrap().to_string();
+  assert!(todo_db.views.iter().any(|view| view.view_id == view_id));
+}
+
+#[tokio::test]
+async fn test_embedding_migration_requires_owner() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let err = member
+    .api_client
+    .migrate_embeddings(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  if !ai_test_enabled() {
+    return;
+  }
+  let status = owner
+    .api_client
+    .get_embedding_status(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(status.stale, 0);
+  let result = owner
+    .api_client
+    .migrate_embeddings(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(result.queued, 0);
+}
+
 async fn create_document_collab(document_id: &str, file_name: &str) -> Document {
   let file_path = PathBuf::from(format!("tests/search/asset/{}", file_name));
   let md = std::fs::read_to_string(file_path).unwrap();
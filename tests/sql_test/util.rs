@@ -1,3 +1,4 @@
+use appflowy_ai_client::dto::EmbeddingModel;
 use bytes::Bytes;
 use collab::core::collab::default_client_id;
 use collab_document::document_data::default_document_collab_data;
@@ -97,9 +98,16 @@ pub async fn upsert_test_chunks(
   chunks: Vec<AFCollabEmbeddedChunk>,
 ) {
   let mut txn = pg.begin().await.unwrap();
-  upsert_collab_embeddings(&mut txn, workspace_id, doc_id, 0, chunks.clone())
-    .await
-    .unwrap();
+  upsert_collab_embeddings(
+    &mut txn,
+    workspace_id,
+    doc_id,
+    0,
+    EmbeddingModel::default_model().name(),
+    chunks.clone(),
+  )
+  .await
+  .unwrap();
   txn.commit().await.unwrap();
 }
 
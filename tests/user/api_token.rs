@@ -0,0 +1,103 @@
+use client_api::entity::api_token_dto::CreateApiTokenParams;
+use client_api::entity::AFApiTokenScope;
+use client_api_test::generate_unique_registered_user_client;
+use reqwest::StatusCode;
+use serde_json::Value;
+
+#[tokio::test]
+async fn api_token_scope_and_revoke_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+
+  let created = c
+    .create_api_token(&CreateApiTokenParams {
+      name: "sync script".to_string(),
+      workspace_ids: vec![workspace_id],
+      scope: AFApiTokenScope::Read,
+      expires_in_days: Some(30),
+    })
+    .await
+    .unwrap();
+  let token = created.token.clone().unwrap();
+  assert!(token.starts_with(&created.token_prefix));
+  assert!(created.expires_at.is_some());
+
+  let tokens = c.list_api_tokens().await.unwrap();
+  assert_eq!(tokens.len(), 1);
+  assert!(tokens[0].token.is_none());
+
+  let http_client = reqwest::Client::new();
+  let get = |url: String| http_client.get(url).bearer_auth(&token).send();
+
+  // a read token can read the workspace it is scoped to
+  let resp = get(format!(
+    "{}/api/workspace/{}/folder",
+    c.base_url, workspace_id
+  ))
+  .await
+  .unwrap();
+  assert_eq!(resp.status(), StatusCode::OK);
+  let body: Value = resp.json().await.unwrap();
+  assert_eq!(body["code"], 0);
+
+  // managing webhooks requires an admin token
+  let resp = get(format!("{}/api/webhook/{}", c.base_url, workspace_id))
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+  // routes that are not explicitly allowed for tokens are rejected, even for their owner
+  let resp = http_client
+    .post(format!(
+      "{}/api/workspace/{}/publish",
+      c.base_url, workspace_id
+    ))
+    .bearer_auth(&token)
+    .json(&Vec::<Value>::new())
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+  let resp = http_client
+    .post(format!(
+      "{}/api/workspace/{}/quick-note",
+      c.base_url, workspace_id
+    ))
+    .bearer_auth(&token)
+    .json(&serde_json::json!({ "data": [] }))
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+  let resp = http_client
+    .delete(format!("{}/api/user", c.base_url))
+    .bearer_auth(&token)
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+  assert!(c.get_profile().await.is_ok());
+  assert!(c
+    .list_quick_notes(workspace_id, None, None, None)
+    .await
+    .unwrap()
+    .quick_notes
+    .is_empty());
+
+  // a token cannot be used to manage tokens
+  let resp = get(format!("{}/api/token", c.base_url)).await.unwrap();
+  assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+  let tokens = c.list_api_tokens().await.unwrap();
+  assert!(tokens[0].last_used_at.is_some());
+
+  c.revoke_api_token(&created.token_id).await.unwrap();
+  let resp = get(format!(
+    "{}/api/workspace/{}/folder",
+    c.base_url, workspace_id
+  ))
+  .await
+  .unwrap();
+  assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+  assert!(c.list_api_tokens().await.unwrap().is_empty());
+}
@@ -1,3 +1,4 @@
+mod api_token;
 mod delete;
 mod image;
 mod refresh;
@@ -1,2 +1,4 @@
 mod actor_test;
 mod conn_test;
+#[cfg(feature = "sync-v2")]
+mod observer_test;
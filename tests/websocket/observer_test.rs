@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use appflowy_proto::{ClientMessage, ServerMessage};
+use client_api::entity::guest_dto::{RevokeSharedViewAccessRequest, ShareViewWithGuestRequest};
+use client_api::entity::AFAccessLevel;
+use client_api_test::{generate_unique_registered_user_client, TestClient, LOCALHOST_WS_V2};
+use collab_entity::CollabType;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use shared_entity::dto::workspace_dto::AppendBlockToPageParams;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+#[tokio::test]
+async fn observer_receives_only_subscribed_collabs_test() {
+  let mut owner = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  let observed_id = owner
+    .create_and_edit_collab(workspace_id, CollabType::Unknown)
+    .await;
+  let other_id = owner
+    .create_and_edit_collab(workspace_id, CollabType::Unknown)
+    .await;
+
+  let url = format!(
+    "{}/{}?mode=observer&token={}",
+    LOCALHOST_WS_V2.as_ref(),
+    workspace_id,
+    owner.api_client.access_token().unwrap()
+  );
+  let (mut observer, _) = connect_async(url).await.unwrap();
+  let subscribe = ClientMessage::Subscribe {
+    object_id: observed_id,
+    collab_type: CollabType::Unknown,
+  };
+  observer
+    .send(Message::Binary(subscribe.into_bytes().unwrap().into()))
+    .await
+    .unwrap();
+
+  let state = next_server_message(&mut observer).await;
+  assert!(
+    matches!(state, ServerMessage::Update { object_id, .. } if object_id == observed_id),
+    "observer should receive the state of the observed collab, got {:?}",
+    state
+  );
+
+  owner.insert_into(&other_id, "title", "ignored").await;
+  owner.insert_into(&observed_id, "title", "observed").await;
+  let update = next_server_message(&mut observer).await;
+  assert!(
+    matches!(update, ServerMessage::Update { object_id, .. } if object_id == observed_id),
+    "observer should only receive the updates of the observed collab, got {:?}",
+    update
+  );
+
+  // observers are read-only
+  let update = ClientMessage::Update {
+    object_id: observed_id,
+    collab_type: CollabType::Unknown,
+    flags: Default::default(),
+    update: vec![0, 0],
+  };
+  observer
+    .send(Message::Binary(update.into_bytes().unwrap().into()))
+    .await
+    .unwrap();
+  let close_code = loop {
+    match timeout(Duration::from_secs(30), observer.next()).await {
+      Ok(Some(Ok(Message::Close(frame)))) => break frame.map(|frame| frame.code),
+      Ok(Some(Ok(_))) => continue,
+      _ => panic!("observer sending updates should be disconnected"),
+    }
+  };
+  assert_eq!(close_code, Some(CloseCode::Policy));
+}
+
+#[tokio::test]
+async fn observer_stops_receiving_revoked_collab_test() {
+  let (owner_client, _) = generate_unique_registered_user_client().await;
+  let workspace_id = owner_client.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = owner_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let view_id = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .and_then(|space| space.children.iter().find(|v| v.name == "Getting started"))
+    .unwrap()
+    .view_id;
+  let (guest_client, guest) = generate_unique_registered_user_client().await;
+  owner_client
+    .share_view_with_guest(
+      &workspace_id,
+      &ShareViewWithGuestRequest {
+        view_id,
+        emails: vec![guest.email.clone()],
+        access_level: AFAccessLevel::ReadOnly,
+        auto_confirm: true,
+      },
+    )
+    .await
+    .unwrap();
+
+  let url = format!(
+    "{}/{}?mode=observer&token={}",
+    LOCALHOST_WS_V2.as_ref(),
+    workspace_id,
+    guest_client.access_token().unwrap()
+  );
+  let (mut observer, _) = connect_async(url).await.unwrap();
+  let subscribe = ClientMessage::Subscribe {
+    object_id: view_id,
+    collab_type: CollabType::Document,
+  };
+  observer
+    .send(Message::Binary(subscribe.into_bytes().unwrap().into()))
+    .await
+    .unwrap();
+  let state = next_server_message(&mut observer).await;
+  assert!(
+    matches!(state, ServerMessage::Update { object_id, .. } if object_id == view_id),
+    "observer should receive the state of the shared collab, got {:?}",
+    state
+  );
+
+  owner_client
+    .revoke_shared_view_access(
+      &workspace_id,
+      &view_id,
+      &RevokeSharedViewAccessRequest {
+        emails: vec![guest.email.clone()],
+      },
+    )
+    .await
+    .unwrap();
+  let revoked = next_server_message(&mut observer).await;
+  assert!(
+    matches!(
+      revoked,
+      ServerMessage::AccessChanges { object_id, can_read: false, .. } if object_id == view_id
+    ),
+    "observer should be told that the collab was revoked, got {:?}",
+    revoked
+  );
+
+  owner_client
+    .append_block_to_page(
+      workspace_id,
+      &view_id,
+      &AppendBlockToPageParams {
+        blocks: vec![json!({
+          "type": "paragraph",
+          "data": {
+            "delta": [{ "insert": "Not for the guest" }]
+          }
+        })],
+      },
+    )
+    .await
+    .unwrap();
+  let result = timeout(Duration::from_secs(5), async {
+    loop {
+      if let ServerMessage::Update { object_id, .. } = next_server_message(&mut observer).await {
+        if object_id == view_id {
+          break;
+        }
+      }
+    }
+  })
+  .await;
+  assert!(
+    result.is_err(),
+    "observer should not receive the updates of a revoked collab"
+  );
+}
+
+async fn next_server_message(
+  conn: &mut WebSocketStream<MaybeTlsStream<TcpStream>>,
+) -> ServerMessage {
+  loop {
+    let message = timeout(Duration::from_secs(30), conn.next())
+      .await
+      .expect("timeout waiting for server message")
+      .unwrap()
+      .unwrap();
+    if let Message::Binary(bytes) = message {
+      return ServerMessage::from_bytes(&bytes).unwrap();
+    }
+  }
+}
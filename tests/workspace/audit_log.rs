@@ -0,0 +1,85 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::{AFRole, AFWorkspaceSettingsChange};
+use shared_entity::dto::audit_dto::{AuditAction, QueryAuditLogParams};
+
+#[tokio::test]
+async fn workspace_audit_log_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .update_workspace_settings(
+      workspace_id.to_string(),
+      &AFWorkspaceSettingsChange::new().disable_search_indexing(true),
+    )
+    .await
+    .unwrap();
+
+  let entries = owner
+    .api_client
+    .list_workspace_audit_logs(&workspace_id, &QueryAuditLogParams::default())
+    .await
+    .unwrap();
+  let owner_uid = owner.uid().await;
+  let invited = entries
+    .iter()
+    .find(|entry| entry.action == AuditAction::MemberInvited.as_str())
+    .unwrap();
+  assert_eq!(invited.actor_uid, Some(owner_uid));
+  assert_eq!(
+    invited.target.as_deref(),
+    Some(member.email().await.as_str())
+  );
+  let settings_changed = &entries[0];
+  assert_eq!(
+    settings_changed.action,
+    AuditAction::WorkspaceSettingsChanged.as_str()
+  );
+  assert!(settings_changed.before.is_some());
+  assert!(settings_changed.after.is_some());
+
+  let entries = owner
+    .api_client
+    .list_workspace_audit_logs(
+      &workspace_id,
+      &QueryAuditLogParams {
+        action: Some(AuditAction::MemberInvited),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(entries.len(), 1);
+
+  let csv = owner
+    .api_client
+    .export_workspace_audit_logs_csv(&workspace_id, &QueryAuditLogParams::default())
+    .await
+    .unwrap();
+  let mut lines = csv.lines();
+  assert!(lines
+    .next()
+    .unwrap()
+    .starts_with("created_at,action,actor_uid"));
+  assert!(csv.contains(AuditAction::WorkspaceSettingsChanged.as_str()));
+
+  let err = member
+    .api_client
+    .list_workspace_audit_logs(&workspace_id, &QueryAuditLogParams::default())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = member
+    .api_client
+    .export_workspace_audit_logs_csv(&workspace_id, &QueryAuditLogParams::default())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
@@ -0,0 +1,88 @@
+use app_error::ErrorCode;
+use async_zip::base::read::mem::ZipFileReader;
+use client_api_test::{generate_unique_registered_user_client, TestClient};
+use std::time::Duration;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn export_workspace_to_zip_test() {
+  let client = TestClient::new_user().await;
+  let workspace_id = client.workspace_id().await;
+
+  let task_id: Uuid = client
+    .api_client
+    .create_export(&workspace_id)
+    .await
+    .unwrap()
+    .task_id
+    .parse()
+    .unwrap();
+  let tasks = client
+    .api_client
+    .get_export_list(&workspace_id)
+    .await
+    .unwrap()
+    .tasks;
+  assert_eq!(tasks.len(), 1);
+  assert_eq!(tasks[0].task_id, task_id.to_string());
+
+  wait_until_export_task_complete(&client, &workspace_id).await;
+
+  let download_url = client
+    .api_client
+    .get_export_download_url(&workspace_id, &task_id)
+    .await
+    .unwrap()
+    .download_url;
+  let bytes = reqwest::get(&download_url)
+    .await
+    .unwrap()
+    .bytes()
+    .await
+    .unwrap();
+  let reader = ZipFileReader::new(bytes.to_vec()).await.unwrap();
+  let file_names = reader
+    .file()
+    .entries()
+    .iter()
+    .map(|entry| entry.filename().as_str().unwrap().to_string())
+    .collect::<Vec<_>>();
+  assert!(file_names.contains(&"manifest.json".to_string()));
+  // The default workspace contains the getting started document
+  assert!(
+    file_names.iter().any(|name| name.ends_with(".md")),
+    "{:?}",
+    file_names
+  );
+}
+
+#[tokio::test]
+async fn export_workspace_without_membership_test() {
+  let owner = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+
+  let (other_client, _) = generate_unique_registered_user_client().await;
+  let err = other_client.create_export(&workspace_id).await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+async fn wait_until_export_task_complete(client: &TestClient, workspace_id: &Uuid) {
+  let mut status = 0;
+  for _ in 0..12 {
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    let tasks = client
+      .api_client
+      .get_export_list(workspace_id)
+      .await
+      .unwrap()
+      .tasks;
+    status = tasks[0].status;
+    if status != 0 {
+      break;
+    }
+  }
+  assert_eq!(
+    status, 1,
+    "The export task was not completed within the expected time."
+  );
+}
@@ -0,0 +1,160 @@
+use client_api::entity::guest_dto::{RevokeSharedViewAccessRequest, ShareViewWithGuestRequest};
+use client_api::entity::{AFAccessLevel, AFRole};
+use client_api_test::generate_unique_registered_user_client;
+use shared_entity::dto::workspace_dto::{CreatePageParams, MovePageParams, ViewLayout};
+
+#[tokio::test]
+async fn share_view_with_guest_and_revoke_access_test() {
+  let (owner_client, _) = generate_unique_registered_user_client().await;
+  let workspaces = owner_client.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id;
+  let folder_view = owner_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let view_id = general_space
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap()
+    .view_id;
+
+  let (guest_client, guest) = generate_unique_registered_user_client().await;
+  owner_client
+    .share_view_with_guest(
+      &workspace_id,
+      &ShareViewWithGuestRequest {
+        view_id,
+        emails: vec![guest.email.clone()],
+        access_level: AFAccessLevel::ReadAndComment,
+        auto_confirm: true,
+      },
+    )
+    .await
+    .unwrap();
+
+  let shared_views = guest_client.get_shared_views(&workspace_id).await.unwrap();
+  assert_eq!(shared_views.shared_views.len(), 1);
+  assert_eq!(shared_views.shared_views[0].view_id, view_id);
+  assert_eq!(
+    shared_views.shared_views[0].access_level,
+    AFAccessLevel::ReadAndComment
+  );
+
+  // the guests of the view are listed along with the guests of its ancestors
+  let details = owner_client
+    .get_shared_view_details(&workspace_id, &view_id, &[general_space.view_id])
+    .await
+    .unwrap();
+  assert_eq!(details.shared_with.len(), 1);
+  assert_eq!(details.shared_with[0].email, guest.email.to_lowercase());
+  assert_eq!(details.shared_with[0].role, AFRole::Guest);
+  assert!(!details.shared_with[0].pending_invitation);
+
+  // guests are not allowed to manage the access of other users
+  let resp = guest_client
+    .revoke_shared_view_access(
+      &workspace_id,
+      &view_id,
+      &RevokeSharedViewAccessRequest {
+        emails: vec![guest.email.clone()],
+      },
+    )
+    .await;
+  assert!(resp.is_err());
+
+  owner_client
+    .revoke_shared_view_access(
+      &workspace_id,
+      &view_id,
+      &RevokeSharedViewAccessRequest {
+        emails: vec![guest.email.clone()],
+      },
+    )
+    .await
+    .unwrap();
+  let shared_views = guest_client.get_shared_views(&workspace_id).await.unwrap();
+  assert!(shared_views.shared_views.is_empty());
+}
+
+#[tokio::test]
+async fn pages_inherit_guest_access_of_their_parent_test() {
+  let (owner_client, _) = generate_unique_registered_user_client().await;
+  let workspaces = owner_client.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id;
+  let folder_view = owner_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let shared_view_id = general_space
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap()
+    .view_id;
+
+  let (_guest_client, guest) = generate_unique_registered_user_client().await;
+  owner_client
+    .share_view_with_guest(
+      &workspace_id,
+      &ShareViewWithGuestRequest {
+        view_id: shared_view_id,
+        emails: vec![guest.email.clone()],
+        access_level: AFAccessLevel::ReadOnly,
+        auto_confirm: true,
+      },
+    )
+    .await
+    .unwrap();
+
+  let page = owner_client
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: shared_view_id,
+        layout: ViewLayout::Document,
+        name: Some("Child page".to_string()),
+        page_data: None,
+        view_id: None,
+        collab_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  // no ancestors are given, so the guest is only listed if the page inherited the grant
+  let details = owner_client
+    .get_shared_view_details(&workspace_id, &page.view_id, &[])
+    .await
+    .unwrap();
+  assert_eq!(details.shared_with.len(), 1);
+  assert_eq!(details.shared_with[0].email, guest.email.to_lowercase());
+  assert_eq!(details.shared_with[0].access_level, AFAccessLevel::ReadOnly);
+
+  // moving the page out of the shared view drops the inherited grant
+  owner_client
+    .move_workspace_page_view(
+      workspace_id,
+      &page.view_id,
+      &MovePageParams {
+        new_parent_view_id: general_space.view_id.to_string(),
+        prev_view_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  let details = owner_client
+    .get_shared_view_details(&workspace_id, &page.view_id, &[])
+    .await
+    .unwrap();
+  assert!(details.shared_with.is_empty());
+}
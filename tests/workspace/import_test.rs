@@ -5,6 +5,7 @@ use collab_folder::ViewLayout;
 
 use collab_database::database::get_inline_view_id;
 use collab_document::blocks::BlockType;
+use database_entity::dto::ImportSource;
 use std::path::PathBuf;
 use std::time::Duration;
 use uuid::Uuid;
@@ -161,6 +162,86 @@ async fn import_project_and_task_zip_test() {
   }
 }
 
+#[tokio::test]
+async fn import_markdown_zip_test() {
+  let (client, imported_workspace_id) =
+    import_file_until_complete("markdown_notes.zip", ImportSource::Markdown).await;
+  let uid = client.uid().await;
+  let folder = client.get_folder(imported_workspace_id).await;
+  let space_views = folder.get_views_belong_to(&imported_workspace_id.to_string(), uid);
+  assert_eq!(space_views.len(), 1);
+  assert_eq!(space_views[0].name, "Imported Space");
+  assert!(space_views[0].space_info().is_some());
+
+  // The folder structure becomes the view hierarchy. The Guide directory is nested under Guide.md
+  let views = folder.get_views_belong_to(&space_views[0].id, uid);
+  let names = views
+    .iter()
+    .map(|view| view.name.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(names, vec!["Guide", "Welcome"]);
+  let guide_children = folder.get_views_belong_to(&views[0].id, uid);
+  assert_eq!(guide_children.len(), 1);
+  assert_eq!(guide_children[0].name, "Install");
+  assert_eq!(guide_children[0].layout, ViewLayout::Document);
+
+  // The relative image is uploaded as a file of the document
+  let welcome_view_id = views[1].id.clone();
+  let document = client
+    .get_document(imported_workspace_id, welcome_view_id.parse().unwrap())
+    .await;
+  let page_block_id = document.get_page_id().unwrap();
+  let image_url = document
+    .get_block_children_ids(&page_block_id)
+    .iter()
+    .filter_map(|block_id| document.get_block_data(block_id))
+    .find(|(block_type, _)| matches!(block_type, BlockType::Image))
+    .and_then(|(_, data)| data.get(URL_FIELD)?.as_str().map(|url| url.to_string()))
+    .expect("image block not found");
+  let prefix = format!(
+    "{}/api/file_storage/{}/v1/blob/{}/",
+    client.api_client.base_url, imported_workspace_id, welcome_view_id
+  );
+  let file_id = image_url
+    .strip_prefix(&prefix)
+    .expect("image is not uploaded to the file storage");
+  client
+    .api_client
+    .get_blob_v1(&imported_workspace_id, &welcome_view_id, file_id)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn import_csv_test() {
+  let (client, imported_workspace_id) =
+    import_file_until_complete("contacts.csv", ImportSource::Csv).await;
+  let uid = client.uid().await;
+  let folder = client.get_folder(imported_workspace_id).await;
+  let workspace_database = client.get_workspace_database(imported_workspace_id).await;
+  let space_views = folder.get_views_belong_to(&imported_workspace_id.to_string(), uid);
+  assert_eq!(space_views.len(), 1);
+
+  let views = folder.get_views_belong_to(&space_views[0].id, uid);
+  assert_eq!(views.len(), 1);
+  assert_eq!(views[0].name, "contacts");
+  assert_eq!(views[0].layout, ViewLayout::Grid);
+
+  let database_id = workspace_database
+    .get_database_meta_with_view_id(&views[0].id)
+    .unwrap()
+    .database_id
+    .clone();
+  let database = client
+    .get_database(imported_workspace_id, &database_id)
+    .await;
+  let inline_view_id = get_inline_view_id(&database).unwrap();
+  let fields = database.get_fields_in_view(&inline_view_id, None);
+  let rows = database.collect_all_rows(false).await;
+  assert_eq!(fields.len(), 3);
+  assert_eq!(rows.len(), 3);
+}
+
 #[tokio::test]
 async fn imported_workspace_do_not_become_latest_visit_workspace_test() {
   let client = TestClient::new_user().await;
@@ -214,13 +295,21 @@ async fn upload_file(
 
 // upload_after_secs: simulate the delay of uploading the file
 async fn import_notion_zip_until_complete(name: &str) -> (TestClient, Uuid) {
+  import_file_until_complete(name, ImportSource::Notion).await
+}
+
+async fn import_file_until_complete(name: &str, source: ImportSource) -> (TestClient, Uuid) {
   let client = TestClient::new_user().await;
 
   // Uncomment the following lines to use the predicated upload file API.
   // Currently, we use `upload_file` to send a file to appflowy_worker, which then
   // processes the upload task.
   let file_path = PathBuf::from(format!("tests/workspace/asset/{name}"));
-  client.api_client.import_file(&file_path).await.unwrap();
+  client
+    .api_client
+    .import_file_with_source(&file_path, source)
+    .await
+    .unwrap();
   // upload_file(&client, name, None).await.unwrap();
 
   let default_workspace_id = client.workspace_id().await;
@@ -435,3 +435,89 @@ async fn workspace_member_through_user_id() {
 
   assert_ne!(owner_member.role, member_1_member.role);
 }
+
+#[cfg(feature = "sync-v2")]
+#[tokio::test]
+async fn workspace_member_changes_are_notified_test() {
+  use appflowy_proto::{MemberChange, WorkspaceNotification};
+  use shared_entity::dto::workspace_dto::PatchWorkspaceParam;
+  use std::time::Duration;
+  use tokio::sync::broadcast::Receiver;
+
+  async fn next_notification(
+    notifications: &mut Receiver<WorkspaceNotification>,
+    predicate: impl Fn(&WorkspaceNotification) -> bool,
+  ) -> WorkspaceNotification {
+    tokio::time::timeout(Duration::from_secs(30), async {
+      loop {
+        let notification = notifications.recv().await.unwrap();
+        if predicate(&notification) {
+          return notification;
+        }
+      }
+    })
+    .await
+    .expect("Timed out waiting for workspace notification")
+  }
+
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let mut notifications = owner.subscribe_workspace_notification(&workspace_id);
+
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let member_uid = member.uid().await;
+  let notification = next_notification(&mut notifications, |notification| {
+    matches!(notification, WorkspaceNotification::MemberChanged { .. })
+  })
+  .await;
+  assert!(matches!(
+    notification,
+    WorkspaceNotification::MemberChanged {
+      uid,
+      change: MemberChange::Added,
+      role: Some(role),
+    } if uid == member_uid && role == i32::from(AFRole::Member)
+  ));
+
+  owner
+    .api_client
+    .patch_workspace(PatchWorkspaceParam {
+      workspace_id,
+      workspace_name: Some("renamed workspace".to_string()),
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+  let notification = next_notification(&mut notifications, |notification| {
+    matches!(notification, WorkspaceNotification::WorkspaceChanged { .. })
+  })
+  .await;
+  assert!(matches!(
+    notification,
+    WorkspaceNotification::WorkspaceChanged { name: Some(name), icon: None } if name == "renamed workspace"
+  ));
+
+  owner
+    .api_client
+    .remove_workspace_members(&workspace_id, vec![member.email().await])
+    .await
+    .unwrap();
+  let notification = next_notification(&mut notifications, |notification| {
+    matches!(
+      notification,
+      WorkspaceNotification::MemberChanged {
+        change: MemberChange::Removed,
+        ..
+      }
+    )
+  })
+  .await;
+  assert!(matches!(
+    notification,
+    WorkspaceNotification::MemberChanged { uid, role: None, .. } if uid == member_uid
+  ));
+}
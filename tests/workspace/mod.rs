@@ -1,6 +1,9 @@
 mod access_request;
+mod audit_log;
 mod default_user_workspace;
 mod edit_workspace;
+mod export_test;
+mod guest_sharing;
 mod import_test;
 mod invitation_crud;
 mod join_workspace;
@@ -11,6 +14,8 @@ mod publish;
 mod published_data;
 mod quick_note;
 mod template;
+mod view_comment;
+mod webhook;
 mod workspace_crud;
 mod workspace_folder;
 mod workspace_settings;
@@ -1,5 +1,6 @@
 use std::{collections::HashSet, time::Duration};
 
+use app_error::ErrorCode;
 use client_api::entity::{QueryCollab, QueryCollabParams};
 use client_api_test::{
   generate_unique_registered_user, generate_unique_registered_user_client, TestClient,
@@ -10,10 +11,10 @@ use collab_folder::{CollabOrigin, Folder};
 use serde_json::{json, Value};
 use shared_entity::dto::workspace_dto::{
   AddRecentPagesParams, AppendBlockToPageParams, CreateFolderViewParams,
-  CreatePageDatabaseViewParams, CreatePageParams, CreateSpaceParams, DuplicatePageParams,
-  FavoritePageParams, IconType, MovePageParams, PublishPageParams, SpacePermission,
-  UpdatePageExtraParams, UpdatePageIconParams, UpdatePageNameParams, UpdatePageParams,
-  UpdateSpaceParams, ViewIcon, ViewLayout,
+  CreatePageDatabaseViewParams, CreatePageParams, CreateSpaceParams, DocumentBlockChangeKind,
+  DocumentChangesQuery, DuplicatePageParams, FavoritePageParams, IconType, MovePageParams,
+  PublishPageParams, SpacePermission, UpdatePageExtraParams, UpdatePageIconParams,
+  UpdatePageNameParams, UpdatePageParams, UpdateSpaceParams, ViewIcon, ViewLayout,
 };
 use tokio::time::sleep;
 use uuid::Uuid;
@@ -343,6 +344,160 @@ async fn append_block_to_page() {
   .unwrap();
 }
 
+#[tokio::test]
+async fn appended_block_is_attributed_to_its_author() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let uid = c.get_profile().await.unwrap().uid;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let getting_started_view_id = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .and_then(|space| space.children.iter().find(|v| v.name == "Getting started"))
+    .unwrap()
+    .view_id;
+  c.append_block_to_page(
+    workspace_id,
+    &getting_started_view_id,
+    &AppendBlockToPageParams {
+      blocks: vec![json!({
+        "type": "paragraph",
+        "data": {
+          "delta": [{ "insert": "Written by the test user" }]
+        }
+      })],
+    },
+  )
+  .await
+  .unwrap();
+
+  // Authors are recorded when the workspace updates are snapshotted, about once a minute
+  for _ in 0..90 {
+    let blame = c
+      .get_document_blame(&workspace_id, &getting_started_view_id)
+      .await
+      .unwrap();
+    let last_block = blame.blocks.last().unwrap();
+    if last_block.last_edited_by.is_some() {
+      assert_eq!(last_block.block_type, "paragraph");
+      assert_eq!(last_block.last_edited_by, Some(uid));
+      assert!(last_block.last_edited_at.is_some());
+      // The template blocks were not written by anyone
+      assert!(blame.blocks[0].last_edited_by.is_none());
+      return;
+    }
+    sleep(Duration::from_secs(1)).await;
+  }
+  panic!("the appended block was not attributed");
+}
+
+#[tokio::test]
+async fn appended_block_is_listed_in_document_changes() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let getting_started_view_id = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .and_then(|space| space.children.iter().find(|v| v.name == "Getting started"))
+    .unwrap()
+    .view_id;
+  let append_paragraph = |text: &'static str| {
+    let c = &c;
+    async move {
+      c.append_block_to_page(
+        workspace_id,
+        &getting_started_view_id,
+        &AppendBlockToPageParams {
+          blocks: vec![json!({
+            "type": "paragraph",
+            "data": {
+              "delta": [{ "insert": text }]
+            }
+          })],
+        },
+      )
+      .await
+    }
+  };
+
+  // The document is younger than the start of the period, so its history is unknown
+  let changes = c
+    .get_document_changes(
+      &workspace_id,
+      &getting_started_view_id,
+      &DocumentChangesQuery {
+        since: chrono::Utc::now() - chrono::Duration::hours(1),
+        until: None,
+      },
+    )
+    .await
+    .unwrap();
+  assert!(!changes.history_available);
+  assert!(changes.changes.is_empty());
+
+  // The first snapshot is taken when the updates of the workspace are snapshotted
+  append_paragraph("A first paragraph").await.unwrap();
+  let mut has_snapshot = false;
+  for _ in 0..90 {
+    let snapshots = c
+      .list_collab_snapshots(&workspace_id, &getting_started_view_id)
+      .await
+      .unwrap();
+    if !snapshots.0.is_empty() {
+      has_snapshot = true;
+      break;
+    }
+    sleep(Duration::from_secs(1)).await;
+  }
+  assert!(has_snapshot, "no snapshot of the document was taken");
+
+  let since = chrono::Utc::now();
+  append_paragraph("A brand new paragraph").await.unwrap();
+  let changes = c
+    .get_document_changes(
+      &workspace_id,
+      &getting_started_view_id,
+      &DocumentChangesQuery { since, until: None },
+    )
+    .await
+    .unwrap();
+  assert!(changes.history_available);
+  assert!(changes.from.is_some());
+  let appended = changes
+    .changes
+    .iter()
+    .find(|change| change.text.as_deref() == Some("A brand new paragraph"))
+    .unwrap();
+  assert_eq!(appended.kind, DocumentBlockChangeKind::Inserted);
+  assert_eq!(appended.block_type, "paragraph");
+  assert!(changes
+    .changes
+    .iter()
+    .all(|change| change.text.as_deref() != Some("A first paragraph")));
+
+  let err = c
+    .get_document_changes(
+      &workspace_id,
+      &getting_started_view_id,
+      &DocumentChangesQuery {
+        since,
+        until: Some(since - chrono::Duration::minutes(1)),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
 #[tokio::test]
 async fn create_new_chat_page() {
   let (c, _user) = generate_unique_registered_user_client().await;
@@ -1063,6 +1218,8 @@ async fn publish_page() {
           visible_database_view_ids: None,
           comments_enabled: None,
           duplicate_enabled: None,
+          password: None,
+          expires_at: None,
         },
       )
       .await
@@ -22,7 +22,8 @@ use collab_folder::{CollabOrigin, Folder};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use shared_entity::dto::auth_dto::UpdateUserParams;
-use shared_entity::dto::publish_dto::PublishDatabaseData;
+use shared_entity::dto::publish_dto::{PublishDatabaseData, PublishedAnalyticsQuery};
+use shared_entity::dto::workspace_dto::PublishedDuplicate;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::thread::sleep;
@@ -132,6 +133,8 @@ async fn test_publish_doc() {
           data: "yrs_encoded_data_1".as_bytes(),
           comments_enabled: true,
           duplicate_enabled: true,
+          password: None,
+          expires_at: None,
         }],
       )
       .await
@@ -157,6 +160,8 @@ async fn test_publish_doc() {
           data: "yrs_encoded_data_1".as_bytes(),
           comments_enabled: true,
           duplicate_enabled: true,
+          password: None,
+          expires_at: None,
         }],
       )
       .await
@@ -184,6 +189,8 @@ async fn test_publish_doc() {
         data: "yrs_encoded_data_1".as_bytes(),
         comments_enabled: true,
         duplicate_enabled: true,
+        password: None,
+        expires_at: None,
       },
       PublishCollabItem {
         meta: PublishCollabMetadata {
@@ -196,6 +203,8 @@ async fn test_publish_doc() {
         data: "yrs_encoded_data_2".as_bytes(),
         comments_enabled: true,
         duplicate_enabled: true,
+        password: None,
+        expires_at: None,
       },
     ],
   )
@@ -217,6 +226,8 @@ async fn test_publish_doc() {
         data: "some_other_yrs_data".as_bytes(),
         comments_enabled: true,
         duplicate_enabled: true,
+        password: None,
+        expires_at: None,
       }],
     )
     .await
@@ -293,6 +304,8 @@ async fn test_publish_doc() {
         data: "yrs_encoded_data_3".as_bytes(),
         comments_enabled: true,
         duplicate_enabled: true,
+        password: None,
+        expires_at: None,
       },
       PublishCollabItem {
         meta: PublishCollabMetadata {
@@ -305,6 +318,8 @@ async fn test_publish_doc() {
         data: "yrs_encoded_data_4".as_bytes(),
         comments_enabled: true,
         duplicate_enabled: true,
+        password: None,
+        expires_at: None,
       },
     ],
   )
@@ -377,6 +392,9 @@ async fn test_publish_doc() {
           publish_name: Some(publish_name_2.to_string()),
           comments_enabled: None,
           duplicate_enabled: None,
+          password: None,
+          expires_at: None,
+          clear_expires_at: false,
         }],
       )
       .await
@@ -393,6 +411,9 @@ async fn test_publish_doc() {
         publish_name: Some(new_publish_name_1.to_string()),
         comments_enabled: None,
         duplicate_enabled: None,
+        password: None,
+        expires_at: None,
+        clear_expires_at: false,
       }],
     )
     .await
@@ -422,6 +443,9 @@ async fn test_publish_doc() {
         publish_name: Some(publish_name_1.to_string()),
         comments_enabled: None,
         duplicate_enabled: None,
+        password: None,
+        expires_at: None,
+        clear_expires_at: false,
       }],
     )
     .await
@@ -507,6 +531,8 @@ async fn test_publish_comments() {
         data: "yrs_encoded_data_1".as_bytes(),
         comments_enabled: true,
         duplicate_enabled: true,
+        password: None,
+        expires_at: None,
       }],
     )
     .await
@@ -739,6 +765,8 @@ async fn test_excessive_comment_length() {
         data: "yrs_encoded_data_1".as_bytes(),
         comments_enabled: true,
         duplicate_enabled: true,
+        password: None,
+        expires_at: None,
       }],
     )
     .await
@@ -777,6 +805,8 @@ async fn test_publish_reactions() {
         data: "yrs_encoded_data_1".as_bytes(),
         comments_enabled: true,
         duplicate_enabled: true,
+        password: None,
+        expires_at: None,
       }],
     )
     .await
@@ -909,6 +939,8 @@ async fn test_publish_load_test() {
       data: vec![0; 100_000],
       comments_enabled: true,
       duplicate_enabled: true,
+      password: None,
+      expires_at: None,
     })
     .collect();
 
@@ -951,6 +983,8 @@ async fn workspace_member_publish_unpublish() {
         data: "yrs_encoded_data_1".as_bytes(),
         comments_enabled: true,
         duplicate_enabled: true,
+        password: None,
+        expires_at: None,
       }],
     )
     .await
@@ -1762,6 +1796,8 @@ async fn test_republish_doc() {
       data: "yrs_encoded_data_1".as_bytes(),
       comments_enabled: true,
       duplicate_enabled: true,
+      password: None,
+      expires_at: None,
     }],
   )
   .await
@@ -1813,6 +1849,8 @@ async fn test_republish_doc() {
         data: "yrs_encoded_data_2".as_bytes(),
         comments_enabled: true,
         duplicate_enabled: true,
+        password: None,
+        expires_at: None,
       }],
     )
     .await
@@ -1860,6 +1898,8 @@ async fn test_republish_patch() {
       data: "yrs_encoded_data_1".as_bytes(),
       comments_enabled: true,
       duplicate_enabled: true,
+      password: None,
+      expires_at: None,
     }],
   )
   .await
@@ -1886,6 +1926,8 @@ async fn test_republish_patch() {
       data: "yrs_encoded_data_1".as_bytes(),
       comments_enabled: true,
       duplicate_enabled: true,
+      password: None,
+      expires_at: None,
     }],
   )
   .await
@@ -1900,8 +1942,395 @@ async fn test_republish_patch() {
       publish_name: Some(publish_name.to_string()),
       comments_enabled: None,
       duplicate_enabled: None,
+      password: None,
+      expires_at: None,
+      clear_expires_at: false,
     }],
   )
   .await
   .unwrap();
 }
+
+#[tokio::test]
+async fn test_publish_password_and_expiry() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace(&c).await;
+  let my_namespace = Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, my_namespace.clone())
+    .await
+    .unwrap();
+
+  let publish_name = "protected-page";
+  let view_id = Uuid::new_v4();
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: publish_name.to_string(),
+        metadata: MyCustomMetadata {
+          title: "my_title".to_string(),
+        },
+      },
+      data: "yrs_encoded_data".as_bytes(),
+      comments_enabled: true,
+      duplicate_enabled: true,
+      password: Some("secret".to_string()),
+      expires_at: None,
+    }],
+  )
+  .await
+  .unwrap();
+
+  let info = c.get_published_collab_info(&view_id).await.unwrap();
+  assert!(info.password_protected);
+
+  // Guest cannot read the page without unlocking it
+  let guest_client = localhost_client();
+  let err = guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, publish_name)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishedViewLocked, "{:?}", err);
+  let err = guest_client
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishedViewLocked, "{:?}", err);
+  let err = guest_client
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishedViewLocked, "{:?}", err);
+  let err = c
+    .duplicate_published_to_workspace(
+      workspace_id,
+      &PublishedDuplicate {
+        published_view_id: view_id,
+        dest_view_id: workspace_id,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishedViewLocked, "{:?}", err);
+
+  let err = guest_client
+    .unlock_published_collab(&my_namespace, publish_name, "wrong")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidPassword, "{:?}", err);
+
+  let token = guest_client
+    .unlock_published_collab(&my_namespace, publish_name, "secret")
+    .await
+    .unwrap();
+  let metadata = guest_client
+    .get_published_collab_with_access_token::<MyCustomMetadata>(
+      &my_namespace,
+      publish_name,
+      Some(&token.access_token),
+    )
+    .await
+    .unwrap();
+  assert_eq!(metadata.title, "my_title");
+  let blob = guest_client
+    .get_published_collab_blob_with_access_token(
+      &my_namespace,
+      publish_name,
+      Some(&token.access_token),
+    )
+    .await
+    .unwrap();
+  assert_eq!(blob, "yrs_encoded_data".as_bytes());
+
+  // Changing the password invalidates issued tokens, removing it opens the page again
+  c.patch_published_collabs(
+    &workspace_id,
+    &[PatchPublishedCollab {
+      view_id,
+      publish_name: None,
+      comments_enabled: None,
+      duplicate_enabled: None,
+      password: Some("new-secret".to_string()),
+      expires_at: None,
+      clear_expires_at: false,
+    }],
+  )
+  .await
+  .unwrap();
+  let err = guest_client
+    .get_published_collab_with_access_token::<MyCustomMetadata>(
+      &my_namespace,
+      publish_name,
+      Some(&token.access_token),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishedViewLocked, "{:?}", err);
+
+  c.patch_published_collabs(
+    &workspace_id,
+    &[PatchPublishedCollab {
+      view_id,
+      publish_name: None,
+      comments_enabled: None,
+      duplicate_enabled: None,
+      password: Some("".to_string()),
+      expires_at: None,
+      clear_expires_at: false,
+    }],
+  )
+  .await
+  .unwrap();
+  guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, publish_name)
+    .await
+    .unwrap();
+
+  // Expired pages are no longer readable
+  c.patch_published_collabs(
+    &workspace_id,
+    &[PatchPublishedCollab {
+      view_id,
+      publish_name: None,
+      comments_enabled: None,
+      duplicate_enabled: None,
+      password: None,
+      expires_at: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+      clear_expires_at: false,
+    }],
+  )
+  .await
+  .unwrap();
+  let err = guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, publish_name)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishedViewExpired, "{:?}", err);
+  let err = guest_client
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishedViewExpired, "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_published_collab_html() {
+  let client = TestClient::new_user().await;
+  let workspace_id = client.workspace_id().await;
+  let doc_1_view_id: Uuid = "e8c4f99a-50ea-4758-bca0-afa7df5c2434".parse().unwrap();
+  let grid_1_view_id: Uuid = "8e062f61-d7ae-4f4b-869c-f44c43149399".parse().unwrap();
+  client
+    .publish_collabs(
+      &workspace_id,
+      vec![
+        (
+          doc_1_view_id,
+          published_data::DOC_1_META,
+          published_data::DOC_1_DOC_STATE_HEX,
+        ),
+        (
+          grid_1_view_id,
+          published_data::GRID_1_META,
+          published_data::GRID_1_DB_DATA,
+        ),
+      ],
+      true,
+      true,
+    )
+    .await;
+
+  let published_views = client
+    .api_client
+    .list_published_views(&workspace_id)
+    .await
+    .unwrap();
+  let published_info = |view_id: Uuid| {
+    published_views
+      .iter()
+      .find(|published_view| published_view.info.view_id == view_id)
+      .map(|published_view| published_view.info.clone())
+      .unwrap()
+  };
+
+  let guest_client = localhost_client();
+  let doc_info = published_info(doc_1_view_id);
+  let html = guest_client
+    .get_published_collab_html(&doc_info.namespace, &doc_info.publish_name)
+    .await
+    .unwrap();
+  assert!(html.contains("<title>doc1</title>"), "{}", html);
+  assert!(
+    html.contains("<meta property=\"og:title\" content=\"doc1\">"),
+    "{}",
+    html
+  );
+  assert!(
+    html.contains(&format!(
+      "/{}/{}\">",
+      doc_info.namespace, doc_info.publish_name
+    )),
+    "{}",
+    html
+  );
+
+  // The second request is served from the cache
+  let cached_html = guest_client
+    .get_published_collab_html(&doc_info.namespace, &doc_info.publish_name)
+    .await
+    .unwrap();
+  assert_eq!(html, cached_html);
+
+  let grid_info = published_info(grid_1_view_id);
+  let html = guest_client
+    .get_published_collab_html(&grid_info.namespace, &grid_info.publish_name)
+    .await
+    .unwrap();
+  assert!(html.contains("<title>grid1</title>"), "{}", html);
+  assert!(html.contains("<table>"), "{}", html);
+}
+
+#[tokio::test]
+async fn test_published_collab_analytics() {
+  let client = TestClient::new_user().await;
+  let workspace_id = client.workspace_id().await;
+  let doc_1_view_id: Uuid = "1a7c3d2e-5f40-4c39-9f0e-1d8a7b6c5e40".parse().unwrap();
+  client
+    .publish_collabs(
+      &workspace_id,
+      vec![(
+        doc_1_view_id,
+        published_data::DOC_1_META,
+        published_data::DOC_1_DOC_STATE_HEX,
+      )],
+      true,
+      true,
+    )
+    .await;
+  let published_views = client
+    .api_client
+    .list_published_views(&workspace_id)
+    .await
+    .unwrap();
+  let doc_info = published_views
+    .iter()
+    .find(|published_view| published_view.info.view_id == doc_1_view_id)
+    .map(|published_view| published_view.info.clone())
+    .unwrap();
+
+  let guest_client = localhost_client();
+  for _ in 0..2 {
+    guest_client
+      .get_published_collab::<serde_json::Value>(&doc_info.namespace, &doc_info.publish_name)
+      .await
+      .unwrap();
+  }
+  // Reading the blob is part of the same visit and is not counted
+  guest_client
+    .get_published_collab_blob(&doc_info.namespace, &doc_info.publish_name)
+    .await
+    .unwrap();
+
+  // View events are recorded in the background
+  let mut analytics = None;
+  for _ in 0..10 {
+    let report = client
+      .api_client
+      .get_published_analytics(&workspace_id, &PublishedAnalyticsQuery::default())
+      .await
+      .unwrap();
+    if report.daily.iter().map(|day| day.views).sum::<i64>() >= 2 {
+      analytics = Some(report);
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+  }
+  let analytics = analytics.expect("view events were not recorded");
+  assert_eq!(analytics.daily.len(), 1);
+  assert_eq!(analytics.daily[0].views, 2);
+  assert_eq!(analytics.daily[0].unique_visitors, 1);
+  assert_eq!(analytics.pages.len(), 1);
+  assert_eq!(analytics.pages[0].view_id, doc_1_view_id);
+  assert_eq!(analytics.pages[0].views, 2);
+  assert!(analytics.referrers.is_empty());
+
+  // Only the workspace owner can read the analytics
+  let (other_client, _) = generate_unique_registered_user_client().await;
+  let err = other_client
+    .get_published_analytics(&workspace_id, &PublishedAnalyticsQuery::default())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn test_publish_custom_domain() {
+  let client = TestClient::new_user().await;
+  let workspace_id = client.workspace_id().await;
+  let domain = format!("docs-{}.example.com", Uuid::new_v4());
+
+  let err = client
+    .api_client
+    .create_publish_custom_domain(&workspace_id, "not a domain")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let custom_domain = client
+    .api_client
+    .create_publish_custom_domain(&workspace_id, &domain.to_uppercase())
+    .await
+    .unwrap();
+  assert_eq!(custom_domain.domain, domain);
+  assert_eq!(
+    custom_domain.verification_record_name,
+    format!("_appflowy-verification.{}", domain)
+  );
+  assert!(custom_domain.verified_at.is_none());
+
+  // Claiming the domain again keeps the verification token
+  let claimed_again = client
+    .api_client
+    .create_publish_custom_domain(&workspace_id, &domain)
+    .await
+    .unwrap();
+  assert_eq!(
+    claimed_again.verification_record_value,
+    custom_domain.verification_record_value
+  );
+
+  // The TXT record does not exist
+  let err = client
+    .api_client
+    .verify_publish_custom_domain(&workspace_id, &domain)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishDomainNotVerified);
+
+  let (other_client, _) = generate_unique_registered_user_client().await;
+  let err = other_client
+    .list_publish_custom_domains(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  let domains = client
+    .api_client
+    .list_publish_custom_domains(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(domains.len(), 1);
+  assert_eq!(domains[0].domain, domain);
+
+  client
+    .api_client
+    .delete_publish_custom_domain(&workspace_id, &domain)
+    .await
+    .unwrap();
+  let domains = client
+    .api_client
+    .list_publish_custom_domains(&workspace_id)
+    .await
+    .unwrap();
+  assert!(domains.is_empty());
+}
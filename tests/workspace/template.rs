@@ -229,6 +229,8 @@ async fn test_template_crud() {
       data: "yrs_encoded_data_1".as_bytes(),
       comments_enabled: true,
       duplicate_enabled: true,
+      password: None,
+      expires_at: None,
     })
     .collect();
 
@@ -0,0 +1,169 @@
+use app_error::ErrorCode;
+use client_api::entity::comment_dto::{
+  CreateViewCommentParams, CreateViewCommentThreadParams, QueryViewCommentThreadParams,
+  UpdateViewCommentParams,
+};
+use client_api::entity::guest_dto::ShareViewWithGuestRequest;
+use client_api::entity::AFAccessLevel;
+use client_api_test::generate_unique_registered_user_client;
+
+#[tokio::test]
+async fn view_comment_thread_test() {
+  let (owner_client, _) = generate_unique_registered_user_client().await;
+  let workspaces = owner_client.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id;
+  let folder_view = owner_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let view_id = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap()
+    .view_id;
+
+  let (guest_client, guest) = generate_unique_registered_user_client().await;
+  let guest_uuid = guest_client.get_profile().await.unwrap().uuid;
+  owner_client
+    .share_view_with_guest(
+      &workspace_id,
+      &ShareViewWithGuestRequest {
+        view_id,
+        emails: vec![guest.email.clone()],
+        access_level: AFAccessLevel::ReadAndComment,
+        auto_confirm: true,
+      },
+    )
+    .await
+    .unwrap();
+
+  let thread = owner_client
+    .create_view_comment_thread(
+      workspace_id,
+      &view_id,
+      &CreateViewCommentThreadParams {
+        block_id: Some("block_1".to_string()),
+        content: "Can you take a look?".to_string(),
+        mentions: vec![guest_uuid],
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(thread.block_id.as_deref(), Some("block_1"));
+  assert_eq!(thread.comments.len(), 1);
+  assert_eq!(thread.comments[0].mentions, vec![guest_uuid]);
+  let owner_comment_id = thread.comments[0].comment_id;
+
+  // guests with the comment access level can reply, but cannot edit the comments of others
+  let thread = guest_client
+    .reply_to_view_comment_thread(
+      workspace_id,
+      &view_id,
+      &thread.thread_id,
+      &CreateViewCommentParams {
+        content: "Sure".to_string(),
+        mentions: vec![],
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(thread.comments.len(), 2);
+  assert!(!thread.comments[0].can_be_edited);
+  assert!(thread.comments[1].can_be_edited);
+  let guest_comment_id = thread.comments[1].comment_id;
+  let err = guest_client
+    .update_view_comment(
+      workspace_id,
+      &view_id,
+      &owner_comment_id,
+      &UpdateViewCommentParams {
+        content: "Edited by someone else".to_string(),
+        mentions: vec![],
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = guest_client
+    .delete_view_comment(workspace_id, &view_id, &owner_comment_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  owner_client
+    .update_view_comment(
+      workspace_id,
+      &view_id,
+      &owner_comment_id,
+      &UpdateViewCommentParams {
+        content: "Can you take a look, please?".to_string(),
+        mentions: vec![guest_uuid],
+      },
+    )
+    .await
+    .unwrap();
+  let edits = owner_client
+    .list_view_comment_edits(workspace_id, &view_id, &owner_comment_id)
+    .await
+    .unwrap();
+  assert_eq!(edits.len(), 1);
+  assert_eq!(edits[0].content, "Can you take a look?");
+
+  // resolved threads are hidden unless requested
+  let resolved = guest_client
+    .resolve_view_comment_thread(workspace_id, &view_id, &thread.thread_id)
+    .await
+    .unwrap();
+  assert!(resolved.is_resolved);
+  assert_eq!(resolved.resolved_by, Some(guest_uuid));
+  let threads = owner_client
+    .list_view_comment_threads(
+      workspace_id,
+      &view_id,
+      &QueryViewCommentThreadParams::default(),
+    )
+    .await
+    .unwrap();
+  assert!(threads.is_empty());
+  let threads = owner_client
+    .list_view_comment_threads(
+      workspace_id,
+      &view_id,
+      &QueryViewCommentThreadParams {
+        block_id: Some("block_1".to_string()),
+        include_resolved: Some(true),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(threads.len(), 1);
+  assert!(threads[0].comments[0].is_edited);
+
+  let reopened = owner_client
+    .reopen_view_comment_thread(workspace_id, &view_id, &thread.thread_id)
+    .await
+    .unwrap();
+  assert!(!reopened.is_resolved);
+
+  // the owner has full access to the view, and can remove the comments of others
+  owner_client
+    .delete_view_comment(workspace_id, &view_id, &guest_comment_id)
+    .await
+    .unwrap();
+  let threads = guest_client
+    .list_view_comment_threads(
+      workspace_id,
+      &view_id,
+      &QueryViewCommentThreadParams::default(),
+    )
+    .await
+    .unwrap();
+  assert_eq!(threads.len(), 1);
+  assert!(threads[0].comments[1].is_deleted);
+  assert!(threads[0].comments[1].content.is_empty());
+}
@@ -0,0 +1,132 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::AFRole;
+use shared_entity::dto::webhook_dto::{
+  CreateWebhookParams, QueryWebhookDeliveryParams, UpdateWebhookParams, WebhookEvent,
+};
+use shared_entity::dto::workspace_dto::{CreatePageParams, ViewLayout};
+
+#[tokio::test]
+async fn webhook_crud_and_delivery_log_test() {
+  let owner = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+
+  for url in [
+    "ftp://example.com/hook",
+    "http://localhost:8000/hook",
+    "http://169.254.169.254/latest/meta-data",
+    "http://[::1]/hook",
+  ] {
+    let err = owner
+      .api_client
+      .create_webhook(
+        &workspace_id,
+        &CreateWebhookParams {
+          url: url.to_string(),
+          events: vec![],
+        },
+      )
+      .await
+      .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidRequest, "{}", url);
+  }
+
+  let webhook = owner
+    .api_client
+    .create_webhook(
+      &workspace_id,
+      &CreateWebhookParams {
+        url: "https://example.com/hook".to_string(),
+        events: vec![WebhookEvent::PageCreated],
+      },
+    )
+    .await
+    .unwrap();
+  let secret = webhook.secret.clone().unwrap();
+  assert!(secret.starts_with("whsec_"));
+  assert!(webhook.enabled);
+
+  let webhooks = owner.api_client.list_webhooks(&workspace_id).await.unwrap();
+  assert_eq!(webhooks.len(), 1);
+  assert!(webhooks[0].secret.is_none());
+
+  // A member is not allowed to manage the webhooks of the workspace
+  let member = TestClient::new_user().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let err = member
+    .api_client
+    .list_webhooks(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  let folder = owner
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(1), None)
+    .await
+    .unwrap();
+  let general_space = folder
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let page = owner
+    .api_client
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id,
+        layout: ViewLayout::Document,
+        name: Some("Webhook page".to_string()),
+        page_data: None,
+        view_id: None,
+        collab_id: None,
+      },
+    )
+    .await
+    .unwrap();
+
+  // Only the subscribed event is recorded, the member joining above is not
+  let deliveries = owner
+    .api_client
+    .list_webhook_deliveries(
+      &workspace_id,
+      &webhook.webhook_id,
+      &QueryWebhookDeliveryParams::default(),
+    )
+    .await
+    .unwrap();
+  assert_eq!(deliveries.len(), 1);
+  assert_eq!(deliveries[0].event, WebhookEvent::PageCreated.to_string());
+  assert_eq!(
+    deliveries[0].payload["data"]["view_id"],
+    page.view_id.to_string()
+  );
+
+  let updated = owner
+    .api_client
+    .update_webhook(
+      &workspace_id,
+      &webhook.webhook_id,
+      &UpdateWebhookParams {
+        enabled: Some(false),
+        rotate_secret: true,
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert!(!updated.enabled);
+  assert_ne!(updated.secret.unwrap(), secret);
+
+  owner
+    .api_client
+    .delete_webhook(&workspace_id, &webhook.webhook_id)
+    .await
+    .unwrap();
+  let webhooks = owner.api_client.list_webhooks(&workspace_id).await.unwrap();
+  assert!(webhooks.is_empty());
+}